@@ -0,0 +1,174 @@
+//! Benchmarks for loading and parsing search-index.js.
+//!
+//! The checked-in fixture is small, so a larger index is synthesized by repeating
+//! every crate in it under a new name. This approximates a workspace with a few
+//! hundred dependencies without committing a 50 MB fixture.
+//!
+//! Criterion only measures wall time. The main benefit of the mmap + lazy path is
+//! lower peak memory (one crate's owned data at a time instead of all of them),
+//! so compare with a memory profiler as well when changing the loading code.
+
+use criterion::{Criterion, Throughput, criterion_group, criterion_main};
+use rdoc::arena::Index;
+use rdoc::index_file::{IndexFile, IndexJson};
+use rdoc::matcher::{CaseMode, MatcherKind};
+use rdoc::prefilter::NameBuffer;
+use rdoc::search_index::{extract_json_string, parse_search_index, parse_search_index_lazy};
+use rdoc::search_items::decode_crate;
+use std::hint::black_box;
+use std::path::PathBuf;
+
+const FIXTURE: &str = "tests/fixtures/search-index.js";
+
+/// How many copies of each fixture crate go into the large index.
+const REPEAT: usize = 12;
+
+/// ... and into the one extraction is measured on, tens of megabytes on a
+/// single line like the indexes of the largest workspaces.
+const EXTRACT_REPEAT: usize = 64;
+
+/// The contents of a search-index.js with every fixture crate repeated
+/// `repeat` times, escaped as rustdoc escapes it.
+fn large_index(repeat: usize) -> String {
+    let content = std::fs::read_to_string(FIXTURE).expect("Failed to read fixture");
+    let json_string = extract_json_string(&content).unwrap();
+    let entries = parse_search_index_lazy(&json_string).expect("Failed to parse fixture");
+
+    let mut crates = Vec::with_capacity(entries.len() * repeat);
+    for copy in 0..repeat {
+        for entry in &entries {
+            let name = serde_json::to_string(&format!("{}_{}", entry.name, copy)).unwrap();
+            crates.push(format!("[{},{}]", name, entry.raw_data.get()));
+        }
+    }
+
+    let json = format!("[{}]", crates.join(","))
+        .replace('\\', r"\\")
+        .replace('\'', r"\'");
+    format!("var searchIndex = new Map(JSON.parse('{}'));\n", json)
+}
+
+/// Write the large index to a temporary file.
+fn write_large_index() -> PathBuf {
+    let path = std::env::temp_dir().join(format!("rdoc-bench-{}.js", std::process::id()));
+    std::fs::write(&path, large_index(REPEAT)).expect("Failed to write large index");
+    path
+}
+
+/// Extracting the JSON from a very large index already in memory, which
+/// should take one copy of it and no more.
+fn bench_extract(c: &mut Criterion) {
+    let content = large_index(EXTRACT_REPEAT);
+
+    let mut group = c.benchmark_group("extract");
+    group.sample_size(10);
+    group.throughput(Throughput::Bytes(content.len() as u64));
+    group.bench_function("extract_json_string", |b| {
+        b.iter(|| black_box(extract_json_string(&content).unwrap()))
+    });
+    group.finish();
+}
+
+fn bench_read(c: &mut Criterion, path: &PathBuf) {
+    let mut group = c.benchmark_group("read_and_extract");
+    group.sample_size(20);
+
+    group.bench_function("read_to_string", |b| {
+        b.iter(|| {
+            let content = std::fs::read_to_string(path).unwrap();
+            black_box(extract_json_string(&content).unwrap().len())
+        })
+    });
+
+    group.bench_function("mmap", |b| {
+        b.iter(|| black_box(IndexJson::extract(IndexFile::open(path).unwrap()).unwrap()))
+    });
+
+    group.finish();
+}
+
+fn bench_parse(c: &mut Criterion, path: &PathBuf) {
+    let content = std::fs::read_to_string(path).unwrap();
+    let json_string = extract_json_string(&content).unwrap();
+
+    let mut group = c.benchmark_group("parse_and_decode");
+    group.sample_size(10);
+
+    group.bench_function("eager", |b| {
+        b.iter(|| {
+            let entries = parse_search_index(&json_string).unwrap();
+            let mut total = 0;
+            for entry in &entries {
+                total += decode_crate(&entry.name, &entry.data).len();
+            }
+            black_box(total)
+        })
+    });
+
+    group.bench_function("lazy", |b| {
+        b.iter(|| {
+            let entries = parse_search_index_lazy(&json_string).unwrap();
+            let mut total = 0;
+            for entry in &entries {
+                let data = entry.data().unwrap();
+                total += decode_crate(&entry.name, &data).len();
+            }
+            black_box(total)
+        })
+    });
+
+    group.finish();
+}
+
+/// Matching one term against every item, as an interactive lookup does, with
+/// and without narrowing it down through a [`NameBuffer`] first, and through
+/// an [`Index`] as `serve` and `repl` do.
+fn bench_match(c: &mut Criterion, path: &PathBuf) {
+    let content = std::fs::read_to_string(path).unwrap();
+    let entries = parse_search_index(&extract_json_string(&content).unwrap()).unwrap();
+    let items: Vec<_> = entries
+        .iter()
+        .flat_map(|entry| decode_crate(&entry.name, &entry.data))
+        .collect();
+    let names = NameBuffer::new(&items);
+    let index: Index = items.iter().cloned().collect();
+
+    let mut group = c.benchmark_group("match");
+    for (kind, term) in [
+        (MatcherKind::Rustdoc, "tostring"),
+        (MatcherKind::Nucleo, "hsmp"),
+    ] {
+        let matcher = kind.matcher(term, CaseMode::Smart).unwrap();
+        group.bench_function(format!("{}_scan", kind), |b| {
+            b.iter(|| {
+                let found = items.iter().filter(|item| matcher.matches_item(item));
+                black_box(found.count())
+            })
+        });
+        group.bench_function(format!("{}_prefiltered", kind), |b| {
+            b.iter(|| {
+                let candidates = matcher.candidates(&names).unwrap();
+                let found = items.iter().enumerate().filter(|&(index, item)| {
+                    candidates.contains(index) && matcher.matches_item(item)
+                });
+                black_box(found.count())
+            })
+        });
+        group.bench_function(format!("{}_arena", kind), |b| {
+            b.iter(|| black_box(index.matching(matcher.as_ref()).len()))
+        });
+    }
+    group.finish();
+}
+
+fn benches(c: &mut Criterion) {
+    let path = write_large_index();
+    bench_extract(c);
+    bench_read(c, &path);
+    bench_parse(c, &path);
+    bench_match(c, &path);
+    let _ = std::fs::remove_file(&path);
+}
+
+criterion_group!(parse, benches);
+criterion_main!(parse);
@@ -0,0 +1,2 @@
+var searchIndex = new Map(JSON.parse('[["cfg_if",{"t":"Q","n":["cfg_if"],"q":[[0,"cfg_if"]],"i":"`","f":"`","D":"d","p":[],"r":[],"b":[],"c":"OjAAAAAAAAA=","e":"OjAAAAAAAAA=","P":[]}],["debug_unreachable",{"t":"Q","n":["debug_unreachable"],"q":[[0,"debug_unreachable"]],"i":"`","f":"`","D":"d","p":[],"r":[],"b":[],"c":"OjAAAAAAAAA=","e":"OjAAAAAAAAA=","P":[]}],["serde_derive",{"t":"YY","n":["Deserialize","Serialize"],"q":[[0,"serde_derive"]],"i":"``","f":"``","D":"b","p":[],"r":[],"b":[],"c":"OjAAAAAAAAA=","e":"OjAAAAEAAAAAAAEAEAAAAAEAAgA=","P":[]}],["precomputed_hash",{"t":"KM","n":["PrecomputedHash","precomputed_hash"],"q":[[0,"precomputed_hash"]],"i":"`b","f":"`{{{d{b}}}f}","D":"f","p":[[10,"PrecomputedHash",0],[1,"reference",null,null,1],[1,"u32"]],"r":[],"b":[],"c":"OjAAAAAAAAA=","e":"OjAAAAAAAAA=","P":[]}],["unicode_ident",{"t":"SHH","n":["UNICODE_VERSION","is_xid_continue","is_xid_start"],"q":[[0,"unicode_ident"],[3,"unicode_ident::tables"]],"i":"```","f":"{{}b}{df}0","D":"f","p":[[1,"tuple",null,null,1],[1,"char"],[1,"bool"]],"r":[[0,3]],"b":[],"c":"OjAAAAAAAAA=","e":"OjAAAAEAAAAAAAAAEAAAAAEA","P":[]}],["itoa",{"t":"FKTNNNNNNNNNNNN","n":["Buffer","Integer","MAX_STR_LEN","borrow","borrow_mut","clone","clone_to_uninit","default","format","from","into","new","try_from","try_into","type_id"],"q":[[0,"itoa"],[15,"core::result"],[16,"core::any"]],"i":"``nf00000000000","f":"```{b{{b{c}}}{}}{{{b{d}}}{{b{dc}}}{}}{{{b{f}}}f}{{bh}j}{{}f}{{{b{df}}c}{{b{l}}}n}{cc{}}{{}c{}}3{c{{A`{e}}}{}{}}{{}{{A`{c}}}{}}{bAb}","D":"A`","p":[[1,"reference",null,null,1],[0,"mut"],[5,"Buffer",0],[1,"u8"],[1,"unit"],[1,"str"],[10,"Integer",0],[6,"Result",15,null,1],[5,"TypeId",16]],"r":[],"b":[],"c":"OjAAAAAAAAA=","e":"OzAAAAEAAAcAAgAEAAQADQACAA==","P":[[3,"T"],[5,""],[8,"I"],[9,"T"],[10,"U"],[11,""],[12,"U,T"],[13,"U"],[14,""]]}]]'));
+if (typeof exports !== 'undefined') exports.searchIndex = searchIndex;
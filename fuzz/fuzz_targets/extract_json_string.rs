@@ -0,0 +1,8 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use rdoc::search_index::extract_json_string;
+
+fuzz_target!(|content: &str| {
+    let _ = extract_json_string(content);
+});
@@ -0,0 +1,17 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use rdoc::search_index::parse_search_index;
+use rdoc::search_items::decode_crate;
+
+fuzz_target!(|json_string: &str| {
+    let Ok(entries) = parse_search_index(json_string) else {
+        return;
+    };
+    // Decode what parsed, skipping crates the CLI would skip too
+    for entry in entries {
+        if entry.data.types.len() == entry.data.names.len() {
+            decode_crate(&entry.name, &entry.data);
+        }
+    }
+});
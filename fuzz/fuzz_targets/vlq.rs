@@ -0,0 +1,8 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use rdoc::vlq::VlqHexDecoder;
+
+fuzz_target!(|encoded: &str| {
+    let _ = VlqHexDecoder::new(encoded).count();
+});
@@ -0,0 +1,197 @@
+// Alternative names items can be searched for by
+
+use crate::item_id::ItemId;
+use crate::normalize::fold_case;
+use crate::resolve::items_at;
+use crate::search_index::CrateData;
+use crate::search_items::SearchItem;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::sync::Arc;
+
+/// Aliases from the search index's `a` field (rustdoc's `#[doc(alias)]`),
+/// merged with the user's own.
+///
+/// Like rustdoc's search, aliases are matched whole and ignoring case.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Aliases {
+    /// Case-folded alias to the items the index gives it to
+    index: HashMap<String, Vec<ItemId>>,
+    /// Case-folded alias to the item path the user gave it to
+    user: HashMap<String, String>,
+}
+
+impl Aliases {
+    /// Start with the user's aliases, each mapping an alias to an item path
+    /// such as `std::collections::HashMap`.
+    pub fn new(user: &BTreeMap<String, String>) -> Self {
+        Self {
+            index: HashMap::new(),
+            user: user
+                .iter()
+                .map(|(alias, path)| (fold_case(alias), path.clone()))
+                .collect(),
+        }
+    }
+
+    /// Add one crate's aliases from its compact data, for items from `source`.
+    pub fn add_crate(&mut self, source: &Arc<str>, crate_name: &str, data: &CrateData) {
+        let crate_id: Arc<str> = Arc::from(crate_name);
+        for (alias, indices) in data.aliases.iter().flatten() {
+            self.index
+                .entry(fold_case(alias))
+                .or_default()
+                .extend(indices.iter().map(|&index| ItemId {
+                    source: source.clone(),
+                    crate_name: crate_id.clone(),
+                    index,
+                }));
+        }
+    }
+
+    /// Add the index aliases of `other`, e.g. a crate's aliases kept from an
+    /// earlier load. Its user aliases are left out.
+    pub fn extend(&mut self, other: &Aliases) {
+        for (alias, ids) in &other.index {
+            self.index
+                .entry(alias.clone())
+                .or_default()
+                .extend(ids.iter().cloned());
+        }
+    }
+
+    /// The items in `items` that `alias` names, through either kind of alias.
+    ///
+    /// A user alias for a path that doesn't resolve names nothing.
+    pub fn targets<'a>(
+        &self,
+        items: impl IntoIterator<Item = &'a SearchItem> + Clone,
+        alias: &str,
+    ) -> Vec<&'a SearchItem> {
+        let alias = fold_case(alias);
+        let ids: HashSet<&ItemId> = self.index.get(&alias).into_iter().flatten().collect();
+        let mut targets: Vec<&SearchItem> = if ids.is_empty() {
+            Vec::new()
+        } else {
+            items
+                .clone()
+                .into_iter()
+                .filter(|item| ids.contains(&item.id))
+                .collect()
+        };
+        if let Some(path) = self.user.get(&alias) {
+            for item in items_at(items, path).unwrap_or_default() {
+                if !targets.iter().any(|target| target.id == item.id) {
+                    targets.push(item);
+                }
+            }
+        }
+        targets
+    }
+
+    /// Whether `alias` names `item`, as [`Aliases::targets`] would find it,
+    /// for items decoded one at a time.
+    pub fn names(&self, item: &SearchItem, alias: &str) -> bool {
+        let alias = fold_case(alias);
+        self.index
+            .get(&alias)
+            .is_some_and(|ids| ids.contains(&item.id))
+            || self.user.get(&alias).is_some_and(|path| {
+                items_at(std::slice::from_ref(item), path).is_ok_and(|items| !items.is_empty())
+            })
+    }
+
+    /// The items in `items` the index gives aliases to (their
+    /// `#[doc(alias)]`es), each with its aliases in order, by crate and path.
+    ///
+    /// Aliases come case-folded, as rustdoc writes them to the index.
+    pub fn declared<'a>(&'a self, items: &'a [SearchItem]) -> Vec<(&'a SearchItem, Vec<&'a str>)> {
+        let by_id: HashMap<&ItemId, &SearchItem> =
+            items.iter().map(|item| (&item.id, item)).collect();
+        let mut declared: HashMap<&ItemId, (&SearchItem, Vec<&str>)> = HashMap::new();
+        for (alias, ids) in &self.index {
+            for id in ids {
+                if let Some(item) = by_id.get(id) {
+                    declared
+                        .entry(id)
+                        .or_insert((item, Vec::new()))
+                        .1
+                        .push(alias);
+                }
+            }
+        }
+        let mut declared: Vec<(&SearchItem, Vec<&str>)> = declared.into_values().collect();
+        for (_, aliases) in &mut declared {
+            aliases.sort_unstable();
+            aliases.dedup();
+        }
+        declared.sort_by(|(a, _), (b, _)| {
+            (&a.crate_name, &a.path, a.parent_name(), &a.name).cmp(&(
+                &b.crate_name,
+                &b.path,
+                b.parent_name(),
+                &b.name,
+            ))
+        });
+        declared
+    }
+
+    /// Whether there are no aliases of either kind.
+    pub fn is_empty(&self) -> bool {
+        self.index.is_empty() && self.user.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::search_index::{extract_json_string, parse_search_index};
+    use crate::search_items::decode_crate;
+
+    #[test]
+    fn test_targets() {
+        let content = std::fs::read_to_string("tests/fixtures/search-index.js").unwrap();
+        let entries = parse_search_index(&extract_json_string(&content).unwrap()).unwrap();
+        // libc's `__error` has `#[doc(alias = "errno", alias = "__errno_location")]`
+        let libc = entries.iter().find(|entry| entry.name == "libc").unwrap();
+        let items = decode_crate(&libc.name, &libc.data);
+
+        let user = BTreeMap::from([
+            ("Err".to_string(), "libc::__error".to_string()),
+            ("nowhere".to_string(), "no::such::item".to_string()),
+            ("🦀".to_string(), "libc::__error".to_string()),
+        ]);
+        let mut aliases = Aliases::new(&user);
+        aliases.add_crate(&Arc::from(ItemId::LOCAL_SOURCE), &libc.name, &libc.data);
+
+        // An index alias, matched ignoring case
+        let errno = aliases.targets(&items, "ERRNO");
+        assert!(!errno.is_empty());
+        assert!(errno.iter().all(|item| item.name == "__error"));
+        // A user alias for the same items adds nothing new
+        let user_targets = aliases.targets(&items, "err");
+        assert_eq!(user_targets, errno);
+        assert_eq!(aliases.targets(&items, "🦀"), errno);
+
+        assert!(aliases.targets(&items, "nowhere").is_empty());
+        assert!(aliases.targets(&items, "errn").is_empty());
+
+        // Item by item, the same items are named
+        for alias in ["ERRNO", "err", "🦀", "nowhere", "errn"] {
+            let named: Vec<&SearchItem> = items
+                .iter()
+                .filter(|item| aliases.names(item, alias))
+                .collect();
+            assert_eq!(named, aliases.targets(&items, alias), "{}", alias);
+        }
+
+        // Only the index's aliases are declared
+        let declared = aliases.declared(&items);
+        let (item, names) = declared
+            .iter()
+            .find(|(item, _)| item.name == "__error")
+            .unwrap();
+        assert_eq!(item.crate_name, "libc");
+        assert_eq!(names, &["__errno_location", "errno"]);
+        assert!(!declared.iter().any(|(_, names)| names.contains(&"err")));
+    }
+}
@@ -0,0 +1,175 @@
+// Decoded items held crate by crate, with the columns matching reads kept apart
+
+use std::iter::FlatMap;
+use std::slice;
+use std::sync::Arc;
+
+use crate::matcher::Matcher;
+use crate::prefilter::NameBuffer;
+use crate::search_items::SearchItem;
+
+/// A handle to an item in an [`Index`]: its position, valid only for the
+/// index it came from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct ItemRef(u32);
+
+impl ItemRef {
+    /// The item's position in its index.
+    pub fn position(self) -> usize {
+        self.0 as usize
+    }
+}
+
+/// Decoded items in shared chunks (one per crate when `serve` reuses the
+/// crates it decoded before), with the column matching reads kept apart
+/// from them.
+///
+/// Matching scans the items' normalized names in one contiguous
+/// [`NameBuffer`] and only looks at the candidates [`Matcher::candidates`]
+/// leaves as whole items. The chunks are shared, not copied, so an index
+/// rebuilt after a reload keeps the crates that didn't change.
+#[derive(Debug, Default)]
+pub struct Index {
+    chunks: Vec<Arc<[SearchItem]>>,
+    /// Where each chunk starts, by item position
+    starts: Vec<usize>,
+    /// Each item's normalized name, in order
+    names: NameBuffer,
+}
+
+impl Index {
+    /// An index over `chunks`' items, in order.
+    pub fn new(chunks: impl IntoIterator<Item = Arc<[SearchItem]>>) -> Self {
+        let mut index = Self::default();
+        for chunk in chunks {
+            index.starts.push(index.len());
+            for item in chunk.iter() {
+                index.names.push(&item.normalized_name);
+            }
+            index.chunks.push(chunk);
+        }
+        index
+    }
+
+    /// How many items the index holds.
+    pub fn len(&self) -> usize {
+        self.names.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.names.is_empty()
+    }
+
+    /// Every item, in order.
+    pub fn iter(&self) -> Items<'_> {
+        self.chunks.iter().flat_map(|chunk| &chunk[..])
+    }
+
+    /// Every item's handle, in order.
+    pub fn refs(&self) -> impl ExactSizeIterator<Item = ItemRef> + use<> {
+        (0..self.len() as u32).map(ItemRef)
+    }
+
+    /// The chunks the items are held in, to share with another index.
+    pub fn chunks(&self) -> &[Arc<[SearchItem]>] {
+        &self.chunks
+    }
+
+    /// The normalized names, for [`Matcher::candidates`]; an item's position
+    /// in it is its [`ItemRef::position`].
+    pub fn names(&self) -> &NameBuffer {
+        &self.names
+    }
+
+    pub fn get(&self, item: ItemRef) -> &SearchItem {
+        let position = item.position();
+        let chunk = self.starts.partition_point(|&start| start <= position) - 1;
+        &self.chunks[chunk][position - self.starts[chunk]]
+    }
+
+    /// The items `matcher` matches, in order. Only those whose names it
+    /// could match are checked whole.
+    pub fn matching(&self, matcher: &dyn Matcher) -> Vec<ItemRef> {
+        let candidates = matcher.candidates(&self.names);
+        self.refs()
+            .zip(self.iter())
+            .filter(|(item_ref, item)| {
+                candidates
+                    .as_ref()
+                    .is_none_or(|candidates| candidates.contains(item_ref.position()))
+                    && matcher.matches_item(item)
+            })
+            .map(|(item_ref, _)| item_ref)
+            .collect()
+    }
+}
+
+/// An [`Index`]'s items, in order.
+pub type Items<'a> = FlatMap<
+    slice::Iter<'a, Arc<[SearchItem]>>,
+    &'a [SearchItem],
+    fn(&'a Arc<[SearchItem]>) -> &'a [SearchItem],
+>;
+
+impl<'a> IntoIterator for &'a Index {
+    type Item = &'a SearchItem;
+    type IntoIter = Items<'a>;
+
+    fn into_iter(self) -> Items<'a> {
+        self.iter()
+    }
+}
+
+impl From<Vec<SearchItem>> for Index {
+    /// An index holding `items` in one chunk.
+    fn from(items: Vec<SearchItem>) -> Self {
+        Self::new([Arc::from(items)])
+    }
+}
+
+impl FromIterator<SearchItem> for Index {
+    fn from_iter<I: IntoIterator<Item = SearchItem>>(items: I) -> Self {
+        Self::from(items.into_iter().collect::<Vec<_>>())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::matcher::{CaseMode, TermMatcher};
+    use crate::search_index::{extract_json_string, parse_search_index};
+    use crate::search_items::decode_crate;
+
+    #[test]
+    fn test_index() {
+        let content = std::fs::read_to_string("tests/fixtures/search-index.js").unwrap();
+        let entries = parse_search_index(&extract_json_string(&content).unwrap()).unwrap();
+        let crates: Vec<Arc<[SearchItem]>> = entries
+            .iter()
+            .map(|entry| decode_crate(&entry.name, &entry.data).into())
+            .collect();
+        let items: Vec<&SearchItem> = crates.iter().flat_map(|chunk| chunk.iter()).collect();
+        let index = Index::new(crates.iter().cloned());
+
+        // Every item is found by its handle, and the chunks are shared
+        assert_eq!(index.len(), items.len());
+        for (item_ref, item) in index.refs().zip(&items) {
+            assert_eq!(index.get(item_ref), *item);
+        }
+        assert!(Arc::ptr_eq(&index.chunks()[0], &crates[0]));
+
+        let matcher = TermMatcher::new("deserializer", CaseMode::Insensitive).unwrap();
+        let matching: Vec<&SearchItem> = index
+            .matching(&matcher)
+            .into_iter()
+            .map(|item| index.get(item))
+            .collect();
+        let expected: Vec<&SearchItem> = items
+            .iter()
+            .copied()
+            .filter(|item| matcher.matches_item(item))
+            .collect();
+        assert!(!expected.is_empty());
+        assert_eq!(matching, expected);
+    }
+}
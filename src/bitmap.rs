@@ -0,0 +1,264 @@
+// Roaring bitmap decoder for the `c` (deprecated) and `e` (empty description) fields
+
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+use std::fmt;
+
+/// Serialization cookie marking a bitmap that contains run containers.
+const SERIAL_COOKIE: u8 = 59;
+
+/// Containers with at least this many values are stored as a full bitset.
+const ARRAY_CONTAINER_MAX: usize = 4096;
+
+/// Size in bytes of a bitset container (65536 bits).
+const BITSET_CONTAINER_BYTES: usize = 8192;
+
+/// Error returned when a bitmap string can't be decoded.
+#[derive(Debug)]
+pub enum BitmapError {
+    /// The string is not valid base64
+    Base64(base64::DecodeError),
+    /// The decoded bytes end before the bitmap does
+    Truncated,
+}
+
+impl fmt::Display for BitmapError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BitmapError::Base64(err) => write!(f, "invalid base64 in bitmap: {}", err),
+            BitmapError::Truncated => write!(f, "bitmap data is truncated"),
+        }
+    }
+}
+
+impl std::error::Error for BitmapError {}
+
+/// A single container holding the low 16 bits of values sharing one key.
+#[derive(Debug, Clone, PartialEq)]
+enum Container {
+    /// Inclusive ranges of consecutive values: `(start, length - 1)`
+    Run(Vec<(u16, u16)>),
+    /// One bit per possible value
+    Bits(Vec<u8>),
+    /// Sorted list of values
+    Array(Vec<u16>),
+}
+
+impl Container {
+    fn contains(&self, value: u16) -> bool {
+        match self {
+            Container::Run(runs) => {
+                let position = runs.partition_point(|&(start, _)| start <= value);
+                position > 0 && {
+                    let (start, len_minus_one) = runs[position - 1];
+                    value as u32 <= start as u32 + len_minus_one as u32
+                }
+            }
+            Container::Bits(bits) => bits[value as usize >> 3] & (1 << (value & 7)) != 0,
+            Container::Array(values) => values.binary_search(&value).is_ok(),
+        }
+    }
+}
+
+/// A decoded roaring bitmap, as used by rustdoc to store sets of bit indices.
+///
+/// See FIELD_DECODING.md for the serialized layout.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct RoaringBitmap {
+    /// Upper 16 bits of the values stored in the matching container
+    keys: Vec<u16>,
+    containers: Vec<Container>,
+}
+
+/// Cursor over the decoded bytes that reports truncation instead of panicking.
+struct Reader<'a> {
+    bytes: &'a [u8],
+    offset: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, len: usize) -> Result<&'a [u8], BitmapError> {
+        let end = self.offset.checked_add(len).ok_or(BitmapError::Truncated)?;
+        let slice = self
+            .bytes
+            .get(self.offset..end)
+            .ok_or(BitmapError::Truncated)?;
+        self.offset = end;
+        Ok(slice)
+    }
+
+    fn remaining(&self) -> usize {
+        self.bytes.len().saturating_sub(self.offset)
+    }
+
+    fn u16(&mut self) -> Result<u16, BitmapError> {
+        let bytes = self.take(2)?;
+        Ok(u16::from_le_bytes([bytes[0], bytes[1]]))
+    }
+
+    fn u32(&mut self) -> Result<u32, BitmapError> {
+        let bytes = self.take(4)?;
+        Ok(u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+    }
+}
+
+impl RoaringBitmap {
+    /// Decode a base64-encoded bitmap string from the search index.
+    ///
+    /// An empty string decodes to an empty bitmap.
+    pub fn from_base64(encoded: &str) -> Result<Self, BitmapError> {
+        if encoded.is_empty() {
+            return Ok(Self::default());
+        }
+
+        let bytes = STANDARD.decode(encoded).map_err(BitmapError::Base64)?;
+        Self::from_bytes(&bytes)
+    }
+
+    fn from_bytes(bytes: &[u8]) -> Result<Self, BitmapError> {
+        let mut reader = Reader { bytes, offset: 0 };
+        let has_runs = reader.take(1)?[0] == SERIAL_COOKIE;
+
+        // Header: container count, plus a bitset of which containers are runs
+        let (size, is_run) = if has_runs {
+            reader.take(1)?;
+            let size = reader.u16()? as usize + 1;
+            (size, reader.take(size.div_ceil(8))?)
+        } else {
+            reader.take(3)?;
+            (reader.u32()? as usize, &[][..])
+        };
+
+        // Each container has a 4-byte header entry, so a count that couldn't fit
+        // in the rest of the input is corrupt; don't allocate for it
+        if size > reader.remaining() / 4 {
+            return Err(BitmapError::Truncated);
+        }
+
+        let mut keys = Vec::with_capacity(size);
+        let mut cardinalities = Vec::with_capacity(size);
+        for _ in 0..size {
+            keys.push(reader.u16()?);
+            cardinalities.push(reader.u16()? as usize + 1);
+        }
+
+        // Container offsets are redundant with the sizes we compute, so skip them
+        if !has_runs || size >= 4 {
+            reader.take(size * 4)?;
+        }
+
+        let mut containers = Vec::with_capacity(size);
+        for (j, &cardinality) in cardinalities.iter().enumerate() {
+            let container = if is_run.get(j >> 3).is_some_and(|b| b & (1 << (j & 7)) != 0) {
+                let run_count = reader.u16()? as usize;
+                let runs = (0..run_count)
+                    .map(|_| Ok((reader.u16()?, reader.u16()?)))
+                    .collect::<Result<_, BitmapError>>()?;
+                Container::Run(runs)
+            } else if cardinality >= ARRAY_CONTAINER_MAX {
+                Container::Bits(reader.take(BITSET_CONTAINER_BYTES)?.to_vec())
+            } else {
+                let values = (0..cardinality)
+                    .map(|_| reader.u16())
+                    .collect::<Result<_, BitmapError>>()?;
+                Container::Array(values)
+            };
+            containers.push(container);
+        }
+
+        Ok(Self { keys, containers })
+    }
+
+    /// Returns true if `value` is in the bitmap.
+    pub fn contains(&self, value: u32) -> bool {
+        let key = (value >> 16) as u16;
+        match self.keys.binary_search(&key) {
+            Ok(position) => self.containers[position].contains(value as u16),
+            Err(_) => false,
+        }
+    }
+
+    /// Returns true if the bitmap contains no values.
+    pub fn is_empty(&self) -> bool {
+        self.keys.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_empty_bitmap() {
+        // The most common value in real indexes: no containers at all
+        let bitmap = RoaringBitmap::from_base64("OjAAAAAAAAA=").unwrap();
+        assert!(bitmap.is_empty());
+        assert!(!bitmap.contains(0));
+        assert!(!bitmap.contains(1));
+
+        assert!(RoaringBitmap::from_base64("").unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_run_container() {
+        // serde's deprecated bitmap: runs 134..=136 (Error::cause) and
+        // 145..=147 (Error::description)
+        let bitmap = RoaringBitmap::from_base64("OzAAAAEAAAUAAgCGAAIAkQACAA==").unwrap();
+        for value in [134, 135, 136, 145, 146, 147] {
+            assert!(bitmap.contains(value), "{} should be set", value);
+        }
+        for value in [0, 133, 137, 144, 148, 65_536 + 134] {
+            assert!(!bitmap.contains(value), "{} should not be set", value);
+        }
+    }
+
+    #[test]
+    fn test_array_container() {
+        // itertools' deprecated bitmap: a single array container
+        let bitmap =
+            RoaringBitmap::from_base64("OjAAAAEAAAAAAAUAEAAAAIoAjAD+ABABaAFqAQ==").unwrap();
+        for value in [138, 140, 254, 272, 360, 362] {
+            assert!(bitmap.contains(value), "{} should be set", value);
+        }
+        for value in [0, 139, 255, 273] {
+            assert!(!bitmap.contains(value), "{} should not be set", value);
+        }
+    }
+
+    #[test]
+    fn test_malformed_bitmaps_error() {
+        assert!(matches!(
+            RoaringBitmap::from_base64("not base64!"),
+            Err(BitmapError::Base64(_))
+        ));
+        // Header claims one container but the data stops there
+        assert!(matches!(
+            RoaringBitmap::from_base64("OjAAAAEAAAA="),
+            Err(BitmapError::Truncated)
+        ));
+        // Found by the parse_search_index fuzz target: a huge container count
+        // used to be allocated for before the data ran out
+        assert!(matches!(
+            RoaringBitmap::from_base64("OjAAAaAAAlll"),
+            Err(BitmapError::Truncated)
+        ));
+    }
+
+    #[test]
+    fn test_decodes_every_fixture_bitmap() {
+        use crate::search_index::{extract_json_string, parse_search_index};
+
+        let content = std::fs::read_to_string("tests/fixtures/search-index.js")
+            .expect("Failed to read fixture");
+        for entry in parse_search_index(&extract_json_string(&content).unwrap()).unwrap() {
+            for (field, encoded) in [("c", &entry.data.c), ("e", &entry.data.e)] {
+                assert!(
+                    RoaringBitmap::from_base64(encoded).is_ok(),
+                    "{} bitmap of {} should decode",
+                    field,
+                    entry.name
+                );
+            }
+        }
+    }
+}
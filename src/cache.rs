@@ -0,0 +1,203 @@
+// Location of rdoc's on-disk cache
+
+use color_eyre::{
+    Result,
+    eyre::{Context, bail, eyre},
+};
+use std::path::{Path, PathBuf};
+use tracing::{info, warn};
+
+use crate::output;
+
+/// Environment variable that overrides the cache directory.
+pub const CACHE_DIR_ENV_VAR: &str = "RDOC_CACHE_DIR";
+
+/// Version of the cache's layout, recorded in [`FORMAT_FILE`]. Whenever cached
+/// files move or change shape, bump it and add a step to [`MIGRATIONS`], so
+/// existing caches are upgraded instead of thrown away.
+pub const CACHE_FORMAT: u32 = 1;
+
+/// File in the cache directory recording its format
+const FORMAT_FILE: &str = "format";
+
+/// A step taking the cache from format `from` to `from + 1`.
+struct Migration {
+    from: u32,
+    description: &'static str,
+    run: fn(&Path) -> Result<()>,
+}
+
+/// The steps upgrading older caches, in order.
+const MIGRATIONS: &[Migration] = &[Migration {
+    from: 0,
+    // Caches from before the format was recorded are laid out as format 1
+    description: "record the cache format",
+    run: |_| Ok(()),
+}];
+
+/// What [`upgrade`] did to a cache.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Upgrade {
+    /// The format the cache was in
+    pub from: u32,
+    /// Each migration run, in order
+    pub steps: Vec<&'static str>,
+}
+
+/// Root directory for everything rdoc caches.
+///
+/// Defaults to the platform cache directory (e.g. `~/.cache/rdoc` on Linux).
+pub fn cache_dir() -> Result<PathBuf> {
+    if let Some(dir) = std::env::var_os(CACHE_DIR_ENV_VAR) {
+        return Ok(PathBuf::from(dir));
+    }
+
+    dirs::cache_dir()
+        .map(|dir| dir.join("rdoc"))
+        .ok_or_else(|| {
+            eyre!(
+                "Could not determine a cache directory; set {}",
+                CACHE_DIR_ENV_VAR
+            )
+        })
+}
+
+/// The format of the cache in `dir`, or `None` if there's no cache there.
+/// Caches from before the format was recorded are format 0.
+pub fn cache_format(dir: &Path) -> Result<Option<u32>> {
+    if !dir.is_dir() {
+        return Ok(None);
+    }
+    let path = dir.join(FORMAT_FILE);
+    if !path.exists() {
+        return Ok(Some(0));
+    }
+    let content = std::fs::read_to_string(&path)
+        .wrap_err_with(|| format!("Failed to read {}", path.display()))?;
+    let format = content
+        .trim()
+        .parse()
+        .wrap_err_with(|| format!("Invalid {}", path.display()))?;
+    Ok(Some(format))
+}
+
+/// Bring the cache in `dir` up to [`CACHE_FORMAT`], running each migration
+/// from its format on, or `None` if there's no cache to upgrade.
+///
+/// The format is recorded after every step, so an interrupted upgrade picks
+/// up where it stopped. Caches a newer rdoc wrote are left alone, as an error.
+pub fn upgrade(dir: &Path) -> Result<Option<Upgrade>> {
+    let Some(from) = cache_format(dir)? else {
+        return Ok(None);
+    };
+    if from > CACHE_FORMAT {
+        bail!(
+            "The cache in {} has format {}, from a newer rdoc (this one reads format {})",
+            dir.display(),
+            from,
+            CACHE_FORMAT
+        );
+    }
+    let mut steps = Vec::new();
+    for migration in MIGRATIONS.iter().filter(|migration| migration.from >= from) {
+        (migration.run)(dir).wrap_err_with(|| format!("Failed to {}", migration.description))?;
+        output::write_atomic(&dir.join(FORMAT_FILE), (migration.from + 1).to_string())?;
+        steps.push(migration.description);
+    }
+    Ok(Some(Upgrade { from, steps }))
+}
+
+/// Upgrade the cache before it's used, if an older rdoc wrote it. Problems
+/// are logged rather than returned: commands work without the cache.
+pub fn upgrade_before_use() {
+    match cache_dir().and_then(|dir| upgrade(&dir)) {
+        Ok(Some(upgrade)) if !upgrade.steps.is_empty() => info!(
+            "upgraded the cache from format {} to {}",
+            upgrade.from, CACHE_FORMAT
+        ),
+        Ok(_) => {}
+        Err(err) => warn!("{:#}", err),
+    }
+}
+
+/// Directory holding the cached copy of a remote documentation site.
+pub fn remote_docs_dir(base_url: &str) -> Result<PathBuf> {
+    Ok(cache_dir()?.join("remote").join(cache_key(base_url)))
+}
+
+/// Where the name index built from the search index at `search_index` is kept.
+pub fn name_index_path(search_index: &Path) -> Result<PathBuf> {
+    let key = index_key(search_index);
+    Ok(cache_dir()?.join("names").join(format!("{}.fst", key)))
+}
+
+/// Where a running `rdoc serve` for the search index at `search_index`
+/// records its address.
+pub fn daemon_path(search_index: &Path) -> Result<PathBuf> {
+    let key = index_key(search_index);
+    Ok(cache_dir()?.join("daemons").join(format!("{}.json", key)))
+}
+
+/// A file name for what's kept about the search index at `search_index`,
+/// the same whichever relative path it's reached by.
+fn index_key(search_index: &Path) -> String {
+    let search_index = search_index
+        .canonicalize()
+        .unwrap_or_else(|_| search_index.to_path_buf());
+    cache_key(&search_index.to_string_lossy())
+}
+
+/// Turn a URL into a file-system safe directory name.
+///
+/// `https://docs.example.com/myapi/` becomes `docs.example.com_myapi`.
+fn cache_key(base_url: &str) -> String {
+    let without_scheme = base_url
+        .split_once("://")
+        .map_or(base_url, |(_, rest)| rest);
+
+    without_scheme
+        .trim_end_matches('/')
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || matches!(c, '.' | '-') {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cache_key() {
+        assert_eq!(
+            cache_key("https://docs.example.com/myapi/"),
+            "docs.example.com_myapi"
+        );
+        assert_eq!(cache_key("http://localhost:8080/doc"), "localhost_8080_doc");
+    }
+
+    #[test]
+    fn test_upgrade() {
+        let dir = std::env::temp_dir().join(format!("rdoc-cache-test-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        assert_eq!(upgrade(&dir).unwrap(), None);
+
+        std::fs::create_dir_all(&dir).unwrap();
+        assert_eq!(cache_format(&dir).unwrap(), Some(0));
+        let upgraded = upgrade(&dir).unwrap().unwrap();
+        assert_eq!(upgraded.from, 0);
+        assert_eq!(upgraded.steps.len(), MIGRATIONS.len());
+        assert_eq!(cache_format(&dir).unwrap(), Some(CACHE_FORMAT));
+        // Up to date already
+        assert!(upgrade(&dir).unwrap().unwrap().steps.is_empty());
+
+        std::fs::write(dir.join(FORMAT_FILE), (CACHE_FORMAT + 1).to_string()).unwrap();
+        assert!(upgrade(&dir).is_err());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,94 @@
+// Stopping long-running queries early: when replaced by a newer one, or out of time
+
+use std::fmt;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
+
+/// Why a query stopped before finishing.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Cancelled {
+    /// Something cancelled it, such as a newer query from the same client
+    Cancelled,
+    /// It ran past its time budget
+    TimedOut(Duration),
+}
+
+impl fmt::Display for Cancelled {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Cancelled::Cancelled => write!(f, "the query was cancelled"),
+            Cancelled::TimedOut(budget) => {
+                write!(f, "the query took longer than its {:?} budget", budget)
+            }
+        }
+    }
+}
+
+impl std::error::Error for Cancelled {}
+
+/// Lets a query be stopped from elsewhere, or after a deadline.
+///
+/// Cancellation is cooperative: the query calls [`check`](Self::check) as it
+/// goes and gives up when it errors. Clones share the cancelled flag, so the
+/// one kept by whoever may cancel stops the query holding another. The
+/// default token never stops anything.
+#[derive(Debug, Clone, Default)]
+pub struct CancelToken {
+    cancelled: Arc<AtomicBool>,
+    deadline: Option<(Instant, Duration)>,
+}
+
+impl CancelToken {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The token, also stopping the query once `budget` has passed from now.
+    pub fn with_budget(self, budget: Duration) -> Self {
+        Self {
+            deadline: Some((Instant::now() + budget, budget)),
+            ..self
+        }
+    }
+
+    /// Stop the query holding this token (or a clone of it).
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    /// An error if the query should stop.
+    pub fn check(&self) -> Result<(), Cancelled> {
+        if self.cancelled.load(Ordering::Relaxed) {
+            return Err(Cancelled::Cancelled);
+        }
+        match self.deadline {
+            Some((deadline, budget)) if Instant::now() >= deadline => {
+                Err(Cancelled::TimedOut(budget))
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cancel_token() {
+        let token = CancelToken::new();
+        assert_eq!(token.check(), Ok(()));
+        let held = token.clone();
+        token.cancel();
+        assert_eq!(held.check(), Err(Cancelled::Cancelled));
+
+        let expired = CancelToken::new().with_budget(Duration::ZERO);
+        assert_eq!(expired.check(), Err(Cancelled::TimedOut(Duration::ZERO)));
+        let running = CancelToken::new().with_budget(Duration::from_secs(60));
+        assert_eq!(running.check(), Ok(()));
+        // A budget keeps the token's flag
+        let budgeted = held.with_budget(Duration::from_secs(60));
+        assert_eq!(budgeted.check(), Err(Cancelled::Cancelled));
+    }
+}
@@ -0,0 +1,195 @@
+// Which types function signatures mention most
+
+use crate::search_index::ItemType;
+use crate::signature::{FunctionSignature, SignatureType, TypeKind};
+use std::collections::{HashMap, HashSet};
+
+/// How many functions mention one named type.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TypeUsage {
+    pub name: String,
+    pub item_type: ItemType,
+    /// Module path of the type, e.g. `serde_json::value`
+    pub path: String,
+    /// Functions mentioning the type anywhere in their signature
+    pub functions: usize,
+    /// Functions taking it in a parameter, e.g. `&Value` or `Option<Value>`
+    pub inputs: usize,
+    /// Functions returning it
+    pub outputs: usize,
+    /// Functions with a generic parameter bounded by it, e.g. `T: Serialize`
+    pub bounds: usize,
+}
+
+impl TypeUsage {
+    /// The crate the type is defined in.
+    pub fn crate_name(&self) -> &str {
+        self.path.split("::").next().unwrap_or_default()
+    }
+}
+
+/// Count, for every named type, the functions whose signatures mention it,
+/// most mentioned first.
+///
+/// A function counts once per type however often it uses it. Primitives and
+/// built-in types such as references, slices and tuples aren't counted.
+pub fn type_usage<'a>(
+    signatures: impl IntoIterator<Item = &'a FunctionSignature>,
+) -> Vec<TypeUsage> {
+    let mut usage: HashMap<(&str, &str), TypeUsage> = HashMap::new();
+    for signature in signatures {
+        let inputs = named_types(&signature.inputs);
+        let outputs = named_types(&signature.output);
+        let bounds = named_types(signature.where_clause.iter().flatten());
+
+        let mentioned: HashSet<_> = inputs.iter().chain(&outputs).chain(&bounds).collect();
+        for &key in mentioned {
+            let (name, path, item_type) = key;
+            let entry = usage.entry((path, name)).or_insert_with(|| TypeUsage {
+                name: name.to_string(),
+                item_type,
+                path: path.to_string(),
+                functions: 0,
+                inputs: 0,
+                outputs: 0,
+                bounds: 0,
+            });
+            entry.functions += 1;
+            entry.inputs += inputs.contains(&key) as usize;
+            entry.outputs += outputs.contains(&key) as usize;
+            entry.bounds += bounds.contains(&key) as usize;
+        }
+    }
+
+    let mut usage: Vec<TypeUsage> = usage.into_values().collect();
+    usage.sort_by(|a, b| {
+        b.functions
+            .cmp(&a.functions)
+            .then_with(|| a.path.cmp(&b.path))
+            .then_with(|| a.name.cmp(&b.name))
+    });
+    usage
+}
+
+/// The distinct named types `signature` mentions, as `(name, path)`, anywhere
+/// in it: parameters, return type and bounds.
+pub fn mentioned_types(signature: &FunctionSignature) -> HashSet<(&str, &str)> {
+    named_types(
+        signature
+            .inputs
+            .iter()
+            .chain(&signature.output)
+            .chain(signature.where_clause.iter().flatten()),
+    )
+    .into_iter()
+    .map(|(name, path, _)| (name, path))
+    .collect()
+}
+
+/// The distinct named types in `types`, nested generic arguments included.
+fn named_types<'a>(
+    types: impl IntoIterator<Item = &'a SignatureType>,
+) -> HashSet<(&'a str, &'a str, ItemType)> {
+    fn visit<'a>(ty: &'a SignatureType, found: &mut HashSet<(&'a str, &'a str, ItemType)>) {
+        if let TypeKind::Named {
+            name,
+            item_type,
+            path,
+            ..
+        } = &ty.kind
+            && !is_builtin(*item_type)
+        {
+            found.insert((name, path, *item_type));
+        }
+        for inner in &ty.generics {
+            visit(inner, found);
+        }
+        for (name, types) in &ty.bindings {
+            visit(name, found);
+            for inner in types {
+                visit(inner, found);
+            }
+        }
+    }
+
+    let mut found = HashSet::new();
+    for ty in types {
+        visit(ty, &mut found);
+    }
+    found
+}
+
+fn is_builtin(item_type: ItemType) -> bool {
+    matches!(item_type, ItemType::Primitive | ItemType::Keyword)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ty(name: &str, path: &str, generics: Vec<SignatureType>) -> SignatureType {
+        SignatureType {
+            kind: TypeKind::Named {
+                name: name.to_string(),
+                item_type: match name {
+                    "reference" | "u8" => ItemType::Primitive,
+                    "Serialize" => ItemType::Trait,
+                    _ => ItemType::Struct,
+                },
+                path: path.to_string(),
+                unbox: false,
+            },
+            generics,
+            bindings: vec![],
+        }
+    }
+
+    fn value() -> SignatureType {
+        ty("Value", "serde_json::value", vec![])
+    }
+
+    #[test]
+    fn test_type_usage() {
+        let signatures = [
+            // fn as_str(&Value) -> Option<&str>
+            FunctionSignature {
+                inputs: vec![ty("reference", "", vec![value()])],
+                output: vec![ty("Option", "core::option", vec![])],
+                where_clause: vec![],
+            },
+            // fn merge(Value, Value) -> Value
+            FunctionSignature {
+                inputs: vec![value(), value()],
+                output: vec![value()],
+                where_clause: vec![],
+            },
+            // fn to_value<T: Serialize>(T) -> Result<Value>
+            FunctionSignature {
+                inputs: vec![],
+                output: vec![ty("Result", "serde_json", vec![value()])],
+                where_clause: vec![vec![ty("Serialize", "serde_core::ser", vec![])]],
+            },
+        ];
+
+        let usage = type_usage(&signatures);
+
+        assert_eq!(usage[0].name, "Value");
+        assert_eq!(usage[0].crate_name(), "serde_json");
+        assert_eq!(
+            (
+                usage[0].functions,
+                usage[0].inputs,
+                usage[0].outputs,
+                usage[0].bounds
+            ),
+            (3, 2, 2, 0)
+        );
+        let serialize = usage
+            .iter()
+            .find(|usage| usage.name == "Serialize")
+            .unwrap();
+        assert_eq!((serialize.functions, serialize.bounds), (1, 1));
+        // Built-in types aren't counted
+        assert!(!usage.iter().any(|usage| usage.name == "reference"));
+    }
+}
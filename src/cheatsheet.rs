@@ -0,0 +1,295 @@
+// Picking the items worth knowing first in a module, for a cheat-sheet
+
+use crate::centrality::{mentioned_types, type_usage};
+use crate::modules::module_sections;
+use crate::search_index::ItemType;
+use crate::search_items::{SearchItem, dedup_reexports};
+use crate::signature::{FunctionSignature, TypeKind};
+use std::collections::HashMap;
+
+/// How many of each kind of entry a cheat-sheet lists.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Limits {
+    /// Key types
+    pub types: usize,
+    /// Constructors, and then methods, per type
+    pub methods: usize,
+    /// Free functions
+    pub functions: usize,
+}
+
+impl Default for Limits {
+    fn default() -> Self {
+        Self {
+            types: 8,
+            methods: 5,
+            functions: 8,
+        }
+    }
+}
+
+/// A module's key types with their constructors and most important methods,
+/// and its most important functions, best first.
+#[derive(Debug)]
+pub struct Cheatsheet<'a> {
+    pub types: Vec<TypeEntry<'a>>,
+    pub functions: Vec<&'a SearchItem>,
+}
+
+/// One of a cheat-sheet's types.
+#[derive(Debug)]
+pub struct TypeEntry<'a> {
+    pub item: &'a SearchItem,
+    pub constructors: Vec<&'a SearchItem>,
+    pub methods: Vec<&'a SearchItem>,
+}
+
+/// Longest description that still counts for more
+const DESCRIPTION_CAP: usize = 200;
+
+/// Methods of the standard traits most types implement, which say little
+/// about any one of them
+const COMMON_TRAIT_METHODS: &[&str] = &[
+    "as_mut",
+    "as_ref",
+    "borrow",
+    "borrow_mut",
+    "clone",
+    "clone_from",
+    "clone_into",
+    "clone_to_uninit",
+    "cmp",
+    "default",
+    "deref",
+    "deref_mut",
+    "drop",
+    "eq",
+    "fmt",
+    "from",
+    "ge",
+    "gt",
+    "hash",
+    "into",
+    "into_iter",
+    "le",
+    "lt",
+    "ne",
+    "partial_cmp",
+    "to_owned",
+    "to_string",
+    "try_from",
+    "try_into",
+    "type_id",
+];
+
+/// Pick the entries of a cheat-sheet for the module at `module_path` (or a
+/// crate name for its root), with `signature` giving items' signatures and
+/// `description_len` the length of their descriptions.
+///
+/// Types come first when many function signatures across the index mention
+/// them, then when they're well documented. Methods and functions are ranked
+/// by how long their descriptions are and how central the types they take
+/// and return are. Constructors are a type's associated functions returning
+/// it without taking it, or named like one (`new`, `with_*`, `from_*`).
+pub fn cheatsheet<'a, 's>(
+    items: &'a [SearchItem],
+    module_path: &str,
+    signature: impl Fn(&SearchItem) -> Option<&'s FunctionSignature>,
+    mut description_len: impl FnMut(&SearchItem) -> usize,
+    limits: Limits,
+) -> Cheatsheet<'a> {
+    let usage = type_usage(dedup_reexports(items).into_iter().filter_map(&signature));
+    let centrality: HashMap<(&str, &str), usize> = usage
+        .iter()
+        .map(|usage| ((usage.name.as_str(), usage.path.as_str()), usage.functions))
+        .collect();
+    let listed: Vec<&SearchItem> = module_sections(items, module_path)
+        .into_iter()
+        .flat_map(|section| section.items)
+        .collect();
+
+    let mut types: Vec<(usize, usize, &SearchItem)> = listed
+        .iter()
+        .filter(|item| is_type(item.item_type))
+        .map(|item| {
+            let used = centrality
+                .get(&(item.name.as_str(), item.path.as_str()))
+                .copied()
+                .unwrap_or(0);
+            (used, description_len(item), *item)
+        })
+        .collect();
+    types.sort_by(|(a_used, a_len, a), (b_used, b_len, b)| {
+        b_used
+            .cmp(a_used)
+            .then_with(|| b_len.cmp(a_len))
+            .then_with(|| a.name.cmp(&b.name))
+    });
+    types.truncate(limits.types);
+
+    let mut score = |item: &SearchItem, own_type: Option<&SearchItem>| {
+        let mentions: usize = signature(item)
+            .map(|signature| {
+                mentioned_types(signature)
+                    .into_iter()
+                    .filter(|&(name, path)| {
+                        own_type.is_none_or(|own| own.name != name || own.path != path)
+                    })
+                    .map(|key| centrality.get(&key).copied().unwrap_or(0))
+                    .sum()
+            })
+            .unwrap_or(0);
+        description_len(item).min(DESCRIPTION_CAP) as f64 + 20.0 * (mentions as f64).ln_1p()
+    };
+
+    let types = types
+        .into_iter()
+        .map(|(_, _, ty)| {
+            let members = dedup_reexports(items.iter().filter(|item| {
+                matches!(item.item_type, ItemType::Method | ItemType::TyMethod)
+                    && item.parent_name() == Some(ty.name.as_str())
+                    && item.exact_path == ty.exact_path
+                    && !COMMON_TRAIT_METHODS.contains(&item.name.as_str())
+                    && signature(item).is_none_or(|signature| mentions(signature, ty))
+            }));
+            let (constructors, methods): (Vec<&SearchItem>, Vec<&SearchItem>) = members
+                .into_iter()
+                .partition(|item| is_constructor(item, ty, signature(item)));
+            TypeEntry {
+                item: ty,
+                constructors: best(constructors, limits.methods, |item| {
+                    // `new` is the one to know
+                    let new = if item.name == "new" { 1000.0 } else { 0.0 };
+                    new + score(item, Some(ty))
+                }),
+                methods: best(methods, limits.methods, |item| score(item, Some(ty))),
+            }
+        })
+        .collect();
+
+    let functions: Vec<&SearchItem> = listed
+        .into_iter()
+        .filter(|item| item.item_type == ItemType::Function)
+        .collect();
+    let functions = best(functions, limits.functions, |item| score(item, None));
+
+    Cheatsheet { types, functions }
+}
+
+/// The `limit` items `score` ranks highest, by name among equal scores.
+fn best(
+    items: Vec<&SearchItem>,
+    limit: usize,
+    mut score: impl FnMut(&SearchItem) -> f64,
+) -> Vec<&SearchItem> {
+    let mut scored: Vec<(f64, &SearchItem)> =
+        items.into_iter().map(|item| (score(item), item)).collect();
+    scored.sort_by(|(a_score, a), (b_score, b)| {
+        b_score.total_cmp(a_score).then_with(|| a.name.cmp(&b.name))
+    });
+    scored
+        .into_iter()
+        .take(limit)
+        .map(|(_, item)| item)
+        .collect()
+}
+
+fn is_type(item_type: ItemType) -> bool {
+    matches!(
+        item_type,
+        ItemType::Struct
+            | ItemType::Enum
+            | ItemType::Union
+            | ItemType::Trait
+            | ItemType::TraitAlias
+            | ItemType::Typedef
+    )
+}
+
+/// Whether `signature` takes or returns a `ty`, as a method of its own does
+/// (unlike blanket implementations' methods, such as `borrow`).
+fn mentions(signature: &FunctionSignature, ty: &SearchItem) -> bool {
+    mentioned_types(signature).contains(&(ty.name.as_str(), ty.path.as_str()))
+}
+
+/// Whether `method` makes a `ty`: it returns one without taking one (as
+/// `self`), or is named like a constructor when its signature isn't known.
+fn is_constructor(
+    method: &SearchItem,
+    ty: &SearchItem,
+    signature: Option<&FunctionSignature>,
+) -> bool {
+    if matches!(ty.item_type, ItemType::Trait | ItemType::TraitAlias) {
+        return false;
+    }
+    let is_ty = |kind: &TypeKind| matches!(kind, TypeKind::Named { name, .. } if *name == ty.name);
+    match signature {
+        Some(signature) => {
+            // `Self` is indexed as the type itself, and `Result<Self>` counts
+            let returns = signature.output.iter().any(|output| {
+                is_ty(&output.kind) || output.generics.iter().any(|inner| is_ty(&inner.kind))
+            });
+            let takes = signature.inputs.iter().any(|input| is_ty(&input.kind));
+            returns && !takes
+        }
+        None => {
+            let name = method.name.as_str();
+            name == "new"
+                || name.starts_with("new_")
+                || name.starts_with("with_")
+                || name.starts_with("from_")
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::search_index::{extract_json_string, parse_search_index};
+    use crate::search_items::decode_crate;
+    use crate::signature::decode_signatures;
+
+    #[test]
+    fn test_cheatsheet() {
+        let content = std::fs::read_to_string("tests/fixtures/search-index.js").unwrap();
+        let entries = parse_search_index(&extract_json_string(&content).unwrap()).unwrap();
+        let fst = entries.iter().find(|entry| entry.name == "fst").unwrap();
+        let items = decode_crate(&fst.name, &fst.data);
+        let signatures = decode_signatures(&fst.data);
+        let signature = |item: &SearchItem| signatures.get(item.id.index)?.as_ref();
+
+        let limits = Limits {
+            types: 3,
+            methods: 2,
+            functions: 2,
+        };
+        let sheet = cheatsheet(&items, "fst::map", signature, |_| 0, limits);
+        assert_eq!(sheet.types.len(), 3);
+        assert!(
+            sheet
+                .types
+                .iter()
+                .all(|entry| is_type(entry.item.item_type))
+        );
+        // `Map` is what fst::map is about, and the type signatures use most
+        let map = &sheet.types[0];
+        assert_eq!(map.item.name, "Map");
+        assert!(map.constructors.len() <= 2 && map.methods.len() <= 2);
+        assert!(map.constructors.iter().any(|item| item.name == "new"));
+        for method in map.constructors.iter().chain(&map.methods) {
+            assert_eq!(method.parent_name(), Some("Map"));
+        }
+        assert!(!map.methods.iter().any(|item| item.name == "new"));
+        // Methods every type has are left out
+        assert!(
+            !map.constructors
+                .iter()
+                .chain(&map.methods)
+                .any(|item| COMMON_TRAIT_METHODS.contains(&item.name.as_str()))
+        );
+
+        // Modules without items give an empty sheet
+        let empty = cheatsheet(&items, "fst::nowhere", signature, |_| 0, limits);
+        assert!(empty.types.is_empty() && empty.functions.is_empty());
+    }
+}
@@ -0,0 +1,69 @@
+// Records of each item's documentation for embedding, as `export chunks` writes them
+
+use serde::Serialize;
+
+use crate::format::ResultRecord;
+
+/// Characters of text a record holds by default: about 500 tokens, which
+/// common embedding models take whole
+pub const DEFAULT_MAX_CHARS: usize = 2000;
+
+/// One item's declaration and documentation, written one per line.
+#[derive(Debug, Serialize)]
+pub struct DocChunk<'a> {
+    #[serde(flatten)]
+    pub item: ResultRecord<'a>,
+    /// The item's full path with a `kind@` prefix, e.g. `trait@serde::Serialize`
+    pub qualified_path: String,
+    /// The declaration from the item's page, when the page can be read
+    pub signature: Option<String>,
+    /// The documentation as text, or the description from the search index
+    /// when the page can't be read; empty for undocumented items
+    pub text: String,
+    /// Whether `text` was cut short to fit
+    pub truncated: bool,
+}
+
+/// `text` cut to at most `max_chars` characters, and whether it had to be.
+///
+/// It's cut at the last paragraph break that fits, or failing that the last
+/// sentence or word, so no record ends mid-word.
+pub fn fit_text(text: &str, max_chars: usize) -> (&str, bool) {
+    let Some((limit, _)) = text.char_indices().nth(max_chars) else {
+        return (text, false);
+    };
+    let fits = &text[..limit];
+    let end = ["\n\n", ". ", "\n", " "]
+        .iter()
+        .find_map(|separator| {
+            fits.rfind(separator)
+                .filter(|&end| end > 0)
+                // Keep a sentence's full stop
+                .map(|end| if *separator == ". " { end + 1 } else { end })
+        })
+        .unwrap_or(limit);
+    (text[..end].trim_end(), true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fit_text() {
+        let text = "Serialize this value.\n\nSee the guide. It has examples.";
+        assert_eq!(fit_text(text, 100), (text, false));
+        assert_eq!(fit_text(text, 30), ("Serialize this value.", true));
+        assert_eq!(
+            fit_text("Serialize this value. See the guide.", 30),
+            ("Serialize this value.", true)
+        );
+        assert_eq!(
+            fit_text("Serialize this value", 15),
+            ("Serialize this", true)
+        );
+        assert_eq!(fit_text("Serialize", 4), ("Seri", true));
+        // Counts characters, not bytes
+        assert_eq!(fit_text("é é", 3), ("é é", false));
+    }
+}
@@ -0,0 +1,249 @@
+// A typed client for the HTTP API `rdoc serve` provides
+
+use serde::Deserialize;
+use std::fmt;
+
+/// One item in a response, as `ResultRecord` writes it.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct Item {
+    pub id: String,
+    pub name: String,
+    pub item_type: String,
+    #[serde(rename = "crate")]
+    pub crate_name: String,
+    pub path: String,
+    pub exact_path: String,
+    pub deprecated: bool,
+    /// The item's page relative to the doc root
+    pub url: Option<String>,
+    /// `url` on the doc site, when the server knows its base URL
+    pub absolute_url: Option<String>,
+}
+
+/// Why a path didn't resolve to exactly one item.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct ResolutionError {
+    /// `invalid_path`, `unknown_kind`, `not_found` or `ambiguous`
+    pub kind: String,
+    pub message: String,
+    /// For `ambiguous`, each candidate with a prefix that resolves to it alone
+    pub candidates: Vec<String>,
+}
+
+/// One path's outcome: exactly one of `item` and `error` is set.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct Resolution {
+    pub path: String,
+    pub item: Option<Item>,
+    pub error: Option<ResolutionError>,
+}
+
+/// One query's results from `GET /search`.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct QueryResults {
+    pub query: String,
+    /// Why the query is invalid, in which case there are no results
+    pub error: Option<String>,
+    pub results: Vec<Item>,
+}
+
+/// An item's preview from `GET /item`.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct ItemPreview {
+    pub item: Item,
+    /// The impl a method or associated item comes from
+    pub origin: Option<String>,
+    pub declaration: Option<String>,
+    /// The description as plain text
+    pub description: Option<String>,
+}
+
+/// The body of a `/resolve` response; the header fields are ignored.
+#[derive(Debug, Deserialize)]
+struct Resolutions {
+    results: Vec<Resolution>,
+}
+
+/// The body the server sends with error statuses.
+#[derive(Debug, Deserialize)]
+struct ApiError {
+    error: String,
+}
+
+/// Why a request to the server failed.
+#[derive(Debug)]
+pub enum ClientError {
+    /// The server couldn't be reached, or the response couldn't be read
+    Http(ureq::Error),
+    /// The server answered with an error status
+    Status { status: u16, message: String },
+    /// The response isn't what the API describes
+    Decode(serde_json::Error),
+}
+
+impl fmt::Display for ClientError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ClientError::Http(err) => write!(f, "request failed: {}", err),
+            ClientError::Status { status, message } => {
+                write!(f, "server answered {}: {}", status, message)
+            }
+            ClientError::Decode(err) => write!(f, "unexpected response: {}", err),
+        }
+    }
+}
+
+impl std::error::Error for ClientError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ClientError::Http(err) => Some(err),
+            ClientError::Status { .. } => None,
+            ClientError::Decode(err) => Some(err),
+        }
+    }
+}
+
+impl From<ureq::Error> for ClientError {
+    fn from(err: ureq::Error) -> Self {
+        ClientError::Http(err)
+    }
+}
+
+/// A connection to a running `rdoc serve`.
+///
+/// ```no_run
+/// let client = rdoc::client::Client::new("http://127.0.0.1:7878");
+/// for resolution in client.resolve(&["serde::Serialize"])? {
+///     println!("{:?}", resolution.item.map(|item| item.absolute_url));
+/// }
+/// # Ok::<(), rdoc::client::ClientError>(())
+/// ```
+#[derive(Debug, Clone)]
+pub struct Client {
+    base_url: String,
+    agent: ureq::Agent,
+}
+
+impl Client {
+    /// A client for the server at `base_url`, e.g. `http://127.0.0.1:7878`.
+    pub fn new(base_url: &str) -> Self {
+        let agent = ureq::Agent::config_builder()
+            // Error statuses carry a message in their body, which we want to read
+            .http_status_as_error(false)
+            .build()
+            .into();
+        Self {
+            base_url: base_url.trim_end_matches('/').to_string(),
+            agent,
+        }
+    }
+
+    /// Resolve each of `paths`, as `POST /resolve` does, in the order given.
+    pub fn resolve<S: AsRef<str>>(&self, paths: &[S]) -> Result<Vec<Resolution>, ClientError> {
+        let paths: Vec<&str> = paths.iter().map(AsRef::as_ref).collect();
+        let body = serde_json::to_string(&paths).map_err(ClientError::Decode)?;
+        let mut response = self
+            .agent
+            .post(format!("{}/resolve", self.base_url))
+            .header("Content-Type", "application/json")
+            .send(body)?;
+        let status = response.status().as_u16();
+        let body = response.body_mut().read_to_string()?;
+        decode_resolutions(status, &body)
+    }
+
+    /// Search items with a `scan` query, answering with at most `limit` results.
+    pub fn search(&self, query: &str, limit: usize) -> Result<QueryResults, ClientError> {
+        self.get(&format!(
+            "/search?q={}&limit={}",
+            urlencoding::encode(query),
+            limit
+        ))
+    }
+
+    /// The preview of the item with `id` (e.g. `serde#142@local`), as IDs
+    /// appear in [`Item::id`].
+    pub fn item(&self, id: &str) -> Result<ItemPreview, ClientError> {
+        self.get(&format!("/item?id={}", urlencoding::encode(id)))
+    }
+
+    /// The server's OpenAPI document (`GET /openapi.json`).
+    pub fn openapi(&self) -> Result<serde_json::Value, ClientError> {
+        self.get("/openapi.json")
+    }
+
+    /// `GET` the endpoint at `path` and decode its response.
+    fn get<T: for<'de> Deserialize<'de>>(&self, path: &str) -> Result<T, ClientError> {
+        let mut response = self
+            .agent
+            .get(format!("{}{}", self.base_url, path))
+            .call()?;
+        let status = response.status().as_u16();
+        let body = response.body_mut().read_to_string()?;
+        decode(status, &body)
+    }
+}
+
+fn decode_resolutions(status: u16, body: &str) -> Result<Vec<Resolution>, ClientError> {
+    decode::<Resolutions>(status, body).map(|resolutions| resolutions.results)
+}
+
+/// `body` as a `T`, or the error a non-200 `status` carries.
+fn decode<T: for<'de> Deserialize<'de>>(status: u16, body: &str) -> Result<T, ClientError> {
+    if status != 200 {
+        let message = match serde_json::from_str::<ApiError>(body) {
+            Ok(error) => error.error,
+            Err(_) => body.to_string(),
+        };
+        return Err(ClientError::Status { status, message });
+    }
+    serde_json::from_str(body).map_err(ClientError::Decode)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::format::{Resolution as ResolutionRecord, Resolutions as ResolutionRecords};
+    use crate::pages::SiteLinks;
+    use crate::resolve::resolve;
+    use crate::schema::Versioned;
+    use crate::search_index::{extract_json_string, parse_search_index};
+    use crate::search_items::decode_crate;
+
+    #[test]
+    fn test_decode_what_the_server_writes() {
+        let content = std::fs::read_to_string("tests/fixtures/search-index.js").unwrap();
+        let entries = parse_search_index(&extract_json_string(&content).unwrap()).unwrap();
+        let items = decode_crate(&entries[0].name, &entries[0].data);
+        let links = SiteLinks::default();
+
+        let results = ["nowhere", &items[0].path]
+            .iter()
+            .map(|&path| ResolutionRecord::new(path, resolve(&items, path), &links))
+            .chain([ResolutionRecord::new(&items[0].name, Ok(&items[0]), &links)])
+            .collect();
+        let body = Versioned::new("Resolutions", ResolutionRecords { results });
+        let body = serde_json::to_string(&body).unwrap();
+
+        let resolutions = decode_resolutions(200, &body).unwrap();
+        assert_eq!(resolutions.len(), 3);
+        assert_eq!(resolutions[0].path, "nowhere");
+        assert_eq!(resolutions[0].error.as_ref().unwrap().kind, "not_found");
+        assert!(resolutions[0].item.is_none());
+        let item = resolutions[2].item.as_ref().unwrap();
+        assert_eq!(item.name, items[0].name);
+        assert_eq!(item.id, items[0].id.to_string());
+
+        match decode_resolutions(405, r#"{"error":"/resolve only accepts POST"}"#) {
+            Err(ClientError::Status { status, message }) => {
+                assert_eq!(status, 405);
+                assert_eq!(message, "/resolve only accepts POST");
+            }
+            other => panic!("expected a status error, got {:?}", other),
+        }
+        assert!(matches!(
+            decode_resolutions(200, "[]"),
+            Err(ClientError::Decode(_))
+        ));
+    }
+}
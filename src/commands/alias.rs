@@ -0,0 +1,93 @@
+// Managing user-defined aliases in the config file
+
+use color_eyre::{Result, eyre::bail};
+use colored::Colorize;
+use std::collections::BTreeMap;
+
+use crate::config;
+
+/// Parse an alias name: a single word, since queries split on spaces and
+/// paths on `::`.
+pub fn parse_alias_name(value: &str) -> Result<String, String> {
+    if value.is_empty() {
+        return Err("an alias can't be empty".to_string());
+    }
+    if value.contains(char::is_whitespace) || value.contains("::") {
+        return Err(format!(
+            "\"{}\" is not a single name; aliases can't contain spaces or \"::\"",
+            value
+        ));
+    }
+    Ok(value.to_string())
+}
+
+/// Add an alias for an item path, replacing any alias with the same name
+pub fn add(name: &str, path: &str, quiet: bool) -> Result<()> {
+    let config_path = config::editable_config_path()?;
+    let replaced = config::set_alias(&config_path, name, Some(path))?;
+    if !quiet {
+        println!(
+            "{} {} {} → {} in {}",
+            "✓".green().bold(),
+            if replaced { "Replaced" } else { "Added" },
+            name.cyan(),
+            path,
+            config_path.display().to_string().dimmed()
+        );
+    }
+    Ok(())
+}
+
+/// Remove an alias from the config file
+pub fn remove(name: &str, quiet: bool) -> Result<()> {
+    let config_path = config::editable_config_path()?;
+    if !config_path.exists() || !config::set_alias(&config_path, name, None)? {
+        bail!("No alias \"{}\" in {}", name, config_path.display());
+    }
+    if !quiet {
+        println!(
+            "{} Removed {} from {}",
+            "✓".green().bold(),
+            name.cyan(),
+            config_path.display().to_string().dimmed()
+        );
+    }
+    Ok(())
+}
+
+/// List the aliases defined in the config file
+pub fn list(aliases: &BTreeMap<String, String>) -> Result<()> {
+    if aliases.is_empty() {
+        println!(
+            "{} No aliases defined; add one with `rdoc alias add NAME PATH`",
+            "✗".red().bold()
+        );
+        return Ok(());
+    }
+    let width = aliases
+        .keys()
+        .map(|name| name.chars().count())
+        .max()
+        .unwrap_or(0);
+    for (name, path) in aliases {
+        println!(
+            "  {}  {}",
+            format!("{:<width$}", name, width = width).cyan(),
+            path
+        );
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_alias_name() {
+        assert_eq!(parse_alias_name("hm"), Ok("hm".to_string()));
+        assert!(parse_alias_name("").is_err());
+        assert!(parse_alias_name("hash map").is_err());
+        assert!(parse_alias_name("std::hm").is_err());
+    }
+}
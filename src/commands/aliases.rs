@@ -0,0 +1,68 @@
+// Listing the aliases crates declare with `#[doc(alias)]`
+
+use color_eyre::Result;
+use colored::Colorize;
+use rdoc::aliases::Aliases;
+use rdoc::human::count;
+use rdoc::resolve::disambiguated_path;
+use rdoc::search_items::SearchItem;
+use std::collections::BTreeMap;
+
+use crate::docs::{self, DocSource};
+
+/// List every item crates give `#[doc(alias)]`es to, with its aliases, to
+/// audit what else an API can be found by
+pub fn execute(crates: &[String], source: &DocSource, quiet: bool) -> Result<()> {
+    if !quiet {
+        println!("{} Listing declared aliases", "→".cyan().bold());
+    }
+
+    let search_index_path = docs::find_search_index(source, quiet, true)?;
+    let json_string = docs::load_search_index_json(&search_index_path)?;
+    let crate_entries = docs::crate_entries(&json_string)?;
+    // Only the index's aliases, not the user's
+    let mut aliases = Aliases::default();
+    let all_items = docs::filter_crates(
+        docs::decode_items_with_aliases(&crate_entries, source, &mut aliases),
+        crates,
+    )?;
+
+    let declared = aliases.declared(&all_items);
+    if declared.is_empty() {
+        println!("{} No aliases declared", "✗".red().bold());
+        return Ok(());
+    }
+    let mut by_crate: BTreeMap<&str, Vec<(&SearchItem, Vec<&str>)>> = BTreeMap::new();
+    for (item, names) in declared {
+        by_crate
+            .entry(&item.crate_name)
+            .or_default()
+            .push((item, names));
+    }
+    for (crate_name, declared) in &by_crate {
+        let total: usize = declared.iter().map(|(_, names)| names.len()).sum();
+        println!(
+            "\n{} {} on {}",
+            crate_name.green().bold(),
+            count(total, "alias", "aliases"),
+            count(declared.len(), "item", "items")
+        );
+        let paths: Vec<String> = declared
+            .iter()
+            .map(|(item, _)| disambiguated_path(item))
+            .collect();
+        let width = paths
+            .iter()
+            .map(|path| path.chars().count())
+            .max()
+            .unwrap_or(0);
+        for (path, (_, names)) in paths.iter().zip(declared) {
+            println!(
+                "  {}  {}",
+                format!("{:<width$}", path, width = width).cyan(),
+                names.join(", ")
+            );
+        }
+    }
+    Ok(())
+}
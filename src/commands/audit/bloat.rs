@@ -0,0 +1,276 @@
+use color_eyre::{Result, eyre::eyre};
+use colored::Colorize;
+use rdoc::descriptions::{shard_count, shard_file};
+use rdoc::human::{count, number};
+use rdoc::resources::resource_suffix;
+use std::cmp::Reverse;
+use std::collections::HashMap;
+use std::path::Path;
+use tracing::debug;
+
+use crate::config::IGNORE_FILE;
+use crate::docs::{self, DocSource};
+
+/// How much of the search index one crate takes up
+#[derive(Debug)]
+struct Footprint {
+    crate_name: String,
+    items: usize,
+    /// Items with a description
+    described: usize,
+    /// The crate's entry in search-index.js
+    index_bytes: u64,
+    /// Its description shards, when they're on disk
+    description_bytes: Option<u64>,
+    /// A workspace member, rather than a dependency
+    own: bool,
+}
+
+impl Footprint {
+    fn bytes(&self) -> u64 {
+        self.index_bytes + self.description_bytes.unwrap_or(0)
+    }
+}
+
+/// Report which crates contribute the most items and bytes to the search
+/// index, failing if there are more than `budget` items in total
+pub fn execute(budget: Option<usize>, limit: usize, source: &DocSource, quiet: bool) -> Result<()> {
+    if !quiet {
+        println!("{} Auditing the search index's size", "→".cyan().bold());
+    }
+
+    let search_index_path = docs::find_search_index(source, quiet, true)?;
+    let json_string = docs::load_search_index_json(&search_index_path)?;
+    let crate_entries = docs::crate_entries(&json_string)?;
+    let all_items = docs::decode_items(&crate_entries, source);
+    // Only used to keep the workspace's crates out of suggestions
+    let own_crates = if source.is_cargo_project() {
+        docs::workspace_crates()
+            .inspect_err(|err| debug!("not telling workspace crates apart: {:#}", err))
+            .unwrap_or_default()
+    } else {
+        Vec::new()
+    };
+
+    let mut counts: HashMap<&str, (usize, usize)> = HashMap::new();
+    for item in &all_items {
+        let (items, described) = counts.entry(&item.crate_name).or_default();
+        *items += 1;
+        *described += usize::from(!item.empty_description);
+    }
+    // Remote docs' shards are downloaded on demand, so their size isn't known
+    let doc_root = match source {
+        DocSource::Remote { .. } => None,
+        _ => search_index_path.parent(),
+    };
+    let suffix = resource_suffix(&search_index_path);
+    let mut footprints: Vec<Footprint> = crate_entries
+        .iter()
+        .map(|entry| {
+            let (items, described) = counts.get(&*entry.name).copied().unwrap_or_default();
+            let description_bytes = doc_root.and_then(|doc_root| {
+                let shards = entry
+                    .data()
+                    .map(|data| shard_count(&data.desc))
+                    .unwrap_or_default();
+                shards_size(doc_root, &entry.name, shards, suffix)
+            });
+            Footprint {
+                crate_name: entry.name.to_string(),
+                items,
+                described,
+                index_bytes: (entry.name.len() + entry.raw_data.get().len()) as u64,
+                description_bytes,
+                own: own_crates.iter().any(|name| *name == entry.name),
+            }
+        })
+        .collect();
+    footprints.sort_by(|a, b| b.bytes().cmp(&a.bytes()).then(b.items.cmp(&a.items)));
+
+    print_footprints(&footprints, limit);
+
+    let total_items: usize = footprints.iter().map(|footprint| footprint.items).sum();
+    let Some(budget) = budget else {
+        return Ok(());
+    };
+    if total_items <= budget {
+        println!(
+            "\n{} {}, within the budget of {}",
+            "✓".green().bold(),
+            count(total_items, "item", "items"),
+            number(budget)
+        );
+        return Ok(());
+    }
+    let cuts = cuts_within_budget(&footprints, budget);
+    if !cuts.is_empty() {
+        println!(
+            "\n{} Adding {} to {} would bring the index within budget",
+            "ℹ".blue().bold(),
+            cuts.join(", ").cyan(),
+            IGNORE_FILE
+        );
+    }
+    Err(eyre!(
+        "The search index has {}, over the budget of {}",
+        count(total_items, "item", "items"),
+        number(budget)
+    ))
+}
+
+fn print_footprints(footprints: &[Footprint], limit: usize) {
+    let total_bytes: u64 = footprints.iter().map(Footprint::bytes).sum();
+    let total_items: usize = footprints.iter().map(|footprint| footprint.items).sum();
+    let width = footprints
+        .iter()
+        .take(limit)
+        .map(|footprint| footprint.crate_name.chars().count())
+        .max()
+        .unwrap_or(0)
+        .max("Crate".len());
+
+    println!(
+        "\n  {:<width$}  {:>8}  {:>9}  {:>10}  {:>12}  {:>6}",
+        "Crate".bold(),
+        "Items".bold(),
+        "Described".bold(),
+        "Index".bold(),
+        "Descriptions".bold(),
+        "Share".bold(),
+    );
+    for footprint in footprints.iter().take(limit) {
+        let name = if footprint.own {
+            footprint.crate_name.green()
+        } else {
+            footprint.crate_name.normal()
+        };
+        println!(
+            "  {:<width$}  {:>8}  {:>9}  {:>10}  {:>12}  {:>5.1}%",
+            name,
+            footprint.items,
+            footprint.described,
+            format_size(footprint.index_bytes),
+            footprint
+                .description_bytes
+                .map_or("-".to_string(), format_size),
+            percent(footprint.bytes(), total_bytes),
+        );
+    }
+    if footprints.len() > limit {
+        println!(
+            "  {}",
+            format!(
+                "... and {}",
+                count(footprints.len() - limit, "smaller crate", "smaller crates")
+            )
+            .dimmed()
+        );
+    }
+    println!(
+        "\n{} {}, {}, {} in all",
+        "ℹ".blue().bold(),
+        count(footprints.len(), "crate", "crates"),
+        count(total_items, "item", "items"),
+        format_size(total_bytes)
+    );
+}
+
+/// The total size of a crate's `shards` description shards, or `None` if
+/// none of them are on disk (as with newer rustdoc, which stores them
+/// elsewhere).
+fn shards_size(doc_root: &Path, crate_name: &str, shards: usize, suffix: &str) -> Option<u64> {
+    let sizes: Vec<u64> = (0..shards)
+        .filter_map(|shard| {
+            let path = shard_file(doc_root, crate_name, shard, suffix);
+            std::fs::metadata(&path)
+                .inspect_err(|err| debug!("no size for {}: {}", path.display(), err))
+                .ok()
+        })
+        .map(|metadata| metadata.len())
+        .collect();
+    (!sizes.is_empty()).then(|| sizes.iter().sum())
+}
+
+/// The fewest dependencies (largest first) to leave out of the index to bring
+/// it within `budget` items, or none if even leaving out every dependency
+/// wouldn't. Workspace crates are never suggested.
+fn cuts_within_budget(footprints: &[Footprint], budget: usize) -> Vec<&str> {
+    let mut total: usize = footprints.iter().map(|footprint| footprint.items).sum();
+    let mut dependencies: Vec<&Footprint> = footprints
+        .iter()
+        .filter(|footprint| !footprint.own)
+        .collect();
+    dependencies.sort_by_key(|footprint| Reverse(footprint.items));
+
+    let mut cuts = Vec::new();
+    for footprint in dependencies {
+        if total <= budget {
+            break;
+        }
+        total -= footprint.items;
+        cuts.push(footprint.crate_name.as_str());
+    }
+    if total <= budget { cuts } else { Vec::new() }
+}
+
+fn percent(part: u64, total: u64) -> f64 {
+    if total == 0 {
+        0.0
+    } else {
+        part as f64 * 100.0 / total as f64
+    }
+}
+
+/// A byte count in B, KiB or MiB.
+fn format_size(bytes: u64) -> String {
+    const KIB: u64 = 1024;
+    const MIB: u64 = 1024 * KIB;
+    if bytes >= MIB {
+        format!("{:.1} MiB", bytes as f64 / MIB as f64)
+    } else if bytes >= KIB {
+        format!("{:.1} KiB", bytes as f64 / KIB as f64)
+    } else {
+        format!("{} B", bytes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn footprint(crate_name: &str, items: usize, own: bool) -> Footprint {
+        Footprint {
+            crate_name: crate_name.to_string(),
+            items,
+            described: 0,
+            index_bytes: 0,
+            description_bytes: None,
+            own,
+        }
+    }
+
+    #[test]
+    fn test_cuts_within_budget() {
+        let footprints = [
+            footprint("app", 500, true),
+            footprint("serde", 800, false),
+            footprint("windows_sys", 9000, false),
+            footprint("syn", 2000, false),
+        ];
+        assert_eq!(cuts_within_budget(&footprints, 5000), ["windows_sys"]);
+        assert_eq!(
+            cuts_within_budget(&footprints, 1500),
+            ["windows_sys", "syn"]
+        );
+        assert!(cuts_within_budget(&footprints, 20000).is_empty());
+        // The workspace alone is over budget
+        assert!(cuts_within_budget(&footprints, 100).is_empty());
+    }
+
+    #[test]
+    fn test_format_size() {
+        assert_eq!(format_size(512), "512 B");
+        assert_eq!(format_size(1536), "1.5 KiB");
+        assert_eq!(format_size(3 * 1024 * 1024), "3.0 MiB");
+    }
+}
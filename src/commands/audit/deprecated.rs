@@ -0,0 +1,154 @@
+use color_eyre::Result;
+use colored::Colorize;
+use rdoc::human::count;
+use rdoc::search_items::SearchItem;
+use std::collections::BTreeMap;
+
+use crate::docs::{self, DocSource};
+
+/// List deprecated items re-exported by your crates or exposed by dependencies
+pub fn execute(crates: &[String], source: &DocSource, quiet: bool) -> Result<()> {
+    if !quiet {
+        println!("{} Auditing deprecated items", "→".cyan().bold());
+    }
+
+    let search_index_path = docs::find_search_index(source, quiet, true)?;
+    let json_string = docs::load_search_index_json(&search_index_path)?;
+    let crate_entries = docs::crate_entries(&json_string)?;
+    let all_items = docs::decode_items(&crate_entries, source);
+
+    // Without --crate, "your crates" are the workspace members; docs outside a
+    // cargo project have none
+    let own_crates = if !crates.is_empty() {
+        crates.to_vec()
+    } else if source.is_cargo_project() {
+        docs::workspace_crates()?
+    } else {
+        Vec::new()
+    };
+
+    let audit = classify(&all_items, &own_crates);
+
+    if audit.reexported.is_empty() && audit.dependencies.is_empty() {
+        println!("{} No deprecated items found", "✓".green().bold());
+        return Ok(());
+    }
+
+    for (crate_name, items) in &audit.reexported {
+        println!(
+            "\n{} re-exports {}:",
+            crate_name.green().bold(),
+            count(items.len(), "deprecated item", "deprecated items")
+        );
+        for item in items {
+            println!(
+                "  {}::{} ({}) from {}",
+                item.path.dimmed(),
+                item.name.cyan(),
+                item.item_type.to_string().yellow(),
+                item.exact_path
+            );
+        }
+    }
+
+    for (crate_name, items) in &audit.dependencies {
+        println!(
+            "\n{} (dependency) exposes {}:",
+            crate_name.green().bold(),
+            count(items.len(), "deprecated item", "deprecated items")
+        );
+        for item in items {
+            println!(
+                "  {}::{} ({})",
+                item.path.dimmed(),
+                item.name.cyan(),
+                item.item_type.to_string().yellow()
+            );
+        }
+    }
+
+    Ok(())
+}
+
+/// Deprecated items grouped by the crate they appear in
+#[derive(Default)]
+struct DeprecationAudit<'a> {
+    /// Items your crates re-export from another crate
+    reexported: BTreeMap<&'a str, Vec<&'a SearchItem>>,
+    /// Items in every other crate
+    dependencies: BTreeMap<&'a str, Vec<&'a SearchItem>>,
+}
+
+/// Sort deprecated items into re-exports by `own_crates` and dependency items.
+///
+/// Items your crates deprecate themselves are left out: this audit is about
+/// migrations away from other crates' APIs.
+fn classify<'a>(items: &'a [SearchItem], own_crates: &[String]) -> DeprecationAudit<'a> {
+    let mut audit = DeprecationAudit::default();
+    for item in items.iter().filter(|item| item.deprecated) {
+        if own_crates.contains(&item.crate_name) {
+            let origin = item.exact_path.split("::").next().unwrap_or_default();
+            if origin != item.crate_name {
+                audit
+                    .reexported
+                    .entry(&item.crate_name)
+                    .or_default()
+                    .push(item);
+            }
+        } else {
+            audit
+                .dependencies
+                .entry(&item.crate_name)
+                .or_default()
+                .push(item);
+        }
+    }
+    audit
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rdoc::item_id::ItemId;
+    use rdoc::search_index::ItemType;
+
+    fn item(crate_name: &str, name: &str, exact_path: &str, deprecated: bool) -> SearchItem {
+        SearchItem {
+            crate_name: crate_name.to_string(),
+            item_type: ItemType::Function,
+            name: name.to_string(),
+            normalized_name: name.to_lowercase(),
+            path: crate_name.to_string(),
+            exact_path: exact_path.to_string(),
+            id: ItemId::new(ItemId::LOCAL_SOURCE, crate_name, 0),
+            param_types: vec![],
+            impl_disambiguator: None,
+            bit_index: 1,
+            parent_index: None,
+            parents: Default::default(),
+            deprecated,
+            empty_description: false,
+            description_location: None,
+        }
+    }
+
+    #[test]
+    fn test_classify_deprecated_items() {
+        let items = vec![
+            item("app", "old_helper", "app", true),
+            item("app", "legacy", "dep::legacy", true),
+            item("app", "current", "dep::current", false),
+            item("dep", "legacy", "dep::legacy", true),
+        ];
+
+        let audit = classify(&items, &["app".to_string()]);
+
+        let reexported: Vec<_> = audit.reexported["app"]
+            .iter()
+            .map(|item| item.name.as_str())
+            .collect();
+        assert_eq!(reexported, vec!["legacy"]);
+        assert_eq!(audit.dependencies["dep"].len(), 1);
+        assert!(!audit.dependencies.contains_key("app"));
+    }
+}
@@ -0,0 +1,67 @@
+use color_eyre::{Result, eyre::eyre};
+use colored::Colorize;
+use rdoc::coverage::DocCoverage;
+use rdoc::human::{count, number};
+use rdoc::search_items::SearchItem;
+use std::collections::BTreeMap;
+
+use crate::docs::{self, DocSource};
+
+/// List items with empty descriptions and optionally enforce a coverage threshold
+pub fn execute(
+    crates: &[String],
+    threshold: Option<f64>,
+    source: &DocSource,
+    quiet: bool,
+) -> Result<()> {
+    if !quiet {
+        println!("{} Auditing documentation coverage", "→".cyan().bold());
+    }
+
+    let search_index_path = docs::find_search_index(source, quiet, true)?;
+    let json_string = docs::load_search_index_json(&search_index_path)?;
+    let crate_entries = docs::crate_entries(&json_string)?;
+    let all_items = docs::filter_crates(docs::decode_items(&crate_entries, source), crates)?;
+
+    let mut by_crate: BTreeMap<&str, Vec<&SearchItem>> = BTreeMap::new();
+    for item in &all_items {
+        by_crate.entry(&item.crate_name).or_default().push(item);
+    }
+
+    for (crate_name, items) in &by_crate {
+        let coverage = DocCoverage::of(items.iter().copied());
+        println!(
+            "\n{} {:.1}% documented ({} of {} undocumented)",
+            crate_name.green().bold(),
+            coverage.percent(),
+            number(coverage.undocumented),
+            count(coverage.total, "item", "items")
+        );
+        for item in items.iter().filter(|item| item.empty_description) {
+            println!(
+                "  {}::{} ({})",
+                item.path.dimmed(),
+                item.name.cyan(),
+                item.item_type.to_string().yellow()
+            );
+        }
+    }
+
+    let coverage = DocCoverage::of(&all_items);
+    println!(
+        "\n{} Overall: {:.1}% documented ({} of {} undocumented)",
+        "ℹ".blue().bold(),
+        coverage.percent(),
+        number(coverage.undocumented),
+        count(coverage.total, "item", "items")
+    );
+
+    match threshold {
+        Some(threshold) if coverage.percent() < threshold => Err(eyre!(
+            "Documentation coverage {:.1}% is below the threshold of {}%",
+            coverage.percent(),
+            threshold
+        )),
+        _ => Ok(()),
+    }
+}
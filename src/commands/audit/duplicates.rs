@@ -0,0 +1,228 @@
+use color_eyre::{Result, eyre::bail};
+use colored::Colorize;
+use rdoc::human::count;
+use rdoc::resolve::disambiguated_path;
+use rdoc::search_index::ItemType;
+use rdoc::search_items::SearchItem;
+use rdoc::signature::FunctionSignature;
+use rdoc::similarity::similarity;
+use std::collections::BTreeMap;
+
+use crate::docs::{self, DocSource};
+
+/// A function defined in more than one of your crates with near-identical signatures
+struct Duplicate<'a> {
+    name: &'a str,
+    /// One definition per crate, each with its signature
+    definitions: Vec<(&'a SearchItem, &'a FunctionSignature)>,
+    /// The least alike any definition is to the first
+    similarity: f64,
+}
+
+/// List functions your crates each define under the same name with signatures
+/// at least `threshold` percent alike, likely copy-pasted utilities
+pub fn execute(crates: &[String], threshold: f64, source: &DocSource, quiet: bool) -> Result<()> {
+    if !quiet {
+        println!(
+            "{} Auditing functions duplicated across crates",
+            "→".cyan().bold()
+        );
+    }
+
+    let search_index_path = docs::find_search_index(source, quiet, true)?;
+    let json_string = docs::load_search_index_json(&search_index_path)?;
+    let crate_entries = docs::crate_entries(&json_string)?;
+    let all_items = docs::decode_items(&crate_entries, source);
+    let signatures = docs::decode_signatures(&crate_entries);
+
+    // Without --crate, "your crates" are the workspace members
+    let own_crates = if !crates.is_empty() {
+        crates.to_vec()
+    } else if source.is_cargo_project() {
+        docs::workspace_crates()?
+    } else {
+        bail!("Not in a cargo project; name the crates to compare with --crate")
+    };
+    if own_crates.len() < 2 {
+        bail!(
+            "Duplicates are found across crates, but there's only {}; name more with --crate",
+            own_crates.first().map_or("none", String::as_str)
+        );
+    }
+
+    // Each function where it's defined, not where it's re-exported
+    let functions: Vec<_> = all_items
+        .iter()
+        .filter(|item| {
+            item.item_type == ItemType::Function
+                && own_crates.contains(&item.crate_name)
+                && item.exact_path.split("::").next() == Some(item.crate_name.as_str())
+        })
+        .filter_map(|item| Some((item, docs::signature_of(&signatures, item)?)))
+        .collect();
+    let duplicates = find_duplicates(&functions, threshold / 100.0);
+
+    if duplicates.is_empty() {
+        println!(
+            "{} No functions duplicated across {}",
+            "✓".green().bold(),
+            own_crates.join(", ")
+        );
+        return Ok(());
+    }
+    for duplicate in &duplicates {
+        println!(
+            "\n{} in {} crates ({:.0}% alike):",
+            duplicate.name.cyan().bold(),
+            duplicate.definitions.len(),
+            duplicate.similarity * 100.0
+        );
+        for (item, signature) in &duplicate.definitions {
+            println!(
+                "  {} {}",
+                disambiguated_path(item),
+                signature.to_string().dimmed()
+            );
+        }
+    }
+    println!(
+        "\n{} {} defined in more than one crate",
+        "✗".yellow().bold(),
+        count(duplicates.len(), "function", "functions")
+    );
+    Ok(())
+}
+
+/// Functions among `functions` with the same name in more than one crate and
+/// signatures at least `threshold` (0.0 to 1.0) alike, by name.
+///
+/// Each definition is compared with the first one of its name; ones that
+/// aren't alike enough start their own group, so unrelated functions sharing
+/// a common name (`new`, `parse`) aren't reported together.
+fn find_duplicates<'a>(
+    functions: &[(&'a SearchItem, &'a FunctionSignature)],
+    threshold: f64,
+) -> Vec<Duplicate<'a>> {
+    let mut by_name: BTreeMap<&str, Vec<(&SearchItem, &FunctionSignature)>> = BTreeMap::new();
+    for &(item, signature) in functions {
+        by_name
+            .entry(&item.name)
+            .or_default()
+            .push((item, signature));
+    }
+
+    let mut duplicates = Vec::new();
+    for (name, definitions) in by_name {
+        let mut groups: Vec<Duplicate> = Vec::new();
+        for (item, signature) in definitions {
+            let group = groups
+                .iter_mut()
+                .find(|group| similarity(group.definitions[0].1, signature) >= threshold);
+            match group {
+                // Another definition in a crate already in the group isn't a
+                // duplicate across crates
+                Some(group)
+                    if group
+                        .definitions
+                        .iter()
+                        .any(|(other, _)| other.crate_name == item.crate_name) => {}
+                Some(group) => {
+                    group.similarity = group
+                        .similarity
+                        .min(similarity(group.definitions[0].1, signature));
+                    group.definitions.push((item, signature));
+                }
+                None => groups.push(Duplicate {
+                    name,
+                    definitions: vec![(item, signature)],
+                    similarity: 1.0,
+                }),
+            }
+        }
+        duplicates.extend(
+            groups
+                .into_iter()
+                .filter(|group| group.definitions.len() > 1),
+        );
+    }
+    duplicates
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rdoc::item_id::ItemId;
+    use rdoc::signature::{SignatureType, TypeKind};
+
+    fn function(crate_name: &str, name: &str) -> SearchItem {
+        SearchItem {
+            crate_name: crate_name.to_string(),
+            item_type: ItemType::Function,
+            name: name.to_string(),
+            normalized_name: name.to_lowercase(),
+            path: crate_name.to_string(),
+            exact_path: crate_name.to_string(),
+            id: ItemId::new(ItemId::LOCAL_SOURCE, crate_name, 0),
+            param_types: vec![],
+            impl_disambiguator: None,
+            bit_index: 1,
+            parent_index: None,
+            parents: Default::default(),
+            deprecated: false,
+            empty_description: false,
+            description_location: None,
+        }
+    }
+
+    fn signature(inputs: &[&str], output: &str) -> FunctionSignature {
+        let ty = |name: &str| SignatureType {
+            kind: TypeKind::Named {
+                name: name.to_string(),
+                item_type: ItemType::Struct,
+                path: "core".to_string(),
+                unbox: false,
+            },
+            generics: vec![],
+            bindings: vec![],
+        };
+        FunctionSignature {
+            inputs: inputs.iter().map(|name| ty(name)).collect(),
+            output: vec![ty(output)],
+            where_clause: vec![],
+        }
+    }
+
+    #[test]
+    fn test_find_duplicates() {
+        let items = [
+            function("app_a", "parse_config"),
+            function("app_b", "parse_config"),
+            function("app_c", "parse_config"),
+            function("app_a", "new"),
+            function("app_b", "new"),
+        ];
+        let signatures = [
+            signature(&["str"], "Config"),
+            signature(&["str"], "Config"),
+            signature(&["Path", "bool"], "Config"),
+            signature(&["u32"], "Client"),
+            signature(&["String"], "Server"),
+        ];
+        let functions: Vec<_> = items.iter().zip(&signatures).collect();
+
+        let duplicates = find_duplicates(&functions, 0.9);
+        assert_eq!(duplicates.len(), 1);
+        assert_eq!(duplicates[0].name, "parse_config");
+        let crates: Vec<&str> = duplicates[0]
+            .definitions
+            .iter()
+            .map(|(item, _)| item.crate_name.as_str())
+            .collect();
+        assert_eq!(crates, ["app_a", "app_b"]);
+        assert_eq!(duplicates[0].similarity, 1.0);
+
+        // A lower threshold takes in the third crate's version
+        let duplicates = find_duplicates(&functions, 0.4);
+        assert_eq!(duplicates[0].definitions.len(), 3);
+    }
+}
@@ -0,0 +1,32 @@
+// Audits over the documentation surface
+
+pub mod bloat;
+pub mod deprecated;
+pub mod docs;
+pub mod duplicates;
+
+/// Parse a `--threshold` percentage between 0 and 100.
+pub fn parse_percentage(value: &str) -> Result<f64, String> {
+    let percentage: f64 = value
+        .trim_end_matches('%')
+        .parse()
+        .map_err(|_| format!("\"{}\" is not a number", value))?;
+    if (0.0..=100.0).contains(&percentage) {
+        Ok(percentage)
+    } else {
+        Err(format!("{} is not between 0 and 100", percentage))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_percentage() {
+        assert_eq!(parse_percentage("80"), Ok(80.0));
+        assert_eq!(parse_percentage("92.5%"), Ok(92.5));
+        assert!(parse_percentage("101").is_err());
+        assert!(parse_percentage("most").is_err());
+    }
+}
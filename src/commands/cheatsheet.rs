@@ -0,0 +1,280 @@
+// A compact, printable overview of a crate or module's most important items
+
+use clap::ValueEnum;
+use color_eyre::{Result, eyre::Context};
+use colored::Colorize;
+use rdoc::cheatsheet::{Cheatsheet, Limits, cheatsheet};
+use rdoc::descriptions::{DescriptionStore, plain_text};
+use rdoc::human::count;
+use rdoc::resolve::resolve;
+use rdoc::search_items::SearchItem;
+use std::io::Write;
+use std::path::Path;
+
+use crate::docs::{self, DocSource};
+use crate::output;
+
+/// Output format for `rdoc cheatsheet`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum CheatsheetFormat {
+    /// Colored and indented, for reading in the terminal
+    Terminal,
+    /// Markdown, for printing or sharing
+    Markdown,
+}
+
+/// Print (or write to `output`) a cheat-sheet of the module or crate
+/// `module`: its key types with their constructors and most important
+/// methods, and its most important functions, each with its one-line
+/// description
+pub fn execute(
+    module: &str,
+    limits: Limits,
+    format: Option<CheatsheetFormat>,
+    output: Option<&Path>,
+    source: &DocSource,
+    quiet: bool,
+) -> Result<()> {
+    // The sheet on stdout must not be mixed with status messages
+    let quiet = quiet || output.is_none();
+    let format = format.unwrap_or(match output {
+        Some(_) => CheatsheetFormat::Markdown,
+        None => CheatsheetFormat::Terminal,
+    });
+    if !quiet {
+        println!(
+            "{} Making a cheat-sheet of {}",
+            "→".cyan().bold(),
+            module.green().bold()
+        );
+    }
+
+    let search_index_path = docs::find_search_index(source, quiet, true)?;
+    let json_string = docs::load_search_index_json(&search_index_path)?;
+    let crate_entries = docs::crate_entries(&json_string)?;
+    let all_items = docs::decode_items(&crate_entries, source);
+    let signatures = docs::decode_signatures(&crate_entries);
+    let mut store = DescriptionStore::new(search_index_path.parent().unwrap_or(Path::new(".")));
+
+    let module = module.trim_end_matches("::");
+    // Crate roots aren't items in the index, so they're found by name
+    let is_crate = !module.contains("::") && crate_entries.iter().any(|entry| entry.name == module);
+    let module_path = if is_crate {
+        module.to_string()
+    } else {
+        let item = resolve(&all_items, &format!("mod@{}", module))?;
+        format!("{}::{}", item.path, item.name)
+    };
+
+    let sheet = cheatsheet(
+        &all_items,
+        &module_path,
+        |item| docs::signature_of(&signatures, item),
+        |item| store.get(item).map_or(0, |description| description.len()),
+        limits,
+    );
+    let entry = |item: &SearchItem| Entry {
+        name: match docs::signature_of(&signatures, item) {
+            Some(signature) => format!("{}{}", item.name, signature),
+            None => item.name.clone(),
+        },
+        description: store.get(item).map(plain_text),
+    };
+    let text = match format {
+        CheatsheetFormat::Terminal => terminal(&module_path, &sheet, entry),
+        CheatsheetFormat::Markdown => markdown(&module_path, &sheet, entry),
+    };
+
+    let Some(path) = output else {
+        let mut out = std::io::stdout().lock();
+        return out
+            .write_all(text.as_bytes())
+            .and_then(|()| out.flush())
+            .wrap_err("Failed to write the cheat-sheet");
+    };
+    output::write_atomic(path, text)
+        .wrap_err_with(|| format!("Failed to write the cheat-sheet to {}", path.display()))?;
+    if !quiet {
+        println!(
+            "{} Wrote a cheat-sheet of {} to {}",
+            "✓".green().bold(),
+            count(sheet.types.len(), "type", "types"),
+            path.display().to_string().cyan()
+        );
+    }
+    Ok(())
+}
+
+/// How one item is shown: its name, with its signature for functions and
+/// methods, and its description
+struct Entry {
+    name: String,
+    description: Option<String>,
+}
+
+/// The sheet as a colored, indented listing.
+fn terminal(
+    module_path: &str,
+    sheet: &Cheatsheet,
+    mut entry: impl FnMut(&SearchItem) -> Entry,
+) -> String {
+    let mut text = format!("\n{} {}\n", module_path.cyan().bold(), "cheat-sheet".bold());
+    let line = |text: &mut String, indent: &str, entry: Entry| {
+        text.push_str(&format!("{}{}", indent, entry.name.cyan()));
+        if let Some(description) = entry.description {
+            text.push_str(&format!("  {}", description.dimmed()));
+        }
+        text.push('\n');
+    };
+    for ty in &sheet.types {
+        text.push('\n');
+        let mut heading = entry(ty.item);
+        heading.name = format!("{} ({})", ty.item.name, ty.item.item_type);
+        line(&mut text, "  ", heading);
+        for (label, items) in [("create", &ty.constructors), ("use", &ty.methods)] {
+            if items.is_empty() {
+                continue;
+            }
+            text.push_str(&format!("    {}\n", label.bold()));
+            for item in items {
+                line(&mut text, "      ", entry(item));
+            }
+        }
+    }
+    if !sheet.functions.is_empty() {
+        text.push_str(&format!("\n  {}\n", "Functions".bold()));
+        for item in &sheet.functions {
+            line(&mut text, "    ", entry(item));
+        }
+    }
+    if sheet.types.is_empty() && sheet.functions.is_empty() {
+        text.push_str(&format!(
+            "\n  {}\n",
+            "No public types or functions".dimmed()
+        ));
+    }
+    text
+}
+
+/// The sheet as Markdown: a section per type, listing how to create and use
+/// it, then the functions.
+fn markdown(
+    module_path: &str,
+    sheet: &Cheatsheet,
+    mut entry: impl FnMut(&SearchItem) -> Entry,
+) -> String {
+    let mut text = format!("# `{}` cheat-sheet\n", module_path);
+    let bullet = |text: &mut String, entry: Entry| {
+        text.push_str(&format!("- `{}`", entry.name));
+        if let Some(description) = entry.description {
+            text.push_str(&format!(": {}", description));
+        }
+        text.push('\n');
+    };
+    if !sheet.types.is_empty() {
+        text.push_str("\n## Types\n");
+    }
+    for ty in &sheet.types {
+        text.push_str(&format!(
+            "\n### `{}` ({})\n",
+            ty.item.name, ty.item.item_type
+        ));
+        if let Some(description) = entry(ty.item).description {
+            text.push_str(&format!("\n{}\n", description));
+        }
+        for (label, items) in [("Create", &ty.constructors), ("Use", &ty.methods)] {
+            if items.is_empty() {
+                continue;
+            }
+            text.push_str(&format!("\n{}:\n\n", label));
+            for item in items {
+                bullet(&mut text, entry(item));
+            }
+        }
+    }
+    if !sheet.functions.is_empty() {
+        text.push_str("\n## Functions\n\n");
+        for item in &sheet.functions {
+            bullet(&mut text, entry(item));
+        }
+    }
+    if sheet.types.is_empty() && sheet.functions.is_empty() {
+        text.push_str("\nNo public types or functions.\n");
+    }
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rdoc::cheatsheet::TypeEntry;
+    use rdoc::item_id::ItemId;
+    use rdoc::search_index::ItemType;
+    use rdoc::search_items::{Parent, Parents};
+
+    fn item(name: &str, item_type: ItemType, parent_name: Option<&str>) -> SearchItem {
+        SearchItem {
+            crate_name: "geo".to_string(),
+            item_type,
+            name: name.to_string(),
+            normalized_name: name.to_lowercase(),
+            path: "geo".to_string(),
+            exact_path: "geo".to_string(),
+            id: ItemId::new(ItemId::LOCAL_SOURCE, "geo", 0),
+            param_types: vec![],
+            impl_disambiguator: None,
+            bit_index: 1,
+            parent_index: parent_name.map(|_| 0),
+            parents: Parents::new(
+                parent_name
+                    .map(|name| Parent {
+                        name: name.to_string(),
+                        item_type: ItemType::Struct,
+                    })
+                    .into_iter()
+                    .collect(),
+            ),
+            deprecated: false,
+            empty_description: false,
+            description_location: None,
+        }
+    }
+
+    #[test]
+    fn test_markdown() {
+        let point = item("Point", ItemType::Struct, None);
+        let new = item("new", ItemType::Method, Some("Point"));
+        let norm = item("norm", ItemType::Method, Some("Point"));
+        let parse = item("parse_point", ItemType::Function, None);
+        let sheet = Cheatsheet {
+            types: vec![TypeEntry {
+                item: &point,
+                constructors: vec![&new],
+                methods: vec![&norm],
+            }],
+            functions: vec![&parse],
+        };
+        let entry = |item: &SearchItem| Entry {
+            name: match item.item_type {
+                ItemType::Struct => item.name.clone(),
+                _ => format!("{}()", item.name),
+            },
+            description: (item.name != "norm").then(|| format!("About {}.", item.name)),
+        };
+        assert_eq!(
+            markdown("geo", &sheet, entry),
+            "# `geo` cheat-sheet\n\n## Types\n\n### `Point` (struct)\n\nAbout Point.\n\
+             \nCreate:\n\n- `new()`: About new.\n\nUse:\n\n- `norm()`\n\
+             \n## Functions\n\n- `parse_point()`: About parse_point.\n"
+        );
+
+        let empty = Cheatsheet {
+            types: vec![],
+            functions: vec![],
+        };
+        assert_eq!(
+            markdown("geo::nothing", &empty, entry),
+            "# `geo::nothing` cheat-sheet\n\nNo public types or functions.\n"
+        );
+    }
+}
@@ -0,0 +1,115 @@
+use color_eyre::{Result, eyre::Context, eyre::eyre};
+use colored::Colorize;
+use rdoc::doc_links::{LinkStatus, check_link, extract_doc_links, module_path};
+use rdoc::human::{count, number};
+use std::path::{Path, PathBuf};
+use tracing::debug;
+
+use crate::docs::{self, DocSource};
+
+/// Check that the intra-doc links in a crate's doc comments resolve against the index
+pub fn execute(
+    src_dir: &Path,
+    crate_name: Option<&str>,
+    source: &DocSource,
+    quiet: bool,
+) -> Result<()> {
+    let crate_name = match (crate_name, source) {
+        (Some(name), _) => name.replace('-', "_"),
+        (None, _) if source.is_cargo_project() => docs::workspace_crates()?
+            .into_iter()
+            .next()
+            .ok_or_else(|| eyre!("No library crate in this workspace; pass --crate"))?,
+        (None, _) => {
+            return Err(eyre!(
+                "Pass --crate to check links against docs outside a cargo project"
+            ));
+        }
+    };
+
+    if !quiet {
+        println!(
+            "{} Checking doc links in {} against {}",
+            "→".cyan().bold(),
+            src_dir.display(),
+            crate_name.green().bold()
+        );
+    }
+
+    let search_index_path = docs::find_search_index(source, quiet, true)?;
+    let json_string = docs::load_search_index_json(&search_index_path)?;
+    let crate_entries = docs::crate_entries(&json_string)?;
+    let all_items = docs::decode_items(&crate_entries, source);
+
+    let mut files = Vec::new();
+    collect_rust_files(src_dir, &mut files)?;
+    files.sort();
+
+    let (mut checked, mut external, mut broken) = (0, 0, 0);
+    for file in &files {
+        let source_code = std::fs::read_to_string(file)
+            .wrap_err_with(|| format!("Failed to read {}", file.display()))?;
+        let relative = file.strip_prefix(src_dir).unwrap_or(file);
+        let module = module_path(&crate_name, relative);
+
+        for link in extract_doc_links(&source_code) {
+            checked += 1;
+            match check_link(&all_items, &link.target, &module) {
+                LinkStatus::Resolved => {}
+                LinkStatus::External => {
+                    external += 1;
+                    debug!(
+                        "{}:{}: not checking {}",
+                        file.display(),
+                        link.line,
+                        link.target
+                    );
+                }
+                LinkStatus::Broken => {
+                    broken += 1;
+                    println!(
+                        "{}:{}: broken link to {}",
+                        file.display(),
+                        link.line,
+                        link.target.red()
+                    );
+                }
+            }
+        }
+    }
+
+    if !quiet {
+        println!(
+            "\n{} Checked {} in {} ({} outside the index skipped)",
+            "ℹ".blue().bold(),
+            count(checked, "link", "links"),
+            count(files.len(), "file", "files"),
+            number(external)
+        );
+    }
+
+    if broken > 0 {
+        return Err(eyre!(
+            "{}",
+            count(broken, "broken doc link", "broken doc links")
+        ));
+    }
+    if !quiet {
+        println!("{} All doc links resolve", "✓".green().bold());
+    }
+    Ok(())
+}
+
+fn collect_rust_files(dir: &Path, files: &mut Vec<PathBuf>) -> Result<()> {
+    let entries =
+        std::fs::read_dir(dir).wrap_err_with(|| format!("Failed to read {}", dir.display()))?;
+    for entry in entries {
+        let path = entry?.path();
+        if path.is_dir() {
+            collect_rust_files(&path, files)?;
+        } else if path.extension().is_some_and(|extension| extension == "rs") {
+            files.push(path);
+        }
+    }
+    Ok(())
+}
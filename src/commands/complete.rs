@@ -0,0 +1,87 @@
+// Completing item names from a cached name index, for shells and editors
+
+use color_eyre::Result;
+use rdoc::name_index::NameIndex;
+use std::hash::{DefaultHasher, Hash, Hasher};
+use std::path::Path;
+use tracing::{debug, warn};
+
+use crate::cache;
+use crate::config::IGNORE_FILE;
+use crate::docs::{self, DocSource};
+use crate::output;
+
+/// Print up to `limit` item names starting with `prefix` (allowing typos with
+/// `fuzzy`), one per line.
+///
+/// Names come from an FST cached next to rdoc's other caches, rebuilt only
+/// when the search index changes, so this answers without decoding the index
+/// and is quick enough to run on every Tab. It never runs cargo doc.
+pub fn execute(
+    prefix: &str,
+    fuzzy: bool,
+    limit: usize,
+    source: &DocSource,
+    quiet: bool,
+) -> Result<()> {
+    let search_index_path = docs::find_search_index(source, quiet, false)?;
+    let index = load_name_index(&search_index_path, source)?;
+    let completions = if fuzzy {
+        index.fuzzy_prefix(prefix, limit)
+    } else {
+        index.prefix(prefix, limit)
+    };
+    for completion in completions {
+        println!("{}", completion.name);
+    }
+    Ok(())
+}
+
+/// The name index of the search index at `search_index_path`: the cached one
+/// if it's up to date, otherwise built from the decoded index and cached.
+pub fn load_name_index(search_index_path: &Path, source: &DocSource) -> Result<NameIndex> {
+    let stamp = index_stamp(search_index_path)?;
+    let cache_path = cache::name_index_path(search_index_path)?;
+    if let Ok(bytes) = std::fs::read(&cache_path) {
+        match NameIndex::from_bytes(bytes) {
+            Ok(index) if index.stamp() == Some(stamp) => {
+                debug!("using the name index at {}", cache_path.display());
+                return Ok(index);
+            }
+            Ok(_) => debug!("{} is out of date", cache_path.display()),
+            Err(err) => debug!("ignoring {}: {}", cache_path.display(), err),
+        }
+    }
+
+    let json_string = docs::load_search_index_json(search_index_path)?;
+    let crate_entries = docs::crate_entries(&json_string)?;
+    let items = docs::decode_items(&crate_entries, source);
+    let index = NameIndex::build(&items, stamp)?;
+    // Completions work without the cache, just slower next time
+    let write = || -> Result<()> {
+        if let Some(dir) = cache_path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        output::write_atomic(&cache_path, index.as_bytes())
+    };
+    match write() {
+        Ok(()) => debug!("cached the name index at {}", cache_path.display()),
+        Err(err) => warn!("could not cache the name index: {:#}", err),
+    }
+    Ok(index)
+}
+
+/// Identifies a version of the search index by its size and modification
+/// time, which change whenever rustdoc rewrites it, and likewise the
+/// `.rdocignore` that decides which of its names are left out.
+fn index_stamp(search_index_path: &Path) -> Result<u64> {
+    let metadata = std::fs::metadata(search_index_path)?;
+    let mut hasher = DefaultHasher::new();
+    metadata.len().hash(&mut hasher);
+    metadata.modified()?.hash(&mut hasher);
+    if let Ok(ignore) = std::fs::metadata(IGNORE_FILE) {
+        ignore.len().hash(&mut hasher);
+        ignore.modified()?.hash(&mut hasher);
+    }
+    Ok(hasher.finish())
+}
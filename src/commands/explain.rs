@@ -0,0 +1,166 @@
+use color_eyre::{Result, eyre::Context, eyre::eyre};
+use colored::Colorize;
+use rdoc::explain::{ItemExplanation, explain_item};
+use rdoc::human::count;
+use rdoc::search_index::CrateData;
+
+use crate::docs::{self, DocSource};
+
+/// Show how a single item in the search index was decoded
+pub fn execute(crate_name: &str, index: usize, source: &DocSource, quiet: bool) -> Result<()> {
+    if !quiet {
+        println!(
+            "{} Explaining item {} of crate {}",
+            "→".cyan().bold(),
+            index.to_string().green().bold(),
+            crate_name.green().bold()
+        );
+    }
+
+    let search_index_path = docs::find_search_index(source, quiet, false)?;
+    let json_string = docs::load_search_index_json(&search_index_path)?;
+    let crate_entries = docs::crate_entries(&json_string)?;
+
+    let entry = crate_entries
+        .iter()
+        .find(|entry| entry.name == crate_name)
+        .ok_or_else(|| eyre!("Crate \"{}\" is not in the search index", crate_name))?;
+    let data = entry
+        .data()
+        .wrap_err_with(|| format!("Failed to decode data for crate {}", crate_name))?;
+
+    let explanation = explain_item(crate_name, &data, index).ok_or_else(|| {
+        eyre!(
+            "Item index {} is out of range: crate {} has {}",
+            index,
+            crate_name,
+            count(data.item_count(), "item", "items")
+        )
+    })?;
+
+    print_explanation(&explanation, &data);
+
+    Ok(())
+}
+
+fn print_explanation(explanation: &ItemExplanation, data: &CrateData) {
+    let item = &explanation.item;
+    let index = item.id.index;
+    let label = |name: &str| format!("  {:<12}", name).bold();
+
+    println!();
+    println!(
+        "{}{} ({}) in {}",
+        label("Item"),
+        item.name.cyan(),
+        item.item_type,
+        item.path.dimmed()
+    );
+
+    println!(
+        "{}t[{}] = '{}' → {} - 65 = {} → {}",
+        label("Type"),
+        index,
+        explanation.type_char,
+        explanation.type_char as u32,
+        explanation.type_char as u32 - 65,
+        item.item_type
+    );
+
+    match explanation.name_source {
+        Some(source) if source == index => {
+            println!("{}n[{}] = \"{}\"", label("Name"), index, item.name)
+        }
+        Some(source) => println!(
+            "{}n[{}] = \"\" → reused from n[{}] = \"{}\"",
+            label("Name"),
+            index,
+            source,
+            item.name
+        ),
+        None => println!(
+            "{}n[{}] = \"\" → no earlier name to reuse",
+            label("Name"),
+            index
+        ),
+    }
+
+    match explanation.path_entry {
+        Some(position) => {
+            let entry = &data.paths[position];
+            let how = if entry.index == index {
+                "set by".to_string()
+            } else {
+                format!("carried forward from item {} via", entry.index)
+            };
+            println!(
+                "{}{} q[{}] = [{}, \"{}\"]",
+                label("Path"),
+                how,
+                position,
+                entry.index,
+                entry.path
+            );
+        }
+        None => println!("{}no q entry at or before this item", label("Path")),
+    }
+
+    match explanation.reexport_entry {
+        Some(position) => {
+            let reexport = &data.reexports[position];
+            println!(
+                "{}r[{}] = [{}, {}] → \"{}\"",
+                label("Exact path"),
+                position,
+                reexport.item_index,
+                reexport.path_index,
+                item.exact_path
+            );
+        }
+        None => println!("{}same as path (no r entry)", label("Exact path")),
+    }
+
+    let parent = match (explanation.parent_value, item.parent_index) {
+        (None, _) => "i is exhausted → no parent".to_string(),
+        (Some(_), None) => "→ no parent".to_string(),
+        (Some(_), Some(parent)) => match data.parent_items.get(parent) {
+            Some(parent_item) => format!(
+                "→ p[{}] = {} ({})",
+                parent, parent_item.name, parent_item.ty
+            ),
+            None => format!("→ p[{}] is out of range", parent),
+        },
+    };
+    // Single characters in '0'..='?' refer back to a recently decoded value
+    let backref = match explanation.parent_bytes.as_bytes() {
+        [c @ b'0'..=b'?'] => format!(" (backreference #{})", c - b'0'),
+        _ => String::new(),
+    };
+    println!(
+        "{}i bytes \"{}\"{} = {} {}",
+        label("Parent"),
+        explanation.parent_bytes,
+        backref,
+        explanation
+            .parent_value
+            .map_or("-".to_string(), |value| value.to_string()),
+        parent
+    );
+
+    let bit = |set: Option<bool>| match set {
+        Some(true) => "set".yellow(),
+        Some(false) => "not set".normal(),
+        None => "bitmap failed to decode".red(),
+    };
+    println!("{}{}", label("Bit index"), item.bit_index);
+    println!(
+        "{}c bit {}",
+        label("Deprecated"),
+        bit(explanation.deprecated)
+    );
+    println!(
+        "{}e bit {}",
+        label("Empty desc"),
+        bit(explanation.empty_description)
+    );
+}
@@ -0,0 +1,83 @@
+use color_eyre::{Result, eyre::Context};
+use colored::Colorize;
+use rdoc::human::count;
+use rdoc::pages::{declaration, item_page};
+use rdoc::public_api::api_line;
+use std::collections::BTreeSet;
+use std::io::Write;
+use std::path::Path;
+use tracing::info;
+
+use crate::docs::{self, DocSource, Pages};
+use crate::output;
+use crate::progress;
+
+/// Write the public API one item per line, sorted, as cargo-public-api
+/// prints it, for diffing between doc builds
+pub fn execute(
+    crates: &[String],
+    signatures: bool,
+    output: Option<&Path>,
+    source: &DocSource,
+    quiet: bool,
+) -> Result<()> {
+    // The listing on stdout must not be mixed with status messages
+    let quiet = quiet || output.is_none();
+    if !quiet {
+        println!("{} Exporting the public API", "→".cyan().bold());
+    }
+
+    let search_index_path = docs::find_search_index(source, quiet, true)?;
+    let json_string = docs::load_search_index_json(&search_index_path)?;
+    let crate_entries = docs::crate_entries(&json_string)?;
+    let items = docs::filter_crates(docs::decode_items(&crate_entries, source), crates)?;
+
+    // Sorted and without duplicates, so the same API always lists the same;
+    // re-exports are lines of their own, at their own paths
+    let mut lines = BTreeSet::new();
+    let bar = progress::bar(items.len() as u64, "Reading declarations");
+    // Pages are kept while a crate's items are read, since a type's members
+    // share its page, then let go
+    for crate_items in items.chunk_by(|a, b| a.crate_name == b.crate_name) {
+        let mut pages = Pages::new(source, &search_index_path);
+        bar.set_message(crate_items[0].crate_name.clone());
+        for item in crate_items {
+            let declaration = if signatures {
+                let anchor = item_page(item).and_then(|page| page.anchor);
+                pages
+                    .item_html(item)?
+                    .and_then(|html| declaration(html, anchor.as_deref()))
+            } else {
+                None
+            };
+            lines.extend(api_line(item, declaration.as_deref()));
+            bar.inc(1);
+        }
+    }
+    bar.finish_and_clear();
+    info!("listed {} lines for {} items", lines.len(), items.len());
+
+    let mut listing = String::new();
+    for line in &lines {
+        listing.push_str(line);
+        listing.push('\n');
+    }
+    let Some(path) = output else {
+        let mut out = std::io::stdout().lock();
+        return out
+            .write_all(listing.as_bytes())
+            .and_then(|()| out.flush())
+            .wrap_err("Failed to write the API listing");
+    };
+    output::write_atomic(path, listing)
+        .wrap_err_with(|| format!("Failed to write the API listing to {}", path.display()))?;
+    if !quiet {
+        println!(
+            "{} Wrote {} to {}",
+            "✓".green().bold(),
+            count(lines.len(), "public item", "public items"),
+            path.display().to_string().cyan()
+        );
+    }
+    Ok(())
+}
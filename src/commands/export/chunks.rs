@@ -0,0 +1,119 @@
+use clap::ValueEnum;
+use color_eyre::{Result, eyre::Context};
+use colored::Colorize;
+use rdoc::chunks::{DocChunk, fit_text};
+use rdoc::descriptions::{DescriptionStore, plain_text};
+use rdoc::format::ResultRecord;
+use rdoc::hidden::HiddenItems;
+use rdoc::human::count;
+use rdoc::pages::{declaration, documentation, item_page};
+use rdoc::resolve::disambiguated_path;
+use std::io::Write;
+use std::path::Path;
+use tracing::info;
+
+use crate::docs::{self, DocSource, Pages};
+use crate::output;
+use crate::progress;
+
+/// Output format for `rdoc export chunks`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum ChunkFormat {
+    /// One JSON record per line
+    Jsonl,
+}
+
+/// Write a record of each item's declaration and documentation, one JSON
+/// object per line, for feeding the docs to embedding pipelines
+pub fn execute(
+    crates: &[String],
+    max_chars: usize,
+    output: Option<&Path>,
+    source: &DocSource,
+    quiet: bool,
+) -> Result<()> {
+    // Records on stdout must not be mixed with status messages
+    let quiet = quiet || output.is_none();
+    if !quiet {
+        println!("{} Exporting documentation chunks", "→".cyan().bold());
+    }
+
+    let search_index_path = docs::find_search_index(source, quiet, true)?;
+    let json_string = docs::load_search_index_json(&search_index_path)?;
+    let crate_entries = docs::crate_entries(&json_string)?;
+    let hidden = HiddenItems::default();
+    let items: Vec<_> = docs::filter_crates(docs::decode_items(&crate_entries, source), crates)?
+        .into_iter()
+        .filter(|item| !hidden.hides(item))
+        .collect();
+    let links = docs::site_links(source);
+    let mut store = DescriptionStore::new(search_index_path.parent().unwrap_or(Path::new(".")));
+
+    let mut records = Vec::new();
+    let mut truncated = 0;
+    let bar = progress::bar(items.len() as u64, "Reading pages");
+    // Pages are kept while a crate's items are read, since a type's members
+    // share its page, then let go
+    for crate_items in items.chunk_by(|a, b| a.crate_name == b.crate_name) {
+        let mut pages = Pages::new(source, &search_index_path);
+        bar.set_message(crate_items[0].crate_name.clone());
+        for item in crate_items {
+            let anchor = item_page(item).and_then(|page| page.anchor);
+            let html = pages.item_html(item)?;
+            let signature = html.and_then(|html| declaration(html, anchor.as_deref()));
+            let text = match html.and_then(|html| documentation(html, anchor.as_deref())) {
+                Some(text) => text,
+                None => store.get(item).map(plain_text).unwrap_or_default(),
+            };
+            let (text, cut) = fit_text(&text, max_chars);
+            truncated += usize::from(cut);
+            let chunk = DocChunk {
+                item: ResultRecord::new(item, &links),
+                qualified_path: disambiguated_path(item),
+                signature,
+                text: text.to_string(),
+                truncated: cut,
+            };
+            serde_json::to_writer(&mut records, &chunk)?;
+            records.push(b'\n');
+            bar.inc(1);
+        }
+    }
+    bar.finish_and_clear();
+    info!(
+        "exported {} items, {} cut to {} characters",
+        items.len(),
+        truncated,
+        max_chars
+    );
+
+    let Some(path) = output else {
+        let mut out = std::io::stdout().lock();
+        return out
+            .write_all(&records)
+            .and_then(|()| out.flush())
+            .wrap_err("Failed to write chunks");
+    };
+    output::write_atomic(path, records)
+        .wrap_err_with(|| format!("Failed to write chunks to {}", path.display()))?;
+    if !quiet {
+        println!(
+            "{} Wrote {} to {}",
+            "✓".green().bold(),
+            count(items.len(), "chunk", "chunks"),
+            path.display().to_string().cyan()
+        );
+        if truncated > 0 {
+            println!(
+                "  {}",
+                format!(
+                    "{} cut to {} characters; raise --max-chars to keep more",
+                    count(truncated, "was", "were"),
+                    max_chars
+                )
+                .dimmed()
+            );
+        }
+    }
+    Ok(())
+}
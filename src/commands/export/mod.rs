@@ -0,0 +1,4 @@
+// Exports of the documentation for other tools
+
+pub mod api;
+pub mod chunks;
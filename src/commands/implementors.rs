@@ -0,0 +1,78 @@
+use color_eyre::{Result, eyre::bail};
+use colored::Colorize;
+use rdoc::human::count;
+use rdoc::implementors::Implementor;
+use rdoc::resolve::{disambiguated_path, resolve};
+use rdoc::search_index::ItemType;
+use std::collections::BTreeMap;
+
+use crate::docs::{self, DocSource, Pages};
+
+/// List the types implementing a trait, grouped by the crate they're in
+pub fn execute(path: &str, source: &DocSource, quiet: bool) -> Result<()> {
+    if !quiet {
+        println!(
+            "{} Finding implementors of {}",
+            "→".cyan().bold(),
+            path.green().bold()
+        );
+    }
+
+    let search_index_path = docs::find_search_index(source, quiet, true)?;
+    let json_string = docs::load_search_index_json(&search_index_path)?;
+    let crate_entries = docs::crate_entries(&json_string)?;
+    let all_items = docs::decode_items(&crate_entries, source);
+
+    // A trait often shares its path with a derive macro; only the trait is wanted
+    let query = if path.contains('@') {
+        path.to_string()
+    } else {
+        format!("trait@{}", path)
+    };
+    let item = resolve(&all_items, &query)?;
+    if item.item_type != ItemType::Trait {
+        bail!(
+            "{} is a {}, not a trait",
+            disambiguated_path(item),
+            item.item_type
+        );
+    }
+
+    let implementors = Pages::new(source, &search_index_path).implementors(item)?;
+
+    let mut by_crate: BTreeMap<&str, Vec<&Implementor>> = BTreeMap::new();
+    for implementor in &implementors {
+        by_crate
+            .entry(&implementor.crate_name)
+            .or_default()
+            .push(implementor);
+    }
+
+    if implementors.is_empty() {
+        println!(
+            "{} No implementors of {} found",
+            "✗".red().bold(),
+            disambiguated_path(item)
+        );
+        return Ok(());
+    }
+    println!(
+        "\n{} {} of {} in {}:\n",
+        "✓".green().bold(),
+        count(implementors.len(), "implementation", "implementations"),
+        disambiguated_path(item).cyan(),
+        count(by_crate.len(), "crate", "crates")
+    );
+    for (crate_name, implementors) in by_crate {
+        println!("  {}", crate_name.yellow());
+        for implementor in implementors {
+            if implementor.synthetic {
+                println!("    {} {}", implementor.declaration, "(auto)".dimmed());
+            } else {
+                println!("    {}", implementor.declaration);
+            }
+        }
+    }
+
+    Ok(())
+}
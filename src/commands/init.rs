@@ -0,0 +1,294 @@
+// First-run setup: a starter config and a check of what rdoc needs
+
+use color_eyre::{Result, eyre::bail};
+use colored::Colorize;
+use rdoc::human::count;
+use rdoc::resources;
+use rdoc::search_index::{extract_json_string, parse_search_index_lazy};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use crate::config::PROJECT_CONFIG_FILE;
+use crate::doc_root;
+use crate::docs;
+use crate::output;
+use crate::remote::SEARCH_INDEX_FILE;
+
+/// What `init` found out about the docs in a doc root
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum DocsCheck {
+    /// A search index rdoc reads, with this many crates
+    Supported { crates: usize },
+    /// A search index rdoc can't read, and why
+    Unsupported(String),
+    /// No docs at all
+    Missing,
+}
+
+/// Detect the project layout, write a starter `.rdoc.toml` (unless one exists
+/// and `force` isn't set), and check the toolchain and docs, running
+/// `cargo doc` first with `generate`. Fails if a check does.
+pub fn execute(
+    doc_dir: Option<&Path>,
+    doc_roots: &[PathBuf],
+    force: bool,
+    generate: bool,
+) -> Result<()> {
+    let in_project = Path::new("Cargo.toml").exists();
+    let mut failures = check_project(in_project, doc_dir);
+    failures += check_toolchain(in_project);
+    let (search_index, docs_failures) = check_doc_roots(doc_dir, doc_roots, generate, in_project)?;
+    failures += docs_failures;
+    let doc_root = search_index
+        .as_deref()
+        .map(|search_index| search_index.parent().unwrap_or(Path::new(".")));
+    write_config(doc_dir, doc_root, force)?;
+
+    if failures > 0 {
+        bail!("{} failed", count(failures, "check", "checks"));
+    }
+    println!("\n{} Ready: try `rdoc scan <name>`", "✓".green().bold());
+    Ok(())
+}
+
+/// Report the project's library crates, returning how many checks failed.
+fn check_project(in_project: bool, doc_dir: Option<&Path>) -> usize {
+    println!("{}", "Project".bold());
+    if in_project {
+        match docs::workspace_crates() {
+            Ok(crates) if crates.is_empty() => pass("Cargo project without a library crate"),
+            Ok(crates) => pass(&format!("Cargo project with {}", crates.join(", "))),
+            Err(err) => {
+                fail(&format!("cargo metadata failed: {:#}", err));
+                return 1;
+            }
+        }
+    } else if doc_dir.is_some() {
+        note("No Cargo.toml; using the docs in --doc-dir");
+    } else {
+        note("No Cargo.toml; rdoc will look for rustdoc output in ./doc, or pass --doc-dir");
+    }
+    0
+}
+
+/// Report the versions of rustc and cargo, returning how many checks failed.
+fn check_toolchain(in_project: bool) -> usize {
+    println!("\n{}", "Toolchain".bold());
+    let mut failures = 0;
+    let rustc = std::env::var_os("RUSTC").unwrap_or_else(|| "rustc".into());
+    for (name, mut command, needed) in [
+        ("rustc", Command::new(rustc), true),
+        ("cargo", docs::cargo_command(), in_project),
+    ] {
+        match tool_version(&mut command) {
+            Some(version) => pass(&version),
+            None if needed => {
+                failures += 1;
+                fail(&format!(
+                    "{} --version failed; is {} installed?",
+                    name, name
+                ));
+            }
+            None => note(&format!(
+                "{} not found (only needed for cargo projects)",
+                name
+            )),
+        }
+    }
+    failures
+}
+
+/// Look for docs rdoc can read, running `cargo doc` first with `generate`,
+/// and report them. Returns the search index found, and how many checks
+/// failed.
+fn check_doc_roots(
+    doc_dir: Option<&Path>,
+    doc_roots: &[PathBuf],
+    generate: bool,
+    in_project: bool,
+) -> Result<(Option<PathBuf>, usize)> {
+    println!("\n{}", "Docs".bold());
+    let roots = match doc_dir {
+        Some(doc_dir) => vec![doc_dir.to_path_buf()],
+        None => doc_root::candidates(None, doc_roots),
+    };
+    let mut found = doc_root::probe(&roots);
+    if found.is_none() && generate && in_project && doc_dir.is_none() {
+        docs::generate_local_docs(false)?;
+        found = doc_root::probe(&roots);
+    }
+    let doc_root = found
+        .as_deref()
+        .map(|search_index| search_index.parent().unwrap_or(Path::new(".")));
+    let check = match doc_root {
+        Some(doc_root) => check_docs(doc_root),
+        None => match docs::unsupported_layout(&roots) {
+            Some(reason) => {
+                DocsCheck::Unsupported(format!("{}; build docs with an older toolchain", reason))
+            }
+            None => DocsCheck::Missing,
+        },
+    };
+    let mut failures = 0;
+    match &check {
+        DocsCheck::Supported { crates } => {
+            let doc_root = doc_root.unwrap_or(Path::new("."));
+            pass(&format!(
+                "{} documented in {}{}",
+                count(*crates, "crate", "crates"),
+                doc_root.display(),
+                docs::detect_rustdoc_version(doc_root)
+                    .map(|version| format!(" by rustdoc {}", version))
+                    .unwrap_or_default()
+            ))
+        }
+        DocsCheck::Unsupported(reason) => {
+            failures += 1;
+            fail(reason);
+        }
+        DocsCheck::Missing if in_project => note(&format!(
+            "No docs yet in {}; rdoc runs cargo doc when first needed, or use init --generate",
+            display_roots(&roots)
+        )),
+        DocsCheck::Missing => {
+            failures += 1;
+            fail(&format!("No docs in {}", display_roots(&roots)));
+        }
+    }
+    Ok((found, failures))
+}
+
+/// Write a starter `.rdoc.toml`, listing `doc_root` if cargo doesn't use it,
+/// unless one exists and `force` isn't set.
+fn write_config(doc_dir: Option<&Path>, doc_root: Option<&Path>, force: bool) -> Result<()> {
+    println!("\n{}", "Config".bold());
+    let config_path = Path::new(PROJECT_CONFIG_FILE);
+    if config_path.exists() && !force {
+        note(&format!(
+            "{} already exists; init --force replaces it",
+            PROJECT_CONFIG_FILE
+        ));
+        return Ok(());
+    }
+    // Only roots cargo doesn't use need listing
+    let custom_root =
+        doc_root.filter(|root| doc_dir.is_none() && *root != doc_root::cargo_doc_root(None));
+    output::write_atomic(config_path, starter_config(custom_root))?;
+    pass(&format!("Wrote {}", PROJECT_CONFIG_FILE));
+    Ok(())
+}
+
+/// Whether the search index in `doc_root` is one rdoc can read.
+fn check_docs(doc_root: &Path) -> DocsCheck {
+    let path =
+        resources::search_index_file(doc_root).unwrap_or_else(|| doc_root.join(SEARCH_INDEX_FILE));
+    let content = match std::fs::read_to_string(&path) {
+        Ok(content) => content,
+        Err(err) => {
+            return DocsCheck::Unsupported(format!("Can't read {}: {}", path.display(), err));
+        }
+    };
+    let parsed = extract_json_string(&content)
+        .map_err(|err| err.to_string())
+        .and_then(|json| {
+            parse_search_index_lazy(&json)
+                .map(|entries| entries.len())
+                .map_err(|err| err.to_string())
+        });
+    match parsed {
+        Ok(crates) => DocsCheck::Supported { crates },
+        Err(err) => DocsCheck::Unsupported(format!(
+            "{} is in a format rdoc doesn't read: {}",
+            path.display(),
+            err
+        )),
+    }
+}
+
+/// A commented `.rdoc.toml` with the common settings, listing `doc_root` if
+/// the docs are somewhere rdoc wouldn't look by default.
+fn starter_config(doc_root: Option<&Path>) -> String {
+    let roots = match doc_root {
+        Some(root) => format!(
+            "roots = [{}]\n",
+            toml::Value::String(root.to_string_lossy().replace('\\', "/"))
+        ),
+        None => "# roots = [\"target/doc\", \"doc\"]\n".to_string(),
+    };
+    format!(
+        "# rdoc settings for this project (see `rdoc --help`)\n\
+         \n\
+         [docs]\n\
+         # Where the generated docs are, relative to the project\n\
+         {}\
+         # Where they're published, for absolute URLs in JSON results\n\
+         # base_url = \"https://docs.example.com/myapi/\"\n\
+         # Link dependencies to docs.rs when their pages weren't generated\n\
+         # docs_rs = true\n\
+         \n\
+         [scan]\n\
+         # profile = \"fuzzy\"\n\
+         \n\
+         [aliases]\n\
+         # hm = \"std::collections::HashMap\"\n",
+        roots
+    )
+}
+
+/// The first line `command --version` prints, if it runs.
+fn tool_version(command: &mut Command) -> Option<String> {
+    let output = command.arg("--version").output().ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    stdout.lines().next().map(|line| line.trim().to_string())
+}
+
+fn display_roots(roots: &[PathBuf]) -> String {
+    roots
+        .iter()
+        .map(|root| root.display().to_string())
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+fn pass(message: &str) {
+    println!("  {} {}", "✓".green().bold(), message);
+}
+
+fn fail(message: &str) {
+    println!("  {} {}", "✗".red().bold(), message);
+}
+
+fn note(message: &str) {
+    println!("  {} {}", "ℹ".blue().bold(), message);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+
+    #[test]
+    fn test_starter_config_parses() {
+        let config: Config = toml::from_str(&starter_config(None)).unwrap();
+        assert!(config.docs.roots.is_empty());
+        assert!(config.aliases.is_empty());
+
+        let config: Config = toml::from_str(&starter_config(Some(Path::new("book/api")))).unwrap();
+        assert_eq!(config.docs.roots, [PathBuf::from("book/api")]);
+    }
+
+    #[test]
+    fn test_check_docs() {
+        assert!(matches!(
+            check_docs(Path::new("tests/fixtures")),
+            DocsCheck::Supported { crates } if crates > 0
+        ));
+        assert!(matches!(
+            check_docs(Path::new("tests/no-such-dir")),
+            DocsCheck::Unsupported(_)
+        ));
+    }
+}
@@ -1,2 +1,30 @@
+pub mod alias;
+pub mod aliases;
+pub mod audit;
+pub mod cheatsheet;
+pub mod check_links;
+pub mod complete;
+pub mod explain;
+pub mod export;
+pub mod implementors;
+pub mod init;
+pub mod path;
+pub mod random;
+pub mod repl;
+pub mod report;
+pub mod resolve;
+pub mod rules;
 pub mod scan;
+pub mod schema;
+pub mod serve;
 pub mod show;
+pub mod similar;
+pub mod snippets;
+pub mod source;
+pub mod stats;
+pub mod summary;
+#[cfg(feature = "scip")]
+pub mod symbols;
+pub mod top;
+pub mod upgrade_index;
+pub mod warm;
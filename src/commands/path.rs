@@ -0,0 +1,52 @@
+// Printing the path to `use` an item by, for editor snippets and scripts
+
+use color_eyre::{Result, eyre::eyre};
+use rdoc::crate_graph::Relation;
+use rdoc::resolve::{disambiguated_path, import_path, resolve};
+use tracing::debug;
+
+use crate::docs::{self, DocSource};
+
+/// Print only the path to import the item `path` resolves to, e.g.
+/// `std::collections::HashMap` for `HashMap`, failing (with the candidates)
+/// if it's ambiguous.
+///
+/// In a cargo project the path goes through a crate the project can name: a
+/// workspace member or direct dependency rather than the transitive crate an
+/// item is defined in.
+pub fn execute(path: &str, source: &DocSource) -> Result<()> {
+    // Nothing but the path goes to stdout
+    let quiet = true;
+    let search_index_path = docs::find_search_index(source, quiet, true)?;
+    let json_string = docs::load_search_index_json(&search_index_path)?;
+    let crate_entries = docs::crate_entries(&json_string)?;
+    let all_items = docs::decode_items(&crate_entries, source);
+
+    let item = resolve(&all_items, path)?;
+
+    let graph = if source.is_cargo_project() {
+        docs::crate_graph()
+            .inspect_err(|err| debug!("not preferring dependencies: {:#}", err))
+            .ok()
+    } else {
+        None
+    };
+    // Crates outside the graph (std and the rest of the sysroot) are usable too
+    let usable = |crate_name: &str| {
+        graph.as_ref().is_none_or(|graph| {
+            graph
+                .relation(crate_name)
+                .is_none_or(|relation| relation <= Relation::Direct)
+        })
+    };
+
+    let import = import_path(&all_items, item, usable).ok_or_else(|| {
+        eyre!(
+            "{} is a {}, which can't be imported",
+            disambiguated_path(item),
+            item.item_type
+        )
+    })?;
+    println!("{}", import);
+    Ok(())
+}
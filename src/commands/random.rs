@@ -0,0 +1,72 @@
+// A few random items and what they're for, to explore a crate by
+
+use color_eyre::{Result, eyre::bail};
+use colored::Colorize;
+use fastrand::Rng;
+use rdoc::descriptions::{DescriptionStore, plain_text};
+use rdoc::discover::sample;
+use rdoc::hidden::HiddenItems;
+use rdoc::search_index::ItemType;
+use rdoc::search_items::{SearchItem, dedup_reexports};
+use std::path::Path;
+
+use crate::docs::{self, DocSource};
+use crate::hyperlink::Hyperlinks;
+
+/// Print `count` items picked at random from `crates` (every crate when
+/// empty), of `types` (any when empty), with their descriptions. The same
+/// `seed` picks the same items from the same docs.
+pub fn execute(
+    crates: &[String],
+    types: &[ItemType],
+    count: usize,
+    seed: Option<u64>,
+    source: &DocSource,
+    quiet: bool,
+) -> Result<()> {
+    if !quiet {
+        println!("{} Picking {} random items", "→".cyan().bold(), count);
+    }
+
+    let search_index_path = docs::find_search_index(source, quiet, true)?;
+    let json_string = docs::load_search_index_json(&search_index_path)?;
+    let crate_entries = docs::crate_entries(&json_string)?;
+    for name in crates {
+        if !crate_entries.iter().any(|entry| &entry.name == name) {
+            bail!("Crate \"{}\" is not in the search index", name);
+        }
+    }
+    let all_items = docs::decode_items(&crate_entries, source);
+
+    let hidden = HiddenItems::default();
+    let candidates: Vec<&SearchItem> = dedup_reexports(&all_items)
+        .into_iter()
+        .filter(|item| crates.is_empty() || crates.contains(&item.crate_name))
+        .filter(|item| types.is_empty() || types.contains(&item.item_type))
+        .filter(|item| !hidden.hides(item))
+        .collect();
+    let mut rng = seed.map_or_else(Rng::new, Rng::with_seed);
+    let picked = sample(&candidates, count, &mut rng);
+    if picked.is_empty() {
+        println!("{} No items to pick from", "✗".red().bold());
+        return Ok(());
+    }
+
+    let mut store = DescriptionStore::new(search_index_path.parent().unwrap_or(Path::new(".")));
+    let hyperlinks = Hyperlinks::detect(source, &search_index_path);
+    for item in picked {
+        let full_path = match item.parent_name() {
+            Some(parent) => format!("{}::{}::{}", item.path, parent, item.name),
+            None => format!("{}::{}", item.path, item.name),
+        };
+        let name = match &hyperlinks {
+            Some(hyperlinks) => hyperlinks.link(full_path.cyan().bold(), item),
+            None => full_path.cyan().bold().to_string(),
+        };
+        println!("\n  {} ({}) in {}", name, item.item_type, item.crate_name);
+        if let Some(description) = store.get(item).map(plain_text) {
+            println!("    {}", description.dimmed());
+        }
+    }
+    Ok(())
+}
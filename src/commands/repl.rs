@@ -0,0 +1,312 @@
+use color_eyre::{
+    Result,
+    eyre::{Context, bail, eyre},
+};
+use colored::Colorize;
+use rdoc::aliases::Aliases;
+use rdoc::arena;
+use rdoc::cancel::CancelToken;
+use rdoc::descriptions::{DEFAULT_CACHED_SHARDS, DescriptionStore};
+use rdoc::human::count;
+use rdoc::normalize::crate_name;
+use rdoc::pages::SiteLinks;
+use rdoc::query::Query;
+use rdoc::resolve::{disambiguated_path, kind_types};
+use rdoc::search_index::{ItemType, LazyCrateEntry};
+use rdoc::search_items::{SearchItem, dedup_reexports};
+use rdoc::signature::{FunctionSignature, TypePattern};
+use rdoc::std_paths::StdPaths;
+use std::collections::HashMap;
+use std::io::{BufRead, Write};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use crate::commands::scan::{ScanOptions, match_query};
+use crate::commands::show;
+use crate::docs::{self, DocSource, Pages};
+use crate::hyperlink::Hyperlinks;
+
+/// Results listed after each query; the rest are still numbered for `:show`
+const LISTED_RESULTS: usize = 20;
+
+const HELP: &str = "\
+  QUERY          search, as with `rdoc scan` (e.g. `read AND async`, `-> u32`)
+  :type KIND     only list items of a kind (struct, trait, fn, macro, ...); `:type` clears
+  :crate NAME    only list items from a crate; `:crate` clears
+  :matcher NAME  match names like rustdoc (`rustdoc`) or fzf (`nucleo`, best first)
+  :show N        show result N's declaration and description
+  :open N        open result N's page in the browser
+  :help          list these commands
+  :quit          leave (or Ctrl-D)";
+
+/// What a session keeps between lines: the decoded index, filters and the last results
+struct Session<'a> {
+    items: &'a arena::Index,
+    crate_entries: &'a [LazyCrateEntry<'a>],
+    aliases: Aliases,
+    options: ScanOptions,
+    /// Decoded on the first `-> Type` query
+    signatures: Option<HashMap<String, Vec<Option<FunctionSignature>>>>,
+    kind: Option<(String, &'static [ItemType])>,
+    crate_name: Option<String>,
+    results: Vec<&'a SearchItem>,
+    source: &'a DocSource,
+    search_index_path: PathBuf,
+    /// Where pages are published, looked up on the first `:open`
+    links: Option<SiteLinks>,
+    /// Links from names in result listings, when the terminal shows them
+    hyperlinks: Option<Hyperlinks<'a>>,
+    pages: Pages<'a>,
+    store: DescriptionStore,
+    /// `std` paths of `core` and `alloc` items, when the `std` docs are loaded
+    std_paths: Option<StdPaths>,
+}
+
+/// Run queries from a prompt against an index decoded once, matching them
+/// with `options`
+pub fn execute(options: ScanOptions, source: &DocSource, quiet: bool) -> Result<()> {
+    let search_index_path = docs::find_search_index(source, quiet, true)?;
+    let json_string = docs::read_search_index_json(&search_index_path)?;
+    let crate_entries = docs::crate_entries(&json_string)?;
+    let mut aliases = Aliases::new(&options.aliases);
+    let all_items: arena::Index =
+        docs::decode_items_with_aliases(&crate_entries, source, &mut aliases).into();
+
+    let mut session = Session {
+        items: &all_items,
+        crate_entries: &crate_entries,
+        aliases,
+        options,
+        signatures: None,
+        kind: None,
+        crate_name: None,
+        results: Vec::new(),
+        source,
+        search_index_path: search_index_path.clone(),
+        links: None,
+        hyperlinks: Hyperlinks::detect(source, &search_index_path),
+        pages: Pages::new(source, &search_index_path),
+        store: DescriptionStore::with_capacity(
+            search_index_path.parent().unwrap_or(Path::new(".")),
+            DEFAULT_CACHED_SHARDS,
+        ),
+        std_paths: StdPaths::new(&all_items),
+    };
+
+    if !quiet {
+        println!(
+            "{} {} from {} loaded. Type a query, or :help",
+            "✓".green().bold(),
+            count(all_items.len(), "item", "items"),
+            count(crate_entries.len(), "crate", "crates")
+        );
+    }
+    let stdin = std::io::stdin();
+    let mut lines = stdin.lock().lines();
+    loop {
+        print!("{} ", session.prompt().cyan().bold());
+        std::io::stdout().flush()?;
+        let Some(line) = lines.next() else {
+            println!();
+            break;
+        };
+        let line = line.wrap_err("Failed to read from stdin")?;
+        match session.run(line.trim()) {
+            Ok(true) => {}
+            Ok(false) => break,
+            // A bad line shouldn't end the session
+            Err(err) => eprintln!("{} {:#}", "✗".red().bold(), err),
+        }
+    }
+
+    Ok(())
+}
+
+impl<'a> Session<'a> {
+    /// The prompt, showing the active filters: `rdoc[tokio trait]>`
+    fn prompt(&self) -> String {
+        let filters: Vec<&str> = self
+            .crate_name
+            .iter()
+            .map(String::as_str)
+            .chain(self.kind.as_ref().map(|(kind, _)| kind.as_str()))
+            .collect();
+        if filters.is_empty() {
+            "rdoc>".to_string()
+        } else {
+            format!("rdoc[{}]>", filters.join(" "))
+        }
+    }
+
+    /// Run one line; returns `false` to end the session.
+    fn run(&mut self, line: &str) -> Result<bool> {
+        let Some(command) = line.strip_prefix(':') else {
+            if !line.is_empty() {
+                self.search(line)?;
+            }
+            return Ok(true);
+        };
+        let (command, argument) = match command.split_once(char::is_whitespace) {
+            Some((command, argument)) => (command, Some(argument.trim())),
+            None => (command, None),
+        };
+        match (command, argument) {
+            ("q" | "quit" | "exit", _) => return Ok(false),
+            ("h" | "help", _) => println!("{}", HELP),
+            ("type", None) => self.kind = None,
+            ("type", Some(kind)) => {
+                let types = kind_types(kind).ok_or_else(|| {
+                    eyre!(
+                        "Unknown kind \"{}\" (expected struct, enum, trait, fn, macro, mod, ...)",
+                        kind
+                    )
+                })?;
+                self.kind = Some((kind.to_string(), types));
+            }
+            ("matcher", Some(name)) => {
+                self.options.matcher = name.parse().map_err(|err: String| eyre!(err))?;
+            }
+            ("matcher", None) => println!("{}", self.options.matcher),
+            ("crate", None) => self.crate_name = None,
+            ("crate", Some(name)) => {
+                let name = crate_name(name);
+                if !self.crate_entries.iter().any(|entry| entry.name == name) {
+                    bail!("Crate \"{}\" is not in the search index", name);
+                }
+                self.crate_name = Some(name.to_string());
+            }
+            ("show", Some(number)) => {
+                let item = self.result(number)?;
+                show::print_item(
+                    item,
+                    self.std_paths.as_ref(),
+                    &mut self.pages,
+                    &mut self.store,
+                )?;
+            }
+            ("open", Some(number)) => {
+                let item = self.result(number)?;
+                let links = self
+                    .links
+                    .get_or_insert_with(|| docs::site_links(self.source));
+                let url = docs::page_url(self.source, &self.search_index_path, links, item)
+                    .ok_or_else(|| eyre!("{} has no page to open", disambiguated_path(item)))?;
+                open_in_browser(&url)?;
+                println!("{} Opened {}", "✓".green().bold(), url.dimmed());
+            }
+            ("show" | "open", None) => bail!("Usage: :{} N, with N from the last results", command),
+            _ => bail!("Unknown command :{}; type :help for the list", command),
+        }
+        Ok(true)
+    }
+
+    /// Run a query, list the results and keep them for `:show` and `:open`.
+    fn search(&mut self, query: &str) -> Result<()> {
+        let mut results = match TypePattern::parse_return_query(query) {
+            Some(pattern) => {
+                let pattern =
+                    pattern.wrap_err_with(|| format!("Invalid return type query \"{}\"", query))?;
+                let signatures = self
+                    .signatures
+                    .get_or_insert_with(|| docs::decode_signatures(self.crate_entries));
+                self.items
+                    .iter()
+                    .filter(|item| {
+                        docs::signature_of(signatures, item)
+                            .is_some_and(|sig| sig.returns(&pattern))
+                    })
+                    .collect()
+            }
+            None => {
+                let query =
+                    Query::parse(query).wrap_err_with(|| format!("Invalid query \"{}\"", query))?;
+                match_query(
+                    &query,
+                    self.items,
+                    &self.aliases,
+                    &mut self.store,
+                    &self.options,
+                    &CancelToken::default(),
+                )?
+            }
+        };
+        results.retain(|item| !self.options.hides(item));
+        if let Some((_, types)) = &self.kind {
+            results.retain(|item| types.contains(&item.item_type));
+        }
+        if let Some(crate_name) = &self.crate_name {
+            results.retain(|item| &item.crate_name == crate_name);
+        }
+        self.results = dedup_reexports(results);
+
+        if self.results.is_empty() {
+            println!("{} No results", "✗".red().bold());
+            return Ok(());
+        }
+        let width = self.results.len().min(LISTED_RESULTS).to_string().len();
+        for (number, item) in self.results.iter().take(LISTED_RESULTS).enumerate() {
+            let name = match &self.hyperlinks {
+                Some(hyperlinks) => hyperlinks.link(item.name.cyan(), item),
+                None => item.name.cyan().to_string(),
+            };
+            println!(
+                "  {:>width$}  {} ({}) {}",
+                number + 1,
+                name,
+                item.item_type,
+                disambiguated_path(item).dimmed(),
+                width = width
+            );
+        }
+        if self.results.len() > LISTED_RESULTS {
+            println!(
+                "  {}",
+                format!(
+                    "... and {} more (narrow with :type or :crate)",
+                    self.results.len() - LISTED_RESULTS
+                )
+                .dimmed()
+            );
+        }
+        Ok(())
+    }
+
+    /// Result `number` (counting from 1) of the last query.
+    fn result(&self, number: &str) -> Result<&'a SearchItem> {
+        let index: usize = number
+            .parse()
+            .map_err(|_| eyre!("\"{}\" is not a result number", number))?;
+        index
+            .checked_sub(1)
+            .and_then(|index| self.results.get(index).copied())
+            .ok_or_else(|| {
+                eyre!(
+                    "No result {}; the last query had {}",
+                    number,
+                    self.results.len()
+                )
+            })
+    }
+}
+
+/// Open `url` with `$BROWSER`, or the platform's opener.
+fn open_in_browser(url: &str) -> Result<()> {
+    let mut command = match std::env::var_os("BROWSER") {
+        Some(browser) => Command::new(browser),
+        None if cfg!(target_os = "macos") => Command::new("open"),
+        None if cfg!(windows) => {
+            let mut command = Command::new("cmd");
+            command.args(["/C", "start", ""]);
+            command
+        }
+        None => Command::new("xdg-open"),
+    };
+    let status = command
+        .arg(url)
+        .status()
+        .wrap_err("Failed to start a browser; set $BROWSER")?;
+    if !status.success() {
+        bail!("The browser exited with {}", status);
+    }
+    Ok(())
+}
@@ -0,0 +1,241 @@
+use color_eyre::{Result, eyre::Context};
+use colored::Colorize;
+use rdoc::coverage::DocCoverage;
+use rdoc::search_items::SearchItem;
+use serde_json::json;
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::path::Path;
+use tracing::info;
+
+use crate::docs::{self, DocSource};
+use crate::output;
+
+/// Write a static HTML report summarizing the documentation surface
+pub fn execute(output: &Path, source: &DocSource, quiet: bool) -> Result<()> {
+    if !quiet {
+        println!("{} Generating documentation report", "→".cyan().bold());
+    }
+
+    let search_index_path = docs::find_search_index(source, quiet, true)?;
+    let json_string = docs::load_search_index_json(&search_index_path)?;
+    let crate_entries = docs::crate_entries(&json_string)?;
+    let all_items = docs::decode_items(&crate_entries, source);
+
+    let html = render_report(&all_items);
+    output::write_atomic(output, html)
+        .wrap_err_with(|| format!("Failed to write report to {}", output.display()))?;
+    info!("wrote {} items to {}", all_items.len(), output.display());
+
+    if !quiet {
+        println!(
+            "{} Wrote report to {}",
+            "✓".green().bold(),
+            output.display().to_string().cyan()
+        );
+    }
+
+    Ok(())
+}
+
+/// Items of one crate, split by what the report lists about them
+#[derive(Default)]
+struct CrateSummary<'a> {
+    coverage: DocCoverage,
+    deprecated: Vec<&'a SearchItem>,
+    undocumented: Vec<&'a SearchItem>,
+}
+
+/// Render the whole report as a single self-contained HTML page.
+fn render_report(items: &[SearchItem]) -> String {
+    let mut crates: BTreeMap<&str, CrateSummary> = BTreeMap::new();
+    for item in items {
+        let summary = crates.entry(item.crate_name.as_str()).or_default();
+        summary.coverage.add(item);
+        if item.deprecated {
+            summary.deprecated.push(item);
+        }
+        if item.empty_description {
+            summary.undocumented.push(item);
+        }
+    }
+
+    let mut html = String::new();
+    html.push_str(HEADER);
+
+    html.push_str("<h2>Crates</h2>\n<table>\n");
+    html.push_str(
+        "<tr><th>Crate</th><th>Items</th><th>Deprecated</th><th>Undocumented</th><th>Coverage</th></tr>\n",
+    );
+    for (name, summary) in &crates {
+        let _ = writeln!(
+            html,
+            "<tr><td><a href=\"#crate-{0}\">{0}</a></td><td>{1}</td><td>{2}</td><td>{3}</td><td>{4:.1}%</td></tr>",
+            escape_html(name),
+            summary.coverage.total,
+            summary.deprecated.len(),
+            summary.coverage.undocumented,
+            summary.coverage.percent()
+        );
+    }
+    html.push_str("</table>\n");
+
+    for (name, summary) in &crates {
+        let _ = writeln!(html, "<h2 id=\"crate-{0}\">{0}</h2>", escape_html(name));
+        render_item_list(&mut html, "Deprecated", &summary.deprecated);
+        render_item_list(&mut html, "Undocumented", &summary.undocumented);
+    }
+
+    // The search box filters this blob client-side, so the page needs no server
+    let search_data: Vec<_> = items
+        .iter()
+        .map(|item| {
+            json!({
+                "crate": item.crate_name,
+                "name": item.name,
+                "path": item.path,
+                "type": item.item_type.to_string(),
+                "deprecated": item.deprecated,
+                "undocumented": item.empty_description,
+            })
+        })
+        .collect();
+    let search_json = serde_json::Value::from(search_data).to_string();
+    let _ = writeln!(
+        html,
+        "<script type=\"application/json\" id=\"search-data\">{}</script>",
+        // Keep item names like `</script>` from ending the element early
+        search_json.replace("</", "<\\/")
+    );
+
+    html.push_str(FOOTER);
+    html
+}
+
+fn render_item_list(html: &mut String, label: &str, items: &[&SearchItem]) {
+    if items.is_empty() {
+        return;
+    }
+
+    let _ = writeln!(
+        html,
+        "<details><summary>{} ({})</summary>\n<ul>",
+        label,
+        items.len()
+    );
+    for item in items {
+        let _ = writeln!(
+            html,
+            "<li><code>{}::{}</code> <span class=\"type\">{}</span></li>",
+            escape_html(&item.path),
+            escape_html(&item.name),
+            item.item_type
+        );
+    }
+    html.push_str("</ul></details>\n");
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+const HEADER: &str = r#"<!DOCTYPE html>
+<html lang="en">
+<head>
+<meta charset="utf-8">
+<title>rdoc documentation report</title>
+<style>
+body { font-family: sans-serif; max-width: 60em; margin: 2em auto; padding: 0 1em; }
+table { border-collapse: collapse; }
+th, td { padding: 0.2em 0.8em; text-align: left; border-bottom: 1px solid #ddd; }
+.type { color: #777; font-size: 0.9em; }
+#search { width: 100%; padding: 0.4em; font-size: 1em; }
+#results { list-style: none; padding-left: 0; }
+</style>
+</head>
+<body>
+<h1>Documentation report</h1>
+<input id="search" type="search" placeholder="Search items...">
+<ul id="results"></ul>
+"#;
+
+const FOOTER: &str = r#"<script>
+const items = JSON.parse(document.getElementById("search-data").textContent);
+const results = document.getElementById("results");
+document.getElementById("search").addEventListener("input", (event) => {
+  const query = event.target.value.toLowerCase();
+  results.replaceChildren();
+  if (!query) return;
+  for (const item of items.filter((item) => item.name.toLowerCase().includes(query)).slice(0, 100)) {
+    const li = document.createElement("li");
+    const code = document.createElement("code");
+    code.textContent = item.path + "::" + item.name;
+    const type = document.createElement("span");
+    type.className = "type";
+    type.textContent = " " + item.type + (item.deprecated ? ", deprecated" : "") +
+      (item.undocumented ? ", undocumented" : "");
+    li.append(code, type);
+    results.append(li);
+  }
+});
+</script>
+</body>
+</html>
+"#;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rdoc::item_id::ItemId;
+    use rdoc::search_index::ItemType;
+
+    fn item(crate_name: &str, name: &str, deprecated: bool, empty_description: bool) -> SearchItem {
+        SearchItem {
+            crate_name: crate_name.to_string(),
+            item_type: ItemType::Function,
+            name: name.to_string(),
+            normalized_name: name.to_lowercase(),
+            path: crate_name.to_string(),
+            exact_path: crate_name.to_string(),
+            id: ItemId::new(ItemId::LOCAL_SOURCE, crate_name, 0),
+            param_types: vec![],
+            impl_disambiguator: None,
+            bit_index: 1,
+            parent_index: None,
+            parents: Default::default(),
+            deprecated,
+            empty_description,
+            description_location: None,
+        }
+    }
+
+    #[test]
+    fn test_render_report_summarizes_crates() {
+        let items = vec![
+            item("alpha", "old_fn", true, false),
+            item("alpha", "bare_fn", false, true),
+            item("alpha", "good_fn", false, false),
+            item("beta", "only_fn", false, false),
+        ];
+
+        let html = render_report(&items);
+
+        assert!(html.contains(
+            "<tr><td><a href=\"#crate-alpha\">alpha</a></td><td>3</td><td>1</td><td>1</td><td>66.7%</td></tr>"
+        ));
+        assert!(html.contains("<summary>Deprecated (1)</summary>"));
+        assert!(html.contains("<code>alpha::bare_fn</code>"));
+        assert!(html.contains("\"name\":\"only_fn\""));
+    }
+
+    #[test]
+    fn test_render_report_escapes_embedded_data() {
+        let html = render_report(&[item("alpha", "</script><b>", false, false)]);
+
+        assert!(!html.contains("</script><b>"));
+        assert!(html.contains("<\\/script><b>"));
+    }
+}
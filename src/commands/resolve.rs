@@ -0,0 +1,44 @@
+use color_eyre::{Result, eyre::Context};
+use colored::Colorize;
+use rdoc::format::Format;
+use rdoc::resolve::{disambiguated_path, resolve};
+
+use crate::docs::{self, DocSource};
+
+/// Resolve a path to exactly one item, failing if it's missing or ambiguous
+pub fn execute(path: &str, format: Option<&Format>, source: &DocSource, quiet: bool) -> Result<()> {
+    // Formatted output on stdout must not be mixed with status messages
+    let quiet = quiet || format.is_some();
+    if !quiet {
+        println!("{} Resolving {}", "→".cyan().bold(), path.green().bold());
+    }
+
+    let search_index_path = docs::find_search_index(source, quiet, true)?;
+    let json_string = docs::load_search_index_json(&search_index_path)?;
+    let crate_entries = docs::crate_entries(&json_string)?;
+    let all_items = docs::decode_items(&crate_entries, source);
+
+    let item = resolve(&all_items, path)?;
+
+    if let Some(format) = format {
+        format
+            .formatter_with_links(docs::site_links(source))
+            .format(&[item], &mut std::io::stdout().lock())
+            .wrap_err_with(|| format!("Failed to format result as {}", format))?;
+        return Ok(());
+    }
+
+    println!(
+        "{} {} ({}) in {}",
+        "✓".green().bold(),
+        disambiguated_path(item).cyan(),
+        item.item_type,
+        item.crate_name.dimmed()
+    );
+    if item.exact_path != item.path {
+        println!("    defined in {}", item.exact_path.dimmed());
+    }
+    println!("    id {}", item.id.to_string().dimmed());
+
+    Ok(())
+}
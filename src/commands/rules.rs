@@ -0,0 +1,39 @@
+// Listing the built-in rules that leave noise out of scan results
+
+use color_eyre::Result;
+use colored::Colorize;
+use rdoc::hidden::{HiddenItems, HideRule};
+
+/// List the built-in noise rules, what each leaves out, and whether the
+/// config turns it off
+pub fn list(hidden: &HiddenItems) -> Result<()> {
+    let names: Vec<String> = HideRule::ALL.iter().map(ToString::to_string).collect();
+    let width = names.iter().map(String::len).max().unwrap_or(0);
+    for (rule, name) in HideRule::ALL.into_iter().zip(&names) {
+        let state = if hidden.is_enabled(rule) {
+            "on ".green()
+        } else {
+            "off".yellow()
+        };
+        println!(
+            "  {}  {}  {}",
+            format!("{:<width$}", name, width = width).cyan(),
+            state,
+            rule.description()
+        );
+        if rule == HideRule::HiddenModules {
+            println!(
+                "  {:<width$}       {}",
+                "",
+                format!("modules: {}", hidden.modules().join(", ")).dimmed(),
+                width = width
+            );
+        }
+    }
+    println!(
+        "\n  {}",
+        "Turn rules off with --disable-rule RULE or [scan] disabled_rules, or all of them with --no-default-ignore"
+            .dimmed()
+    );
+    Ok(())
+}
@@ -1,97 +1,1599 @@
-use color_eyre::{eyre::Context, Result};
+use clap::ValueEnum;
+use color_eyre::{
+    Report, Result,
+    eyre::{Context, bail, eyre},
+};
 use colored::Colorize;
-use std::path::Path;
-use std::process::Command;
+use rdoc::aliases::Aliases;
+use rdoc::arena;
+use rdoc::cancel::CancelToken;
+use rdoc::crate_graph::{CrateGraph, Relation};
+use rdoc::descriptions::{DescriptionStore, plain_text};
+use rdoc::events::EVENT_TARGET;
+use rdoc::format::{BatchResults, Format, Formatter, JsonFormatter, NdjsonFormatter, QueryResults};
+use rdoc::glob::Glob;
+use rdoc::hidden::HiddenItems;
+use rdoc::human::count;
+use rdoc::ignore::IgnoreRules;
+use rdoc::index_file::{IndexFile, IndexJson};
+use rdoc::item_id::ItemId;
+use rdoc::matcher::{CaseMode, Matcher, MatcherKind, RustdocRank};
+use rdoc::members::MemberCounts;
+use rdoc::pages::{SiteLinks, impl_origin};
+use rdoc::platform::TargetPlatform;
+use rdoc::prefilter::Candidates;
+use rdoc::query::Query;
+use rdoc::resolve::kind_types;
+use rdoc::schema::Versioned;
+use rdoc::search_index::{ItemType, LazyCrateEntry, parse_search_index_lazy};
+use rdoc::search_items::{ReexportFilter, SearchItem, decode_crate, dedup_reexports};
+use rdoc::signature::{FunctionSignature, TypePattern};
+use rdoc::std_paths::StdPaths;
+use rdoc::suggest::{suggest_dependencies, symbol_name};
+use rdoc::timings::Timings;
+use serde::Deserialize;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::io::{self, BufRead, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Instant;
+use tracing::{debug, info};
 
-use crate::search_index::{extract_json_string, parse_search_index};
-use crate::search_items::decode_crate;
+use crate::cache;
+use crate::config::Config;
+use crate::daemon::{self, ScanAnswer};
+use crate::docs::{self, DocSource, Pages};
+use crate::hyperlink::{self, Hyperlinks};
+use crate::output;
+use crate::remote::SEARCH_INDEX_FILE;
 
-/// Scan rustdocs for a specific symbol
-pub fn execute(symbol: &str) -> Result<()> {
-    println!(
-        "{} Scanning for symbol: {}",
-        "→".cyan().bold(),
-        symbol.green().bold()
-    );
+/// Flags that change how `scan` matches and lists items
+#[derive(Debug, Clone, Default)]
+pub struct ScanOptions {
+    /// Also match query terms against item descriptions
+    pub descriptions: bool,
+    /// List every re-export of an item instead of just one
+    pub show_reexports: bool,
+    /// How letter case is compared
+    pub case: CaseMode,
+    /// How terms are matched against names, and whether results are ranked
+    pub matcher: MatcherKind,
+    /// How results are written; `None` is the colored listing for the terminal
+    pub format: Option<Format>,
+    /// Write results to this file instead of the terminal
+    pub output: Option<PathBuf>,
+    /// Only keep items gated behind one of these cargo features
+    pub features: Vec<String>,
+    /// Leave out items not available on this platform
+    pub target_platform: Option<TargetPlatform>,
+    /// Only keep items inside this module or type path
+    pub within: Option<String>,
+    /// Only keep items from the workspace's crates and their direct dependencies
+    pub direct_deps_only: bool,
+    /// User-defined aliases from the config file, by alias
+    pub aliases: BTreeMap<String, String>,
+    /// Leave out items of these types
+    pub exclude_types: Vec<ItemType>,
+    /// Leave out these crates and everything under these paths
+    pub exclude: IgnoreRules,
+    /// Leave out internal and generated items; `None` lists them
+    pub hidden: Option<HiddenItems>,
+    /// Send the query to a running `rdoc serve` for the same docs, if there
+    /// is one, instead of decoding them
+    pub use_daemon: bool,
+    /// Report how long each phase of answering the query took
+    pub timings: bool,
+    /// Suggest crates to add when nothing is found
+    pub suggest_deps: bool,
+}
 
-    // Check if we're in a Rust project
-    if !Path::new("Cargo.toml").exists() {
-        return Err(color_eyre::eyre::eyre!(
-            "No Cargo.toml found. Please run rdoc from a Rust project directory."
-        ));
+impl ScanOptions {
+    /// The options `scan` uses without flags: the config file's aliases,
+    /// profile, matcher and noise rules.
+    pub fn from_config(config: &Config) -> Result<Self> {
+        let mut options = ScanOptions {
+            aliases: config.aliases.clone(),
+            matcher: config.scan.matcher.unwrap_or_default(),
+            hidden: Some(config.scan.hidden_items()?),
+            ..ScanOptions::default()
+        };
+        if let Some(profile) = config.scan.profile {
+            profile.apply(&mut options);
+        }
+        Ok(options)
     }
 
-    let search_index_path = Path::new("target/doc/search-index.js");
+    /// Whether the `--exclude-*` filters leave `item` out.
+    fn excludes(&self, item: &SearchItem) -> bool {
+        self.exclude_types.contains(&item.item_type)
+            || (!self.exclude.is_empty() && self.exclude.ignores_item(item))
+    }
 
-    // Check if docs exist, if not generate them
-    if !search_index_path.exists() {
-        println!(
-            "{} Documentation not found. Generating with cargo doc...",
-            "ℹ".blue().bold()
-        );
+    /// Whether `item` is internal or generated and not being listed.
+    pub(crate) fn hides(&self, item: &SearchItem) -> bool {
+        self.hidden
+            .as_ref()
+            .is_some_and(|hidden| hidden.hides(item))
+    }
+}
+
+/// Whether `item` is available on `platform`, by its page's `doc(cfg)` notes;
+/// items without a readable page are kept.
+fn is_available(pages: &mut Pages, item: &SearchItem, platform: &TargetPlatform) -> Result<bool> {
+    Ok(pages
+        .availability(item)?
+        .iter()
+        .all(|condition| platform.allows(condition)))
+}
 
-        let output = Command::new("cargo")
-            .arg("doc")
-            .output()
-            .wrap_err("Failed to execute cargo doc")?;
+/// Parse an `--exclude-type` kind: one `kind@` paths take (struct, fn, macro,
+/// ...), or any type's own name (impl, import, tymethod, ...).
+pub fn parse_kind(kind: &str) -> Result<&'static [ItemType], String> {
+    match kind {
+        "use" => Ok(&[ItemType::Import]),
+        kind => kind_types(kind).ok_or_else(|| {
+            format!(
+                "unknown kind \"{}\" (expected struct, enum, trait, fn, macro, mod, impl, import, ...)",
+                kind
+            )
+        }),
+    }
+}
+
+/// A named combination of matching options, so common behaviors don't take
+/// several flags. Flags given alongside a profile override it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Profile {
+    /// grep-like: names only, case must match exactly, every re-export listed
+    Strict,
+    /// rustdoc-like: case ignored, re-exports collapsed
+    Fuzzy,
+    /// Like fuzzy, but terms also match item descriptions
+    Docs,
+}
 
-        if !output.status.success() {
-            let stderr = String::from_utf8_lossy(&output.stderr);
-            return Err(color_eyre::eyre::eyre!(
-                "cargo doc failed:\n{}",
-                stderr
-            ));
+impl Profile {
+    /// Apply the profile's settings to `options`.
+    pub fn apply(self, options: &mut ScanOptions) {
+        match self {
+            Profile::Strict => {
+                options.case = CaseMode::Sensitive;
+                options.descriptions = false;
+                options.show_reexports = true;
+            }
+            Profile::Fuzzy => {
+                options.case = CaseMode::Insensitive;
+                options.descriptions = false;
+                options.show_reexports = false;
+            }
+            Profile::Docs => {
+                options.case = CaseMode::Insensitive;
+                options.descriptions = true;
+                options.show_reexports = false;
+            }
         }
+    }
+}
+
+/// What a `scan` query searches for
+enum Search {
+    /// Terms matched against names (and descriptions)
+    Names(Query),
+    /// `-> Type`: functions returning a matching type
+    ReturnType(TypePattern),
+}
 
-        println!("{} Documentation generated successfully!", "✓".green().bold());
+impl Search {
+    fn parse(symbol: &str) -> Result<Self> {
+        Ok(match TypePattern::parse_return_query(symbol) {
+            Some(pattern) => Search::ReturnType(
+                pattern.wrap_err_with(|| format!("Invalid return type query \"{}\"", symbol))?,
+            ),
+            None => Search::Names(
+                Query::parse(symbol).wrap_err_with(|| format!("Invalid query \"{}\"", symbol))?,
+            ),
+        })
     }
+}
 
-    // Parse the search index
-    let content = std::fs::read_to_string(search_index_path)
-        .wrap_err("Failed to read search-index.js")?;
+/// The decoded index and what's looked up along the way, kept so a batch of
+/// queries decodes everything once
+struct Scanner<'a> {
+    options: &'a ScanOptions,
+    source: &'a DocSource,
+    crate_entries: &'a [LazyCrateEntry<'a>],
+    items: &'a arena::Index,
+    aliases: &'a Aliases,
+    store: DescriptionStore,
+    pages: Pages<'a>,
+    /// For ranking results by how close their crate is to the workspace
+    graph: Option<CrateGraph>,
+    /// Decoded on the first `-> Type` query
+    signatures: Option<HashMap<String, Vec<Option<FunctionSignature>>>>,
+    /// Links from names in the colored listing, when the terminal shows them
+    hyperlinks: Option<Hyperlinks<'a>>,
+    /// Every type's and trait's members, counted for the colored listing
+    members: Option<MemberCounts<'a>>,
+    /// `std` paths of `core` and `alloc` items for the colored listing, when
+    /// the `std` docs are loaded
+    std_paths: Option<StdPaths>,
+    /// Whether the colored listing counts traits' implementors, which reads
+    /// their pages; only for local docs, so a scan downloads nothing extra
+    count_implementors: bool,
+    /// How long the last query took, phase by phase
+    timings: Timings,
+}
 
-    let json_string = extract_json_string(&content);
-    let crate_entries = parse_search_index(&json_string);
+/// One query's results, ready to write
+struct Found<'a> {
+    results: Vec<&'a SearchItem>,
+    /// Re-exports left out of `results`
+    hidden: usize,
+    /// Internal and generated items left out of `results`
+    internal: usize,
+    /// The features each result needs, when filtering by feature
+    gates: HashMap<&'a ItemId, Vec<String>>,
+    /// The query terms that matched a result only as one of its aliases
+    via_aliases: HashMap<&'a ItemId, Vec<String>>,
+    /// How many implementations each trait in the results has, when counted
+    implementors: HashMap<&'a ItemId, usize>,
+}
 
-    // Decode all crates into search items
-    let mut all_items = Vec::new();
-    for entry in &crate_entries {
-        let items = decode_crate(&entry.name, &entry.data);
-        all_items.extend(items);
+impl<'a> Scanner<'a> {
+    fn new(
+        options: &'a ScanOptions,
+        source: &'a DocSource,
+        search_index_path: &Path,
+        crate_entries: &'a [LazyCrateEntry<'a>],
+        items: &'a arena::Index,
+        aliases: &'a Aliases,
+        graph: Option<CrateGraph>,
+    ) -> Self {
+        Self {
+            options,
+            source,
+            crate_entries,
+            items,
+            aliases,
+            store: DescriptionStore::new(search_index_path.parent().unwrap_or(Path::new("."))),
+            pages: Pages::new(source, search_index_path),
+            graph,
+            signatures: None,
+            hyperlinks: options
+                .format
+                .is_none()
+                .then(|| Hyperlinks::detect(source, search_index_path))
+                .flatten(),
+            members: options.format.is_none().then(|| MemberCounts::new(items)),
+            std_paths: options
+                .format
+                .is_none()
+                .then(|| StdPaths::new(items))
+                .flatten(),
+            count_implementors: options.format.is_none()
+                && matches!(source, DocSource::Local { .. }),
+            timings: Timings::default(),
+        }
     }
 
-    // Search for items matching the symbol (case-insensitive substring match)
-    let search_term = symbol.to_lowercase();
-    let results: Vec<_> = all_items
-        .iter()
-        .filter(|item| item.name.to_lowercase().contains(&search_term))
-        .collect();
+    fn search(&mut self, search: &Search) -> Result<Found<'a>> {
+        let start = Instant::now();
+        let mut results = self.matches(search)?;
+        let internal = self.filter(&mut results);
+        self.timings.matching = start.elapsed();
 
-    // Display results
-    if results.is_empty() {
-        println!("{} No results found for \"{}\"", "✗".red().bold(), symbol);
-    } else {
-        println!(
-            "\n{} Found {} result{} for \"{}\":\n",
+        let start = Instant::now();
+        let hidden = self.rank(search, &mut results)?;
+        self.timings.rank = start.elapsed();
+
+        let start = Instant::now();
+        let gates = self.filter_by_pages(&mut results)?;
+        self.timings.matching += start.elapsed();
+
+        // What the listing notes about the results is part of writing it
+        let start = Instant::now();
+        let via_aliases = match search {
+            Search::Names(query) => self.via_aliases(query, &results)?,
+            Search::ReturnType(_) => HashMap::new(),
+        };
+        let implementors = self.implementors(&results);
+        self.timings.render = start.elapsed();
+
+        Ok(Found {
+            results,
+            hidden,
+            internal,
+            gates,
+            via_aliases,
+            implementors,
+        })
+    }
+
+    /// The items `search` matches, within `--within`'s scope.
+    fn matches(&mut self, search: &Search) -> Result<Vec<&'a SearchItem>> {
+        let options = self.options;
+        let mut results = match search {
+            Search::Names(query) => match_unranked(
+                query,
+                self.items,
+                self.aliases,
+                &mut self.store,
+                options,
+                &CancelToken::default(),
+            )?,
+            Search::ReturnType(pattern) => {
+                let crate_entries = self.crate_entries;
+                let signatures = self
+                    .signatures
+                    .get_or_insert_with(|| docs::decode_signatures(crate_entries));
+                self.items
+                    .iter()
+                    .filter(|item| {
+                        docs::signature_of(signatures, item).is_some_and(|sig| sig.returns(pattern))
+                    })
+                    .collect()
+            }
+        };
+        if let Some(scope) = &options.within {
+            results.retain(|item| item.is_within(scope));
+            if results.is_empty() && !self.items.iter().any(|item| item.is_within(scope)) {
+                return Err(unknown_scope(scope));
+            }
+        }
+        Ok(results)
+    }
+
+    /// Leave out excluded items, then internal and generated ones, returning
+    /// how many of those there were.
+    fn filter(&self, results: &mut Vec<&'a SearchItem>) -> usize {
+        results.retain(|item| !self.options.excludes(item));
+        let unfiltered = results.len();
+        results.retain(|item| !self.options.hides(item));
+        unfiltered - results.len()
+    }
+
+    /// Order `results` best first, and leave out re-exports unless they're
+    /// shown, returning how many were.
+    fn rank(&self, search: &Search, results: &mut Vec<&'a SearchItem>) -> Result<usize> {
+        let options = self.options;
+        if let Search::Names(query) = search {
+            rank_by_score(query, results, options, &CancelToken::default())?;
+        }
+        // Workspace crates first, then direct dependencies, then everything else
+        if let Some(graph) = &self.graph {
+            if options.direct_deps_only {
+                results.retain(|item| is_direct(graph, item));
+            }
+            results.sort_by_key(|item| {
+                let relation = graph.relation(&item.crate_name);
+                (relation.is_none(), relation)
+            });
+        }
+
+        let matched = results.len();
+        if !options.show_reexports {
+            *results = dedup_reexports(std::mem::take(results));
+        }
+        Ok(matched - results.len())
+    }
+
+    /// Keep the results with one of `--features`' features and available on
+    /// `--target-platform`, returning the features each needs. Both are only
+    /// on the HTML pages, so they're looked up only when filtering.
+    fn filter_by_pages(
+        &mut self,
+        results: &mut Vec<&'a SearchItem>,
+    ) -> Result<HashMap<&'a ItemId, Vec<String>>> {
+        let options = self.options;
+        let mut gates = HashMap::new();
+        if !options.features.is_empty() {
+            let mut gated = Vec::new();
+            for item in results.drain(..) {
+                let required = self.pages.required_features(item)?;
+                if required
+                    .iter()
+                    .any(|feature| options.features.contains(feature))
+                {
+                    gates.insert(&item.id, required);
+                    gated.push(item);
+                }
+            }
+            *results = gated;
+        }
+        if let Some(platform) = &options.target_platform {
+            let mut available = Vec::new();
+            for item in results.drain(..) {
+                if is_available(&mut self.pages, item, platform)? {
+                    available.push(item);
+                }
+            }
+            *results = available;
+        }
+        Ok(gates)
+    }
+
+    /// How many implementations each trait among `results` has, when they're
+    /// counted.
+    fn implementors(&mut self, results: &[&'a SearchItem]) -> HashMap<&'a ItemId, usize> {
+        let mut implementors = HashMap::new();
+        if self.count_implementors {
+            for item in results {
+                // None found may just mean the pages weren't readable
+                if item.item_type == ItemType::Trait
+                    && let Ok(found) = self.pages.implementors(item)
+                    && !found.is_empty()
+                {
+                    implementors.insert(&item.id, found.len());
+                }
+            }
+        }
+        implementors
+    }
+
+    /// For results that a term of `query` names as an alias but doesn't
+    /// match by name, those terms: why `len` found `count()`.
+    fn via_aliases(
+        &self,
+        query: &Query,
+        results: &[&'a SearchItem],
+    ) -> Result<HashMap<&'a ItemId, Vec<String>>> {
+        let mut via_aliases: HashMap<&ItemId, Vec<String>> = HashMap::new();
+        if self.aliases.is_empty() {
+            return Ok(via_aliases);
+        }
+        let results: HashSet<&ItemId> = results.iter().map(|item| &item.id).collect();
+        for term in query.terms() {
+            let matcher = self.options.matcher.matcher(term, self.options.case)?;
+            for item in self.aliases.targets(self.items, term) {
+                if results.contains(&item.id) && !matcher.matches_item(item) {
+                    via_aliases
+                        .entry(&item.id)
+                        .or_default()
+                        .push(term.to_string());
+                }
+            }
+        }
+        Ok(via_aliases)
+    }
+
+    /// Write one query's results in the chosen format, or as the colored
+    /// listing for the terminal.
+    fn write(
+        &mut self,
+        symbol: &str,
+        search: &Search,
+        found: &Found,
+        out: &mut dyn Write,
+    ) -> Result<()> {
+        let start = Instant::now();
+        match &self.options.format {
+            Some(format) => self
+                .formatter(format)
+                .format(&found.results, out)
+                .wrap_err_with(|| format!("Failed to format results as {}", format))?,
+            None => self
+                .print(symbol, search, found, out)
+                .wrap_err("Failed to write results")?,
+        }
+        self.timings.render += start.elapsed();
+        Ok(())
+    }
+
+    /// The formatter writing `format`, with the query's timings so far in
+    /// JSON when they're asked for.
+    fn formatter(&self, format: &Format) -> Box<dyn Formatter> {
+        let links = docs::site_links(self.source);
+        match format {
+            Format::Json if self.options.timings => Box::new(JsonFormatter {
+                links,
+                timings: Some(self.timings),
+            }),
+            format => format.formatter_with_links(links),
+        }
+    }
+
+    /// Print one query's results as the colored listing for the terminal,
+    /// with return types for `-> Type` queries.
+    fn print(
+        &self,
+        symbol: &str,
+        search: &Search,
+        found: &Found,
+        out: &mut dyn Write,
+    ) -> io::Result<()> {
+        let results = &found.results;
+        if results.is_empty() {
+            writeln!(
+                out,
+                "{} No results found for \"{}\"",
+                "✗".red().bold(),
+                symbol
+            )?;
+            return Ok(());
+        }
+        writeln!(
+            out,
+            "\n{} Found {} for \"{}\":\n",
             "✓".green().bold(),
-            results.len(),
-            if results.len() == 1 { "" } else { "s" },
+            count(results.len(), "result", "results"),
             symbol
-        );
+        )?;
+        if found.hidden > 0 {
+            writeln!(
+                out,
+                "  {}\n",
+                format!(
+                    "({} hidden; use --show-reexports to list them)",
+                    count(found.hidden, "re-export", "re-exports")
+                )
+                .dimmed()
+            )?;
+        }
+        if found.internal > 0 {
+            writeln!(
+                out,
+                "  {}\n",
+                format!(
+                    "({} hidden by `rdoc rules list`; use --no-default-ignore to list them)",
+                    count(found.internal, "internal item", "internal items")
+                )
+                .dimmed()
+            )?;
+        }
+
+        for entry in group_by_parent(results) {
+            match entry {
+                Listed::Item(item) => self.print_item(item, search, found, false, out)?,
+                Listed::Members(members) => {
+                    let first = members[0];
+                    writeln!(
+                        out,
+                        "  {} ({}) in {}",
+                        first.parent_name().unwrap_or_default().cyan(),
+                        first
+                            .parent_type()
+                            .map(|parent_type| parent_type.to_string())
+                            .unwrap_or_default()
+                            .yellow(),
+                        first.crate_name.dimmed()
+                    )?;
+                    if !first.path.is_empty() {
+                        writeln!(out, "    at {}", first.path.dimmed())?;
+                    }
+                    for item in members {
+                        self.print_item(item, search, found, true, out)?;
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
 
-        for item in results {
-            let type_str = format!("{:?}", item.item_type);
-            println!(
-                "  {} ({}) in {}",
-                item.name.cyan(),
-                type_str.yellow(),
+    /// Print one result of the listing; `nested` ones are under a header
+    /// naming their parent, crate and path, so leave those out.
+    fn print_item(
+        &self,
+        item: &SearchItem,
+        search: &Search,
+        found: &Found,
+        nested: bool,
+        out: &mut dyn Write,
+    ) -> io::Result<()> {
+        let indent = if nested { "    " } else { "  " };
+        let type_str = item.item_type.to_string().yellow().to_string();
+        // The type's size: its members, and a trait's implementations
+        let mut size: Vec<String> = self
+            .members
+            .as_ref()
+            .and_then(|members| members.of(item).summary(item.item_type))
+            .into_iter()
+            .collect();
+        if let Some(&implementors) = found.implementors.get(&item.id) {
+            size.push(count(implementors, "implementor", "implementors"));
+        }
+        let type_str = if size.is_empty() {
+            type_str
+        } else {
+            format!("{}, {}", type_str, size.join(", ").dimmed())
+        };
+        let name = match &self.hyperlinks {
+            Some(hyperlinks) => hyperlinks.link(item.name.cyan(), item),
+            None => item.name.cyan().to_string(),
+        };
+        if nested {
+            writeln!(out, "{}{} ({})", indent, name, type_str)?;
+        } else {
+            writeln!(
+                out,
+                "{}{} ({}) in {}",
+                indent,
+                name,
+                type_str,
                 item.crate_name.dimmed()
-            );
-            if !item.path.is_empty() {
-                println!("    at {}", item.path.dimmed());
+            )?;
+            let std_path = self
+                .std_paths
+                .as_ref()
+                .and_then(|std_paths| std_paths.module_path(item));
+            match std_path {
+                Some(std_path) => writeln!(
+                    out,
+                    "{}  at {} {}",
+                    indent,
+                    std_path.dimmed(),
+                    format!("(defined in {})", item.path).dimmed()
+                )?,
+                None if !item.path.is_empty() => {
+                    writeln!(out, "{}  at {}", indent, item.path.dimmed())?
+                }
+                None => {}
             }
         }
+        if let Some(origin) = impl_origin(item) {
+            writeln!(out, "{}  from {}", indent, origin.to_string().dimmed())?;
+        }
+        if let Some(terms) = found.via_aliases.get(&item.id) {
+            let terms: Vec<String> = terms.iter().map(|term| format!("`{}`", term)).collect();
+            writeln!(
+                out,
+                "{}  {}",
+                indent,
+                format!("matched alias {}", terms.join(", ")).dimmed()
+            )?;
+        }
+        if let (Search::ReturnType(_), Some(signatures)) = (search, &self.signatures)
+            && let Some(signature) = docs::signature_of(signatures, item)
+        {
+            let output: Vec<String> = signature.output.iter().map(ToString::to_string).collect();
+            writeln!(out, "{}  -> {}", indent, output.join(", ").dimmed())?;
+        }
+        if let Some(required) = found.gates.get(&item.id) {
+            let required: Vec<String> = required
+                .iter()
+                .map(|feature| format!("`{}`", feature))
+                .collect();
+            writeln!(
+                out,
+                "{}  {}",
+                indent,
+                format!("requires feature {}", required.join(", ")).yellow()
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// An entry of the terminal listing
+#[derive(Debug, PartialEq)]
+enum Listed<'a> {
+    Item(&'a SearchItem),
+    /// Several results with the same parent (methods of one type, say),
+    /// listed together under it
+    Members(Vec<&'a SearchItem>),
+}
+
+/// `results` in listing order: results sharing a parent with another are
+/// gathered where the first of them is, and the rest are left as they are.
+fn group_by_parent<'a>(results: &[&'a SearchItem]) -> Vec<Listed<'a>> {
+    type Parent<'a> = (&'a str, &'a str, &'a str);
+    let parent = |item: &'a SearchItem| -> Option<Parent<'a>> {
+        Some((
+            item.crate_name.as_str(),
+            item.path.as_str(),
+            item.parent_name()?,
+        ))
+    };
+
+    let mut counts: HashMap<Parent, usize> = HashMap::new();
+    for item in results {
+        if let Some(parent) = parent(item) {
+            *counts.entry(parent).or_default() += 1;
+        }
+    }
+    let mut listed = Vec::new();
+    let mut positions: HashMap<Parent, usize> = HashMap::new();
+    for &item in results {
+        match parent(item).filter(|parent| counts[parent] > 1) {
+            Some(parent) => match positions.get(&parent) {
+                Some(&position) => {
+                    if let Listed::Members(members) = &mut listed[position] {
+                        members.push(item);
+                    }
+                }
+                None => {
+                    positions.insert(parent, listed.len());
+                    listed.push(Listed::Members(vec![item]));
+                }
+            },
+            None => listed.push(Listed::Item(item)),
+        }
+    }
+    listed
+}
+
+/// Scan rustdocs for items matching a query
+pub fn execute(symbol: &str, options: &ScanOptions, source: &DocSource, quiet: bool) -> Result<()> {
+    let search = Search::parse(symbol)?;
+    // Formatted output on stdout must not be mixed with status messages
+    let quiet = quiet || (options.format.is_some() && options.output.is_none());
+
+    if !quiet {
+        println!(
+            "{} Scanning for symbol: {}",
+            "→".cyan().bold(),
+            symbol.green().bold()
+        );
+    }
+
+    let mut timings = Timings::default();
+    let start = Instant::now();
+    let search_index_path = docs::find_search_index(source, quiet, true)?;
+    timings.locate = start.elapsed();
+    if options.use_daemon && answerable(symbol, options) {
+        let hyperlinks = options.format.is_none() && hyperlink::enabled();
+        if let Some(answer) = daemon::scan(&search_index_path, source, hyperlinks)? {
+            return print_answer(symbol, options, answer, &search_index_path, &timings);
+        }
     }
+    let start = Instant::now();
+    let json_string = docs::load_search_index_json(&search_index_path)?;
+    timings.extract = start.elapsed();
+    let start = Instant::now();
+    let crate_entries = docs::crate_entries(&json_string)?;
+    timings.parse = start.elapsed();
+    if options.format == Some(Format::Ndjson) && options.output.is_none() {
+        stream(
+            symbol,
+            &search,
+            &crate_entries,
+            &search_index_path,
+            options,
+            source,
+            &mut timings,
+        )?;
+        if options.timings {
+            report_timings(&timings, false);
+        }
+        return Ok(());
+    }
+    let start = Instant::now();
+    let mut aliases = Aliases::new(&options.aliases);
+    let all_items: arena::Index =
+        docs::decode_items_with_aliases(&crate_entries, source, &mut aliases).into();
+    timings.decode = start.elapsed();
+
+    let mut scanner = Scanner::new(
+        options,
+        source,
+        &search_index_path,
+        &crate_entries,
+        &all_items,
+        &aliases,
+        dependency_graph(options, source)?,
+    );
+    scanner.timings = timings;
+    let found = scanner.search(&search)?;
+    debug!(
+        "{} of {} items matched \"{}\"",
+        found.results.len(),
+        all_items.len(),
+        symbol
+    );
+    results_ready(symbol, found.results.len());
+
+    match &options.output {
+        Some(path) => write_results_file(&mut scanner, symbol, &found, path, quiet),
+        None => print_results(&mut scanner, symbol, &search, &found, &search_index_path),
+    }
+}
 
+/// Print a daemon's `answer` to the scan for `symbol`, as this process would
+/// have printed its own results.
+fn print_answer(
+    symbol: &str,
+    options: &ScanOptions,
+    answer: ScanAnswer,
+    search_index_path: &Path,
+    timings: &Timings,
+) -> Result<()> {
+    results_ready(symbol, answer.results);
+    let mut out = std::io::stdout().lock();
+    out.write_all(answer.output.as_bytes())
+        .and_then(|()| out.flush())
+        .wrap_err("Failed to write results")?;
+    if answer.results == 0 && options.suggest_deps && options.format.is_none() {
+        let json_string = docs::load_search_index_json(search_index_path)?;
+        let crate_entries = docs::crate_entries(&json_string)?;
+        print_suggestions(symbol, &crate_entries, search_index_path);
+    }
+    if options.timings {
+        let daemon_timings = answer.timings.unwrap_or_default();
+        report_timings(
+            &Timings {
+                locate: timings.locate,
+                ..daemon_timings
+            },
+            true,
+        );
+    }
+    Ok(())
+}
+
+/// Print `found`, the results for `symbol`, suggesting crates that have it
+/// when there are none.
+fn print_results(
+    scanner: &mut Scanner,
+    symbol: &str,
+    search: &Search,
+    found: &Found,
+    search_index_path: &Path,
+) -> Result<()> {
+    let options = scanner.options;
+    scanner.write(symbol, search, found, &mut std::io::stdout().lock())?;
+    if found.results.is_empty() && options.suggest_deps && options.format.is_none() {
+        print_suggestions(symbol, scanner.crate_entries, search_index_path);
+    }
+    if options.timings {
+        report_timings(&scanner.timings, false);
+    }
     Ok(())
 }
+
+/// Write `found`, the results for `symbol`, to `path` in `--format`'s format.
+fn write_results_file(
+    scanner: &mut Scanner,
+    symbol: &str,
+    found: &Found,
+    path: &Path,
+    quiet: bool,
+) -> Result<()> {
+    let options = scanner.options;
+    let start = Instant::now();
+    let format = options.format.clone().unwrap_or_default();
+    let mut contents = Vec::new();
+    scanner
+        .formatter(&format)
+        .format(&found.results, &mut contents)
+        .wrap_err_with(|| format!("Failed to format results as {}", format))?;
+    output::write_atomic(path, contents)
+        .wrap_err_with(|| format!("Failed to write results to {}", path.display()))?;
+    scanner.timings.render += start.elapsed();
+    if options.timings {
+        report_timings(&scanner.timings, false);
+    }
+    if !quiet {
+        println!(
+            "{} Wrote {} for \"{}\" to {}",
+            "✓".green().bold(),
+            count(found.results.len(), "result", "results"),
+            symbol,
+            path.display().to_string().cyan()
+        );
+    }
+    Ok(())
+}
+
+/// Suggest `cargo add` for crates that provide `symbol`, which the docs at
+/// `search_index_path` don't have.
+fn print_suggestions(symbol: &str, crate_entries: &[LazyCrateEntry], search_index_path: &Path) {
+    let documented: Vec<&str> = crate_entries.iter().map(|entry| &*entry.name).collect();
+    let cached_items = cached_items_named(symbol, search_index_path);
+    let suggestions = suggest_dependencies(symbol, &cached_items, &documented);
+    if suggestions.is_empty() {
+        return;
+    }
+    println!(
+        "\n{} {} \"{}\", not a dependency yet:",
+        "ℹ".blue().bold(),
+        if suggestions.len() == 1 {
+            "A crate providing"
+        } else {
+            "Crates providing"
+        },
+        symbol
+    );
+    for suggestion in &suggestions {
+        let basis = match &suggestion.item {
+            Some(item) => format!("has {} (cached docs)", item),
+            None => "commonly used for it".to_string(),
+        };
+        println!(
+            "  cargo add {}  {}",
+            suggestion.crate_name.cyan(),
+            basis.dimmed()
+        );
+    }
+}
+
+/// Items named like `symbol` in the docs cached for other sites with
+/// `--doc-url`. Only crates whose compact data mentions the name are decoded,
+/// and sites that can't be read are skipped.
+fn cached_items_named(symbol: &str, search_index_path: &Path) -> Vec<SearchItem> {
+    let Ok(dir) = cache::cache_dir() else {
+        return Vec::new();
+    };
+    let name = symbol_name(symbol).to_ascii_lowercase();
+    let current = search_index_path.canonicalize().ok();
+    let mut items = Vec::new();
+    for site in std::fs::read_dir(dir.join("remote"))
+        .into_iter()
+        .flatten()
+        .flatten()
+    {
+        let path = site.path().join(SEARCH_INDEX_FILE);
+        if !path.is_file() || path.canonicalize().ok() == current {
+            continue;
+        }
+        let Ok(json) = IndexFile::open(&path).and_then(IndexJson::extract) else {
+            debug!("skipping unreadable cached index {}", path.display());
+            continue;
+        };
+        let Ok(entries) = parse_search_index_lazy(&json) else {
+            continue;
+        };
+        for entry in &entries {
+            if !entry.raw_data.get().to_ascii_lowercase().contains(&name) {
+                continue;
+            }
+            if let Ok(data) = entry.data() {
+                items.extend(decode_crate(&entry.name, &data));
+            }
+        }
+    }
+    items
+}
+
+/// Print how long each phase of a query took on stderr, where it stays out of
+/// results piped elsewhere; `answered` when a running `rdoc serve` answered,
+/// so decoding took no time here.
+fn report_timings(timings: &Timings, answered: bool) {
+    eprintln!(
+        "\n{} Timings{}:",
+        "ℹ".blue().bold(),
+        if answered {
+            " (answered by a running rdoc serve)"
+        } else {
+            ""
+        }
+    );
+    for line in timings.to_string().lines() {
+        eprintln!("  {}", line);
+    }
+}
+
+/// Whether a running `rdoc serve` can answer `symbol` as [`execute`] would:
+/// it doesn't write files or run formatters for others, and `-> Type` queries
+/// need the signatures it doesn't keep.
+pub(crate) fn answerable(symbol: &str, options: &ScanOptions) -> bool {
+    options.output.is_none()
+        && !matches!(options.format, Some(Format::Exec(_)))
+        && TypePattern::parse_return_query(symbol).is_none()
+}
+
+/// What a running `rdoc serve` has decoded, to answer the scans sent to it
+pub(crate) struct Decoded<'a> {
+    pub search_index_path: &'a Path,
+    pub items: &'a arena::Index,
+    pub aliases: &'a Aliases,
+    /// The project's dependency graph, read when the daemon started
+    pub graph: Option<&'a CrateGraph>,
+}
+
+/// Answer an [`answerable`] `symbol` from docs a running `rdoc serve` has
+/// decoded, writing to `out` what [`execute`] prints after its status
+/// messages, with hyperlinks if asked for; the number of results and how long
+/// matching, ranking and writing them took.
+pub(crate) fn answer(
+    symbol: &str,
+    options: &ScanOptions,
+    source: &DocSource,
+    decoded: &Decoded,
+    hyperlinks: bool,
+    out: &mut dyn Write,
+) -> Result<(usize, Timings)> {
+    let search = Search::parse(symbol)?;
+    let graph = match decoded.graph {
+        Some(graph) => Some(graph.clone()),
+        // Fails as it would for the client
+        None if options.direct_deps_only => dependency_graph(options, source)?,
+        None => None,
+    };
+    let mut scanner = Scanner::new(
+        options,
+        source,
+        decoded.search_index_path,
+        &[],
+        decoded.items,
+        decoded.aliases,
+        graph,
+    );
+    scanner.hyperlinks = (hyperlinks && options.format.is_none())
+        .then(|| Hyperlinks::new(source, decoded.search_index_path));
+    let found = scanner.search(&search)?;
+    results_ready(symbol, found.results.len());
+    scanner.write(symbol, &search, &found, out)?;
+    Ok((found.results.len(), scanner.timings))
+}
+
+/// Run one query per line of stdin against an index decoded once, writing each
+/// query's results as a group: in the colored listing, one JSON object per line
+/// with `--format ndjson`, or a single document with `--format json`.
+///
+/// Blank lines and lines starting with `#` are skipped. A query that fails
+/// (an invalid pattern, say) is reported in its group rather than ending the batch.
+pub fn execute_batch(options: &ScanOptions, source: &DocSource, quiet: bool) -> Result<()> {
+    if !matches!(options.format, None | Some(Format::Json | Format::Ndjson)) {
+        bail!("--stdin writes the colored listing, --format json or --format ndjson");
+    }
+    let quiet = quiet || options.format.is_some();
+
+    let search_index_path = docs::find_search_index(source, quiet, true)?;
+    let json_string = docs::load_search_index_json(&search_index_path)?;
+    let crate_entries = docs::crate_entries(&json_string)?;
+    let mut aliases = Aliases::new(&options.aliases);
+    let all_items: arena::Index =
+        docs::decode_items_with_aliases(&crate_entries, source, &mut aliases).into();
+    let mut scanner = Scanner::new(
+        options,
+        source,
+        &search_index_path,
+        &crate_entries,
+        &all_items,
+        &aliases,
+        dependency_graph(options, source)?,
+    );
+    let links = if options.format.is_some() {
+        docs::site_links(source)
+    } else {
+        SiteLinks::default()
+    };
+
+    let mut groups = Vec::new();
+    let mut out = std::io::stdout().lock();
+    for line in std::io::stdin().lock().lines() {
+        let line = line.wrap_err("Failed to read queries from stdin")?;
+        let symbol = line.trim();
+        if symbol.is_empty() || symbol.starts_with('#') {
+            continue;
+        }
+        let found = Search::parse(symbol).and_then(|search| Ok((scanner.search(&search)?, search)));
+        if let Ok((found, _)) = &found {
+            results_ready(symbol, found.results.len());
+        }
+
+        let Some(format) = &options.format else {
+            match found {
+                Ok((found, search)) => scanner.write(symbol, &search, &found, &mut out)?,
+                Err(err) => writeln!(out, "\n{} \"{}\": {:#}", "✗".red().bold(), symbol, err)
+                    .wrap_err("Failed to write results")?,
+            }
+            continue;
+        };
+        let group = match found {
+            Ok((found, _)) => QueryResults::new(symbol, &found.results, &links),
+            Err(err) => QueryResults::failed(symbol, format!("{:#}", err)),
+        };
+        if *format == Format::Ndjson {
+            serde_json::to_writer(&mut out, &group).wrap_err("Failed to write results")?;
+            writeln!(out).wrap_err("Failed to write results")?;
+            out.flush().wrap_err("Failed to write results")?;
+        } else {
+            groups.push(group);
+        }
+    }
+
+    if options.format == Some(Format::Json) {
+        let batch = Versioned::new("BatchResults", BatchResults { queries: groups });
+        serde_json::to_writer_pretty(&mut out, &batch).wrap_err("Failed to write results")?;
+        writeln!(out).wrap_err("Failed to write results")?;
+    }
+    Ok(())
+}
+
+/// `--format ndjson` on stdout: decode one item at a time and write it as
+/// soon as it matches, instead of collecting every result first.
+///
+/// Re-exports are collapsed to the first occurrence found, which may not be the
+/// definition the other formats prefer. Decoding, matching and writing each
+/// add to `timings`; nothing is ranked.
+fn stream(
+    symbol: &str,
+    search: &Search,
+    crate_entries: &[LazyCrateEntry],
+    search_index_path: &Path,
+    options: &ScanOptions,
+    source: &DocSource,
+    timings: &mut Timings,
+) -> Result<()> {
+    let source_id: Arc<str> = Arc::from(source.id());
+    let mut filter = StreamFilter {
+        search,
+        options,
+        matchers: match search {
+            Search::Names(query) => term_matchers(query, options)?,
+            Search::ReturnType(_) => HashMap::new(),
+        },
+        store: DescriptionStore::new(search_index_path.parent().unwrap_or(Path::new("."))),
+        pages: Pages::new(source, search_index_path),
+        reexports: ReexportFilter::default(),
+        graph: dependency_graph(options, source)?.filter(|_| options.direct_deps_only),
+        scope_exists: false,
+    };
+    let mut out = std::io::stdout().lock();
+    let mut written = 0;
+    let ndjson = NdjsonFormatter {
+        links: docs::site_links(source),
+    };
+
+    for entry in crate_entries {
+        let start = Instant::now();
+        let data = docs::crate_entry_data(entry);
+        timings.decode += start.elapsed();
+        let Some(data) = data else {
+            continue;
+        };
+        let mut aliases = Aliases::new(&options.aliases);
+        let mut signatures = Vec::new();
+        match search {
+            Search::Names(_) => aliases.add_crate(&source_id, &entry.name, &data),
+            Search::ReturnType(_) => signatures = docs::crate_signatures(&data),
+        }
+
+        let mut decoded = docs::crate_items(&entry.name, &data, &source_id);
+        let keep = |item: &SearchItem| filter.keeps(item, &aliases, &signatures);
+        let write = |item: &SearchItem| {
+            ndjson
+                .format(&[item], &mut out)
+                .wrap_err("Failed to write results")
+        };
+        written += write_as_decoded(&mut decoded, keep, write, timings)?;
+        docs::record_diagnostics(&entry.name, decoded);
+
+        let start = Instant::now();
+        out.flush().wrap_err("Failed to write results")?;
+        timings.render += start.elapsed();
+    }
+
+    if let Some(scope) = &options.within
+        && written == 0
+        && !filter.scope_exists
+    {
+        return Err(unknown_scope(scope));
+    }
+    results_ready(symbol, written);
+    Ok(())
+}
+
+/// What [`stream`] checks each decoded item against: the search, and the
+/// filters `scan` applies to its results
+struct StreamFilter<'a> {
+    search: &'a Search,
+    options: &'a ScanOptions,
+    matchers: HashMap<&'a str, Box<dyn Matcher>>,
+    store: DescriptionStore,
+    pages: Pages<'a>,
+    reexports: ReexportFilter,
+    /// The dependency graph, when only direct dependencies are kept
+    graph: Option<CrateGraph>,
+    /// Whether any item so far was within `--within`'s scope
+    scope_exists: bool,
+}
+
+impl StreamFilter<'_> {
+    /// Whether to write `item`, from a crate with `aliases` and `signatures`
+    /// (decoded for return type searches).
+    fn keeps(
+        &mut self,
+        item: &SearchItem,
+        aliases: &Aliases,
+        signatures: &[Option<FunctionSignature>],
+    ) -> Result<bool> {
+        let options = self.options;
+        if !docs::is_kept(item) {
+            return Ok(false);
+        }
+        let is_match = match self.search {
+            Search::Names(query) => matches_query(
+                query,
+                item,
+                &self.matchers,
+                |term| self.matchers[term].matches_item(item) || aliases.names(item, term),
+                &mut self.store,
+                options,
+            ),
+            Search::ReturnType(pattern) => signatures
+                .get(item.id.index)
+                .and_then(Option::as_ref)
+                .is_some_and(|sig| sig.returns(pattern)),
+        };
+        if let Some(scope) = &options.within {
+            self.scope_exists |= item.is_within(scope);
+            if !item.is_within(scope) {
+                return Ok(false);
+            }
+        }
+        if !is_match || options.excludes(item) || options.hides(item) {
+            return Ok(false);
+        }
+        if !options.show_reexports && !self.reexports.is_first(item) {
+            return Ok(false);
+        }
+        if self
+            .graph
+            .as_ref()
+            .is_some_and(|graph| !is_direct(graph, item))
+        {
+            return Ok(false);
+        }
+        if !options.features.is_empty()
+            && !self
+                .pages
+                .required_features(item)?
+                .iter()
+                .any(|feature| options.features.contains(feature))
+        {
+            return Ok(false);
+        }
+        if let Some(platform) = &options.target_platform
+            && !is_available(&mut self.pages, item, platform)?
+        {
+            return Ok(false);
+        }
+        Ok(true)
+    }
+}
+
+/// Pull items from `items` one at a time, writing each `keep` accepts before
+/// the next is decoded, and return how many were written. Time spent in each
+/// step adds to `timings`.
+fn write_as_decoded(
+    items: &mut dyn Iterator<Item = SearchItem>,
+    mut keep: impl FnMut(&SearchItem) -> Result<bool>,
+    mut write: impl FnMut(&SearchItem) -> Result<()>,
+    timings: &mut Timings,
+) -> Result<usize> {
+    let mut written = 0;
+    loop {
+        let start = Instant::now();
+        let item = items.next();
+        timings.decode += start.elapsed();
+        let Some(item) = item else {
+            return Ok(written);
+        };
+
+        let start = Instant::now();
+        let kept = keep(&item)?;
+        timings.matching += start.elapsed();
+        if !kept {
+            continue;
+        }
+        let start = Instant::now();
+        write(&item)?;
+        timings.render += start.elapsed();
+        written += 1;
+    }
+}
+
+/// Log the `results_ready` progress event for a query.
+fn results_ready(symbol: &str, results: usize) {
+    info!(
+        target: EVENT_TARGET,
+        event = "results_ready",
+        query = symbol,
+        results
+    );
+}
+
+/// The project's dependency graph for ranking results, or `None` when there
+/// isn't one and --direct-deps-only doesn't need it.
+pub(crate) fn dependency_graph(
+    options: &ScanOptions,
+    source: &DocSource,
+) -> Result<Option<CrateGraph>> {
+    if !source.is_cargo_project() {
+        if options.direct_deps_only {
+            bail!("--direct-deps-only needs a local cargo project, not --doc-url or --doc-dir");
+        }
+        return Ok(None);
+    }
+    match docs::crate_graph() {
+        Ok(graph) => Ok(Some(graph)),
+        Err(err) if options.direct_deps_only => {
+            Err(err.wrap_err("--direct-deps-only needs the project's dependency graph"))
+        }
+        Err(err) => {
+            debug!("not ranking results by dependency: {:#}", err);
+            Ok(None)
+        }
+    }
+}
+
+/// Whether `item` is from a workspace crate or one of their direct dependencies.
+fn is_direct(graph: &CrateGraph, item: &SearchItem) -> bool {
+    graph
+        .relation(&item.crate_name)
+        .is_some_and(|relation| relation <= Relation::Direct)
+}
+
+/// How many items [`match_query`] matches between checks for cancellation;
+/// a check reads the clock, so not on every item
+const CANCEL_CHECK_INTERVAL: usize = 1024;
+
+fn unknown_scope(scope: &str) -> Report {
+    eyre!("No module or type \"{}\" in the search index", scope)
+}
+
+/// Items whose name or alias (or, with --descriptions, description) matches
+/// `query`, best matches first when the matcher ranks them.
+///
+/// Fails with [`Cancelled`](rdoc::cancel::Cancelled) once `cancel` says to stop, checked every
+/// [`CANCEL_CHECK_INTERVAL`] items.
+pub(crate) fn match_query<'a>(
+    query: &Query,
+    index: &'a arena::Index,
+    aliases: &Aliases,
+    store: &mut DescriptionStore,
+    options: &ScanOptions,
+    cancel: &CancelToken,
+) -> Result<Vec<&'a SearchItem>> {
+    let mut results = match_unranked(query, index, aliases, store, options, cancel)?;
+    rank_by_score(query, &mut results, options, cancel)?;
+    Ok(results)
+}
+
+/// Each of `query`'s terms with the matcher `options` ask for.
+fn term_matchers<'q>(
+    query: &'q Query,
+    options: &ScanOptions,
+) -> Result<HashMap<&'q str, Box<dyn Matcher>>> {
+    query
+        .terms()
+        .into_iter()
+        .map(|term| {
+            options
+                .matcher
+                .matcher(term, options.case)
+                .map(|matcher| (term, matcher))
+                .wrap_err_with(|| format!("Invalid pattern \"{}\"", term))
+        })
+        .collect()
+}
+
+/// [`match_query`]'s matches in index order.
+fn match_unranked<'a>(
+    query: &Query,
+    index: &'a arena::Index,
+    aliases: &Aliases,
+    store: &mut DescriptionStore,
+    options: &ScanOptions,
+    cancel: &CancelToken,
+) -> Result<Vec<&'a SearchItem>> {
+    // Each query term matches the name (as a substring or glob) and, with
+    // --descriptions, the description text
+    let matchers = term_matchers(query, options)?;
+    // Narrow each term down to the names it could match in one pass over all
+    // of them, so the matcher itself (which may be fuzzy scoring) runs on few items
+    let candidates: HashMap<&str, Option<Candidates>> = matchers
+        .iter()
+        .map(|(&term, matcher)| (term, matcher.candidates(index.names())))
+        .collect();
+    // A term that is an alias also matches the items it names
+    let aliased: HashMap<&str, HashSet<&ItemId>> = query
+        .terms()
+        .into_iter()
+        .map(|term| {
+            let targets = aliases.targets(index, term);
+            (term, targets.into_iter().map(|item| &item.id).collect())
+        })
+        .collect();
+    cancel.check()?;
+    let mut results = Vec::new();
+    for (item_ref, item) in index.refs().zip(index) {
+        let position = item_ref.position();
+        if position % CANCEL_CHECK_INTERVAL == 0 {
+            cancel.check()?;
+        }
+        let name_match = |term: &str| {
+            let name_match = candidates[term]
+                .as_ref()
+                .is_none_or(|candidates| candidates.contains(position))
+                && matchers[term].matches_item(item);
+            name_match || aliased[term].contains(&item.id)
+        };
+        if matches_query(query, item, &matchers, name_match, store, options) {
+            results.push(item);
+        }
+    }
+    Ok(results)
+}
+
+/// Whether `item` matches `query`, each term matching when `name_match` says
+/// it matches the name or an alias, or with --descriptions, when it appears
+/// in the description.
+fn matches_query(
+    query: &Query,
+    item: &SearchItem,
+    matchers: &HashMap<&str, Box<dyn Matcher>>,
+    name_match: impl Fn(&str) -> bool,
+    store: &mut DescriptionStore,
+    options: &ScanOptions,
+) -> bool {
+    let mut description = None;
+    query.matches(&mut |term| {
+        if name_match(term) {
+            return true;
+        }
+        if !options.descriptions {
+            return false;
+        }
+        let description =
+            description.get_or_insert_with(|| store.get(item).map(plain_text).unwrap_or_default());
+        matchers[term].matches_text(description)
+    })
+}
+
+/// Put the best matches of `query` first, keeping index order among equal
+/// scores, when the matcher ranks them. A single word matched the rustdoc way
+/// is ordered as rustdoc's web search orders it.
+fn rank_by_score(
+    query: &Query,
+    results: &mut [&SearchItem],
+    options: &ScanOptions,
+    cancel: &CancelToken,
+) -> Result<()> {
+    if let Query::Term(term) = query
+        && options.matcher == MatcherKind::Rustdoc
+        && !Glob::is_glob(term)
+    {
+        cancel.check()?;
+        results.sort_by_cached_key(|item| RustdocRank::new(term, item));
+        return Ok(());
+    }
+    if !options.matcher.ranks() {
+        return Ok(());
+    }
+    let matchers = term_matchers(query, options)?;
+    cancel.check()?;
+    results.sort_by_cached_key(|item| {
+        let score: u32 = matchers.values().map(|matcher| matcher.score(item)).sum();
+        std::cmp::Reverse(score)
+    });
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rdoc::search_index::{extract_json_string, parse_search_index};
+    use rdoc::search_items::{Parent, Parents, decode_crate_iter};
+
+    #[test]
+    fn test_profiles() {
+        let mut options = ScanOptions::default();
+        Profile::Strict.apply(&mut options);
+        assert_eq!(options.case, CaseMode::Sensitive);
+        assert!(options.show_reexports);
+
+        Profile::Docs.apply(&mut options);
+        assert_eq!(options.case, CaseMode::Insensitive);
+        assert!(options.descriptions);
+        assert!(!options.show_reexports);
+    }
+
+    #[test]
+    fn test_parse_kind() {
+        assert_eq!(parse_kind("impl"), Ok(&[ItemType::Impl][..]));
+        assert_eq!(parse_kind("struct"), Ok(&[ItemType::Struct][..]));
+        assert_eq!(parse_kind("tymethod"), Ok(&[ItemType::TyMethod][..]));
+        assert_eq!(parse_kind("use"), parse_kind("import"));
+        assert!(parse_kind("widget").is_err());
+
+        let options = ScanOptions {
+            exclude_types: vec![ItemType::Import],
+            exclude: IgnoreRules::excluding(&["syn".to_string()], &[]).unwrap(),
+            ..ScanOptions::default()
+        };
+        let item = |crate_name: &str, item_type| SearchItem {
+            crate_name: crate_name.to_string(),
+            item_type,
+            name: "Ident".to_string(),
+            normalized_name: "ident".to_string(),
+            path: crate_name.to_string(),
+            exact_path: crate_name.to_string(),
+            id: ItemId::new(ItemId::LOCAL_SOURCE, crate_name, 0),
+            param_types: vec![],
+            impl_disambiguator: None,
+            bit_index: 1,
+            parent_index: None,
+            parents: Default::default(),
+            deprecated: false,
+            empty_description: false,
+            description_location: None,
+        };
+        assert!(options.excludes(&item("syn", ItemType::Struct)));
+        assert!(options.excludes(&item("proc_macro2", ItemType::Import)));
+        assert!(!options.excludes(&item("proc_macro2", ItemType::Struct)));
+    }
+
+    #[test]
+    fn test_group_by_parent() {
+        let item = |name: &str, parent: Option<&str>| SearchItem {
+            crate_name: "serde".to_string(),
+            item_type: if parent.is_some() {
+                ItemType::TyMethod
+            } else {
+                ItemType::Trait
+            },
+            name: name.to_string(),
+            normalized_name: name.to_lowercase(),
+            path: "serde::de".to_string(),
+            exact_path: "serde::de".to_string(),
+            id: ItemId::new(ItemId::LOCAL_SOURCE, "serde", 0),
+            param_types: vec![],
+            impl_disambiguator: None,
+            bit_index: 1,
+            parent_index: parent.map(|_| 0),
+            parents: Parents::new(
+                parent
+                    .map(|name| Parent {
+                        name: name.to_string(),
+                        item_type: ItemType::Trait,
+                    })
+                    .into_iter()
+                    .collect(),
+            ),
+            deprecated: false,
+            empty_description: false,
+            description_location: None,
+        };
+        let items = [
+            item("deserialize_any", Some("Deserializer")),
+            item("Deserializer", None),
+            item("visit_str", Some("Visitor")),
+            item("deserialize_str", Some("Deserializer")),
+        ];
+        let results: Vec<&SearchItem> = items.iter().collect();
+
+        assert_eq!(
+            group_by_parent(&results),
+            [
+                Listed::Members(vec![&items[0], &items[3]]),
+                Listed::Item(&items[1]),
+                // Alone under its parent, so not grouped
+                Listed::Item(&items[2]),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_stream_writes_before_crate_is_decoded() {
+        let content = std::fs::read_to_string("tests/fixtures/search-index.js").unwrap();
+        let entries = parse_search_index(&extract_json_string(&content).unwrap()).unwrap();
+        let data = &entries
+            .iter()
+            .find(|entry| entry.name == "libc")
+            .unwrap()
+            .data;
+
+        // How many items had been decoded when each was written
+        let decoded = std::cell::Cell::new(0);
+        let mut items = decode_crate_iter("libc", data).inspect(|_| decoded.set(decoded.get() + 1));
+        let mut decoded_when_written = Vec::new();
+        let written = write_as_decoded(
+            &mut items,
+            |item| Ok(item.name.contains("errno")),
+            |_| {
+                decoded_when_written.push(decoded.get());
+                Ok(())
+            },
+            &mut Timings::default(),
+        )
+        .unwrap();
+
+        assert_eq!(written, decoded_when_written.len());
+        assert!(decoded_when_written[0] < data.item_count());
+        assert!(decoded_when_written.is_sorted());
+        assert_eq!(decoded.get(), data.item_count());
+    }
+}
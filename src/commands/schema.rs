@@ -0,0 +1,15 @@
+use color_eyre::Result;
+use rdoc::openapi::openapi_spec;
+use rdoc::schema::output_schema;
+
+/// Print the JSON Schema of rdoc's JSON output formats, or with `openapi`, the
+/// OpenAPI document of `serve`
+pub fn execute(openapi: bool) -> Result<()> {
+    let document = if openapi {
+        openapi_spec()
+    } else {
+        output_schema()
+    };
+    println!("{}", serde_json::to_string_pretty(&document)?);
+    Ok(())
+}
@@ -0,0 +1,1073 @@
+// Serving lookups over HTTP from an index decoded once, and again when the docs change
+
+use arc_swap::ArcSwap;
+use color_eyre::{Result, eyre::eyre};
+use colored::Colorize;
+use rdoc::aliases::Aliases;
+use rdoc::arena;
+use rdoc::cancel::CancelToken;
+use rdoc::crate_graph::CrateGraph;
+use rdoc::descriptions::{DEFAULT_CACHED_SHARDS, DescriptionStore, plain_text};
+use rdoc::format::{ItemPreview, QueryResults, Resolution, Resolutions, ResultRecord};
+use rdoc::human::count;
+use rdoc::item_id::ItemId;
+use rdoc::openapi::openapi_spec;
+use rdoc::pages::{SiteLinks, declaration, impl_origin, item_page};
+use rdoc::query::Query;
+use rdoc::resolve::resolve;
+use rdoc::schema::Versioned;
+use rdoc::search_items::{SearchItem, dedup_reexports};
+use serde::Serialize;
+use serde_json::json;
+use std::collections::{BTreeMap, HashMap};
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, Sender};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime};
+use tiny_http::{Header, Request, Response, Server};
+use tracing::{info, warn};
+
+use crate::commands::scan::{self, Decoded, ScanOptions, dependency_graph, match_query};
+use crate::config::Config;
+use crate::daemon::{self, Registration, ScanAnswer, ScanRequest, Settings};
+use crate::docs::{self, DecodeCache, DocSource, Pages};
+
+/// Largest request body read, so a runaway client can't exhaust memory
+const MAX_BODY_BYTES: u64 = 4 * 1024 * 1024;
+
+/// Results `/search` answers with unless the request asks for a `limit`
+const DEFAULT_SEARCH_LIMIT: usize = 50;
+
+/// Serve the endpoints on `addr` until the process is stopped:
+///
+/// - `GET /`: a web UI searching the index and previewing items
+/// - `GET /search?q=QUERY&limit=N&timeout=MS`: a `QueryResults` document,
+///   matching `QUERY` as the `scan` command does. A search stops early when
+///   it runs past `search_timeout` (or the request's `timeout`), or when the
+///   same client sends another search, as the web UI does on every keystroke
+/// - `GET /item?id=ID`: an `ItemPreview` document for the item with that ID
+/// - `POST /resolve`: a JSON array of paths in, a `Resolutions` document out,
+///   resolving each path as the `resolve` command does
+/// - `GET /docs/FILE`: the local docs' files, so pages open without a
+///   published site
+/// - `POST /scan`: a `scan` command line in, what `scan` would print out;
+///   `scan` sends its queries here when it finds this server running for the
+///   same docs, as it's recorded in the cache while serving
+/// - `POST /reload`: decode the docs again in the background, as is done
+///   when the search index file changes (checked every `reload_interval`)
+/// - `GET /openapi.json`: the OpenAPI document describing these endpoints
+///
+/// Requests are answered on `threads` workers at once, all from the same
+/// decoded index; a reload swaps in a new one without stopping them.
+pub fn execute(
+    addr: &str,
+    options: &ServeOptions,
+    config: &Config,
+    source: &DocSource,
+    quiet: bool,
+) -> Result<()> {
+    let search_index_path = docs::find_search_index(source, quiet, true)?;
+    let mut decoded = DecodeCache::default();
+    let index = Index::load(&search_index_path, 0, &config.aliases, source, &mut decoded)?;
+    let doc_root = search_index_path
+        .parent()
+        .unwrap_or(Path::new("."))
+        .to_path_buf();
+
+    let server =
+        Server::http(addr).map_err(|err| eyre!("Failed to listen on {}: {}", addr, err))?;
+    if !quiet {
+        println!(
+            "{} Serving {} from {} on http://{} ({})",
+            "✓".green().bold(),
+            count(index.items.len(), "item", "items"),
+            count(index.crates, "crate", "crates"),
+            server.server_addr(),
+            count(options.threads, "thread", "threads")
+        );
+    }
+    let _registration = match server.server_addr().to_ip() {
+        Some(addr) => Some(Registration::new(&search_index_path, addr)?),
+        None => None,
+    };
+    // Scans are answered with colors, which their clients strip if they don't
+    // show them; the server itself prints nothing more
+    colored::control::set_override(true);
+
+    let scan_options = ScanOptions::from_config(config)?;
+    let shared = Shared {
+        index: ArcSwap::from_pointee(index),
+        source,
+        config,
+        // Read once, for ranking the results of every scan sent here
+        graph: dependency_graph(&scan_options, source)?,
+        options: scan_options,
+        links: docs::site_links(source),
+        doc_root: matches!(source, DocSource::Local { .. }).then_some(doc_root),
+        search_timeout: options.search_timeout,
+        settings: Settings::current(source),
+    };
+    // Requests are received on one thread, so a search can be cancelled while
+    // a worker is still running it, and answered on the others
+    let (sender, requests) = mpsc::channel();
+    let requests = Mutex::new(requests);
+    let (reloads, reload_requests) = mpsc::channel();
+    std::thread::scope(|scope| {
+        scope.spawn(|| receive(&server, sender));
+        let shared = &shared;
+        scope.spawn(move || reload(shared, options.reload_interval, reload_requests, decoded));
+        for _ in 0..options.threads.max(1) {
+            let reloads = reloads.clone();
+            scope.spawn(|| {
+                let mut api = Api::new(shared, reloads);
+                work(&requests, |(request, cancel)| {
+                    respond(&mut api, request, &cancel)
+                });
+            });
+        }
+    });
+    Ok(())
+}
+
+/// How `serve` answers and reloads
+#[derive(Debug, Clone)]
+pub struct ServeOptions {
+    /// Workers answering requests at once
+    pub threads: usize,
+    /// How long a search may take unless the request says; `None` is no limit
+    pub search_timeout: Option<Duration>,
+    /// How often to check whether the docs were regenerated, reloading them if
+    /// so; `None` only reloads on `POST /reload`
+    pub reload_interval: Option<Duration>,
+}
+
+/// The decoded index requests are answered from. It's never changed once
+/// built: a reload builds a new one and swaps it in, so queries already
+/// running on the old one finish undisturbed.
+struct Index {
+    /// Which load this is: 0 at startup, then one more each reload
+    epoch: u64,
+    search_index_path: PathBuf,
+    /// When the search index was written, to tell when the docs are regenerated
+    modified: Option<SystemTime>,
+    crates: usize,
+    items: arena::Index,
+    aliases: Aliases,
+}
+
+impl Index {
+    fn load(
+        search_index_path: &Path,
+        epoch: u64,
+        user_aliases: &BTreeMap<String, String>,
+        source: &DocSource,
+        decoded: &mut DecodeCache,
+    ) -> Result<Self> {
+        let modified = modified(search_index_path);
+        let json_string = docs::read_search_index_json(search_index_path)?;
+        let crate_entries = docs::crate_entries(&json_string)?;
+        let mut aliases = Aliases::new(user_aliases);
+        let items = docs::decode_items_cached(&crate_entries, source, &mut aliases, decoded);
+        Ok(Self {
+            epoch,
+            search_index_path: search_index_path.to_path_buf(),
+            modified,
+            crates: crate_entries.len(),
+            items,
+            aliases,
+        })
+    }
+}
+
+fn modified(path: &Path) -> Option<SystemTime> {
+    std::fs::metadata(path)
+        .and_then(|metadata| metadata.modified())
+        .ok()
+}
+
+/// What every worker shares
+struct Shared<'a> {
+    /// The current index, swapped whole on reload
+    index: ArcSwap<Index>,
+    source: &'a DocSource,
+    config: &'a Config,
+    /// The project's dependency graph, if it has one
+    graph: Option<CrateGraph>,
+    /// What the web UI's searches match with: `scan`'s, without flags
+    options: ScanOptions,
+    links: SiteLinks,
+    /// The doc root served under `/docs/`; `None` for remote sources, whose
+    /// pages are linked on their site instead
+    doc_root: Option<PathBuf>,
+    search_timeout: Option<Duration>,
+    /// What `scan` clients must share with the server to be answered here
+    settings: Settings,
+}
+
+/// Header naming the search session a `/search` request belongs to, like
+/// the `session` parameter
+const SESSION_HEADER: &str = "X-Rdoc-Session";
+
+/// Answer requests from `requests` with `answer` until the sender is gone.
+/// Workers share `requests`, so the lock is only held while waiting for the
+/// next one: answering it runs alongside the other workers.
+fn work<T>(requests: &Mutex<Receiver<T>>, mut answer: impl FnMut(T)) {
+    loop {
+        let next = requests.lock().unwrap().recv();
+        let Ok(request) = next else { break };
+        answer(request);
+    }
+}
+
+/// Pass `server`'s requests on with a token cancelling each, cancelling a
+/// search still running when another comes in for the same session.
+fn receive(server: &Server, requests: Sender<(Request, CancelToken)>) {
+    let mut searches: HashMap<String, CancelToken> = HashMap::new();
+    for request in server.incoming_requests() {
+        let cancel = CancelToken::new();
+        if let Some(session) = search_session(request.url(), request.headers())
+            && let Some(previous) = searches.insert(session, cancel.clone())
+        {
+            previous.cancel();
+        }
+        if requests.send((request, cancel)).is_err() {
+            break;
+        }
+    }
+}
+
+/// The session a `/search` request names, with its `session` parameter or
+/// [`SESSION_HEADER`]; `None` for other requests and searches without one,
+/// which nothing cancels. Clients pick their own session IDs, so several
+/// editors or tabs on one machine don't cancel each other's searches.
+fn search_session(url: &str, headers: &[Header]) -> Option<String> {
+    if url.split('?').next() != Some("/search") {
+        return None;
+    }
+    query_param(url, "session")
+        .or_else(|| {
+            headers
+                .iter()
+                .find(|header| header.field.equiv(SESSION_HEADER))
+                .map(|header| header.value.to_string())
+        })
+        .filter(|session| !session.is_empty())
+}
+
+/// Rebuild the index in the background when asked to, or when the search
+/// index file changes, checked every `interval`, then swap it in. Only the
+/// crates whose data changed since `decoded` was filled are decoded again.
+fn reload(
+    shared: &Shared,
+    interval: Option<Duration>,
+    requests: Receiver<()>,
+    mut decoded: DecodeCache,
+) {
+    loop {
+        let requested = match interval {
+            Some(interval) => match requests.recv_timeout(interval) {
+                Ok(()) => true,
+                Err(RecvTimeoutError::Timeout) => false,
+                Err(RecvTimeoutError::Disconnected) => return,
+            },
+            None => match requests.recv() {
+                Ok(()) => true,
+                Err(_) => return,
+            },
+        };
+        let current = shared.index.load();
+        if !requested && modified(&current.search_index_path) == current.modified {
+            continue;
+        }
+        // A remote source's index may have moved on, so it's looked up again
+        let loaded = docs::find_search_index(shared.source, true, false).and_then(|path| {
+            Index::load(
+                &path,
+                current.epoch + 1,
+                &shared.config.aliases,
+                shared.source,
+                &mut decoded,
+            )
+        });
+        match loaded {
+            Ok(index) => {
+                info!(
+                    "reloaded {} items from {} crates (epoch {}, {} crates cached)",
+                    index.items.len(),
+                    index.crates,
+                    index.epoch,
+                    decoded.len()
+                );
+                shared.index.store(Arc::new(index));
+            }
+            Err(err) => warn!(
+                "failed to reload the index, still serving the last one: {:#}",
+                err
+            ),
+        }
+    }
+}
+
+/// Answer one request.
+fn respond(api: &mut Api, mut request: Request, cancel: &CancelToken) {
+    let mut body = Vec::new();
+    let read = request
+        .as_reader()
+        .take(MAX_BODY_BYTES + 1)
+        .read_to_end(&mut body);
+    let reply = match read {
+        Ok(_) if body.len() as u64 > MAX_BODY_BYTES => Reply::error(
+            413,
+            format!("Request bodies are limited to {} bytes", MAX_BODY_BYTES),
+        ),
+        Ok(_) => api.handle(request.method().as_str(), request.url(), &body, cancel),
+        Err(err) => Reply::error(400, format!("Failed to read the request body: {}", err)),
+    };
+    info!("{} {} -> {}", request.method(), request.url(), reply.status);
+
+    let content_type =
+        Header::from_bytes("Content-Type", reply.content_type).expect("static header is valid");
+    let response = Response::from_data(reply.body)
+        .with_status_code(reply.status)
+        .with_header(content_type);
+    if let Err(err) = request.respond(response) {
+        warn!("failed to send a response: {}", err);
+    }
+}
+
+/// What one worker keeps between requests: the caches of the pages and
+/// descriptions it has read, for the index they were read for
+struct Api<'a> {
+    shared: &'a Shared<'a>,
+    /// Asks the reloader to reload
+    reloads: Sender<()>,
+    /// The index `pages` and `store` hold files of; they're dropped when the
+    /// index is reloaded, since the docs may have changed under them
+    epoch: u64,
+    pages: Pages<'a>,
+    store: DescriptionStore,
+}
+
+impl<'a> Api<'a> {
+    fn new(shared: &'a Shared<'a>, reloads: Sender<()>) -> Self {
+        let index = shared.index.load();
+        let (pages, store) = caches(shared, &index);
+        Self {
+            shared,
+            reloads,
+            epoch: index.epoch,
+            pages,
+            store,
+        }
+    }
+}
+
+/// Empty caches of `index`'s pages and descriptions.
+fn caches<'a>(shared: &Shared<'a>, index: &Index) -> (Pages<'a>, DescriptionStore) {
+    let doc_root = index.search_index_path.parent().unwrap_or(Path::new("."));
+    (
+        Pages::new(shared.source, &index.search_index_path),
+        DescriptionStore::with_capacity(doc_root, DEFAULT_CACHED_SHARDS),
+    )
+}
+
+/// A response's status, content type and body
+struct Reply {
+    status: u16,
+    content_type: &'static str,
+    body: Vec<u8>,
+}
+
+impl Reply {
+    fn json(status: u16, body: &impl Serialize) -> Self {
+        Self {
+            status,
+            content_type: "application/json",
+            body: serde_json::to_vec_pretty(body).unwrap_or_default(),
+        }
+    }
+
+    fn error(status: u16, message: String) -> Self {
+        Self::json(status, &json!({ "error": message }))
+    }
+}
+
+impl Api<'_> {
+    /// The response to `method` on `url`, from the current index.
+    fn handle(&mut self, method: &str, url: &str, body: &[u8], cancel: &CancelToken) -> Reply {
+        let index = self.shared.index.load_full();
+        if index.epoch != self.epoch {
+            (self.pages, self.store) = caches(self.shared, &index);
+            self.epoch = index.epoch;
+        }
+        let path = url.split('?').next().unwrap_or(url);
+        if let Some(file) = path.strip_prefix("/docs/") {
+            return match method {
+                "GET" => self.doc_file(file),
+                _ => Reply::error(405, "/docs/ only accepts GET".to_string()),
+            };
+        }
+        match (method, path) {
+            ("GET", "/") => Reply {
+                status: 200,
+                content_type: "text/html; charset=utf-8",
+                body: INDEX_HTML.as_bytes().to_vec(),
+            },
+            ("GET", "/search") => self.search(&index, url, cancel),
+            ("GET", "/item") => self.item(&index, url),
+            ("POST", "/resolve") => {
+                let paths: Vec<String> = match serde_json::from_slice(body) {
+                    Ok(paths) => paths,
+                    Err(err) => {
+                        return Reply::error(
+                            400,
+                            format!("Expected a JSON array of item paths: {}", err),
+                        );
+                    }
+                };
+                let results = paths
+                    .iter()
+                    .map(|path| {
+                        Resolution::new(path, resolve(&index.items, path), &self.shared.links)
+                    })
+                    .collect();
+                Reply::json(200, &Versioned::new("Resolutions", Resolutions { results }))
+            }
+            ("POST", "/scan") => self.scan(&index, body),
+            ("POST", "/reload") => {
+                // The reload happens in the background; the reply doesn't wait
+                // for it, so it has the epoch of the index still in use
+                let _ = self.reloads.send(());
+                Reply::json(202, &json!({ "epoch": index.epoch }))
+            }
+            ("GET", "/openapi.json") => Reply::json(200, &openapi_spec()),
+            (_, "/resolve" | "/scan" | "/reload") => {
+                Reply::error(405, format!("{} only accepts POST", path))
+            }
+            (_, "/" | "/search" | "/item" | "/openapi.json") => {
+                Reply::error(405, format!("{} only accepts GET", path))
+            }
+            _ => Reply::error(404, format!("No endpoint {}", path)),
+        }
+    }
+
+    fn search(&mut self, index: &Index, url: &str, cancel: &CancelToken) -> Reply {
+        let Some(query) = query_param(url, "q") else {
+            return Reply::error(400, "Missing the q parameter".to_string());
+        };
+        let limit = match query_param(url, "limit").map(|limit| limit.parse()) {
+            None => DEFAULT_SEARCH_LIMIT,
+            Some(Ok(limit)) => limit,
+            Some(Err(_)) => return Reply::error(400, "limit must be a number".to_string()),
+        };
+        let timeout = match query_param(url, "timeout").map(|timeout| timeout.parse()) {
+            None => self.shared.search_timeout,
+            Some(Ok(0)) => None,
+            Some(Ok(millis)) => Some(Duration::from_millis(millis)),
+            Some(Err(_)) => {
+                return Reply::error(400, "timeout must be a number of milliseconds".to_string());
+            }
+        };
+        let cancel = match timeout {
+            Some(timeout) => cancel.clone().with_budget(timeout),
+            None => cancel.clone(),
+        };
+
+        let matched = Query::parse(&query)
+            .map_err(|err| err.to_string())
+            .and_then(|parsed| {
+                match_query(
+                    &parsed,
+                    &index.items,
+                    &index.aliases,
+                    &mut self.store,
+                    &self.shared.options,
+                    &cancel,
+                )
+                .map_err(|err| format!("{:#}", err))
+            });
+        let results = match matched {
+            Ok(results) => {
+                let shown = results
+                    .into_iter()
+                    .filter(|item| !self.shared.options.hides(item));
+                let results: Vec<&SearchItem> =
+                    dedup_reexports(shown).into_iter().take(limit).collect();
+                QueryResults::new(&query, &results, &self.shared.links)
+            }
+            Err(err) => QueryResults::failed(&query, err),
+        };
+        Reply::json(200, &results)
+    }
+
+    fn scan(&self, index: &Index, body: &[u8]) -> Reply {
+        let request: ScanRequest = match serde_json::from_slice(body) {
+            Ok(request) => request,
+            Err(err) => return Reply::error(400, format!("Invalid scan request: {}", err)),
+        };
+        // Answering from docs the client didn't find, or from an index about
+        // to be reloaded, could answer differently than the client would
+        if !same_file(&request.search_index, &index.search_index_path)
+            || modified(&index.search_index_path) != index.modified
+        {
+            return Reply::error(
+                409,
+                format!(
+                    "Serving {} as it was when last loaded, not {}",
+                    index.search_index_path.display(),
+                    request.search_index.display()
+                ),
+            );
+        }
+        // Nor from settings that could answer differently than the client's
+        if request.settings != self.shared.settings {
+            return Reply::error(
+                409,
+                "Started with other settings, or in another directory".to_string(),
+            );
+        }
+        let (symbol, options) = match daemon::parse_scan(&request.args, self.shared.config) {
+            Ok(Some(scan)) => scan,
+            Ok(None) => return Reply::error(422, "Not a scan this server answers".to_string()),
+            Err(err) => return Reply::error(400, format!("{:#}", err)),
+        };
+        let decoded = Decoded {
+            search_index_path: &index.search_index_path,
+            items: &index.items,
+            aliases: &index.aliases,
+            graph: self.shared.graph.as_ref(),
+        };
+        let mut output = Vec::new();
+        let answered = scan::answer(
+            &symbol,
+            &options,
+            self.shared.source,
+            &decoded,
+            request.hyperlinks,
+            &mut output,
+        );
+        match answered {
+            Ok((results, timings)) => Reply::json(
+                200,
+                &ScanAnswer {
+                    output: String::from_utf8_lossy(&output).into_owned(),
+                    results,
+                    timings: options.timings.then_some(timings),
+                },
+            ),
+            Err(err) => Reply::error(400, format!("{:#}", err)),
+        }
+    }
+
+    fn item(&mut self, index: &Index, url: &str) -> Reply {
+        let Some(id) = query_param(url, "id") else {
+            return Reply::error(400, "Missing the id parameter".to_string());
+        };
+        let id: ItemId = match id.parse() {
+            Ok(id) => id,
+            Err(err) => return Reply::error(400, format!("Invalid item ID \"{}\": {}", id, err)),
+        };
+        let Some(item) = index.items.iter().find(|item| item.id == id) else {
+            return Reply::error(404, format!("No item {}", id));
+        };
+
+        let anchor = item_page(item).and_then(|page| page.anchor);
+        let html = self.pages.item_html(item).unwrap_or_else(|err| {
+            warn!("could not read the page of {}: {:#}", id, err);
+            None
+        });
+        let declaration = html.and_then(|html| declaration(html, anchor.as_deref()));
+        let preview = ItemPreview {
+            item: ResultRecord::new(item, &self.shared.links),
+            origin: impl_origin(item).map(|origin| origin.to_string()),
+            declaration,
+            description: self.store.get(item).map(plain_text),
+        };
+        Reply::json(200, &Versioned::new("ItemPreview", preview))
+    }
+
+    /// A file from the local doc root, refusing paths that would leave it.
+    fn doc_file(&self, file: &str) -> Reply {
+        let Some(doc_root) = &self.shared.doc_root else {
+            return Reply::error(404, "Only local docs are served under /docs/".to_string());
+        };
+        let Ok(file) = urlencoding::decode(file) else {
+            return Reply::error(400, "The path isn't valid UTF-8".to_string());
+        };
+        let mut path = doc_root.clone();
+        for segment in file.split('/').filter(|segment| !segment.is_empty()) {
+            if segment == "." || segment == ".." || segment.contains('\\') {
+                return Reply::error(400, format!("Invalid path /docs/{}", file));
+            }
+            path.push(segment);
+        }
+        if path.is_dir() {
+            path.push("index.html");
+        }
+        match std::fs::read(&path) {
+            Ok(body) => Reply {
+                status: 200,
+                content_type: content_type(&path),
+                body,
+            },
+            Err(_) => Reply::error(404, format!("No file /docs/{}", file)),
+        }
+    }
+}
+
+/// Whether `a` and `b` are the same file, however they're reached.
+fn same_file(a: &Path, b: &Path) -> bool {
+    match (a.canonicalize(), b.canonicalize()) {
+        (Ok(a), Ok(b)) => a == b,
+        _ => a == b,
+    }
+}
+
+/// The decoded value of the `name` parameter in `url`'s query string.
+fn query_param(url: &str, name: &str) -> Option<String> {
+    let (_, query) = url.split_once('?')?;
+    query.split('&').find_map(|pair| {
+        let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+        if key != name {
+            return None;
+        }
+        urlencoding::decode(&value.replace('+', " "))
+            .ok()
+            .map(|value| value.into_owned())
+    })
+}
+
+/// The content type of a file in rustdoc's output, by extension.
+fn content_type(path: &Path) -> &'static str {
+    match path.extension().and_then(|extension| extension.to_str()) {
+        Some("html") => "text/html; charset=utf-8",
+        Some("js") => "text/javascript; charset=utf-8",
+        Some("css") => "text/css; charset=utf-8",
+        Some("svg") => "image/svg+xml",
+        Some("png") => "image/png",
+        Some("woff2") => "font/woff2",
+        Some("json") => "application/json",
+        Some("txt" | "md") => "text/plain; charset=utf-8",
+        _ => "application/octet-stream",
+    }
+}
+
+const INDEX_HTML: &str = r#"<!DOCTYPE html>
+<html lang="en">
+<head>
+<meta charset="utf-8">
+<title>rdoc</title>
+<style>
+body { font-family: sans-serif; margin: 0; display: flex; height: 100vh; }
+#side { width: 40%; display: flex; flex-direction: column; border-right: 1px solid #ddd; }
+#search { margin: 1em; padding: 0.4em; font-size: 1em; }
+#status { margin: 0 1em; color: #777; font-size: 0.9em; }
+#results { list-style: none; margin: 0; padding: 0.5em 0; overflow-y: auto; }
+#results li { padding: 0.3em 1em; cursor: pointer; }
+#results li:hover, #results li.selected { background: #eef; }
+#preview { flex: 1; padding: 1em 2em; overflow-y: auto; }
+.type, .path { color: #777; font-size: 0.9em; }
+.deprecated { color: #b60; }
+pre { background: #f5f5f5; padding: 0.8em; overflow-x: auto; }
+</style>
+</head>
+<body>
+<div id="side">
+<input id="search" type="search" placeholder="Search items (e.g. read AND async, Hash*Map)" autofocus>
+<div id="status"></div>
+<ul id="results"></ul>
+</div>
+<div id="preview"></div>
+<script>
+const search = document.getElementById("search");
+const status = document.getElementById("status");
+const results = document.getElementById("results");
+const preview = document.getElementById("preview");
+
+function element(tag, className, text) {
+  const node = document.createElement(tag);
+  if (className) node.className = className;
+  if (text !== undefined) node.textContent = text;
+  return node;
+}
+
+// Each tab's searches cancel its own earlier ones, not other tabs'
+const session = Math.random().toString(36).slice(2);
+
+async function runSearch(query) {
+  const response = await fetch(
+    "/search?q=" + encodeURIComponent(query) + "&session=" + session
+  );
+  const body = await response.json();
+  if (search.value.trim() !== query) return;
+  results.replaceChildren();
+  status.textContent = body.error || body.results.length + " results";
+  for (const item of body.results || []) {
+    const li = element("li");
+    li.append(element("span", "", item.name), " ", element("span", "type", item.item_type),
+      element("div", "path", item.path));
+    li.addEventListener("click", () => {
+      for (const selected of results.querySelectorAll(".selected")) selected.classList.remove("selected");
+      li.classList.add("selected");
+      showItem(item.id);
+    });
+    results.append(li);
+  }
+}
+
+async function showItem(id) {
+  const response = await fetch("/item?id=" + encodeURIComponent(id));
+  const body = await response.json();
+  preview.replaceChildren();
+  if (body.error) {
+    preview.append(element("p", "", body.error));
+    return;
+  }
+  const item = body.item;
+  preview.append(element("h2", "", item.path + "::" + item.name),
+    element("p", "type", item.item_type + " in " + item.crate));
+  if (body.origin) preview.append(element("p", "path", "from " + body.origin));
+  if (item.deprecated) preview.append(element("p", "deprecated", "deprecated"));
+  if (body.declaration) preview.append(element("pre", "", body.declaration));
+  if (body.description) preview.append(element("p", "", body.description));
+  const href = item.absolute_url || (item.url && "/docs/" + item.url);
+  if (href) {
+    const link = element("a", "", "Open page");
+    link.href = href;
+    link.target = "_blank";
+    preview.append(link);
+  }
+}
+
+let timer;
+search.addEventListener("input", () => {
+  clearTimeout(timer);
+  const query = search.value.trim();
+  if (!query) {
+    results.replaceChildren();
+    status.textContent = "";
+    return;
+  }
+  timer = setTimeout(() => runSearch(query), 150);
+});
+</script>
+</body>
+</html>
+"#;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::docs::StaleDocs;
+    use rdoc::search_index::{extract_json_string, parse_search_index};
+    use rdoc::search_items::decode_crate;
+    use std::sync::LazyLock;
+
+    fn fixture_items() -> Vec<SearchItem> {
+        let content = std::fs::read_to_string("tests/fixtures/search-index.js").unwrap();
+        let entries = parse_search_index(&extract_json_string(&content).unwrap()).unwrap();
+        entries
+            .iter()
+            .flat_map(|entry| decode_crate(&entry.name, &entry.data))
+            .collect()
+    }
+
+    fn local_source() -> DocSource {
+        DocSource::Local {
+            doc_dir: None,
+            doc_roots: Vec::new(),
+            base_url: None,
+            docs_rs: false,
+            stale: StaleDocs::Ignore,
+        }
+    }
+
+    fn index(epoch: u64) -> Index {
+        Index {
+            epoch,
+            search_index_path: PathBuf::from("tests/fixtures/search-index.js"),
+            modified: modified(Path::new("tests/fixtures/search-index.js")),
+            crates: 0,
+            items: fixture_items().into(),
+            aliases: Aliases::default(),
+        }
+    }
+
+    static CONFIG: LazyLock<Config> = LazyLock::new(Config::default);
+
+    fn shared(source: &DocSource, search_timeout: Option<Duration>) -> Shared<'_> {
+        Shared {
+            index: ArcSwap::from_pointee(index(0)),
+            source,
+            config: &CONFIG,
+            graph: None,
+            options: ScanOptions::from_config(&CONFIG).unwrap(),
+            links: SiteLinks::default(),
+            doc_root: Some(PathBuf::from("tests/fixtures")),
+            search_timeout,
+            settings: Settings::current(source),
+        }
+    }
+
+    fn api<'a>(shared: &'a Shared<'a>) -> Api<'a> {
+        Api::new(shared, mpsc::channel().0)
+    }
+
+    fn json(reply: &Reply) -> serde_json::Value {
+        assert_eq!(reply.content_type, "application/json");
+        serde_json::from_slice(&reply.body).unwrap()
+    }
+
+    #[test]
+    fn test_handle_resolve() {
+        let source = local_source();
+        let shared = shared(&source, None);
+        let mut api = api(&shared);
+        let cancel = CancelToken::new();
+
+        let body = br#"["serde_json::from_str", "serde::Serialize", "no::such::item"]"#;
+        let reply = api.handle("POST", "/resolve", body, &cancel);
+        assert_eq!(reply.status, 200);
+        let response = json(&reply);
+        let results = response["results"].as_array().unwrap();
+        assert_eq!(results.len(), 3);
+        assert_eq!(results[0]["item"]["name"], "from_str");
+        assert!(results[0]["error"].is_null());
+        assert_eq!(results[1]["error"]["kind"], "ambiguous");
+        assert!(
+            !results[1]["error"]["candidates"]
+                .as_array()
+                .unwrap()
+                .is_empty()
+        );
+        assert_eq!(results[2]["error"]["kind"], "not_found");
+
+        assert_eq!(api.handle("POST", "/resolve", b"{}", &cancel).status, 400);
+        assert_eq!(api.handle("GET", "/resolve", b"", &cancel).status, 405);
+        assert_eq!(api.handle("GET", "/nowhere", b"", &cancel).status, 404);
+
+        let reply = api.handle("GET", "/openapi.json", b"", &cancel);
+        assert_eq!(reply.status, 200);
+        assert!(json(&reply)["paths"]["/resolve"]["post"].is_object());
+    }
+
+    #[test]
+    fn test_handle_web_ui() {
+        let source = local_source();
+        let shared = shared(&source, None);
+        let mut api = api(&shared);
+        let cancel = CancelToken::new();
+
+        let page = api.handle("GET", "/", b"", &cancel);
+        assert_eq!(page.status, 200);
+        assert!(page.content_type.starts_with("text/html"));
+
+        let found = json(&api.handle("GET", "/search?q=from_str&limit=3", b"", &cancel));
+        assert_eq!(found["query"], "from_str");
+        let results = found["results"].as_array().unwrap();
+        assert!(!results.is_empty() && results.len() <= 3);
+        let failed = json(&api.handle("GET", "/search?q=a+AND", b"", &cancel));
+        assert_eq!(failed["query"], "a AND");
+        assert!(failed["error"].is_string());
+        assert_eq!(api.handle("GET", "/search", b"", &cancel).status, 400);
+
+        // Out of time, or replaced by a newer search
+        let unlimited = json(&api.handle("GET", "/search?q=from_str&timeout=0", b"", &cancel));
+        assert!(unlimited["error"].is_null());
+        let impatient = self::shared(&source, Some(Duration::ZERO));
+        let timed_out =
+            json(&self::api(&impatient).handle("GET", "/search?q=from_str", b"", &cancel));
+        assert!(timed_out["error"].as_str().unwrap().contains("budget"));
+        let cancelled = CancelToken::new();
+        cancelled.cancel();
+        let replaced = json(&api.handle("GET", "/search?q=from_str", b"", &cancelled));
+        assert!(replaced["error"].as_str().unwrap().contains("cancelled"));
+
+        let id = results[0]["id"].as_str().unwrap();
+        let url = format!("/item?id={}", urlencoding::encode(id));
+        let reply = api.handle("GET", &url, b"", &cancel);
+        assert_eq!(reply.status, 200);
+        assert_eq!(json(&reply)["item"], results[0]);
+        assert_eq!(
+            api.handle("GET", "/item?id=serde%23999999@local", b"", &cancel)
+                .status,
+            404
+        );
+        assert_eq!(
+            api.handle("GET", "/item?id=serde", b"", &cancel).status,
+            400
+        );
+
+        let file = api.handle("GET", "/docs/search-index.js", b"", &cancel);
+        assert_eq!(file.status, 200);
+        assert!(file.content_type.starts_with("text/javascript"));
+        assert_eq!(
+            api.handle("GET", "/docs/../Cargo.toml", b"", &cancel)
+                .status,
+            400
+        );
+        assert_eq!(
+            api.handle("GET", "/docs/%2e%2e/Cargo.toml", b"", &cancel)
+                .status,
+            400
+        );
+        assert_eq!(
+            api.handle("GET", "/docs/missing.html", b"", &cancel).status,
+            404
+        );
+    }
+
+    #[test]
+    fn test_search_hides_noise() {
+        let source = local_source();
+        let shared = shared(&source, None);
+        let mut api = api(&shared);
+        let cancel = CancelToken::new();
+
+        // fst's `use` of MapBuilder is hidden as `scan` hides it
+        let found = json(&api.handle("GET", "/search?q=MapBuilder&limit=100", b"", &cancel));
+        let types: Vec<&str> = found["results"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|result| result["item_type"].as_str().unwrap())
+            .collect();
+        assert!(types.contains(&"struct"));
+        assert!(!types.contains(&"import"));
+    }
+
+    #[test]
+    fn test_workers_answer_concurrently() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        let (sender, receiver) = mpsc::channel();
+        let requests = Mutex::new(receiver);
+        sender.send(()).unwrap();
+        sender.send(()).unwrap();
+        drop(sender);
+        let running = AtomicUsize::new(0);
+        let most_running = AtomicUsize::new(0);
+        std::thread::scope(|scope| {
+            for _ in 0..2 {
+                scope.spawn(|| {
+                    work(&requests, |()| {
+                        let now = running.fetch_add(1, Ordering::SeqCst) + 1;
+                        most_running.fetch_max(now, Ordering::SeqCst);
+                        std::thread::sleep(Duration::from_millis(200));
+                        running.fetch_sub(1, Ordering::SeqCst);
+                    })
+                });
+            }
+        });
+        assert_eq!(most_running.load(Ordering::SeqCst), 2);
+    }
+
+    #[test]
+    fn test_search_session() {
+        let header = Header::from_bytes("x-rdoc-session", "editor-2").unwrap();
+        assert_eq!(
+            search_session("/search?q=read&session=tab-1", &[]).as_deref(),
+            Some("tab-1")
+        );
+        assert_eq!(
+            search_session("/search?q=read", std::slice::from_ref(&header)).as_deref(),
+            Some("editor-2")
+        );
+        // Without a session, nothing cancels the search
+        assert_eq!(search_session("/search?q=read", &[]), None);
+        assert_eq!(search_session("/search?q=read&session=", &[]), None);
+        assert_eq!(search_session("/item?id=x&session=tab-1", &[header]), None);
+    }
+
+    #[test]
+    fn test_reload() {
+        let source = local_source();
+        let shared = shared(&source, None);
+        let (reloads, requested) = mpsc::channel();
+        let mut api = Api::new(&shared, reloads);
+        let cancel = CancelToken::new();
+
+        let reply = api.handle("POST", "/reload", b"", &cancel);
+        assert_eq!(reply.status, 202);
+        assert_eq!(json(&reply)["epoch"], 0);
+        assert!(requested.try_recv().is_ok());
+        assert_eq!(api.handle("GET", "/reload", b"", &cancel).status, 405);
+
+        // Requests after a swap are answered from the new index, with fresh caches
+        shared.index.store(Arc::new(index(1)));
+        let found = json(&api.handle("GET", "/search?q=from_str", b"", &cancel));
+        assert!(!found["results"].as_array().unwrap().is_empty());
+        assert_eq!(api.epoch, 1);
+    }
+
+    #[test]
+    fn test_scan() {
+        let source = local_source();
+        let shared = shared(&source, None);
+        let mut api = api(&shared);
+        let cancel = CancelToken::new();
+        let send = |api: &mut Api, args: &[&str], search_index: &str, settings: Settings| {
+            let request = ScanRequest {
+                args: args.iter().map(|arg| arg.to_string()).collect(),
+                search_index: PathBuf::from(search_index),
+                settings,
+                hyperlinks: false,
+            };
+            let body = serde_json::to_vec(&request).unwrap();
+            api.handle("POST", "/scan", &body, &cancel)
+        };
+        let scan = |api: &mut Api, args: &[&str], search_index: &str| {
+            send(api, args, search_index, Settings::current(&source))
+        };
+        let fixture = "tests/fixtures/search-index.js";
+
+        let reply = scan(&mut api, &["scan", "from_str", "--format", "json"], fixture);
+        assert_eq!(reply.status, 200);
+        let answer: ScanAnswer = serde_json::from_slice(&reply.body).unwrap();
+        let results: serde_json::Value = serde_json::from_str(&answer.output).unwrap();
+        assert_eq!(results["results"].as_array().unwrap().len(), answer.results);
+        assert!(answer.results > 0);
+        let listing = scan(
+            &mut api,
+            &["scan", "from_str"],
+            "tests/../tests/fixtures/search-index.js",
+        );
+        let answer: ScanAnswer = serde_json::from_slice(&listing.body).unwrap();
+        assert!(answer.output.contains("from_str"));
+
+        // Failed scans are the client's errors; what can't be answered here
+        // is left to the client
+        assert_eq!(scan(&mut api, &["scan", "a AND"], fixture).status, 400);
+        assert_eq!(
+            scan(&mut api, &["scan", "x", "--output", "out.txt"], fixture).status,
+            422
+        );
+        assert_eq!(scan(&mut api, &["scan", "x"], "Cargo.toml").status, 409);
+
+        // Nor is a client linking its docs elsewhere, or run elsewhere
+        let linked = DocSource::Local {
+            doc_dir: None,
+            doc_roots: Vec::new(),
+            base_url: Some("https://docs.example.com/".to_string()),
+            docs_rs: false,
+            stale: StaleDocs::Ignore,
+        };
+        let args = [
+            "--base-url",
+            "https://docs.example.com/",
+            "scan",
+            "from_str",
+        ];
+        let reply = send(&mut api, &args, fixture, Settings::current(&linked));
+        assert_eq!(reply.status, 409);
+        let elsewhere = Settings {
+            cwd: PathBuf::from("/elsewhere"),
+            ..Settings::current(&source)
+        };
+        let reply = send(&mut api, &["scan", "from_str"], fixture, elsewhere);
+        assert_eq!(reply.status, 409);
+        assert_eq!(api.handle("POST", "/scan", b"[]", &cancel).status, 400);
+        assert_eq!(api.handle("GET", "/scan", b"", &cancel).status, 405);
+    }
+}
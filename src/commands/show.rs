@@ -1,40 +1,184 @@
-use color_eyre::Result;
+use color_eyre::{Result, eyre::bail};
 use colored::Colorize;
+use rdoc::aliases::Aliases;
+use rdoc::descriptions::{DescriptionStore, plain_text};
+use rdoc::human::count;
+use rdoc::item_id::ItemRef;
+use rdoc::pages::{Generics, availability, declaration, impl_origin, item_page, required_features};
+use rdoc::platform::mentions_platform;
+use rdoc::resolve::{disambiguated_path, resolve_with_aliases};
+use rdoc::search_items::SearchItem;
+use rdoc::std_paths::StdPaths;
+use std::collections::BTreeMap;
 use std::path::Path;
+use tracing::info;
 
-/// Show documentation for a fully qualified path
-pub fn execute(item_path: &str) -> Result<()> {
-    println!(
-        "{} Looking up documentation for: {}",
-        "→".cyan().bold(),
-        item_path.green().bold()
-    );
+use crate::docs::{self, DocSource, Pages};
 
-    // Check if we're in a Rust project
-    if !Path::new("Cargo.toml").exists() {
-        return Err(color_eyre::eyre::eyre!(
-            "No Cargo.toml found. Please run rdoc from a Rust project directory."
-        ));
+/// Show documentation for a fully qualified path, or an alias of one
+pub fn execute(
+    item_path: &str,
+    user_aliases: &BTreeMap<String, String>,
+    source: &DocSource,
+    quiet: bool,
+) -> Result<()> {
+    if !quiet {
+        println!(
+            "{} Looking up documentation for: {}",
+            "→".cyan().bold(),
+            item_path.green().bold()
+        );
     }
 
-    let search_index_path = Path::new("target/doc/search-index.js");
+    let search_index_path = docs::find_search_index(source, quiet, false)?;
+    let json_string = docs::load_search_index_json(&search_index_path)?;
+    let crate_entries = docs::crate_entries(&json_string)?;
+    let mut aliases = Aliases::new(user_aliases);
+    let all_items = docs::decode_items_with_aliases(&crate_entries, source, &mut aliases);
+    let item = resolve_with_aliases(&all_items, item_path, &aliases)?;
+    show(item, &all_items, &search_index_path, source)
+}
+
+/// Show documentation for the item with an ID from earlier output, as
+/// `crate:index` or a whole item ID
+pub fn execute_id(item_ref: &ItemRef, source: &DocSource, quiet: bool) -> Result<()> {
+    if !quiet {
+        println!(
+            "{} Looking up documentation for item: {}",
+            "→".cyan().bold(),
+            item_ref.to_string().green().bold()
+        );
+    }
+    let source_id = source.id();
+    if let Some(other) = item_ref
+        .source
+        .as_deref()
+        .filter(|other| *other != source_id)
+    {
+        bail!(
+            "Item {} is from {}, not the docs being read ({})",
+            item_ref,
+            other,
+            source_id
+        );
+    }
 
-    // Check if docs exist
-    if !search_index_path.exists() {
-        return Err(color_eyre::eyre::eyre!(
-            "Documentation not found. Please run 'cargo doc' first or use 'rdoc scan' to generate docs."
-        ));
+    let search_index_path = docs::find_search_index(source, quiet, false)?;
+    let json_string = docs::load_search_index_json(&search_index_path)?;
+    let crate_entries = docs::crate_entries(&json_string)?;
+    if !crate_entries
+        .iter()
+        .any(|entry| entry.name == item_ref.crate_name)
+    {
+        bail!(
+            "Crate \"{}\" is not in the search index",
+            item_ref.crate_name
+        );
     }
+    let all_items = docs::decode_items(&crate_entries, source);
+    let Some(item) = all_items.iter().find(|item| item_ref.matches(&item.id)) else {
+        let items = all_items
+            .iter()
+            .filter(|item| *item.id.crate_name == item_ref.crate_name)
+            .count();
+        bail!(
+            "Item index {} is out of range: crate {} has {}",
+            item_ref.index,
+            item_ref.crate_name,
+            count(items, "item", "items")
+        );
+    };
+    show(item, &all_items, &search_index_path, source)
+}
 
-    // TODO: Parse the fully qualified path (e.g., "std::fs::read_to_string")
-    // TODO: Look up the item in the search index
-    // TODO: Display the documentation for the item
+fn show(
+    item: &SearchItem,
+    all_items: &[SearchItem],
+    search_index_path: &Path,
+    source: &DocSource,
+) -> Result<()> {
+    let mut pages = Pages::new(source, search_index_path);
+    let mut store = DescriptionStore::new(search_index_path.parent().unwrap_or(Path::new(".")));
+    let std_paths = StdPaths::new(all_items);
+    print_item(item, std_paths.as_ref(), &mut pages, &mut store)
+}
+
+/// Print an item's kind, declaration, generics, feature and platform gates
+/// and description, and its `std` path if it's a `core` or `alloc` item
+pub fn print_item(
+    item: &SearchItem,
+    std_paths: Option<&StdPaths>,
+    pages: &mut Pages,
+    store: &mut DescriptionStore,
+) -> Result<()> {
+    // The declaration, generics and feature gates only exist in the HTML pages
+    let anchor = item_page(item).and_then(|page| page.anchor);
+    let html = pages.item_html(item)?;
 
     println!(
-        "{} Documentation lookup not yet implemented",
-        "ℹ".blue().bold()
+        "\n{} ({}) in {}",
+        disambiguated_path(item).cyan().bold(),
+        item.item_type,
+        item.crate_name
     );
-    println!("    Path to look up: {}", item_path);
+    if item.exact_path != item.path {
+        println!("  {}", format!("defined in {}", item.exact_path).dimmed());
+    }
+    if let Some(module) = std_paths.and_then(|std_paths| std_paths.module_path(item)) {
+        let name = match item.parent_name() {
+            Some(parent) => format!("{}::{}::{}", module, parent, item.name),
+            None => format!("{}::{}", module, item.name),
+        };
+        println!("  {}", format!("use as {}", name).dimmed());
+    }
+    if let Some(origin) = impl_origin(item) {
+        println!("  {}", format!("from {}", origin).dimmed());
+    }
+    if item.deprecated {
+        println!("  {}", "deprecated".yellow());
+    }
+    if let Some(html) = html {
+        for feature in required_features(html, anchor.as_deref()) {
+            println!("  {}", format!("requires feature `{}`", feature).yellow());
+        }
+        for condition in availability(html, anchor.as_deref()) {
+            if mentions_platform(&condition) {
+                println!("  {}", format!("available on {} only", condition).yellow());
+            }
+        }
+    }
+
+    match html.and_then(|html| declaration(html, anchor.as_deref())) {
+        Some(declaration) => {
+            println!();
+            for line in declaration.lines() {
+                println!("  {}", line);
+            }
+
+            let generics = Generics::parse(&declaration, &item.name);
+            if !generics.params.is_empty() {
+                println!("\n  {}", "Generic parameters".bold());
+                for param in &generics.params {
+                    println!("    {}", param);
+                }
+            }
+            if !generics.where_predicates.is_empty() {
+                println!("\n  {}", "Where".bold());
+                for predicate in &generics.where_predicates {
+                    println!("    {}", predicate);
+                }
+            }
+        }
+        None => info!(
+            "no declaration found for {} on {}",
+            disambiguated_path(item),
+            item_page(item).map_or("its page".to_string(), |page| page.href())
+        ),
+    }
+
+    if let Some(description) = store.get(item) {
+        println!("\n  {}", plain_text(description));
+    }
 
     Ok(())
 }
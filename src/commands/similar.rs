@@ -0,0 +1,77 @@
+use color_eyre::{Result, eyre::eyre};
+use colored::Colorize;
+use rdoc::resolve::{disambiguated_path, resolve};
+use rdoc::search_items::dedup_reexports;
+use rdoc::similarity::similarity;
+
+use crate::docs::{self, DocSource};
+
+/// List the functions whose signatures are most similar to `path`'s
+pub fn execute(path: &str, limit: usize, source: &DocSource, quiet: bool) -> Result<()> {
+    if !quiet {
+        println!(
+            "{} Finding functions similar to {}",
+            "→".cyan().bold(),
+            path.green().bold()
+        );
+    }
+
+    let search_index_path = docs::find_search_index(source, quiet, true)?;
+    let json_string = docs::load_search_index_json(&search_index_path)?;
+    let crate_entries = docs::crate_entries(&json_string)?;
+    let all_items = docs::decode_items(&crate_entries, source);
+    let signatures = docs::decode_signatures(&crate_entries);
+
+    let target = resolve(&all_items, path)?;
+    let signature = docs::signature_of(&signatures, target).ok_or_else(|| {
+        eyre!(
+            "{} is a {}, not a function; only functions have signatures",
+            disambiguated_path(target),
+            target.item_type
+        )
+    })?;
+
+    let candidates = all_items.iter().filter(|item| {
+        // The function itself, wherever it's re-exported
+        !(item.exact_path == target.exact_path
+            && item.name == target.name
+            && item.item_type == target.item_type
+            && item.parent_name() == target.parent_name())
+    });
+    let mut ranked: Vec<_> = dedup_reexports(candidates)
+        .into_iter()
+        .filter_map(|item| {
+            let other = docs::signature_of(&signatures, item)?;
+            Some((similarity(signature, other), item, other))
+        })
+        .filter(|(score, _, _)| *score > 0.0)
+        .collect();
+    ranked.sort_by(|(a, a_item, _), (b, b_item, _)| {
+        b.total_cmp(a).then_with(|| a_item.path.cmp(&b_item.path))
+    });
+    ranked.truncate(limit);
+
+    println!(
+        "{} {} {}",
+        "✓".green().bold(),
+        disambiguated_path(target).cyan(),
+        signature.to_string().dimmed()
+    );
+    if ranked.is_empty() {
+        println!("{} No functions with similar signatures", "✗".red().bold());
+        return Ok(());
+    }
+    println!();
+    for (score, item, other) in ranked {
+        println!(
+            "  {:>3.0}%  {} ({}) in {}",
+            score * 100.0,
+            disambiguated_path(item).cyan(),
+            item.item_type,
+            item.crate_name.dimmed()
+        );
+        println!("        {}", other.to_string().dimmed());
+    }
+
+    Ok(())
+}
@@ -0,0 +1,266 @@
+// Collecting the code examples of matching items into a file to start from
+
+use color_eyre::{
+    Result,
+    eyre::{Context, bail},
+};
+use colored::Colorize;
+use rdoc::aliases::Aliases;
+use rdoc::arena;
+use rdoc::cancel::CancelToken;
+use rdoc::descriptions::DescriptionStore;
+use rdoc::human::count;
+use rdoc::pages::{examples, item_page};
+use rdoc::query::Query;
+use rdoc::resolve::{disambiguated_path, import_path};
+use rdoc::search_items::{SearchItem, dedup_reexports};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use tracing::info;
+
+use crate::commands::scan::{ScanOptions, match_query};
+use crate::docs::{self, DocSource, Pages};
+use crate::output;
+
+/// Where `snippets` puts the examples it finds
+#[derive(Debug, Clone)]
+pub enum SnippetsOutput {
+    /// Print them
+    Stdout,
+    /// Write them to this file
+    File(PathBuf),
+    /// Write them to `examples/<name>.rs`, for `cargo run --example <name>`
+    Example(String),
+}
+
+/// An item's examples, with the path to import it by
+struct ItemExamples<'a> {
+    item: &'a SearchItem,
+    import: Option<String>,
+    examples: Vec<String>,
+}
+
+/// Collect the runnable examples from the docs of up to `limit` items
+/// matching `query`, as `scan` matches it, into one Rust file, each example a
+/// function `main` calls.
+pub fn execute(
+    query: &str,
+    limit: usize,
+    output: &SnippetsOutput,
+    force: bool,
+    source: &DocSource,
+    quiet: bool,
+) -> Result<()> {
+    // Printed snippets must not be mixed with status messages
+    let quiet = quiet || matches!(output, SnippetsOutput::Stdout);
+    let path = output_path(output, force)?;
+    if !quiet {
+        println!(
+            "{} Collecting examples for {}",
+            "→".cyan().bold(),
+            query.green().bold()
+        );
+    }
+
+    let parsed = Query::parse(query).wrap_err_with(|| format!("Invalid query \"{}\"", query))?;
+    let search_index_path = docs::find_search_index(source, quiet, true)?;
+    let json_string = docs::load_search_index_json(&search_index_path)?;
+    let crate_entries = docs::crate_entries(&json_string)?;
+    let all_items: arena::Index = docs::decode_items(&crate_entries, source).into();
+    let doc_root = search_index_path.parent().unwrap_or(Path::new("."));
+    let matches = match_query(
+        &parsed,
+        &all_items,
+        &Aliases::default(),
+        &mut DescriptionStore::new(doc_root),
+        &ScanOptions::default(),
+        &CancelToken::default(),
+    )?;
+
+    let mut pages = Pages::new(source, &search_index_path);
+    let found = collect_examples(&all_items, matches, limit, &mut pages)?;
+    let examples_found = count(
+        found.iter().map(|item| item.examples.len()).sum(),
+        "example",
+        "examples",
+    );
+    info!(
+        "found {} in {}",
+        examples_found,
+        count(found.len(), "item", "items")
+    );
+    if found.is_empty() {
+        bail!("No items matching \"{}\" have runnable examples", query);
+    }
+
+    let file = snippets_file(&found);
+    let Some(path) = path else {
+        let mut out = std::io::stdout().lock();
+        return out
+            .write_all(file.as_bytes())
+            .and_then(|()| out.flush())
+            .wrap_err("Failed to write the snippets");
+    };
+    write_snippets(&path, file)?;
+    if !quiet {
+        println!(
+            "{} Wrote {} to {}",
+            "✓".green().bold(),
+            examples_found,
+            path.display().to_string().cyan()
+        );
+        if let SnippetsOutput::Example(name) = output {
+            println!("  Run it with `cargo run --example {}`", name);
+        }
+    }
+    Ok(())
+}
+
+/// The file `output` names, or `None` to print the snippets. Fails if the
+/// file exists and `force` isn't set.
+fn output_path(output: &SnippetsOutput, force: bool) -> Result<Option<PathBuf>> {
+    let path = match output {
+        SnippetsOutput::Stdout => return Ok(None),
+        SnippetsOutput::File(path) => path.clone(),
+        SnippetsOutput::Example(name) => Path::new("examples").join(format!("{}.rs", name)),
+    };
+    if path.exists() && !force {
+        bail!(
+            "{} already exists; pass --force to replace it",
+            path.display()
+        );
+    }
+    Ok(Some(path))
+}
+
+/// The examples of the first `limit` of `matches` that have some, with the
+/// paths to import them by among `all_items`.
+fn collect_examples<'a>(
+    all_items: &arena::Index,
+    matches: Vec<&'a SearchItem>,
+    limit: usize,
+    pages: &mut Pages,
+) -> Result<Vec<ItemExamples<'a>>> {
+    // Examples are only on the pages, so they're read until enough items had some
+    let mut found = Vec::new();
+    for item in dedup_reexports(matches) {
+        if found.len() == limit {
+            break;
+        }
+        let anchor = item_page(item).and_then(|page| page.anchor);
+        let item_examples = pages
+            .item_html(item)?
+            .map(|html| examples(html, anchor.as_deref()))
+            .unwrap_or_default();
+        if item_examples.is_empty() {
+            continue;
+        }
+        found.push(ItemExamples {
+            item,
+            import: import_path(all_items, item, |_| true),
+            examples: item_examples,
+        });
+    }
+    Ok(found)
+}
+
+/// Write the snippets `file` to `path`, creating its directory.
+fn write_snippets(path: &Path, file: String) -> Result<()> {
+    if let Some(dir) = path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+        std::fs::create_dir_all(dir)
+            .wrap_err_with(|| format!("Failed to create {}", dir.display()))?;
+    }
+    output::write_atomic(path, file)
+        .wrap_err_with(|| format!("Failed to write the snippets to {}", path.display()))
+}
+
+/// A Rust file with each example in a function of its own, under a comment
+/// naming the item it documents, all called from `main`.
+///
+/// Doctests' hidden lines aren't on the pages, so the items are imported by
+/// their paths instead; other imports an example needs are left to add.
+fn snippets_file(found: &[ItemExamples]) -> String {
+    let mut file = String::from("#![allow(unused)]\n\n");
+    let mut imports: Vec<&str> = found
+        .iter()
+        .filter_map(|item| item.import.as_deref())
+        .collect();
+    imports.sort_unstable();
+    imports.dedup();
+    for import in &imports {
+        file.push_str(&format!("use {};\n", import));
+    }
+
+    let mut functions = Vec::new();
+    for item_examples in found {
+        let path = disambiguated_path(item_examples.item);
+        for (number, example) in item_examples.examples.iter().enumerate() {
+            let function = format!("example_{}", functions.len() + 1);
+            file.push_str(&format!(
+                "\n// Example {} of {}\nfn {}() {{\n",
+                number + 1,
+                path,
+                function
+            ));
+            for line in example.lines() {
+                match line {
+                    "" => file.push('\n'),
+                    line => file.push_str(&format!("    {}\n", line)),
+                }
+            }
+            file.push_str("}\n");
+            functions.push(function);
+        }
+    }
+
+    file.push_str("\nfn main() {\n");
+    for function in &functions {
+        file.push_str(&format!("    {}();\n", function));
+    }
+    file.push_str("}\n");
+    file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rdoc::item_id::ItemId;
+    use rdoc::search_index::ItemType;
+
+    #[test]
+    fn test_snippets_file() {
+        let item = SearchItem {
+            crate_name: "geo".to_string(),
+            item_type: ItemType::Function,
+            name: "parse_point".to_string(),
+            normalized_name: "parse_point".to_string(),
+            path: "geo".to_string(),
+            exact_path: "geo".to_string(),
+            id: ItemId::new(ItemId::LOCAL_SOURCE, "geo", 0),
+            param_types: vec![],
+            impl_disambiguator: None,
+            bit_index: 1,
+            parent_index: None,
+            parents: Default::default(),
+            deprecated: false,
+            empty_description: false,
+            description_location: None,
+        };
+        let found = [ItemExamples {
+            item: &item,
+            import: Some("geo::parse_point".to_string()),
+            examples: vec![
+                "let point = parse_point(\"1,2\");\n\nassert_eq!(point.x, 1);".to_string(),
+                "parse_point(\"\");".to_string(),
+            ],
+        }];
+        assert_eq!(
+            snippets_file(&found),
+            "#![allow(unused)]\n\nuse geo::parse_point;\n\n\
+             // Example 1 of fn@geo::parse_point\nfn example_1() {\n    \
+             let point = parse_point(\"1,2\");\n\n    assert_eq!(point.x, 1);\n}\n\n\
+             // Example 2 of fn@geo::parse_point\nfn example_2() {\n    parse_point(\"\");\n}\n\n\
+             fn main() {\n    example_1();\n    example_2();\n}\n"
+        );
+    }
+}
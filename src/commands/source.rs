@@ -0,0 +1,116 @@
+use color_eyre::{Result, eyre::bail};
+use colored::{ColoredString, Colorize};
+use rdoc::pages::item_page;
+use rdoc::resolve::{disambiguated_path, resolve};
+use rdoc::source::{Highlight, source_lines, source_link};
+
+use crate::docs::{self, DocSource, Pages};
+
+/// Print an item's source code, read from the documentation's source pages
+pub fn execute(path: &str, context: usize, source: &DocSource, quiet: bool) -> Result<()> {
+    if !quiet {
+        println!(
+            "{} Finding source of {}",
+            "→".cyan().bold(),
+            path.green().bold()
+        );
+    }
+
+    let search_index_path = docs::find_search_index(source, quiet, true)?;
+    let json_string = docs::load_search_index_json(&search_index_path)?;
+    let crate_entries = docs::crate_entries(&json_string)?;
+    let all_items = docs::decode_items(&crate_entries, source);
+
+    let item = resolve(&all_items, path)?;
+    let Some(page) = item_page(item) else {
+        bail!(
+            "{} is a {}, which has no page to link its source from",
+            disambiguated_path(item),
+            item.item_type
+        );
+    };
+    let mut pages = Pages::new(source, &search_index_path);
+    let Some(html) = pages.item_html(item)? else {
+        bail!(
+            "The page for {} ({}) wasn't found",
+            disambiguated_path(item),
+            page.file
+        );
+    };
+    let Some(link) = source_link(html, &page.file, page.anchor.as_deref()) else {
+        bail!(
+            "{} has no source link; it may be generated by a macro or the docs were built without sources",
+            disambiguated_path(item)
+        );
+    };
+    if link.is_external() {
+        bail!(
+            "The source of {} is hosted elsewhere: {}#{}-{}",
+            disambiguated_path(item),
+            link.file,
+            link.lines.start(),
+            link.lines.end()
+        );
+    }
+    let Some(source_html) = pages.file(&link.file)? else {
+        bail!("Source page {} wasn't found", link.file);
+    };
+    let lines = source_lines(source_html);
+
+    let first = link.lines.start().saturating_sub(context).max(1);
+    let last = (link.lines.end() + context).min(lines.len());
+    if first > last {
+        bail!(
+            "{} has only {} lines, but {} is at lines {}-{}",
+            link.file,
+            lines.len(),
+            disambiguated_path(item),
+            link.lines.start(),
+            link.lines.end()
+        );
+    }
+
+    if !quiet {
+        let file = link
+            .file
+            .trim_start_matches("src/")
+            .trim_end_matches(".html");
+        println!(
+            "{} {} at {}:{}\n",
+            "✓".green().bold(),
+            disambiguated_path(item).cyan(),
+            file,
+            link.lines.start()
+        );
+    }
+    let width = last.to_string().len();
+    for number in first..=last {
+        let code: String = lines[number - 1]
+            .iter()
+            .map(|run| highlight(run).to_string())
+            .collect();
+        let gutter = format!("{:>width$} │", number, width = width);
+        if link.lines.contains(&number) {
+            println!("{} {}", gutter.yellow(), code);
+        } else {
+            println!("{} {}", gutter.dimmed(), code);
+        }
+    }
+
+    Ok(())
+}
+
+/// Color a run of source by rustdoc's highlighting class.
+fn highlight(run: &Highlight) -> ColoredString {
+    let text = run.text.as_str();
+    match run.class.as_deref() {
+        Some("kw" | "kw-2") => text.magenta(),
+        Some("comment" | "doccomment") => text.bright_black(),
+        Some("string") => text.green(),
+        Some("number" | "bool-val" | "lifetime") => text.yellow(),
+        Some("macro" | "macro-nonterminal" | "attr") => text.cyan(),
+        Some("self" | "prelude-val") => text.red(),
+        Some("prelude-ty" | "struct" | "enum" | "trait" | "union" | "type") => text.blue(),
+        _ => text.normal(),
+    }
+}
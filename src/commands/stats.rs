@@ -0,0 +1,119 @@
+use clap::ValueEnum;
+use color_eyre::{Result, eyre::Context};
+use colored::Colorize;
+use rdoc::human::count;
+use rdoc::schema::Versioned;
+use rdoc::stats::{ApiStats, Change};
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use crate::docs::{self, DocSource};
+
+/// Output format for `rdoc stats`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum StatsFormat {
+    /// Human-readable table
+    Table,
+    /// JSON, usable as a snapshot for `--compare`
+    Json,
+}
+
+/// Count items per item type and module, optionally comparing against a snapshot
+pub fn execute(
+    crates: &[String],
+    compare: Option<&Path>,
+    format: StatsFormat,
+    source: &DocSource,
+    quiet: bool,
+) -> Result<()> {
+    // Status messages would corrupt JSON on stdout
+    let quiet = quiet || format == StatsFormat::Json;
+    if !quiet {
+        println!("{} Collecting API statistics", "→".cyan().bold());
+    }
+
+    let search_index_path = docs::find_search_index(source, quiet, true)?;
+    let json_string = docs::load_search_index_json(&search_index_path)?;
+    let crate_entries = docs::crate_entries(&json_string)?;
+    let all_items = docs::filter_crates(docs::decode_items(&crate_entries, source), crates)?;
+    let stats = ApiStats::of(&all_items);
+
+    let Some(snapshot_path) = compare else {
+        match format {
+            StatsFormat::Json => println!(
+                "{}",
+                serde_json::to_string_pretty(&Versioned::new("ApiStats", &stats))?
+            ),
+            StatsFormat::Table => print_stats(&stats),
+        }
+        return Ok(());
+    };
+
+    let snapshot = std::fs::read_to_string(snapshot_path)
+        .wrap_err_with(|| format!("Failed to read snapshot {}", snapshot_path.display()))?;
+    let before: ApiStats = serde_json::from_str(&snapshot).wrap_err_with(|| {
+        format!(
+            "{} is not a snapshot written by `rdoc stats --format json`",
+            snapshot_path.display()
+        )
+    })?;
+    let comparison = stats.compare(&before);
+
+    match format {
+        StatsFormat::Json => println!(
+            "{}",
+            serde_json::to_string_pretty(&Versioned::new("StatsComparison", &comparison))?
+        ),
+        StatsFormat::Table => {
+            println!();
+            print_change_row("Total", &comparison.total);
+            print_changes("Item type", &comparison.by_type);
+            print_changes("Module", &comparison.by_module);
+        }
+    }
+
+    Ok(())
+}
+
+fn print_stats(stats: &ApiStats) {
+    println!(
+        "\n{} {}",
+        "✓".green().bold(),
+        count(stats.total, "item", "items")
+    );
+    for (heading, counts) in [("Item type", &stats.by_type), ("Module", &stats.by_module)] {
+        println!("\n  {}", heading.bold());
+        for (key, count) in counts {
+            println!("  {:>8}  {}", count, key);
+        }
+    }
+}
+
+/// Print the rows that changed; unchanged types and modules are left out.
+fn print_changes(heading: &str, changes: &BTreeMap<String, Change>) {
+    let changed: Vec<_> = changes
+        .iter()
+        .filter(|(_, change)| change.change != 0)
+        .collect();
+    if changed.is_empty() {
+        return;
+    }
+
+    println!("\n  {}", heading.bold());
+    for (key, change) in changed {
+        print_change_row(key, change);
+    }
+}
+
+fn print_change_row(label: &str, change: &Change) {
+    let delta = format!("{:+}", change.change);
+    let delta = match change.change {
+        0 => delta.normal(),
+        d if d > 0 => delta.green(),
+        _ => delta.red(),
+    };
+    println!(
+        "  {:>8} → {:<8} {:>8}  {}",
+        change.before, change.after, delta, label
+    );
+}
@@ -0,0 +1,91 @@
+// A module's docs and items, like its rustdoc index page, in the terminal
+
+use color_eyre::Result;
+use colored::Colorize;
+use rdoc::descriptions::{DescriptionStore, plain_text};
+use rdoc::modules::module_sections;
+use rdoc::pages::intro;
+use rdoc::resolve::resolve;
+use std::path::Path;
+
+use crate::docs::{self, DocSource, Pages};
+use crate::hyperlink::Hyperlinks;
+
+/// Print a module's (or crate root's) introduction, then its public items by
+/// section, each with its one-line description
+pub fn execute(module: &str, source: &DocSource, quiet: bool) -> Result<()> {
+    if !quiet {
+        println!(
+            "{} Summarizing {}",
+            "→".cyan().bold(),
+            module.green().bold()
+        );
+    }
+
+    let search_index_path = docs::find_search_index(source, quiet, true)?;
+    let json_string = docs::load_search_index_json(&search_index_path)?;
+    let crate_entries = docs::crate_entries(&json_string)?;
+    let all_items = docs::decode_items(&crate_entries, source);
+    let mut pages = Pages::new(source, &search_index_path);
+    let mut store = DescriptionStore::new(search_index_path.parent().unwrap_or(Path::new(".")));
+
+    let module = module.trim_end_matches("::");
+    // Crate roots aren't items in the index, so they're found by name
+    let is_crate = !module.contains("::") && crate_entries.iter().any(|entry| entry.name == module);
+    let (module_path, html, description) = if is_crate {
+        println!("\n{} (Crate)", module.cyan().bold());
+        let html = pages.file(&format!("{}/index.html", module))?;
+        (module.to_string(), html, None)
+    } else {
+        let item = resolve(&all_items, &format!("mod@{}", module))?;
+        println!(
+            "\n{} (Module) in {}",
+            format!("{}::{}", item.path, item.name).cyan().bold(),
+            item.crate_name
+        );
+        if item.exact_path != item.path {
+            println!("  {}", format!("defined in {}", item.exact_path).dimmed());
+        }
+        let description = store.get(item).map(plain_text);
+        (
+            format!("{}::{}", item.path, item.name),
+            pages.item_html(item)?,
+            description,
+        )
+    };
+
+    // The page's opening paragraphs, or the summary line the index has
+    if let Some(intro) = html.and_then(intro).or(description) {
+        for paragraph in intro.split("\n\n") {
+            println!("\n  {}", paragraph);
+        }
+    }
+
+    let sections = module_sections(&all_items, &module_path);
+    if sections.is_empty() {
+        println!("\n  {}", "No public items".dimmed());
+        return Ok(());
+    }
+    let hyperlinks = Hyperlinks::detect(source, &search_index_path);
+    for section in &sections {
+        println!("\n  {}", section.heading().bold());
+        let width = section
+            .items
+            .iter()
+            .map(|item| item.name.chars().count())
+            .max()
+            .unwrap_or(0);
+        for item in &section.items {
+            let name = match &hyperlinks {
+                Some(hyperlinks) => hyperlinks.link(item.name.cyan(), item),
+                None => item.name.cyan().to_string(),
+            };
+            let padding = " ".repeat(width - item.name.chars().count());
+            match store.get(item).map(plain_text) {
+                Some(description) => println!("    {}{}  {}", name, padding, description.dimmed()),
+                None => println!("    {}", name),
+            }
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,124 @@
+// Searching a SCIP index from rust-analyzer, for projects without generated docs
+
+use color_eyre::{
+    Result,
+    eyre::{Context, bail, eyre},
+};
+use colored::Colorize;
+use rdoc::aliases::Aliases;
+use rdoc::arena;
+use rdoc::cancel::CancelToken;
+use rdoc::descriptions::DescriptionStore;
+use rdoc::format::Format;
+use rdoc::human::count;
+use rdoc::query::Query;
+use rdoc::resolve::disambiguated_path;
+use rdoc::scip::{decode_symbols, read_symbols};
+use std::path::Path;
+use std::process::Command;
+use tracing::info;
+
+use crate::commands::scan::{ScanOptions, match_query};
+use crate::progress;
+
+/// Where `rust-analyzer scip` writes its index unless told otherwise
+pub const DEFAULT_INDEX: &str = "index.scip";
+
+/// List up to `limit` of the items in the SCIP index at `index` matching
+/// `query`, as `scan` matches them. The default index is generated with
+/// `rust-analyzer scip` when it's missing and this is a cargo project.
+pub fn execute(
+    query: &str,
+    index: Option<&Path>,
+    limit: usize,
+    format: Option<&Format>,
+    quiet: bool,
+) -> Result<()> {
+    // Formatted output on stdout must not be mixed with status messages
+    let quiet = quiet || format.is_some();
+    let parsed = Query::parse(query).wrap_err_with(|| format!("Invalid query \"{}\"", query))?;
+    let index_path = index.unwrap_or(Path::new(DEFAULT_INDEX));
+    if !index_path.exists() {
+        if index.is_some() || !Path::new("Cargo.toml").exists() {
+            bail!("No SCIP index at {}", index_path.display());
+        }
+        generate_index(quiet)?;
+    }
+    if !quiet {
+        println!(
+            "{} Searching {} for {}",
+            "→".cyan().bold(),
+            index_path.display().to_string().cyan(),
+            query.green().bold()
+        );
+    }
+
+    let bytes = std::fs::read(index_path)
+        .wrap_err_with(|| format!("Failed to read {}", index_path.display()))?;
+    let symbols = read_symbols(&bytes)
+        .wrap_err_with(|| format!("Failed to read {}", index_path.display()))?;
+    let all_items: arena::Index =
+        decode_symbols(&symbols, &index_path.display().to_string()).into();
+    info!(
+        "decoded {} from {}",
+        count(all_items.len(), "item", "items"),
+        count(symbols.len(), "symbol", "symbols")
+    );
+
+    // There are no description shards, so matching is on names and paths only
+    let mut results = match_query(
+        &parsed,
+        &all_items,
+        &Aliases::default(),
+        &mut DescriptionStore::new(Path::new("")),
+        &ScanOptions::default(),
+        &CancelToken::default(),
+    )?;
+    results.truncate(limit);
+
+    if let Some(format) = format {
+        format
+            .formatter()
+            .format(&results, &mut std::io::stdout().lock())
+            .wrap_err_with(|| format!("Failed to format results as {}", format))?;
+        return Ok(());
+    }
+    if results.is_empty() {
+        println!("{} No results found for \"{}\"", "✗".red().bold(), query);
+        return Ok(());
+    }
+    for item in &results {
+        println!(
+            "  {} ({}) in {}",
+            disambiguated_path(item).cyan(),
+            item.item_type,
+            item.crate_name.dimmed()
+        );
+    }
+    Ok(())
+}
+
+/// Run `rust-analyzer scip .` in the current project, writing [`DEFAULT_INDEX`].
+fn generate_index(quiet: bool) -> Result<()> {
+    let spinner = progress::spinner("Running rust-analyzer scip");
+    let output = Command::new("rust-analyzer")
+        .args(["scip", "."])
+        .output()
+        .wrap_err(
+            "Failed to execute rust-analyzer; install it with `rustup component add rust-analyzer`",
+        )?;
+    spinner.finish_and_clear();
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(eyre!("rust-analyzer scip failed:\n{}", stderr));
+    }
+    if !quiet {
+        println!(
+            "{} Generated {} with rust-analyzer",
+            "✓".green().bold(),
+            DEFAULT_INDEX.cyan()
+        );
+    }
+    Ok(())
+}
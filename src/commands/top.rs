@@ -0,0 +1,79 @@
+use color_eyre::{Result, eyre::bail};
+use colored::Colorize;
+use rdoc::centrality::{TypeUsage, type_usage};
+use rdoc::search_items::dedup_reexports;
+use std::collections::BTreeMap;
+
+use crate::docs::{self, DocSource};
+
+/// List each crate's types that the most function signatures mention
+pub fn execute(crates: &[String], limit: usize, source: &DocSource, quiet: bool) -> Result<()> {
+    if !quiet {
+        println!(
+            "{} Counting types used in function signatures",
+            "→".cyan().bold()
+        );
+    }
+
+    let search_index_path = docs::find_search_index(source, quiet, true)?;
+    let json_string = docs::load_search_index_json(&search_index_path)?;
+    let crate_entries = docs::crate_entries(&json_string)?;
+    let all_items = docs::decode_items(&crate_entries, source);
+    let signatures = docs::decode_signatures(&crate_entries);
+
+    for name in crates {
+        if !crate_entries.iter().any(|entry| &entry.name == name) {
+            bail!("Crate \"{}\" is not in the search index", name);
+        }
+    }
+
+    // Every crate's functions count, so a type used mostly by its dependents
+    // still ranks; a re-exported function only counts once
+    let usage = type_usage(
+        dedup_reexports(&all_items)
+            .into_iter()
+            .filter_map(|item| docs::signature_of(&signatures, item)),
+    );
+
+    let mut by_crate: BTreeMap<&str, Vec<&TypeUsage>> = BTreeMap::new();
+    for type_usage in &usage {
+        let crate_name = type_usage.crate_name();
+        let listed = if crates.is_empty() {
+            crate_entries.iter().any(|entry| entry.name == crate_name)
+        } else {
+            crates.iter().any(|name| name == crate_name)
+        };
+        if listed {
+            by_crate.entry(crate_name).or_default().push(type_usage);
+        }
+    }
+
+    if by_crate.is_empty() {
+        println!(
+            "{} No function signatures mention the crates' types",
+            "✗".red().bold()
+        );
+        return Ok(());
+    }
+    for (crate_name, usage) in by_crate {
+        println!("\n  {}", crate_name.yellow().bold());
+        for type_usage in usage.into_iter().take(limit) {
+            let mut roles = vec![
+                format!("{} in", type_usage.inputs),
+                format!("{} out", type_usage.outputs),
+            ];
+            if type_usage.bounds > 0 {
+                roles.push(format!("{} bound", type_usage.bounds));
+            }
+            println!(
+                "  {:>6}  {} ({}) {}",
+                type_usage.functions,
+                format!("{}::{}", type_usage.path, type_usage.name).cyan(),
+                type_usage.item_type,
+                roles.join(", ").dimmed()
+            );
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,102 @@
+// Upgrading rdoc's cache after an update changes how it's laid out
+
+use color_eyre::Result;
+use colored::Colorize;
+use rdoc::human::count;
+use rdoc::index_file::{IndexFile, IndexJson};
+use rdoc::search_index::parse_search_index_lazy;
+use std::path::Path;
+
+use crate::cache;
+use crate::docs;
+use crate::remote::SEARCH_INDEX_FILE;
+
+/// Upgrade the cache to this rdoc's format, then check every cached remote
+/// site's index is still one rdoc reads. Sites that aren't are listed to be
+/// downloaded again, rather than removed.
+pub fn execute(quiet: bool) -> Result<()> {
+    let dir = cache::cache_dir()?;
+    if !quiet {
+        println!(
+            "{} Upgrading the cache in {}",
+            "→".cyan().bold(),
+            dir.display()
+        );
+    }
+
+    let Some(upgrade) = cache::upgrade(&dir)? else {
+        println!("{} No cache yet; nothing to upgrade", "ℹ".blue().bold());
+        return Ok(());
+    };
+    if upgrade.steps.is_empty() {
+        println!(
+            "{} The cache is up to date (format {})",
+            "✓".green().bold(),
+            cache::CACHE_FORMAT
+        );
+    } else {
+        println!(
+            "{} Upgraded the cache from format {} to {}",
+            "✓".green().bold(),
+            upgrade.from,
+            cache::CACHE_FORMAT
+        );
+        for step in &upgrade.steps {
+            println!("  {}", step.dimmed());
+        }
+    }
+
+    let mut sites: Vec<_> = std::fs::read_dir(dir.join("remote"))
+        .into_iter()
+        .flatten()
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|site| site.join(SEARCH_INDEX_FILE).is_file())
+        .collect();
+    if sites.is_empty() {
+        return Ok(());
+    }
+    sites.sort();
+
+    let mut unreadable = 0;
+    for site in &sites {
+        let name = site.file_name().unwrap_or_default().to_string_lossy();
+        match check_site(site) {
+            Ok(crates) => println!(
+                "  {} {}: {}{}",
+                "✓".green().bold(),
+                name,
+                count(crates, "crate", "crates"),
+                docs::detect_rustdoc_version(site)
+                    .map(|version| format!(" (rustdoc {})", version).dimmed().to_string())
+                    .unwrap_or_default()
+            ),
+            Err(err) => {
+                unreadable += 1;
+                println!("  {} {}: {}", "✗".yellow().bold(), name, err);
+            }
+        }
+    }
+    if unreadable > 0 {
+        println!(
+            "{} {}",
+            "✗".yellow().bold(),
+            if unreadable == 1 {
+                "1 cached site can't be read; download it again with --doc-url URL --refresh"
+                    .to_string()
+            } else {
+                format!(
+                    "{} can't be read; download them again with --doc-url URL --refresh",
+                    count(unreadable, "cached site", "cached sites")
+                )
+            }
+        );
+    }
+    Ok(())
+}
+
+/// How many crates the cached index in `site` has, or why it can't be read.
+fn check_site(site: &Path) -> Result<usize> {
+    let json = IndexJson::extract(IndexFile::open(&site.join(SEARCH_INDEX_FILE))?)?;
+    Ok(parse_search_index_lazy(&json)?.len())
+}
@@ -0,0 +1,166 @@
+use color_eyre::{Result, eyre::Context};
+use colored::Colorize;
+use rdoc::human::{count, number};
+use rdoc::lockfile::{LockedPackage, parse_lockfile};
+use std::path::Path;
+use tracing::info;
+
+use crate::docs;
+use crate::remote::{self, HttpOptions};
+
+/// Where dependencies' docs are downloaded from unless `--docs-host` says
+pub const DEFAULT_DOCS_HOST: &str = "https://docs.rs";
+
+/// Where to get each dependency's docs from
+pub struct WarmOptions<'a> {
+    /// Host serving `<name>/<version>/` documentation, like docs.rs
+    pub docs_host: &'a str,
+    /// Generate the docs with `cargo doc` instead of downloading them
+    pub local: bool,
+    pub http: &'a HttpOptions,
+    pub refresh: bool,
+    pub jobs: usize,
+}
+
+/// Pre-fetch documentation for every dependency in Cargo.lock into the cache
+pub fn execute(lockfile: &Path, options: &WarmOptions, quiet: bool) -> Result<()> {
+    if options.local {
+        if !quiet {
+            println!(
+                "{} Generating documentation for all dependencies",
+                "→".cyan().bold()
+            );
+        }
+        return docs::generate_local_docs(quiet);
+    }
+
+    let contents = std::fs::read_to_string(lockfile)
+        .wrap_err_with(|| format!("Failed to read {}", lockfile.display()))?;
+    let packages = registry_packages(&contents)
+        .wrap_err_with(|| format!("Failed to parse {}", lockfile.display()))?;
+
+    if !quiet {
+        println!(
+            "{} Fetching documentation for {} from {}",
+            "→".cyan().bold(),
+            count(packages.len(), "dependency", "dependencies"),
+            options.docs_host
+        );
+    }
+
+    let urls: Vec<String> = packages
+        .iter()
+        .map(|package| docs_url(options.docs_host, package))
+        .collect();
+    let results = remote::fetch_all(&urls, options.http, options.refresh, options.jobs);
+
+    let mut failed = 0;
+    for (package, result) in packages.iter().zip(results) {
+        match result {
+            Ok(path) => info!(
+                "cached {} {} at {}",
+                package.name,
+                package.version,
+                path.display()
+            ),
+            Err(err) => {
+                failed += 1;
+                // Failures are expected (e.g. docs.rs build failures), so keep going
+                eprintln!(
+                    "{} {} {}: {:#}",
+                    "✗".red().bold(),
+                    package.name,
+                    package.version,
+                    err
+                );
+            }
+        }
+    }
+
+    if !quiet {
+        println!(
+            "{} Cached documentation for {} of {}",
+            "✓".green().bold(),
+            number(packages.len() - failed),
+            count(packages.len(), "dependency", "dependencies")
+        );
+    }
+
+    Ok(())
+}
+
+/// Packages from a registry, which are the ones with hosted documentation.
+///
+/// Each name and version appears once even if the lockfile lists it twice.
+fn registry_packages(lockfile: &str) -> Result<Vec<LockedPackage>> {
+    let mut packages: Vec<_> = parse_lockfile(lockfile)?
+        .into_iter()
+        .filter(LockedPackage::is_registry)
+        .collect();
+    packages.sort_by(|a, b| (&a.name, &a.version).cmp(&(&b.name, &b.version)));
+    packages.dedup_by(|a, b| a.name == b.name && a.version == b.version);
+    Ok(packages)
+}
+
+/// Documentation root for one package version, e.g. `https://docs.rs/serde/1.0.219/`.
+fn docs_url(docs_host: &str, package: &LockedPackage) -> String {
+    format!(
+        "{}/{}/{}/",
+        docs_host.trim_end_matches('/'),
+        package.name,
+        package.version
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LOCKFILE: &str = r#"
+version = 4
+
+[[package]]
+name = "myapp"
+version = "0.1.0"
+dependencies = ["serde"]
+
+[[package]]
+name = "serde"
+version = "1.0.219"
+source = "registry+https://github.com/rust-lang/crates.io-index"
+checksum = "abc"
+
+[[package]]
+name = "bitflags"
+version = "1.3.2"
+source = "sparse+https://index.crates.io/"
+
+[[package]]
+name = "forked"
+version = "0.2.0"
+source = "git+https://github.com/example/forked#0123abc"
+"#;
+
+    #[test]
+    fn test_registry_packages() {
+        let names: Vec<_> = registry_packages(LOCKFILE)
+            .unwrap()
+            .into_iter()
+            .map(|package| format!("{} {}", package.name, package.version))
+            .collect();
+        assert_eq!(names, vec!["bitflags 1.3.2", "serde 1.0.219"]);
+    }
+
+    #[test]
+    fn test_docs_url() {
+        let package = LockedPackage {
+            name: "serde".to_string(),
+            version: "1.0.219".to_string(),
+            source: None,
+        };
+        assert_eq!(
+            docs_url("https://docs.rs/", &package),
+            "https://docs.rs/serde/1.0.219/"
+        );
+    }
+}
@@ -0,0 +1,316 @@
+// User and project configuration
+
+use color_eyre::{
+    Result,
+    eyre::{Context, eyre},
+};
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::hash::{DefaultHasher, Hash, Hasher};
+use std::path::{Path, PathBuf};
+use tracing::info;
+
+use crate::commands::scan::Profile;
+use crate::output;
+use rdoc::hidden::{HiddenItems, HideRule};
+use rdoc::ignore::IgnoreRules;
+use rdoc::matcher::MatcherKind;
+
+/// Environment variable pointing at an explicit config file.
+pub const CONFIG_ENV_VAR: &str = "RDOC_CONFIG";
+
+/// Config file name looked up in the current directory.
+pub const PROJECT_CONFIG_FILE: &str = ".rdoc.toml";
+
+/// File in the current directory listing crates and paths to leave out of
+/// searches and reports, kept apart from the config so it can be committed
+/// and shared like a `.gitignore`.
+pub const IGNORE_FILE: &str = ".rdocignore";
+
+/// Settings read from the config file.
+///
+/// Every section is optional; a missing file is the same as an empty one.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct Config {
+    /// Settings for fetching remote documentation
+    pub http: HttpConfig,
+
+    /// Where to look for a project's generated docs
+    pub docs: DocsConfig,
+
+    /// Defaults for `scan`
+    pub scan: ScanConfig,
+
+    /// `[aliases]` section: extra names for items, e.g. `hm = "std::collections::HashMap"`.
+    /// `scan` and `show` accept them like the aliases in the search index
+    pub aliases: BTreeMap<String, String>,
+}
+
+/// `[docs]` section: where local documentation lives.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct DocsConfig {
+    /// Doc roots to try, relative to the project (e.g. a custom `--out-dir` or
+    /// docs committed under `doc/`). Defaults to `target/doc` and `doc`.
+    pub roots: Vec<PathBuf>,
+
+    /// Where the local docs are published (e.g. `https://docs.example.com/api/`),
+    /// for the `absolute_url` of JSON results
+    pub base_url: Option<String>,
+
+    /// Link dependencies' items to docs.rs, as `--docs-rs` does
+    pub docs_rs: bool,
+}
+
+/// `[scan]` section: defaults for searching.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct ScanConfig {
+    /// Matching profile used when `--profile` isn't given (also by `repl`)
+    pub profile: Option<Profile>,
+
+    /// Matcher used when `--matcher` isn't given (also by `repl`)
+    pub matcher: Option<MatcherKind>,
+
+    /// Module globs whose items are hidden unless `--no-default-ignore` is
+    /// given, replacing the default `_*` and `*::_*`
+    pub hidden: Option<Vec<String>>,
+
+    /// Built-in noise rules to turn off, by name (see `rdoc rules list`)
+    pub disabled_rules: Vec<HideRule>,
+}
+
+impl ScanConfig {
+    /// The built-in noise rules as configured.
+    pub fn hidden_items(&self) -> Result<HiddenItems> {
+        let hidden = match &self.hidden {
+            Some(modules) => HiddenItems::with_modules(modules)
+                .wrap_err("Invalid module pattern in [scan] hidden")?,
+            None => HiddenItems::default(),
+        };
+        Ok(hidden.without(&self.disabled_rules))
+    }
+}
+
+/// `[http]` section: credentials and headers for private doc hosts.
+///
+/// Prefer the user config file or `RDOC_HTTP_TOKEN` for secrets rather than a
+/// project `.rdoc.toml` that may be committed.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct HttpConfig {
+    /// Bearer token sent as `Authorization: Bearer <token>`
+    pub token: Option<String>,
+
+    /// Username for HTTP basic auth
+    pub username: Option<String>,
+
+    /// Password for HTTP basic auth
+    pub password: Option<String>,
+
+    /// Extra headers sent with every request (e.g. for SSO proxies)
+    pub headers: BTreeMap<String, String>,
+}
+
+impl Config {
+    /// Load the config file, if there is one.
+    ///
+    /// Uses the first of: `$RDOC_CONFIG`, `./.rdoc.toml`, then `rdoc/config.toml`
+    /// in the platform config directory.
+    pub fn load() -> Result<Self> {
+        match config_path() {
+            Some(path) => Self::load_from(&path),
+            None => Ok(Self::default()),
+        }
+    }
+
+    fn load_from(path: &Path) -> Result<Self> {
+        info!("loading config from {}", path.display());
+        let content = std::fs::read_to_string(path)
+            .wrap_err_with(|| format!("Failed to read config file {}", path.display()))?;
+        toml::from_str(&content).wrap_err_with(|| format!("Invalid config file {}", path.display()))
+    }
+}
+
+/// The config file `rdoc alias` edits: the one in use, or a new user config
+/// file if there is none.
+pub fn editable_config_path() -> Result<PathBuf> {
+    config_path()
+        .or_else(|| dirs::config_dir().map(|dir| dir.join("rdoc").join("config.toml")))
+        .ok_or_else(|| eyre!("Could not find a config directory; set {}", CONFIG_ENV_VAR))
+}
+
+/// Set `alias` to `target` in the config file at `path`, or remove it when
+/// `target` is `None`, creating the file if needed. Returns whether the file
+/// had the alias before.
+///
+/// The rest of the file's settings are kept, but not its comments or layout.
+pub fn set_alias(path: &Path, alias: &str, target: Option<&str>) -> Result<bool> {
+    let content = match std::fs::read_to_string(path) {
+        Ok(content) => content,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => String::new(),
+        Err(err) => {
+            return Err(err)
+                .wrap_err_with(|| format!("Failed to read config file {}", path.display()));
+        }
+    };
+    let (content, existed) = with_alias(&content, alias, target)
+        .wrap_err_with(|| format!("Invalid config file {}", path.display()))?;
+    if let Some(dir) = path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+        std::fs::create_dir_all(dir)
+            .wrap_err_with(|| format!("Failed to create {}", dir.display()))?;
+    }
+    output::write_atomic(path, content)
+        .wrap_err_with(|| format!("Failed to write config file {}", path.display()))?;
+    Ok(existed)
+}
+
+/// `content` with `alias` set or removed in its `[aliases]` table, and whether
+/// the alias was there before.
+fn with_alias(content: &str, alias: &str, target: Option<&str>) -> Result<(String, bool)> {
+    let mut config: toml::Table = toml::from_str(content)?;
+    let aliases = config
+        .entry("aliases")
+        .or_insert_with(|| toml::Value::Table(toml::Table::new()))
+        .as_table_mut()
+        .ok_or_else(|| eyre!("[aliases] is not a table"))?;
+    let existed = match target {
+        Some(target) => aliases
+            .insert(alias.to_string(), toml::Value::String(target.to_string()))
+            .is_some(),
+        None => aliases.remove(alias).is_some(),
+    };
+    if aliases.is_empty() {
+        config.remove("aliases");
+    }
+    Ok((toml::to_string(&config)?, existed))
+}
+
+/// The rules in [`IGNORE_FILE`], or none if there isn't one.
+pub fn load_ignore_rules() -> Result<IgnoreRules> {
+    let content = match std::fs::read_to_string(IGNORE_FILE) {
+        Ok(content) => content,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => {
+            return Ok(IgnoreRules::default());
+        }
+        Err(err) => return Err(err).wrap_err_with(|| format!("Failed to read {}", IGNORE_FILE)),
+    };
+    info!("loading ignore rules from {}", IGNORE_FILE);
+    IgnoreRules::parse(&content).wrap_err_with(|| format!("Invalid {}", IGNORE_FILE))
+}
+
+/// A hash of the config file in effect and [`IGNORE_FILE`], to tell whether
+/// two rdoc processes are configured alike without passing around the
+/// credentials a config may hold.
+pub fn fingerprint() -> u64 {
+    let mut hasher = DefaultHasher::new();
+    let path = config_path();
+    path.as_ref()
+        .and_then(|path| std::fs::read(path).ok())
+        .hash(&mut hasher);
+    path.hash(&mut hasher);
+    std::fs::read(IGNORE_FILE).ok().hash(&mut hasher);
+    hasher.finish()
+}
+
+fn config_path() -> Option<PathBuf> {
+    if let Some(path) = std::env::var_os(CONFIG_ENV_VAR) {
+        return Some(PathBuf::from(path));
+    }
+
+    let project_config = PathBuf::from(PROJECT_CONFIG_FILE);
+    if project_config.exists() {
+        return Some(project_config);
+    }
+
+    dirs::config_dir()
+        .map(|dir| dir.join("rdoc").join("config.toml"))
+        .filter(|path| path.exists())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_http_section() {
+        let config: Config = toml::from_str(
+            r#"
+            [http]
+            token = "secret"
+            username = "me"
+            password = "hunter2"
+            headers = { "X-Proxy-Auth" = "abc" }
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(config.http.token.as_deref(), Some("secret"));
+        assert_eq!(config.http.username.as_deref(), Some("me"));
+        assert_eq!(config.http.password.as_deref(), Some("hunter2"));
+        assert_eq!(config.http.headers["X-Proxy-Auth"], "abc");
+    }
+
+    #[test]
+    fn test_empty_config() {
+        let config: Config = toml::from_str("").unwrap();
+        assert!(config.http.token.is_none());
+        assert!(config.http.headers.is_empty());
+        assert!(config.docs.roots.is_empty());
+        assert!(config.aliases.is_empty());
+        assert!(config.scan.profile.is_none());
+    }
+
+    #[test]
+    fn test_parse_scan_section() {
+        let config: Config = toml::from_str(
+            r#"
+            [scan]
+            profile = "docs"
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(config.scan.profile, Some(Profile::Docs));
+        let config: Config = toml::from_str("[scan]\nmatcher = \"nucleo\"").unwrap();
+        assert_eq!(config.scan.matcher, Some(MatcherKind::Nucleo));
+        assert!(toml::from_str::<Config>("[scan]\nprofile = \"loose\"").is_err());
+    }
+
+    #[test]
+    fn test_with_alias() {
+        let content = r#"
+            [http]
+            token = "secret"
+            "#;
+
+        let (content, existed) =
+            with_alias(content, "hm", Some("std::collections::HashMap")).unwrap();
+        assert!(!existed);
+        let config: Config = toml::from_str(&content).unwrap();
+        assert_eq!(config.aliases["hm"], "std::collections::HashMap");
+        assert_eq!(config.http.token.as_deref(), Some("secret"));
+
+        let (content, existed) = with_alias(&content, "hm", None).unwrap();
+        assert!(existed);
+        assert!(!content.contains("aliases"));
+        assert!(!with_alias(&content, "hm", None).unwrap().1);
+    }
+
+    #[test]
+    fn test_parse_docs_section() {
+        let config: Config = toml::from_str(
+            r#"
+            [docs]
+            roots = ["book/api", "doc"]
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            config.docs.roots,
+            [PathBuf::from("book/api"), PathBuf::from("doc")]
+        );
+    }
+}
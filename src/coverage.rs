@@ -0,0 +1,82 @@
+// Documentation coverage from the empty-description bitmap
+
+use crate::search_items::SearchItem;
+
+/// How many of a set of items have a doc comment.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct DocCoverage {
+    /// Number of items counted
+    pub total: usize,
+    /// Number of items whose bit is set in the `e` bitmap
+    pub undocumented: usize,
+}
+
+impl DocCoverage {
+    /// Count documented and undocumented items.
+    pub fn of<'a>(items: impl IntoIterator<Item = &'a SearchItem>) -> Self {
+        let mut coverage = Self::default();
+        for item in items {
+            coverage.add(item);
+        }
+        coverage
+    }
+
+    /// Count one more item.
+    pub fn add(&mut self, item: &SearchItem) {
+        self.total += 1;
+        if item.empty_description {
+            self.undocumented += 1;
+        }
+    }
+
+    /// Percentage of items with a doc comment; an empty set is fully covered.
+    pub fn percent(&self) -> f64 {
+        if self.total == 0 {
+            return 100.0;
+        }
+        100.0 * (self.total - self.undocumented) as f64 / self.total as f64
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::search_index::CrateData;
+    use crate::search_items::decode_crate;
+
+    #[test]
+    fn test_coverage_percent() {
+        assert_eq!(DocCoverage::default().percent(), 100.0);
+
+        let coverage = DocCoverage {
+            total: 4,
+            undocumented: 1,
+        };
+        assert_eq!(coverage.percent(), 75.0);
+    }
+
+    #[test]
+    fn test_coverage_of_items() {
+        let crate_data = CrateData {
+            types: "HHH".to_string(),
+            names: vec!["a".to_string(), "b".to_string(), "c".to_string()],
+            paths: vec![],
+            parent_items: vec![],
+            reexports: vec![],
+            i: String::new(),
+            f: String::new(),
+            desc: String::new(),
+            param_types: vec![],
+            impl_disambiguators: vec![],
+            c: String::new(),
+            e: String::new(),
+            aliases: None,
+        };
+        let mut items = decode_crate("test_crate", &crate_data);
+        items[1].empty_description = true;
+
+        let coverage = DocCoverage::of(&items);
+        assert_eq!(coverage.total, 3);
+        assert_eq!(coverage.undocumented, 1);
+    }
+}
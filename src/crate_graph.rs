@@ -0,0 +1,193 @@
+// How each documented crate relates to the workspace, from `cargo metadata`
+
+/// Where docs.rs publishes every version of every crate
+pub const DOCS_RS_URL: &str = "https://docs.rs/";
+
+use crate::normalize;
+use serde_json::Value;
+use std::collections::HashMap;
+
+/// How a crate is related to the workspace being documented, closest first.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Relation {
+    /// One of the workspace's own crates
+    Workspace,
+    /// A dependency of a workspace crate
+    Direct,
+    /// Only a dependency of other dependencies
+    Transitive,
+}
+
+/// The [`Relation`] of every library crate in a cargo dependency graph, by the
+/// name rustdoc gives it.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CrateGraph {
+    relations: HashMap<String, Relation>,
+    /// Package name and version of each library, by its crate name
+    packages: HashMap<String, (String, String)>,
+}
+
+impl CrateGraph {
+    /// Build the graph from the output of `cargo metadata --format-version 1`
+    /// (run with dependencies, so it has a `resolve` graph).
+    pub fn from_metadata(metadata: &Value) -> Self {
+        let packages = metadata["packages"].as_array().into_iter().flatten();
+        let libraries: Vec<(&str, String, &Value)> = packages
+            .filter_map(|package| Some((package["id"].as_str()?, library_name(package)?, package)))
+            .collect();
+        let names: HashMap<&str, String> = libraries
+            .iter()
+            .map(|(id, name, _)| (*id, name.clone()))
+            .collect();
+        let packages = libraries
+            .iter()
+            .filter_map(|(_, name, package)| {
+                let package_name = package["name"].as_str()?.to_string();
+                let version = package["version"].as_str()?.to_string();
+                Some((name.clone(), (package_name, version)))
+            })
+            .collect();
+        let members: Vec<&str> = metadata["workspace_members"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter_map(Value::as_str)
+            .collect();
+
+        let mut relations: HashMap<String, Relation> = names
+            .values()
+            .map(|name| (name.clone(), Relation::Transitive))
+            .collect();
+        let mut relate = |id: &str, relation: Relation| {
+            if let Some(name) = names.get(id) {
+                let current = relations.entry(name.clone()).or_insert(relation);
+                *current = (*current).min(relation);
+            }
+        };
+
+        for node in metadata["resolve"]["nodes"]
+            .as_array()
+            .into_iter()
+            .flatten()
+        {
+            if !node["id"].as_str().is_some_and(|id| members.contains(&id)) {
+                continue;
+            }
+            for dep in node["deps"].as_array().into_iter().flatten() {
+                if let Some(id) = dep["pkg"].as_str() {
+                    relate(id, Relation::Direct);
+                }
+            }
+        }
+        for id in &members {
+            relate(id, Relation::Workspace);
+        }
+
+        Self {
+            relations,
+            packages,
+        }
+    }
+
+    /// How `crate_name` relates to the workspace, or `None` if it isn't in the
+    /// graph (as for the standard library).
+    pub fn relation(&self, crate_name: &str) -> Option<Relation> {
+        self.relations
+            .get(&*normalize::crate_name(crate_name))
+            .copied()
+    }
+
+    /// Every library crate in the graph.
+    pub fn crate_names(&self) -> impl Iterator<Item = &str> {
+        self.relations.keys().map(String::as_str)
+    }
+
+    /// The root of `crate_name`'s docs on docs.rs for the version in the graph
+    /// (e.g. `https://docs.rs/serde-json/1.0.0/`), or `None` for workspace
+    /// crates, which may not be published, and crates outside the graph.
+    pub fn docs_rs_url(&self, crate_name: &str) -> Option<String> {
+        if self.relation(crate_name)? == Relation::Workspace {
+            return None;
+        }
+        let (package, version) = self.packages.get(&*normalize::crate_name(crate_name))?;
+        Some(format!("{}{}/{}/", DOCS_RS_URL, package, version))
+    }
+}
+
+/// The name rustdoc gives a package's library: its library target's name,
+/// with `-` replaced by `_`. `None` for packages without a library.
+pub fn library_name(package: &Value) -> Option<String> {
+    package["targets"]
+        .as_array()?
+        .iter()
+        .find(|target| {
+            target["kind"].as_array().is_some_and(|kinds| {
+                kinds
+                    .iter()
+                    .any(|kind| kind == "lib" || kind == "proc-macro")
+            })
+        })
+        .and_then(|target| target["name"].as_str())
+        .map(|name| name.replace('-', "_"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn package(id: &str, name: &str, kind: &str) -> Value {
+        let (package_name, version) = id.split_once(' ').unwrap();
+        json!({
+            "id": id,
+            "name": package_name,
+            "version": version,
+            "targets": [{ "name": name, "kind": [kind] }],
+        })
+    }
+
+    #[test]
+    fn test_relations() {
+        // app depends on serde-json, which depends on serde; tool is a binary
+        let metadata = json!({
+            "packages": [
+                package("app 0.1.0", "app", "lib"),
+                package("serde-json 1.0.0", "serde-json", "lib"),
+                package("serde 1.0.0", "serde", "lib"),
+                package("derive 1.0.0", "derive", "proc-macro"),
+                package("tool 0.1.0", "tool", "bin"),
+            ],
+            "workspace_members": ["app 0.1.0", "tool 0.1.0"],
+            "resolve": { "nodes": [
+                { "id": "app 0.1.0", "deps": [
+                    { "name": "json", "pkg": "serde-json 1.0.0" },
+                    { "name": "derive", "pkg": "derive 1.0.0" },
+                ] },
+                { "id": "serde-json 1.0.0", "deps": [{ "name": "serde", "pkg": "serde 1.0.0" }] },
+                { "id": "serde 1.0.0", "deps": [] },
+            ] },
+        });
+
+        let graph = CrateGraph::from_metadata(&metadata);
+
+        assert_eq!(graph.relation("app"), Some(Relation::Workspace));
+        assert_eq!(graph.relation("serde_json"), Some(Relation::Direct));
+        assert_eq!(graph.relation("serde-json"), Some(Relation::Direct));
+        assert_eq!(graph.relation("derive"), Some(Relation::Direct));
+        assert_eq!(graph.relation("serde"), Some(Relation::Transitive));
+        assert_eq!(graph.relation("tool"), None);
+        assert_eq!(graph.relation("std"), None);
+        assert!(Relation::Workspace < Relation::Direct);
+
+        assert_eq!(
+            graph.docs_rs_url("serde_json").as_deref(),
+            Some("https://docs.rs/serde-json/1.0.0/")
+        );
+        assert_eq!(
+            graph.docs_rs_url("serde-json").as_deref(),
+            Some("https://docs.rs/serde-json/1.0.0/")
+        );
+        assert_eq!(graph.docs_rs_url("app"), None);
+        assert_eq!(graph.docs_rs_url("std"), None);
+    }
+}
@@ -0,0 +1,366 @@
+// Handing `scan` queries to a running `rdoc serve`, so they're answered from
+// docs it already decoded instead of decoding them again
+
+use clap::Parser;
+use color_eyre::{
+    Result,
+    eyre::{Context, eyre},
+};
+use serde::{Deserialize, Serialize};
+use std::ffi::OsString;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use tracing::{debug, info};
+
+use rdoc::timings::Timings;
+
+use crate::cache;
+use crate::commands::scan::{ScanOptions, answerable};
+use crate::config::{self, Config};
+use crate::docs::DocSource;
+use crate::output;
+use crate::{Cli, Commands};
+
+/// How long `scan` waits to connect to a daemon before decoding the docs itself
+const CONNECT_TIMEOUT: Duration = Duration::from_millis(250);
+
+/// What a running `rdoc serve` records about itself for `scan` to find it
+#[derive(Debug, Serialize, Deserialize)]
+struct Announcement {
+    /// Where it answers, as reached from this machine
+    addr: SocketAddr,
+    pid: u32,
+}
+
+/// A daemon's record that it answers for a search index, removed when dropped
+/// as the daemon exits. One killed before then leaves it behind, for `scan`
+/// to remove when it finds nothing answering.
+#[derive(Debug)]
+pub struct Registration {
+    path: PathBuf,
+}
+
+impl Registration {
+    /// Record that the server listening on `addr` answers for the search
+    /// index at `search_index`, replacing any earlier daemon's record.
+    pub fn new(search_index: &Path, addr: SocketAddr) -> Result<Self> {
+        let path = cache::daemon_path(search_index)?;
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)
+                .wrap_err_with(|| format!("Failed to create {}", dir.display()))?;
+        }
+        let announcement = Announcement {
+            addr: reachable(addr),
+            pid: std::process::id(),
+        };
+        let contents = serde_json::to_vec(&announcement).wrap_err("Failed to record the daemon")?;
+        output::write_atomic(&path, contents)?;
+        Ok(Self { path })
+    }
+}
+
+impl Drop for Registration {
+    fn drop(&mut self) {
+        // A daemon started since for the same docs has replaced the record
+        if read_announcement(&self.path).is_some_and(|found| found.pid == std::process::id()) {
+            let _ = std::fs::remove_file(&self.path);
+        }
+    }
+}
+
+/// `addr`, with a wildcard address (listening everywhere) replaced by loopback.
+fn reachable(addr: SocketAddr) -> SocketAddr {
+    let ip = match addr.ip() {
+        IpAddr::V4(ip) if ip.is_unspecified() => IpAddr::V4(Ipv4Addr::LOCALHOST),
+        IpAddr::V6(ip) if ip.is_unspecified() => IpAddr::V6(Ipv6Addr::LOCALHOST),
+        ip => ip,
+    };
+    SocketAddr::new(ip, addr.port())
+}
+
+fn read_announcement(path: &Path) -> Option<Announcement> {
+    let contents = std::fs::read(path).ok()?;
+    serde_json::from_slice(&contents).ok()
+}
+
+/// What a `scan`'s answer depends on besides its own arguments and the docs it
+/// searches, which a daemon must share with a client to answer for it.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Settings {
+    /// The directory rdoc runs in, where the project's config and
+    /// `.rdocignore` are read from
+    pub cwd: PathBuf,
+    /// Where the docs are and where they link to
+    pub source: SourceSettings,
+    /// A hash of the config and `.rdocignore` in effect (see [`config::fingerprint`])
+    pub config: u64,
+}
+
+/// The parts of a [`DocSource`] that change a `scan`'s answer, as the global
+/// options (`--doc-dir`, `--doc-url`, `--base-url`, `--docs-rs`) and the
+/// config set them
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SourceSettings {
+    Local {
+        doc_dir: Option<PathBuf>,
+        doc_roots: Vec<PathBuf>,
+        base_url: Option<String>,
+        docs_rs: bool,
+    },
+    Remote {
+        base_url: String,
+    },
+}
+
+impl Settings {
+    /// This process's settings, for docs from `source`.
+    pub fn current(source: &DocSource) -> Self {
+        let source = match source {
+            DocSource::Local {
+                doc_dir,
+                doc_roots,
+                base_url,
+                docs_rs,
+                ..
+            } => SourceSettings::Local {
+                doc_dir: doc_dir.clone(),
+                doc_roots: doc_roots.clone(),
+                base_url: base_url.clone(),
+                docs_rs: *docs_rs,
+            },
+            DocSource::Remote { base_url, .. } => SourceSettings::Remote {
+                base_url: base_url.clone(),
+            },
+        };
+        Self {
+            cwd: std::env::current_dir().unwrap_or_default(),
+            source,
+            config: config::fingerprint(),
+        }
+    }
+}
+
+/// A `scan` sent to a daemon: the whole command line, so the daemon parses
+/// and configures it exactly as it would be here.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ScanRequest {
+    /// rdoc's arguments, without the program name
+    pub args: Vec<String>,
+    /// The search index the client found, which the daemon must be serving
+    pub search_index: PathBuf,
+    /// The client's settings, which the daemon must share
+    pub settings: Settings,
+    /// Whether the client's terminal shows hyperlinks
+    pub hyperlinks: bool,
+}
+
+/// A daemon's answer to a [`ScanRequest`]
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ScanAnswer {
+    /// What `scan` prints after its status messages, with colors
+    pub output: String,
+    pub results: usize,
+    /// How long the daemon took, when `--timings` asked for them
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timings: Option<Timings>,
+}
+
+/// `args` as strings, or `None` if any isn't UTF-8.
+fn utf8_args(args: impl IntoIterator<Item = OsString>) -> Option<Vec<String>> {
+    args.into_iter().map(|arg| arg.into_string().ok()).collect()
+}
+
+/// Have a running daemon for the docs at `search_index` from `source` answer
+/// this process's `scan` command line.
+///
+/// `None` when no daemon is running for them, or the one running can't answer
+/// as decoding them here would (it's still reloading them, or was started with
+/// other settings, say), so the caller should decode them itself. A query the
+/// daemon rejects is an error, as it would be here.
+pub fn scan(
+    search_index: &Path,
+    source: &DocSource,
+    hyperlinks: bool,
+) -> Result<Option<ScanAnswer>> {
+    let Ok(path) = cache::daemon_path(search_index) else {
+        return Ok(None);
+    };
+    let Some(announcement) = read_announcement(&path) else {
+        return Ok(None);
+    };
+    // The request carries them as JSON strings, so a command line with one
+    // that isn't UTF-8 is answered here
+    let Some(args) = utf8_args(std::env::args_os().skip(1)) else {
+        debug!(
+            "not asking the daemon at {}: an argument isn't UTF-8",
+            announcement.addr
+        );
+        return Ok(None);
+    };
+    let request = ScanRequest {
+        args,
+        search_index: search_index.to_path_buf(),
+        settings: Settings::current(source),
+        hyperlinks,
+    };
+    let body = serde_json::to_string(&request).wrap_err("Failed to encode the query")?;
+    let agent: ureq::Agent = ureq::Agent::config_builder()
+        // Error statuses carry a message in their body, which we want to read
+        .http_status_as_error(false)
+        .timeout_connect(Some(CONNECT_TIMEOUT))
+        .build()
+        .into();
+    let response = agent
+        .post(format!("http://{}/scan", announcement.addr))
+        .header("Content-Type", "application/json")
+        .send(body);
+    let mut response = match response {
+        Ok(response) => response,
+        Err(err) => {
+            debug!(
+                "no daemon answering at {} ({}); removing its record",
+                announcement.addr, err
+            );
+            let _ = std::fs::remove_file(&path);
+            return Ok(None);
+        }
+    };
+    let status = response.status().as_u16();
+    let body = match response.body_mut().read_to_string() {
+        Ok(body) => body,
+        Err(err) => {
+            debug!("failed to read the daemon's answer: {}", err);
+            return Ok(None);
+        }
+    };
+    match status {
+        200 => {
+            let Ok(mut answer) = serde_json::from_str::<ScanAnswer>(&body) else {
+                debug!("the daemon at {} answered unexpectedly", announcement.addr);
+                return Ok(None);
+            };
+            info!("answered by the daemon at {}", announcement.addr);
+            if !colored::control::SHOULD_COLORIZE.should_colorize() {
+                answer.output = strip_colors(&answer.output);
+            }
+            Ok(Some(answer))
+        }
+        400 => {
+            let message = serde_json::from_str::<serde_json::Value>(&body)
+                .ok()
+                .and_then(|error| error["error"].as_str().map(str::to_string))
+                .unwrap_or(body);
+            Err(eyre!(message))
+        }
+        status => {
+            debug!(
+                "the daemon at {} can't answer ({}): {}",
+                announcement.addr, status, body
+            );
+            Ok(None)
+        }
+    }
+}
+
+/// The query and options of a [`ScanRequest`]'s command line, parsed and
+/// configured with `config` as `main` does, or `None` if it isn't a `scan` a
+/// daemon answers (one writing a file, say, or from another rdoc version).
+pub fn parse_scan(args: &[String], config: &Config) -> Result<Option<(String, ScanOptions)>> {
+    let args = std::iter::once("rdoc").chain(args.iter().map(String::as_str));
+    let Ok(Cli {
+        command: Some(Commands::Scan(args)),
+        ..
+    }) = Cli::try_parse_from(args)
+    else {
+        return Ok(None);
+    };
+    let (Some(symbol), false) = (&args.symbol, args.stdin) else {
+        return Ok(None);
+    };
+    let options = args.options(config)?;
+    Ok(answerable(symbol, &options).then(|| (symbol.clone(), options)))
+}
+
+/// `text` without its ANSI color and style escapes (`ESC [ ... m`), keeping
+/// the hyperlink ones.
+fn strip_colors(text: &str) -> String {
+    let mut stripped = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find("\x1b[") {
+        stripped.push_str(&rest[..start]);
+        let escape = &rest[start + 2..];
+        match escape.find(|c: char| !(c.is_ascii_digit() || c == ';')) {
+            Some(end) if escape[end..].starts_with('m') => rest = &escape[end + 1..],
+            _ => {
+                stripped.push_str("\x1b[");
+                rest = escape;
+            }
+        }
+    }
+    stripped.push_str(rest);
+    stripped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_strip_colors() {
+        assert_eq!(
+            strip_colors("\x1b[1;32m✓\x1b[0m Found \x1b[36mfrom_str\x1b[0m"),
+            "✓ Found from_str"
+        );
+        let link = "\x1b]8;;file:///doc/index.html\x1b\\name\x1b]8;;\x1b\\";
+        assert_eq!(strip_colors(link), link);
+        assert_eq!(strip_colors("a \x1b[2K b"), "a \x1b[2K b");
+    }
+
+    #[test]
+    fn test_parse_scan() {
+        let config = Config::default();
+        let args =
+            |args: &[&str]| -> Vec<String> { args.iter().map(|arg| arg.to_string()).collect() };
+
+        let (symbol, options) = parse_scan(
+            &args(&["-q", "scan", "from_str", "--case", "sensitive"]),
+            &config,
+        )
+        .unwrap()
+        .unwrap();
+        assert_eq!(symbol, "from_str");
+        assert!(options.case.is_sensitive("from_str"));
+        assert!(options.hidden.is_some());
+
+        // Nothing a daemon shouldn't do for someone else, or can't answer
+        for refused in [
+            &["scan", "x", "--output", "results.txt"][..],
+            &["scan", "x", "--format", "exec:sh"],
+            &["scan", "--stdin"],
+            &["scan", "-> Value"],
+            &["show", "serde::Serialize"],
+            &["scan", "x", "--no-such-flag"],
+        ] {
+            assert!(
+                parse_scan(&args(refused), &config).unwrap().is_none(),
+                "{:?}",
+                refused
+            );
+        }
+        assert!(parse_scan(&args(&["scan", "x", "--exclude-crate", "["]), &config).is_err());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_utf8_args() {
+        use std::os::unix::ffi::OsStringExt;
+
+        assert_eq!(
+            utf8_args([OsString::from("scan"), OsString::from("Größe")]),
+            Some(vec!["scan".to_string(), "Größe".to_string()])
+        );
+        let invalid = OsString::from_vec(b"results-\xff.txt".to_vec());
+        assert_eq!(utf8_args([OsString::from("scan"), invalid]), None);
+    }
+}
@@ -0,0 +1,356 @@
+// Item descriptions stored in search.desc shard files
+
+use crate::events::EVENT_TARGET;
+use crate::resources;
+use crate::search_items::SearchItem;
+use crate::vlq::VlqHexDecoder;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use tracing::{debug, info};
+
+/// Where an item's description is stored: the `index`th line of a crate's
+/// description shard number `shard`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DescriptionLocation {
+    pub shard: usize,
+    pub index: usize,
+}
+
+/// Path of a description shard file, relative to the documentation root.
+///
+/// Rustdoc splits each crate's descriptions into shards written to
+/// `search.desc/<crate>/<crate>-desc-<shard>-<suffix>.js`, where `suffix` is
+/// the docs' `--resource-suffix` (usually empty).
+pub fn shard_path(crate_name: &str, shard: usize, suffix: &str) -> String {
+    format!(
+        "search.desc/{}/{}",
+        crate_name,
+        shard_file_name(crate_name, shard, suffix)
+    )
+}
+
+/// Location of a description shard file on disk, under the documentation root.
+///
+/// Unlike [`shard_path`], which is also used in URLs, this joins the path with
+/// the platform's separator.
+pub fn shard_file(doc_root: &Path, crate_name: &str, shard: usize, suffix: &str) -> PathBuf {
+    doc_root
+        .join("search.desc")
+        .join(crate_name)
+        .join(shard_file_name(crate_name, shard, suffix))
+}
+
+fn shard_file_name(crate_name: &str, shard: usize, suffix: &str) -> String {
+    format!("{}-desc-{}-{}.js", crate_name, shard, suffix)
+}
+
+/// Number of description shards for a crate, from its `D` field.
+///
+/// `D` holds one VLQ-encoded length per shard.
+pub fn shard_count(shard_lengths: &str) -> usize {
+    VlqHexDecoder::new(shard_lengths).count()
+}
+
+/// Work out where each of a crate's `item_count` items has its description,
+/// mirroring rustdoc's search.js.
+///
+/// `is_empty` reports whether a bit index is set in the `e` bitmap; those items
+/// have no description and take up no space in the shards. Bit 0 is the crate
+/// itself, whose description comes first.
+pub fn description_locations(
+    shard_lengths: &str,
+    item_count: usize,
+    is_empty: impl Fn(u32) -> bool,
+) -> Vec<Option<DescriptionLocation>> {
+    let mut lengths = VlqHexDecoder::new(shard_lengths);
+    let mut shard = 0;
+    let mut shard_len = lengths.next().unwrap_or(0).max(0) as usize;
+    let mut index = usize::from(!is_empty(0));
+
+    (0..item_count)
+        .map(|i| {
+            let bit_index = i as u32 + 1;
+            if is_empty(bit_index) {
+                return None;
+            }
+
+            if index >= shard_len {
+                shard += 1;
+                shard_len = lengths.next().unwrap_or(0).max(0) as usize;
+                index = 0;
+            }
+
+            let location = DescriptionLocation { shard, index };
+            index += 1;
+            Some(location)
+        })
+        .collect()
+}
+
+/// Extract the descriptions from a shard file's contents.
+///
+/// Shards are a single call: `searchState.loadedDescShard("crate", 0, "one\ntwo")`,
+/// with one description (as HTML) per line.
+pub fn parse_shard(content: &str) -> Option<Vec<String>> {
+    let start = content.find("loadedDescShard(")? + "loadedDescShard(".len();
+    let end = content.rfind(')')?;
+    let args = content.get(start..end)?;
+
+    // The arguments are JSON values, so they parse as a JSON array
+    let (_crate_name, _shard, descriptions): (String, usize, String) =
+        serde_json::from_str(&format!("[{}]", args)).ok()?;
+    Some(descriptions.split('\n').map(str::to_string).collect())
+}
+
+/// Strip the HTML from a description, leaving its text.
+pub fn plain_text(html: &str) -> String {
+    let mut text = String::with_capacity(html.len());
+    let mut in_tag = false;
+    for c in html.chars() {
+        match c {
+            '<' => in_tag = true,
+            '>' if in_tag => in_tag = false,
+            _ if !in_tag => text.push(c),
+            _ => {}
+        }
+    }
+
+    text.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&#39;", "'")
+        .replace("&amp;", "&")
+}
+
+/// Shards a long-running session keeps loaded; rustdoc caps shards at about
+/// 128 KiB, so this bounds the cache to a few megabytes.
+pub const DEFAULT_CACHED_SHARDS: usize = 64;
+
+/// A loaded shard, or `None` if it's missing or malformed
+struct CachedShard {
+    descriptions: Option<Vec<String>>,
+    /// When the shard was last read, for evicting the least recently used
+    last_used: u64,
+}
+
+/// Loads description shards from a documentation root on demand.
+///
+/// Each shard is read once and kept; missing or malformed shards are
+/// remembered so they aren't retried. A store made with
+/// [`DescriptionStore::with_capacity`] keeps only the most recently used
+/// shards, for sessions that look up descriptions for as long as they run.
+pub struct DescriptionStore {
+    doc_root: PathBuf,
+    /// The docs' `--resource-suffix`, which shard file names end with
+    suffix: String,
+    shards: HashMap<(String, usize), CachedShard>,
+    /// Most shards kept at once; `None` keeps every shard loaded
+    capacity: Option<usize>,
+    /// Counts lookups, to order shards by their last use
+    clock: u64,
+}
+
+impl DescriptionStore {
+    /// Create a store for docs whose search-index.js is in `doc_root`.
+    pub fn new(doc_root: impl Into<PathBuf>) -> Self {
+        let doc_root = doc_root.into();
+        Self {
+            suffix: resources::suffix_in(&doc_root),
+            doc_root,
+            shards: HashMap::new(),
+            capacity: None,
+            clock: 0,
+        }
+    }
+
+    /// Create a store keeping at most `capacity` shards, evicting the least
+    /// recently used one to load another.
+    pub fn with_capacity(doc_root: impl Into<PathBuf>, capacity: usize) -> Self {
+        Self {
+            capacity: Some(capacity.max(1)),
+            ..Self::new(doc_root)
+        }
+    }
+
+    /// The item's description as HTML, or `None` if it has none or its shard
+    /// couldn't be loaded.
+    pub fn get(&mut self, item: &SearchItem) -> Option<&str> {
+        let location = item.description_location?;
+        self.description(&item.crate_name, location)
+    }
+
+    /// The description at `location` among `crate_name`'s shards.
+    fn description(&mut self, crate_name: &str, location: DescriptionLocation) -> Option<&str> {
+        self.clock += 1;
+        let key = (crate_name.to_string(), location.shard);
+        if !self.shards.contains_key(&key) {
+            self.evict();
+            let path = shard_file(&self.doc_root, crate_name, location.shard, &self.suffix);
+            let descriptions = std::fs::read_to_string(&path)
+                .ok()
+                .and_then(|content| parse_shard(&content));
+            match &descriptions {
+                Some(descriptions) => info!(
+                    target: EVENT_TARGET,
+                    event = "shard_loaded",
+                    crate_name,
+                    shard = location.shard,
+                    descriptions = descriptions.len()
+                ),
+                None => debug!("no descriptions available from {}", path.display()),
+            }
+            self.shards.insert(
+                key.clone(),
+                CachedShard {
+                    descriptions,
+                    last_used: 0,
+                },
+            );
+        }
+
+        let shard = self.shards.get_mut(&key)?;
+        shard.last_used = self.clock;
+        shard
+            .descriptions
+            .as_ref()?
+            .get(location.index)
+            .map(String::as_str)
+    }
+
+    /// Make room for one more shard, if the store is full.
+    fn evict(&mut self) {
+        let Some(capacity) = self.capacity else {
+            return;
+        };
+        while self.shards.len() >= capacity {
+            let Some(oldest) = self
+                .shards
+                .iter()
+                .min_by_key(|(_, shard)| shard.last_used)
+                .map(|(key, _)| key.clone())
+            else {
+                return;
+            };
+            debug!("evicting description shard {} of {}", oldest.1, oldest.0);
+            self.shards.remove(&oldest);
+        }
+    }
+
+    /// Number of shards currently loaded (or remembered as missing).
+    pub fn cached_shards(&self) -> usize {
+        self.shards.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_shard_path() {
+        assert_eq!(
+            shard_path("serde", 0, ""),
+            "search.desc/serde/serde-desc-0-.js"
+        );
+        assert_eq!(
+            shard_path("syn", 2, "-1.78.0"),
+            "search.desc/syn/syn-desc-2--1.78.0.js"
+        );
+    }
+
+    #[test]
+    fn test_shard_file() {
+        let path = shard_file(Path::new("target/doc"), "serde", 1, "");
+        assert!(
+            path.ends_with(
+                Path::new("search.desc")
+                    .join("serde")
+                    .join("serde-desc-1-.js")
+            )
+        );
+    }
+
+    #[cfg(windows)]
+    #[test]
+    fn test_shard_file_uses_windows_separators() {
+        let path = shard_file(Path::new(r"C:\project\target\doc"), "serde", 0, "");
+        assert_eq!(
+            path.to_str(),
+            Some(r"C:\project\target\doc\search.desc\serde\serde-desc-0-.js")
+        );
+    }
+
+    #[test]
+    fn test_shard_count() {
+        // "Gl" is a single two-byte length, "C@n" a single three-byte length
+        assert_eq!(shard_count("Gl"), 1);
+        assert_eq!(shard_count("C@n"), 1);
+        assert_eq!(shard_count("C@nd"), 2);
+        assert_eq!(shard_count(""), 0);
+    }
+
+    #[test]
+    fn test_description_locations() {
+        // Shards of 2 and 2 descriptions; the crate (bit 0) takes the first slot
+        // and item 1 (bit 2) has no description
+        let locations = description_locations("dd", 4, |bit| bit == 2);
+
+        let at = |shard, index| Some(DescriptionLocation { shard, index });
+        assert_eq!(locations, vec![at(0, 1), None, at(1, 0), at(1, 1)]);
+    }
+
+    #[test]
+    fn test_parse_shard() {
+        let content = r#"searchState.loadedDescShard("mylib", 0, "First line\nUses <code>&quot;quotes&quot;</code>\n")"#;
+        assert_eq!(
+            parse_shard(content).unwrap(),
+            vec!["First line", "Uses <code>&quot;quotes&quot;</code>", ""]
+        );
+        assert_eq!(parse_shard("not a shard"), None);
+    }
+
+    #[test]
+    fn test_store_evicts_least_recently_used() {
+        let dir =
+            std::env::temp_dir().join(format!("rdoc-descriptions-test-{}", std::process::id()));
+        for shard in 0..3 {
+            let path = shard_file(&dir, "mylib", shard, "");
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            let content = format!(
+                "searchState.loadedDescShard(\"mylib\", {}, \"shard {}\")",
+                shard, shard
+            );
+            std::fs::write(&path, content).unwrap();
+        }
+        let at = |shard| DescriptionLocation { shard, index: 0 };
+
+        let mut store = DescriptionStore::with_capacity(&dir, 2);
+        assert_eq!(store.description("mylib", at(0)), Some("shard 0"));
+        assert_eq!(store.description("mylib", at(1)), Some("shard 1"));
+        // Using shard 0 again makes shard 1 the one to evict
+        assert_eq!(store.description("mylib", at(0)), Some("shard 0"));
+        assert_eq!(store.description("mylib", at(2)), Some("shard 2"));
+        assert_eq!(store.cached_shards(), 2);
+        assert!(store.shards.contains_key(&("mylib".to_string(), 0)));
+        assert!(!store.shards.contains_key(&("mylib".to_string(), 1)));
+        // An evicted shard is loaded again when it's needed
+        assert_eq!(store.description("mylib", at(1)), Some("shard 1"));
+        assert_eq!(store.description("other", at(0)), None);
+        assert_eq!(store.cached_shards(), 2);
+
+        let mut unbounded = DescriptionStore::new(&dir);
+        for shard in 0..3 {
+            unbounded.description("mylib", at(shard));
+        }
+        assert_eq!(unbounded.cached_shards(), 3);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_plain_text() {
+        assert_eq!(
+            plain_text("Returns <code>Option&lt;T&gt;</code> &amp; more"),
+            "Returns Option<T> & more"
+        );
+    }
+}
@@ -0,0 +1,130 @@
+// Problems decoding worked around, reported rather than failing the decode
+
+use std::fmt;
+
+/// Something wrong with a crate's index data that decoding worked around.
+///
+/// None of these stop a crate from decoding, but each means some of its items
+/// may be listed with the wrong type, path or parent. They usually come from
+/// an index written by a newer rustdoc than rdoc knows.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Diagnostic {
+    /// An item's type code isn't one rdoc knows; the item's type is
+    /// [`ItemType::Unknown`](crate::search_index::ItemType::Unknown)
+    UnknownItemType { item: usize, code: u8 },
+    /// A re-export's path index has no path; the item's own path was used
+    MissingReexportPath { item: usize, path_index: usize },
+    /// An item's parent index is past the end of the crate's parent items; the
+    /// item was decoded without a parent
+    MissingParent { item: usize, parent_index: usize },
+    /// The VLQ-encoded parent indices don't have one entry per item
+    ParentCountMismatch { items: usize, parents: usize },
+    /// One of the crate's bitmaps couldn't be decoded and was read as empty
+    MalformedBitmap { field: &'static str, error: String },
+    /// The first item's name is empty, meaning "the name before it", which
+    /// there isn't; as in rustdoc's search, it and the items reusing its name
+    /// were decoded with an empty name
+    EmptyFirstName,
+    /// The first item has no path, meaning "the path before it", which there
+    /// isn't; as in rustdoc's search, it and the items reusing its path were
+    /// decoded with an empty path
+    MissingFirstPath,
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Diagnostic::UnknownItemType { item, code } => {
+                write!(f, "item {}: unknown type code {}", item, code)
+            }
+            Diagnostic::MissingReexportPath { item, path_index } => write!(
+                f,
+                "item {}: re-export path {} doesn't exist, used the item's path",
+                item, path_index
+            ),
+            Diagnostic::MissingParent { item, parent_index } => write!(
+                f,
+                "item {}: parent {} doesn't exist, decoded without a parent",
+                item, parent_index
+            ),
+            Diagnostic::ParentCountMismatch { items, parents } => {
+                write!(f, "{} parent indices for {} items", parents, items)
+            }
+            Diagnostic::MalformedBitmap { field, error } => {
+                write!(f, "malformed `{}` bitmap, read as empty: {}", field, error)
+            }
+            Diagnostic::EmptyFirstName => {
+                write!(f, "item 0: empty name with none before it to reuse")
+            }
+            Diagnostic::MissingFirstPath => {
+                write!(f, "item 0: no path with none before it to reuse")
+            }
+        }
+    }
+}
+
+/// The diagnostics of every crate decoded, by crate, in the order decoded.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Diagnostics {
+    crates: Vec<(String, Vec<Diagnostic>)>,
+}
+
+impl Diagnostics {
+    pub const fn new() -> Self {
+        Self { crates: Vec::new() }
+    }
+
+    /// Record `diagnostics` for `crate_name`; crates without any are left out.
+    pub fn add(&mut self, crate_name: &str, diagnostics: Vec<Diagnostic>) {
+        if !diagnostics.is_empty() {
+            self.crates.push((crate_name.to_string(), diagnostics));
+        }
+    }
+
+    /// How many diagnostics there are, across crates.
+    pub fn len(&self) -> usize {
+        self.crates
+            .iter()
+            .map(|(_, diagnostics)| diagnostics.len())
+            .sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.crates.is_empty()
+    }
+
+    /// Each crate with diagnostics, with them.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &[Diagnostic])> {
+        self.crates
+            .iter()
+            .map(|(crate_name, diagnostics)| (crate_name.as_str(), diagnostics.as_slice()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_diagnostics() {
+        let mut diagnostics = Diagnostics::new();
+        diagnostics.add("serde", vec![]);
+        assert!(diagnostics.is_empty());
+
+        diagnostics.add(
+            "tokio",
+            vec![
+                Diagnostic::UnknownItemType { item: 3, code: 30 },
+                Diagnostic::ParentCountMismatch {
+                    items: 10,
+                    parents: 9,
+                },
+            ],
+        );
+        assert_eq!(diagnostics.len(), 2);
+        let (crate_name, found) = diagnostics.iter().next().unwrap();
+        assert_eq!(crate_name, "tokio");
+        assert_eq!(found[0].to_string(), "item 3: unknown type code 30");
+        assert_eq!(found[1].to_string(), "9 parent indices for 10 items");
+    }
+}
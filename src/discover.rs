@@ -0,0 +1,94 @@
+// Picking items at random to browse, favoring the ones worth reading about
+
+use crate::search_index::ItemType;
+use crate::search_items::SearchItem;
+use fastrand::Rng;
+
+/// How likely `item` is to be picked, relative to other items; 0 never picks it.
+///
+/// Types, traits, functions and macros are favored over the methods, fields
+/// and variants that belong to them, which far outnumber them. Items without
+/// a description, or deprecated ones, are rarely picked. Impls, imports and
+/// keywords never are.
+pub fn discovery_weight(item: &SearchItem) -> f64 {
+    use ItemType::*;
+    let weight = match item.item_type {
+        Struct | Enum | Union | Trait | TraitAlias | Function | Macro | ProcAttribute
+        | ProcDerive => 4.0,
+        Module | Typedef | Constant | Static | Primitive => 2.0,
+        Method | TyMethod | AssocType | AssocConst | ForeignType => 1.0,
+        StructField | Variant => 0.5,
+        Impl | Import | ExternCrate | Keyword | OpaqueTy | Unknown(_) => 0.0,
+    };
+    let documented = if item.empty_description { 0.1 } else { 1.0 };
+    let current = if item.deprecated { 0.2 } else { 1.0 };
+    weight * documented * current
+}
+
+/// Pick up to `count` distinct items, each as likely as its
+/// [weight](discovery_weight) makes it, in the order picked.
+///
+/// This is weighted sampling without replacement (Efraimidis and Spirakis):
+/// every item draws a key `u^(1/weight)` and the highest keys are kept.
+pub fn sample<'a>(items: &[&'a SearchItem], count: usize, rng: &mut Rng) -> Vec<&'a SearchItem> {
+    let mut keyed: Vec<(f64, &SearchItem)> = items
+        .iter()
+        .filter_map(|&item| {
+            let weight = discovery_weight(item);
+            (weight > 0.0).then(|| (rng.f64().powf(1.0 / weight), item))
+        })
+        .collect();
+    keyed.sort_by(|(a, _), (b, _)| b.total_cmp(a));
+    keyed.truncate(count);
+    keyed.into_iter().map(|(_, item)| item).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn item(name: &str, item_type: ItemType, empty_description: bool) -> SearchItem {
+        SearchItem::builder("serde", item_type, name)
+            .empty_description(empty_description)
+            .build()
+    }
+
+    #[test]
+    fn test_sample() {
+        let items = [
+            item("Serialize", ItemType::Trait, false),
+            item("serialize", ItemType::TyMethod, false),
+            item("Undocumented", ItemType::Struct, true),
+            item("Serializer", ItemType::Import, false),
+        ];
+        let items: Vec<&SearchItem> = items.iter().collect();
+
+        let mut rng = Rng::with_seed(7);
+        let picked = sample(&items, 10, &mut rng);
+        assert_eq!(picked.len(), 3, "imports are never picked");
+        assert!(picked.iter().all(|item| item.item_type != ItemType::Import));
+        assert_eq!(sample(&items, 1, &mut rng).len(), 1);
+
+        // The same seed picks the same items
+        let names = |seed| {
+            sample(&items, 2, &mut Rng::with_seed(seed))
+                .iter()
+                .map(|item| item.name.clone())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(names(42), names(42));
+
+        // The documented trait comes first far more often than the rest
+        let mut first: HashMap<String, usize> = HashMap::new();
+        for seed in 0..1000 {
+            *first.entry(names(seed)[0].clone()).or_default() += 1;
+        }
+        assert!(first["Serialize"] > 650, "{:?}", first);
+        assert!(
+            first.get("Undocumented").copied().unwrap_or(0) < 150,
+            "{:?}",
+            first
+        );
+    }
+}
@@ -0,0 +1,434 @@
+// Finding intra-doc links in Rust source and checking their targets
+
+use crate::resolve::{ResolveError, resolve};
+use crate::search_items::SearchItem;
+use std::collections::HashMap;
+use std::path::{Component, Path};
+
+/// An intra-doc link found in a doc comment.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DocLink {
+    /// 1-based line the link is on
+    pub line: usize,
+    /// The link target as written, without backticks (e.g. `crate::Config`)
+    pub target: String,
+}
+
+/// Whether a link's target was found.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LinkStatus {
+    /// The target names an item in the index
+    Resolved,
+    /// The target is in a crate that isn't documented in the index, such as `std`
+    External,
+    /// Nothing in the index matches the target
+    Broken,
+}
+
+/// Crates whose items are linked to but never appear in a project's own docs.
+const EXTERNAL_CRATES: &[&str] = &["std", "core", "alloc", "proc_macro", "test"];
+
+/// Names in scope everywhere through the standard prelude, plus primitive types.
+const PRELUDE: &[&str] = &[
+    "Option",
+    "Some",
+    "None",
+    "Result",
+    "Ok",
+    "Err",
+    "Vec",
+    "String",
+    "Box",
+    "ToString",
+    "ToOwned",
+    "Iterator",
+    "IntoIterator",
+    "Extend",
+    "Default",
+    "Clone",
+    "Copy",
+    "Send",
+    "Sync",
+    "Sized",
+    "Unpin",
+    "Drop",
+    "Fn",
+    "FnMut",
+    "FnOnce",
+    "From",
+    "Into",
+    "TryFrom",
+    "TryInto",
+    "AsRef",
+    "AsMut",
+    "PartialEq",
+    "Eq",
+    "PartialOrd",
+    "Ord",
+    "Debug",
+    "Hash",
+    "bool",
+    "char",
+    "str",
+    "u8",
+    "u16",
+    "u32",
+    "u64",
+    "u128",
+    "usize",
+    "i8",
+    "i16",
+    "i32",
+    "i64",
+    "i128",
+    "isize",
+    "f32",
+    "f64",
+    "slice",
+    "array",
+    "tuple",
+    "unit",
+    "never",
+    "pointer",
+    "reference",
+    "fn",
+];
+
+/// Find the intra-doc links in the `///` and `//!` comments of a Rust source file.
+///
+/// Recognizes shortcut links (``[`Config`]``), inline links (`[the config](Config)`)
+/// and reference links (`[the config][cfg]` with a `[cfg]: Config` definition).
+/// Links inside code spans and fenced code blocks are ignored, as are URLs and
+/// anything that isn't shaped like a Rust path.
+pub fn extract_doc_links(source: &str) -> Vec<DocLink> {
+    let mut raw_links = Vec::new();
+    let mut definitions = HashMap::new();
+    let mut in_fence = false;
+
+    for (number, line) in source.lines().enumerate() {
+        let line_number = number + 1;
+        let trimmed = line.trim_start();
+        let Some(text) = trimmed
+            .strip_prefix("///")
+            .or_else(|| trimmed.strip_prefix("//!"))
+        else {
+            in_fence = false;
+            continue;
+        };
+        // `////` is an ordinary comment, not a doc comment
+        if text.starts_with('/') {
+            continue;
+        }
+        let text = text.trim();
+
+        if text.starts_with("```") {
+            in_fence = !in_fence;
+            continue;
+        }
+        if in_fence {
+            continue;
+        }
+
+        // `[label]: target` defines a reference used elsewhere in the comment
+        if let Some((label, target)) = text
+            .strip_prefix('[')
+            .and_then(|rest| rest.split_once("]:"))
+        {
+            definitions.insert(label.to_string(), target.trim().to_string());
+            continue;
+        }
+
+        raw_links.extend(scan_line(text).into_iter().map(|link| (line_number, link)));
+    }
+
+    // References can be defined after they are used, so resolve them at the end
+    raw_links
+        .into_iter()
+        .filter_map(|(line, link)| {
+            let target = match link {
+                RawLink::Target(target) => target,
+                // A shortcut link with no definition links to its own label
+                RawLink::Reference(label) => definitions.get(&label).cloned().unwrap_or(label),
+            };
+            normalize_target(&target).map(|target| DocLink { line, target })
+        })
+        .collect()
+}
+
+enum RawLink {
+    Target(String),
+    Reference(String),
+}
+
+fn scan_line(text: &str) -> Vec<RawLink> {
+    let chars: Vec<char> = text.chars().collect();
+    let mut links = Vec::new();
+    let mut in_code = false;
+    let mut i = 0;
+
+    while i < chars.len() {
+        match chars[i] {
+            '`' => in_code = !in_code,
+            '[' if !in_code => {
+                let Some(close) = chars[i + 1..].iter().position(|&c| c == ']') else {
+                    break;
+                };
+                let close = i + 1 + close;
+                let label: String = chars[i + 1..close].iter().collect();
+                match chars.get(close + 1) {
+                    Some('(') => {
+                        let (target, end) = until_closing_paren(&chars, close + 2);
+                        links.push(RawLink::Target(target));
+                        i = end;
+                    }
+                    Some('[') => {
+                        let end = chars[close + 2..]
+                            .iter()
+                            .position(|&c| c == ']')
+                            .map_or(chars.len(), |end| close + 2 + end);
+                        let reference: String =
+                            chars[close + 2..end.min(chars.len())].iter().collect();
+                        // `[text][]` is a collapsed reference to `text`
+                        links.push(RawLink::Reference(if reference.is_empty() {
+                            label
+                        } else {
+                            reference
+                        }));
+                        i = end;
+                    }
+                    _ => {
+                        links.push(RawLink::Reference(label));
+                        i = close;
+                    }
+                }
+            }
+            _ => {}
+        }
+        i += 1;
+    }
+
+    links
+}
+
+/// Collect an inline link target, allowing balanced parentheses like `foo()`.
+fn until_closing_paren(chars: &[char], start: usize) -> (String, usize) {
+    let mut depth = 0;
+    for (offset, &c) in chars[start.min(chars.len())..].iter().enumerate() {
+        match c {
+            '(' => depth += 1,
+            ')' if depth == 0 => {
+                return (
+                    chars[start..start + offset].iter().collect(),
+                    start + offset,
+                );
+            }
+            ')' => depth -= 1,
+            _ => {}
+        }
+    }
+    (
+        chars[start.min(chars.len())..].iter().collect(),
+        chars.len(),
+    )
+}
+
+/// Strip backticks and generics, returning `None` for targets that aren't Rust paths.
+fn normalize_target(target: &str) -> Option<String> {
+    let target = target.trim().trim_matches('`');
+    // Drop generic arguments: `Vec<T>` links to `Vec`
+    let target = match target.find('<') {
+        Some(start) if target.ends_with('>') => &target[..start],
+        _ => target,
+    };
+
+    let (kind, path) = match target.split_once('@') {
+        Some((kind, path)) => (Some(kind), path),
+        None => (None, target),
+    };
+    let path_body = path
+        .strip_suffix("()")
+        .or_else(|| path.strip_suffix('!'))
+        .unwrap_or(path);
+
+    let is_path = !path_body.is_empty()
+        && path_body.split("::").all(is_identifier)
+        && kind.is_none_or(|kind| kind.chars().all(|c| c.is_ascii_lowercase()));
+    is_path.then(|| target.to_string())
+}
+
+fn is_identifier(segment: &str) -> bool {
+    let mut chars = segment.chars();
+    chars.next().is_some_and(|c| c.is_alphabetic() || c == '_')
+        && chars.all(|c| c.is_alphanumeric() || c == '_')
+}
+
+/// The module path of a source file, relative to the crate's `src` directory.
+///
+/// `lib.rs` and `main.rs` are the crate root, `a/mod.rs` is `crate::a` and
+/// `a/b.rs` is `crate::a::b`.
+pub fn module_path(crate_name: &str, relative_file: &Path) -> String {
+    let mut segments = vec![crate_name.to_string()];
+    for component in relative_file.with_extension("").components() {
+        if let Component::Normal(segment) = component {
+            segments.push(segment.to_string_lossy().into_owned());
+        }
+    }
+    if matches!(
+        segments.last().map(String::as_str),
+        Some("lib" | "main" | "mod")
+    ) && segments.len() > 1
+    {
+        segments.pop();
+    }
+    segments.join("::")
+}
+
+/// Check whether `target`, written in a doc comment in `module`, names a documented item.
+///
+/// `crate::`, `self::` and `super::` are expanded relative to `module`. Paths that
+/// don't resolve from the crate root are also tried relative to `module` and
+/// as the tail of any item's path, since `use` imports aren't known.
+pub fn check_link(items: &[SearchItem], target: &str, module: &str) -> LinkStatus {
+    let (kind, path) = match target.split_once('@') {
+        Some((kind, path)) => (format!("{}@", kind), path),
+        None => (String::new(), target),
+    };
+
+    let Some(path) = expand_relative(path, module) else {
+        return LinkStatus::External;
+    };
+    let first_segment = path.split("::").next().unwrap_or_default();
+    if EXTERNAL_CRATES.contains(&first_segment) || PRELUDE.contains(&path.as_str()) {
+        return LinkStatus::External;
+    }
+
+    let candidates = [path.clone(), format!("{}::{}", module, path)];
+    for candidate in candidates {
+        match resolve(items, &format!("{}{}", kind, candidate)) {
+            Ok(_) | Err(ResolveError::Ambiguous { .. }) => return LinkStatus::Resolved,
+            Err(_) => {}
+        }
+    }
+
+    // An imported item: match the end of its full path
+    let bare = path
+        .trim_end_matches("()")
+        .trim_end_matches('!')
+        .to_string();
+    let suffix = format!("::{}", bare);
+    let imported = items.iter().any(|item| {
+        let full = match item.parent_name() {
+            Some(parent) => format!("{}::{}::{}", item.path, parent, item.name),
+            None => format!("{}::{}", item.path, item.name),
+        };
+        full.ends_with(&suffix)
+    });
+    if imported {
+        LinkStatus::Resolved
+    } else {
+        LinkStatus::Broken
+    }
+}
+
+/// Expand `crate::`, `self::` and `super::` against `module`.
+///
+/// Returns `None` for `Self::` paths, whose meaning depends on the surrounding impl.
+fn expand_relative(path: &str, module: &str) -> Option<String> {
+    if path == "Self" || path.starts_with("Self::") {
+        return None;
+    }
+
+    let crate_name = module.split("::").next().unwrap_or(module);
+    if let Some(rest) = path.strip_prefix("crate::") {
+        return Some(format!("{}::{}", crate_name, rest));
+    }
+    if let Some(rest) = path.strip_prefix("self::") {
+        return Some(format!("{}::{}", module, rest));
+    }
+
+    let mut module: Vec<&str> = module.split("::").collect();
+    let mut rest = path;
+    if !rest.starts_with("super::") {
+        return Some(path.to_string());
+    }
+    while let Some(after) = rest.strip_prefix("super::") {
+        if module.len() > 1 {
+            module.pop();
+        }
+        rest = after;
+    }
+    Some(format!("{}::{}", module.join("::"), rest))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::search_index::ItemType;
+
+    fn targets(source: &str) -> Vec<(usize, String)> {
+        extract_doc_links(source)
+            .into_iter()
+            .map(|link| (link.line, link.target))
+            .collect()
+    }
+
+    #[test]
+    fn test_extract_doc_links() {
+        let source = r#"
+/// Reads a [`Config`] using [the loader](crate::load()) and [`Vec<T>`].
+/// See [the parser][parse] or [Error], not `a[i]` or [a link](https://example.com).
+///
+/// [parse]: crate::parse::Parser
+/// ```
+/// let x = v[`Nope`];
+/// ```
+//// [NotDoc]
+fn read() {}
+//! [macro@json]
+"#;
+        assert_eq!(
+            targets(source),
+            vec![
+                (2, "Config".to_string()),
+                (2, "crate::load()".to_string()),
+                (2, "Vec".to_string()),
+                (3, "crate::parse::Parser".to_string()),
+                (3, "Error".to_string()),
+                (11, "macro@json".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_module_path() {
+        assert_eq!(module_path("mylib", Path::new("lib.rs")), "mylib");
+        assert_eq!(module_path("mylib", Path::new("de/mod.rs")), "mylib::de");
+        assert_eq!(
+            module_path("mylib", Path::new("de/value.rs")),
+            "mylib::de::value"
+        );
+    }
+
+    fn item(path: &str, name: &str) -> SearchItem {
+        SearchItem::builder("mylib", ItemType::Struct, name)
+            .path(path)
+            .build()
+    }
+
+    #[test]
+    fn test_check_link() {
+        let items = [item("mylib", "Config"), item("mylib::de", "Parser")];
+        let check = |target| check_link(&items, target, "mylib::de");
+
+        assert_eq!(check("crate::Config"), LinkStatus::Resolved);
+        assert_eq!(check("super::Config"), LinkStatus::Resolved);
+        assert_eq!(check("Parser"), LinkStatus::Resolved);
+        assert_eq!(check("self::Parser"), LinkStatus::Resolved);
+        assert_eq!(check("de::Parser"), LinkStatus::Resolved);
+        assert_eq!(check("std::io::Read"), LinkStatus::External);
+        assert_eq!(check("Option"), LinkStatus::External);
+        assert_eq!(check("Self::new"), LinkStatus::External);
+        assert_eq!(check("crate::Missing"), LinkStatus::Broken);
+        assert_eq!(check("Missing"), LinkStatus::Broken);
+    }
+}
@@ -0,0 +1,157 @@
+// Finding where a project's rustdoc output lives
+
+use color_eyre::{Result, eyre::Context};
+use rdoc::resources;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use tracing::{info, warn};
+
+use crate::cache;
+use crate::output;
+
+/// Doc roots tried when the config doesn't list any, relative to the project.
+pub const DEFAULT_DOC_ROOTS: &[&str] = &["target/doc", "doc"];
+
+/// File in the cache directory remembering which doc root each project used.
+const RECORD_FILE: &str = "doc-roots.json";
+
+/// Where `cargo doc` writes its output: `$CARGO_TARGET_DIR/doc`, or `target/doc`.
+pub fn cargo_doc_root(target_dir: Option<&Path>) -> PathBuf {
+    target_dir.unwrap_or(Path::new("target")).join("doc")
+}
+
+/// The doc roots to try for the current project, in order.
+///
+/// The root recorded by an earlier run comes first, then cargo's output
+/// directory, then the configured roots (or [`DEFAULT_DOC_ROOTS`]).
+pub fn candidates(recorded: Option<PathBuf>, configured: &[PathBuf]) -> Vec<PathBuf> {
+    let target_dir = std::env::var_os("CARGO_TARGET_DIR").map(PathBuf::from);
+    let defaults: Vec<PathBuf>;
+    let configured = if configured.is_empty() {
+        defaults = DEFAULT_DOC_ROOTS.iter().map(PathBuf::from).collect();
+        &defaults
+    } else {
+        configured
+    };
+
+    let mut roots: Vec<PathBuf> = Vec::new();
+    for root in recorded
+        .into_iter()
+        .chain([cargo_doc_root(target_dir.as_deref())])
+        .chain(configured.iter().cloned())
+    {
+        if !roots.contains(&root) {
+            roots.push(root);
+        }
+    }
+    roots
+}
+
+/// The first of `roots` that holds a search index, named with a resource
+/// suffix or not.
+pub fn probe(roots: &[PathBuf]) -> Option<PathBuf> {
+    roots.iter().find_map(|root| {
+        info!("looking for a search index in {}", root.display());
+        resources::search_index_file(root)
+    })
+}
+
+/// The doc root an earlier run used for the project in `project_dir`.
+pub fn recorded(project_dir: &Path) -> Option<PathBuf> {
+    read_records().ok()?.remove(&project_key(project_dir))
+}
+
+/// Remember that the project in `project_dir` keeps its docs in `doc_root`.
+///
+/// A failure to write the record only costs a slower probe next time, so it
+/// is logged rather than returned.
+pub fn record(project_dir: &Path, doc_root: &Path) {
+    let result = (|| -> Result<()> {
+        let mut records = read_records()?;
+        let key = project_key(project_dir);
+        if records.get(&key).map(PathBuf::as_path) == Some(doc_root) {
+            return Ok(());
+        }
+        records.insert(key, doc_root.to_path_buf());
+        let path = cache::cache_dir()?.join(RECORD_FILE);
+        std::fs::create_dir_all(path.parent().unwrap_or(Path::new(".")))?;
+        output::write_atomic(&path, serde_json::to_string_pretty(&records)?)
+    })();
+
+    if let Err(err) = result {
+        warn!("could not record doc root {}: {}", doc_root.display(), err);
+    }
+}
+
+fn read_records() -> Result<BTreeMap<String, PathBuf>> {
+    let path = cache::cache_dir()?.join(RECORD_FILE);
+    if !path.exists() {
+        return Ok(BTreeMap::new());
+    }
+    let content = std::fs::read_to_string(&path)
+        .wrap_err_with(|| format!("Failed to read {}", path.display()))?;
+    serde_json::from_str(&content).wrap_err_with(|| format!("Invalid {}", path.display()))
+}
+
+fn project_key(project_dir: &Path) -> String {
+    project_dir
+        .canonicalize()
+        .unwrap_or_else(|_| project_dir.to_path_buf())
+        .to_string_lossy()
+        .into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::remote::SEARCH_INDEX_FILE;
+
+    #[test]
+    fn test_cargo_doc_root() {
+        assert_eq!(cargo_doc_root(None), Path::new("target").join("doc"));
+        assert_eq!(
+            cargo_doc_root(Some(Path::new("/tmp/build"))),
+            Path::new("/tmp/build").join("doc")
+        );
+    }
+
+    #[test]
+    fn test_candidates_order() {
+        let cargo_root = cargo_doc_root(
+            std::env::var_os("CARGO_TARGET_DIR")
+                .map(PathBuf::from)
+                .as_deref(),
+        );
+
+        let roots = candidates(Some(PathBuf::from("book/api")), &[]);
+        assert_eq!(roots[0], Path::new("book/api"));
+        assert_eq!(roots[1], cargo_root);
+        assert!(roots.contains(&PathBuf::from("doc")));
+
+        // Configured roots replace the defaults, and nothing is tried twice
+        let roots = candidates(None, &[PathBuf::from("site/rustdoc"), cargo_root.clone()]);
+        assert_eq!(roots, [cargo_root, PathBuf::from("site/rustdoc")]);
+    }
+
+    #[test]
+    fn test_probe_finds_first_root_with_index() {
+        let dir = std::env::temp_dir().join(format!("rdoc-doc-root-test-{}", std::process::id()));
+        let found = dir.join("found");
+        std::fs::create_dir_all(&found).unwrap();
+        std::fs::write(found.join(SEARCH_INDEX_FILE), "").unwrap();
+
+        let roots = [dir.join("missing"), found.clone()];
+        assert_eq!(probe(&roots), Some(found.join(SEARCH_INDEX_FILE)));
+        assert_eq!(probe(&roots[..1]), None);
+
+        // Docs built with `--resource-suffix` name their index after it
+        let suffixed = dir.join("suffixed");
+        std::fs::create_dir_all(&suffixed).unwrap();
+        std::fs::write(suffixed.join("search-index-1.78.0.js"), "").unwrap();
+        assert_eq!(
+            probe(std::slice::from_ref(&suffixed)),
+            Some(suffixed.join("search-index-1.78.0.js"))
+        );
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
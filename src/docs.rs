@@ -0,0 +1,1100 @@
+// Locating and loading the generated documentation for commands
+
+use color_eyre::{Result, eyre::Context, eyre::eyre};
+use colored::Colorize;
+use rdoc::aliases::Aliases;
+use rdoc::arena;
+use rdoc::crate_graph::{CrateGraph, library_name};
+use rdoc::diagnostics::Diagnostics;
+use rdoc::events::EVENT_TARGET;
+use rdoc::human::count;
+use rdoc::ignore::IgnoreRules;
+use rdoc::implementors::{Implementor, implementors_files, local_implementors, parse_implementors};
+use rdoc::index_file::{IndexFile, IndexJson};
+use rdoc::item_id::ItemId;
+use rdoc::lockfile::parse_lockfile;
+use rdoc::pages::{SiteLinks, availability, item_page, required_features};
+use rdoc::resources;
+use rdoc::rustdoc_version::{IndexFeatures, RustdocVersion};
+use rdoc::search_index::{CrateData, LazyCrateEntry, parse_search_index_lazy};
+use rdoc::search_items::{CrateItems, SearchItem, warn_unknown_item_types};
+use rdoc::signature::{self, FunctionSignature};
+use rdoc::stale::{self, LockedLibraries};
+use std::collections::{HashMap, HashSet};
+use std::hash::{DefaultHasher, Hash, Hasher};
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::Instant;
+use tracing::{debug, info, warn};
+
+use crate::doc_root;
+use crate::output;
+use crate::progress;
+use crate::remote::{self, HttpOptions};
+
+/// How much of a crate's `index.html` to read looking for its version
+const VERSION_SCAN_BYTES: u64 = 16 * 1024;
+
+/// Crates and paths every command leaves out, from `.rdocignore`
+static IGNORE_RULES: OnceLock<IgnoreRules> = OnceLock::new();
+
+/// Set the rules [`crate_entries`] and [`is_kept`] apply. Only the
+/// first call has an effect.
+pub fn set_ignore_rules(rules: IgnoreRules) {
+    let _ = IGNORE_RULES.set(rules);
+}
+
+fn ignore_rules() -> &'static IgnoreRules {
+    IGNORE_RULES.get_or_init(IgnoreRules::default)
+}
+
+/// The rustdoc version that wrote the index [`load_search_index_json`] last
+/// loaded, if its docs say
+static RUSTDOC_VERSION: Mutex<Option<RustdocVersion>> = Mutex::new(None);
+
+/// Pages every doc root has, checked for the rustdoc version before crate pages
+const ROOT_PAGES: &[&str] = &["settings.html", "help.html"];
+
+/// The rustdoc version that wrote the loaded index, if known.
+pub fn rustdoc_version() -> Option<RustdocVersion> {
+    *RUSTDOC_VERSION
+        .lock()
+        .unwrap_or_else(|err| err.into_inner())
+}
+
+/// The index format of the loaded index, by the version that wrote it.
+fn index_features() -> IndexFeatures {
+    IndexFeatures::of(rustdoc_version())
+}
+
+/// ` (written by rustdoc 1.95.0)` when the version that wrote the loaded
+/// index is known, for error messages.
+fn written_by() -> String {
+    rustdoc_version()
+        .map(|version| format!(" (written by rustdoc {})", version))
+        .unwrap_or_default()
+}
+
+/// Where newer rustdoc writes its index instead of `search-index.js`
+const UNSUPPORTED_INDEX_DIR: &str = "search.index";
+
+/// Why the docs in `roots` can't be read, if one of them has only the newer
+/// [`UNSUPPORTED_INDEX_DIR`] index.
+pub fn unsupported_layout(roots: &[PathBuf]) -> Option<String> {
+    let root = roots
+        .iter()
+        .find(|root| root.join(UNSUPPORTED_INDEX_DIR).is_dir())?;
+    Some(format!(
+        "{} has rustdoc{}'s newer {}/ index, which rdoc can't read yet",
+        root.display(),
+        detect_rustdoc_version(root)
+            .map(|version| format!(" {}", version))
+            .unwrap_or_default(),
+        UNSUPPORTED_INDEX_DIR
+    ))
+}
+
+/// The rustdoc version the docs in `doc_root` were written by, from the head
+/// of one of its pages.
+pub fn detect_rustdoc_version(doc_root: &Path) -> Option<RustdocVersion> {
+    let crate_pages = std::fs::read_dir(doc_root)
+        .into_iter()
+        .flatten()
+        .flatten()
+        .map(|entry| entry.path().join("index.html"));
+    ROOT_PAGES
+        .iter()
+        .map(|page| doc_root.join(page))
+        .chain(crate_pages)
+        .find_map(|page| {
+            let file = std::fs::File::open(page).ok()?;
+            let mut head = Vec::new();
+            file.take(VERSION_SCAN_BYTES).read_to_end(&mut head).ok()?;
+            RustdocVersion::from_page(&String::from_utf8_lossy(&head))
+        })
+}
+
+/// Problems [`record_diagnostics`] kept, for [`report_diagnostics`]
+static DIAGNOSTICS: Mutex<Diagnostics> = Mutex::new(Diagnostics::new());
+
+/// List on stderr the problems decoding the index worked around, with
+/// `show` (`--warnings`); otherwise just say how many there were.
+pub fn report_diagnostics(show: bool) {
+    let diagnostics = DIAGNOSTICS.lock().unwrap_or_else(|err| err.into_inner());
+    if diagnostics.is_empty() {
+        return;
+    }
+    if !show {
+        warn!(
+            "worked around {} decoding the search index; --warnings lists them",
+            count(diagnostics.len(), "problem", "problems")
+        );
+        return;
+    }
+    eprintln!(
+        "{} {} decoding the search index:",
+        "✗".yellow().bold(),
+        count(diagnostics.len(), "problem", "problems")
+    );
+    for (crate_name, problems) in diagnostics.iter() {
+        for problem in problems {
+            eprintln!("  {}: {}", crate_name.bold(), problem);
+        }
+    }
+}
+
+/// Where `cargo doc` writes the search index, relative to the project root.
+fn local_search_index_path(target_dir: Option<&Path>) -> PathBuf {
+    doc_root::cargo_doc_root(target_dir).join(remote::SEARCH_INDEX_FILE)
+}
+
+/// A `cargo` command, using the same cargo that invoked rdoc when there is one.
+///
+/// Cargo sets `CARGO` for subcommands and build scripts; otherwise `cargo` is
+/// looked up on `PATH` (as `cargo.exe` on Windows).
+pub fn cargo_command() -> Command {
+    Command::new(std::env::var_os("CARGO").unwrap_or_else(|| "cargo".into()))
+}
+
+/// Where the documentation to search comes from.
+pub enum DocSource {
+    /// The current project's generated docs, usually `target/doc`
+    Local {
+        /// A doc root given outright (`--doc-dir`), such as the output of a
+        /// plain `rustdoc src/lib.rs`; used as is, without a cargo project
+        doc_dir: Option<PathBuf>,
+        /// Doc roots to look in besides cargo's, relative to the project
+        doc_roots: Vec<PathBuf>,
+        /// Where the same docs are published, for linking to them
+        base_url: Option<String>,
+        /// Link dependencies' items to their pages on docs.rs
+        docs_rs: bool,
+        /// What to do about docs of crates at versions Cargo.lock no longer has
+        stale: StaleDocs,
+    },
+    /// A hosted rustdoc site, downloaded into the cache
+    Remote {
+        /// Root URL of the documentation site
+        base_url: String,
+        /// Credentials and headers for the site
+        http: HttpOptions,
+        /// Re-download even if a cached copy exists
+        refresh: bool,
+        /// Number of downloads to run at once
+        jobs: usize,
+    },
+}
+
+/// What to do about local docs of crates at versions Cargo.lock no longer has.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum StaleDocs {
+    /// Leave them, without checking, as reading every crate's page takes a while
+    #[default]
+    Ignore,
+    /// Warn about them
+    Warn,
+    /// Remove them and regenerate the docs
+    Clean,
+}
+
+impl DocSource {
+    /// Whether these are the docs of a cargo project in the current directory,
+    /// so its metadata, Cargo.lock and `cargo doc` apply to them.
+    pub fn is_cargo_project(&self) -> bool {
+        matches!(self, DocSource::Local { doc_dir: None, .. }) && Path::new("Cargo.toml").exists()
+    }
+
+    /// The source recorded in the [`ItemId`]s of items from these docs.
+    pub fn id(&self) -> String {
+        match self {
+            DocSource::Local { .. } => ItemId::LOCAL_SOURCE.to_string(),
+            DocSource::Remote { base_url, .. } => {
+                remote::normalize_base_url(base_url).unwrap_or_else(|_| base_url.clone())
+            }
+        }
+    }
+}
+
+/// Find the search-index.js for `source`.
+///
+/// For local docs that haven't been generated yet, runs `cargo doc` if
+/// `generate` is set and returns an error otherwise.
+pub fn find_search_index(source: &DocSource, quiet: bool, generate: bool) -> Result<PathBuf> {
+    match source {
+        DocSource::Local {
+            doc_dir: Some(doc_dir),
+            ..
+        } => {
+            let Some(search_index_path) = resources::search_index_file(doc_dir) else {
+                return Err(eyre!(
+                    "No {} in {}. Generate docs there with rustdoc (e.g. `rustdoc src/lib.rs -o {}`) or cargo doc",
+                    remote::SEARCH_INDEX_FILE,
+                    doc_dir.display(),
+                    doc_dir.display()
+                ));
+            };
+            info!("using search index at {}", search_index_path.display());
+            Ok(search_index_path)
+        }
+        DocSource::Local {
+            doc_roots, stale, ..
+        } => {
+            let search_index_path = find_local_search_index(doc_roots, quiet, generate)?;
+            if *stale != StaleDocs::Ignore {
+                check_stale_docs(&search_index_path, *stale == StaleDocs::Clean, quiet)?;
+            }
+            Ok(search_index_path)
+        }
+        DocSource::Remote {
+            base_url,
+            http,
+            refresh,
+            jobs,
+        } => remote::fetch_docs(base_url, http, *refresh, *jobs, quiet),
+    }
+}
+
+fn find_local_search_index(doc_roots: &[PathBuf], quiet: bool, generate: bool) -> Result<PathBuf> {
+    // Outside a cargo project there's nothing to record or generate, but docs
+    // rustdoc wrote to one of the usual roots (`doc/` by default) still work
+    let in_project = Path::new("Cargo.toml").exists();
+    let project_dir = std::env::current_dir().wrap_err("Failed to read the current directory")?;
+    let recorded = in_project
+        .then(|| doc_root::recorded(&project_dir))
+        .flatten();
+    let roots = doc_root::candidates(recorded, doc_roots);
+    if let Some(search_index_path) = doc_root::probe(&roots) {
+        info!("using search index at {}", search_index_path.display());
+        if in_project {
+            doc_root::record(
+                &project_dir,
+                search_index_path.parent().unwrap_or(Path::new(".")),
+            );
+        }
+        return Ok(search_index_path);
+    }
+
+    // Generating docs again would only write the same unreadable index
+    if let Some(reason) = unsupported_layout(&roots) {
+        return Err(eyre!("{}; build docs with an older toolchain", reason));
+    }
+
+    if !in_project {
+        let tried: Vec<String> = roots
+            .iter()
+            .map(|root| root.display().to_string())
+            .collect();
+        return Err(eyre!(
+            "No Cargo.toml found and no docs in {}. Run rdoc from a Rust project directory, or pass --doc-dir with the directory rustdoc wrote docs to.",
+            tried.join(", ")
+        ));
+    }
+
+    if !generate {
+        let tried: Vec<String> = roots
+            .iter()
+            .map(|root| root.display().to_string())
+            .collect();
+        return Err(eyre!(
+            "Documentation not found in {}. Please run 'cargo doc' first or use 'rdoc scan' to generate docs.",
+            tried.join(", ")
+        ));
+    }
+
+    if !quiet {
+        println!(
+            "{} Documentation not found. Generating with cargo doc...",
+            "ℹ".blue().bold()
+        );
+    }
+
+    info!("no search index in {:?}, running cargo doc", roots);
+    generate_local_docs(quiet)?;
+
+    let target_dir = std::env::var_os("CARGO_TARGET_DIR").map(PathBuf::from);
+    let cargo_doc_root = doc_root::cargo_doc_root(target_dir.as_deref());
+    if let Some(reason) = unsupported_layout(std::slice::from_ref(&cargo_doc_root)) {
+        return Err(eyre!(
+            "cargo doc succeeded, but {}; build docs with an older toolchain",
+            reason
+        ));
+    }
+    // RUSTDOCFLAGS may have given the files a resource suffix
+    let search_index_path = resources::search_index_file(&cargo_doc_root)
+        .unwrap_or_else(|| local_search_index_path(target_dir.as_deref()));
+    doc_root::record(&project_dir, &cargo_doc_root);
+    Ok(search_index_path)
+}
+
+/// Warn about docs of crates at versions Cargo.lock no longer has (left in the
+/// doc root after dependency updates), or with `clean`, remove them and
+/// regenerate the docs. Crates whose library can't be matched to a package
+/// are left alone.
+fn check_stale_docs(search_index_path: &Path, clean: bool, quiet: bool) -> Result<()> {
+    let doc_root = search_index_path.parent().unwrap_or(Path::new("."));
+    let Ok(lockfile) = std::fs::read_to_string("Cargo.lock") else {
+        warn!("not checking for stale docs: no readable Cargo.lock");
+        return Ok(());
+    };
+    let locked = match parse_lockfile(&lockfile) {
+        Ok(locked) => locked,
+        Err(err) => {
+            warn!("not checking for stale docs: invalid Cargo.lock: {}", err);
+            return Ok(());
+        }
+    };
+    // Libraries needn't be named after their packages
+    let locked = match cargo_metadata(&[]) {
+        Ok(metadata) => LockedLibraries::new(&locked, &metadata),
+        Err(err) => {
+            warn!("not checking for stale docs: {}", err);
+            return Ok(());
+        }
+    };
+    let crates_file = resources::crates_file(resources::resource_suffix(search_index_path));
+    let Some(crates) = std::fs::read_to_string(doc_root.join(&crates_file))
+        .ok()
+        .and_then(|crates_js| stale::all_crates(&crates_js))
+    else {
+        warn!("not checking for stale docs: no readable {}", crates_file);
+        return Ok(());
+    };
+
+    let versions: Vec<Option<String>> = crates
+        .iter()
+        .map(|name| crate_version(doc_root, name))
+        .collect();
+    let stale = stale::stale_crates(
+        crates
+            .iter()
+            .map(String::as_str)
+            .zip(versions.iter().map(Option::as_deref)),
+        &locked,
+    );
+    if stale.is_empty() {
+        return Ok(());
+    }
+    let listed: Vec<String> = stale.iter().map(ToString::to_string).collect();
+    if !clean {
+        warn!(
+            "docs in {} don't match Cargo.lock for {}; rerun with --clean-stale to remove and regenerate them",
+            doc_root.display(),
+            listed.join(", ")
+        );
+        return Ok(());
+    }
+
+    let names: HashSet<&str> = stale.iter().map(|krate| krate.name.as_str()).collect();
+    for name in &names {
+        for dir in [
+            doc_root.join(name),
+            doc_root.join("src").join(name),
+            doc_root.join("search.desc").join(name),
+        ] {
+            if dir.is_dir() {
+                std::fs::remove_dir_all(&dir)
+                    .wrap_err_with(|| format!("Failed to remove {}", dir.display()))?;
+            }
+        }
+    }
+    for path in [search_index_path.to_path_buf(), doc_root.join(&crates_file)] {
+        let contents = std::fs::read_to_string(&path)
+            .wrap_err_with(|| format!("Failed to read {}", path.display()))?;
+        let cleaned = stale::remove_crates(&contents, &names).ok_or_else(|| {
+            eyre!(
+                "Can't remove crates from {}, which older rustdoc wrote; run `cargo clean --doc` and `cargo doc` instead",
+                path.display()
+            )
+        })?;
+        output::write_atomic(&path, cleaned)?;
+    }
+    if !quiet {
+        println!(
+            "{} Removed stale docs for {}",
+            "✓".green().bold(),
+            listed.join(", ")
+        );
+    }
+
+    // Crates still in Cargo.lock get documented again at their locked versions
+    if stale.iter().any(|krate| !krate.locked.is_empty()) {
+        generate_local_docs(quiet)?;
+    }
+    Ok(())
+}
+
+/// The version a crate's docs were built from, read from the sidebar near the
+/// top of its `index.html`.
+fn crate_version(doc_root: &Path, crate_name: &str) -> Option<String> {
+    let file = std::fs::File::open(doc_root.join(crate_name).join("index.html")).ok()?;
+    let mut head = Vec::new();
+    file.take(VERSION_SCAN_BYTES).read_to_end(&mut head).ok()?;
+    stale::documented_version(&String::from_utf8_lossy(&head)).map(str::to_string)
+}
+
+/// Run `cargo doc` in the current project, documenting it and all its dependencies.
+pub fn generate_local_docs(quiet: bool) -> Result<()> {
+    let spinner = progress::spinner("Running cargo doc");
+    let start = Instant::now();
+    info!(target: EVENT_TARGET, event = "doc_generation_started");
+    let output = cargo_command()
+        .arg("doc")
+        .output()
+        .wrap_err("Failed to execute cargo doc; without cargo, pass --doc-dir with docs rustdoc already wrote")?;
+    spinner.finish_and_clear();
+    info!(
+        target: EVENT_TARGET,
+        event = "doc_generation_finished",
+        success = output.status.success(),
+        elapsed_ms = start.elapsed().as_millis() as u64
+    );
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(eyre!("cargo doc failed:\n{}", stderr));
+    }
+
+    if !quiet {
+        println!(
+            "{} Documentation generated successfully!",
+            "✓".green().bold()
+        );
+    }
+
+    Ok(())
+}
+
+/// Reads the documentation's HTML pages, keeping each one for the rest of the run
+/// since a type's members all share its page.
+pub struct Pages<'a> {
+    source: &'a DocSource,
+    search_index_path: PathBuf,
+    pages: HashMap<String, Option<String>>,
+}
+
+impl<'a> Pages<'a> {
+    pub fn new(source: &'a DocSource, search_index_path: &Path) -> Self {
+        Self {
+            source,
+            search_index_path: search_index_path.to_path_buf(),
+            pages: HashMap::new(),
+        }
+    }
+
+    /// The HTML of the page `item` is documented on, if there is one.
+    pub fn item_html(&mut self, item: &SearchItem) -> Result<Option<&str>> {
+        let Some(page) = item_page(item) else {
+            return Ok(None);
+        };
+        self.file(&page.file)
+    }
+
+    /// The contents of `file`, relative to the documentation root, if it exists.
+    pub fn file(&mut self, file: &str) -> Result<Option<&str>> {
+        if !self.pages.contains_key(file) {
+            let contents = read_page(self.source, &self.search_index_path, file)?;
+            self.pages.insert(file.to_string(), contents);
+        }
+        Ok(self.pages[file].as_deref())
+    }
+
+    /// The cargo features `item` requires, according to its page.
+    pub fn required_features(&mut self, item: &SearchItem) -> Result<Vec<String>> {
+        let anchor = item_page(item).and_then(|page| page.anchor);
+        Ok(self
+            .item_html(item)?
+            .map(|html| required_features(html, anchor.as_deref()))
+            .unwrap_or_default())
+    }
+
+    /// The conditions of `item`'s `doc(cfg)` notes, according to its page.
+    pub fn availability(&mut self, item: &SearchItem) -> Result<Vec<String>> {
+        let anchor = item_page(item).and_then(|page| page.anchor);
+        Ok(self
+            .item_html(item)?
+            .map(|html| availability(html, anchor.as_deref()))
+            .unwrap_or_default())
+    }
+
+    /// The implementations of `trait_item`: those in its crate, from its
+    /// page, and the rest from the implementors script.
+    pub fn implementors(&mut self, trait_item: &SearchItem) -> Result<Vec<Implementor>> {
+        let mut implementors = self
+            .item_html(trait_item)?
+            .map(|html| local_implementors(html, &trait_item.crate_name))
+            .unwrap_or_default();
+        for file in implementors_files(trait_item) {
+            if let Some(js) = self.file(&file)? {
+                implementors.extend(
+                    parse_implementors(js).wrap_err_with(|| format!("Failed to parse {}", file))?,
+                );
+                break;
+            }
+        }
+        Ok(implementors)
+    }
+}
+
+/// Where `source`'s pages are published: the remote site itself, or for local
+/// docs the configured base URL and, with `docs_rs`, docs.rs for each
+/// dependency at the version the project uses.
+pub fn site_links(source: &DocSource) -> SiteLinks {
+    let (base_url, docs_rs) = match source {
+        DocSource::Local {
+            base_url, docs_rs, ..
+        } => (base_url.clone(), *docs_rs),
+        DocSource::Remote { base_url, .. } => (remote::normalize_base_url(base_url).ok(), false),
+    };
+    let mut links = SiteLinks::new(base_url.as_deref());
+    if docs_rs {
+        match crate_graph() {
+            Ok(graph) => {
+                links.crate_base_urls = graph
+                    .crate_names()
+                    .filter_map(|name| Some((name.to_string(), graph.docs_rs_url(name)?)))
+                    .collect();
+            }
+            Err(err) => warn!("not linking dependencies to docs.rs: {:#}", err),
+        }
+    }
+    links
+}
+
+/// The URL of the page `item` is documented on. For local docs that's a
+/// `file://` URL into the doc root, unless the page wasn't generated (as with
+/// `cargo doc --no-deps`) and `links` knows where it's published. `None` for
+/// items without a page.
+pub fn page_url(
+    source: &DocSource,
+    search_index_path: &Path,
+    links: &SiteLinks,
+    item: &SearchItem,
+) -> Option<String> {
+    let page = item_page(item)?;
+    if matches!(source, DocSource::Remote { .. }) {
+        return links.absolute_url(item);
+    }
+
+    let doc_root = search_index_path.parent().unwrap_or(Path::new("."));
+    let doc_root = doc_root
+        .canonicalize()
+        .unwrap_or_else(|_| doc_root.to_path_buf());
+    let path = page
+        .file
+        .split('/')
+        .fold(doc_root, |path, segment| path.join(segment));
+    if !path.exists()
+        && let Some(url) = links.absolute_url(item)
+    {
+        return Some(url);
+    }
+    let mut url = format!("file://{}", path.display());
+    if let Some(anchor) = page.anchor {
+        url.push('#');
+        url.push_str(&anchor);
+    }
+    Some(url)
+}
+
+/// Read one of the documentation's HTML pages, downloading it for remote docs.
+///
+/// `file` is relative to the directory holding `search_index_path`, with `/`
+/// separators. Returns `None` if the page doesn't exist.
+fn read_page(source: &DocSource, search_index_path: &Path, file: &str) -> Result<Option<String>> {
+    if let DocSource::Remote { base_url, http, .. } = source {
+        return remote::fetch_page(base_url, http, file);
+    }
+
+    let doc_root = search_index_path.parent().unwrap_or(Path::new("."));
+    let path = file
+        .split('/')
+        .fold(doc_root.to_path_buf(), |path, segment| path.join(segment));
+    if !path.exists() {
+        return Ok(None);
+    }
+    std::fs::read_to_string(&path)
+        .map(Some)
+        .wrap_err_with(|| format!("Failed to read {}", path.display()))
+}
+
+/// Names of the library crates in the current workspace, as they appear in the
+/// search index.
+pub fn workspace_crates() -> Result<Vec<String>> {
+    let metadata = cargo_metadata(&["--no-deps"])?;
+    Ok(metadata["packages"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(library_name)
+        .collect())
+}
+
+/// How each crate in the current project's dependency graph relates to the workspace.
+pub fn crate_graph() -> Result<CrateGraph> {
+    Ok(CrateGraph::from_metadata(&cargo_metadata(&[])?))
+}
+
+fn cargo_metadata(args: &[&str]) -> Result<serde_json::Value> {
+    let output = cargo_command()
+        .args(["metadata", "--format-version", "1"])
+        .args(args)
+        .output()
+        .wrap_err("Failed to execute cargo metadata")?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(eyre!("cargo metadata failed:\n{}", stderr));
+    }
+    serde_json::from_slice(&output.stdout).wrap_err("Invalid cargo metadata output")
+}
+
+/// Map search-index.js and extract the JSON it embeds, for commands that are
+/// done with the index before its docs could be regenerated.
+pub fn load_search_index_json(search_index_path: &Path) -> Result<IndexJson> {
+    extract_search_index_json(search_index_path, IndexFile::open)
+}
+
+/// Read search-index.js into memory and extract the JSON it embeds, for
+/// `serve` and `repl`, which keep the index while `cargo doc` may rewrite it
+/// (see [`IndexFile::open`]).
+pub fn read_search_index_json(search_index_path: &Path) -> Result<IndexJson> {
+    extract_search_index_json(search_index_path, IndexFile::read)
+}
+
+fn extract_search_index_json(
+    search_index_path: &Path,
+    open: fn(&Path) -> std::io::Result<IndexFile>,
+) -> Result<IndexJson> {
+    let spinner = progress::spinner("Parsing search index");
+    let index_file = open(search_index_path).wrap_err("Failed to open search-index.js")?;
+    info!(
+        "{} {} ({} bytes)",
+        if index_file.is_mapped() {
+            "mapped"
+        } else {
+            "read"
+        },
+        search_index_path.display(),
+        index_file.len()
+    );
+    info!(
+        target: EVENT_TARGET,
+        event = "index_loaded",
+        path = %search_index_path.display(),
+        bytes = index_file.len() as u64
+    );
+
+    let version = search_index_path.parent().and_then(detect_rustdoc_version);
+    match version {
+        Some(version) => debug!("docs written by rustdoc {}", version),
+        None => debug!("docs don't say which rustdoc wrote them"),
+    }
+    *RUSTDOC_VERSION
+        .lock()
+        .unwrap_or_else(|err| err.into_inner()) = version;
+
+    let json = IndexJson::extract(index_file).wrap_err_with(|| {
+        format!(
+            "{} is not a rustdoc search index{}",
+            search_index_path.display(),
+            written_by()
+        )
+    })?;
+    debug!(
+        "{} the index JSON",
+        if json.is_borrowed() {
+            "borrowed"
+        } else {
+            "unescaped"
+        }
+    );
+    spinner.finish_and_clear();
+
+    Ok(json)
+}
+
+/// Split the extracted JSON into lazily-decoded crate entries, leaving out
+/// crates `.rdocignore` ignores.
+pub fn crate_entries(json_string: &str) -> Result<Vec<LazyCrateEntry<'_>>> {
+    let mut entries = parse_search_index_lazy(json_string)
+        .wrap_err_with(|| format!("Failed to parse search-index.js{}", written_by()))?;
+    let rules = ignore_rules();
+    if !rules.is_empty() {
+        entries.retain(|entry| {
+            let ignored = rules.ignores_crate(&entry.name);
+            if ignored {
+                debug!("ignoring crate {}", entry.name);
+            }
+            !ignored
+        });
+    }
+    Ok(entries)
+}
+
+/// Decode every crate into search items, skipping (and logging) malformed crates.
+///
+/// Crates are decoded one at a time so only a single crate's data is held in memory.
+pub fn decode_items(crate_entries: &[LazyCrateEntry], source: &DocSource) -> Vec<SearchItem> {
+    decode_items_into(crate_entries, source, None)
+}
+
+/// Like [`decode_items`], also adding each crate's `#[doc(alias)]` names to `aliases`.
+pub fn decode_items_with_aliases(
+    crate_entries: &[LazyCrateEntry],
+    source: &DocSource,
+    aliases: &mut Aliases,
+) -> Vec<SearchItem> {
+    decode_items_into(crate_entries, source, Some(aliases))
+}
+
+/// Like [`decode_items_with_aliases`], reusing the items of crates that
+/// would decode the same as when they were decoded into `cache`, and keeping
+/// the rest there for the next load. The index shares each crate's items
+/// with the cache rather than copying them.
+pub fn decode_items_cached(
+    crate_entries: &[LazyCrateEntry],
+    source: &DocSource,
+    aliases: &mut Aliases,
+    cache: &mut DecodeCache,
+) -> arena::Index {
+    let source_id: Arc<str> = Arc::from(source.id());
+    let decode_start = Instant::now();
+    let (features, rules) = (index_features(), ignore_rules());
+    let mut chunks = Vec::with_capacity(crate_entries.len());
+    let mut reused = 0;
+    // Crates no longer in the index are dropped from the cache
+    let mut previous = std::mem::take(&mut cache.crates);
+    decode_each(crate_entries, |entry| {
+        let key = crate_key(entry, features, rules);
+        let cached = match previous.remove(entry.name.as_ref()) {
+            Some(cached) if cached.key == key => {
+                reused += 1;
+                cached
+            }
+            _ => {
+                let Some((items, data)) = decode_crate_entry(entry, &source_id) else {
+                    return;
+                };
+                log_crate_decoded(entry, items.len());
+                let mut crate_aliases = Aliases::default();
+                crate_aliases.add_crate(&source_id, &entry.name, &data);
+                CachedCrate {
+                    key,
+                    items: items.into(),
+                    aliases: crate_aliases,
+                }
+            }
+        };
+        aliases.extend(&cached.aliases);
+        chunks.push(Arc::clone(&cached.items));
+        cache.crates.insert(entry.name.to_string(), cached);
+    });
+    let index = arena::Index::new(chunks);
+    log_items_decoded(crate_entries.len(), index.len(), decode_start);
+    info!("{} of {} crates unchanged", reused, crate_entries.len());
+    index
+}
+
+/// Crates decoded on earlier loads, by name, each with a key telling
+/// whether it would decode differently now. Reloading after
+/// `cargo doc -p one_crate` then only decodes that crate again.
+#[derive(Debug, Default)]
+pub struct DecodeCache {
+    crates: HashMap<String, CachedCrate>,
+}
+
+#[derive(Debug)]
+struct CachedCrate {
+    /// The [`crate_key`] it was decoded under
+    key: u64,
+    items: Arc<[SearchItem]>,
+    aliases: Aliases,
+}
+
+impl DecodeCache {
+    /// How many crates are kept.
+    pub fn len(&self) -> usize {
+        self.crates.len()
+    }
+}
+
+/// A hash of what decoding a crate depends on: its raw data, the index
+/// format it's read as and the `.rdocignore` rules leaving items out.
+fn crate_key(entry: &LazyCrateEntry, features: IndexFeatures, rules: &IgnoreRules) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    entry.raw_data.get().hash(&mut hasher);
+    features.hash(&mut hasher);
+    rules.hash(&mut hasher);
+    hasher.finish()
+}
+
+fn decode_items_into(
+    crate_entries: &[LazyCrateEntry],
+    source: &DocSource,
+    mut aliases: Option<&mut Aliases>,
+) -> Vec<SearchItem> {
+    let source_id: Arc<str> = Arc::from(source.id());
+    let decode_start = Instant::now();
+    let mut all_items = Vec::new();
+    decode_each(crate_entries, |entry| {
+        if let Some((items, data)) = decode_crate_entry(entry, &source_id) {
+            log_crate_decoded(entry, items.len());
+            if let Some(aliases) = aliases.as_deref_mut() {
+                aliases.add_crate(&source_id, &entry.name, &data);
+            }
+            all_items.extend(items);
+        }
+    });
+    log_items_decoded(crate_entries.len(), all_items.len(), decode_start);
+    all_items
+}
+
+/// Run `decode` on each crate in turn, showing progress.
+fn decode_each(crate_entries: &[LazyCrateEntry], mut decode: impl FnMut(&LazyCrateEntry)) {
+    let bar = progress::bar(crate_entries.len() as u64, "Decoding crates");
+    for entry in crate_entries {
+        bar.set_message(entry.name.to_string());
+        bar.inc(1);
+        decode(entry);
+    }
+    bar.finish_and_clear();
+}
+
+fn log_items_decoded(crates: usize, items: usize, decode_start: Instant) {
+    info!(
+        "decoded {} items from {} crates in {:?}",
+        items,
+        crates,
+        decode_start.elapsed()
+    );
+    info!(
+        target: EVENT_TARGET,
+        event = "items_decoded",
+        crates,
+        items,
+        elapsed_ms = decode_start.elapsed().as_millis() as u64
+    );
+}
+
+fn log_crate_decoded(entry: &LazyCrateEntry, items: usize) {
+    info!(
+        target: EVENT_TARGET,
+        event = "crate_decoded",
+        crate_name = %entry.name,
+        items
+    );
+}
+
+/// Decode one crate's items along with its data, or `None` (logged) if the
+/// crate is malformed. Items `.rdocignore` ignores are left out.
+pub fn decode_crate_entry(
+    entry: &LazyCrateEntry,
+    source_id: &Arc<str>,
+) -> Option<(Vec<SearchItem>, CrateData)> {
+    let data = crate_entry_data(entry)?;
+    // Ignored items are dropped as they're decoded rather than kept until the end
+    let mut decoded = crate_items(&entry.name, &data, source_id);
+    let items: Vec<SearchItem> = decoded.by_ref().filter(is_kept).collect();
+    record_diagnostics(&entry.name, decoded);
+    Some((items, data))
+}
+
+/// One crate's data, or `None` (logged) if the crate is malformed.
+pub fn crate_entry_data(entry: &LazyCrateEntry) -> Option<CrateData> {
+    let data = match entry.data() {
+        Ok(data) => data,
+        Err(err) => {
+            warn!(
+                "skipping crate {}: malformed crate data{}: {}",
+                entry.name,
+                written_by(),
+                err
+            );
+            return None;
+        }
+    };
+
+    // decode_crate relies on `t` and `n` being parallel arrays
+    if data.item_count() != data.names.len() {
+        warn!(
+            "skipping crate {}: {} type codes but {} names",
+            entry.name,
+            data.item_count(),
+            data.names.len()
+        );
+        return None;
+    }
+    Some(data)
+}
+
+/// The items of `data` (from [`crate_entry_data`]), decoded one at a time
+/// as the loaded index's version lays them out. Pass the iterator to
+/// [`record_diagnostics`] once it's done.
+pub fn crate_items<'a>(
+    crate_name: &str,
+    data: &'a CrateData,
+    source_id: &Arc<str>,
+) -> CrateItems<'a> {
+    CrateItems::new(source_id, crate_name, data, index_features())
+}
+
+/// Whether `.rdocignore` keeps `item`.
+pub fn is_kept(item: &SearchItem) -> bool {
+    let rules = ignore_rules();
+    rules.is_empty() || !rules.ignores_item(item)
+}
+
+/// Warn about and keep, for [`report_diagnostics`], the problems decoding
+/// `crate_name`'s items worked around.
+pub fn record_diagnostics(crate_name: &str, decoded: CrateItems) {
+    let diagnostics = decoded.into_diagnostics();
+    warn_unknown_item_types(crate_name, &diagnostics);
+    DIAGNOSTICS
+        .lock()
+        .unwrap_or_else(|err| err.into_inner())
+        .add(crate_name, diagnostics);
+}
+
+/// Decode the function signatures of every crate, by crate name.
+///
+/// Each crate's list is indexed like [`ItemId::index`]. Crates whose data
+/// can't be decoded are left out, as in [`decode_items`].
+pub fn decode_signatures(
+    crate_entries: &[LazyCrateEntry],
+) -> HashMap<String, Vec<Option<FunctionSignature>>> {
+    crate_entries
+        .iter()
+        .filter_map(|entry| {
+            let data = entry.data().ok()?;
+            Some((entry.name.to_string(), crate_signatures(&data)))
+        })
+        .collect()
+}
+
+/// The signature of each of a crate's items, as [`signature::decode_signatures`]
+/// gives them, or none if the index predates the signature encoding rdoc reads.
+pub fn crate_signatures(data: &CrateData) -> Vec<Option<FunctionSignature>> {
+    if !index_features().vlq_signatures {
+        return vec![None; data.names.len()];
+    }
+    signature::decode_signatures(data)
+}
+
+/// The signature of `item`, from the result of [`decode_signatures`].
+pub fn signature_of<'a>(
+    signatures: &'a HashMap<String, Vec<Option<FunctionSignature>>>,
+    item: &SearchItem,
+) -> Option<&'a FunctionSignature> {
+    signatures
+        .get(&item.crate_name)?
+        .get(item.id.index)?
+        .as_ref()
+}
+
+/// Keep only items from `crates`, or every item if none were named.
+///
+/// Errors if a named crate is not in the search index, which usually means a typo.
+pub fn filter_crates(items: Vec<SearchItem>, crates: &[String]) -> Result<Vec<SearchItem>> {
+    if crates.is_empty() {
+        return Ok(items);
+    }
+
+    for name in crates {
+        if !items.iter().any(|item| &item.crate_name == name) {
+            return Err(eyre!("Crate \"{}\" is not in the search index", name));
+        }
+    }
+
+    Ok(items
+        .into_iter()
+        .filter(|item| crates.contains(&item.crate_name))
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_local_search_index_path() {
+        let path = local_search_index_path(None);
+        let components: Vec<_> = path.iter().collect();
+        assert_eq!(components, ["target", "doc", "search-index.js"]);
+    }
+
+    #[test]
+    fn test_decode_items_cached() {
+        let content = std::fs::read_to_string("tests/fixtures/search-index.js").unwrap();
+        let json_string = rdoc::search_index::extract_json_string(&content).unwrap();
+        let entries = parse_search_index_lazy(&json_string).unwrap();
+        let source = DocSource::Local {
+            doc_dir: None,
+            doc_roots: Vec::new(),
+            base_url: None,
+            docs_rs: false,
+            stale: StaleDocs::Ignore,
+        };
+        let mut aliases = Aliases::default();
+        let expected = decode_items_with_aliases(&entries, &source, &mut aliases);
+
+        let mut cache = DecodeCache::default();
+        let mut first = Aliases::default();
+        let index = decode_items_cached(&entries, &source, &mut first, &mut cache);
+        assert!(index.iter().eq(&expected));
+        assert_eq!(first, aliases);
+        assert_eq!(cache.len(), entries.len());
+
+        // Unchanged crates come from the cache, with their aliases, their
+        // items shared rather than copied
+        let mut second = Aliases::default();
+        let reloaded = decode_items_cached(&entries, &source, &mut second, &mut cache);
+        assert!(reloaded.iter().eq(&expected));
+        assert_eq!(second, aliases);
+        assert_eq!(reloaded.chunks().len(), entries.len());
+        for (chunk, cached) in reloaded.chunks().iter().zip(index.chunks()) {
+            assert!(Arc::ptr_eq(chunk, cached));
+        }
+
+        // A crate whose data changed is decoded again; one that's gone is dropped
+        let changed = &entries[0];
+        let key = crate_key(changed, index_features(), ignore_rules());
+        cache.crates.get_mut(changed.name.as_ref()).unwrap().key = key ^ 1;
+        let rest = &entries[..entries.len() - 1];
+        let index = decode_items_cached(rest, &source, &mut Aliases::default(), &mut cache);
+        assert_eq!(cache.len(), rest.len());
+        assert_eq!(cache.crates[changed.name.as_ref()].key, key);
+        assert!(index.iter().eq(&decode_items(rest, &source)));
+    }
+
+    #[test]
+    fn test_crate_key() {
+        let content = std::fs::read_to_string("tests/fixtures/search-index.js").unwrap();
+        let json_string = rdoc::search_index::extract_json_string(&content).unwrap();
+        let entries = parse_search_index_lazy(&json_string).unwrap();
+        let features = IndexFeatures::default();
+        let rules = IgnoreRules::default();
+        let key = crate_key(&entries[0], features, &rules);
+        assert_eq!(key, crate_key(&entries[0], features, &rules));
+        assert_ne!(key, crate_key(&entries[1], features, &rules));
+
+        // Decoding under another index format or other ignore rules gives
+        // other items
+        let older = IndexFeatures {
+            description_shards: false,
+            ..features
+        };
+        assert_ne!(key, crate_key(&entries[0], older, &rules));
+        let ignoring = IgnoreRules::parse("serde::__private").unwrap();
+        assert_ne!(key, crate_key(&entries[0], features, &ignoring));
+    }
+
+    #[cfg(windows)]
+    #[test]
+    fn test_local_search_index_path_on_windows() {
+        assert_eq!(
+            local_search_index_path(None).to_str(),
+            Some(r"target\doc\search-index.js")
+        );
+    }
+}
@@ -0,0 +1,156 @@
+// Encoding item tables into crate data the way rustdoc compresses them, so
+// tests can check that decoding reverses it
+
+use proptest::prelude::*;
+use std::collections::VecDeque;
+
+use crate::search_index::{CrateData, ITEM_TYPES, ItemType, PathItem, QualifiedPath};
+
+/// Recently encoded values a later one can refer back to, as the decoder keeps them
+const BACKREF_QUEUE_LEN: usize = 16;
+
+/// One item of a table to encode
+#[derive(Debug, Clone)]
+pub struct Row {
+    pub ty: ItemType,
+    pub name: String,
+    /// Module path; an empty one is left out for the first item
+    pub path: String,
+    /// Index into the table's parents
+    pub parent: Option<usize>,
+}
+
+/// Encode `rows` as rustdoc would: a name equal to the one before it is
+/// written empty, a `q` entry is written only where the path changes, and
+/// parents are VLQ-encoded indices into `parents`.
+///
+/// An empty name after a non-empty one can't be encoded; it decodes as the
+/// name before it. A first row with an empty name or path encodes as the
+/// malformed data rustdoc never writes.
+pub fn encode_crate(rows: &[Row], parents: &[(ItemType, String)]) -> CrateData {
+    let mut last_name = "";
+    let mut last_path = "";
+    let mut names = Vec::with_capacity(rows.len());
+    let mut paths = Vec::new();
+    for (index, row) in rows.iter().enumerate() {
+        names.push(if row.name == last_name {
+            String::new()
+        } else {
+            row.name.clone()
+        });
+        if row.path != last_path {
+            paths.push(QualifiedPath {
+                index,
+                path: row.path.clone(),
+            });
+        }
+        last_name = &row.name;
+        last_path = &row.path;
+    }
+    let parent_values: Vec<i32> = rows
+        .iter()
+        .map(|row| row.parent.map_or(0, |parent| parent as i32 + 1))
+        .collect();
+
+    CrateData {
+        types: rows
+            .iter()
+            .map(|row| char::from(b'A' + row.ty.code()))
+            .collect(),
+        names,
+        paths,
+        parent_items: parents
+            .iter()
+            .map(|(ty, name)| PathItem {
+                ty: *ty,
+                name: name.clone(),
+                path_index: None,
+                exact_path_index: None,
+                unbox_flag: None,
+            })
+            .collect(),
+        reexports: vec![],
+        i: encode_vlq(&parent_values),
+        f: String::new(),
+        desc: String::new(),
+        param_types: vec![],
+        impl_disambiguators: vec![],
+        c: String::new(),
+        e: String::new(),
+        aliases: None,
+    }
+}
+
+/// Encode `values` as a VLQ hex string, referring back to recent values
+/// instead of repeating them and writing zeros as `` ` ``.
+pub fn encode_vlq(values: &[i32]) -> String {
+    let mut encoded = String::new();
+    let mut backrefs: VecDeque<i32> = VecDeque::with_capacity(BACKREF_QUEUE_LEN);
+    for &value in values {
+        if value == 0 {
+            encoded.push('`');
+            continue;
+        }
+        if let Some(position) = backrefs.iter().position(|&recent| recent == value) {
+            encoded.push(char::from(b'0' + position as u8));
+            continue;
+        }
+        let mut n = (value.unsigned_abs() << 1) | u32::from(value < 0);
+        let mut digits = Vec::new();
+        loop {
+            digits.push((n & 15) as u8);
+            n >>= 4;
+            if n == 0 {
+                break;
+            }
+        }
+        // Most significant first; all but the last are continuation digits
+        for (position, digit) in digits.iter().rev().enumerate() {
+            let base = if position + 1 == digits.len() {
+                b'`'
+            } else {
+                b'@'
+            };
+            encoded.push(char::from(base + digit));
+        }
+        backrefs.push_front(value);
+        backrefs.truncate(BACKREF_QUEUE_LEN);
+    }
+    encoded
+}
+
+/// Item tables of up to 40 rows and the parents they refer to. Names and
+/// paths come from small sets so runs of the same one, written empty or left
+/// out of `q`, are common; the first row's name and path may be empty.
+pub fn tables() -> impl Strategy<Value = (Vec<Row>, Vec<(ItemType, String)>)> {
+    let item_types = || prop::sample::select(ITEM_TYPES.to_vec());
+    let parents = prop::collection::vec((item_types(), "[A-Z][a-z]{0,4}"), 0..4);
+    parents.prop_flat_map(move |parents| {
+        let parent = if parents.is_empty() {
+            Just(None).boxed()
+        } else {
+            prop::option::of(0..parents.len()).boxed()
+        };
+        let row = (
+            item_types(),
+            prop::sample::select(&["new", "from_str", "Value", "de", "_"][..]),
+            prop::sample::select(&["", "serde", "serde::de", "serde_json::value"][..]),
+            parent,
+        )
+            .prop_map(|(ty, name, path, parent)| Row {
+                ty,
+                name: name.to_string(),
+                path: path.to_string(),
+                parent,
+            });
+        let rows = (prop::collection::vec(row, 0..40), any::<bool>()).prop_map(
+            |(mut rows, leading_empty_name)| {
+                if let Some(first) = rows.first_mut().filter(|_| leading_empty_name) {
+                    first.name.clear();
+                }
+                rows
+            },
+        );
+        (rows, Just(parents))
+    })
+}
@@ -0,0 +1,17 @@
+// Structured progress events, for dashboards and CI logs
+
+/// The tracing target of rdoc's progress events, kept apart from its
+/// diagnostics so they can be turned on alone (`RDOC_LOG=rdoc::events=info`,
+/// or `--log-format json`).
+///
+/// Each event is logged at `info` with an `event` field naming it:
+///
+/// - `doc_generation_started` and `doc_generation_finished` (`elapsed_ms`)
+///   around `cargo doc`
+/// - `index_loaded` (`path`, `bytes`) once `search-index.js` is read
+/// - `crate_decoded` (`crate_name`, `items`) for each crate, then
+///   `items_decoded` (`crates`, `items`, `elapsed_ms`)
+/// - `shard_loaded` (`crate_name`, `shard`, `descriptions`) for each
+///   description shard read
+/// - `results_ready` (`query`, `results`) when a search is done
+pub const EVENT_TARGET: &str = "rdoc::events";
@@ -0,0 +1,189 @@
+// Step-by-step account of how a single item was decoded
+
+use crate::bitmap::RoaringBitmap;
+use crate::search_index::CrateData;
+use crate::search_items::{SearchItem, decode_crate};
+use crate::vlq::VlqHexDecoder;
+
+/// Where each field of a decoded item came from in the compact crate data.
+///
+/// Used by `rdoc explain` to debug the decoder against changes in rustdoc's format.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ItemExplanation {
+    /// The item as decoded by [`decode_crate`]
+    pub item: SearchItem,
+
+    /// Raw character from the `t` string
+    pub type_char: char,
+
+    /// Index into `n` of the non-empty name this item uses.
+    ///
+    /// Equal to the item's own index unless the name was compressed, and `None`
+    /// if no earlier item had a name to reuse.
+    pub name_source: Option<usize>,
+
+    /// Position in `q` of the entry that supplied the item's path, if any
+    pub path_entry: Option<usize>,
+
+    /// Position in `r` of the entry that supplied the item's exact path, if any
+    pub reexport_entry: Option<usize>,
+
+    /// Raw bytes from the `i` string that encoded this item's parent
+    pub parent_bytes: String,
+
+    /// Value decoded from `parent_bytes` (1-based index into `p`, 0 = no parent)
+    pub parent_value: Option<i32>,
+
+    /// Whether the item's bit is set in the `c` (deprecated) bitmap.
+    /// `None` if the bitmap could not be decoded.
+    pub deprecated: Option<bool>,
+
+    /// Whether the item's bit is set in the `e` (empty description) bitmap.
+    /// `None` if the bitmap could not be decoded.
+    pub empty_description: Option<bool>,
+}
+
+/// Explain how the item at `index` in a crate's compact data is decoded.
+///
+/// Returns `None` if `index` is out of range.
+pub fn explain_item(
+    crate_name: &str,
+    crate_data: &CrateData,
+    index: usize,
+) -> Option<ItemExplanation> {
+    let type_char = crate_data.types.chars().nth(index)?;
+    let item = decode_crate(crate_name, crate_data)
+        .into_iter()
+        .nth(index)?;
+
+    let name_source = (0..=index)
+        .rev()
+        .find(|&i| crate_data.names.get(i).is_some_and(|name| !name.is_empty()));
+
+    // Paths carry forward, so the source is the closest entry at or before this item
+    let path_entry = crate_data
+        .paths
+        .iter()
+        .enumerate()
+        .filter(|(_, qualified_path)| qualified_path.index <= index)
+        .max_by_key(|(_, qualified_path)| qualified_path.index)
+        .map(|(position, _)| position);
+
+    let reexport_entry = crate_data
+        .reexports
+        .iter()
+        .position(|reexport| reexport.item_index == index);
+
+    let (parent_bytes, parent_value) = parent_encoding(&crate_data.i, index);
+
+    let bit_index = item.bit_index as u32;
+    let bit_is_set = |encoded: &str| {
+        RoaringBitmap::from_base64(encoded)
+            .ok()
+            .map(|bitmap| bitmap.contains(bit_index))
+    };
+
+    Some(ItemExplanation {
+        type_char,
+        name_source,
+        path_entry,
+        reexport_entry,
+        parent_bytes,
+        parent_value,
+        deprecated: bit_is_set(&crate_data.c),
+        empty_description: bit_is_set(&crate_data.e),
+        item,
+    })
+}
+
+/// Find the raw bytes in the `i` string for the item at `index`, and their value.
+fn parent_encoding(encoded: &str, index: usize) -> (String, Option<i32>) {
+    let mut decoder = VlqHexDecoder::new(encoded);
+    for _ in 0..index {
+        decoder.next();
+    }
+
+    let start = decoder.offset();
+    let value = decoder.next();
+    (encoded[start..decoder.offset()].to_string(), value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::search_index::{QualifiedPath, Reexport};
+
+    fn crate_data() -> CrateData {
+        CrateData {
+            types: "CFN".to_string(),
+            names: vec!["foo".to_string(), "Bar".to_string(), "".to_string()],
+            paths: vec![
+                QualifiedPath {
+                    index: 0,
+                    path: "mylib".to_string(),
+                },
+                QualifiedPath {
+                    index: 1,
+                    path: "mylib::structs".to_string(),
+                },
+            ],
+            parent_items: vec![],
+            reexports: vec![Reexport {
+                item_index: 1,
+                path_index: 0,
+            }],
+            // 0, 0, then a two-byte value (9)
+            i: "``Ab".to_string(),
+            f: String::new(),
+            desc: String::new(),
+            param_types: vec![],
+            impl_disambiguators: vec![],
+            // Bits 2 and 3 (items 1 and 2) are deprecated
+            c: "OjAAAAEAAAAAAAEAEAAAAAIAAwA=".to_string(),
+            e: String::new(),
+            aliases: None,
+        }
+    }
+
+    #[test]
+    fn test_explain_compressed_name_and_path() {
+        let explanation = explain_item("mylib", &crate_data(), 2).unwrap();
+
+        assert_eq!(explanation.type_char, 'N');
+        assert_eq!(explanation.item.name, "Bar");
+        assert_eq!(explanation.name_source, Some(1));
+        assert_eq!(explanation.path_entry, Some(1));
+        assert_eq!(explanation.reexport_entry, None);
+    }
+
+    #[test]
+    fn test_explain_parent_bytes() {
+        let data = crate_data();
+
+        let first = explain_item("mylib", &data, 0).unwrap();
+        assert_eq!(first.parent_bytes, "`");
+        assert_eq!(first.parent_value, Some(0));
+
+        let multi_byte = explain_item("mylib", &data, 2).unwrap();
+        assert_eq!(multi_byte.parent_bytes, "Ab");
+        assert_eq!(multi_byte.parent_value, Some(9));
+    }
+
+    #[test]
+    fn test_explain_reexport_and_bitmaps() {
+        let data = crate_data();
+
+        let explanation = explain_item("mylib", &data, 1).unwrap();
+        assert_eq!(explanation.reexport_entry, Some(0));
+        assert_eq!(explanation.deprecated, Some(true));
+        assert_eq!(explanation.empty_description, Some(false));
+
+        let first = explain_item("mylib", &data, 0).unwrap();
+        assert_eq!(first.deprecated, Some(false));
+    }
+
+    #[test]
+    fn test_explain_out_of_range() {
+        assert!(explain_item("mylib", &crate_data(), 3).is_none());
+    }
+}
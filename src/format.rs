@@ -0,0 +1,546 @@
+// Output formats for search results
+
+use crate::pages::{SiteLinks, item_page};
+use crate::resolve::ResolveError;
+use crate::schema::Versioned;
+use crate::search_items::SearchItem;
+use crate::timings::Timings;
+use serde::Serialize;
+use std::fmt;
+use std::io::{self, Write};
+use std::process::{Command, Stdio};
+use std::str::FromStr;
+
+/// Writes a list of search results in some output format.
+pub trait Formatter {
+    fn format(&self, results: &[&SearchItem], out: &mut dyn Write) -> io::Result<()>;
+}
+
+/// One search result as written by the JSON formats and sent to external formatters.
+#[derive(Debug, Serialize)]
+pub struct ResultRecord<'a> {
+    pub id: String,
+    pub name: &'a str,
+    pub item_type: String,
+    #[serde(rename = "crate")]
+    pub crate_name: &'a str,
+    pub path: &'a str,
+    pub exact_path: &'a str,
+    pub deprecated: bool,
+    /// The item's page relative to the doc root, e.g.
+    /// `serde/trait.Serialize.html`, or `None` for items without one
+    pub url: Option<String>,
+    /// `url` on the doc site, when its base URL is known
+    pub absolute_url: Option<String>,
+}
+
+impl<'a> ResultRecord<'a> {
+    /// The record for `item`, with its page's absolute URL from `links`.
+    pub fn new(item: &'a SearchItem, links: &SiteLinks) -> Self {
+        let url = item_page(item).map(|page| page.href());
+        let absolute_url = links.absolute_url(item);
+        Self {
+            id: item.id.to_string(),
+            name: &item.name,
+            item_type: item.item_type.to_string(),
+            crate_name: &item.crate_name,
+            path: &item.path,
+            exact_path: &item.exact_path,
+            deprecated: item.deprecated,
+            url,
+            absolute_url,
+        }
+    }
+}
+
+impl<'a> From<&'a SearchItem> for ResultRecord<'a> {
+    fn from(item: &'a SearchItem) -> Self {
+        Self::new(item, &SiteLinks::default())
+    }
+}
+
+/// A format selected with `--format`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum Format {
+    /// One result per line with its path underneath
+    #[default]
+    Plain,
+    /// Pretty-printed JSON: a [`Versioned`] header and a `results` array of
+    /// [`ResultRecord`]s
+    Json,
+    /// One compact [`ResultRecord`] per line (newline-delimited JSON), written
+    /// as results are found
+    Ndjson,
+    /// A Markdown table
+    Markdown,
+    /// Columns aligned with spaces
+    Table,
+    /// An external program that reads results as JSON lines on stdin and
+    /// writes the formatted output to stdout
+    Exec(String),
+}
+
+impl Format {
+    /// The formatter that writes this format.
+    pub fn formatter(&self) -> Box<dyn Formatter> {
+        self.formatter_with_links(SiteLinks::default())
+    }
+
+    /// The formatter for this format, giving JSON records absolute URLs from
+    /// `links`.
+    pub fn formatter_with_links(&self, links: SiteLinks) -> Box<dyn Formatter> {
+        match self {
+            Format::Plain => Box::new(PlainFormatter),
+            Format::Json => Box::new(JsonFormatter {
+                links,
+                timings: None,
+            }),
+            Format::Ndjson => Box::new(NdjsonFormatter { links }),
+            Format::Markdown => Box::new(MarkdownFormatter),
+            Format::Table => Box::new(TableFormatter),
+            Format::Exec(command) => Box::new(ExecFormatter {
+                command: command.clone(),
+                links,
+            }),
+        }
+    }
+}
+
+impl FromStr for Format {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if let Some(command) = s.strip_prefix("exec:") {
+            if command.trim().is_empty() {
+                return Err("exec: needs a formatter command, e.g. exec:./my-formatter".to_string());
+            }
+            return Ok(Format::Exec(command.to_string()));
+        }
+
+        match s {
+            "plain" => Ok(Format::Plain),
+            "json" => Ok(Format::Json),
+            "ndjson" => Ok(Format::Ndjson),
+            "markdown" => Ok(Format::Markdown),
+            "table" => Ok(Format::Table),
+            _ => Err(format!(
+                "unknown format \"{}\" (expected plain, json, ndjson, markdown, table or exec:COMMAND)",
+                s
+            )),
+        }
+    }
+}
+
+impl fmt::Display for Format {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Format::Plain => f.write_str("plain"),
+            Format::Json => f.write_str("json"),
+            Format::Ndjson => f.write_str("ndjson"),
+            Format::Markdown => f.write_str("markdown"),
+            Format::Table => f.write_str("table"),
+            Format::Exec(command) => write!(f, "exec:{}", command),
+        }
+    }
+}
+
+/// `name (Type) in crate`, with `at path` on the next line.
+pub struct PlainFormatter;
+
+impl Formatter for PlainFormatter {
+    fn format(&self, results: &[&SearchItem], out: &mut dyn Write) -> io::Result<()> {
+        for item in results {
+            writeln!(
+                out,
+                "{} ({:?}) in {}",
+                item.name, item.item_type, item.crate_name
+            )?;
+            if !item.path.is_empty() {
+                writeln!(out, "    at {}", item.path)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// The body of the `Results` JSON format.
+#[derive(Debug, Serialize)]
+struct ResultList<'a> {
+    results: Vec<ResultRecord<'a>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    timings: Option<Timings>,
+}
+
+/// One query's results in `scan --stdin` output: a line of its NDJSON, or an
+/// entry of [`BatchResults`]
+#[derive(Debug, Serialize)]
+pub struct QueryResults<'a> {
+    pub query: String,
+    /// Why the query failed, in which case there are no results
+    pub error: Option<String>,
+    pub results: Vec<ResultRecord<'a>>,
+}
+
+impl<'a> QueryResults<'a> {
+    pub fn new(query: &str, results: &[&'a SearchItem], links: &SiteLinks) -> Self {
+        Self {
+            query: query.to_string(),
+            error: None,
+            results: results
+                .iter()
+                .map(|&item| ResultRecord::new(item, links))
+                .collect(),
+        }
+    }
+
+    pub fn failed(query: &str, error: String) -> Self {
+        Self {
+            query: query.to_string(),
+            error: Some(error),
+            results: Vec::new(),
+        }
+    }
+}
+
+/// One path's outcome in a bulk resolution: the item it names, or why it
+/// doesn't name exactly one
+#[derive(Debug, Serialize)]
+pub struct Resolution<'a> {
+    pub path: String,
+    pub item: Option<ResultRecord<'a>>,
+    pub error: Option<ResolutionError>,
+}
+
+/// A [`ResolveError`] as JSON
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ResolutionError {
+    /// [`ResolveError::kind`], e.g. `ambiguous`
+    pub kind: &'static str,
+    pub message: String,
+    /// For `ambiguous`, each candidate with a prefix that resolves to it alone
+    pub candidates: Vec<String>,
+}
+
+impl<'a> Resolution<'a> {
+    pub fn new(
+        path: &str,
+        resolved: Result<&'a SearchItem, ResolveError>,
+        links: &SiteLinks,
+    ) -> Self {
+        let (item, error) = match resolved {
+            Ok(item) => (Some(ResultRecord::new(item, links)), None),
+            Err(err) => (
+                None,
+                Some(ResolutionError {
+                    kind: err.kind(),
+                    message: err.to_string(),
+                    candidates: err.candidates().to_vec(),
+                }),
+            ),
+        };
+        Self {
+            path: path.to_string(),
+            item,
+            error,
+        }
+    }
+}
+
+/// Bulk resolution results, in the order the paths were given, wrapped in a
+/// [`Versioned`] header
+#[derive(Debug, Serialize)]
+pub struct Resolutions<'a> {
+    pub results: Vec<Resolution<'a>>,
+}
+
+/// One item with what its page and description say about it, for previews
+/// in `serve`'s web UI; wrapped in a [`Versioned`] header
+#[derive(Debug, Serialize)]
+pub struct ItemPreview<'a> {
+    pub item: ResultRecord<'a>,
+    /// The impl a method or associated item comes from, e.g. `impl Read for File`
+    pub origin: Option<String>,
+    /// The declaration from the item's page, when the page can be read
+    pub declaration: Option<String>,
+    /// The description as plain text
+    pub description: Option<String>,
+}
+
+/// `scan --stdin --format json`: every query's results, wrapped in a
+/// [`Versioned`] header
+#[derive(Debug, Serialize)]
+pub struct BatchResults<'a> {
+    pub queries: Vec<QueryResults<'a>>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct JsonFormatter {
+    /// Where pages are published, for each record's `absolute_url`
+    pub links: SiteLinks,
+    /// How long the query took, when asked for with `--timings`
+    pub timings: Option<Timings>,
+}
+
+impl Formatter for JsonFormatter {
+    fn format(&self, results: &[&SearchItem], out: &mut dyn Write) -> io::Result<()> {
+        let records = ResultList {
+            results: results
+                .iter()
+                .map(|&item| ResultRecord::new(item, &self.links))
+                .collect(),
+            timings: self.timings,
+        };
+        serde_json::to_writer_pretty(&mut *out, &Versioned::new("Results", records))?;
+        writeln!(out)
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct NdjsonFormatter {
+    /// Where pages are published, for each record's `absolute_url`
+    pub links: SiteLinks,
+}
+
+impl Formatter for NdjsonFormatter {
+    fn format(&self, results: &[&SearchItem], out: &mut dyn Write) -> io::Result<()> {
+        for &item in results {
+            serde_json::to_writer(&mut *out, &ResultRecord::new(item, &self.links))?;
+            writeln!(out)?;
+        }
+        Ok(())
+    }
+}
+
+pub struct MarkdownFormatter;
+
+impl Formatter for MarkdownFormatter {
+    fn format(&self, results: &[&SearchItem], out: &mut dyn Write) -> io::Result<()> {
+        writeln!(out, "| Name | Type | Crate | Path |")?;
+        writeln!(out, "| --- | --- | --- | --- |")?;
+        for item in results {
+            writeln!(
+                out,
+                "| `{}` | {} | {} | `{}` |",
+                escape_markdown_cell(&item.name),
+                item.item_type,
+                escape_markdown_cell(&item.crate_name),
+                escape_markdown_cell(&item.path)
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Keep a value from ending its table cell early.
+fn escape_markdown_cell(value: &str) -> String {
+    value.replace('|', "\\|")
+}
+
+pub struct TableFormatter;
+
+impl Formatter for TableFormatter {
+    fn format(&self, results: &[&SearchItem], out: &mut dyn Write) -> io::Result<()> {
+        let rows: Vec<[String; 4]> = results
+            .iter()
+            .map(|item| {
+                [
+                    item.name.clone(),
+                    item.item_type.to_string(),
+                    item.crate_name.clone(),
+                    item.path.clone(),
+                ]
+            })
+            .collect();
+        let header = ["NAME", "TYPE", "CRATE", "PATH"].map(String::from);
+
+        let mut widths = header.clone().map(|cell| cell.chars().count());
+        for row in &rows {
+            for (width, cell) in widths.iter_mut().zip(row) {
+                *width = (*width).max(cell.chars().count());
+            }
+        }
+
+        for row in std::iter::once(&header).chain(&rows) {
+            let line = row
+                .iter()
+                .zip(widths)
+                .map(|(cell, width)| format!("{:<width$}", cell, width = width))
+                .collect::<Vec<_>>()
+                .join("  ");
+            writeln!(out, "{}", line.trim_end())?;
+        }
+        Ok(())
+    }
+}
+
+/// Pipes results to an external program, one JSON [`ResultRecord`] per line,
+/// and copies what it prints to the output.
+///
+/// The command is split on whitespace into the program and its arguments.
+pub struct ExecFormatter {
+    pub command: String,
+    /// Where pages are published, for each record's `absolute_url`
+    pub links: SiteLinks,
+}
+
+impl Formatter for ExecFormatter {
+    fn format(&self, results: &[&SearchItem], out: &mut dyn Write) -> io::Result<()> {
+        let mut parts = self.command.split_whitespace();
+        let program = parts.next().ok_or_else(|| {
+            io::Error::new(io::ErrorKind::InvalidInput, "empty formatter command")
+        })?;
+
+        let mut input = Vec::new();
+        for &item in results {
+            serde_json::to_writer(&mut input, &ResultRecord::new(item, &self.links))?;
+            input.push(b'\n');
+        }
+
+        let mut child = Command::new(program)
+            .args(parts)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .spawn()
+            .map_err(|err| {
+                io::Error::new(
+                    err.kind(),
+                    format!("could not run formatter {}: {}", program, err),
+                )
+            })?;
+
+        // Feed stdin from another thread so a formatter that writes before it has
+        // read everything can't deadlock against us
+        let mut stdin = child.stdin.take().expect("stdin is piped");
+        let writer = std::thread::spawn(move || stdin.write_all(&input));
+        let mut stdout = child.stdout.take().expect("stdout is piped");
+        io::copy(&mut stdout, out)?;
+
+        match writer.join().expect("formatter input thread panicked") {
+            // The formatter is free to stop reading early
+            Err(err) if err.kind() != io::ErrorKind::BrokenPipe => return Err(err),
+            _ => {}
+        }
+
+        let status = child.wait()?;
+        if !status.success() {
+            return Err(io::Error::other(format!(
+                "formatter {} exited with {}",
+                program, status
+            )));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::search_index::ItemType;
+
+    fn item(name: &str, path: &str) -> SearchItem {
+        SearchItem::builder("std", ItemType::Function, name)
+            .path(path)
+            .index(3)
+            .build()
+    }
+
+    fn render(format: &Format, items: &[SearchItem]) -> String {
+        let results: Vec<&SearchItem> = items.iter().collect();
+        let mut out = Vec::new();
+        format.formatter().format(&results, &mut out).unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn test_parse_format() {
+        assert_eq!("table".parse::<Format>(), Ok(Format::Table));
+        assert_eq!(
+            "exec:./fmt --wide".parse::<Format>(),
+            Ok(Format::Exec("./fmt --wide".to_string()))
+        );
+        assert!("exec:".parse::<Format>().is_err());
+        assert!("yaml".parse::<Format>().is_err());
+        assert_eq!(Format::Exec("./fmt".to_string()).to_string(), "exec:./fmt");
+    }
+
+    #[test]
+    fn test_plain_and_json() {
+        let items = [item("read_to_string", "std::fs")];
+        assert_eq!(
+            render(&Format::Plain, &items),
+            "read_to_string (Function) in std\n    at std::fs\n"
+        );
+
+        let json: serde_json::Value = serde_json::from_str(&render(&Format::Json, &items)).unwrap();
+        assert_eq!(json["schema_version"], crate::schema::SCHEMA_VERSION);
+        assert_eq!(json["results"][0]["id"], "std#3@local");
+        assert_eq!(json["results"][0]["crate"], "std");
+
+        let ndjson = render(&Format::Ndjson, &[item("a", "std"), item("b", "std")]);
+        let lines: Vec<serde_json::Value> = ndjson
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[1]["name"], "b");
+    }
+
+    #[test]
+    fn test_json_urls() {
+        let items = [item("read_to_string", "std::fs")];
+        let json: serde_json::Value = serde_json::from_str(&render(&Format::Json, &items)).unwrap();
+        assert_eq!(json["results"][0]["url"], "std/fs/fn.read_to_string.html");
+        assert!(json["results"][0]["absolute_url"].is_null());
+
+        let results: Vec<&SearchItem> = items.iter().collect();
+        let mut out = Vec::new();
+        Format::Ndjson
+            .formatter_with_links(SiteLinks::new(Some("https://docs.example.com/api")))
+            .format(&results, &mut out)
+            .unwrap();
+        let record: serde_json::Value = serde_json::from_slice(&out).unwrap();
+        assert_eq!(
+            record["absolute_url"],
+            "https://docs.example.com/api/std/fs/fn.read_to_string.html"
+        );
+    }
+
+    #[test]
+    fn test_markdown_escapes_pipes() {
+        let items = [item("BitOr", "std::ops|weird")];
+        let markdown = render(&Format::Markdown, &items);
+        assert!(markdown.starts_with("| Name | Type | Crate | Path |\n"));
+        assert!(markdown.ends_with("| `BitOr` | fn | std | `std::ops\\|weird` |\n"));
+    }
+
+    #[test]
+    fn test_table_aligns_columns() {
+        let items = [
+            item("read_to_string", "std::fs"),
+            item("new", "std::fs::File"),
+        ];
+        assert_eq!(
+            render(&Format::Table, &items),
+            "NAME            TYPE  CRATE  PATH\n\
+             read_to_string  fn    std    std::fs\n\
+             new             fn    std    std::fs::File\n"
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_exec_formatter_receives_json_lines() {
+        let items = [item("a", "std"), item("b", "std")];
+        let output = render(&Format::Exec("wc -l".to_string()), &items);
+        assert_eq!(output.trim(), "2");
+
+        let failing = Format::Exec("false".to_string());
+        let results: Vec<&SearchItem> = items.iter().collect();
+        assert!(
+            failing
+                .formatter()
+                .format(&results, &mut Vec::new())
+                .is_err()
+        );
+    }
+}
@@ -0,0 +1,185 @@
+// Shell-style glob patterns for symbol names
+
+use std::fmt;
+
+/// A compiled glob pattern supporting `*`, `?` and `[...]` character classes.
+///
+/// Globs match the whole string: `try_*` matches `try_from` but not `retry_from`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Glob {
+    tokens: Vec<Token>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+enum Token {
+    /// A literal character
+    Char(char),
+    /// `?`: any single character
+    Any,
+    /// `*`: any run of characters, including none
+    Star,
+    /// `[...]`: one character from a set, or outside it when negated with `!` or `^`
+    Class {
+        negated: bool,
+        ranges: Vec<(char, char)>,
+    },
+}
+
+/// Why a glob pattern could not be compiled.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GlobError {
+    /// A `[` without a closing `]`
+    UnclosedClass,
+}
+
+impl fmt::Display for GlobError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GlobError::UnclosedClass => write!(f, "unclosed character class"),
+        }
+    }
+}
+
+impl std::error::Error for GlobError {}
+
+impl Glob {
+    /// Whether `pattern` uses any glob syntax, as opposed to being a plain word.
+    pub fn is_glob(pattern: &str) -> bool {
+        pattern.contains(['*', '?', '['])
+    }
+
+    /// Compile a glob pattern.
+    pub fn new(pattern: &str) -> Result<Self, GlobError> {
+        let mut tokens = Vec::new();
+        let mut chars = pattern.chars().peekable();
+
+        while let Some(c) = chars.next() {
+            match c {
+                '*' => {
+                    // Consecutive stars match the same as one
+                    if tokens.last() != Some(&Token::Star) {
+                        tokens.push(Token::Star);
+                    }
+                }
+                '?' => tokens.push(Token::Any),
+                '[' => {
+                    let negated = chars.next_if(|&c| c == '!' || c == '^').is_some();
+                    let mut ranges = Vec::new();
+                    // A `]` straight after the opening bracket is a literal
+                    let mut first = true;
+                    loop {
+                        let start = match chars.next() {
+                            Some(']') if !first => break,
+                            Some(c) => c,
+                            None => return Err(GlobError::UnclosedClass),
+                        };
+                        first = false;
+
+                        let is_range = chars.peek() == Some(&'-')
+                            && chars.clone().nth(1).is_some_and(|c| c != ']');
+                        if is_range {
+                            chars.next();
+                            let end = chars.next().ok_or(GlobError::UnclosedClass)?;
+                            ranges.push((start, end));
+                        } else {
+                            ranges.push((start, start));
+                        }
+                    }
+                    tokens.push(Token::Class { negated, ranges });
+                }
+                c => tokens.push(Token::Char(c)),
+            }
+        }
+
+        Ok(Self { tokens })
+    }
+
+    /// Whether the whole of `text` matches the pattern.
+    pub fn is_match(&self, text: &str) -> bool {
+        let text: Vec<char> = text.chars().collect();
+
+        // Iterative matching that backtracks to the most recent `*`
+        let (mut t, mut p) = (0, 0);
+        let mut backtrack: Option<(usize, usize)> = None;
+        while t < text.len() {
+            match self.tokens.get(p) {
+                Some(Token::Star) => {
+                    backtrack = Some((p, t));
+                    p += 1;
+                }
+                Some(token) if token.matches(text[t]) => {
+                    p += 1;
+                    t += 1;
+                }
+                _ => match backtrack {
+                    // Let the star swallow one more character and retry
+                    Some((star_p, star_t)) => {
+                        backtrack = Some((star_p, star_t + 1));
+                        p = star_p + 1;
+                        t = star_t + 1;
+                    }
+                    None => return false,
+                },
+            }
+        }
+
+        self.tokens[p..].iter().all(|token| *token == Token::Star)
+    }
+}
+
+impl Token {
+    fn matches(&self, c: char) -> bool {
+        match self {
+            Token::Char(expected) => *expected == c,
+            Token::Any => true,
+            Token::Star => false,
+            Token::Class { negated, ranges } => {
+                ranges
+                    .iter()
+                    .any(|&(start, end)| (start..=end).contains(&c))
+                    != *negated
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn matches(pattern: &str, text: &str) -> bool {
+        Glob::new(pattern).unwrap().is_match(text)
+    }
+
+    #[test]
+    fn test_is_glob() {
+        assert!(Glob::is_glob("try_*"));
+        assert!(Glob::is_glob("[ab]c"));
+        assert!(!Glob::is_glob("read_to_string"));
+    }
+
+    #[test]
+    fn test_star_and_question_mark() {
+        assert!(matches("try_*", "try_from"));
+        assert!(matches("try_*", "try_"));
+        assert!(!matches("try_*", "retry_from"));
+        assert!(matches("*_mut", "as_mut"));
+        assert!(matches("*to*string*", "read_to_string"));
+        assert!(matches("u?", "u8"));
+        assert!(!matches("u?", "u16"));
+        assert!(matches("**", ""));
+    }
+
+    #[test]
+    fn test_character_classes() {
+        assert!(matches("[iu]8", "i8"));
+        assert!(matches("[iu]8", "u8"));
+        assert!(!matches("[iu]8", "f8"));
+        assert!(matches("[a-c]x", "bx"));
+        assert!(matches("[!a-c]x", "dx"));
+        assert!(!matches("[^a-c]x", "ax"));
+        assert!(matches("[]]", "]"));
+        assert!(matches("[a-]", "-"));
+        assert_eq!(Glob::new("[ab"), Err(GlobError::UnclosedClass));
+    }
+}
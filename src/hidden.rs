@@ -0,0 +1,216 @@
+// Internal and generated items left out of results unless asked for
+
+use serde::Deserialize;
+use std::fmt;
+use std::str::FromStr;
+
+use crate::glob::{Glob, GlobError};
+use crate::search_index::ItemType;
+use crate::search_items::SearchItem;
+
+/// Modules hidden by default: any with a segment starting with `_`, such as
+/// macro support modules (`serde::__private`, `_serde`) and the modules derive
+/// macros generate.
+pub const DEFAULT_HIDDEN_MODULES: &[&str] = &["_*", "*::_*"];
+
+/// One of the built-in rules for leaving noise out of results, each of which
+/// can be turned off on its own.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum HideRule {
+    /// `use` entries, which duplicate the items they re-export
+    Imports,
+    /// `impl` entries, which duplicate the types and traits they're on
+    Impls,
+    /// Items in modules crates hide with `#[doc(hidden)]` by convention,
+    /// those [starting with `_`](DEFAULT_HIDDEN_MODULES), and the `__` macros
+    /// exported for public macros to expand to
+    HiddenModules,
+    /// Items of the `*_internal`, `*_internals` and `*_impl` crates proc
+    /// macros are implemented in, which their facade crates re-export
+    ProcMacroInternals,
+}
+
+impl HideRule {
+    /// Every rule, in the order they're listed
+    pub const ALL: [HideRule; 4] = [
+        HideRule::Imports,
+        HideRule::Impls,
+        HideRule::HiddenModules,
+        HideRule::ProcMacroInternals,
+    ];
+
+    /// What the rule leaves out, for `rules list`
+    pub fn description(self) -> &'static str {
+        match self {
+            HideRule::Imports => "`use` entries, which duplicate the items they re-export",
+            HideRule::Impls => "`impl` entries, which duplicate the types and traits they're on",
+            HideRule::HiddenModules => {
+                "items in modules starting with `_` (`__private`, `_serde`) and `__` macros"
+            }
+            HideRule::ProcMacroInternals => {
+                "items of proc-macro implementation crates (`*_internal`, `*_internals`, `*_impl`)"
+            }
+        }
+    }
+
+    /// Whether the rule, with `modules` as the hidden modules, leaves `item` out.
+    fn matches(self, item: &SearchItem, modules: &[Glob]) -> bool {
+        match self {
+            HideRule::Imports => item.item_type == ItemType::Import,
+            HideRule::Impls => item.item_type == ItemType::Impl,
+            HideRule::HiddenModules => {
+                // The module and each one it's in
+                let mut within = item
+                    .path
+                    .match_indices("::")
+                    .map(|(end, _)| &item.path[..end])
+                    .chain([item.path.as_str()]);
+                (item.item_type == ItemType::Macro && item.name.starts_with("__"))
+                    || within.any(|module| modules.iter().any(|glob| glob.is_match(module)))
+            }
+            HideRule::ProcMacroInternals => ["_internal", "_internals", "_impl"]
+                .iter()
+                .any(|suffix| item.crate_name.ends_with(suffix)),
+        }
+    }
+}
+
+impl FromStr for HideRule {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        HideRule::ALL
+            .into_iter()
+            .find(|rule| rule.to_string() == s)
+            .ok_or_else(|| {
+                let names: Vec<String> = HideRule::ALL.iter().map(ToString::to_string).collect();
+                format!(
+                    "unknown rule \"{}\" (expected one of {})",
+                    s,
+                    names.join(", ")
+                )
+            })
+    }
+}
+
+impl fmt::Display for HideRule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            HideRule::Imports => "imports",
+            HideRule::Impls => "impls",
+            HideRule::HiddenModules => "hidden-modules",
+            HideRule::ProcMacroInternals => "proc-macro-internals",
+        })
+    }
+}
+
+/// Recognizes items that are rustdoc-internal or generated rather than part
+/// of a crate's API, by the [rules](HideRule) left on.
+#[derive(Debug, Clone)]
+pub struct HiddenItems {
+    /// The patterns `modules` were made from, as given
+    module_patterns: Vec<String>,
+    modules: Vec<Glob>,
+    disabled: Vec<HideRule>,
+}
+
+impl HiddenItems {
+    /// Hide the items in modules matching `modules` (globs matched against
+    /// the module path an item is documented at, and everything under it)
+    /// instead of the default ones.
+    pub fn with_modules(modules: &[String]) -> Result<Self, GlobError> {
+        Ok(Self {
+            module_patterns: modules.to_vec(),
+            modules: modules
+                .iter()
+                .map(|module| Glob::new(module.trim_end_matches("::")))
+                .collect::<Result<_, _>>()?,
+            disabled: Vec::new(),
+        })
+    }
+
+    /// The same, with `rules` turned off.
+    pub fn without(mut self, rules: &[HideRule]) -> Self {
+        for rule in rules {
+            if !self.disabled.contains(rule) {
+                self.disabled.push(*rule);
+            }
+        }
+        self
+    }
+
+    /// Whether `rule` is on.
+    pub fn is_enabled(&self, rule: HideRule) -> bool {
+        !self.disabled.contains(&rule)
+    }
+
+    /// The module globs [`HideRule::HiddenModules`] hides.
+    pub fn modules(&self) -> &[String] {
+        &self.module_patterns
+    }
+
+    /// The first rule that's on and hides `item`, if any.
+    pub fn rule_for(&self, item: &SearchItem) -> Option<HideRule> {
+        HideRule::ALL
+            .into_iter()
+            .find(|&rule| self.is_enabled(rule) && rule.matches(item, &self.modules))
+    }
+
+    /// Whether `item` is hidden.
+    pub fn hides(&self, item: &SearchItem) -> bool {
+        self.rule_for(item).is_some()
+    }
+}
+
+impl Default for HiddenItems {
+    fn default() -> Self {
+        let modules: Vec<String> = DEFAULT_HIDDEN_MODULES
+            .iter()
+            .map(|module| module.to_string())
+            .collect();
+        Self::with_modules(&modules).expect("the default hidden modules are valid globs")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn item(path: &str, name: &str, item_type: ItemType) -> SearchItem {
+        SearchItem::builder("serde", item_type, name)
+            .path(path)
+            .build()
+    }
+
+    #[test]
+    fn test_hides() {
+        let hidden = HiddenItems::default();
+        assert!(hidden.hides(&item("serde::__private::de", "Content", ItemType::Enum)));
+        assert!(hidden.hides(&item("_serde", "Serialize", ItemType::Trait)));
+        assert!(hidden.hides(&item("serde", "Serialize", ItemType::Import)));
+        assert!(!hidden.hides(&item("serde::de", "Deserializer", ItemType::Trait)));
+        // Items named with underscores are often real API (libc's `__errno_location`)
+        assert!(!hidden.hides(&item("libc", "__errno_location", ItemType::Function)));
+
+        let custom = HiddenItems::with_modules(&["serde::de".to_string()]).unwrap();
+        assert!(custom.hides(&item("serde::de::value", "Error", ItemType::Struct)));
+        assert!(!custom.hides(&item("serde::__private", "de", ItemType::Module)));
+
+        // Exported support macros, and proc-macro implementation crates
+        assert!(hidden.hides(&item("serde", "__private_serialize", ItemType::Macro)));
+        let mut internal = item("serde_derive_internals", "Ctxt", ItemType::Struct);
+        internal.crate_name = "serde_derive_internals".to_string();
+        assert_eq!(
+            hidden.rule_for(&internal),
+            Some(HideRule::ProcMacroInternals)
+        );
+
+        // Rules turned off one at a time
+        let imports_shown = HiddenItems::default().without(&[HideRule::Imports]);
+        assert!(!imports_shown.hides(&item("serde", "Serialize", ItemType::Import)));
+        assert!(imports_shown.hides(&item("serde", "impl", ItemType::Impl)));
+        assert_eq!("hidden-modules".parse(), Ok(HideRule::HiddenModules));
+        assert!("everything".parse::<HideRule>().is_err());
+    }
+}
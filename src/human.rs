@@ -0,0 +1,98 @@
+// Text for people rather than scripts: counts with their nouns in the right
+// number, and numbers grouped as the user's locale writes them. JSON and the
+// other formats meant for scripts never go through here, so they don't vary.
+
+use std::sync::OnceLock;
+
+/// How the user's locale writes numbers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Locale {
+    /// Between groups of three digits, e.g. `,` in `21,340`; `None` leaves
+    /// numbers ungrouped
+    pub group_separator: Option<char>,
+}
+
+impl Locale {
+    /// Numbers without grouping, as in the C and POSIX locales
+    pub const PLAIN: Self = Self {
+        group_separator: None,
+    };
+
+    /// The locale named by the first of `LC_ALL`, `LC_NUMERIC` and `LANG`
+    /// that's set, or [`Locale::PLAIN`] if none is.
+    pub fn from_env() -> Self {
+        ["LC_ALL", "LC_NUMERIC", "LANG"]
+            .iter()
+            .find_map(|var| std::env::var(var).ok().filter(|name| !name.is_empty()))
+            .map_or(Self::PLAIN, |name| Self::from_name(&name))
+    }
+
+    /// The locale named `name`, e.g. `de_DE.UTF-8`, whose language decides
+    /// how digits are grouped.
+    pub fn from_name(name: &str) -> Self {
+        let language = name.split(['_', '-', '.', '@']).next().unwrap_or_default();
+        let group_separator = match language {
+            "" | "C" | "POSIX" => None,
+            "de" | "es" | "it" | "nl" | "pt" | "da" | "id" | "tr" | "el" => Some('.'),
+            "fr" | "ru" | "pl" | "cs" | "sk" | "sv" | "fi" | "nb" | "no" | "uk" | "hu" => {
+                Some('\u{a0}')
+            }
+            _ => Some(','),
+        };
+        Self { group_separator }
+    }
+
+    /// `n` with its digits grouped, e.g. `21,340`.
+    pub fn number(&self, n: usize) -> String {
+        let digits = n.to_string();
+        let Some(separator) = self.group_separator else {
+            return digits;
+        };
+        let mut grouped = String::with_capacity(digits.len() + digits.len() / 3);
+        for (i, digit) in digits.chars().enumerate() {
+            if i > 0 && (digits.len() - i).is_multiple_of(3) {
+                grouped.push(separator);
+            }
+            grouped.push(digit);
+        }
+        grouped
+    }
+}
+
+/// The environment's locale, read the first time it's needed.
+pub fn locale() -> Locale {
+    static LOCALE: OnceLock<Locale> = OnceLock::new();
+    *LOCALE.get_or_init(Locale::from_env)
+}
+
+/// `n` as the user's locale writes it, e.g. `21,340` in English.
+pub fn number(n: usize) -> String {
+    locale().number(n)
+}
+
+/// `n` and the noun for that many: `count(1, "result", "results")` is
+/// `1 result`. Taking both forms, rather than adding an "s", keeps irregular
+/// plurals right and leaves each phrase whole for translating.
+pub fn count(n: usize, one: &str, many: &str) -> String {
+    format!("{} {}", number(n), if n == 1 { one } else { many })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_numbers_by_locale() {
+        let english = Locale::from_name("en_US.UTF-8");
+        assert_eq!(english.number(21340), "21,340");
+        assert_eq!(english.number(1234567), "1,234,567");
+        assert_eq!(english.number(999), "999");
+        assert_eq!(Locale::from_name("de_DE.UTF-8").number(21340), "21.340");
+        assert_eq!(Locale::from_name("fr_FR").number(21340), "21\u{a0}340");
+        assert_eq!(Locale::from_name("C").number(21340), "21340");
+        assert_eq!(Locale::PLAIN.number(0), "0");
+
+        assert_eq!(count(1, "index", "indices"), "1 index");
+        assert_eq!(count(0, "result", "results"), "0 results");
+    }
+}
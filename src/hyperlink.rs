@@ -0,0 +1,154 @@
+// Clickable item names in terminal output (OSC 8 hyperlinks)
+
+use rdoc::pages::SiteLinks;
+use rdoc::search_items::SearchItem;
+use std::fmt::Display;
+use std::io::IsTerminal;
+use std::path::{Path, PathBuf};
+
+use crate::docs::{self, DocSource};
+
+/// Environment variable forcing hyperlinks on (any value but `0`) or off
+/// (`0`), whatever the terminal; the name other tools use for the same.
+pub const FORCE_HYPERLINK_ENV_VAR: &str = "FORCE_HYPERLINK";
+
+/// Terminals known to support OSC 8, by `TERM_PROGRAM`
+const HYPERLINK_PROGRAMS: &[&str] = &[
+    "Hyper",
+    "iTerm.app",
+    "terminology",
+    "WezTerm",
+    "vscode",
+    "ghostty",
+];
+
+/// ... and by `TERM`
+const HYPERLINK_TERMS: &[&str] = &[
+    "xterm-kitty",
+    "alacritty",
+    "alacritty-direct",
+    "xterm-ghostty",
+];
+
+/// Links item names in the terminal listing to their pages: local HTML files,
+/// or their published or docs.rs pages.
+pub struct Hyperlinks<'a> {
+    source: &'a DocSource,
+    search_index_path: PathBuf,
+    links: SiteLinks,
+}
+
+impl<'a> Hyperlinks<'a> {
+    /// Hyperlinks for results from the index at `search_index_path`.
+    pub fn new(source: &'a DocSource, search_index_path: &Path) -> Self {
+        Self {
+            source,
+            search_index_path: search_index_path.to_path_buf(),
+            links: docs::site_links(source),
+        }
+    }
+
+    /// Hyperlinks for results from the index at `search_index_path`, or `None`
+    /// when stdout isn't a terminal that shows them.
+    pub fn detect(source: &'a DocSource, search_index_path: &Path) -> Option<Self> {
+        enabled().then(|| Self::new(source, search_index_path))
+    }
+
+    /// `text` (typically `item`'s styled name) linked to `item`'s page, or
+    /// unchanged if it has none.
+    pub fn link(&self, text: impl Display, item: &SearchItem) -> String {
+        match docs::page_url(self.source, &self.search_index_path, &self.links, item) {
+            Some(url) => hyperlink(text, &url),
+            None => text.to_string(),
+        }
+    }
+}
+
+/// Whether stdout is a terminal that shows hyperlinks.
+pub fn enabled() -> bool {
+    supports_hyperlinks(
+        |name| std::env::var(name).ok(),
+        std::io::stdout().is_terminal(),
+    )
+}
+
+/// `text` wrapped in the OSC 8 escapes making it a link to `url`.
+pub fn hyperlink(text: impl Display, url: &str) -> String {
+    format!("\x1b]8;;{}\x1b\\{}\x1b]8;;\x1b\\", url, text)
+}
+
+/// Whether to write hyperlinks, given the environment (`var`) and whether
+/// stdout is a terminal. Terminals don't announce OSC 8 support, so this goes
+/// by the ones known to have it; the rest print the escapes invisibly at
+/// best, so they're left out.
+fn supports_hyperlinks(var: impl Fn(&str) -> Option<String>, is_terminal: bool) -> bool {
+    if let Some(force) = var(FORCE_HYPERLINK_ENV_VAR) {
+        return force != "0";
+    }
+    if !is_terminal {
+        return false;
+    }
+    if var("DOMTERM").is_some() || var("WT_SESSION").is_some() || var("KONSOLE_VERSION").is_some() {
+        return true;
+    }
+    // GNOME Terminal and other VTE-based terminals since 0.50
+    if var("VTE_VERSION")
+        .and_then(|version| version.parse::<u32>().ok())
+        .is_some_and(|version| version >= 5000)
+    {
+        return true;
+    }
+    var("TERM_PROGRAM").is_some_and(|program| HYPERLINK_PROGRAMS.contains(&program.as_str()))
+        || var("TERM").is_some_and(|term| {
+            HYPERLINK_TERMS.contains(&term.as_str()) || term.starts_with("foot")
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn env<'a>(vars: &'a [(&str, &str)]) -> impl Fn(&str) -> Option<String> + 'a {
+        move |name| {
+            vars.iter()
+                .find(|(var, _)| *var == name)
+                .map(|(_, value)| value.to_string())
+        }
+    }
+
+    #[test]
+    fn test_supports_hyperlinks() {
+        assert!(supports_hyperlinks(
+            env(&[("TERM_PROGRAM", "WezTerm")]),
+            true
+        ));
+        assert!(supports_hyperlinks(env(&[("VTE_VERSION", "7600")]), true));
+        assert!(!supports_hyperlinks(env(&[("VTE_VERSION", "4600")]), true));
+        assert!(supports_hyperlinks(env(&[("TERM", "foot-extra")]), true));
+        assert!(!supports_hyperlinks(
+            env(&[("TERM", "xterm-256color")]),
+            true
+        ));
+        // Never into pipes, unless forced either way
+        assert!(!supports_hyperlinks(
+            env(&[("TERM_PROGRAM", "WezTerm")]),
+            false
+        ));
+        assert!(supports_hyperlinks(
+            env(&[(FORCE_HYPERLINK_ENV_VAR, "1")]),
+            false
+        ));
+        assert!(!supports_hyperlinks(
+            env(&[(FORCE_HYPERLINK_ENV_VAR, "0"), ("TERM_PROGRAM", "WezTerm")]),
+            true
+        ));
+    }
+
+    #[test]
+    fn test_hyperlink() {
+        assert_eq!(
+            hyperlink("Serialize", "file:///doc/serde/trait.Serialize.html"),
+            "\x1b]8;;file:///doc/serde/trait.Serialize.html\x1b\\Serialize\x1b]8;;\x1b\\"
+        );
+    }
+}
@@ -0,0 +1,217 @@
+// Crates and paths left out of every search and report, from `.rdocignore`
+
+use crate::glob::{Glob, GlobError};
+use crate::normalize::{crate_name, crate_path};
+use crate::search_items::SearchItem;
+use std::fmt;
+
+/// Rules in `.rdocignore` format: one pattern per line, `#` comments and
+/// blank lines skipped, like `.gitignore`.
+///
+/// A pattern without `::` is a crate name (`windows_*`); one with `::` is a
+/// path prefix (`serde::__private`, `*::sealed`), ignoring everything at or
+/// below it. Patterns may be globs. A leading `!` brings back what an earlier
+/// rule ignored; as in `.gitignore`, the last rule matching decides, and
+/// nothing inside an ignored crate can be brought back.
+#[derive(Debug, Clone, Default, Hash)]
+pub struct IgnoreRules {
+    rules: Vec<Rule>,
+}
+
+#[derive(Debug, Clone, Hash)]
+struct Rule {
+    pattern: Glob,
+    /// Whether the pattern is a path prefix rather than a crate name
+    is_path: bool,
+    /// `!pattern`: un-ignore
+    negated: bool,
+}
+
+impl Rule {
+    /// The glob for `pattern`, with crates named as rustdoc documents them
+    /// (`serde-json` is `serde_json`).
+    fn glob(pattern: &str, is_path: bool) -> Result<Glob, GlobError> {
+        if is_path {
+            Glob::new(&crate_path(pattern))
+        } else {
+            Glob::new(&crate_name(pattern))
+        }
+    }
+}
+
+/// Why a line of an ignore file couldn't be read.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IgnoreError {
+    /// The line, counting from 1
+    pub line: usize,
+    pub error: GlobError,
+}
+
+impl fmt::Display for IgnoreError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.error)
+    }
+}
+
+impl std::error::Error for IgnoreError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        Some(&self.error)
+    }
+}
+
+impl IgnoreRules {
+    pub fn parse(text: &str) -> Result<Self, IgnoreError> {
+        let mut rules = Vec::new();
+        for (index, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let (negated, pattern) = match line.strip_prefix('!') {
+                Some(pattern) => (true, pattern.trim_start()),
+                None => (false, line),
+            };
+            // `serde::__private::` means the same as `serde::__private`
+            let pattern = pattern.trim_end_matches("::");
+            let is_path = pattern.contains("::");
+            rules.push(Rule {
+                pattern: Rule::glob(pattern, is_path).map_err(|error| IgnoreError {
+                    line: index + 1,
+                    error,
+                })?,
+                is_path,
+                negated,
+            });
+        }
+        Ok(Self { rules })
+    }
+
+    /// Rules ignoring the crates named by `crates` and everything at or below
+    /// the paths `paths` name, each possibly a glob: filters given on the
+    /// command line rather than in a file.
+    pub fn excluding(crates: &[String], paths: &[String]) -> Result<Self, GlobError> {
+        let mut rules = Vec::new();
+        for (patterns, is_path) in [(crates, false), (paths, true)] {
+            for pattern in patterns {
+                rules.push(Rule {
+                    pattern: Rule::glob(pattern.trim_end_matches("::"), is_path)?,
+                    is_path,
+                    negated: false,
+                });
+            }
+        }
+        Ok(Self { rules })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rules.is_empty()
+    }
+
+    /// Whether the whole crate `name` is ignored.
+    pub fn ignores_crate(&self, name: &str) -> bool {
+        self.decide(|rule| !rule.is_path && rule.pattern.is_match(name))
+    }
+
+    /// Whether `item` is ignored, by its crate or its path.
+    pub fn ignores_item(&self, item: &SearchItem) -> bool {
+        if self.ignores_crate(&item.crate_name) {
+            return true;
+        }
+        // Methods and fields are under their type
+        let full_path = match item.parent_name() {
+            Some(parent) => format!("{}::{}::{}", item.path, parent, item.name),
+            None => format!("{}::{}", item.path, item.name),
+        };
+        // Each prefix ending at a `::`, and the whole path
+        let prefixes: Vec<&str> = full_path
+            .match_indices("::")
+            .map(|(end, _)| &full_path[..end])
+            .chain([full_path.as_str()])
+            .collect();
+        self.decide(|rule| {
+            rule.is_path && prefixes.iter().any(|prefix| rule.pattern.is_match(prefix))
+        })
+    }
+
+    /// Whether the last rule `matches` accepts ignores (rather than un-ignores).
+    fn decide(&self, matches: impl Fn(&Rule) -> bool) -> bool {
+        self.rules
+            .iter()
+            .rev()
+            .find(|rule| matches(rule))
+            .is_some_and(|rule| !rule.negated)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::search_index::ItemType;
+
+    fn item(crate_name: &str, path: &str, name: &str) -> SearchItem {
+        SearchItem::builder(crate_name, ItemType::Struct, name)
+            .path(path)
+            .build()
+    }
+
+    #[test]
+    fn test_ignore_rules() {
+        let rules = IgnoreRules::parse(
+            "# Platform bindings\n\
+             windows_*\n\
+             !windows_core\n\
+             \n\
+             serde::__private::\n\
+             *::sealed\n\
+             tokio::runtime\n\
+             !tokio::runtime::Builder\n",
+        )
+        .unwrap();
+
+        assert!(rules.ignores_crate("windows_sys"));
+        assert!(!rules.ignores_crate("windows_core"));
+        assert!(!rules.ignores_crate("serde"));
+        assert!(rules.ignores_item(&item("windows_sys", "windows_sys::Win32", "HANDLE")));
+
+        assert!(rules.ignores_item(&item("serde", "serde::__private::de", "Content")));
+        assert!(rules.ignores_item(&item("serde", "serde::__private", "de")));
+        assert!(!rules.ignores_item(&item("serde", "serde::de", "Deserializer")));
+        // Prefixes end at a `::`, not in the middle of a name
+        assert!(!rules.ignores_item(&item("serde", "serde", "__private_helper")));
+        assert!(rules.ignores_item(&item("http", "http::header::sealed", "Sealed")));
+
+        assert!(rules.ignores_item(&item("tokio", "tokio::runtime", "Handle")));
+        assert!(!rules.ignores_item(&item("tokio", "tokio::runtime", "Builder")));
+        let method = |parent: &str, name: &str| {
+            SearchItem::builder("tokio", ItemType::Method, name)
+                .path("tokio::runtime")
+                .parent(parent, ItemType::Struct)
+                .build()
+        };
+        assert!(!rules.ignores_item(&method("Builder", "build")));
+        assert!(rules.ignores_item(&method("Runtime", "block_on")));
+    }
+
+    #[test]
+    fn test_excluding() {
+        let rules = IgnoreRules::excluding(
+            &["syn".to_string(), "serde-json".to_string()],
+            &["*::__private::*".to_string(), "serde::de".to_string()],
+        )
+        .unwrap();
+        assert!(rules.ignores_item(&item("syn", "syn", "Ident")));
+        assert!(rules.ignores_item(&item("serde_json", "serde_json", "Value")));
+        assert!(rules.ignores_item(&item("serde", "serde::__private::de", "Content")));
+        assert!(rules.ignores_item(&item("serde", "serde::de::value", "Error")));
+        assert!(!rules.ignores_item(&item("serde", "serde::ser", "Serializer")));
+        assert!(IgnoreRules::excluding(&["syn[".to_string()], &[]).is_err());
+    }
+
+    #[test]
+    fn test_invalid_pattern() {
+        let err = IgnoreRules::parse("either\nserde_[json\n").unwrap_err();
+        assert_eq!(err.line, 2);
+        assert_eq!(err.to_string(), "line 2: unclosed character class");
+        assert!(IgnoreRules::parse("").unwrap().is_empty());
+    }
+}
@@ -0,0 +1,230 @@
+// Trait implementors from rustdoc's `trait.impl/` (formerly `implementors/`) files
+
+use crate::descriptions::plain_text;
+use crate::search_items::SearchItem;
+use std::fmt;
+
+/// One implementation of a trait.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Implementor {
+    /// The crate the implementation is in
+    pub crate_name: String,
+    /// The impl header as text, e.g. `impl Serialize for Value`
+    pub declaration: String,
+    /// Whether the compiler derived the implementation, as for auto traits
+    /// like `Send` or blanket impls
+    pub synthetic: bool,
+}
+
+/// Error returned when an implementors file can't be parsed.
+#[derive(Debug)]
+pub enum ImplementorsError {
+    /// No implementors data was found in the file
+    MissingData,
+    /// The data isn't valid JSON
+    Json(serde_json::Error),
+    /// An entry doesn't have the expected shape
+    Malformed(String),
+}
+
+impl fmt::Display for ImplementorsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ImplementorsError::MissingData => write!(f, "no implementors data found"),
+            ImplementorsError::Json(err) => write!(f, "invalid implementors JSON: {}", err),
+            ImplementorsError::Malformed(crate_name) => {
+                write!(f, "malformed implementors entry for crate {}", crate_name)
+            }
+        }
+    }
+}
+
+impl std::error::Error for ImplementorsError {}
+
+/// Where rustdoc may have written the implementors of `trait_item` from other
+/// crates, relative to the documentation root: `trait.impl/` since Rust 1.78,
+/// `implementors/` before.
+///
+/// The files are named after where the trait is defined, so
+/// `serde::Serialize` is under `serde_core/ser/`.
+pub fn implementors_files(trait_item: &SearchItem) -> Vec<String> {
+    let directory = trait_item.exact_path.replace("::", "/");
+    ["trait.impl", "implementors"]
+        .into_iter()
+        .map(|root| format!("{}/{}/trait.{}.js", root, directory, trait_item.name))
+        .collect()
+}
+
+/// Parse an implementors file.
+///
+/// Recent rustdoc writes `Object.fromEntries([[crate, [entry, ...]], ...])`,
+/// older versions an object literal `{"crate": [entry, ...], ...}`. Each entry
+/// is an array starting with the impl header as HTML; synthetic impls end with
+/// `1` and the implementing types.
+pub fn parse_implementors(js: &str) -> Result<Vec<Implementor>, ImplementorsError> {
+    let crates: Vec<(String, serde_json::Value)> =
+        if let Some(start) = js.find("Object.fromEntries(") {
+            first_json_value(&js[start + "Object.fromEntries(".len()..])?
+        } else {
+            let start = js
+                .find("implementors = ")
+                .ok_or(ImplementorsError::MissingData)?;
+            let map: serde_json::Map<String, serde_json::Value> =
+                first_json_value(&js[start + "implementors = ".len()..])?;
+            map.into_iter().collect()
+        };
+
+    let mut implementors = Vec::new();
+    for (crate_name, entries) in crates {
+        let entries = entries
+            .as_array()
+            .ok_or_else(|| ImplementorsError::Malformed(crate_name.clone()))?;
+        for entry in entries {
+            let fields = entry
+                .as_array()
+                .ok_or_else(|| ImplementorsError::Malformed(crate_name.clone()))?;
+            let Some(html) = fields.first().and_then(|field| field.as_str()) else {
+                return Err(ImplementorsError::Malformed(crate_name));
+            };
+            implementors.push(Implementor {
+                crate_name: crate_name.clone(),
+                declaration: header_text(html),
+                // `[html, negative, 1, types]` now, `[html, 1, types]` before
+                // negative impls were marked
+                synthetic: fields.last().is_some_and(|field| field.is_array()),
+            });
+        }
+    }
+    Ok(implementors)
+}
+
+/// Implementations in the trait's own crate, which rustdoc writes into the
+/// trait's page rather than an implementors file.
+pub fn local_implementors(html: &str, crate_name: &str) -> Vec<Implementor> {
+    let mut implementors = Vec::new();
+    for (list, synthetic) in [
+        ("id=\"implementors-list\"", false),
+        ("id=\"synthetic-implementors-list\"", true),
+    ] {
+        let Some(start) = html.find(list) else {
+            continue;
+        };
+        let section = &html[start..];
+        // The list ends where the next one (or the page's scripts) begin
+        let end = ["id=\"synthetic-implementors", "<script"]
+            .iter()
+            .filter_map(|marker| section[1..].find(marker).map(|end| end + 1))
+            .min()
+            .unwrap_or(section.len());
+
+        let mut rest = &section[..end];
+        while let Some(header) = rest.find("class=\"code-header\"") {
+            rest = &rest[header..];
+            let Some(start) = rest.find('>') else {
+                break;
+            };
+            let Some(end) = rest.find("</h3>") else {
+                break;
+            };
+            implementors.push(Implementor {
+                crate_name: crate_name.to_string(),
+                declaration: header_text(&rest[start + 1..end]),
+                synthetic,
+            });
+            rest = &rest[end..];
+        }
+    }
+    implementors
+}
+
+/// Deserialize the JSON value at the start of `text`, ignoring what follows it.
+fn first_json_value<T: serde::de::DeserializeOwned>(text: &str) -> Result<T, ImplementorsError> {
+    serde_json::Deserializer::from_str(text)
+        .into_iter()
+        .next()
+        .ok_or(ImplementorsError::MissingData)?
+        .map_err(ImplementorsError::Json)
+}
+
+/// An impl header's HTML as text on one line.
+fn header_text(html: &str) -> String {
+    let text = plain_text(&html.replace("&nbsp;", " "));
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_current_format() {
+        // trait.impl/core/marker/trait.Send.js for tests/fixture-crate, rustdoc 1.95
+        let js = r#"(function() {
+    const implementors = Object.fromEntries([["fixture_crate",[["impl <a class=\"trait\" href=\"https://doc.rust-lang.org/1.95.0/core/marker/trait.Send.html\" title=\"trait core::marker::Send\">Send</a> for <a class=\"enum\" href=\"fixture_crate/enum.Direction.html\" title=\"enum fixture_crate::Direction\">Direction</a>",0,1,["fixture_crate::Direction"]]]],["other",[["impl&lt;T&gt; Send for <a>Wrapper</a>&lt;T&gt;",0]]]]);
+    if (window.register_implementors) {
+        window.register_implementors(implementors);
+    } else {
+        window.pending_implementors = implementors;
+    }
+})()
+//{"start":59,"fragment_lengths":[908]}"#;
+
+        let implementors = parse_implementors(js).unwrap();
+        assert_eq!(
+            implementors,
+            vec![
+                Implementor {
+                    crate_name: "fixture_crate".to_string(),
+                    declaration: "impl Send for Direction".to_string(),
+                    synthetic: true,
+                },
+                Implementor {
+                    crate_name: "other".to_string(),
+                    declaration: "impl<T> Send for Wrapper<T>".to_string(),
+                    synthetic: false,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_object_format() {
+        let js = r#"(function() {var implementors = {
+"serde_json":[["impl <a>Serialize</a> for <a>Value</a>"],["impl Serialize for <a>Map</a>&lt;String, Value&gt;"]]};
+if (window.register_implementors) {window.register_implementors(implementors);} else {window.pending_implementors = implementors;}})()"#;
+
+        let implementors = parse_implementors(js).unwrap();
+        assert_eq!(implementors.len(), 2);
+        assert_eq!(implementors[0].declaration, "impl Serialize for Value");
+        assert_eq!(
+            implementors[1].declaration,
+            "impl Serialize for Map<String, Value>"
+        );
+        assert!(
+            implementors
+                .iter()
+                .all(|imp| imp.crate_name == "serde_json")
+        );
+
+        assert!(matches!(
+            parse_implementors("console.log(1)"),
+            Err(ImplementorsError::MissingData)
+        ));
+        assert!(matches!(
+            parse_implementors("var implementors = {\"a\": 1};"),
+            Err(ImplementorsError::Malformed(_))
+        ));
+    }
+
+    #[test]
+    fn test_local_implementors() {
+        let html = r##"<h2 id="implementors" class="section-header">Implementors</h2><div id="implementors-list"><details class="toggle implementors-toggle"><summary><section id="impl-Draw-for-Circle" class="impl"><a href="#impl-Draw-for-Circle" class="anchor">§</a><h3 class="code-header">impl <a class="trait">Draw</a> for <a class="struct">Circle</a></h3></section></summary><div class="impl-items"><section id="method.draw" class="method trait-impl"><h4 class="code-header">fn <a>draw</a>(&amp;self) -&gt; String</h4></section></div></details></div><h2 id="synthetic-implementors" class="section-header">Auto implementors</h2><div id="synthetic-implementors-list"><section id="impl-Send-for-Circle" class="impl"><h3 class="code-header">impl Send for <a>Circle</a></h3></section></div><script src="../trait.impl/fixture_crate/trait.Draw.js" async></script>"##;
+
+        let implementors = local_implementors(html, "fixture_crate");
+        assert_eq!(implementors.len(), 2);
+        assert_eq!(implementors[0].declaration, "impl Draw for Circle");
+        assert!(!implementors[0].synthetic);
+        assert_eq!(implementors[1].declaration, "impl Send for Circle");
+        assert!(implementors[1].synthetic);
+    }
+}
@@ -0,0 +1,180 @@
+// Memory-mapped (or read) access to search-index.js
+
+use crate::search_index::extract_json_string;
+use memmap2::Mmap;
+use std::borrow::Cow;
+use std::fs::File;
+use std::io;
+use std::ops::{Deref, Range};
+use std::path::Path;
+
+/// A search-index.js file mapped into memory, or read into a buffer.
+///
+/// Large workspaces produce index files of tens of megabytes; mapping the file
+/// avoids reading it into a heap buffer up front and lets the parser borrow
+/// directly from the page cache. That's only safe for a moment, though (see
+/// [`IndexFile::open`]), so processes that keep an index loaded use
+/// [`IndexFile::read`].
+pub struct IndexFile {
+    bytes: Bytes,
+}
+
+enum Bytes {
+    Mapped(Mmap),
+    Read(Vec<u8>),
+}
+
+impl IndexFile {
+    /// Map the file at `path` into memory, for one-shot commands that are
+    /// done with it before docs are likely to be regenerated.
+    pub fn open(path: &Path) -> io::Result<Self> {
+        let file = File::open(path)?;
+
+        // SAFETY: the mapping is read-only, but rustdoc writes search-index.js
+        // with `fs::write`, truncating and rewriting the same file in place. A
+        // `cargo doc` running while the file is mapped can shrink it under the
+        // mapping, and reading past its new end raises SIGBUS (or reads a
+        // half-written index). One-shot commands accept that for the moment
+        // they read the index; anything long-running must use `read` instead.
+        let mmap = unsafe { Mmap::map(&file)? };
+
+        Ok(Self {
+            bytes: Bytes::Mapped(mmap),
+        })
+    }
+
+    /// Read the file at `path` into memory, for processes that keep the
+    /// index for long (`serve`, `repl`), which docs can be regenerated under.
+    pub fn read(path: &Path) -> io::Result<Self> {
+        Ok(Self {
+            bytes: Bytes::Read(std::fs::read(path)?),
+        })
+    }
+
+    /// Whether the file is mapped rather than read into a buffer.
+    pub fn is_mapped(&self) -> bool {
+        matches!(self.bytes, Bytes::Mapped(_))
+    }
+
+    /// Size of the file in bytes.
+    pub fn len(&self) -> usize {
+        self.as_bytes().len()
+    }
+
+    /// Returns true if the file is empty.
+    pub fn is_empty(&self) -> bool {
+        self.as_bytes().is_empty()
+    }
+
+    /// The file contents as UTF-8 text, borrowed from the mapping or buffer.
+    pub fn contents(&self) -> io::Result<&str> {
+        std::str::from_utf8(self.as_bytes())
+            .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))
+    }
+
+    fn as_bytes(&self) -> &[u8] {
+        match &self.bytes {
+            Bytes::Mapped(mmap) => mmap,
+            Bytes::Read(bytes) => bytes,
+        }
+    }
+}
+
+/// The JSON a search-index.js embeds, ready to parse.
+///
+/// Indexes without escapes (most of them) are parsed straight from the
+/// file's bytes without copying the JSON; only indexes with escapes get an
+/// unescaped copy.
+pub struct IndexJson {
+    file: IndexFile,
+    json: Json,
+}
+
+enum Json {
+    /// The JSON's bytes in the file, already checked to be UTF-8
+    Borrowed(Range<usize>),
+    Unescaped(String),
+}
+
+impl IndexJson {
+    /// Extract the JSON from `file`, as [`extract_json_string`] does. Files
+    /// that aren't UTF-8 or don't wrap the JSON as rustdoc does are
+    /// [`io::ErrorKind::InvalidData`].
+    pub fn extract(file: IndexFile) -> io::Result<Self> {
+        let contents = file.contents()?;
+        let json = match extract_json_string(contents)
+            .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?
+        {
+            Cow::Borrowed(json) => {
+                let start = json.as_ptr() as usize - contents.as_ptr() as usize;
+                Json::Borrowed(start..start + json.len())
+            }
+            Cow::Owned(json) => Json::Unescaped(json),
+        };
+        Ok(Self { file, json })
+    }
+
+    /// Whether the JSON is borrowed from the file rather than an unescaped copy.
+    pub fn is_borrowed(&self) -> bool {
+        matches!(self.json, Json::Borrowed(_))
+    }
+}
+
+impl Deref for IndexJson {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        match &self.json {
+            // SAFETY: the range is a slice of the file's contents, which
+            // `extract` checked are UTF-8 (see `IndexFile::open` for when a
+            // mapping can change under us)
+            Json::Borrowed(range) => unsafe {
+                std::str::from_utf8_unchecked(&self.file.as_bytes()[range.clone()])
+            },
+            Json::Unescaped(json) => json,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_open_maps_file_contents() {
+        let path = Path::new("tests/fixtures/search-index.js");
+        let index_file = IndexFile::open(path).expect("Failed to map fixture");
+        let expected = std::fs::read_to_string(path).expect("Failed to read fixture");
+
+        assert_eq!(index_file.len(), expected.len());
+        assert_eq!(index_file.contents().unwrap(), expected);
+        assert!(index_file.is_mapped());
+    }
+
+    #[test]
+    fn test_read_copies_file_contents() {
+        let path = Path::new("tests/fixtures/search-index.js");
+        let index_file = IndexFile::read(path).unwrap();
+        let expected = std::fs::read_to_string(path).unwrap();
+
+        assert!(!index_file.is_mapped());
+        assert_eq!(index_file.contents().unwrap(), expected);
+        let json = IndexJson::extract(index_file).unwrap();
+        assert_eq!(&*json, extract_json_string(&expected).unwrap());
+    }
+
+    #[test]
+    fn test_index_json() {
+        let path = Path::new("tests/fixtures/search-index.js");
+        let json = IndexJson::extract(IndexFile::open(path).unwrap()).unwrap();
+        let expected = std::fs::read_to_string(path).unwrap();
+        assert_eq!(&*json, extract_json_string(&expected).unwrap());
+        // The fixture has escaped quotes in impl disambiguators
+        assert!(!json.is_borrowed());
+    }
+
+    #[test]
+    fn test_open_missing_file_errors() {
+        assert!(IndexFile::open(Path::new("tests/fixtures/does-not-exist.js")).is_err());
+    }
+}
@@ -0,0 +1,201 @@
+// Globally unique identifiers for search index items
+
+use crate::normalize;
+use std::fmt;
+use std::str::FromStr;
+use std::sync::Arc;
+
+/// Identifies an item across every index rdoc has loaded.
+///
+/// An item's position in its crate's compact data is only unique within that
+/// crate, and the same crate can appear in several indexes (the local project
+/// and hosted sites), so the ID also records the crate and where the index
+/// came from. It is written as `crate#index@source`, e.g.
+/// `serde#142@local` or `serde#142@https://docs.rs/serde/1.0.219/`.
+///
+/// IDs are only stable for the same search index: docs regenerated from
+/// changed sources, or by another rustdoc, can number a crate's items
+/// differently, so a stored ID may then name another item or none.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct ItemId {
+    /// The index the item came from: [`ItemId::LOCAL_SOURCE`] or a site's base URL
+    pub source: Arc<str>,
+    /// The crate containing the item
+    pub crate_name: Arc<str>,
+    /// The item's position in the crate's compact data
+    pub index: usize,
+}
+
+/// Why a string could not be parsed as an [`ItemId`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ItemIdError {
+    /// The string isn't shaped like `crate#index@source`
+    Malformed,
+    /// The part between `#` and `@` isn't a number
+    InvalidIndex(String),
+    /// The string is neither an [`ItemId`] nor `crate:index`
+    MalformedRef,
+}
+
+impl fmt::Display for ItemIdError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ItemIdError::Malformed => write!(f, "expected an item ID like serde#142@local"),
+            ItemIdError::InvalidIndex(index) => write!(f, "\"{}\" is not an item index", index),
+            ItemIdError::MalformedRef => {
+                write!(f, "expected an item like serde:142 or serde#142@local")
+            }
+        }
+    }
+}
+
+impl std::error::Error for ItemIdError {}
+
+impl ItemId {
+    /// Source of items from the current project's `target/doc`.
+    pub const LOCAL_SOURCE: &str = "local";
+
+    pub fn new(source: impl Into<Arc<str>>, crate_name: impl Into<Arc<str>>, index: usize) -> Self {
+        Self {
+            source: source.into(),
+            crate_name: crate_name.into(),
+            index,
+        }
+    }
+}
+
+impl fmt::Display for ItemId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}#{}@{}", self.crate_name, self.index, self.source)
+    }
+}
+
+impl FromStr for ItemId {
+    type Err = ItemIdError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        // Crate names can't contain `#` or `@`, but URLs in the source can
+        let (crate_name, rest) = s.split_once('#').ok_or(ItemIdError::Malformed)?;
+        let (index, source) = rest.split_once('@').ok_or(ItemIdError::Malformed)?;
+        if crate_name.is_empty() || source.is_empty() {
+            return Err(ItemIdError::Malformed);
+        }
+        let index = index
+            .parse()
+            .map_err(|_| ItemIdError::InvalidIndex(index.to_string()))?;
+        Ok(Self::new(source, crate_name, index))
+    }
+}
+
+/// An item as `show --id` takes it: `crate:index` for an item in the docs
+/// being read, or a whole [`ItemId`], which must be from them.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ItemRef {
+    pub crate_name: String,
+    pub index: usize,
+    /// The source an [`ItemId`] named, if it was one
+    pub source: Option<String>,
+}
+
+impl ItemRef {
+    /// Whether `id` is the item referred to.
+    pub fn matches(&self, id: &ItemId) -> bool {
+        self.source
+            .as_deref()
+            .is_none_or(|source| source == &*id.source)
+            && *id.crate_name == self.crate_name
+            && id.index == self.index
+    }
+}
+
+impl fmt::Display for ItemRef {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.source {
+            Some(source) => write!(f, "{}#{}@{}", self.crate_name, self.index, source),
+            None => write!(f, "{}:{}", self.crate_name, self.index),
+        }
+    }
+}
+
+impl FromStr for ItemRef {
+    type Err = ItemIdError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.contains('#') {
+            let id: ItemId = s.parse()?;
+            return Ok(Self {
+                crate_name: id.crate_name.to_string(),
+                index: id.index,
+                source: Some(id.source.to_string()),
+            });
+        }
+        let (crate_name, index) = s.split_once(':').ok_or(ItemIdError::MalformedRef)?;
+        if crate_name.is_empty() {
+            return Err(ItemIdError::MalformedRef);
+        }
+        let index = index
+            .parse()
+            .map_err(|_| ItemIdError::InvalidIndex(index.to_string()))?;
+        Ok(Self {
+            crate_name: normalize::crate_name(crate_name).into_owned(),
+            index,
+            source: None,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_item_id_round_trip() {
+        for id in [
+            ItemId::new(ItemId::LOCAL_SOURCE, "serde", 142),
+            ItemId::new("https://user@docs.example.com/api/", "mylib", 0),
+        ] {
+            assert_eq!(id.to_string().parse::<ItemId>().unwrap(), id);
+        }
+        assert_eq!(
+            ItemId::new("local", "serde", 142).to_string(),
+            "serde#142@local"
+        );
+    }
+
+    #[test]
+    fn test_item_id_parse_errors() {
+        assert_eq!("serde".parse::<ItemId>(), Err(ItemIdError::Malformed));
+        assert_eq!("serde#142".parse::<ItemId>(), Err(ItemIdError::Malformed));
+        assert_eq!("#1@local".parse::<ItemId>(), Err(ItemIdError::Malformed));
+        assert_eq!(
+            "serde#x@local".parse::<ItemId>(),
+            Err(ItemIdError::InvalidIndex("x".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_item_ref() {
+        let short: ItemRef = "serde:142".parse().unwrap();
+        assert_eq!(short.to_string(), "serde:142");
+        let id = ItemId::new("local", "serde", 142);
+        assert!(short.matches(&id));
+        assert!(short.matches(&ItemId::new("https://docs.rs/", "serde", 142)));
+        assert!(!short.matches(&ItemId::new("local", "serde", 141)));
+
+        let full: ItemRef = "serde#142@local".parse().unwrap();
+        assert_eq!(full.to_string(), "serde#142@local");
+        assert!(full.matches(&id));
+        assert!(!full.matches(&ItemId::new("https://docs.rs/", "serde", 142)));
+
+        let hyphenated: ItemRef = "serde-json:3".parse().unwrap();
+        assert!(hyphenated.matches(&ItemId::new("local", "serde_json", 3)));
+
+        assert_eq!("serde".parse::<ItemRef>(), Err(ItemIdError::MalformedRef));
+        assert_eq!(":1".parse::<ItemRef>(), Err(ItemIdError::MalformedRef));
+        assert_eq!(
+            "serde:x".parse::<ItemRef>(),
+            Err(ItemIdError::InvalidIndex("x".to_string()))
+        );
+        assert_eq!("serde#1".parse::<ItemRef>(), Err(ItemIdError::Malformed));
+    }
+}
@@ -0,0 +1,61 @@
+//! Parsing and decoding of rustdoc's `search-index.js` format.
+//!
+//! The `rdoc` binary is built on top of this library; it is also used by the
+//! benchmarks so the parser can be measured in isolation.
+
+pub mod aliases;
+pub mod arena;
+pub mod bitmap;
+pub mod cancel;
+pub mod centrality;
+pub mod cheatsheet;
+pub mod chunks;
+pub mod client;
+pub mod coverage;
+pub mod crate_graph;
+pub mod descriptions;
+pub mod diagnostics;
+pub mod discover;
+pub mod doc_links;
+#[cfg(test)]
+mod encode;
+pub mod events;
+pub mod explain;
+pub mod format;
+pub mod glob;
+pub mod hidden;
+pub mod human;
+pub mod ignore;
+pub mod implementors;
+pub mod index_file;
+pub mod item_id;
+pub mod lockfile;
+pub mod matcher;
+pub mod members;
+pub mod modules;
+pub mod name_index;
+pub mod normalize;
+pub mod openapi;
+pub mod pages;
+pub mod platform;
+pub mod prefilter;
+pub mod public_api;
+pub mod query;
+pub mod resolve;
+pub mod resources;
+pub mod rustdoc_version;
+pub mod schema;
+#[cfg(feature = "scip")]
+pub mod scip;
+pub mod search_index;
+pub mod search_items;
+pub mod signature;
+pub mod similarity;
+pub mod source;
+pub mod stale;
+pub mod stats;
+pub mod std_paths;
+pub mod suggest;
+pub mod timings;
+pub mod unify;
+pub mod vlq;
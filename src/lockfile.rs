@@ -0,0 +1,61 @@
+// Reading the packages a project has locked in Cargo.lock
+
+use serde::Deserialize;
+
+#[derive(Debug, Deserialize)]
+struct Lockfile {
+    #[serde(default)]
+    package: Vec<LockedPackage>,
+}
+
+/// One `[[package]]` entry of a Cargo.lock.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct LockedPackage {
+    pub name: String,
+    pub version: String,
+    /// Missing for workspace members and path dependencies
+    pub source: Option<String>,
+}
+
+impl LockedPackage {
+    /// Whether the package comes from a registry, and so has hosted documentation.
+    pub fn is_registry(&self) -> bool {
+        self.source
+            .as_deref()
+            .is_some_and(|source| source.starts_with("registry+") || source.starts_with("sparse+"))
+    }
+}
+
+/// Every package in the contents of a Cargo.lock.
+pub fn parse_lockfile(contents: &str) -> Result<Vec<LockedPackage>, toml::de::Error> {
+    let lockfile: Lockfile = toml::from_str(contents)?;
+    Ok(lockfile.package)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_lockfile() {
+        let packages = parse_lockfile(
+            r#"
+version = 4
+
+[[package]]
+name = "myapp"
+version = "0.1.0"
+
+[[package]]
+name = "serde-json"
+version = "1.0.0"
+source = "sparse+https://index.crates.io/"
+"#,
+        )
+        .unwrap();
+        assert_eq!(packages.len(), 2);
+        assert!(!packages[0].is_registry());
+        assert!(packages[1].is_registry());
+        assert!(parse_lockfile("package = 1").is_err());
+    }
+}
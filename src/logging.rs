@@ -0,0 +1,112 @@
+// Diagnostic logging setup
+
+use clap::ValueEnum;
+use rdoc::events::EVENT_TARGET;
+use tracing_subscriber::EnvFilter;
+use tracing_subscriber::filter::Directive;
+
+/// Environment variable that overrides the `-v` flags with a full filter directive
+/// (e.g. `RDOC_LOG=rdoc=trace` or `RDOC_LOG=debug`).
+pub const LOG_ENV_VAR: &str = "RDOC_LOG";
+
+/// How log lines on stderr are written
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum LogFormat {
+    /// Human-readable lines
+    #[default]
+    Text,
+    /// One JSON object per line, including progress events (doc generation,
+    /// crates decoded, shards loaded, results ready) for dashboards and CI
+    Json,
+}
+
+/// Map the number of `-v` flags to a default filter directive.
+///
+/// Logs from rdoc itself are raised with verbosity; dependencies stay at `warn`.
+pub fn filter_for_verbosity(verbosity: u8) -> &'static str {
+    match verbosity {
+        0 => "warn",
+        1 => "warn,rdoc=info",
+        2 => "warn,rdoc=debug",
+        _ => "warn,rdoc=trace",
+    }
+}
+
+/// Turn on ANSI escape handling in the terminal, returning whether colors can be used.
+///
+/// Windows consoles need virtual terminal processing enabled first; when that
+/// fails (e.g. an old console), colored output is turned off instead.
+pub fn enable_ansi() -> bool {
+    #[cfg(windows)]
+    if colored::control::set_virtual_terminal(true).is_err() {
+        colored::control::set_override(false);
+        return false;
+    }
+    true
+}
+
+/// Install the global tracing subscriber, writing to stderr.
+///
+/// `RDOC_LOG` takes precedence over the verbosity flags when it is set.
+/// Progress events are always logged in JSON, and in text only when
+/// `RDOC_LOG` asks for them.
+pub fn init(verbosity: u8, ansi: bool, format: LogFormat) {
+    let filter = match EnvFilter::try_from_env(LOG_ENV_VAR) {
+        Ok(filter) => filter,
+        Err(_) => EnvFilter::new(filter_for_verbosity(verbosity))
+            .add_directive(events_directive(LogFormat::Text)),
+    };
+
+    match format {
+        LogFormat::Text => tracing_subscriber::fmt()
+            .with_env_filter(filter)
+            .with_writer(std::io::stderr)
+            .with_ansi(ansi)
+            .with_target(false)
+            .without_time()
+            .init(),
+        LogFormat::Json => tracing_subscriber::fmt()
+            .json()
+            .flatten_event(true)
+            .with_env_filter(filter.add_directive(events_directive(format)))
+            .with_writer(std::io::stderr)
+            .with_current_span(false)
+            .init(),
+    }
+}
+
+/// The filter directive turning progress events on or off for `format`.
+fn events_directive(format: LogFormat) -> Directive {
+    let level = match format {
+        LogFormat::Text => "off",
+        LogFormat::Json => "info",
+    };
+    format!("{}={}", EVENT_TARGET, level)
+        .parse()
+        .expect("valid events directive")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_filter_for_verbosity() {
+        assert_eq!(filter_for_verbosity(0), "warn");
+        assert_eq!(filter_for_verbosity(1), "warn,rdoc=info");
+        assert_eq!(filter_for_verbosity(2), "warn,rdoc=debug");
+        assert_eq!(filter_for_verbosity(5), "warn,rdoc=trace");
+    }
+
+    #[test]
+    fn test_events_directive() {
+        assert_eq!(
+            events_directive(LogFormat::Json).to_string(),
+            "rdoc::events=info"
+        );
+        assert_eq!(
+            events_directive(LogFormat::Text).to_string(),
+            "rdoc::events=off"
+        );
+    }
+}
@@ -1,10 +1,40 @@
-use clap::{Parser, Subcommand};
-use color_eyre::Result;
+use clap::{Args, Parser, Subcommand};
+use color_eyre::{Result, eyre::Context};
+use commands::cheatsheet::CheatsheetFormat;
+use commands::export::chunks::ChunkFormat;
+use commands::scan::{Profile, ScanOptions};
+use commands::serve::ServeOptions;
+use commands::snippets::SnippetsOutput;
+use commands::stats::StatsFormat;
+use commands::warm::{DEFAULT_DOCS_HOST, WarmOptions};
+use config::Config;
+use docs::{DocSource, StaleDocs};
+use logging::LogFormat;
+use rdoc::cheatsheet::Limits;
+use rdoc::format::Format;
+use rdoc::hidden::HideRule;
+use rdoc::ignore::IgnoreRules;
+use rdoc::item_id::ItemRef;
+use rdoc::matcher::{CaseMode, MatcherKind};
+use rdoc::normalize;
+use rdoc::platform::TargetPlatform;
+use rdoc::search_index::ItemType;
+use remote::HttpOptions;
+use std::convert::Infallible;
+use std::path::PathBuf;
+use std::time::Duration;
 
+mod cache;
 mod commands;
-mod search_index;
-mod search_items;
-mod vlq;
+mod config;
+mod daemon;
+mod doc_root;
+mod docs;
+mod hyperlink;
+mod logging;
+mod output;
+mod progress;
+mod remote;
 
 /// A CLI tool for searching generated Rust documentation
 #[derive(Parser)]
@@ -12,6 +42,68 @@ mod vlq;
 #[command(about = "Search generated Rust documentation", long_about = None)]
 #[command(version)]
 struct Cli {
+    /// Suppress status messages and progress output
+    #[arg(short, long, global = true)]
+    quiet: bool,
+
+    /// Increase log verbosity (-v for info, -vv for debug, -vvv for trace).
+    /// RDOC_LOG overrides this with a full filter (e.g. RDOC_LOG=rdoc=debug)
+    #[arg(short, long, global = true, action = clap::ArgAction::Count)]
+    verbose: u8,
+
+    /// How to write logs on stderr: text, or json for one JSON object per line
+    /// including progress events, for dashboards and CI log parsing
+    #[arg(long, global = true, value_enum, value_name = "FORMAT", default_value_t = LogFormat::Text)]
+    log_format: LogFormat,
+
+    /// List the problems decoding the search index worked around (unknown
+    /// item types, dangling path or parent indices, malformed bitmaps)
+    #[arg(long, global = true)]
+    warnings: bool,
+
+    /// Search a hosted rustdoc site instead of the local project's docs
+    /// (e.g. https://docs.example.com/myapi/). Downloads are cached
+    #[arg(long, global = true, value_name = "URL")]
+    doc_url: Option<String>,
+
+    /// Search the rustdoc output in DIR (e.g. from `rustdoc src/lib.rs`) as
+    /// is, without needing a cargo project or running cargo doc
+    #[arg(long, global = true, value_name = "DIR", conflicts_with = "doc_url")]
+    doc_dir: Option<PathBuf>,
+
+    /// Re-download remote docs even if a cached copy exists
+    #[arg(long, global = true)]
+    refresh: bool,
+
+    /// Where the local docs are published, for absolute URLs in JSON results and
+    /// pages missing locally. Overrides [docs] base_url in the config
+    #[arg(long, global = true, value_name = "URL", conflicts_with = "doc_url")]
+    base_url: Option<String>,
+
+    /// Link dependencies' items to docs.rs, at the version the project uses,
+    /// when their pages weren't generated (e.g. with `cargo doc --no-deps`)
+    #[arg(long, global = true, conflicts_with = "doc_url")]
+    docs_rs: bool,
+
+    /// Warn about the docs of crates at versions Cargo.lock no longer has
+    /// (left behind by dependency updates)
+    #[arg(long, global = true, conflicts_with = "doc_url")]
+    check_stale: bool,
+
+    /// Remove the docs of crates at versions Cargo.lock no longer has and
+    /// regenerate the rest
+    #[arg(long, global = true, conflicts_with = "doc_url")]
+    clean_stale: bool,
+
+    /// Extra HTTP header for remote docs, as "Name: value" (repeatable).
+    /// Credentials can also come from RDOC_HTTP_TOKEN or the [http] config section
+    #[arg(long = "header", global = true, value_name = "HEADER")]
+    headers: Vec<String>,
+
+    /// Number of downloads to run at once when fetching remote docs
+    #[arg(short, long, global = true, value_name = "N", default_value_t = remote::DEFAULT_JOBS)]
+    jobs: usize,
+
     #[command(subcommand)]
     command: Option<Commands>,
 }
@@ -20,37 +112,907 @@ struct Cli {
 enum Commands {
     /// Scan rustdocs for a specific symbol
     #[command(about = "Search for a symbol in generated rustdocs")]
-    Scan {
-        /// The symbol to search for (e.g., "Result", "Vec", "HashMap")
-        #[arg(value_name = "SYMBOL")]
-        symbol: String,
-    },
+    Scan(Box<ScanArgs>),
     /// Show documentation for a fully qualified path
     #[command(about = "Show documentation for a fully qualified item path")]
     Show {
         /// The fully qualified path (e.g., "std::fs::read_to_string")
+        #[arg(value_name = "PATH", required_unless_present = "id")]
+        path: Option<String>,
+        /// Show the item with this ID instead: "crate:index", or an `id` from
+        /// JSON output (e.g., "serde#142@local"). IDs only stay valid while
+        /// the docs aren't regenerated
+        #[arg(long, value_name = "ID", conflicts_with = "path")]
+        id: Option<ItemRef>,
+    },
+    /// Summarize a module like its rustdoc index page
+    #[command(
+        about = "Show a module's introduction and its public items by kind, with their descriptions"
+    )]
+    Summary {
+        /// The module's path (e.g., "serde::de"), or a crate name for its root
+        #[arg(value_name = "MODULE")]
+        module: String,
+    },
+    /// Make a cheat-sheet of a crate or module
+    #[command(
+        about = "Print a compact cheat-sheet of a module's key types, constructors, methods and functions"
+    )]
+    Cheatsheet {
+        /// The module's path (e.g., "tokio::sync"), or a crate name for its root
+        #[arg(value_name = "MODULE")]
+        module: String,
+        /// How many types to list, most used in signatures first
+        #[arg(long, value_name = "N", default_value_t = Limits::default().types)]
+        types: usize,
+        /// How many constructors, and how many other methods, to list per type
+        #[arg(long, value_name = "N", default_value_t = Limits::default().methods)]
+        methods: usize,
+        /// How many free functions to list
+        #[arg(long, value_name = "N", default_value_t = Limits::default().functions)]
+        functions: usize,
+        /// Output format. Defaults to terminal, or markdown with --output
+        #[arg(long, value_enum)]
+        format: Option<CheatsheetFormat>,
+        /// Write the cheat-sheet to this file instead of stdout
+        #[arg(short, long, value_name = "FILE")]
+        output: Option<PathBuf>,
+    },
+    /// Search a SCIP index from rust-analyzer instead of generated docs
+    #[cfg(feature = "scip")]
+    #[command(
+        about = "Search the items in a SCIP index from `rust-analyzer scip`, without running cargo doc"
+    )]
+    Symbols {
+        /// The query, as for scan (e.g., "Point" or "geo::parse")
+        #[arg(value_name = "QUERY")]
+        query: String,
+        /// The SCIP index to search. Defaults to index.scip, generated with
+        /// `rust-analyzer scip .` when it's missing
+        #[arg(long, value_name = "FILE")]
+        index: Option<PathBuf>,
+        /// How many items to list
+        #[arg(short = 'n', long, value_name = "N", default_value_t = 20)]
+        limit: usize,
+        /// Output format: plain, json, ndjson, markdown, table or exec:COMMAND
+        #[arg(long, value_name = "FORMAT")]
+        format: Option<Format>,
+    },
+    /// Run queries interactively against an index decoded once
+    #[command(
+        about = "Run queries from a prompt, with :type, :crate, :show N and :open N, decoding the index once"
+    )]
+    Repl {
+        /// How terms match names: rustdoc or nucleo (fzf-style), as for scan;
+        /// `:matcher` switches during the session
+        #[arg(long, value_name = "MATCHER")]
+        matcher: Option<MatcherKind>,
+    },
+    /// Print an item's source code
+    #[command(about = "Print an item's source code from the documentation's source pages")]
+    Source {
+        /// The item's path (e.g., "serde_json::from_str")
+        #[arg(value_name = "PATH")]
+        path: String,
+        /// Also print this many lines before and after the item
+        #[arg(short = 'C', long, value_name = "N", default_value_t = 0)]
+        context: usize,
+    },
+    /// Resolve a path to exactly one item
+    #[command(
+        about = "Resolve a path to exactly one item, or list the candidates if it is ambiguous"
+    )]
+    Resolve {
+        /// The item's path or name (e.g., "serde::Serialize"). Prefix a kind like
+        /// "trait@serde::Serialize", or write "json!" or "as_str()", to pick between
+        /// items sharing a path
         #[arg(value_name = "PATH")]
         path: String,
+        /// Output format: plain, json, ndjson, markdown, table or exec:COMMAND
+        #[arg(long, value_name = "FORMAT")]
+        format: Option<Format>,
+    },
+    /// Print the path to `use` an item by, and nothing else
+    #[command(
+        about = "Print the path to import an item by, resolving re-exports; fails on ambiguity"
+    )]
+    Path {
+        /// The item's path or name (e.g., "HashMap" or "serde_json::Value::as_str"),
+        /// with the same disambiguators as resolve
+        #[arg(value_name = "PATH")]
+        path: String,
+    },
+    /// Find functions with signatures similar to a given one
+    #[command(about = "List functions whose signatures are most like the given function's")]
+    Similar {
+        /// The function's path (e.g., "mycrate::parse_config")
+        #[arg(value_name = "PATH")]
+        path: String,
+        /// How many functions to list
+        #[arg(short = 'n', long, value_name = "N", default_value_t = 10)]
+        limit: usize,
+    },
+    /// Collect matching items' code examples into a file
+    #[command(
+        about = "Collect the runnable code examples of matching items into a scratch file or examples/ program"
+    )]
+    Snippets {
+        /// The query to match items by, as for scan (e.g., "from_str")
+        #[arg(value_name = "QUERY")]
+        query: String,
+        /// Most items to take examples from, best matches first
+        #[arg(short = 'n', long, value_name = "N", default_value_t = 5)]
+        limit: usize,
+        /// Write the examples to this file instead of stdout
+        #[arg(short, long, value_name = "FILE")]
+        output: Option<PathBuf>,
+        /// Write the examples to examples/NAME.rs, to run with `cargo run --example NAME`
+        #[arg(long, value_name = "NAME", conflicts_with = "output")]
+        example: Option<String>,
+        /// Replace the file if it already exists
+        #[arg(long)]
+        force: bool,
     },
+    /// List the types implementing a trait
+    #[command(about = "List the types implementing a trait, across every documented crate")]
+    Implementors {
+        /// The trait's path (e.g., "serde::Serialize")
+        #[arg(value_name = "PATH")]
+        path: String,
+    },
+    /// Check intra-doc links in the crate's source
+    #[command(about = "Check that intra-doc links in doc comments resolve to documented items")]
+    CheckLinks {
+        /// Directory of Rust source to scan
+        #[arg(long, value_name = "DIR", default_value = "src")]
+        src: PathBuf,
+        /// The crate the source belongs to (defaults to the workspace's first library)
+        #[arg(long = "crate", value_name = "CRATE", value_parser = crate_name_arg)]
+        crate_name: Option<String>,
+    },
+    /// Explain how a single search index item was decoded
+    #[command(about = "Show how an item in the search index was decoded, field by field")]
+    Explain {
+        /// The crate containing the item (e.g., "serde")
+        #[arg(long = "crate", value_name = "CRATE", value_parser = crate_name_arg)]
+        crate_name: String,
+        /// The item's index within the crate's compact data
+        #[arg(long, value_name = "INDEX")]
+        item: usize,
+    },
+    /// Audit the documentation surface
+    #[command(about = "Audit documentation coverage and API hygiene")]
+    Audit {
+        #[command(subcommand)]
+        audit: AuditCommands,
+    },
+    /// Count items per item type and module
+    #[command(about = "Show public API size per item type and module, or compare with a snapshot")]
+    Stats {
+        /// Only count this crate (repeatable; defaults to every crate in the index)
+        #[arg(long = "crate", value_name = "CRATE", value_parser = crate_name_arg)]
+        crates: Vec<String>,
+        /// Snapshot from an earlier `rdoc stats --format json` to compare against
+        #[arg(long, value_name = "SNAPSHOT")]
+        compare: Option<PathBuf>,
+        /// Output format
+        #[arg(long, value_enum, default_value_t = StatsFormat::Table)]
+        format: StatsFormat,
+    },
+    /// List the types function signatures mention most
+    #[command(
+        about = "List each crate's most central types: those the most function signatures use"
+    )]
+    Top {
+        /// Only list this crate's types (repeatable; defaults to every crate in the index)
+        #[arg(long = "crate", value_name = "CRATE", value_parser = crate_name_arg)]
+        crates: Vec<String>,
+        /// How many types to list per crate
+        #[arg(short = 'n', long, value_name = "N", default_value_t = 10)]
+        limit: usize,
+    },
+    /// Print random items with their descriptions, to explore crates by
+    #[command(
+        about = "Print a few random items with their descriptions, favoring documented types and functions"
+    )]
+    Random {
+        /// Only pick this crate's items (repeatable; defaults to every crate in the index)
+        #[arg(long = "crate", value_name = "CRATE", value_parser = crate_name_arg)]
+        crates: Vec<String>,
+        /// Only pick items of these kinds, comma-separated (e.g., "struct,trait")
+        #[arg(long = "type", value_name = "KINDS", value_delimiter = ',', value_parser = commands::scan::parse_kind)]
+        types: Vec<&'static [ItemType]>,
+        /// How many items to print
+        #[arg(short = 'n', long, value_name = "N", default_value_t = 5)]
+        count: usize,
+        /// Pick the same items every time for the same docs
+        #[arg(long, value_name = "SEED")]
+        seed: Option<u64>,
+    },
+    /// Manage user-defined search aliases
+    #[command(
+        about = "Add, remove or list your own aliases, which scan and show accept like index aliases"
+    )]
+    Alias {
+        #[command(subcommand)]
+        alias: AliasCommands,
+    },
+    /// Inspect the built-in rules that leave noise out of results
+    #[command(about = "List the built-in noise rules scan applies, and which are turned off")]
+    Rules {
+        #[command(subcommand)]
+        rules: RulesCommands,
+    },
+    /// List the aliases crates declare with #[doc(alias)]
+    #[command(about = "List the items crates give #[doc(alias)]es to, with their aliases")]
+    Aliases {
+        /// Only list this crate's aliases (repeatable; defaults to every crate in the index)
+        #[arg(value_name = "CRATE")]
+        crates: Vec<String>,
+    },
+    /// Serve lookups over HTTP from an index decoded once
+    #[command(
+        about = "Serve a web UI and HTTP API over an index decoded once (search, previews, /resolve)"
+    )]
+    Serve {
+        /// Address to listen on
+        #[arg(long, value_name = "ADDR", default_value = "127.0.0.1:7878")]
+        addr: String,
+        /// Stop searches taking longer than this many milliseconds (0 for no
+        /// limit); a request's timeout parameter overrides it
+        #[arg(long, value_name = "MS", default_value_t = 5000)]
+        search_timeout: u64,
+        /// Requests answered at once. Defaults to the number of CPUs
+        #[arg(long, value_name = "N")]
+        threads: Option<usize>,
+        /// Check for regenerated docs every this many seconds, reloading them in
+        /// the background (0 to only reload on POST /reload)
+        #[arg(long, value_name = "SECS", default_value_t = 2)]
+        reload_interval: u64,
+    },
+    /// Print the JSON Schema of rdoc's JSON output
+    #[command(about = "Print a JSON Schema describing the JSON that --format json and stats write")]
+    Schema {
+        /// Print the OpenAPI document of `serve`'s HTTP API instead
+        #[arg(long)]
+        openapi: bool,
+    },
+    /// Generate an HTML report of the documentation surface
+    #[command(
+        about = "Write a static HTML report of item counts, deprecated and undocumented items"
+    )]
+    Report {
+        /// Where to write the report
+        #[arg(short, long, value_name = "FILE", default_value = "rdoc-report.html")]
+        output: PathBuf,
+    },
+    /// Export the documentation for other tools
+    #[command(about = "Export the documentation in formats other tools read")]
+    Export {
+        #[command(subcommand)]
+        export: ExportCommands,
+    },
+    /// Complete an item name
+    #[command(
+        about = "Print item names starting with a prefix, from a cached name index (for shell completions)"
+    )]
+    Complete {
+        /// The start of the name; case and underscores are ignored
+        #[arg(value_name = "PREFIX", default_value = "")]
+        prefix: String,
+        /// Also complete names starting with a near miss of the prefix (typos)
+        #[arg(long)]
+        fuzzy: bool,
+        /// Most names to print
+        #[arg(short = 'n', long, value_name = "N", default_value_t = 50)]
+        limit: usize,
+    },
+    /// Set up a project for rdoc
+    #[command(about = "Write a starter .rdoc.toml and check the toolchain and docs rdoc needs")]
+    Init {
+        /// Replace an existing .rdoc.toml
+        #[arg(long)]
+        force: bool,
+        /// Run cargo doc if the project has no docs yet
+        #[arg(long)]
+        generate: bool,
+    },
+    /// Pre-fetch documentation for every dependency in Cargo.lock
+    #[command(about = "Download docs for every dependency in Cargo.lock into the cache")]
+    Warm {
+        /// Lockfile listing the dependencies
+        #[arg(long, value_name = "FILE", default_value = "Cargo.lock")]
+        lockfile: PathBuf,
+        /// Host serving each crate's docs under <name>/<version>/ [default:
+        /// https://docs.rs]. `[http]` credentials and --header are only sent
+        /// to a host given here
+        #[arg(long, value_name = "URL")]
+        docs_host: Option<String>,
+        /// Generate the docs locally with cargo doc instead of downloading them
+        #[arg(long, conflicts_with = "docs_host")]
+        local: bool,
+    },
+    /// Upgrade rdoc's cache to this version's format
+    #[command(
+        about = "Upgrade the cache after updating rdoc and check cached remote indexes still read"
+    )]
+    UpgradeIndex,
+}
+
+impl Commands {
+    /// Whether the command writes JSON for other programs to read
+    /// (`--format json` or `ndjson`).
+    fn is_machine_readable(&self) -> bool {
+        let format = match self {
+            Commands::Scan(args) => args.format.as_ref(),
+            #[cfg(feature = "scip")]
+            Commands::Symbols { format, .. } => format.as_ref(),
+            Commands::Resolve { format, .. } => format.as_ref(),
+            Commands::Stats { format, .. } => return *format == StatsFormat::Json,
+            _ => None,
+        };
+        matches!(format, Some(Format::Json | Format::Ndjson))
+    }
+}
+
+#[derive(Subcommand)]
+enum AuditCommands {
+    /// List items without documentation
+    #[command(about = "List items with empty descriptions, per crate")]
+    Docs {
+        /// Only audit this crate (repeatable; defaults to every crate in the index)
+        #[arg(long = "crate", value_name = "CRATE", value_parser = crate_name_arg)]
+        crates: Vec<String>,
+        /// Exit with an error if overall coverage is below this percentage
+        #[arg(long, value_name = "PERCENT", value_parser = commands::audit::parse_percentage)]
+        threshold: Option<f64>,
+    },
+    /// List deprecated items still in use
+    #[command(about = "List deprecated items your crates re-export or your dependencies expose")]
+    Deprecated {
+        /// Treat this crate as yours (repeatable; defaults to the workspace members)
+        #[arg(long = "crate", value_name = "CRATE", value_parser = crate_name_arg)]
+        crates: Vec<String>,
+    },
+    /// List functions duplicated across your crates
+    #[command(
+        about = "List functions several workspace crates define with the same name and near-identical signatures"
+    )]
+    Duplicates {
+        /// Compare this crate (repeatable; defaults to the workspace members)
+        #[arg(long = "crate", value_name = "CRATE", value_parser = crate_name_arg)]
+        crates: Vec<String>,
+        /// How alike signatures must be to count as duplicates, as a percentage
+        #[arg(long, value_name = "PERCENT", default_value_t = 90.0, value_parser = commands::audit::parse_percentage)]
+        threshold: f64,
+    },
+    /// Report which crates make the search index big
+    #[command(about = "Show which crates contribute the most items and bytes to the search index")]
+    Bloat {
+        /// Exit with an error if the index has more items than this, suggesting
+        /// dependencies to leave out
+        #[arg(long, value_name = "ITEMS")]
+        budget: Option<usize>,
+        /// How many crates to list, largest first
+        #[arg(short = 'n', long, value_name = "N", default_value_t = 20)]
+        limit: usize,
+    },
+}
+
+#[derive(Subcommand)]
+enum ExportCommands {
+    /// Export each item's declaration and documentation for embedding
+    #[command(
+        about = "Write one record per item with its path, signature and documentation text, for embedding pipelines"
+    )]
+    Chunks {
+        /// Only export this crate (repeatable; defaults to every crate in the index)
+        #[arg(long = "crate", value_name = "CRATE", value_parser = crate_name_arg)]
+        crates: Vec<String>,
+        /// Output format
+        #[arg(long, value_enum, default_value_t = ChunkFormat::Jsonl)]
+        format: ChunkFormat,
+        /// Cut each record's text to at most this many characters, at a
+        /// paragraph or sentence break
+        #[arg(long, value_name = "N", default_value_t = rdoc::chunks::DEFAULT_MAX_CHARS)]
+        max_chars: usize,
+        /// Write the records to this file instead of stdout
+        #[arg(short, long, value_name = "FILE")]
+        output: Option<PathBuf>,
+    },
+    /// Export the public API as a sorted list, for diffing
+    #[command(
+        about = "List every public item with its signature, one per line as cargo-public-api prints them, for diffing"
+    )]
+    Api {
+        /// Only list this crate (repeatable; defaults to every crate in the index)
+        #[arg(long = "crate", value_name = "CRATE", value_parser = crate_name_arg)]
+        crates: Vec<String>,
+        /// List kinds and paths only, without reading signatures from the pages
+        #[arg(long)]
+        no_signatures: bool,
+        /// Write the listing to this file instead of stdout
+        #[arg(short, long, value_name = "FILE")]
+        output: Option<PathBuf>,
+    },
+}
+
+/// The arguments of `scan`
+#[derive(Args)]
+struct ScanArgs {
+    /// The symbol or query to search for (e.g., "Result", "read AND async NOT blocking",
+    /// "+read -blocking"). Terms may be globs ("try_*", "u[0-9]*"); globs containing
+    /// "::" match the full path ("std::fs::*")
+    #[arg(value_name = "QUERY", required_unless_present = "stdin")]
+    symbol: Option<String>,
+    /// Read one query per line from stdin instead, decoding the index once, and
+    /// write each query's results as a group (a JSON object per line with
+    /// --format ndjson)
+    #[arg(long, conflicts_with_all = ["symbol", "output"])]
+    stdin: bool,
+    /// Also match query terms against item descriptions
+    #[arg(short, long)]
+    descriptions: bool,
+    /// List every place an item is re-exported instead of collapsing them
+    #[arg(long)]
+    show_reexports: bool,
+    /// Case matching: sensitive, insensitive, or smart (lowercase terms ignore case,
+    /// terms with uppercase letters match exactly). Defaults to smart
+    #[arg(long, value_name = "MODE")]
+    case: Option<CaseMode>,
+    /// How terms match names: rustdoc (substrings ignoring underscores, one-word
+    /// queries ranked like rustdoc's search) or nucleo (fzf-style fuzzy matching,
+    /// best matches first). Defaults to [scan] matcher in the config, then rustdoc
+    #[arg(long, value_name = "MATCHER")]
+    matcher: Option<MatcherKind>,
+    /// Preset matching options: strict (grep-like: exact case, names only, every
+    /// re-export), fuzzy (rustdoc-like: any case, re-exports collapsed) or docs
+    /// (fuzzy, also matching descriptions). Defaults to [scan] profile in the
+    /// config; other flags override it
+    #[arg(long, value_enum, value_name = "PROFILE")]
+    profile: Option<Profile>,
+    /// Output format: plain, json, ndjson (streamed as results are found),
+    /// markdown, table, or exec:COMMAND to pipe results as JSON lines to an
+    /// external formatter. Defaults to a colored listing on the terminal and
+    /// plain with --output
+    #[arg(long, value_name = "FORMAT")]
+    format: Option<Format>,
+    /// Write results to this file (replaced atomically) and print only a summary
+    #[arg(short, long, value_name = "FILE")]
+    output: Option<PathBuf>,
+    /// Only list items that need this cargo feature (repeatable). Reads each
+    /// result's HTML page, downloading it for --doc-url
+    #[arg(long = "feature", value_name = "FEATURE")]
+    features: Vec<String>,
+    /// Leave out items not available on this platform (linux, macos, windows,
+    /// wasm, ..., or "host" for this one), by the "Available on ... only"
+    /// notes on their pages
+    #[arg(long, value_name = "PLATFORM")]
+    target_platform: Option<TargetPlatform>,
+    /// Only search inside this module or type (e.g., "serde::de"), including
+    /// items re-exported there
+    #[arg(long = "in", value_name = "PATH")]
+    within: Option<String>,
+    /// Only list items from the workspace's crates and their direct dependencies
+    #[arg(long)]
+    direct_deps_only: bool,
+    /// Leave out items of these kinds, comma-separated (e.g., "impl,import")
+    #[arg(long, value_name = "KINDS", value_delimiter = ',', value_parser = commands::scan::parse_kind)]
+    exclude_type: Vec<&'static [ItemType]>,
+    /// Leave out this crate's items (repeatable; may be a glob)
+    #[arg(long, value_name = "CRATE")]
+    exclude_crate: Vec<String>,
+    /// Leave out everything at or below this path (repeatable; may be a
+    /// glob, e.g. "*::__private::*")
+    #[arg(long, value_name = "PATH")]
+    exclude_path: Vec<String>,
+    /// Turn off every built-in noise rule, listing the impls, imports and
+    /// internal items they hide (see `rdoc rules list`)
+    #[arg(long, visible_alias = "show-hidden")]
+    no_default_ignore: bool,
+    /// Turn off one built-in noise rule, e.g. "imports" (repeatable, or
+    /// comma-separated)
+    #[arg(long, value_name = "RULE", value_delimiter = ',')]
+    disable_rule: Vec<HideRule>,
+    /// Decode the docs here even if a running `rdoc serve` for them could
+    /// answer the query
+    #[arg(long)]
+    no_daemon: bool,
+    /// Report how long locating, parsing, decoding, matching, ranking and
+    /// writing took, on stderr (and in the results with --format json)
+    #[arg(long, conflicts_with = "stdin")]
+    timings: bool,
+    /// When nothing is found, suggest `cargo add` for crates providing the
+    /// symbol, from docs cached with --doc-url and a list of common ones
+    #[arg(long, conflicts_with = "stdin")]
+    suggest_deps: bool,
+}
+
+impl ScanArgs {
+    /// The options the flags, and `config` where they're left out, ask for.
+    fn options(&self, config: &Config) -> Result<ScanOptions> {
+        let defaults = ScanOptions::from_config(config)?;
+        let mut options = ScanOptions {
+            format: self.format.clone(),
+            output: self.output.clone(),
+            features: self.features.clone(),
+            target_platform: self.target_platform,
+            within: self.within.clone(),
+            direct_deps_only: self.direct_deps_only,
+            exclude_types: self.exclude_type.concat(),
+            exclude: IgnoreRules::excluding(&self.exclude_crate, &self.exclude_path)
+                .wrap_err("Invalid --exclude-crate or --exclude-path pattern")?,
+            hidden: if self.no_default_ignore {
+                None
+            } else {
+                defaults
+                    .hidden
+                    .map(|hidden| hidden.without(&self.disable_rule))
+            },
+            use_daemon: !self.no_daemon,
+            timings: self.timings,
+            suggest_deps: self.suggest_deps,
+            ..defaults
+        };
+        // Every profile sets what the config's did, so a flag replaces it whole
+        if let Some(profile) = self.profile {
+            profile.apply(&mut options);
+        }
+        if let Some(case) = self.case {
+            options.case = case;
+        }
+        if let Some(matcher) = self.matcher {
+            options.matcher = matcher;
+        }
+        options.descriptions |= self.descriptions;
+        options.show_reexports |= self.show_reexports;
+        Ok(options)
+    }
+}
+
+#[derive(Subcommand)]
+enum AliasCommands {
+    /// Add an alias, replacing one with the same name
+    #[command(
+        about = "Add an alias for an item path (e.g., `rdoc alias add hm std::collections::HashMap`)"
+    )]
+    Add {
+        /// The alias; matched whole and ignoring case
+        #[arg(value_name = "NAME", value_parser = commands::alias::parse_alias_name)]
+        name: String,
+        /// The item path it stands for
+        #[arg(value_name = "PATH")]
+        path: String,
+    },
+    /// Remove an alias
+    #[command(about = "Remove an alias")]
+    Remove {
+        #[arg(value_name = "NAME")]
+        name: String,
+    },
+    /// List aliases
+    #[command(about = "List the aliases in the config file")]
+    List,
+}
+
+#[derive(Subcommand)]
+enum RulesCommands {
+    /// List the rules
+    #[command(about = "List the built-in noise rules, what each hides, and whether it's on")]
+    List,
+}
+
+/// A `--crate` name as rustdoc documents the crate: `serde-json` is `serde_json`.
+fn crate_name_arg(name: &str) -> Result<String, Infallible> {
+    Ok(normalize::crate_name(name).into_owned())
 }
 
 fn main() -> Result<()> {
     color_eyre::install()?;
 
     let cli = Cli::parse();
+    let ansi = logging::enable_ansi();
+    logging::init(cli.verbose, ansi, cli.log_format);
+
+    // Spinners would interleave with the JSON lines on stderr, or with JSON
+    // another program reads from stdout
+    if cli.quiet
+        || cli.log_format == LogFormat::Json
+        || cli
+            .command
+            .as_ref()
+            .is_some_and(Commands::is_machine_readable)
+    {
+        progress::set_enabled(false);
+    }
+
+    let config = Config::load()?;
+    docs::set_ignore_rules(config::load_ignore_rules()?);
+    // upgrade-index reports what upgrading does, so it's left to it
+    if !matches!(cli.command, Some(Commands::UpgradeIndex)) {
+        cache::upgrade_before_use();
+    }
+
+    let http = HttpOptions::new(
+        &config.http,
+        std::env::var(remote::HTTP_TOKEN_ENV_VAR).ok(),
+        &cli.headers,
+    )?;
+    let source = match cli.doc_url {
+        Some(base_url) => DocSource::Remote {
+            http: http.for_host(&base_url),
+            base_url,
+            refresh: cli.refresh,
+            jobs: cli.jobs,
+        },
+        None => DocSource::Local {
+            doc_dir: cli.doc_dir.clone(),
+            doc_roots: config.docs.roots.clone(),
+            base_url: cli
+                .base_url
+                .or(config.docs.base_url.clone())
+                .map(|url| remote::normalize_base_url(&url))
+                .transpose()?,
+            docs_rs: cli.docs_rs || config.docs.docs_rs,
+            stale: if cli.clean_stale {
+                StaleDocs::Clean
+            } else if cli.check_stale {
+                StaleDocs::Warn
+            } else {
+                StaleDocs::Ignore
+            },
+        },
+    };
 
     match cli.command {
-        Some(Commands::Scan { symbol }) => {
-            commands::scan::execute(&symbol)?;
+        Some(Commands::Scan(args)) => {
+            let options = args.options(&config)?;
+            match &args.symbol {
+                Some(symbol) if !args.stdin => {
+                    commands::scan::execute(symbol, &options, &source, cli.quiet)?
+                }
+                _ => commands::scan::execute_batch(&options, &source, cli.quiet)?,
+            }
+        }
+        Some(Commands::Show { path, id }) => match (&path, &id) {
+            (Some(path), None) => {
+                commands::show::execute(path, &config.aliases, &source, cli.quiet)?
+            }
+            // Clap requires one or the other
+            (_, id) => commands::show::execute_id(id.as_ref().unwrap(), &source, cli.quiet)?,
+        },
+        Some(Commands::Summary { module }) => {
+            commands::summary::execute(&module, &source, cli.quiet)?;
+        }
+        Some(Commands::Repl { matcher }) => {
+            let mut options = ScanOptions::from_config(&config)?;
+            if let Some(matcher) = matcher {
+                options.matcher = matcher;
+            }
+            commands::repl::execute(options, &source, cli.quiet)?;
+        }
+        Some(Commands::Source { path, context }) => {
+            commands::source::execute(&path, context, &source, cli.quiet)?;
+        }
+        Some(Commands::Resolve { path, format }) => {
+            commands::resolve::execute(&path, format.as_ref(), &source, cli.quiet)?;
+        }
+        Some(Commands::Path { path }) => {
+            commands::path::execute(&path, &source)?;
+        }
+        Some(Commands::Cheatsheet {
+            module,
+            types,
+            methods,
+            functions,
+            format,
+            output,
+        }) => {
+            let limits = Limits {
+                types,
+                methods,
+                functions,
+            };
+            commands::cheatsheet::execute(
+                &module,
+                limits,
+                format,
+                output.as_deref(),
+                &source,
+                cli.quiet,
+            )?;
+        }
+        #[cfg(feature = "scip")]
+        Some(Commands::Symbols {
+            query,
+            index,
+            limit,
+            format,
+        }) => {
+            commands::symbols::execute(
+                &query,
+                index.as_deref(),
+                limit,
+                format.as_ref(),
+                cli.quiet,
+            )?;
+        }
+        Some(Commands::Similar { path, limit }) => {
+            commands::similar::execute(&path, limit, &source, cli.quiet)?;
+        }
+        Some(Commands::Snippets {
+            query,
+            limit,
+            output,
+            example,
+            force,
+        }) => {
+            let output = match (output, example) {
+                (Some(path), _) => SnippetsOutput::File(path),
+                (None, Some(name)) => SnippetsOutput::Example(name),
+                (None, None) => SnippetsOutput::Stdout,
+            };
+            commands::snippets::execute(&query, limit, &output, force, &source, cli.quiet)?;
+        }
+        Some(Commands::Implementors { path }) => {
+            commands::implementors::execute(&path, &source, cli.quiet)?;
+        }
+        Some(Commands::CheckLinks { src, crate_name }) => {
+            commands::check_links::execute(&src, crate_name.as_deref(), &source, cli.quiet)?;
         }
-        Some(Commands::Show { path }) => {
-            commands::show::execute(&path)?;
+        Some(Commands::Explain { crate_name, item }) => {
+            commands::explain::execute(&crate_name, item, &source, cli.quiet)?;
         }
+        Some(Commands::Audit { audit }) => match audit {
+            AuditCommands::Docs { crates, threshold } => {
+                commands::audit::docs::execute(&crates, threshold, &source, cli.quiet)?;
+            }
+            AuditCommands::Duplicates { crates, threshold } => {
+                commands::audit::duplicates::execute(&crates, threshold, &source, cli.quiet)?;
+            }
+            AuditCommands::Bloat { budget, limit } => {
+                commands::audit::bloat::execute(budget, limit, &source, cli.quiet)?;
+            }
+            AuditCommands::Deprecated { crates } => {
+                commands::audit::deprecated::execute(&crates, &source, cli.quiet)?;
+            }
+        },
+        Some(Commands::Stats {
+            crates,
+            compare,
+            format,
+        }) => {
+            commands::stats::execute(&crates, compare.as_deref(), format, &source, cli.quiet)?;
+        }
+        Some(Commands::Top { crates, limit }) => {
+            commands::top::execute(&crates, limit, &source, cli.quiet)?;
+        }
+        Some(Commands::Random {
+            crates,
+            types,
+            count,
+            seed,
+        }) => {
+            commands::random::execute(&crates, &types.concat(), count, seed, &source, cli.quiet)?;
+        }
+        Some(Commands::Alias { alias }) => match alias {
+            AliasCommands::Add { name, path } => commands::alias::add(&name, &path, cli.quiet)?,
+            AliasCommands::Remove { name } => commands::alias::remove(&name, cli.quiet)?,
+            AliasCommands::List => commands::alias::list(&config.aliases)?,
+        },
+        Some(Commands::Rules { rules }) => match rules {
+            RulesCommands::List => commands::rules::list(&config.scan.hidden_items()?)?,
+        },
+        Some(Commands::Aliases { crates }) => {
+            commands::aliases::execute(&crates, &source, cli.quiet)?;
+        }
+        Some(Commands::Serve {
+            addr,
+            search_timeout,
+            threads,
+            reload_interval,
+        }) => {
+            let options = ServeOptions {
+                threads: threads.unwrap_or_else(|| {
+                    std::thread::available_parallelism().map_or(1, |threads| threads.get())
+                }),
+                search_timeout: (search_timeout > 0).then(|| Duration::from_millis(search_timeout)),
+                reload_interval: (reload_interval > 0)
+                    .then(|| Duration::from_secs(reload_interval)),
+            };
+            commands::serve::execute(&addr, &options, &config, &source, cli.quiet)?;
+        }
+        Some(Commands::Schema { openapi }) => {
+            commands::schema::execute(openapi)?;
+        }
+        Some(Commands::Report { output }) => {
+            commands::report::execute(&output, &source, cli.quiet)?;
+        }
+        Some(Commands::Export { export }) => match export {
+            // JSON lines are the only format so far
+            ExportCommands::Chunks {
+                crates,
+                format: ChunkFormat::Jsonl,
+                max_chars,
+                output,
+            } => {
+                commands::export::chunks::execute(
+                    &crates,
+                    max_chars,
+                    output.as_deref(),
+                    &source,
+                    cli.quiet,
+                )?;
+            }
+            ExportCommands::Api {
+                crates,
+                no_signatures,
+                output,
+            } => {
+                commands::export::api::execute(
+                    &crates,
+                    !no_signatures,
+                    output.as_deref(),
+                    &source,
+                    cli.quiet,
+                )?;
+            }
+        },
+        Some(Commands::Complete {
+            prefix,
+            fuzzy,
+            limit,
+        }) => {
+            commands::complete::execute(&prefix, fuzzy, limit, &source, cli.quiet)?;
+        }
+        Some(Commands::Init { force, generate }) => {
+            commands::init::execute(cli.doc_dir.as_deref(), &config.docs.roots, force, generate)?;
+        }
+        Some(Commands::Warm {
+            lockfile,
+            docs_host,
+            local,
+        }) => {
+            let http = docs_host
+                .as_deref()
+                .map(|docs_host| http.for_host(docs_host))
+                .unwrap_or_default();
+            let options = WarmOptions {
+                docs_host: docs_host.as_deref().unwrap_or(DEFAULT_DOCS_HOST),
+                local,
+                http: &http,
+                refresh: cli.refresh,
+                jobs: cli.jobs,
+            };
+            commands::warm::execute(&lockfile, &options, cli.quiet)?;
+        }
+        Some(Commands::UpgradeIndex) => commands::upgrade_index::execute(cli.quiet)?,
         None => {
             // When no subcommand is provided, show help
             Cli::parse_from(["rdoc", "--help"]);
         }
     }
 
+    docs::report_diagnostics(cli.warnings);
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_machine_readable() {
+        let command = |args: &[&str]| {
+            let args = std::iter::once("rdoc").chain(args.iter().copied());
+            Cli::try_parse_from(args).unwrap().command.unwrap()
+        };
+        assert!(command(&["scan", "x", "--format", "json"]).is_machine_readable());
+        assert!(command(&["scan", "x", "--format", "ndjson"]).is_machine_readable());
+        assert!(command(&["resolve", "x", "--format", "json"]).is_machine_readable());
+        assert!(command(&["stats", "--format", "json"]).is_machine_readable());
+        assert!(!command(&["scan", "x"]).is_machine_readable());
+        assert!(!command(&["scan", "x", "--format", "table"]).is_machine_readable());
+        assert!(!command(&["stats"]).is_machine_readable());
+    }
+}
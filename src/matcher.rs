@@ -0,0 +1,552 @@
+// Matching individual query terms against items
+
+use crate::glob::{Glob, GlobError};
+use crate::normalize::{crate_path, fold_case, normalize_name, strip_raw};
+use crate::prefilter::{Candidates, NameBuffer};
+use crate::search_items::SearchItem;
+use nucleo_matcher::pattern::{Atom, AtomKind, CaseMatching, Normalization};
+use nucleo_matcher::{Config, Utf32Str};
+use serde::Deserialize;
+use std::cell::RefCell;
+use std::fmt;
+use std::str::FromStr;
+
+/// How letter case is treated when matching terms.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum CaseMode {
+    /// Case must match exactly
+    Sensitive,
+    /// Case is ignored
+    Insensitive,
+    /// Like ripgrep's smart case: all-lowercase terms ignore case, terms with
+    /// any uppercase letter are case-sensitive
+    #[default]
+    Smart,
+}
+
+impl CaseMode {
+    /// Whether `term` should be matched case-sensitively.
+    pub fn is_sensitive(self, term: &str) -> bool {
+        match self {
+            CaseMode::Sensitive => true,
+            CaseMode::Insensitive => false,
+            CaseMode::Smart => term.chars().any(char::is_uppercase),
+        }
+    }
+}
+
+impl FromStr for CaseMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "sensitive" => Ok(CaseMode::Sensitive),
+            "insensitive" => Ok(CaseMode::Insensitive),
+            "smart" => Ok(CaseMode::Smart),
+            _ => Err(format!(
+                "unknown case mode \"{}\" (expected sensitive, insensitive or smart)",
+                s
+            )),
+        }
+    }
+}
+
+impl fmt::Display for CaseMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            CaseMode::Sensitive => "sensitive",
+            CaseMode::Insensitive => "insensitive",
+            CaseMode::Smart => "smart",
+        })
+    }
+}
+
+/// Compares a single query term with items and text.
+pub trait Matcher {
+    /// Whether the term matches the item's name (or path, for path globs).
+    fn matches_item(&self, item: &SearchItem) -> bool;
+
+    /// Whether the term appears in free text such as a description.
+    fn matches_text(&self, text: &str) -> bool;
+
+    /// How well the term matches the item, higher being better, for ranking
+    /// results; 0 if it doesn't match or the matcher doesn't rank.
+    fn score(&self, _item: &SearchItem) -> u32 {
+        0
+    }
+
+    /// The items in `names` whose name the term could match, found in one
+    /// pass over the buffer, so [`Matcher::matches_item`] only needs to run
+    /// on those; `None` if the term can't be narrowed down this way.
+    fn candidates(&self, _names: &NameBuffer) -> Option<Candidates> {
+        None
+    }
+}
+
+/// Which [`Matcher`] query terms use.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MatcherKind {
+    /// [`TermMatcher`]: substrings ignoring underscores, as rustdoc's search
+    /// does, or globs
+    #[default]
+    Rustdoc,
+    /// [`FuzzyMatcher`]: fzf-style fuzzy matching, ranking the best matches first
+    Nucleo,
+}
+
+impl MatcherKind {
+    /// The matcher for `term`. Globs are matched as globs with either kind.
+    pub fn matcher(self, term: &str, case: CaseMode) -> Result<Box<dyn Matcher>, GlobError> {
+        match self {
+            MatcherKind::Nucleo if !Glob::is_glob(term) => {
+                Ok(Box::new(FuzzyMatcher::new(term, case)))
+            }
+            _ => Ok(Box::new(TermMatcher::new(term, case)?)),
+        }
+    }
+
+    /// Whether results should be ordered by [`Matcher::score`].
+    pub fn ranks(self) -> bool {
+        self == MatcherKind::Nucleo
+    }
+}
+
+impl FromStr for MatcherKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "rustdoc" => Ok(MatcherKind::Rustdoc),
+            "nucleo" => Ok(MatcherKind::Nucleo),
+            _ => Err(format!(
+                "unknown matcher \"{}\" (expected rustdoc or nucleo)",
+                s
+            )),
+        }
+    }
+}
+
+impl fmt::Display for MatcherKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            MatcherKind::Rustdoc => "rustdoc",
+            MatcherKind::Nucleo => "nucleo",
+        })
+    }
+}
+
+/// How a single query term is compared with an item, the way rustdoc's web
+/// search does.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TermMatcher {
+    pattern: Pattern,
+    case_sensitive: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Pattern {
+    /// The term appears anywhere in the name, ignoring underscores in both (as
+    /// rustdoc's web search does, so `readtostring` finds `read_to_string`)
+    Substring { term: String, normalized: String },
+    /// The whole name matches a glob. Globs containing `::` are matched
+    /// against the item's full path (`module::path::name`) instead.
+    Glob { glob: Glob, match_path: bool },
+}
+
+impl TermMatcher {
+    /// Build the matcher for a term: a glob if it uses glob syntax, otherwise a substring.
+    pub fn new(term: &str, case: CaseMode) -> Result<Self, GlobError> {
+        // rustdoc indexes `r#try` as `try`
+        let term = strip_raw(term);
+        let case_sensitive = case.is_sensitive(term);
+        let term = if case_sensitive {
+            term.to_string()
+        } else {
+            fold_case(term)
+        };
+
+        let pattern = if Glob::is_glob(&term) {
+            Pattern::Glob {
+                glob: Glob::new(&crate_path(&term))?,
+                match_path: term.contains("::"),
+            }
+        } else {
+            Pattern::Substring {
+                normalized: normalize(&term),
+                term,
+            }
+        };
+
+        Ok(Self {
+            pattern,
+            case_sensitive,
+        })
+    }
+
+    fn fold_case(&self, text: &str) -> String {
+        if self.case_sensitive {
+            text.to_string()
+        } else {
+            fold_case(text)
+        }
+    }
+}
+
+impl Matcher for TermMatcher {
+    fn matches_item(&self, item: &SearchItem) -> bool {
+        match &self.pattern {
+            // normalized_name is already case folded with underscores removed
+            Pattern::Substring { normalized, .. } if !self.case_sensitive => {
+                item.normalized_name.contains(normalized.as_str())
+            }
+            Pattern::Substring { normalized, .. } => {
+                normalize(&item.name).contains(normalized.as_str())
+            }
+            Pattern::Glob {
+                glob,
+                match_path: false,
+            } => glob.is_match(&self.fold_case(&item.name)),
+            Pattern::Glob {
+                glob,
+                match_path: true,
+            } => glob.is_match(&self.fold_case(&format!("{}::{}", item.path, item.name))),
+        }
+    }
+
+    /// Globs only apply to names, so they never match text.
+    fn matches_text(&self, text: &str) -> bool {
+        match &self.pattern {
+            Pattern::Substring { term, .. } => self.fold_case(text).contains(term.as_str()),
+            Pattern::Glob { .. } => false,
+        }
+    }
+
+    fn candidates(&self, names: &NameBuffer) -> Option<Candidates> {
+        match &self.pattern {
+            Pattern::Substring { normalized, .. } if !self.case_sensitive => {
+                Some(names.containing(normalized))
+            }
+            // The buffer is case folded, so this finds a superset to check exactly
+            Pattern::Substring { normalized, .. } => Some(names.containing(&fold_case(normalized))),
+            Pattern::Glob { .. } => None,
+        }
+    }
+}
+
+/// fzf-style fuzzy matching with nucleo: the term's characters appear in the
+/// name in order, not necessarily together (`hsmp` finds `HashMap`), scored
+/// by how close together and how near word starts they are.
+///
+/// Descriptions are matched by substring, since nearly any term is
+/// scattered somewhere through a paragraph.
+pub struct FuzzyMatcher {
+    /// The term as normalized names are, for narrowing down candidates
+    normalized: String,
+    name: Atom,
+    text: Atom,
+    /// nucleo's scratch space, reused between items
+    matcher: RefCell<nucleo_matcher::Matcher>,
+}
+
+impl FuzzyMatcher {
+    pub fn new(term: &str, case: CaseMode) -> Self {
+        // rustdoc indexes `r#try` as `try`
+        let term = strip_raw(term);
+        let case = if case.is_sensitive(term) {
+            CaseMatching::Respect
+        } else {
+            CaseMatching::Ignore
+        };
+        let atom = |kind| Atom::new(term, case, Normalization::Smart, kind, false);
+        Self {
+            normalized: normalize_name(term),
+            name: atom(AtomKind::Fuzzy),
+            text: atom(AtomKind::Substring),
+            matcher: RefCell::new(nucleo_matcher::Matcher::new(Config::DEFAULT)),
+        }
+    }
+
+    fn score_text(&self, atom: &Atom, text: &str) -> Option<u16> {
+        let mut chars = Vec::new();
+        atom.score(
+            Utf32Str::new(text, &mut chars),
+            &mut self.matcher.borrow_mut(),
+        )
+    }
+}
+
+impl Matcher for FuzzyMatcher {
+    fn matches_item(&self, item: &SearchItem) -> bool {
+        self.score_text(&self.name, &item.name).is_some()
+    }
+
+    fn matches_text(&self, text: &str) -> bool {
+        self.score_text(&self.text, text).is_some()
+    }
+
+    fn score(&self, item: &SearchItem) -> u32 {
+        self.score_text(&self.name, &item.name).map_or(0, u32::from)
+    }
+
+    fn candidates(&self, names: &NameBuffer) -> Option<Candidates> {
+        names.subsequence(&self.normalized)
+    }
+}
+
+impl fmt::Debug for FuzzyMatcher {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("FuzzyMatcher")
+            .field("name", &self.name)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Where rustdoc's web search lists an item among the results of a one-word
+/// query: the criteria of `sortResults` in search.js, most important first,
+/// so sorting by this orders results as the browser does. Lower sorts first.
+///
+/// Only name matches are covered; rustdoc's preferred crate (the crate whose
+/// docs are open) has no equivalent here.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct RustdocRank {
+    /// The query has uppercase letters and the name isn't exactly the query
+    case_differs: bool,
+    /// The name isn't the query, ignoring case
+    inexact: bool,
+    /// The name doesn't contain the query (a match through an alias)
+    not_contained: bool,
+    /// Where the query starts in the name, ignoring underscores
+    position: usize,
+    /// Edit distance from the query, capped at a third of the query's length
+    /// plus one as search.js caps it
+    distance: usize,
+    deprecated: bool,
+    name_length: usize,
+    lowercase_name: String,
+    empty_description: bool,
+    type_code: u8,
+    path: String,
+}
+
+impl RustdocRank {
+    /// The rank of `item` among the results of searching for `term`.
+    pub fn new(term: &str, item: &SearchItem) -> Self {
+        let term = strip_raw(term);
+        let lowercase_term = term.to_lowercase();
+        let lowercase_name = item.name.to_lowercase();
+        let normalized_term = normalize(&lowercase_term);
+        let normalized_name = normalize(&lowercase_name);
+
+        // search.js counts positions and lengths in UTF-16 code units
+        let utf16_len = |text: &str| text.encode_utf16().count();
+        let position = normalized_name.find(&normalized_term);
+        let limit = utf16_len(&lowercase_term) / 3;
+        // A name containing the term is exactly as far from it as it is longer
+        let distance = match position {
+            Some(_) => (utf16_len(&normalized_name) - utf16_len(&normalized_term)).min(limit + 1),
+            None => limit + 1,
+        };
+
+        Self {
+            case_differs: lowercase_term != term && item.name != term,
+            inexact: lowercase_name != lowercase_term,
+            not_contained: position.is_none(),
+            position: position.map_or(0, |position| utf16_len(&normalized_name[..position])),
+            distance,
+            deprecated: item.deprecated,
+            name_length: utf16_len(&lowercase_name),
+            lowercase_name,
+            empty_description: item.empty_description,
+            type_code: item.item_type.code(),
+            path: item.path.clone(),
+        }
+    }
+}
+
+/// Remove underscores, the same normalization rustdoc applies to names.
+fn normalize(name: &str) -> String {
+    name.replace('_', "")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::search_index::ItemType;
+
+    fn item(path: &str, name: &str) -> SearchItem {
+        SearchItem::builder("std", ItemType::Function, name)
+            .path(path)
+            .build()
+    }
+
+    #[test]
+    fn test_substring_matcher() {
+        let matcher = TermMatcher::new("string", CaseMode::Smart).unwrap();
+        assert!(matcher.matches_item(&item("std::fs", "read_to_string")));
+        assert!(matcher.matches_text("Reads a file into a String."));
+    }
+
+    #[test]
+    fn test_glob_matchers() {
+        let name_glob = TermMatcher::new("read_*", CaseMode::Smart).unwrap();
+        assert!(name_glob.matches_item(&item("std::fs", "read_to_string")));
+        assert!(!name_glob.matches_item(&item("std::fs", "try_read")));
+        assert!(!name_glob.matches_text("read_to_string"));
+
+        let path_glob = TermMatcher::new("std::fs::*", CaseMode::Smart).unwrap();
+        assert!(path_glob.matches_item(&item("std::fs", "write")));
+        assert!(!path_glob.matches_item(&item("std::io", "write")));
+        let crate_glob = TermMatcher::new("serde-json::*", CaseMode::Smart).unwrap();
+        assert!(crate_glob.matches_item(&item("serde_json", "to_string")));
+
+        assert!(TermMatcher::new("[ab", CaseMode::Smart).is_err());
+    }
+
+    #[test]
+    fn test_case_modes() {
+        let read_trait = item("std::io", "Read");
+        let read_fn = item("std::fs", "read");
+
+        let matches = |term: &str, case: CaseMode, item: &SearchItem| {
+            TermMatcher::new(term, case).unwrap().matches_item(item)
+        };
+
+        // Smart case: lowercase ignores case, mixed case is exact
+        assert!(matches("read", CaseMode::Smart, &read_trait));
+        assert!(matches("Read", CaseMode::Smart, &read_trait));
+        assert!(!matches("Read", CaseMode::Smart, &read_fn));
+
+        assert!(!matches("read", CaseMode::Sensitive, &read_trait));
+        assert!(matches("Read", CaseMode::Insensitive, &read_fn));
+        assert!(matches("R*", CaseMode::Smart, &read_trait));
+        assert!(!matches("R*", CaseMode::Smart, &read_fn));
+    }
+
+    #[test]
+    fn test_underscore_insensitive_names() {
+        let read_to_string = item("std::fs", "read_to_string");
+        let matches = |term: &str, case: CaseMode| {
+            TermMatcher::new(term, case)
+                .unwrap()
+                .matches_item(&read_to_string)
+        };
+
+        assert!(matches("readtostring", CaseMode::Smart));
+        assert!(matches("read_tostring", CaseMode::Sensitive));
+        assert!(matches("ReadToString", CaseMode::Insensitive));
+        assert!(!matches("ReadToString", CaseMode::Smart));
+        // Globs match the name as written
+        assert!(!matches("readto*", CaseMode::Smart));
+    }
+
+    #[test]
+    fn test_non_ascii_names() {
+        let strasse = item("geo", "Straße_Name");
+        let sigma = item("greek", "ΣΟΦΟΣ");
+        let crab = item("ferris", "🦀_crab");
+        let matches = |term: &str, item: &SearchItem| {
+            TermMatcher::new(term, CaseMode::Smart)
+                .unwrap()
+                .matches_item(item)
+        };
+
+        // Case folding, not just lowercasing: ß is ss, final sigma is sigma
+        assert!(matches("strassename", &strasse));
+        assert!(matches("straßename", &strasse));
+        assert!(matches("σοφος", &sigma));
+        assert!(!matches("Strasse", &strasse));
+        assert!(matches("🦀crab", &crab));
+        assert!(matches("🦀_*", &crab));
+        assert!(matches("?_crab", &crab));
+        assert!(
+            TermMatcher::new("Straße is a street", CaseMode::Insensitive)
+                .unwrap()
+                .matches_text("STRASSE IS A STREET")
+        );
+        // Raw identifiers are indexed without their r#
+        assert!(matches("r#try", &item("std", "try")));
+    }
+
+    #[test]
+    fn test_fuzzy_matcher() {
+        let hash_map = item("std::collections", "HashMap");
+        let matcher = MatcherKind::Nucleo
+            .matcher("hsmp", CaseMode::Smart)
+            .unwrap();
+        assert!(matcher.matches_item(&hash_map));
+        assert!(!matcher.matches_item(&item("std::collections", "BTreeMap")));
+        assert!(
+            !MatcherKind::Rustdoc
+                .matcher("hsmp", CaseMode::Smart)
+                .unwrap()
+                .matches_item(&hash_map)
+        );
+
+        // Closer matches score higher
+        let matcher = MatcherKind::Nucleo.matcher("map", CaseMode::Smart).unwrap();
+        let exact = item("std::iter", "Map");
+        let scattered = item("std::io", "MemoryAccessPolicy");
+        assert!(matcher.matches_item(&scattered));
+        assert!(matcher.score(&exact) > matcher.score(&scattered));
+        assert_eq!(matcher.score(&item("std", "read")), 0);
+
+        // Descriptions need the term whole; globs stay globs
+        assert!(matcher.matches_text("Maps each element"));
+        assert!(!matcher.matches_text("Makes a pair"));
+        let glob = MatcherKind::Nucleo
+            .matcher("Hash*", CaseMode::Smart)
+            .unwrap();
+        assert!(glob.matches_item(&hash_map));
+        assert!(!glob.matches_item(&item("std::collections", "BHashMap")));
+    }
+
+    #[test]
+    fn test_rustdoc_rank() {
+        let ranked = |term: &str, names: &[&str]| {
+            let mut items: Vec<SearchItem> = names.iter().map(|name| item("std", name)).collect();
+            items.sort_by_cached_key(|item| RustdocRank::new(term, item));
+            items.into_iter().map(|item| item.name).collect::<Vec<_>>()
+        };
+
+        // Exact names first, then earlier and closer matches, then shorter names
+        assert_eq!(
+            ranked(
+                "map",
+                &["try_map", "map_or_else", "Map", "map_or", "BTreeMap", "map"]
+            ),
+            ["Map", "map", "map_or", "map_or_else", "try_map", "BTreeMap"]
+        );
+        // Queries with uppercase letters put their exact case first
+        assert_eq!(ranked("Map", &["map", "Map"]), ["Map", "map"]);
+        // Underscores are ignored in positions and distances
+        assert_eq!(
+            ranked("tostring", &["to_string_lossy", "to_string", "into_string"]),
+            ["to_string", "to_string_lossy", "into_string"]
+        );
+
+        let mut deprecated = item("std", "map");
+        deprecated.deprecated = true;
+        assert!(
+            RustdocRank::new("map", &item("std", "map")) < RustdocRank::new("map", &deprecated)
+        );
+        assert!(
+            RustdocRank::new("map", &item("core", "map"))
+                < RustdocRank::new("map", &item("std", "map"))
+        );
+    }
+
+    #[test]
+    fn test_parse_matcher_kind() {
+        assert_eq!("nucleo".parse::<MatcherKind>(), Ok(MatcherKind::Nucleo));
+        assert_eq!(MatcherKind::default().to_string(), "rustdoc");
+        assert!("fzf".parse::<MatcherKind>().is_err());
+    }
+
+    #[test]
+    fn test_parse_case_mode() {
+        assert_eq!("smart".parse::<CaseMode>(), Ok(CaseMode::Smart));
+        assert_eq!("sensitive".parse::<CaseMode>(), Ok(CaseMode::Sensitive));
+        assert!("upper".parse::<CaseMode>().is_err());
+    }
+}
@@ -0,0 +1,164 @@
+// How many methods, fields and variants types and traits have
+
+use crate::human::{count, number};
+use crate::search_index::ItemType;
+use crate::search_items::SearchItem;
+use std::collections::{HashMap, HashSet};
+
+/// The members of one type or trait, by kind.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Members {
+    /// Trait methods without a default body
+    pub required_methods: usize,
+    /// Trait methods with a default body, or a type's methods
+    pub methods: usize,
+    pub fields: usize,
+    pub variants: usize,
+    /// Associated types and constants
+    pub associated: usize,
+}
+
+impl Members {
+    /// The counts as a short phrase for a listing, e.g. `3 fields, 12 methods`
+    /// for a struct or `1 required, 75 provided methods` for a trait; `None`
+    /// when there are no members.
+    pub fn summary(&self, item_type: ItemType) -> Option<String> {
+        let nonzero = |n: usize, one: &str, many: &str| (n > 0).then(|| count(n, one, many));
+        let parts: Vec<String> = if item_type == ItemType::Trait {
+            let methods = match (self.required_methods, self.methods) {
+                (0, provided) => nonzero(provided, "provided method", "provided methods"),
+                (required, 0) => nonzero(required, "required method", "required methods"),
+                (required, provided) => Some(format!(
+                    "{} required, {}",
+                    number(required),
+                    count(provided, "provided method", "provided methods")
+                )),
+            };
+            [
+                nonzero(self.associated, "associated item", "associated items"),
+                methods,
+            ]
+            .into_iter()
+            .flatten()
+            .collect()
+        } else {
+            [
+                nonzero(self.fields, "field", "fields"),
+                nonzero(self.variants, "variant", "variants"),
+                nonzero(self.associated, "associated item", "associated items"),
+                nonzero(self.methods, "method", "methods"),
+            ]
+            .into_iter()
+            .flatten()
+            .collect()
+        };
+        (!parts.is_empty()).then(|| parts.join(", "))
+    }
+}
+
+/// The members of every type and trait in an index, found from each item's
+/// parent. Members are counted by name, so a method listed more than once
+/// (`from` for several `From` impls, or an index listing it under two paths
+/// to the type) counts once.
+#[derive(Debug, Default)]
+pub struct MemberCounts<'a> {
+    /// By the parent's crate, module path, type and name
+    counts: HashMap<(&'a str, &'a str, ItemType, &'a str), Members>,
+}
+
+impl<'a> MemberCounts<'a> {
+    pub fn new(items: impl IntoIterator<Item = &'a SearchItem>) -> Self {
+        let mut counts: HashMap<_, Members> = HashMap::new();
+        let mut seen = HashSet::new();
+        for item in items {
+            let Some(parent) = item.parent() else {
+                continue;
+            };
+            let parent = (
+                item.crate_name.as_str(),
+                item.path.as_str(),
+                parent.item_type,
+                parent.name.as_str(),
+            );
+            if !seen.insert((parent, item.item_type, item.name.as_str())) {
+                continue;
+            }
+            let members = counts.entry(parent).or_default();
+            match item.item_type {
+                ItemType::TyMethod => members.required_methods += 1,
+                ItemType::Method => members.methods += 1,
+                ItemType::StructField => members.fields += 1,
+                ItemType::Variant => members.variants += 1,
+                ItemType::AssocType | ItemType::AssocConst => members.associated += 1,
+                _ => {}
+            }
+        }
+        Self { counts }
+    }
+
+    /// The members of `item`, which are none for items other than types and
+    /// traits.
+    pub fn of(&self, item: &SearchItem) -> Members {
+        self.counts
+            .get(&(
+                item.crate_name.as_str(),
+                item.path.as_str(),
+                item.item_type,
+                item.name.as_str(),
+            ))
+            .copied()
+            .unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::search_index::{extract_json_string, parse_search_index};
+    use crate::search_items::decode_crate;
+
+    #[test]
+    fn test_member_counts() {
+        let content = std::fs::read_to_string("tests/fixtures/search-index.js").unwrap();
+        let entries = parse_search_index(&extract_json_string(&content).unwrap()).unwrap();
+        let serde_json = entries
+            .iter()
+            .find(|entry| entry.name == "serde_json")
+            .unwrap();
+        let items = decode_crate(&serde_json.name, &serde_json.data);
+        let counts = MemberCounts::new(&items);
+
+        let find = |name: &str, item_type: ItemType| {
+            items
+                .iter()
+                .find(|item| item.name == name && item.item_type == item_type)
+                .unwrap()
+        };
+        let value = counts.of(find("Value", ItemType::Enum));
+        assert!(value.variants >= 6 && value.methods > 0, "{:?}", value);
+        assert_eq!(
+            counts.of(find("to_string", ItemType::Function)),
+            Members::default()
+        );
+
+        let trait_members = Members {
+            required_methods: 1,
+            methods: 75,
+            ..Members::default()
+        };
+        assert_eq!(
+            trait_members.summary(ItemType::Trait).unwrap(),
+            "1 required, 75 provided methods"
+        );
+        let struct_members = Members {
+            fields: 1,
+            methods: 3,
+            ..Members::default()
+        };
+        assert_eq!(
+            struct_members.summary(ItemType::Struct).unwrap(),
+            "1 field, 3 methods"
+        );
+        assert_eq!(Members::default().summary(ItemType::Struct), None);
+    }
+}
@@ -0,0 +1,151 @@
+// The items a module lists on its rustdoc index page, by section
+
+use crate::search_index::ItemType;
+use crate::search_items::SearchItem;
+
+/// One section of a module's index page: its items of one type, by name.
+#[derive(Debug)]
+pub struct Section<'a> {
+    pub item_type: ItemType,
+    pub items: Vec<&'a SearchItem>,
+}
+
+impl Section<'_> {
+    /// The section's heading on rustdoc's module pages, e.g. `Type Aliases`.
+    pub fn heading(&self) -> &'static str {
+        section_heading(self.item_type)
+    }
+}
+
+/// The public items of the module at `module_path` (e.g. `serde::de`, or a
+/// crate name for its root), grouped into sections in the order rustdoc's
+/// module pages list them.
+///
+/// Items re-exported into the module are listed as its own, as rustdoc lists
+/// inlined re-exports; members of types (methods, fields, variants) aren't
+/// listed.
+pub fn module_sections<'a>(items: &'a [SearchItem], module_path: &str) -> Vec<Section<'a>> {
+    let mut listed: Vec<&SearchItem> = items
+        .iter()
+        .filter(|item| {
+            item.path == module_path
+                && item.parent_name().is_none()
+                && !matches!(item.item_type, ItemType::Impl | ItemType::Import)
+        })
+        .collect();
+    listed.sort_by(|a, b| {
+        (section_order(a.item_type), &a.name, a.item_type.code()).cmp(&(
+            section_order(b.item_type),
+            &b.name,
+            b.item_type.code(),
+        ))
+    });
+    // The same item can be in the index more than once, e.g. from each crate
+    // re-exporting it here
+    listed.dedup_by(|a, b| a.name == b.name && a.item_type == b.item_type);
+
+    let mut sections: Vec<Section> = Vec::new();
+    for item in listed {
+        match sections.last_mut() {
+            Some(section)
+                if section_heading(section.item_type) == section_heading(item.item_type) =>
+            {
+                section.items.push(item);
+            }
+            _ => sections.push(Section {
+                item_type: item.item_type,
+                items: vec![item],
+            }),
+        }
+    }
+    sections
+}
+
+/// Where rustdoc puts a section on module pages, following its own ordering.
+fn section_order(item_type: ItemType) -> u8 {
+    use ItemType::*;
+    match item_type {
+        ExternCrate => 0,
+        Import => 1,
+        Primitive => 2,
+        Module => 3,
+        Macro => 4,
+        Struct => 5,
+        Enum => 6,
+        Constant => 7,
+        Static => 8,
+        Trait | TraitAlias => 9,
+        Function => 10,
+        Typedef => 11,
+        Union => 12,
+        ProcAttribute => 13,
+        ProcDerive => 14,
+        _ => 15,
+    }
+}
+
+/// The heading of the module page section listing items of `item_type`.
+pub fn section_heading(item_type: ItemType) -> &'static str {
+    use ItemType::*;
+    match item_type {
+        Module => "Modules",
+        ExternCrate => "Crates",
+        Import => "Re-exports",
+        Struct => "Structs",
+        Union => "Unions",
+        Enum => "Enums",
+        Function => "Functions",
+        Typedef => "Type Aliases",
+        Static => "Statics",
+        Trait | TraitAlias => "Traits",
+        Macro => "Macros",
+        Primitive => "Primitive Types",
+        Constant => "Constants",
+        ForeignType => "Foreign Types",
+        Keyword => "Keywords",
+        ProcAttribute => "Attribute Macros",
+        ProcDerive => "Derive Macros",
+        _ => "Other Items",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::search_index::{extract_json_string, parse_search_index};
+    use crate::search_items::decode_crate;
+
+    #[test]
+    fn test_module_sections() {
+        let content = std::fs::read_to_string("tests/fixtures/search-index.js").unwrap();
+        let entries = parse_search_index(&extract_json_string(&content).unwrap()).unwrap();
+        let items: Vec<SearchItem> = entries
+            .iter()
+            .flat_map(|entry| decode_crate(&entry.name, &entry.data))
+            .collect();
+
+        let sections = module_sections(&items, "serde::de");
+        assert_eq!(sections[0].heading(), "Modules");
+        assert!(
+            sections
+                .windows(2)
+                .all(|pair| section_order(pair[0].item_type) < section_order(pair[1].item_type))
+        );
+
+        let traits = sections
+            .iter()
+            .find(|section| section.heading() == "Traits")
+            .unwrap();
+        assert!(traits.items.iter().any(|item| item.name == "Deserializer"));
+        let names: Vec<&str> = traits.items.iter().map(|item| item.name.as_str()).collect();
+        assert!(names.is_sorted());
+        for section in &sections {
+            for item in &section.items {
+                assert_eq!(item.path, "serde::de");
+                assert!(item.parent_name().is_none());
+            }
+        }
+
+        assert!(module_sections(&items, "serde::no_such_module").is_empty());
+    }
+}
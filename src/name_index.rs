@@ -0,0 +1,194 @@
+// A finite-state transducer over item names, for prefix lookups without the index
+
+use crate::normalize::normalize_name;
+use crate::search_items::SearchItem;
+use fst::automaton::{Automaton, Levenshtein, Str};
+use fst::{IntoStreamer, Map, MapBuilder, Streamer};
+use std::collections::BTreeMap;
+
+/// Key of the stamp entry. No UTF-8 name contains the byte 0xFF, so it can't
+/// collide with a name, and it sorts after all of them.
+const STAMP_KEY: &[u8] = &[0xFF];
+
+/// Separates a key's normalized name from the name as written
+const SEPARATOR: u8 = 0;
+
+/// Every distinct item name in an index, as an FST mapping
+/// `normalized name \0 name` to the number of items with that name.
+///
+/// It's small and needs no decoding to query, so it can be written to the
+/// cache and answer prefix lookups (e.g. shell completions) in a fraction of
+/// the time decoding the search index takes. A stamp identifying the index it
+/// was built from tells when it's out of date.
+pub struct NameIndex {
+    map: Map<Vec<u8>>,
+}
+
+/// A name a prefix lookup found.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Completion {
+    /// The name as written, e.g. `read_to_string`
+    pub name: String,
+    /// How many items have this name
+    pub items: u64,
+}
+
+impl NameIndex {
+    /// Build the index of `items`' names, recording `stamp` to identify the
+    /// search index they come from.
+    pub fn build(items: &[SearchItem], stamp: u64) -> Result<Self, fst::Error> {
+        let mut names: BTreeMap<Vec<u8>, u64> = BTreeMap::new();
+        for item in items {
+            *names
+                .entry(key(&item.normalized_name, &item.name))
+                .or_default() += 1;
+        }
+        let mut builder = MapBuilder::memory();
+        for (key, items) in names {
+            builder.insert(key, items)?;
+        }
+        builder.insert(STAMP_KEY, stamp)?;
+        Self::from_bytes(builder.into_inner()?)
+    }
+
+    /// An index previously written out with [`NameIndex::as_bytes`].
+    pub fn from_bytes(bytes: Vec<u8>) -> Result<Self, fst::Error> {
+        Ok(Self {
+            map: Map::new(bytes)?,
+        })
+    }
+
+    pub fn as_bytes(&self) -> &[u8] {
+        self.map.as_fst().as_bytes()
+    }
+
+    /// The stamp given to [`NameIndex::build`].
+    pub fn stamp(&self) -> Option<u64> {
+        self.map.get(STAMP_KEY)
+    }
+
+    /// How many distinct names there are.
+    pub fn len(&self) -> usize {
+        self.map.len() - usize::from(self.stamp().is_some())
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Up to `limit` names starting with `prefix`, compared the way rustdoc's
+    /// search compares names (ignoring case and underscores), in order.
+    pub fn prefix(&self, prefix: &str, limit: usize) -> Vec<Completion> {
+        let normalized = normalize_name(prefix);
+        self.collect(Str::new(&normalized).starts_with(), limit)
+    }
+
+    /// Like [`NameIndex::prefix`], but allowing typos: names starting with
+    /// something a few edits away from `prefix` (none for up to 3 characters,
+    /// one for up to 6, two beyond).
+    pub fn fuzzy_prefix(&self, prefix: &str, limit: usize) -> Vec<Completion> {
+        let normalized = normalize_name(prefix);
+        let distance = match normalized.chars().count() {
+            0..=3 => 0,
+            4..=6 => 1,
+            _ => 2,
+        };
+        // Long queries can make the automaton too big to build; allow fewer
+        // edits then
+        for distance in (1..=distance).rev() {
+            if let Ok(automaton) = Levenshtein::new(&normalized, distance) {
+                return self.collect(automaton.starts_with(), limit);
+            }
+        }
+        self.prefix(prefix, limit)
+    }
+
+    fn collect<A: Automaton>(&self, automaton: A, limit: usize) -> Vec<Completion> {
+        let mut stream = self.map.search(automaton).into_stream();
+        let mut found = Vec::new();
+        while found.len() < limit {
+            let Some((key, items)) = stream.next() else {
+                break;
+            };
+            if key == STAMP_KEY {
+                continue;
+            }
+            let name = key
+                .iter()
+                .position(|&byte| byte == SEPARATOR)
+                .map_or(key, |separator| &key[separator + 1..]);
+            found.push(Completion {
+                name: String::from_utf8_lossy(name).into_owned(),
+                items,
+            });
+        }
+        found
+    }
+}
+
+fn key(normalized: &str, name: &str) -> Vec<u8> {
+    let mut key = Vec::with_capacity(normalized.len() + 1 + name.len());
+    key.extend_from_slice(normalized.as_bytes());
+    key.push(SEPARATOR);
+    key.extend_from_slice(name.as_bytes());
+    key
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::search_index::{extract_json_string, parse_search_index};
+    use crate::search_items::decode_crate;
+
+    fn fixture_items() -> Vec<SearchItem> {
+        let content = std::fs::read_to_string("tests/fixtures/search-index.js").unwrap();
+        let entries = parse_search_index(&extract_json_string(&content).unwrap()).unwrap();
+        entries
+            .iter()
+            .flat_map(|entry| decode_crate(&entry.name, &entry.data))
+            .collect()
+    }
+
+    #[test]
+    fn test_prefix() {
+        let items = fixture_items();
+        let index = NameIndex::build(&items, 42).unwrap();
+        assert_eq!(index.stamp(), Some(42));
+
+        let found = index.prefix("Deserial", 1000);
+        assert!(!found.is_empty());
+        assert!(
+            found
+                .iter()
+                .any(|completion| completion.name == "Deserialize")
+        );
+        for completion in &found {
+            assert!(normalize_name(&completion.name).starts_with("deserial"));
+            let count = items
+                .iter()
+                .filter(|item| item.name == completion.name)
+                .count();
+            assert_eq!(completion.items, count as u64);
+        }
+        assert_eq!(index.prefix("deserial", 2).len(), 2);
+        assert!(index.prefix("zzzz_not_a_name", 10).is_empty());
+
+        // A round trip through bytes keeps everything
+        let reloaded = NameIndex::from_bytes(index.as_bytes().to_vec()).unwrap();
+        assert_eq!(reloaded.len(), index.len());
+        assert_eq!(reloaded.prefix("Deserial", 1000), found);
+    }
+
+    #[test]
+    fn test_fuzzy_prefix() {
+        let index = NameIndex::build(&fixture_items(), 0).unwrap();
+        let found = index.fuzzy_prefix("Deserailize", 1000);
+        assert!(
+            found
+                .iter()
+                .any(|completion| completion.name == "Deserialize")
+        );
+        // Short prefixes are exact
+        assert_eq!(index.fuzzy_prefix("de", 1000), index.prefix("de", 1000));
+    }
+}
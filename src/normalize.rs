@@ -0,0 +1,116 @@
+// Unicode-aware case folding and normalization of names and query terms
+
+use std::borrow::Cow;
+
+/// Fold `text` for case-insensitive comparison.
+///
+/// Lowercasing alone leaves some letters that differ only in case unequal:
+/// `ß` and `SS`, or the final and medial forms of Greek sigma. This applies
+/// Unicode's default case folding on top of lowercasing for those letters, so
+/// `STRASSE` and `straße` fold to the same string.
+pub fn fold_case(text: &str) -> String {
+    let mut folded = String::with_capacity(text.len());
+    for c in text.chars().flat_map(char::to_lowercase) {
+        match c {
+            'ß' => folded.push_str("ss"),
+            c => folded.push(fold_char(c)),
+        }
+    }
+    folded
+}
+
+/// The folded form of letters whose lowercase isn't their case fold
+/// (the one-character `C` mappings of CaseFolding.txt that lowercasing misses).
+fn fold_char(c: char) -> char {
+    match c {
+        'ς' => 'σ',
+        'ſ' => 's',
+        'ϐ' => 'β',
+        'ϑ' => 'θ',
+        'ϕ' => 'φ',
+        'ϖ' => 'π',
+        'ϰ' => 'κ',
+        'ϱ' => 'ρ',
+        'ϵ' => 'ε',
+        '\u{345}' | '\u{1fbe}' => 'ι',
+        'ẛ' => 'ṡ',
+        c => c,
+    }
+}
+
+/// A name as rustdoc's search compares it: case folded, without underscores.
+pub fn normalize_name(name: &str) -> String {
+    fold_case(name).replace('_', "")
+}
+
+/// `ident` without a raw identifier's `r#` prefix; rustdoc indexes `r#try`
+/// as `try`.
+pub fn strip_raw(ident: &str) -> &str {
+    ident.strip_prefix("r#").unwrap_or(ident)
+}
+
+/// The name rustdoc documents a crate under. Package names may use `-`, which
+/// crate names can't, so the `serde-json` package is documented as `serde_json`.
+pub fn crate_name(name: &str) -> Cow<'_, str> {
+    if name.contains('-') {
+        Cow::Owned(name.replace('-', "_"))
+    } else {
+        Cow::Borrowed(name)
+    }
+}
+
+/// `path` with its first segment, the crate, named as rustdoc documents it:
+/// `serde-json::Value` is `serde_json::Value`. A `kind@` prefix is kept, and
+/// a path of one segment, a name, is left as it is.
+pub fn crate_path(path: &str) -> Cow<'_, str> {
+    let start = path.find('@').map_or(0, |at| at + 1);
+    match path[start..].split_once("::") {
+        Some((krate, rest)) if krate.contains('-') => {
+            Cow::Owned(format!("{}{}::{}", &path[..start], crate_name(krate), rest))
+        }
+        _ => Cow::Borrowed(path),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fold_case() {
+        assert_eq!(fold_case("STRASSE"), fold_case("straße"));
+        assert_eq!(fold_case("ΣΟΦΟΣ"), fold_case("σοφος"));
+        assert_eq!(fold_case("Größe"), "grösse");
+        assert_eq!(fold_case("ǅ"), "ǆ");
+        assert_eq!(fold_case("🦀Crab"), "🦀crab");
+    }
+
+    #[test]
+    fn test_normalize_name() {
+        assert_eq!(normalize_name("read_to_string"), "readtostring");
+        assert_eq!(normalize_name("Ünïcödé_Näme"), "ünïcödénäme");
+    }
+
+    #[test]
+    fn test_crate_names() {
+        assert_eq!(crate_name("serde-json"), "serde_json");
+        assert_eq!(crate_name("serde_json"), "serde_json");
+        assert_eq!(crate_path("serde-json::Value"), "serde_json::Value");
+        assert_eq!(crate_path("serde_json::Value"), "serde_json::Value");
+        assert_eq!(
+            crate_path("enum@serde-json::Value"),
+            "enum@serde_json::Value"
+        );
+        assert_eq!(crate_path("tokio-*::sync::*"), "tokio_*::sync::*");
+        // Only the crate: names and modules have no `-` to replace
+        assert_eq!(crate_path("a::b-c"), "a::b-c");
+        assert_eq!(crate_path("read-only"), "read-only");
+    }
+
+    #[test]
+    fn test_strip_raw() {
+        assert_eq!(strip_raw("r#try"), "try");
+        assert_eq!(strip_raw("try"), "try");
+        assert_eq!(strip_raw("r"), "r");
+    }
+}
@@ -0,0 +1,287 @@
+// OpenAPI description of the HTTP API `rdoc serve` provides
+
+use crate::schema::output_schema;
+use serde_json::{Value, json};
+
+/// The OpenAPI 3.1 document for `rdoc serve`, served at `GET /openapi.json`.
+///
+/// Its component schemas are the output schema's definitions, so responses
+/// are described once for both the CLI's JSON and the HTTP API.
+pub fn openapi_spec() -> Value {
+    let mut schemas = output_schema()["$defs"].take();
+    rewrite_refs(&mut schemas);
+    schemas["ApiError"] = json!({
+        "description": "Why a request failed",
+        "type": "object",
+        "properties": { "error": { "type": "string" } },
+        "required": ["error"],
+    });
+    let error = |description: &str| {
+        json!({
+            "description": description,
+            "content": {
+                "application/json": {
+                    "schema": { "$ref": "#/components/schemas/ApiError" },
+                },
+            },
+        })
+    };
+
+    let document = |schema: &str, description: &str| {
+        json!({
+            "description": description,
+            "content": {
+                "application/json": {
+                    "schema": { "$ref": format!("#/components/schemas/{}", schema) },
+                },
+            },
+        })
+    };
+
+    json!({
+        "openapi": "3.1.0",
+        "info": {
+            "title": "rdoc",
+            "description": "Lookups against a rustdoc search index decoded once by `rdoc serve`",
+            "version": env!("CARGO_PKG_VERSION"),
+        },
+        "paths": {
+            "/search": {
+                "get": {
+                    "operationId": "search",
+                    "summary": "Search items, as the scan command does",
+                    "parameters": [
+                        {
+                            "name": "q",
+                            "in": "query",
+                            "required": true,
+                            "description": "A scan query, e.g. read AND async",
+                            "schema": { "type": "string" },
+                        },
+                        {
+                            "name": "limit",
+                            "in": "query",
+                            "description": "Most results to answer with (default 50)",
+                            "schema": { "type": "integer", "minimum": 0 },
+                        },
+                        {
+                            "name": "timeout",
+                            "in": "query",
+                            "description": "Milliseconds the search may take before it stops with an error (0 for no limit; default serve's --search-timeout)",
+                            "schema": { "type": "integer", "minimum": 0 },
+                        },
+                        {
+                            "name": "session",
+                            "in": "query",
+                            "description": "An ID the client picks for its searches (or the X-Rdoc-Session header): a search still running stops when another comes in for the same session. Searches without one run to the end",
+                            "schema": { "type": "string" },
+                        },
+                    ],
+                    "responses": {
+                        "200": document("QueryResults", "The matching items, or why the query is invalid or stopped"),
+                        "400": error("q is missing, or limit or timeout isn't a number"),
+                    },
+                },
+            },
+            "/item": {
+                "get": {
+                    "operationId": "item",
+                    "summary": "Preview an item: its declaration and description",
+                    "parameters": [
+                        {
+                            "name": "id",
+                            "in": "query",
+                            "required": true,
+                            "description": "An item ID from another response, e.g. serde#142@local",
+                            "schema": { "type": "string" },
+                        },
+                    ],
+                    "responses": {
+                        "200": document("ItemPreview", "The item's preview"),
+                        "400": error("id is missing or isn't an item ID"),
+                        "404": error("No item has this ID"),
+                    },
+                },
+            },
+            "/resolve": {
+                "post": {
+                    "operationId": "resolve",
+                    "summary": "Resolve item paths, as the resolve command does",
+                    "requestBody": {
+                        "required": true,
+                        "content": {
+                            "application/json": {
+                                "schema": {
+                                    "description": "Item paths, e.g. serde::Serialize or fn@serde_json::from_str",
+                                    "type": "array",
+                                    "items": { "type": "string" },
+                                },
+                            },
+                        },
+                    },
+                    "responses": {
+                        "200": document("Resolutions", "Each path's outcome, in the order given"),
+                        "400": error("The body isn't a JSON array of strings"),
+                        "405": error("Only POST is accepted"),
+                        "413": error("The body is over 4 MiB"),
+                    },
+                },
+            },
+            "/scan": {
+                "post": {
+                    "operationId": "scan",
+                    "summary": "Answer an rdoc scan command line from the decoded index, as rdoc scan does when it finds this server running",
+                    "requestBody": {
+                        "required": true,
+                        "content": {
+                            "application/json": {
+                                "schema": {
+                                    "type": "object",
+                                    "properties": {
+                                        "args": {
+                                            "description": "rdoc's arguments without the program name, e.g. [\"scan\", \"Serialize\", \"--format\", \"json\"]",
+                                            "type": "array",
+                                            "items": { "type": "string" },
+                                        },
+                                        "search_index": {
+                                            "description": "The search index the client found, which must be the one served",
+                                            "type": "string",
+                                        },
+                                        "hyperlinks": {
+                                            "description": "Whether to link names in the colored listing to their pages",
+                                            "type": "boolean",
+                                        },
+                                    },
+                                    "required": ["args", "search_index", "hyperlinks"],
+                                },
+                            },
+                        },
+                    },
+                    "responses": {
+                        "200": {
+                            "description": "What rdoc scan prints after its status messages",
+                            "content": {
+                                "application/json": {
+                                    "schema": {
+                                        "type": "object",
+                                        "properties": {
+                                            "output": {
+                                                "description": "The results in the requested format, or the colored listing with ANSI colors",
+                                                "type": "string",
+                                            },
+                                            "results": { "type": "integer" },
+                                        },
+                                        "required": ["output", "results"],
+                                    },
+                                },
+                            },
+                        },
+                        "400": error("The body is invalid, or the scan failed (an invalid query, say)"),
+                        "405": error("Only POST is accepted"),
+                        "409": error("The server isn't serving that search index as it is now, e.g. while reloading it"),
+                        "422": error("Not a scan the server answers: one writing a file, running a formatter or reading stdin, or a -> Type query"),
+                    },
+                },
+            },
+            "/reload": {
+                "post": {
+                    "operationId": "reload",
+                    "summary": "Decode the docs again in the background, as when they're regenerated",
+                    "responses": {
+                        "202": {
+                            "description": "The reload started; requests are answered from the current index until it's done",
+                            "content": {
+                                "application/json": {
+                                    "schema": {
+                                        "type": "object",
+                                        "properties": {
+                                            "epoch": {
+                                                "description": "How many times the index currently in use was reloaded",
+                                                "type": "integer",
+                                            },
+                                        },
+                                        "required": ["epoch"],
+                                    },
+                                },
+                            },
+                        },
+                        "405": error("Only POST is accepted"),
+                    },
+                },
+            },
+            "/openapi.json": {
+                "get": {
+                    "operationId": "openapi",
+                    "summary": "This document",
+                    "responses": {
+                        "200": {
+                            "description": "The OpenAPI document",
+                            "content": { "application/json": { "schema": { "type": "object" } } },
+                        },
+                    },
+                },
+            },
+        },
+        "components": { "schemas": schemas },
+    })
+}
+
+/// Point the output schema's `#/$defs/...` references at the same definitions
+/// under `#/components/schemas/`.
+fn rewrite_refs(value: &mut Value) {
+    match value {
+        Value::Object(object) => {
+            for (key, value) in object.iter_mut() {
+                match value {
+                    Value::String(target) if key == "$ref" => {
+                        if let Some(name) = target.strip_prefix("#/$defs/") {
+                            *target = format!("#/components/schemas/{}", name);
+                        }
+                    }
+                    _ => rewrite_refs(value),
+                }
+            }
+        }
+        Value::Array(values) => values.iter_mut().for_each(rewrite_refs),
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Every `$ref` in `value`.
+    fn refs<'a>(value: &'a Value, found: &mut Vec<&'a str>) {
+        match value {
+            Value::Object(object) => {
+                for (key, value) in object {
+                    match value.as_str() {
+                        Some(target) if key == "$ref" => found.push(target),
+                        _ => refs(value, found),
+                    }
+                }
+            }
+            Value::Array(values) => values.iter().for_each(|value| refs(value, found)),
+            _ => {}
+        }
+    }
+
+    #[test]
+    fn test_refs_resolve() {
+        let spec = openapi_spec();
+        let mut found = Vec::new();
+        refs(&spec, &mut found);
+        assert!(found.contains(&"#/components/schemas/Resolutions"));
+        for target in found {
+            let name = target
+                .strip_prefix("#/components/schemas/")
+                .unwrap_or_else(|| panic!("{} points outside the components", target));
+            assert!(
+                spec["components"]["schemas"].get(name).is_some(),
+                "{} is not defined",
+                target
+            );
+        }
+    }
+}
@@ -0,0 +1,50 @@
+// Writing command output to files
+
+use color_eyre::{Result, eyre::Context};
+use std::ffi::OsString;
+use std::path::{Path, PathBuf};
+
+/// Write `contents` to `path` via a temporary file in the same directory, so
+/// readers never see a partially written file and an interrupted write leaves
+/// any previous version intact.
+pub fn write_atomic(path: &Path, contents: impl AsRef<[u8]>) -> Result<()> {
+    let temp_path = partial_path(path);
+    std::fs::write(&temp_path, contents)
+        .wrap_err_with(|| format!("Failed to write {}", temp_path.display()))?;
+    std::fs::rename(&temp_path, path)
+        .wrap_err_with(|| format!("Failed to write {}", path.display()))
+}
+
+/// `results.json` becomes `results.json.partial`.
+fn partial_path(path: &Path) -> PathBuf {
+    let mut file_name = path.file_name().map(OsString::from).unwrap_or_default();
+    file_name.push(".partial");
+    path.with_file_name(file_name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_partial_path() {
+        assert_eq!(
+            partial_path(Path::new("out/results.json")),
+            Path::new("out/results.json.partial")
+        );
+    }
+
+    #[test]
+    fn test_write_atomic_replaces_file() {
+        let dir = std::env::temp_dir().join(format!("rdoc-output-test-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("results.json");
+
+        write_atomic(&path, "old").unwrap();
+        write_atomic(&path, "new").unwrap();
+
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "new");
+        assert!(!partial_path(&path).exists());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
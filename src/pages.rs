@@ -0,0 +1,824 @@
+// Rustdoc HTML pages: where an item is documented and what its declaration says
+
+use crate::descriptions::plain_text;
+use crate::search_index::ItemType;
+use crate::search_items::SearchItem;
+use std::collections::HashMap;
+use std::fmt;
+
+/// Where an item is documented, relative to the documentation root.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ItemPage {
+    /// The HTML file, with `/` separators (e.g. `serde_json/enum.Value.html`)
+    pub file: String,
+    /// The anchor of the item's section for items documented on their parent's page
+    pub anchor: Option<String>,
+}
+
+impl ItemPage {
+    /// The page as a relative URL, including the anchor.
+    pub fn href(&self) -> String {
+        match &self.anchor {
+            Some(anchor) => format!("{}#{}", self.file, anchor),
+            None => self.file.clone(),
+        }
+    }
+}
+
+/// The name rustdoc uses for an item type in file names and anchors
+/// (`struct.Foo.html`, `#method.bar`).
+pub fn type_slug(item_type: ItemType) -> Option<&'static str> {
+    item_type.name()
+}
+
+/// The page an item is documented on, following the links rustdoc's search builds.
+///
+/// Returns `None` for item types that have no page of their own.
+pub fn item_page(item: &SearchItem) -> Option<ItemPage> {
+    let directory = item.path.replace("::", "/");
+    let slug = type_slug(item.item_type)?;
+
+    let page = match item.item_type {
+        ItemType::Module => ItemPage {
+            file: format!("{}/{}/index.html", directory, item.name),
+            anchor: None,
+        },
+        ItemType::ExternCrate => ItemPage {
+            file: format!("{}/index.html", item.name),
+            anchor: None,
+        },
+        ItemType::Import => return None,
+        _ => match item.parent() {
+            Some(parent) => {
+                let mut anchor = format!("{}.{}", slug, item.name);
+                if let Some(disambiguator) = &item.impl_disambiguator {
+                    anchor = format!("{}/{}", disambiguator, anchor);
+                }
+                ItemPage {
+                    file: format!(
+                        "{}/{}.{}.html",
+                        directory,
+                        type_slug(parent.item_type)?,
+                        parent.name
+                    ),
+                    anchor: Some(anchor),
+                }
+            }
+            _ => ItemPage {
+                file: format!("{}/{}.{}.html", directory, slug, item.name),
+                anchor: None,
+            },
+        },
+    };
+    Some(page)
+}
+
+/// The impl block an associated item is defined in, decoded from its impl
+/// disambiguator (the anchor of the impl's section on the parent's page).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ImplOrigin {
+    /// The implemented trait, e.g. `Iterator` or `AsRef<str>`; `None` for
+    /// inherent impls
+    pub trait_name: Option<String>,
+    /// The type the impl is for, e.g. `Lines<'a>`
+    pub self_type: String,
+}
+
+impl ImplOrigin {
+    /// Decode an impl disambiguator such as `impl-Iterator-for-Lines%3C'a%3E`.
+    ///
+    /// Rustdoc URL-encodes the impl's header, writing spaces as `+`, and
+    /// numbers repeated anchors on a page with a `-N` suffix.
+    pub fn parse(disambiguator: &str) -> Option<Self> {
+        let encoded = disambiguator.strip_prefix("impl-")?;
+        let encoded = match encoded.rsplit_once('-') {
+            Some((rest, number))
+                if !rest.is_empty() && number.bytes().all(|b| b.is_ascii_digit()) =>
+            {
+                rest
+            }
+            _ => encoded,
+        };
+        let header = urlencoding::decode(&encoded.replace('+', " "))
+            .ok()?
+            .into_owned();
+
+        // `-for-` inside the trait's generic arguments isn't the separator
+        let mut depth = 0i32;
+        for (i, c) in header.char_indices() {
+            match c {
+                '<' | '(' | '[' => depth += 1,
+                '>' | ')' | ']' => depth -= 1,
+                '-' if depth == 0 && header[i..].starts_with("-for-") => {
+                    return Some(Self {
+                        trait_name: Some(header[..i].to_string()),
+                        self_type: header[i + "-for-".len()..].to_string(),
+                    });
+                }
+                _ => {}
+            }
+        }
+        Some(Self {
+            trait_name: None,
+            self_type: header,
+        })
+    }
+}
+
+impl fmt::Display for ImplOrigin {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.trait_name {
+            Some(trait_name) => write!(f, "impl {} for {}", trait_name, self.self_type),
+            None => write!(f, "impl {}", self.self_type),
+        }
+    }
+}
+
+/// The impl block `item` comes from, for associated items rustdoc gave an impl
+/// disambiguator (those of trait impls, and of types with several impls).
+pub fn impl_origin(item: &SearchItem) -> Option<ImplOrigin> {
+    ImplOrigin::parse(item.impl_disambiguator.as_deref()?)
+}
+
+/// Where items' pages are published, for absolute links to them.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SiteLinks {
+    /// Root URL of the site every crate's pages are on
+    pub base_url: Option<String>,
+    /// Roots for single crates, used instead of `base_url` (e.g. docs.rs for
+    /// dependencies)
+    pub crate_base_urls: HashMap<String, String>,
+}
+
+impl SiteLinks {
+    /// Links to the site rooted at `base_url`.
+    pub fn new(base_url: Option<&str>) -> Self {
+        Self {
+            base_url: base_url.map(str::to_string),
+            crate_base_urls: HashMap::new(),
+        }
+    }
+
+    /// The absolute URL of `item`'s page, if it has one and its crate's site
+    /// is known.
+    pub fn absolute_url(&self, item: &SearchItem) -> Option<String> {
+        let base_url = self
+            .crate_base_urls
+            .get(&item.crate_name)
+            .or(self.base_url.as_ref())?;
+        let page = item_page(item)?;
+        Some(format!(
+            "{}/{}",
+            base_url.trim_end_matches('/'),
+            page.href()
+        ))
+    }
+}
+
+/// The item's declaration as text, taken from its page's HTML.
+///
+/// For an item with its own page this is the `item-decl` block; for an item
+/// documented on its parent's page (such as a method) it's the code header of
+/// the section with `anchor`.
+pub fn declaration(html: &str, anchor: Option<&str>) -> Option<String> {
+    let block = match anchor {
+        None => {
+            let start = html.find("class=\"rust item-decl\"")?;
+            let start = start + html[start..].find('>')? + 1;
+            let end = start + html[start..].find("</pre>")?;
+            &html[start..end]
+        }
+        Some(anchor) => {
+            let section = html.find(&format!("id=\"{}\"", anchor))?;
+            let header = section + html[section..].find("class=\"code-header\"")?;
+            let start = header + html[header..].find('>')? + 1;
+            // Methods' headers are h4s, variants' and fields' h3s
+            let end = start + html[start..].find("</h")?;
+            &html[start..end]
+        }
+    };
+
+    // Where clauses are put on their own line by CSS, or by `<br>` in older rustdoc
+    let block = block
+        .replace("<div class=\"where\">", "\n<div class=\"where\">")
+        .replace("<br>", "\n")
+        .replace("&nbsp;", " ");
+    let text = plain_text(&block);
+    let text = text.trim();
+    (!text.is_empty()).then(|| text.to_string())
+}
+
+/// The opening paragraphs of a page's documentation, as text: those before
+/// its first heading, code block or list, as a module index page shows
+/// above its items.
+pub fn intro(html: &str) -> Option<String> {
+    // The page's own docs come before its first section, and after its
+    // declaration on item pages
+    let page_end = html.find("<h2 id=\"").unwrap_or(html.len());
+    let start = html[..page_end].find("class=\"docblock")?;
+    let mut rest = &html[start + html[start..].find('>')? + 1..];
+
+    let mut paragraphs = Vec::new();
+    while let Some(paragraph) = rest.trim_start().strip_prefix("<p>") {
+        let end = paragraph.find("</p>")?;
+        let text = plain_text(&paragraph[..end]);
+        paragraphs.push(text.split_whitespace().collect::<Vec<_>>().join(" "));
+        rest = &paragraph[end + "</p>".len()..];
+    }
+    (!paragraphs.is_empty()).then(|| paragraphs.join("\n\n"))
+}
+
+/// The item's documentation as text: paragraphs, lists and headings on lines
+/// of their own, and code blocks fenced with ```.
+///
+/// For an item with its own page this is the page's top docblock; for an item
+/// documented on its parent's page it's the docblock under the section with
+/// `anchor`. Undocumented items have none.
+pub fn documentation(html: &str, anchor: Option<&str>) -> Option<String> {
+    let text = docblock_text(docblock(html, anchor)?);
+    (!text.is_empty()).then_some(text)
+}
+
+/// The runnable Rust examples in the item's documentation (found as for
+/// [`documentation`]), as code. Examples marked `ignore` or `compile_fail`
+/// are left out, as are other languages' code blocks.
+///
+/// The pages only show examples' visible lines: those doctests hide with `#`,
+/// such as `use`s and a wrapping `fn main`, aren't there.
+pub fn examples(html: &str, anchor: Option<&str>) -> Vec<String> {
+    let Some(mut rest) = docblock(html, anchor) else {
+        return Vec::new();
+    };
+    const PRE: &str = "<pre class=\"";
+    let mut examples = Vec::new();
+    while let Some(start) = rest.find(PRE) {
+        let block = &rest[start + PRE.len()..];
+        let Some(end) = block.find("</pre>") else {
+            break;
+        };
+        rest = &block[end..];
+        let Some((classes, code)) = block[..end].split_once('"') else {
+            continue;
+        };
+        let classes: Vec<&str> = classes.split_whitespace().collect();
+        let runnable = classes.contains(&"rust")
+            && !classes
+                .iter()
+                .any(|class| matches!(*class, "ignore" | "compile_fail"));
+        let Some((_, code)) = code.split_once("<code>").filter(|_| runnable) else {
+            continue;
+        };
+        let code = plain_text(code);
+        if !code.trim().is_empty() {
+            examples.push(code.trim_end().to_string());
+        }
+    }
+    examples
+}
+
+/// The HTML inside the item's docblock: the page's top one, or the one under
+/// the section with `anchor`.
+fn docblock<'a>(html: &'a str, anchor: Option<&str>) -> Option<&'a str> {
+    let start = match anchor {
+        // The page's own docs are in the "top-doc" toggle after its declaration
+        None => {
+            let top = html.find("top-doc")?;
+            top + html[top..].find("<div class=\"docblock")?
+        }
+        Some(anchor) => {
+            let section = html.find(&format!("id=\"{}\"", anchor))?;
+            let end = section + html[section..].find("</section>")? + "</section>".len();
+            let rest = html[end..].trim_start();
+            let rest = rest.strip_prefix("</summary>").unwrap_or(rest).trim_start();
+            // Docs inherited from a trait are quoted with '
+            if !rest.starts_with("<div class=\"docblock")
+                && !rest.starts_with("<div class='docblock")
+            {
+                return None;
+            }
+            html.len() - rest.len()
+        }
+    };
+    let contents = start + html[start..].find('>')? + 1;
+
+    // Docblocks hold divs of their own, around code examples
+    let mut depth = 1;
+    let mut position = contents;
+    let end = loop {
+        let close = position + html[position..].find("</div>")?;
+        match html[position..close].find("<div") {
+            Some(open) => {
+                depth += 1;
+                position += open + "<div".len();
+            }
+            None => {
+                depth -= 1;
+                if depth == 0 {
+                    break close;
+                }
+                position = close + "</div>".len();
+            }
+        }
+    };
+
+    Some(&html[contents..end])
+}
+
+/// A docblock's HTML as text, keeping its block structure.
+fn docblock_text(html: &str) -> String {
+    // Headings' "§" links and examples' "ⓘ" notes aren't part of the docs
+    let mut block = String::with_capacity(html.len());
+    let mut rest = html;
+    while let Some(start) = rest.find("<a ") {
+        let tag_end = start + rest[start..].find('>').unwrap_or(rest.len() - start);
+        let tag = &rest[start..tag_end];
+        block.push_str(&rest[..start]);
+        if tag.contains("class=\"doc-anchor\"") || tag.contains("class=\"tooltip") {
+            let end = rest[start..]
+                .find("</a>")
+                .map_or(rest.len(), |end| start + end + "</a>".len());
+            rest = &rest[end..];
+        } else {
+            block.push_str(tag);
+            rest = &rest[tag_end..];
+        }
+    }
+    block.push_str(rest);
+
+    let mut block = block
+        .replace("</p>", "\n\n")
+        .replace("<br>", "\n")
+        .replace("<li>", "- ")
+        .replace("</li>\n", "\n")
+        .replace("</li>", "\n")
+        .replace("</ul>", "\n")
+        .replace("</ol>", "\n")
+        .replace("<pre", "```\n<pre")
+        .replace("</pre>", "\n```\n\n");
+    for level in 1..=6 {
+        block = block.replace(&format!("</h{}>", level), "\n\n");
+    }
+
+    // Trim each line, and leave at most one blank line between blocks
+    let text = plain_text(&block);
+    let mut lines: Vec<&str> = Vec::new();
+    for line in text.lines().map(str::trim_end) {
+        if line.is_empty() && lines.last().is_none_or(|last| last.is_empty()) {
+            continue;
+        }
+        lines.push(line);
+    }
+    lines.join("\n").trim().to_string()
+}
+
+/// Cargo features the item requires, from rustdoc's `#[doc(cfg(...))]` notes
+/// ("Available on crate feature `tls` only").
+///
+/// Items on their parent's page (`anchor` set) also need the parent's features.
+pub fn required_features(html: &str, anchor: Option<&str>) -> Vec<String> {
+    let mut features: Vec<String> = portability_notes(html, anchor)
+        .into_iter()
+        .flat_map(features_in)
+        .collect();
+    features.sort();
+    features.dedup();
+    features
+}
+
+/// The conditions of the item's `#[doc(cfg(...))]` notes as text, e.g. `Unix`
+/// or `Unix and crate feature net`; the item is available where all of them
+/// hold.
+///
+/// Items on their parent's page (`anchor` set) have the parent's too.
+pub fn availability(html: &str, anchor: Option<&str>) -> Vec<String> {
+    let mut conditions = Vec::new();
+    for note in portability_notes(html, anchor) {
+        let Some(open) = note.find("<strong>") else {
+            continue;
+        };
+        let condition = &note[open + "<strong>".len()..];
+        let condition = &condition[..condition.find("</strong>").unwrap_or(condition.len())];
+        let condition = plain_text(condition);
+        if !conditions.contains(&condition) {
+            conditions.push(condition);
+        }
+    }
+    conditions
+}
+
+/// The portability notes that apply to the item: its page's own, and for a
+/// member (`anchor` set), the member's.
+fn portability_notes<'a>(html: &'a str, anchor: Option<&str>) -> Vec<&'a str> {
+    // The page's own note sits between the title and the first section; the
+    // sidebar before the title has headings of its own
+    let title = html.find("<h1").unwrap_or(0);
+    let page_end = html[title..]
+        .find("<h2")
+        .map_or(html.len(), |end| title + end);
+    let mut notes = notes_in(&html[title..page_end]);
+
+    if let Some(anchor) = anchor
+        && let Some(start) = html.find(&format!("id=\"{}\"", anchor))
+    {
+        // A member's note follows its code header, before its docs or the next member
+        let rest = &html[start..];
+        let end = ["</summary>", "<section", "class=\"docblock"]
+            .iter()
+            .filter_map(|marker| rest[1..].find(marker).map(|end| end + 1))
+            .min()
+            .unwrap_or(rest.len());
+        notes.extend(notes_in(&rest[..end]));
+    }
+    notes
+}
+
+/// The portability notes in `html`, each up to its closing tag.
+fn notes_in(html: &str) -> Vec<&str> {
+    html.match_indices("class=\"stab portability\"")
+        .map(|(start, _)| {
+            let note = &html[start..];
+            &note[..note
+                .find("</div>")
+                .or_else(|| note.find("</span>"))
+                .unwrap_or(note.len())]
+        })
+        .collect()
+}
+
+/// Feature names in the `<code>` elements of a portability note, if it mentions features.
+fn features_in(note: &str) -> Vec<String> {
+    let mut features = Vec::new();
+    if !note.contains("feature") {
+        return features;
+    }
+    let mut rest = note;
+    while let Some(open) = rest.find("<code>") {
+        rest = &rest[open + "<code>".len()..];
+        let Some(close) = rest.find("</code>") else {
+            break;
+        };
+        features.push(plain_text(&rest[..close]));
+        rest = &rest[close..];
+    }
+    features
+}
+
+/// Generic parameters and `where` predicates of a declaration.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Generics {
+    /// Parameters between the angle brackets after the name, e.g. `T: Clone`, `'a`
+    pub params: Vec<String>,
+    /// Predicates of the `where` clause, e.g. `T: Serialize`
+    pub where_predicates: Vec<String>,
+}
+
+impl Generics {
+    /// Pull the generics for the item called `name` out of its declaration.
+    pub fn parse(declaration: &str, name: &str) -> Self {
+        let mut generics = Self::default();
+
+        // The parameters follow the item's name, as in `fn name<T>` or `struct Name<'a, T>`
+        let after_name =
+            find_word(declaration, name).map(|start| &declaration[start + name.len()..]);
+        if let Some(rest) = after_name.and_then(|rest| rest.strip_prefix('<')) {
+            let params = until_closing_angle(rest);
+            generics.params = split_top_level(params);
+        }
+
+        if let Some(start) = find_word(declaration, "where") {
+            let clause = &declaration[start + "where".len()..];
+            // The clause runs until the item's body, if it has one
+            let clause = clause.split(['{', ';']).next().unwrap_or(clause);
+            generics.where_predicates = split_top_level(clause);
+        }
+
+        generics
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.params.is_empty() && self.where_predicates.is_empty()
+    }
+}
+
+/// Position of `word` in `text` where it isn't part of a longer identifier.
+fn find_word(text: &str, word: &str) -> Option<usize> {
+    let is_ident = |c: char| c.is_alphanumeric() || c == '_';
+    text.match_indices(word)
+        .map(|(start, _)| start)
+        .find(|&start| {
+            let before = text[..start].chars().next_back();
+            let after = text[start + word.len()..].chars().next();
+            !before.is_some_and(is_ident) && !after.is_some_and(is_ident)
+        })
+}
+
+/// The text up to the `>` that closes an already-opened `<`, skipping `->` arrows.
+fn until_closing_angle(text: &str) -> &str {
+    let mut depth = 0;
+    let mut previous = ' ';
+    for (position, c) in text.char_indices() {
+        match c {
+            '<' => depth += 1,
+            '>' if previous == '-' => {}
+            '>' if depth == 0 => return &text[..position],
+            '>' => depth -= 1,
+            _ => {}
+        }
+        previous = c;
+    }
+    text
+}
+
+/// Split on commas that aren't nested inside brackets, trimming each part.
+fn split_top_level(text: &str) -> Vec<String> {
+    let mut parts = Vec::new();
+    let mut depth = 0i32;
+    let mut previous = ' ';
+    let mut current = String::new();
+    for c in text.chars() {
+        match c {
+            '<' | '(' | '[' => depth += 1,
+            '>' if previous == '-' => {}
+            '>' | ')' | ']' => depth -= 1,
+            ',' if depth == 0 => {
+                parts.push(std::mem::take(&mut current));
+                previous = c;
+                continue;
+            }
+            _ => {}
+        }
+        current.push(c);
+        previous = c;
+    }
+    parts.push(current);
+
+    parts
+        .into_iter()
+        .map(|part| part.split_whitespace().collect::<Vec<_>>().join(" "))
+        .filter(|part| !part.is_empty())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::search_index::{extract_json_string, parse_search_index};
+    use crate::search_items::decode_crate;
+
+    fn item(
+        path: &str,
+        name: &str,
+        item_type: ItemType,
+        parent: Option<(&str, ItemType)>,
+    ) -> SearchItem {
+        let item = SearchItem::builder("serde_json", item_type, name).path(path);
+        match parent {
+            Some((parent, parent_type)) => item.parent(parent, parent_type).build(),
+            None => item.build(),
+        }
+    }
+
+    #[test]
+    fn test_item_page() {
+        let page = |item: SearchItem| item_page(&item).map(|page| page.href());
+
+        assert_eq!(
+            page(item("serde_json", "Value", ItemType::Enum, None)),
+            Some("serde_json/enum.Value.html".to_string())
+        );
+        assert_eq!(
+            page(item("serde_json", "value", ItemType::Module, None)),
+            Some("serde_json/value/index.html".to_string())
+        );
+        assert_eq!(
+            page(item(
+                "serde_json",
+                "as_str",
+                ItemType::Method,
+                Some(("Value", ItemType::Enum))
+            )),
+            Some("serde_json/enum.Value.html#method.as_str".to_string())
+        );
+        assert_eq!(
+            page(item(
+                "libc",
+                "CLOCK_THREAD_CPUTIME_ID",
+                ItemType::Constant,
+                None
+            )),
+            Some("libc/constant.CLOCK_THREAD_CPUTIME_ID.html".to_string())
+        );
+        assert_eq!(
+            page(item(
+                "core::iter",
+                "Item",
+                ItemType::AssocType,
+                Some(("Iterator", ItemType::Trait))
+            )),
+            Some("core/iter/trait.Iterator.html#associatedtype.Item".to_string())
+        );
+        assert_eq!(
+            page(item("libc", "sigval", ItemType::Union, None)),
+            Some("libc/union.sigval.html".to_string())
+        );
+    }
+
+    #[test]
+    fn test_item_page_of_fixture_items() {
+        let content = std::fs::read_to_string("tests/fixtures/search-index.js").unwrap();
+        let entries = parse_search_index(&extract_json_string(&content).unwrap()).unwrap();
+        let libc = entries.iter().find(|entry| entry.name == "libc").unwrap();
+        let items = decode_crate(&libc.name, &libc.data);
+        let page = |name: &str| {
+            let item = items.iter().find(|item| item.name == name).unwrap();
+            item_page(item).map(|page| page.href())
+        };
+        assert_eq!(
+            page("CLOCK_THREAD_CPUTIME_ID").as_deref(),
+            Some("libc/constant.CLOCK_THREAD_CPUTIME_ID.html")
+        );
+    }
+
+    #[test]
+    fn test_impl_origin() {
+        let parse = |disambiguator| ImplOrigin::parse(disambiguator).map(|o| o.to_string());
+        assert_eq!(
+            parse("impl-Iterator-for-Lines%3C'a%3E").as_deref(),
+            Some("impl Iterator for Lines<'a>")
+        );
+        assert_eq!(
+            parse("impl-AsRef%3C%5BTarget%5D%3E-for-Either%3CL,+R%3E").as_deref(),
+            Some("impl AsRef<[Target]> for Either<L, R>")
+        );
+        assert_eq!(
+            parse("impl-Default-for-%26Value-1").as_deref(),
+            Some("impl Default for &Value")
+        );
+        assert_eq!(
+            parse("impl-dyn+Error+%2B+Send").as_deref(),
+            Some("impl dyn Error + Send")
+        );
+        assert_eq!(
+            ImplOrigin::parse("impl-Ident"),
+            Some(ImplOrigin {
+                trait_name: None,
+                self_type: "Ident".to_string()
+            })
+        );
+        assert_eq!(parse("method.next"), None);
+    }
+
+    #[test]
+    fn test_site_links() {
+        let value = item("serde_json", "Value", ItemType::Enum, None);
+        assert_eq!(SiteLinks::default().absolute_url(&value), None);
+
+        let mut links = SiteLinks::new(Some("https://docs.example.com/api/"));
+        assert_eq!(
+            links.absolute_url(&value).as_deref(),
+            Some("https://docs.example.com/api/serde_json/enum.Value.html")
+        );
+        links.crate_base_urls.insert(
+            "serde_json".to_string(),
+            "https://docs.rs/serde_json/1.0.0/".to_string(),
+        );
+        assert_eq!(
+            links.absolute_url(&value).as_deref(),
+            Some("https://docs.rs/serde_json/1.0.0/serde_json/enum.Value.html")
+        );
+    }
+
+    #[test]
+    fn test_declaration() {
+        let html = r#"<pre class="rust item-decl"><code>pub fn <a class="fn">to_string</a>&lt;T&gt;(value: &amp;T) -&gt; <a>Result</a>&lt;String&gt;<div class="where">where
+    T: ?<a>Sized</a> + <a>Serialize</a>,</div></code></pre>"#;
+        assert_eq!(
+            declaration(html, None).unwrap(),
+            "pub fn to_string<T>(value: &T) -> Result<String>\nwhere\n    T: ?Sized + Serialize,"
+        );
+
+        let html = r#"<section id="method.as_str" class="method"><h4 class="code-header">pub fn <a>as_str</a>(&amp;self) -&gt; Option&lt;&amp;str&gt;</h4></section>"#;
+        assert_eq!(
+            declaration(html, Some("method.as_str")).unwrap(),
+            "pub fn as_str(&self) -> Option<&str>"
+        );
+        assert_eq!(declaration(html, Some("method.missing")), None);
+    }
+
+    #[test]
+    fn test_intro() {
+        let html = r#"<h1>Module <span>de</span></h1><details class="toggle top-doc" open><summary class="hideme"><span>Expand description</span></summary><div class="docblock"><p>Generic data structure
+<strong>deserialization</strong> framework.</p>
+<p>The two most important traits are <a href="trait.Deserialize.html"><code>Deserialize</code></a> and <code>Deserializer</code>.</p>
+<h2 id="the-deserialize-trait"><a class="doc-anchor" href="">§</a>The Deserialize trait</h2><p>Not part of the intro.</p></div></details><h2 id="modules" class="section-header">Modules</h2>"#;
+        assert_eq!(
+            intro(html).unwrap(),
+            "Generic data structure deserialization framework.\n\n\
+             The two most important traits are Deserialize and Deserializer."
+        );
+        // Undocumented modules have no docblock above their sections
+        assert_eq!(
+            intro(r#"<h2 id="structs">Structs</h2><div class="docblock"><p>Elsewhere</p></div>"#),
+            None
+        );
+    }
+
+    #[test]
+    fn test_documentation() {
+        let html = r##"<pre class="rust item-decl"><code>pub trait Serialize</code></pre><details class="toggle top-doc" open><summary class="hideme"><span>Expand description</span></summary><div class="docblock"><p>A <strong>data structure</strong> that can be
+serialized.</p>
+<h2 id="example"><a class="doc-anchor" href="#example">§</a>Example</h2><div class="example-wrap"><pre class="rust rust-example-rendered"><code>let json = to_string(&amp;value)?;
+assert!(json.is_empty());</code></pre></div><ul>
+<li>One</li>
+<li>Two</li>
+</ul></div></details><h2 id="required-methods">Required Methods</h2><details class="toggle method-toggle" open><summary><section id="tymethod.serialize" class="method"><h4 class="code-header">fn serialize()</h4></section></summary><div class="docblock"><p>Serialize this value.</p>
+</div></details><section id="method.undocumented" class="method"><h4 class="code-header">fn undocumented()</h4></section><details class="toggle method-toggle" open><summary><section id="method.borrow" class="method trait-impl"><h4 class="code-header">fn borrow()</h4></section></summary><div class='docblock'>Immutably borrows.</div></details>"##;
+        assert_eq!(
+            documentation(html, None).unwrap(),
+            "A data structure that can be\nserialized.\n\nExample\n\n```\n\
+             let json = to_string(&value)?;\nassert!(json.is_empty());\n```\n\n- One\n- Two"
+        );
+        assert_eq!(
+            documentation(html, Some("tymethod.serialize")).unwrap(),
+            "Serialize this value."
+        );
+        assert_eq!(
+            documentation(html, Some("method.borrow")).unwrap(),
+            "Immutably borrows."
+        );
+        assert_eq!(documentation(html, Some("method.undocumented")), None);
+        assert_eq!(documentation("<h2 id=\"fields\">Fields</h2>", None), None);
+    }
+
+    #[test]
+    fn test_examples() {
+        let html = r##"<details class="toggle top-doc" open><div class="docblock"><p>Parses.</p>
+<div class="example-wrap"><pre class="rust rust-example-rendered"><code><span class="kw">let </span>point = parse(<span class="string">&quot;1,2&quot;</span>);
+<span class="macro">assert_eq!</span>(point.x, <span class="number">1</span>);</code></pre></div>
+<div class="example-wrap ignore"><a href="#" class="tooltip" title="This example is not tested">ⓘ</a><pre class="rust rust-example-rendered ignore"><code>launch();</code></pre></div>
+<div class="example-wrap compile_fail"><pre class="rust rust-example-rendered compile_fail"><code>parse(1);</code></pre></div>
+<div class="example-wrap"><pre class="language-text"><code>1,2</code></pre></div>
+</div></details><section id="method.x" class="method"><h4 class="code-header">fn x()</h4></section><div class="docblock"><div class="example-wrap"><pre class="rust rust-example-rendered"><code>point.x();</code></pre></div></div>"##;
+        assert_eq!(
+            examples(html, None),
+            ["let point = parse(\"1,2\");\nassert_eq!(point.x, 1);"]
+        );
+        assert_eq!(examples(html, Some("method.x")), ["point.x();"]);
+        assert!(examples(html, Some("method.y")).is_empty());
+    }
+
+    #[test]
+    fn test_required_features() {
+        let html = r#"<h1>Struct <span>TlsStream</span></h1><pre class="rust item-decl"><code>pub struct TlsStream</code></pre><span class="item-info"><div class="stab portability">Available on <strong>crate features <code>tls</code> and <code>rt</code></strong> only.</div></span>
+<h2 id="implementations">Implementations</h2>
+<details><summary><section id="method.connect" class="method"><h4 class="code-header">pub fn connect()</h4></section><span class="item-info"><div class="stab portability">Available on <strong>crate feature <code>alpn</code></strong> only.</div></span></summary></details>
+<details><summary><section id="method.close" class="method"><h4 class="code-header">pub fn close()</h4></section></summary></details>"#;
+
+        assert_eq!(required_features(html, None), vec!["rt", "tls"]);
+        assert_eq!(
+            required_features(html, Some("method.connect")),
+            vec!["alpn", "rt", "tls"]
+        );
+        assert_eq!(
+            required_features(html, Some("method.close")),
+            vec!["rt", "tls"]
+        );
+
+        let unix_only =
+            r#"<div class="stab portability">Available on <strong>Unix</strong> only.</div>"#;
+        assert!(required_features(unix_only, None).is_empty());
+        assert_eq!(availability(unix_only, None), vec!["Unix"]);
+        // The sidebar's headings come before the title
+        let with_sidebar = format!(
+            "<nav><h2>Sections</h2></nav>{}",
+            unix_only.replace("<div", "<h1>UnixStream</h1><div")
+        );
+        assert_eq!(availability(&with_sidebar, None), vec!["Unix"]);
+        assert_eq!(
+            availability(html, Some("method.connect")),
+            vec!["crate features tls and rt", "crate feature alpn"]
+        );
+    }
+
+    #[test]
+    fn test_generics() {
+        let generics = Generics::parse(
+            "pub fn from_reader<R, T>(rdr: R) -> Result<T>\nwhere\n    R: Read,\n    T: DeserializeOwned,",
+            "from_reader",
+        );
+        assert_eq!(generics.params, vec!["R", "T"]);
+        assert_eq!(
+            generics.where_predicates,
+            vec!["R: Read", "T: DeserializeOwned"]
+        );
+
+        let generics = Generics::parse(
+            "pub struct Map<'a, K: Ord, F: Fn(K) -> Vec<K>> { /* private fields */ }",
+            "Map",
+        );
+        assert_eq!(generics.params, vec!["'a", "K: Ord", "F: Fn(K) -> Vec<K>"]);
+        assert!(generics.where_predicates.is_empty());
+
+        assert!(Generics::parse("pub fn as_str(&self) -> Option<&str>", "as_str").is_empty());
+    }
+}
@@ -0,0 +1,286 @@
+// Which platforms an item is available on, from the `#[doc(cfg(...))]` notes
+// rustdoc writes on its page ("Available on Unix only.")
+
+use std::fmt;
+use std::str::FromStr;
+
+/// A platform to keep results for: its OS and the names rustdoc gives the
+/// `cfg`s that hold on it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TargetPlatform {
+    /// Its name, as `--target-platform` takes it
+    pub name: &'static str,
+    /// `target_os`
+    pub os: &'static str,
+    /// `target_family`, if it has one
+    pub family: Option<&'static str>,
+    /// What rustdoc calls the platform `cfg`s that hold, e.g. `Linux` and `Unix`
+    labels: &'static [&'static str],
+}
+
+/// Every platform `--target-platform` knows
+pub const PLATFORMS: &[TargetPlatform] = &[
+    platform("linux", "linux", Some("unix"), &["Linux", "Unix"]),
+    platform("android", "android", Some("unix"), &["Android", "Unix"]),
+    platform("macos", "macos", Some("unix"), &["macOS", "Apple", "Unix"]),
+    platform("ios", "ios", Some("unix"), &["iOS", "Apple", "Unix"]),
+    platform("freebsd", "freebsd", Some("unix"), &["FreeBSD", "Unix"]),
+    platform("openbsd", "openbsd", Some("unix"), &["OpenBSD", "Unix"]),
+    platform("netbsd", "netbsd", Some("unix"), &["NetBSD", "Unix"]),
+    platform("windows", "windows", Some("windows"), &["Windows"]),
+    platform("wasi", "wasi", Some("wasm"), &["WASI", "WebAssembly"]),
+    platform("wasm", "unknown", Some("wasm"), &["WebAssembly"]),
+];
+
+/// Names rustdoc gives platform `cfg`s, true or not on a given platform;
+/// conditions on anything else (features, atomics, pointer width) are left
+/// undecided
+const PLATFORM_LABELS: &[&str] = &[
+    "Unix",
+    "Windows",
+    "Linux",
+    "Android",
+    "macOS",
+    "iOS",
+    "Apple",
+    "FreeBSD",
+    "OpenBSD",
+    "NetBSD",
+    "DragonFly BSD",
+    "illumos",
+    "Solaris",
+    "Fuchsia",
+    "Redox",
+    "Haiku",
+    "Hermit",
+    "Trusty",
+    "Motor OS",
+    "Cygwin",
+    "WASI",
+    "WebAssembly",
+];
+
+const fn platform(
+    name: &'static str,
+    os: &'static str,
+    family: Option<&'static str>,
+    labels: &'static [&'static str],
+) -> TargetPlatform {
+    TargetPlatform {
+        name,
+        os,
+        family,
+        labels,
+    }
+}
+
+impl TargetPlatform {
+    /// The platform rdoc is running on, if it's one of [`PLATFORMS`].
+    pub fn host() -> Option<Self> {
+        PLATFORMS
+            .iter()
+            .find(|platform| platform.os == std::env::consts::OS)
+            .copied()
+    }
+
+    /// Whether an item available on `condition`, the text of a `doc(cfg)`
+    /// note such as `Unix and crate feature net`, is available here. Parts
+    /// that aren't about the platform count as met, so only items that are
+    /// certainly unavailable are left out.
+    pub fn allows(&self, condition: &str) -> bool {
+        self.eval(&Condition::parse(condition)) != Some(false)
+    }
+
+    /// The condition's value here, or `None` if it depends on more than the
+    /// platform.
+    fn eval(&self, condition: &Condition) -> Option<bool> {
+        match condition {
+            Condition::Label(label) => self.label(label),
+            Condition::Not(inner) => self.eval(inner).map(|value| !value),
+            Condition::All(all) => {
+                let values: Vec<_> = all.iter().map(|inner| self.eval(inner)).collect();
+                if values.contains(&Some(false)) {
+                    Some(false)
+                } else if values.contains(&None) {
+                    None
+                } else {
+                    Some(true)
+                }
+            }
+            Condition::Any(any) => {
+                let values: Vec<_> = any.iter().map(|inner| self.eval(inner)).collect();
+                if values.contains(&Some(true)) {
+                    Some(true)
+                } else if values.contains(&None) {
+                    None
+                } else {
+                    Some(false)
+                }
+            }
+        }
+    }
+
+    fn label(&self, label: &str) -> Option<bool> {
+        if PLATFORM_LABELS.contains(&label) {
+            return Some(self.labels.contains(&label));
+        }
+        // `cfg`s rustdoc has no name for are shown as written
+        if matches!(label, "unix" | "windows") {
+            return Some(self.family == Some(label));
+        }
+        let (key, value) = label.split_once('=')?;
+        let value = value.trim_matches('"');
+        match key.trim() {
+            "target_os" => Some(value == self.os),
+            "target_family" => Some(self.family == Some(value)),
+            _ => None,
+        }
+    }
+}
+
+/// Whether `condition`, the text of a `doc(cfg)` note, says anything about
+/// the platform, rather than only features and the like.
+pub fn mentions_platform(condition: &str) -> bool {
+    PLATFORM_LABELS
+        .iter()
+        .any(|label| condition.contains(label))
+        || condition.contains("target_os")
+        || condition.contains("target_family")
+}
+
+impl fmt::Display for TargetPlatform {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name)
+    }
+}
+
+impl FromStr for TargetPlatform {
+    type Err = String;
+
+    /// A name from [`PLATFORMS`], or `host` for the one rdoc runs on.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let names = || {
+            PLATFORMS
+                .iter()
+                .map(|platform| platform.name)
+                .collect::<Vec<_>>()
+                .join(", ")
+        };
+        if s == "host" {
+            return Self::host().ok_or_else(|| {
+                format!(
+                    "this host's OS ({}) isn't one rdoc knows; name one of {}",
+                    std::env::consts::OS,
+                    names()
+                )
+            });
+        }
+        PLATFORMS
+            .iter()
+            .find(|platform| platform.name.eq_ignore_ascii_case(s))
+            .copied()
+            .ok_or_else(|| format!("unknown platform \"{}\" (expected host, {})", s, names()))
+    }
+}
+
+/// A `doc(cfg)` note's condition, parsed back from the text rustdoc renders
+/// it as: `A and B`, `A or B` (with commas when they're mixed), `non-A`,
+/// `neither A nor B`, and parentheses around nested ones.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Condition {
+    Label(String),
+    Not(Box<Condition>),
+    All(Vec<Condition>),
+    Any(Vec<Condition>),
+}
+
+impl Condition {
+    fn parse(text: &str) -> Self {
+        let text = text.trim().trim_end_matches('.');
+        let any = split_top_level(text, &[", or ", " or "]);
+        if any.len() > 1 {
+            return Condition::Any(any.into_iter().map(Condition::parse).collect());
+        }
+        let all = split_top_level(text, &[", and ", " and "]);
+        if all.len() > 1 {
+            return Condition::All(all.into_iter().map(Condition::parse).collect());
+        }
+        if let Some(inner) = text
+            .strip_prefix('(')
+            .and_then(|rest| rest.strip_suffix(')'))
+        {
+            return Condition::parse(inner);
+        }
+        if let Some(rest) = text.strip_prefix("neither ") {
+            let any = split_top_level(rest, &[" nor "]);
+            return Condition::Not(Box::new(Condition::Any(
+                any.into_iter().map(Condition::parse).collect(),
+            )));
+        }
+        if let Some(rest) = text.strip_prefix("non-") {
+            return Condition::Not(Box::new(Condition::parse(rest)));
+        }
+        Condition::Label(text.to_string())
+    }
+}
+
+/// `text` split at each of `separators` outside parentheses.
+fn split_top_level<'a>(text: &'a str, separators: &[&str]) -> Vec<&'a str> {
+    let mut parts = Vec::new();
+    let mut depth = 0usize;
+    let mut start = 0;
+    let mut i = 0;
+    while i < text.len() {
+        match text.as_bytes()[i] {
+            b'(' => depth += 1,
+            b')' => depth = depth.saturating_sub(1),
+            _ if depth == 0 => {
+                if let Some(separator) = separators.iter().find(|sep| text[i..].starts_with(**sep))
+                {
+                    parts.push(&text[start..i]);
+                    i += separator.len();
+                    start = i;
+                    continue;
+                }
+            }
+            _ => {}
+        }
+        i += 1;
+    }
+    parts.push(&text[start..]);
+    parts
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_platform_conditions() {
+        let linux: TargetPlatform = "linux".parse().unwrap();
+        let windows: TargetPlatform = "Windows".parse().unwrap();
+        let macos: TargetPlatform = "macos".parse().unwrap();
+
+        assert!(linux.allows("Unix"));
+        assert!(!windows.allows("Unix"));
+        assert!(linux.allows("Linux or FreeBSD or OpenBSD"));
+        assert!(!macos.allows("Linux or FreeBSD or OpenBSD"));
+        assert!(linux.allows("(Android or Linux or Cygwin) and Unix"));
+        assert!(!linux.allows("non-Linux"));
+        assert!(!windows.allows("neither Windows nor WebAssembly"));
+        assert!(linux.allows("neither Windows nor WebAssembly"));
+        assert!(!windows.allows("Unix and crate feature net"));
+        // What isn't about the platform doesn't leave anything out
+        assert!(linux.allows("crate feature std, or non-no_core_cstr and crate feature alloc"));
+        assert!(windows.allows("target_has_atomic=64"));
+        assert!(windows.allows("Unix or crate feature net"));
+        // cfgs rustdoc shows as written
+        assert!(linux.allows("target_os=\"linux\""));
+        assert!(!macos.allows("target_os=\"linux\""));
+        assert!(!windows.allows("unix"));
+
+        assert!(mentions_platform("Unix and crate feature net"));
+        assert!(!mentions_platform("crate feature std"));
+        assert!("beos".parse::<TargetPlatform>().is_err());
+    }
+}
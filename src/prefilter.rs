@@ -0,0 +1,181 @@
+// Narrowing a term's possible matches down before matching items one at a time
+
+use crate::search_items::SearchItem;
+use memchr::{memchr, memmem};
+
+/// Separates names in a [`NameBuffer`]; no name contains it
+const SEPARATOR: u8 = b'\n';
+
+/// Every item's normalized name (case folded, without underscores) in one
+/// buffer, so a term can be looked for in all of them with a single SIMD
+/// substring search rather than a search per item.
+///
+/// Build it once per decoded index and keep it alongside the items: on
+/// indexes of hundreds of thousands of items, one pass over this buffer is
+/// what keeps interactive lookups to a few milliseconds.
+#[derive(Debug, Clone, Default)]
+pub struct NameBuffer {
+    names: Vec<u8>,
+    /// Where each item's name ends (the position of its separator)
+    ends: Vec<usize>,
+    /// Whether each item's name is all ASCII
+    ascii: Vec<bool>,
+}
+
+/// The items (by their position in the slice a [`NameBuffer`] was built from)
+/// that a term could match. It may include items that don't match, but never
+/// leaves out one that does.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Candidates(Vec<bool>);
+
+impl Candidates {
+    /// Whether the item at `index` could match.
+    pub fn contains(&self, index: usize) -> bool {
+        self.0[index]
+    }
+
+    /// How many items could match.
+    pub fn count(&self) -> usize {
+        self.0.iter().filter(|&&candidate| candidate).count()
+    }
+}
+
+impl NameBuffer {
+    pub fn new(items: &[SearchItem]) -> Self {
+        let total: usize = items
+            .iter()
+            .map(|item| item.normalized_name.len() + 1)
+            .sum();
+        let mut buffer = Self {
+            names: Vec::with_capacity(total),
+            ends: Vec::with_capacity(items.len()),
+            ascii: Vec::with_capacity(items.len()),
+        };
+        for item in items {
+            buffer.push(&item.normalized_name);
+        }
+        buffer
+    }
+
+    /// Add the next item's normalized name.
+    pub(crate) fn push(&mut self, normalized_name: &str) {
+        self.names.extend_from_slice(normalized_name.as_bytes());
+        self.ends.push(self.names.len());
+        self.names.push(SEPARATOR);
+        self.ascii.push(normalized_name.is_ascii());
+    }
+
+    /// How many items the buffer has names for.
+    pub fn len(&self) -> usize {
+        self.ends.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.ends.is_empty()
+    }
+
+    /// The items whose normalized name contains `needle`, which should be
+    /// normalized the same way. Exact: these are the items that match.
+    pub fn containing(&self, needle: &str) -> Candidates {
+        if needle.is_empty() {
+            return Candidates(vec![true; self.len()]);
+        }
+        let mut found = vec![false; self.len()];
+        let finder = memmem::Finder::new(needle.as_bytes());
+        let mut position = 0;
+        while let Some(offset) = finder.find(&self.names[position..]) {
+            // Needles never contain the separator, so a hit is inside one name
+            let item = self.ends.partition_point(|&end| end < position + offset);
+            found[item] = true;
+            // The rest of this name can't add anything
+            position = self.ends[item] + 1;
+        }
+        Candidates(found)
+    }
+
+    /// The items whose normalized name contains the characters of `needle`
+    /// in order, the least a fuzzy match needs. `needle` should be case
+    /// folded with underscores removed.
+    ///
+    /// Returns `None` for needles with non-ASCII characters, which fuzzy
+    /// matchers may compare after Unicode normalization; names with non-ASCII
+    /// characters are always candidates for the same reason.
+    pub fn subsequence(&self, needle: &str) -> Option<Candidates> {
+        if !needle.is_ascii() {
+            return None;
+        }
+        let needle = needle.as_bytes();
+        let mut start = 0;
+        let found = self
+            .ends
+            .iter()
+            .zip(&self.ascii)
+            .map(|(&end, &ascii)| {
+                let name = &self.names[start..end];
+                start = end + 1;
+                !ascii || is_subsequence(needle, name)
+            })
+            .collect();
+        Some(Candidates(found))
+    }
+}
+
+/// Whether the bytes of `needle` appear in `haystack` in order.
+fn is_subsequence(needle: &[u8], mut haystack: &[u8]) -> bool {
+    for &byte in needle {
+        match memchr(byte, haystack) {
+            Some(position) => haystack = &haystack[position + 1..],
+            None => return false,
+        }
+    }
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::search_index::ItemType;
+
+    fn items(names: &[&str]) -> Vec<SearchItem> {
+        names
+            .iter()
+            .enumerate()
+            .map(|(index, name)| {
+                SearchItem::builder("std", ItemType::Function, name)
+                    .index(index)
+                    .build()
+            })
+            .collect()
+    }
+
+    fn indexes(candidates: &Candidates, len: usize) -> Vec<usize> {
+        (0..len).filter(|&i| candidates.contains(i)).collect()
+    }
+
+    #[test]
+    fn test_containing() {
+        let names = ["read_to_string", "Read", "write", "to_string", "Größe"];
+        let buffer = NameBuffer::new(&items(&names));
+        assert_eq!(buffer.len(), 5);
+
+        assert_eq!(indexes(&buffer.containing("string"), 5), [0, 3]);
+        assert_eq!(indexes(&buffer.containing("readtostring"), 5), [0]);
+        assert_eq!(indexes(&buffer.containing("read"), 5), [0, 1]);
+        assert_eq!(indexes(&buffer.containing("grösse"), 5), [4]);
+        // No hit spans two names
+        assert_eq!(buffer.containing("dwrite").count(), 0);
+        assert_eq!(buffer.containing("").count(), 5);
+    }
+
+    #[test]
+    fn test_subsequence() {
+        let names = ["HashMap", "hash_set", "BTreeMap", "Größe"];
+        let buffer = NameBuffer::new(&items(&names));
+
+        let candidates = buffer.subsequence("hsmp").unwrap();
+        // Non-ASCII names are always candidates
+        assert_eq!(indexes(&candidates, 4), [0, 3]);
+        assert_eq!(indexes(&buffer.subsequence("hs").unwrap(), 4), [0, 1, 3]);
+        assert_eq!(buffer.subsequence("größe"), None);
+    }
+}
@@ -0,0 +1,78 @@
+// Progress reporting for long-running operations
+
+use indicatif::{ProgressBar, ProgressStyle};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+
+/// Whether progress output should be drawn at all.
+///
+/// Disabled by `--quiet` and by machine-readable output modes so spinners
+/// never end up interleaved with data meant for other programs.
+static ENABLED: AtomicBool = AtomicBool::new(true);
+
+/// Globally enable or disable progress output.
+pub fn set_enabled(enabled: bool) {
+    ENABLED.store(enabled, Ordering::Relaxed);
+}
+
+/// Returns true if progress output is currently enabled.
+pub fn is_enabled() -> bool {
+    ENABLED.load(Ordering::Relaxed)
+}
+
+/// Create a spinner for an operation of unknown length (e.g. `cargo doc`).
+///
+/// Returns a hidden spinner when progress is disabled, so callers never need
+/// to check [`is_enabled`] themselves.
+pub fn spinner(message: impl Into<String>) -> ProgressBar {
+    spinner_if(is_enabled(), message)
+}
+
+/// [`spinner`], hidden unless `enabled`.
+fn spinner_if(enabled: bool, message: impl Into<String>) -> ProgressBar {
+    if !enabled {
+        return ProgressBar::hidden();
+    }
+
+    let spinner = ProgressBar::new_spinner().with_message(message.into());
+    spinner.set_style(
+        ProgressStyle::with_template("{spinner:.cyan} {msg} {elapsed:.dim}")
+            .expect("valid spinner template"),
+    );
+    spinner.enable_steady_tick(Duration::from_millis(100));
+    spinner
+}
+
+/// Create a progress bar for an operation over `len` known steps
+/// (e.g. decoding each crate in the index).
+///
+/// `label` is shown before the bar; use `set_message` to show the current step.
+pub fn bar(len: u64, label: impl Into<String>) -> ProgressBar {
+    bar_if(is_enabled(), len, label)
+}
+
+/// [`bar`], hidden unless `enabled`.
+fn bar_if(enabled: bool, len: u64, label: impl Into<String>) -> ProgressBar {
+    if !enabled {
+        return ProgressBar::hidden();
+    }
+
+    let bar = ProgressBar::new(len).with_prefix(label.into());
+    bar.set_style(
+        ProgressStyle::with_template("{prefix} [{bar:30.cyan/blue}] {pos}/{len} {msg:.dim}")
+            .expect("valid bar template")
+            .progress_chars("=> "),
+    );
+    bar
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_disabled_progress_is_hidden() {
+        assert!(spinner_if(false, "working").is_hidden());
+        assert!(bar_if(false, 10, "working").is_hidden());
+    }
+}
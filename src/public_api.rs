@@ -0,0 +1,168 @@
+// The public API as one line per item, in the format cargo-public-api prints,
+// so its diffs and the CI jobs built on them work from a doc build
+
+use crate::resolve::item_path;
+use crate::search_index::ItemType;
+use crate::search_items::SearchItem;
+
+/// The item's line, e.g. `pub fn serde_json::to_string<T>(value: &T) ->
+/// Result<String> where T: ?Sized + Serialize`, or `None` for what the listing
+/// leaves out: impls, imports, primitives and keywords.
+///
+/// The signature comes from `declaration`, the item's declaration as its page
+/// shows it, with whitespace collapsed and bodies left out. Without one, or
+/// for modules and macros, the line is just the item's kind and path.
+pub fn api_line(item: &SearchItem, declaration: Option<&str>) -> Option<String> {
+    use ItemType::*;
+    let keyword = match item.item_type {
+        Module => "mod",
+        Struct => "struct",
+        Enum => "enum",
+        Union => "union",
+        Trait | TraitAlias => "trait",
+        Function | Method | TyMethod => "fn",
+        Macro | ProcAttribute | ProcDerive => "macro",
+        Constant | AssocConst => "const",
+        Static => "static",
+        Typedef | AssocType | ForeignType => "type",
+        // Variants and fields are listed by path alone
+        Variant | StructField => "",
+        ExternCrate | Import | Impl | OpaqueTy | Primitive | Keyword | Unknown(_) => return None,
+    };
+    let path = item_path(item);
+
+    let signature = declaration
+        .filter(|_| !matches!(item.item_type, Module | Macro | ProcAttribute | ProcDerive))
+        .and_then(|declaration| {
+            with_path(&signature(item.item_type, declaration), &item.name, &path)
+        });
+    Some(match signature {
+        Some(signature) if signature.starts_with("pub ") => signature,
+        // Trait items, variants and fields are as public as what they're in
+        Some(signature) => format!("pub {}", signature),
+        None if matches!(item.item_type, Macro) => format!("pub macro {}!", path),
+        None if keyword.is_empty() => format!("pub {}", path),
+        None => format!("pub {} {}", keyword, path),
+    })
+}
+
+/// `declaration` on one line, without attributes, bodies, or the values of
+/// constants and statics.
+fn signature(item_type: ItemType, declaration: &str) -> String {
+    let mut signature = declaration
+        .lines()
+        .filter(|line| !line.trim_start().starts_with("#["))
+        .flat_map(str::split_whitespace)
+        .collect::<Vec<_>>()
+        .join(" ")
+        .replace("( ", "(")
+        .replace(", )", ")")
+        .replace(",)", ")");
+    // Types and traits end in `{ .. }` or `;`, where clauses in a `,`
+    if let Some(body) = signature.find(" {") {
+        signature.truncate(body);
+    }
+    if matches!(
+        item_type,
+        ItemType::Constant | ItemType::AssocConst | ItemType::Static
+    ) && let Some(value) = signature.find(" = ")
+    {
+        signature.truncate(value);
+    }
+    signature.trim_end_matches([';', ',']).to_string()
+}
+
+/// `signature` with the item's name, where it's declared, replaced by its
+/// full path; `None` if the name isn't there.
+fn with_path(signature: &str, name: &str, path: &str) -> Option<String> {
+    let is_ident = |c: char| c.is_alphanumeric() || c == '_';
+    let start = signature
+        .match_indices(name)
+        .map(|(start, _)| start)
+        .find(|&start| {
+            let before = signature[..start].chars().next_back();
+            let after = signature[start + name.len()..].chars().next();
+            !before.is_some_and(is_ident) && !after.is_some_and(is_ident)
+        })?;
+    Some(format!(
+        "{}{}{}",
+        &signature[..start],
+        path,
+        &signature[start + name.len()..]
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn item(item_type: ItemType, name: &str, path: &str, parent: Option<&str>) -> SearchItem {
+        let item = SearchItem::builder("serde", item_type, name).path(path);
+        match parent {
+            Some(parent) => item.parent(parent, ItemType::Struct).build(),
+            None => item.build(),
+        }
+    }
+
+    #[test]
+    fn test_api_line() {
+        let serialize = item(ItemType::Trait, "Serialize", "serde", None);
+        assert_eq!(
+            api_line(
+                &serialize,
+                Some("pub trait Serialize {\n    // Required method\n    fn serialize();\n}")
+            )
+            .unwrap(),
+            "pub trait serde::Serialize"
+        );
+        let method = item(ItemType::TyMethod, "serialize", "serde", Some("Serialize"));
+        assert_eq!(
+            api_line(
+                &method,
+                Some("fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>\nwhere\n    S: Serializer,")
+            )
+            .unwrap(),
+            "pub fn serde::Serialize::serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error> where S: Serializer"
+        );
+        let function = item(ItemType::Function, "from_str", "serde_json", None);
+        assert_eq!(
+            api_line(
+                &function,
+                Some("pub fn from_str<'a, T>(\n    s: &'a str,\n) -> Result<T>")
+            )
+            .unwrap(),
+            "pub fn serde_json::from_str<'a, T>(s: &'a str) -> Result<T>"
+        );
+        let constant = item(ItemType::Constant, "MAX", "serde", None);
+        assert_eq!(
+            api_line(&constant, Some("pub const MAX: usize = 4usize;")).unwrap(),
+            "pub const serde::MAX: usize"
+        );
+        let variant = item(ItemType::Variant, "Bool", "serde::de", Some("Unexpected"));
+        assert_eq!(
+            api_line(&variant, Some("Bool(bool)")).unwrap(),
+            "pub serde::de::Unexpected::Bool(bool)"
+        );
+
+        // Without a declaration, or one missing the name
+        let de = item(ItemType::Module, "de", "serde", None);
+        assert_eq!(api_line(&de, None).unwrap(), "pub mod serde::de");
+        let macro_item = item(ItemType::Macro, "forward_to_deserialize_any", "serde", None);
+        assert_eq!(
+            api_line(
+                &macro_item,
+                Some("macro_rules! forward_to_deserialize_any { ... }")
+            )
+            .unwrap(),
+            "pub macro serde::forward_to_deserialize_any!"
+        );
+        assert_eq!(
+            api_line(&function, Some("pub fn renamed()")).unwrap(),
+            "pub fn serde_json::from_str"
+        );
+        assert_eq!(
+            api_line(&item(ItemType::Impl, "impl", "serde", None), None),
+            None
+        );
+    }
+}
@@ -0,0 +1,336 @@
+// Search queries with boolean operators
+
+use std::fmt;
+
+/// A parsed search query.
+///
+/// Terms are combined with `AND` (the default between terms), `OR` and `NOT`,
+/// grouped with parentheses. `+term` is shorthand for a required term and
+/// `-term` for an excluded one; `"quoted phrases"` are matched as one term.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Query {
+    /// Matches when the term matches
+    Term(String),
+    /// Matches when every sub-query matches
+    And(Vec<Query>),
+    /// Matches when any sub-query matches
+    Or(Vec<Query>),
+    /// Matches when the sub-query doesn't
+    Not(Box<Query>),
+}
+
+/// Why a query could not be parsed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum QueryError {
+    /// The query has no terms
+    Empty,
+    /// An operator is missing its operand, e.g. a trailing `NOT`
+    MissingOperand(String),
+    /// A `(` without a matching `)`, or the other way around
+    UnbalancedParenthesis,
+    /// A `"` without a closing quote
+    UnterminatedQuote,
+}
+
+impl fmt::Display for QueryError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            QueryError::Empty => write!(f, "query is empty"),
+            QueryError::MissingOperand(operator) => {
+                write!(f, "{} is missing a term to apply to", operator)
+            }
+            QueryError::UnbalancedParenthesis => write!(f, "unbalanced parenthesis"),
+            QueryError::UnterminatedQuote => write!(f, "missing closing quote"),
+        }
+    }
+}
+
+impl std::error::Error for QueryError {}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Token {
+    Word(String),
+    And,
+    Or,
+    Not,
+    Required,
+    Excluded,
+    Open,
+    Close,
+}
+
+impl Query {
+    /// Parse a query such as `read AND async NOT blocking` or `+read -blocking`.
+    pub fn parse(input: &str) -> Result<Query, QueryError> {
+        let tokens = tokenize(input)?;
+        if tokens.is_empty() {
+            return Err(QueryError::Empty);
+        }
+
+        let mut parser = Parser {
+            tokens,
+            position: 0,
+        };
+        let query = parser.or_expr()?;
+        // The only token the grammar leaves unconsumed is an unmatched `)`
+        match parser.peek() {
+            None => Ok(query),
+            Some(_) => Err(QueryError::UnbalancedParenthesis),
+        }
+    }
+
+    /// Every term in the query, in order.
+    pub fn terms(&self) -> Vec<&str> {
+        match self {
+            Query::Term(term) => vec![term.as_str()],
+            Query::And(queries) | Query::Or(queries) => {
+                queries.iter().flat_map(Query::terms).collect()
+            }
+            Query::Not(query) => query.terms(),
+        }
+    }
+
+    /// Evaluate the query, using `matches_term` to test each term.
+    pub fn matches(&self, matches_term: &mut impl FnMut(&str) -> bool) -> bool {
+        match self {
+            Query::Term(term) => matches_term(term),
+            Query::And(queries) => queries.iter().all(|query| query.matches(matches_term)),
+            Query::Or(queries) => queries.iter().any(|query| query.matches(matches_term)),
+            Query::Not(query) => !query.matches(matches_term),
+        }
+    }
+}
+
+fn tokenize(input: &str) -> Result<Vec<Token>, QueryError> {
+    let mut tokens = Vec::new();
+    let mut chars = input.chars().peekable();
+
+    while let Some(&c) = chars.peek() {
+        match c {
+            c if c.is_whitespace() => {
+                chars.next();
+            }
+            '(' => {
+                chars.next();
+                tokens.push(Token::Open);
+            }
+            ')' => {
+                chars.next();
+                tokens.push(Token::Close);
+            }
+            // Prefixes only count at the start of a word, so `foo-bar` stays one term
+            '+' | '-' => {
+                chars.next();
+                tokens.push(if c == '+' {
+                    Token::Required
+                } else {
+                    Token::Excluded
+                });
+            }
+            '"' => {
+                chars.next();
+                let mut phrase = String::new();
+                loop {
+                    match chars.next() {
+                        Some('"') => break,
+                        Some(c) => phrase.push(c),
+                        None => return Err(QueryError::UnterminatedQuote),
+                    }
+                }
+                tokens.push(Token::Word(phrase));
+            }
+            _ => {
+                let mut word = String::new();
+                while let Some(&c) = chars.peek() {
+                    if c.is_whitespace() || matches!(c, '(' | ')' | '"') {
+                        break;
+                    }
+                    word.push(c);
+                    chars.next();
+                }
+                tokens.push(match word.as_str() {
+                    "AND" => Token::And,
+                    "OR" => Token::Or,
+                    "NOT" => Token::Not,
+                    _ => Token::Word(word),
+                });
+            }
+        }
+    }
+
+    Ok(tokens)
+}
+
+/// Recursive descent parser; `OR` binds loosest, then `AND`, then `NOT`/prefixes.
+struct Parser {
+    tokens: Vec<Token>,
+    position: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.position)
+    }
+
+    fn advance(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.position).cloned();
+        self.position += 1;
+        token
+    }
+
+    fn or_expr(&mut self) -> Result<Query, QueryError> {
+        let mut queries = vec![self.and_expr()?];
+        while self.peek() == Some(&Token::Or) {
+            self.advance();
+            queries.push(self.and_expr().map_err(missing_after("OR"))?);
+        }
+        Ok(flatten(queries, Query::Or))
+    }
+
+    fn and_expr(&mut self) -> Result<Query, QueryError> {
+        let mut queries = vec![self.unary()?];
+        loop {
+            match self.peek() {
+                Some(Token::And) => {
+                    self.advance();
+                    queries.push(self.unary().map_err(missing_after("AND"))?);
+                }
+                // Adjacent terms are implicitly ANDed
+                Some(Token::Or | Token::Close) | None => break,
+                Some(_) => queries.push(self.unary()?),
+            }
+        }
+        Ok(flatten(queries, Query::And))
+    }
+
+    fn unary(&mut self) -> Result<Query, QueryError> {
+        match self.advance() {
+            Some(Token::Word(word)) => Ok(Query::Term(word)),
+            Some(Token::Not) => Ok(Query::Not(Box::new(
+                self.unary().map_err(missing_after("NOT"))?,
+            ))),
+            Some(Token::Excluded) => Ok(Query::Not(Box::new(
+                self.unary().map_err(missing_after("-"))?,
+            ))),
+            Some(Token::Required) => self.unary().map_err(missing_after("+")),
+            Some(Token::Open) => {
+                let query = self.or_expr().map_err(missing_after("("))?;
+                match self.advance() {
+                    Some(Token::Close) => Ok(query),
+                    _ => Err(QueryError::UnbalancedParenthesis),
+                }
+            }
+            Some(Token::Close) => Err(QueryError::UnbalancedParenthesis),
+            Some(Token::And) => Err(missing("AND")),
+            Some(Token::Or) => Err(missing("OR")),
+            None => Err(QueryError::Empty),
+        }
+    }
+}
+
+fn missing(operator: &str) -> QueryError {
+    QueryError::MissingOperand(operator.to_string())
+}
+
+/// Report running out of tokens after `operator` as a missing operand, keeping
+/// other errors as they are.
+fn missing_after(operator: &str) -> impl FnOnce(QueryError) -> QueryError + '_ {
+    move |err| match err {
+        QueryError::Empty if operator == "(" => QueryError::UnbalancedParenthesis,
+        QueryError::Empty => missing(operator),
+        err => err,
+    }
+}
+
+/// Avoid wrapping a single query in a one-element And/Or.
+fn flatten(mut queries: Vec<Query>, combine: fn(Vec<Query>) -> Query) -> Query {
+    if queries.len() == 1 {
+        queries.remove(0)
+    } else {
+        combine(queries)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn term(word: &str) -> Query {
+        Query::Term(word.to_string())
+    }
+
+    fn not(query: Query) -> Query {
+        Query::Not(Box::new(query))
+    }
+
+    #[test]
+    fn test_parse_single_term() {
+        assert_eq!(Query::parse("Result").unwrap(), term("Result"));
+    }
+
+    #[test]
+    fn test_parse_operators() {
+        assert_eq!(
+            Query::parse("read AND async NOT blocking").unwrap(),
+            Query::And(vec![term("read"), term("async"), not(term("blocking"))])
+        );
+        assert_eq!(
+            Query::parse("+read -blocking").unwrap(),
+            Query::And(vec![term("read"), not(term("blocking"))])
+        );
+        assert_eq!(
+            Query::parse("a b OR c").unwrap(),
+            Query::Or(vec![Query::And(vec![term("a"), term("b")]), term("c")])
+        );
+        assert_eq!(
+            Query::parse("a (b OR c)").unwrap(),
+            Query::And(vec![term("a"), Query::Or(vec![term("b"), term("c")])])
+        );
+        assert_eq!(
+            Query::parse("\"read to\" foo-bar").unwrap(),
+            Query::And(vec![term("read to"), term("foo-bar")])
+        );
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!(Query::parse("  "), Err(QueryError::Empty));
+        assert_eq!(
+            Query::parse("read NOT"),
+            Err(QueryError::MissingOperand("NOT".to_string()))
+        );
+        assert_eq!(
+            Query::parse("OR read"),
+            Err(QueryError::MissingOperand("OR".to_string()))
+        );
+        assert_eq!(
+            Query::parse("(read"),
+            Err(QueryError::UnbalancedParenthesis)
+        );
+        assert_eq!(
+            Query::parse("read)"),
+            Err(QueryError::UnbalancedParenthesis)
+        );
+        assert_eq!(
+            Query::parse("NOT (read"),
+            Err(QueryError::UnbalancedParenthesis)
+        );
+        assert_eq!(Query::parse("\"read"), Err(QueryError::UnterminatedQuote));
+    }
+
+    #[test]
+    fn test_terms() {
+        let query = Query::parse("a (b OR NOT c)").unwrap();
+        assert_eq!(query.terms(), vec!["a", "b", "c"]);
+    }
+
+    #[test]
+    fn test_matches() {
+        let query = Query::parse("read AND async NOT blocking").unwrap();
+        let text = |words: &'static str| move |term: &str| words.split(' ').any(|w| w == term);
+
+        assert!(query.matches(&mut text("read async")));
+        assert!(!query.matches(&mut text("read async blocking")));
+        assert!(!query.matches(&mut text("read")));
+    }
+}
@@ -0,0 +1,517 @@
+// Fetching documentation from hosted rustdoc sites
+
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+use color_eyre::{Result, eyre::Context, eyre::eyre};
+use colored::Colorize;
+use indicatif::ProgressBar;
+use rdoc::descriptions;
+use rdoc::search_index::{extract_json_string, parse_search_index_lazy};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use tracing::{info, warn};
+
+use crate::config::HttpConfig;
+use crate::{cache, output, progress};
+
+/// File name of the search index at the root of a rustdoc site.
+pub const SEARCH_INDEX_FILE: &str = "search-index.js";
+
+/// Environment variable holding a bearer token for private doc hosts.
+pub const HTTP_TOKEN_ENV_VAR: &str = "RDOC_HTTP_TOKEN";
+
+/// Credentials and extra headers sent when fetching remote docs.
+#[derive(Default, Clone)]
+pub struct HttpOptions {
+    /// Value of the `Authorization` header, if any
+    authorization: Option<String>,
+    /// Additional headers as (name, value) pairs
+    headers: Vec<(String, String)>,
+    /// The origin (`https://host:port`) the authorization and headers are for;
+    /// nothing is sent until one is set
+    origin: Option<String>,
+}
+
+impl HttpOptions {
+    /// Combine the `[http]` config section, the `RDOC_HTTP_TOKEN` value and
+    /// `--header "Name: value"` arguments.
+    ///
+    /// The environment token overrides the configured one, a bearer token takes
+    /// precedence over basic auth, and command-line headers are sent after
+    /// (and so alongside) configured ones.
+    pub fn new(
+        config: &HttpConfig,
+        env_token: Option<String>,
+        cli_headers: &[String],
+    ) -> Result<Self> {
+        let authorization = match (env_token.or_else(|| config.token.clone()), &config.username) {
+            (Some(token), _) => Some(format!("Bearer {}", token)),
+            (None, Some(username)) => {
+                let password = config.password.as_deref().unwrap_or_default();
+                let credentials = STANDARD.encode(format!("{}:{}", username, password));
+                Some(format!("Basic {}", credentials))
+            }
+            (None, None) => None,
+        };
+
+        let mut headers: Vec<_> = config
+            .headers
+            .iter()
+            .map(|(name, value)| (name.clone(), value.clone()))
+            .collect();
+        for header in cli_headers {
+            headers.push(parse_header(header)?);
+        }
+
+        Ok(Self {
+            authorization,
+            headers,
+            origin: None,
+        })
+    }
+
+    /// These options for requests to `url`'s origin only, so the credentials
+    /// for a private doc host never reach docs.rs or another site.
+    pub fn for_host(&self, url: &str) -> Self {
+        Self {
+            origin: Some(origin(url)),
+            ..self.clone()
+        }
+    }
+
+    /// The headers to send with a request for `url`: all of them on the
+    /// origin they're for, none elsewhere.
+    fn headers_for(&self, url: &str) -> Vec<(&str, &str)> {
+        if self.origin.as_deref() != Some(origin(url).as_str()) {
+            return Vec::new();
+        }
+        let authorization = self
+            .authorization
+            .as_deref()
+            .map(|authorization| ("Authorization", authorization));
+        authorization
+            .into_iter()
+            .chain(
+                self.headers
+                    .iter()
+                    .map(|(name, value)| (name.as_str(), value.as_str())),
+            )
+            .collect()
+    }
+}
+
+/// The scheme, host and port of `url`, lowercased: `https://docs.rs` for
+/// `https://docs.rs/serde/`.
+fn origin(url: &str) -> String {
+    let authority_start = url.find("://").map_or(0, |scheme_end| scheme_end + 3);
+    let end = url[authority_start..]
+        .find(['/', '?', '#'])
+        .map_or(url.len(), |end| authority_start + end);
+    url[..end].to_ascii_lowercase()
+}
+
+// Header values often carry secrets, so only their names are shown
+impl std::fmt::Debug for HttpOptions {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let header_names: Vec<_> = self.headers.iter().map(|(name, _)| name).collect();
+        f.debug_struct("HttpOptions")
+            .field(
+                "authorization",
+                &self.authorization.as_ref().map(|_| "<redacted>"),
+            )
+            .field("headers", &header_names)
+            .field("origin", &self.origin)
+            .finish()
+    }
+}
+
+/// Parse a `Name: value` header argument.
+fn parse_header(header: &str) -> Result<(String, String)> {
+    match header.split_once(':') {
+        Some((name, value)) if !name.trim().is_empty() => {
+            Ok((name.trim().to_string(), value.trim().to_string()))
+        }
+        _ => Err(eyre!(
+            "Invalid header \"{}\": expected \"Name: value\"",
+            header
+        )),
+    }
+}
+
+/// Validate a documentation root URL and make sure it ends with a slash, so
+/// relative paths can be appended to it.
+pub fn normalize_base_url(url: &str) -> Result<String> {
+    if !(url.starts_with("http://") || url.starts_with("https://")) {
+        return Err(eyre!(
+            "Invalid documentation URL \"{}\": expected an http:// or https:// URL",
+            url
+        ));
+    }
+
+    if url.ends_with('/') {
+        Ok(url.to_string())
+    } else {
+        Ok(format!("{}/", url))
+    }
+}
+
+/// Default number of downloads to run at once.
+pub const DEFAULT_JOBS: usize = 8;
+
+/// Download a rustdoc site's search index and description shards into the cache.
+///
+/// Returns the path of the cached search-index.js. A previously cached copy is
+/// reused unless `refresh` is set. Up to `jobs` description shards are
+/// downloaded at once.
+pub fn fetch_docs(
+    base_url: &str,
+    http: &HttpOptions,
+    refresh: bool,
+    jobs: usize,
+    quiet: bool,
+) -> Result<PathBuf> {
+    let agent = ureq::Agent::new_with_defaults();
+    fetch_site(&agent, base_url, http, refresh, jobs, !quiet)
+}
+
+/// Fetch several documentation sites concurrently, `jobs` at a time.
+///
+/// Returns one result per URL, in the same order, so a failure for one site
+/// doesn't stop the others.
+pub fn fetch_all(
+    base_urls: &[String],
+    http: &HttpOptions,
+    refresh: bool,
+    jobs: usize,
+) -> Vec<Result<PathBuf>> {
+    let agent = ureq::Agent::new_with_defaults();
+    let bar = progress::bar(base_urls.len() as u64, "Fetching docs");
+    let results = run_concurrently(base_urls, jobs, |base_url| {
+        bar.set_message(base_url.clone());
+        // Each site's shards are fetched one at a time; the sites run in parallel
+        let result = fetch_site(&agent, base_url, http, refresh, 1, false);
+        bar.inc(1);
+        result
+    });
+    bar.finish_and_clear();
+    results
+}
+
+fn fetch_site(
+    agent: &ureq::Agent,
+    base_url: &str,
+    http: &HttpOptions,
+    refresh: bool,
+    jobs: usize,
+    show_progress: bool,
+) -> Result<PathBuf> {
+    let base_url = normalize_base_url(base_url)?;
+    let docs_dir = cache::remote_docs_dir(&base_url)?;
+    let search_index_path = docs_dir.join(SEARCH_INDEX_FILE);
+
+    if search_index_path.exists() && !refresh {
+        info!(
+            "using cached docs for {} from {}",
+            base_url,
+            docs_dir.display()
+        );
+        return Ok(search_index_path);
+    }
+
+    if show_progress {
+        println!(
+            "{} Fetching documentation from {}",
+            "ℹ".blue().bold(),
+            base_url
+        );
+    }
+
+    let spinner = if show_progress {
+        progress::spinner(format!("Downloading {}", SEARCH_INDEX_FILE))
+    } else {
+        ProgressBar::hidden()
+    };
+    let content = download(agent, &format!("{}{}", base_url, SEARCH_INDEX_FILE), http)?;
+    spinner.finish_and_clear();
+
+    write_cached(&search_index_path, &content)?;
+    fetch_description_shards(
+        agent,
+        &base_url,
+        http,
+        &docs_dir,
+        &content,
+        jobs,
+        show_progress,
+    )?;
+
+    Ok(search_index_path)
+}
+
+/// Read a page of a remote documentation site, downloading it into the cache
+/// on first use.
+///
+/// `file` is relative to the site root with `/` separators. Returns `None` if
+/// the page can't be fetched, since pages are only used for extra detail.
+pub fn fetch_page(base_url: &str, http: &HttpOptions, file: &str) -> Result<Option<String>> {
+    let base_url = normalize_base_url(base_url)?;
+    let path = file
+        .split('/')
+        .fold(cache::remote_docs_dir(&base_url)?, |path, segment| {
+            path.join(segment)
+        });
+    if path.exists() {
+        let content = std::fs::read_to_string(&path)
+            .wrap_err_with(|| format!("Failed to read {}", path.display()))?;
+        return Ok(Some(content));
+    }
+
+    let agent = ureq::Agent::new_with_defaults();
+    match download(&agent, &format!("{}{}", base_url, file), http) {
+        Ok(content) => {
+            write_cached(&path, &content)?;
+            Ok(Some(content))
+        }
+        Err(err) => {
+            warn!("could not fetch {}: {:#}", file, err);
+            Ok(None)
+        }
+    }
+}
+
+/// Download every crate's description shards next to the cached index.
+///
+/// Descriptions are optional for searching, so a missing shard is logged and skipped.
+fn fetch_description_shards(
+    agent: &ureq::Agent,
+    base_url: &str,
+    http: &HttpOptions,
+    docs_dir: &Path,
+    content: &str,
+    jobs: usize,
+    show_progress: bool,
+) -> Result<()> {
+    let json_string = extract_json_string(content)
+        .wrap_err("Downloaded search-index.js is not a rustdoc search index")?;
+    let crate_entries = parse_search_index_lazy(&json_string)
+        .wrap_err("Downloaded search-index.js could not be parsed")?;
+
+    let mut shards = Vec::new();
+    for entry in &crate_entries {
+        let data = match entry.data() {
+            Ok(data) => data,
+            Err(err) => {
+                warn!("skipping descriptions for {}: {}", entry.name, err);
+                continue;
+            }
+        };
+        for shard in 0..descriptions::shard_count(&data.desc) {
+            shards.push((entry.name.as_ref(), shard));
+        }
+    }
+
+    let bar = if show_progress {
+        progress::bar(shards.len() as u64, "Downloading descriptions")
+    } else {
+        ProgressBar::hidden()
+    };
+    let results = run_concurrently(&shards, jobs, |&(crate_name, shard)| {
+        let shard_path = descriptions::shard_path(crate_name, shard, "");
+        bar.set_message(shard_path.clone());
+        let result = match download(agent, &format!("{}{}", base_url, shard_path), http) {
+            Ok(content) => write_cached(
+                &descriptions::shard_file(docs_dir, crate_name, shard, ""),
+                &content,
+            ),
+            Err(err) => {
+                warn!("could not fetch {}: {:#}", shard_path, err);
+                Ok(())
+            }
+        };
+        bar.inc(1);
+        result
+    });
+    bar.finish_and_clear();
+    info!("fetched {} description shards", shards.len());
+
+    results.into_iter().collect()
+}
+
+/// Apply `f` to every item using up to `jobs` threads, returning the results in
+/// the same order as `items`.
+fn run_concurrently<T: Sync, R: Send>(
+    items: &[T],
+    jobs: usize,
+    f: impl Fn(&T) -> R + Sync,
+) -> Vec<R> {
+    let next = AtomicUsize::new(0);
+    let results: Mutex<Vec<Option<R>>> = Mutex::new(items.iter().map(|_| None).collect());
+
+    std::thread::scope(|scope| {
+        for _ in 0..jobs.clamp(1, items.len().max(1)) {
+            scope.spawn(|| {
+                loop {
+                    let index = next.fetch_add(1, Ordering::Relaxed);
+                    let Some(item) = items.get(index) else {
+                        break;
+                    };
+                    let result = f(item);
+                    results.lock().expect("worker panicked")[index] = Some(result);
+                }
+            });
+        }
+    });
+
+    results
+        .into_inner()
+        .expect("worker panicked")
+        .into_iter()
+        .map(|result| result.expect("every item processed"))
+        .collect()
+}
+
+fn download(agent: &ureq::Agent, url: &str, http: &HttpOptions) -> Result<String> {
+    info!("GET {}", url);
+    let mut request = agent.get(url);
+    // ureq drops the Authorization header on redirects, so credentials stay on this host
+    for (name, value) in http.headers_for(url) {
+        request = request.header(name, value);
+    }
+
+    request
+        .call()
+        .wrap_err_with(|| format!("Failed to fetch {}", url))?
+        .body_mut()
+        .with_config()
+        // Search indexes for large doc sites are well over ureq's 10 MB default
+        .limit(u64::MAX)
+        .read_to_string()
+        .wrap_err_with(|| format!("Failed to read response from {}", url))
+}
+
+/// Write a file into the cache via a temporary file, so an interrupted download
+/// never leaves a truncated file that later runs would trust.
+fn write_cached(path: &Path, content: &str) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)
+            .wrap_err_with(|| format!("Failed to create {}", parent.display()))?;
+    }
+
+    output::write_atomic(path, content)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalize_base_url() {
+        assert_eq!(
+            normalize_base_url("https://docs.example.com/myapi").unwrap(),
+            "https://docs.example.com/myapi/"
+        );
+        assert_eq!(
+            normalize_base_url("http://localhost:8000/").unwrap(),
+            "http://localhost:8000/"
+        );
+        assert!(normalize_base_url("docs.example.com/myapi").is_err());
+    }
+
+    #[test]
+    fn test_run_concurrently_preserves_order() {
+        let items: Vec<u32> = (0..50).collect();
+        let doubled = run_concurrently(&items, 4, |n| n * 2);
+        assert_eq!(doubled, items.iter().map(|n| n * 2).collect::<Vec<_>>());
+
+        assert!(run_concurrently(&[] as &[u32], 4, |n| *n).is_empty());
+    }
+
+    #[test]
+    fn test_http_options_bearer_token() {
+        let config = HttpConfig {
+            token: Some("from-config".to_string()),
+            ..Default::default()
+        };
+
+        let options = HttpOptions::new(&config, None, &[]).unwrap();
+        assert_eq!(options.authorization.as_deref(), Some("Bearer from-config"));
+
+        // The environment variable wins over the config file
+        let options = HttpOptions::new(&config, Some("from-env".to_string()), &[]).unwrap();
+        assert_eq!(options.authorization.as_deref(), Some("Bearer from-env"));
+    }
+
+    #[test]
+    fn test_http_options_basic_auth() {
+        let config = HttpConfig {
+            username: Some("user".to_string()),
+            password: Some("pass".to_string()),
+            ..Default::default()
+        };
+
+        let options = HttpOptions::new(&config, None, &[]).unwrap();
+        assert_eq!(options.authorization.as_deref(), Some("Basic dXNlcjpwYXNz"));
+    }
+
+    #[test]
+    fn test_http_options_headers() {
+        let mut config = HttpConfig::default();
+        config
+            .headers
+            .insert("X-From-Config".to_string(), "1".to_string());
+
+        let options = HttpOptions::new(&config, None, &["X-From-Cli:  two ".to_string()]).unwrap();
+        assert_eq!(options.authorization, None);
+        assert_eq!(
+            options.headers,
+            vec![
+                ("X-From-Config".to_string(), "1".to_string()),
+                ("X-From-Cli".to_string(), "two".to_string()),
+            ]
+        );
+
+        assert!(HttpOptions::new(&config, None, &["no colon".to_string()]).is_err());
+        assert!(HttpOptions::new(&config, None, &[": value".to_string()]).is_err());
+    }
+
+    #[test]
+    fn test_http_options_scoped_to_host() {
+        let config = HttpConfig {
+            token: Some("secret".to_string()),
+            headers: [("X-Sso".to_string(), "1".to_string())].into(),
+            ..Default::default()
+        };
+        let options = HttpOptions::new(&config, None, &[]).unwrap();
+        let sent = |options: &HttpOptions, url| options.headers_for(url).len();
+
+        // Until scoped to a host, credentials go nowhere
+        assert_eq!(
+            sent(&options, "https://docs.rs/serde/1.0.0/search-index.js"),
+            0
+        );
+
+        let scoped = options.for_host("https://Docs.Example.com/myapi/");
+        assert_eq!(
+            sent(&scoped, "https://docs.example.com/myapi/search-index.js"),
+            2
+        );
+        assert_eq!(sent(&scoped, "https://docs.example.com/other/page.html"), 2);
+        assert_eq!(
+            sent(&scoped, "https://docs.rs/serde/1.0.0/search-index.js"),
+            0
+        );
+        assert_eq!(
+            sent(&scoped, "https://docs.example.com.evil.org/search-index.js"),
+            0
+        );
+        assert_eq!(
+            sent(&scoped, "http://docs.example.com/myapi/search-index.js"),
+            0
+        );
+        assert_eq!(
+            sent(&scoped, "https://docs.example.com:8443/search-index.js"),
+            0
+        );
+    }
+}
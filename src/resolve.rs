@@ -0,0 +1,464 @@
+// Resolving a path to exactly one item
+
+use crate::aliases::Aliases;
+use crate::normalize::{crate_path, strip_raw};
+use crate::pages::impl_origin;
+use crate::search_index::{ITEM_TYPES, ItemType};
+use crate::search_items::{SearchItem, dedup_reexports};
+use std::fmt;
+
+/// Why a path could not be resolved to a single item.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ResolveError {
+    /// The path is empty or has an empty segment
+    InvalidPath(String),
+    /// The `kind@` prefix isn't a known item kind
+    UnknownKind(String),
+    /// No item has this path
+    NotFound(String),
+    /// Several items have this path; each candidate is written with a `kind@`
+    /// prefix that resolves to it alone, and the impl it's from for methods
+    /// that only differ by impl
+    Ambiguous {
+        path: String,
+        candidates: Vec<String>,
+    },
+}
+
+impl fmt::Display for ResolveError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ResolveError::InvalidPath(path) => write!(f, "\"{}\" is not an item path", path),
+            ResolveError::UnknownKind(kind) => write!(f, "unknown item kind \"{}@\"", kind),
+            ResolveError::NotFound(path) => write!(f, "no item named \"{}\"", path),
+            ResolveError::Ambiguous { path, candidates } => {
+                write!(f, "\"{}\" is ambiguous; it could be any of:", path)?;
+                for candidate in candidates {
+                    write!(f, "\n  {}", candidate)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+impl std::error::Error for ResolveError {}
+
+impl ResolveError {
+    /// A short name for the kind of error, for JSON output: `invalid_path`,
+    /// `unknown_kind`, `not_found` or `ambiguous`.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            ResolveError::InvalidPath(_) => "invalid_path",
+            ResolveError::UnknownKind(_) => "unknown_kind",
+            ResolveError::NotFound(_) => "not_found",
+            ResolveError::Ambiguous { .. } => "ambiguous",
+        }
+    }
+
+    /// The items an ambiguous path could mean; empty for other errors.
+    pub fn candidates(&self) -> &[String] {
+        match self {
+            ResolveError::Ambiguous { candidates, .. } => candidates,
+            _ => &[],
+        }
+    }
+}
+
+/// Resolve `path` to the single item it names.
+///
+/// `path` is either a bare name (`Serialize`) or a full path
+/// (`serde_json::Value::as_str`), matched exactly against where the item is
+/// exported or where it is defined. Like rustdoc's intra-doc links, a kind
+/// prefix (`trait@Serialize`, `macro@vec`) or suffix (`vec!`, `len()`) picks
+/// between items that share a path. Re-exports of the same item count as one.
+pub fn resolve<'a>(
+    items: impl IntoIterator<Item = &'a SearchItem>,
+    path: &str,
+) -> Result<&'a SearchItem, ResolveError> {
+    single(items_at(items, path)?, path)
+}
+
+/// Like [`resolve`], but if nothing has the path, try it as an alias: one of
+/// rustdoc's `#[doc(alias)]` names or a user-defined alias.
+pub fn resolve_with_aliases<'a>(
+    items: impl IntoIterator<Item = &'a SearchItem> + Clone,
+    path: &str,
+    aliases: &Aliases,
+) -> Result<&'a SearchItem, ResolveError> {
+    match resolve(items.clone(), path) {
+        Err(ResolveError::NotFound(_)) => single(aliases.targets(items, path), path),
+        resolved => resolved,
+    }
+}
+
+/// Every item `path` names, re-exports included.
+pub(crate) fn items_at<'a>(
+    items: impl IntoIterator<Item = &'a SearchItem>,
+    path: &str,
+) -> Result<Vec<&'a SearchItem>, ResolveError> {
+    let (kinds, bare_path) = split_kind(path)?;
+    let bare_path = crate_path(bare_path);
+    // rustdoc indexes raw identifiers like `r#try` without their prefix
+    let segments: Vec<&str> = bare_path.split("::").map(strip_raw).collect();
+    if segments.iter().any(|segment| segment.is_empty()) {
+        return Err(ResolveError::InvalidPath(path.to_string()));
+    }
+    let bare_path = segments.join("::");
+
+    Ok(items
+        .into_iter()
+        .filter(|item| {
+            kinds.is_none_or(|kinds| kinds.contains(&item.item_type))
+                && match segments.as_slice() {
+                    [name] => item.name == *name,
+                    _ => {
+                        full_path(&item.path, item) == bare_path
+                            || full_path(&item.exact_path, item) == bare_path
+                    }
+                }
+        })
+        .collect())
+}
+
+/// The one item among `matches`, counting re-exports of it once.
+fn single<'a>(matches: Vec<&'a SearchItem>, path: &str) -> Result<&'a SearchItem, ResolveError> {
+    match dedup_reexports(matches).as_slice() {
+        [] => Err(ResolveError::NotFound(path.to_string())),
+        [item] => Ok(item),
+        candidates => Err(ResolveError::Ambiguous {
+            path: path.to_string(),
+            candidates: candidates
+                .iter()
+                .map(|item| match impl_origin(item) {
+                    Some(origin) => format!("{} (from {})", disambiguated_path(item), origin),
+                    None => disambiguated_path(item),
+                })
+                .collect(),
+        }),
+    }
+}
+
+/// The item's full path with a `kind@` prefix, e.g. `trait@serde::Serialize`.
+pub fn disambiguated_path(item: &SearchItem) -> String {
+    let path = item_path(item);
+    match kind_name(item.item_type) {
+        Some(kind) => format!("{}@{}", kind, path),
+        None => path,
+    }
+}
+
+/// The path to `use` `item` by, or `None` for items that can't be imported
+/// (keywords and impls).
+///
+/// Of every place the item is exported (its re-exports share its exact path),
+/// this picks one in a crate `usable` accepts (the project's dependencies),
+/// avoiding modules that look internal (`__private`), with the fewest
+/// segments, and preferring a re-export to where the item is defined, since
+/// facades like `std` and `serde` re-export what `alloc` or `serde_core`
+/// define. Methods, fields and associated items can't be imported on their
+/// own, so theirs is their type's path.
+pub fn import_path<'a>(
+    items: impl IntoIterator<Item = &'a SearchItem>,
+    item: &'a SearchItem,
+    usable: impl Fn(&str) -> bool,
+) -> Option<String> {
+    if matches!(item.item_type, ItemType::Keyword | ItemType::Impl) {
+        return None;
+    }
+    let importable = |exported: &SearchItem| {
+        let path = match item.item_type {
+            ItemType::Variant => full_path(&exported.path, exported),
+            _ => match exported.parent_name() {
+                Some(parent) if !exported.path.is_empty() => {
+                    format!("{}::{}", exported.path, parent)
+                }
+                Some(parent) => parent.to_string(),
+                None => full_path(&exported.path, exported),
+            },
+        };
+        let internal = path.split("::").any(|segment| segment.starts_with("__"));
+        let rank = (
+            !usable(&exported.crate_name),
+            internal,
+            path.matches("::").count(),
+            exported.exact_path.split("::").next() == Some(exported.crate_name.as_str()),
+        );
+        (rank, path)
+    };
+    items
+        .into_iter()
+        .filter(|other| {
+            other.exact_path == item.exact_path
+                && other.parent_name() == item.parent_name()
+                && other.name == item.name
+                && other.item_type == item.item_type
+                && other.impl_disambiguator == item.impl_disambiguator
+        })
+        .chain([item])
+        .map(importable)
+        .min()
+        .map(|(_, path)| path)
+}
+
+/// The item's full path, e.g. `serde::Serialize` or `serde::Serialize::serialize`.
+pub fn item_path(item: &SearchItem) -> String {
+    full_path(&item.path, item)
+}
+
+/// `module::Parent::name`, or `module::name` for items without a parent.
+fn full_path(module: &str, item: &SearchItem) -> String {
+    let mut full = String::from(module);
+    for segment in item.parent_name().into_iter().chain([item.name.as_str()]) {
+        if !full.is_empty() {
+            full.push_str("::");
+        }
+        full.push_str(segment);
+    }
+    full
+}
+
+/// Split a kind prefix or suffix off `path`, returning the item types it allows.
+fn split_kind(path: &str) -> Result<(Option<&'static [ItemType]>, &str), ResolveError> {
+    if let Some((kind, rest)) = path.split_once('@') {
+        let kinds = kind_types(kind).ok_or_else(|| ResolveError::UnknownKind(kind.to_string()))?;
+        return Ok((Some(kinds), rest));
+    }
+    if let Some(rest) = path.strip_suffix('!') {
+        return Ok((kind_types("macro"), rest));
+    }
+    if let Some(rest) = path.strip_suffix("()") {
+        return Ok((kind_types("fn"), rest));
+    }
+    Ok((None, path))
+}
+
+/// Item types for each kind prefix, following rustdoc's intra-doc link
+/// disambiguators; any other name of a type (see [`ItemType::name`]) selects
+/// that type alone.
+pub fn kind_types(kind: &str) -> Option<&'static [ItemType]> {
+    use ItemType::*;
+    Some(match kind {
+        "struct" => &[Struct],
+        "enum" => &[Enum],
+        "union" => &[Union],
+        "trait" => &[Trait, TraitAlias],
+        "type" => &[Typedef, AssocType, ForeignType],
+        "mod" | "module" => &[Module],
+        "const" | "constant" => &[Constant, AssocConst],
+        "static" => &[Static],
+        "fn" | "function" | "method" => &[Function, Method, TyMethod],
+        "macro" => &[Macro, ProcAttribute, ProcDerive],
+        "derive" => &[ProcDerive],
+        "attr" => &[ProcAttribute],
+        "prim" | "primitive" => &[Primitive],
+        "field" => &[StructField],
+        "variant" => &[Variant],
+        "keyword" => &[Keyword],
+        _ => {
+            let item_type: ItemType = kind.parse().ok()?;
+            return ITEM_TYPES
+                .iter()
+                .find(|known| **known == item_type)
+                .map(std::slice::from_ref);
+        }
+    })
+}
+
+/// The kind prefix that selects `item_type`, the inverse of [`kind_types`].
+fn kind_name(item_type: ItemType) -> Option<&'static str> {
+    use ItemType::*;
+    Some(match item_type {
+        Struct => "struct",
+        Enum => "enum",
+        Union => "union",
+        Trait | TraitAlias => "trait",
+        Typedef | AssocType | ForeignType => "type",
+        Module => "mod",
+        Constant | AssocConst => "const",
+        Static => "static",
+        Function | Method | TyMethod => "fn",
+        Macro => "macro",
+        ProcDerive => "derive",
+        ProcAttribute => "attr",
+        Primitive => "prim",
+        StructField => "field",
+        Variant => "variant",
+        Keyword => "keyword",
+        // Kinds rustdoc links have no prefix for
+        ExternCrate | Import | Impl | OpaqueTy | Unknown(_) => {
+            return None;
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn item(
+        crate_name: &str,
+        path: &str,
+        exact_path: &str,
+        parent: Option<&str>,
+        name: &str,
+        item_type: ItemType,
+    ) -> SearchItem {
+        let item = SearchItem::builder(crate_name, item_type, name)
+            .path(path)
+            .exact_path(exact_path);
+        match parent {
+            Some(parent) => item.parent(parent, ItemType::Struct).build(),
+            None => item.build(),
+        }
+    }
+
+    fn items() -> Vec<SearchItem> {
+        vec![
+            item(
+                "serde",
+                "serde",
+                "serde::ser",
+                None,
+                "Serialize",
+                ItemType::Trait,
+            ),
+            item(
+                "serde",
+                "serde",
+                "serde",
+                None,
+                "Serialize",
+                ItemType::ProcDerive,
+            ),
+            // A re-export of the trait from another crate
+            item(
+                "serde_core",
+                "serde_core",
+                "serde::ser",
+                None,
+                "Serialize",
+                ItemType::Trait,
+            ),
+            item(
+                "serde_json",
+                "serde_json",
+                "serde_json::value",
+                Some("Value"),
+                "as_str",
+                ItemType::Method,
+            ),
+            item(
+                "serde_json",
+                "serde_json",
+                "serde_json",
+                None,
+                "json",
+                ItemType::Macro,
+            ),
+        ]
+    }
+
+    #[test]
+    fn test_resolve_full_and_defining_paths() {
+        let items = items();
+        let as_str = resolve(&items, "serde_json::Value::as_str").unwrap();
+        assert_eq!(as_str.name, "as_str");
+        assert_eq!(
+            resolve(&items, "serde_json::value::Value::as_str").unwrap(),
+            as_str
+        );
+        assert_eq!(resolve(&items, "json!").unwrap().name, "json");
+        // Raw identifiers resolve to the item rustdoc indexes without `r#`
+        assert_eq!(
+            resolve(&items, "r#serde_json::Value::r#as_str").unwrap(),
+            as_str
+        );
+        // The crate as its package is named
+        assert_eq!(
+            resolve(&items, "serde-json::Value::as_str").unwrap(),
+            as_str
+        );
+    }
+
+    #[test]
+    fn test_resolve_ambiguity_and_kinds() {
+        let items = items();
+        assert_eq!(
+            resolve(&items, "serde::Serialize"),
+            Err(ResolveError::Ambiguous {
+                path: "serde::Serialize".to_string(),
+                candidates: vec![
+                    "trait@serde::Serialize".to_string(),
+                    "derive@serde::Serialize".to_string()
+                ],
+            })
+        );
+
+        // Both the candidate spellings resolve, and re-exports collapse to the definition
+        let serialize = resolve(&items, "trait@serde::Serialize").unwrap();
+        assert_eq!(serialize.crate_name, "serde");
+        assert_eq!(resolve(&items, "trait@Serialize").unwrap(), serialize);
+        assert_eq!(
+            resolve(&items, "derive@serde::Serialize")
+                .unwrap()
+                .item_type,
+            ItemType::ProcDerive
+        );
+    }
+
+    #[test]
+    fn test_import_path() {
+        let items = items();
+        let serialize = &items[0];
+        // The re-export over the definition, unless only the definition's crate is usable
+        assert_eq!(
+            import_path(&items, serialize, |_| true).as_deref(),
+            Some("serde_core::Serialize")
+        );
+        assert_eq!(
+            import_path(&items, serialize, |name| name == "serde").as_deref(),
+            Some("serde::Serialize")
+        );
+        // Methods are imported with their type
+        assert_eq!(
+            import_path(&items, &items[3], |_| true).as_deref(),
+            Some("serde_json::Value")
+        );
+
+        let hidden = item(
+            "serde",
+            "serde::__private",
+            "serde::__private",
+            None,
+            "Helper",
+            ItemType::Struct,
+        );
+        let public = SearchItem {
+            path: "serde::de::value".to_string(),
+            ..hidden.clone()
+        };
+        assert_eq!(
+            import_path(&[hidden.clone(), public], &hidden, |_| true).as_deref(),
+            Some("serde::de::value::Helper")
+        );
+    }
+
+    #[test]
+    fn test_resolve_errors() {
+        let items = items();
+        assert_eq!(
+            resolve(&items, "serde::Deserialize"),
+            Err(ResolveError::NotFound("serde::Deserialize".to_string()))
+        );
+        assert_eq!(resolve(&items, "serde::ser::Serialize"), Ok(&items[0]));
+        assert_eq!(
+            resolve(&items, "widget@Serialize"),
+            Err(ResolveError::UnknownKind("widget".to_string()))
+        );
+        assert_eq!(
+            resolve(&items, "serde::"),
+            Err(ResolveError::InvalidPath("serde::".to_string()))
+        );
+    }
+}
@@ -0,0 +1,104 @@
+// Names of the files rustdoc shares between crates, which `--resource-suffix` changes
+
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+use tracing::debug;
+
+/// What every search index's file name starts with
+const SEARCH_INDEX_STEM: &str = "search-index";
+
+/// What the file name of the doc root's list of crates, for the sidebar's
+/// crate picker, starts with
+const CRATES_STEM: &str = "crates";
+
+/// The search index in `doc_root`: `search-index.js`, or for docs built with
+/// `--resource-suffix`, `search-index<suffix>.js` (`search-index-1.78.0.js`,
+/// say). If there are several suffixed ones, the last written is taken.
+pub fn search_index_file(doc_root: &Path) -> Option<PathBuf> {
+    let plain = doc_root.join(format!("{}.js", SEARCH_INDEX_STEM));
+    if plain.is_file() {
+        return Some(plain);
+    }
+    let mut found: Vec<(Option<SystemTime>, PathBuf)> = std::fs::read_dir(doc_root)
+        .ok()?
+        .flatten()
+        .filter(|entry| {
+            let name = entry.file_name();
+            name.to_str().and_then(suffix_of_search_index).is_some()
+        })
+        .map(|entry| {
+            let modified = entry.metadata().and_then(|meta| meta.modified()).ok();
+            (modified, entry.path())
+        })
+        .collect();
+    found.sort();
+    let (_, path) = found.pop()?;
+    debug!("found suffixed search index {}", path.display());
+    Some(path)
+}
+
+/// The resource suffix of the search index at `search_index_path`, empty if
+/// it has none (or isn't named like a search index).
+pub fn resource_suffix(search_index_path: &Path) -> &str {
+    search_index_path
+        .file_name()
+        .and_then(|name| name.to_str())
+        .and_then(suffix_of_search_index)
+        .unwrap_or("")
+}
+
+/// The resource suffix of the docs in `doc_root`, empty if they have none.
+pub fn suffix_in(doc_root: &Path) -> String {
+    search_index_file(doc_root)
+        .map(|path| resource_suffix(&path).to_string())
+        .unwrap_or_default()
+}
+
+/// The name of `crates.js` for docs with resource suffix `suffix`.
+pub fn crates_file(suffix: &str) -> String {
+    format!("{}{}.js", CRATES_STEM, suffix)
+}
+
+fn suffix_of_search_index(file_name: &str) -> Option<&str> {
+    file_name
+        .strip_prefix(SEARCH_INDEX_STEM)?
+        .strip_suffix(".js")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resource_suffix() {
+        assert_eq!(resource_suffix(Path::new("doc/search-index.js")), "");
+        assert_eq!(
+            resource_suffix(Path::new("doc/search-index-1.78.0.js")),
+            "-1.78.0"
+        );
+        assert_eq!(resource_suffix(Path::new("doc/index.html")), "");
+        assert_eq!(crates_file("-1.78.0"), "crates-1.78.0.js");
+    }
+
+    #[test]
+    fn test_search_index_file() {
+        let dir = std::env::temp_dir().join(format!("rdoc-resources-test-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        assert_eq!(search_index_file(&dir), None);
+
+        std::fs::write(dir.join("search-index.js.bak"), "").unwrap();
+        std::fs::write(dir.join("search-index-1.78.0.js"), "").unwrap();
+        assert_eq!(
+            search_index_file(&dir),
+            Some(dir.join("search-index-1.78.0.js"))
+        );
+        assert_eq!(suffix_in(&dir), "-1.78.0");
+
+        // The unsuffixed index wins
+        std::fs::write(dir.join("search-index.js"), "").unwrap();
+        assert_eq!(search_index_file(&dir), Some(dir.join("search-index.js")));
+        assert_eq!(suffix_in(&dir), "");
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,122 @@
+// The rustdoc version that wrote a set of docs, and the index format it writes
+
+use std::fmt;
+
+/// The attribute rustdoc pages record the version that wrote them in
+const VERSION_ATTRIBUTE: &str = "data-rustdoc-version=\"";
+
+/// First version encoding function signatures in `f` as VLQ hex
+const VLQ_SIGNATURES: RustdocVersion = RustdocVersion::new(1, 71, 0);
+
+/// First version moving descriptions into `search.desc` shards (sized by `D`)
+/// and writing the `c` and `e` flags as roaring bitmaps
+const DESCRIPTION_SHARDS: RustdocVersion = RustdocVersion::new(1, 78, 0);
+
+/// A rustdoc (toolchain) version, e.g. `1.95.0`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct RustdocVersion {
+    pub major: u32,
+    pub minor: u32,
+    pub patch: u32,
+}
+
+impl RustdocVersion {
+    pub const fn new(major: u32, minor: u32, patch: u32) -> Self {
+        Self {
+            major,
+            minor,
+            patch,
+        }
+    }
+
+    /// Parse a version as rustdoc writes it, possibly with a channel and
+    /// commit after it: `1.95.0 (59807616e 2026-04-14)`,
+    /// `1.80.0-nightly (ada5e2c7b 2024-05-31)`.
+    pub fn parse(text: &str) -> Option<Self> {
+        let version = text.split([' ', '-']).next()?;
+        let mut parts = version.split('.').map(|part| part.parse::<u32>().ok());
+        let version = Self::new(parts.next()??, parts.next()??, parts.next()??);
+        parts.next().is_none().then_some(version)
+    }
+
+    /// The version a rustdoc page says wrote it, or `None` if it doesn't.
+    pub fn from_page(html: &str) -> Option<Self> {
+        let start = html.find(VERSION_ATTRIBUTE)? + VERSION_ATTRIBUTE.len();
+        let end = start + html[start..].find('"')?;
+        Self::parse(&html[start..end])
+    }
+}
+
+impl fmt::Display for RustdocVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}.{}.{}", self.major, self.minor, self.patch)
+    }
+}
+
+/// The parts of the search index format that vary with the rustdoc version,
+/// so decoding only reads fields in the encoding the index has.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct IndexFeatures {
+    /// Function signatures in `f` are VLQ hex encoded
+    pub vlq_signatures: bool,
+    /// Descriptions are in `search.desc` shards, and the deprecated (`c`) and
+    /// empty description (`e`) flags are roaring bitmaps
+    pub description_shards: bool,
+}
+
+impl IndexFeatures {
+    /// The features of indexes `version` writes. Without a version, the
+    /// latest format is assumed.
+    pub fn of(version: Option<RustdocVersion>) -> Self {
+        match version {
+            Some(version) => Self {
+                vlq_signatures: version >= VLQ_SIGNATURES,
+                description_shards: version >= DESCRIPTION_SHARDS,
+            },
+            None => Self::default(),
+        }
+    }
+}
+
+impl Default for IndexFeatures {
+    fn default() -> Self {
+        Self {
+            vlq_signatures: true,
+            description_shards: true,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(
+            RustdocVersion::parse("1.95.0 (59807616e 2026-04-14)"),
+            Some(RustdocVersion::new(1, 95, 0))
+        );
+        assert_eq!(
+            RustdocVersion::parse("1.80.0-nightly (ada5e2c7b 2024-05-31)"),
+            Some(RustdocVersion::new(1, 80, 0))
+        );
+        assert_eq!(RustdocVersion::parse("1.95"), None);
+        assert_eq!(RustdocVersion::parse("1.95.0.1"), None);
+        assert_eq!(RustdocVersion::parse(""), None);
+
+        let page = r#"<meta name="rustdoc-vars" data-root-path="../" data-rustdoc-version="1.95.0 (59807616e 2026-04-14)" data-channel="1.95.0">"#;
+        let version = RustdocVersion::from_page(page).unwrap();
+        assert_eq!(version.to_string(), "1.95.0");
+        assert_eq!(RustdocVersion::from_page("<html></html>"), None);
+    }
+
+    #[test]
+    fn test_index_features() {
+        assert_eq!(IndexFeatures::of(None), IndexFeatures::default());
+        let old = IndexFeatures::of(Some(RustdocVersion::new(1, 75, 0)));
+        assert!(old.vlq_signatures && !old.description_shards);
+        assert!(IndexFeatures::of(Some(RustdocVersion::new(1, 78, 0))).description_shards);
+        assert!(!IndexFeatures::of(Some(RustdocVersion::new(1, 70, 0))).vlq_signatures);
+    }
+}
@@ -0,0 +1,417 @@
+// JSON Schema for rdoc's JSON output, and the header that versions it
+
+use serde::Serialize;
+use serde_json::{Value, json};
+
+/// Version of the JSON output formats. Bumped when a field is removed, renamed
+/// or changes meaning; adding fields doesn't change it.
+pub const SCHEMA_VERSION: u32 = 2;
+
+/// `$id` of the schema `rdoc schema` prints.
+pub const SCHEMA_ID: &str = "urn:rdoc:output-schema:2";
+
+/// JSON output with a header naming the schema definition it follows.
+///
+/// The header fields come first, then `body`'s fields.
+#[derive(Debug, Serialize)]
+pub struct Versioned<T> {
+    /// The definition in the schema, e.g. `urn:rdoc:output-schema:2#/$defs/Results`
+    #[serde(rename = "$schema")]
+    pub schema: String,
+    pub schema_version: u32,
+    #[serde(flatten)]
+    pub body: T,
+}
+
+impl<T> Versioned<T> {
+    /// Wrap `body`, which follows the schema definition named `definition`.
+    pub fn new(definition: &str, body: T) -> Self {
+        Self {
+            schema: format!("{}#/$defs/{}", SCHEMA_ID, definition),
+            schema_version: SCHEMA_VERSION,
+            body,
+        }
+    }
+}
+
+/// The JSON Schema (draft 2020-12) of every JSON format rdoc writes:
+///
+/// - `Results`: `scan` and `resolve` with `--format json`
+/// - `QueryResults`: each line of `scan --stdin --format ndjson`, and
+///   responses of `serve`'s `/search`
+/// - `BatchResults`: `scan --stdin --format json`
+/// - `Resolutions`: responses of `serve`'s `/resolve`
+/// - `ItemPreview`: responses of `serve`'s `/item`
+/// - `ResultRecord`: each line of `--format ndjson`, and of the input to
+///   `--format exec:COMMAND` formatters
+/// - `DocChunk`: each line of `export chunks`
+/// - `ApiStats`: `stats --format json`, the snapshot `--compare` reads
+/// - `StatsComparison`: `stats --compare --format json`
+pub fn output_schema() -> Value {
+    let header = |definition: &str| {
+        json!({
+            "$schema": { "const": format!("{}#/$defs/{}", SCHEMA_ID, definition) },
+            "schema_version": { "const": SCHEMA_VERSION },
+        })
+    };
+    let with_header = |definition: &str, properties: Value, required: &[&str]| {
+        let mut all = header(definition);
+        all.as_object_mut()
+            .unwrap()
+            .extend(properties.as_object().unwrap().clone());
+        let mut all_required = vec!["$schema", "schema_version"];
+        all_required.extend(required);
+        json!({
+            "type": "object",
+            "properties": all,
+            "required": all_required,
+        })
+    };
+    let counts = json!({
+        "type": "object",
+        "additionalProperties": { "type": "integer", "minimum": 0 },
+    });
+    let changes = json!({
+        "type": "object",
+        "additionalProperties": { "$ref": "#/$defs/Change" },
+    });
+
+    json!({
+        "$schema": "https://json-schema.org/draft/2020-12/schema",
+        "$id": SCHEMA_ID,
+        "title": "rdoc JSON output",
+        "description": format!(
+            "JSON formats written by rdoc, version {}. Each document names its definition in `$schema`.",
+            SCHEMA_VERSION
+        ),
+        "oneOf": [
+            { "$ref": "#/$defs/Results" },
+            { "$ref": "#/$defs/BatchResults" },
+            { "$ref": "#/$defs/Resolutions" },
+            { "$ref": "#/$defs/ItemPreview" },
+            { "$ref": "#/$defs/ApiStats" },
+            { "$ref": "#/$defs/StatsComparison" },
+        ],
+        "$defs": {
+            "ResultRecord": {
+                "description": "One search result",
+                "type": "object",
+                "properties": {
+                    "id": {
+                        "description": "Unique item ID, e.g. serde#142@local",
+                        "type": "string",
+                    },
+                    "name": { "type": "string" },
+                    "item_type": {
+                        "description": "Kind of item, by rustdoc's name for it, e.g. struct, fn or method",
+                        "type": "string",
+                    },
+                    "crate": { "type": "string" },
+                    "path": {
+                        "description": "Module path the item is documented at",
+                        "type": "string",
+                    },
+                    "exact_path": {
+                        "description": "Module path the item is defined at",
+                        "type": "string",
+                    },
+                    "deprecated": { "type": "boolean" },
+                    "url": {
+                        "description": "Page relative to the doc root, e.g. serde/trait.Serialize.html",
+                        "type": ["string", "null"],
+                    },
+                    "absolute_url": {
+                        "description": "url on the doc site, when its base URL is known",
+                        "type": ["string", "null"],
+                    },
+                },
+                "required": [
+                    "id", "name", "item_type", "crate", "path", "exact_path", "deprecated",
+                    "url", "absolute_url",
+                ],
+            },
+            "DocChunk": {
+                "description": "One item's declaration and documentation, with the fields of a ResultRecord",
+                "type": "object",
+                "allOf": [{ "$ref": "#/$defs/ResultRecord" }],
+                "properties": {
+                    "qualified_path": {
+                        "description": "Full path with a kind@ prefix, e.g. trait@serde::Serialize",
+                        "type": "string",
+                    },
+                    "signature": {
+                        "description": "The declaration from the item's page, when it can be read",
+                        "type": ["string", "null"],
+                    },
+                    "text": {
+                        "description": "The documentation as text, or the description when the page can't be read",
+                        "type": "string",
+                    },
+                    "truncated": {
+                        "description": "Whether text was cut short to --max-chars",
+                        "type": "boolean",
+                    },
+                },
+                "required": ["qualified_path", "signature", "text", "truncated"],
+            },
+            "Results": with_header(
+                "Results",
+                json!({
+                    "results": {
+                        "type": "array",
+                        "items": { "$ref": "#/$defs/ResultRecord" },
+                    },
+                    "timings": { "$ref": "#/$defs/Timings" },
+                }),
+                &["results"],
+            ),
+            "Timings": {
+                "description": "Milliseconds each phase of answering the query took, with scan --timings; render_ms is 0, as the results are still being written",
+                "type": "object",
+                "properties": {
+                    "locate_ms": { "type": "number", "minimum": 0 },
+                    "extract_ms": { "type": "number", "minimum": 0 },
+                    "parse_ms": { "type": "number", "minimum": 0 },
+                    "decode_ms": { "type": "number", "minimum": 0 },
+                    "match_ms": { "type": "number", "minimum": 0 },
+                    "rank_ms": { "type": "number", "minimum": 0 },
+                    "render_ms": { "type": "number", "minimum": 0 },
+                },
+                "required": [
+                    "locate_ms", "extract_ms", "parse_ms", "decode_ms", "match_ms", "rank_ms",
+                    "render_ms",
+                ],
+            },
+            "QueryResults": {
+                "description": "One query's results from scan --stdin",
+                "type": "object",
+                "properties": {
+                    "query": { "type": "string" },
+                    "error": {
+                        "description": "Why the query failed, in which case results is empty",
+                        "type": ["string", "null"],
+                    },
+                    "results": {
+                        "type": "array",
+                        "items": { "$ref": "#/$defs/ResultRecord" },
+                    },
+                },
+                "required": ["query", "error", "results"],
+            },
+            "BatchResults": with_header(
+                "BatchResults",
+                json!({
+                    "queries": {
+                        "type": "array",
+                        "items": { "$ref": "#/$defs/QueryResults" },
+                    },
+                }),
+                &["queries"],
+            ),
+            "Resolution": {
+                "description": "One path's outcome: the item it names, or why it doesn't name exactly one",
+                "type": "object",
+                "properties": {
+                    "path": { "type": "string" },
+                    "item": {
+                        "oneOf": [{ "$ref": "#/$defs/ResultRecord" }, { "type": "null" }],
+                    },
+                    "error": {
+                        "oneOf": [{ "$ref": "#/$defs/ResolutionError" }, { "type": "null" }],
+                    },
+                },
+                "required": ["path", "item", "error"],
+            },
+            "ResolutionError": {
+                "type": "object",
+                "properties": {
+                    "kind": {
+                        "enum": ["invalid_path", "unknown_kind", "not_found", "ambiguous"],
+                    },
+                    "message": { "type": "string" },
+                    "candidates": {
+                        "description": "For ambiguous paths, a spelling of each candidate that resolves to it alone",
+                        "type": "array",
+                        "items": { "type": "string" },
+                    },
+                },
+                "required": ["kind", "message", "candidates"],
+            },
+            "Resolutions": with_header(
+                "Resolutions",
+                json!({
+                    "results": {
+                        "type": "array",
+                        "items": { "$ref": "#/$defs/Resolution" },
+                    },
+                }),
+                &["results"],
+            ),
+            "ItemPreview": with_header(
+                "ItemPreview",
+                json!({
+                    "item": { "$ref": "#/$defs/ResultRecord" },
+                    "origin": {
+                        "description": "The impl a method comes from, e.g. impl Read for File",
+                        "type": ["string", "null"],
+                    },
+                    "declaration": {
+                        "description": "The declaration from the item's page, when it can be read",
+                        "type": ["string", "null"],
+                    },
+                    "description": {
+                        "description": "The description as plain text",
+                        "type": ["string", "null"],
+                    },
+                }),
+                &["item", "origin", "declaration", "description"],
+            ),
+            "ApiStats": with_header(
+                "ApiStats",
+                json!({
+                    "total": { "type": "integer", "minimum": 0 },
+                    "by_type": counts,
+                    "by_module": counts,
+                }),
+                &["total", "by_type", "by_module"],
+            ),
+            "Change": {
+                "type": "object",
+                "properties": {
+                    "before": { "type": "integer", "minimum": 0 },
+                    "after": { "type": "integer", "minimum": 0 },
+                    "change": { "type": "integer" },
+                },
+                "required": ["before", "after", "change"],
+            },
+            "StatsComparison": with_header(
+                "StatsComparison",
+                json!({
+                    "total": { "$ref": "#/$defs/Change" },
+                    "by_type": changes,
+                    "by_module": changes,
+                }),
+                &["total", "by_type", "by_module"],
+            ),
+        },
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chunks::DocChunk;
+    use crate::format::{
+        BatchResults, Format, ItemPreview, QueryResults, Resolution, Resolutions, ResultRecord,
+    };
+    use crate::pages::SiteLinks;
+    use crate::resolve::{disambiguated_path, resolve};
+    use crate::search_index::{extract_json_string, parse_search_index};
+    use crate::search_items::decode_crate;
+    use crate::stats::ApiStats;
+    use crate::timings::Timings;
+
+    /// The fields of `value`, sorted.
+    fn keys(value: &Value) -> Vec<String> {
+        let mut keys: Vec<String> = value.as_object().unwrap().keys().cloned().collect();
+        keys.sort();
+        keys
+    }
+
+    /// The fields definition `name` requires, sorted.
+    fn required(schema: &Value, name: &str) -> Vec<String> {
+        let mut keys: Vec<String> = schema["$defs"][name]["required"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|key| key.as_str().unwrap().to_string())
+            .collect();
+        keys.sort();
+        keys
+    }
+
+    #[test]
+    fn test_schema_matches_output() {
+        let content = std::fs::read_to_string("tests/fixtures/search-index.js").unwrap();
+        let entries = parse_search_index(&extract_json_string(&content).unwrap()).unwrap();
+        let items = decode_crate(&entries[0].name, &entries[0].data);
+        let schema = output_schema();
+
+        let record = serde_json::to_value(ResultRecord::from(&items[0])).unwrap();
+        assert_eq!(keys(&record), required(&schema, "ResultRecord"));
+
+        let chunk = DocChunk {
+            item: ResultRecord::from(&items[0]),
+            qualified_path: disambiguated_path(&items[0]),
+            signature: None,
+            text: String::new(),
+            truncated: false,
+        };
+        let chunk = serde_json::to_value(chunk).unwrap();
+        let mut chunk_fields = required(&schema, "ResultRecord");
+        chunk_fields.extend(required(&schema, "DocChunk"));
+        chunk_fields.sort();
+        assert_eq!(keys(&chunk), chunk_fields);
+
+        let mut json = Vec::new();
+        Format::Json
+            .formatter()
+            .format(&[&items[0]], &mut json)
+            .unwrap();
+        let results: Value = serde_json::from_slice(&json).unwrap();
+        assert_eq!(keys(&results), required(&schema, "Results"));
+        assert_eq!(
+            results["$schema"],
+            schema["$defs"]["Results"]["properties"]["$schema"]["const"]
+        );
+
+        let timings = serde_json::to_value(Timings::default()).unwrap();
+        assert_eq!(keys(&timings), required(&schema, "Timings"));
+
+        let group = QueryResults::new("Serialize", &[&items[0]], &SiteLinks::default());
+        let group = serde_json::to_value(group).unwrap();
+        assert_eq!(keys(&group), required(&schema, "QueryResults"));
+        let batch = Versioned::new("BatchResults", BatchResults { queries: vec![] });
+        let batch = serde_json::to_value(batch).unwrap();
+        assert_eq!(keys(&batch), required(&schema, "BatchResults"));
+
+        let links = SiteLinks::default();
+        let resolved = Resolution::new(&items[0].name, Ok(&items[0]), &links);
+        let resolved = serde_json::to_value(resolved).unwrap();
+        assert_eq!(keys(&resolved), required(&schema, "Resolution"));
+        let failed = Resolution::new("nowhere", resolve(&items, "nowhere"), &links);
+        let failed = serde_json::to_value(failed).unwrap();
+        assert_eq!(failed["error"]["kind"], "not_found");
+        assert_eq!(keys(&failed["error"]), required(&schema, "ResolutionError"));
+        let resolutions = Versioned::new("Resolutions", Resolutions { results: vec![] });
+        let resolutions = serde_json::to_value(resolutions).unwrap();
+        assert_eq!(keys(&resolutions), required(&schema, "Resolutions"));
+
+        let preview = Versioned::new(
+            "ItemPreview",
+            ItemPreview {
+                item: ResultRecord::from(&items[0]),
+                origin: None,
+                declaration: None,
+                description: None,
+            },
+        );
+        let preview = serde_json::to_value(preview).unwrap();
+        assert_eq!(keys(&preview), required(&schema, "ItemPreview"));
+
+        let stats = ApiStats::of(&items);
+        let snapshot = serde_json::to_value(Versioned::new("ApiStats", &stats)).unwrap();
+        assert_eq!(keys(&snapshot), required(&schema, "ApiStats"));
+        let comparison = serde_json::to_value(Versioned::new(
+            "StatsComparison",
+            stats.compare(&ApiStats::default()),
+        ))
+        .unwrap();
+        assert_eq!(keys(&comparison), required(&schema, "StatsComparison"));
+        assert_eq!(keys(&comparison["total"]), required(&schema, "Change"));
+
+        // Snapshots with a header still read back as stats
+        let read: ApiStats = serde_json::from_value(snapshot).unwrap();
+        assert_eq!(read, stats);
+    }
+}
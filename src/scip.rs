@@ -0,0 +1,555 @@
+// Reading items from a SCIP index, such as `rust-analyzer scip` writes, for
+// projects without generated docs
+
+use crate::item_id::ItemId;
+use crate::search_index::ItemType;
+use crate::search_items::{Parent, Parents, SearchItem};
+use std::collections::HashMap;
+use std::fmt;
+
+/// Error returned when a SCIP index can't be read.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ScipError {
+    /// The data ends in the middle of a field
+    Truncated,
+    /// A field uses a protobuf wire type SCIP doesn't
+    WireType(u64),
+    /// A string field isn't valid UTF-8
+    Utf8,
+}
+
+impl fmt::Display for ScipError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ScipError::Truncated => write!(f, "SCIP index is truncated"),
+            ScipError::WireType(wire_type) => {
+                write!(
+                    f,
+                    "SCIP index has a field of unknown wire type {}",
+                    wire_type
+                )
+            }
+            ScipError::Utf8 => write!(f, "SCIP index has a string that isn't UTF-8"),
+        }
+    }
+}
+
+impl std::error::Error for ScipError {}
+
+/// A symbol defined in the index, with what SCIP says about it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SymbolInformation {
+    /// The symbol, e.g. `rust-analyzer cargo fst 0.4.7 map/Map#new().`
+    pub symbol: String,
+    /// `SymbolInformation.Kind`, or 0 when the indexer didn't set it
+    pub kind: u64,
+    /// The symbol's signature and doc comment, in Markdown
+    pub documentation: Vec<String>,
+}
+
+/// The symbols an index defines, from its documents and its external symbols.
+pub fn read_symbols(index: &[u8]) -> Result<Vec<SymbolInformation>, ScipError> {
+    let mut symbols = Vec::new();
+    let mut fields = Fields::new(index);
+    while let Some((number, value)) = fields.next_field()? {
+        match (number, value) {
+            // Index.documents
+            (2, Value::Bytes(document)) => {
+                let mut fields = Fields::new(document);
+                while let Some((number, value)) = fields.next_field()? {
+                    // Document.symbols
+                    if let (3, Value::Bytes(symbol)) = (number, value) {
+                        symbols.push(symbol_information(symbol)?);
+                    }
+                }
+            }
+            // Index.external_symbols
+            (3, Value::Bytes(symbol)) => symbols.push(symbol_information(symbol)?),
+            _ => {}
+        }
+    }
+    Ok(symbols)
+}
+
+/// The items `symbols` define, as search items of the crates they're in,
+/// with IDs from `source`. Locals, parameters and type parameters aren't
+/// items, and are left out.
+///
+/// Items get their kinds from SCIP's `Kind` when the indexer set it, and
+/// otherwise from how their symbols end (`/` for modules, `#` for types,
+/// `().` for functions and so on). SCIP has no re-exports, so each item's
+/// path is where it's defined.
+pub fn decode_symbols(symbols: &[SymbolInformation], source: &str) -> Vec<SearchItem> {
+    let parsed: Vec<(&SymbolInformation, ParsedSymbol)> = symbols
+        .iter()
+        .filter_map(|info| Some((info, parse_symbol(&info.symbol)?)))
+        .collect();
+    let (parents, parent_indexes) = parent_table(&parsed);
+
+    let mut next_index: HashMap<&str, usize> = HashMap::new();
+    parsed
+        .iter()
+        .zip(parent_indexes)
+        .map(|((info, symbol), parent_index)| {
+            let index = next_index.entry(symbol.crate_name.as_str()).or_default();
+            let item = symbol_item(info, symbol, source, *index, parent_index, &parents);
+            *index += 1;
+            item
+        })
+        .collect()
+}
+
+/// One parent table for the whole index, each parent once, and the position
+/// in it of each of `parsed`'s parents.
+fn parent_table(parsed: &[(&SymbolInformation, ParsedSymbol)]) -> (Parents, Vec<Option<usize>>) {
+    let kinds: HashMap<&str, ItemType> = parsed
+        .iter()
+        .map(|(info, symbol)| (info.symbol.as_str(), item_type(info.kind, symbol)))
+        .collect();
+    let mut parent_positions: HashMap<(&str, ItemType), usize> = HashMap::new();
+    let mut parent_table = Vec::new();
+    let parent_indexes = parsed
+        .iter()
+        .map(|(_, symbol)| {
+            let name = symbol.parent.as_deref()?;
+            let item_type = symbol
+                .parent_symbol
+                .as_deref()
+                .and_then(|parent| kinds.get(parent).copied())
+                .unwrap_or(ItemType::Struct);
+            Some(
+                *parent_positions
+                    .entry((name, item_type))
+                    .or_insert_with(|| {
+                        parent_table.push(Parent {
+                            name: name.to_string(),
+                            item_type,
+                        });
+                        parent_table.len() - 1
+                    }),
+            )
+        })
+        .collect();
+    (Parents::new(parent_table), parent_indexes)
+}
+
+/// The search item for `symbol`, the `index`th of its crate.
+fn symbol_item(
+    info: &SymbolInformation,
+    symbol: &ParsedSymbol,
+    source: &str,
+    index: usize,
+    parent_index: Option<usize>,
+    parents: &Parents,
+) -> SearchItem {
+    let mut item_type = item_type(info.kind, symbol);
+    let parent_type = parent_index
+        .and_then(|index| parents.get(index))
+        .map(|parent| parent.item_type);
+    // Trait methods and constants are only told apart by their parent
+    if parent_type == Some(ItemType::Trait) {
+        item_type = match item_type {
+            ItemType::Method => ItemType::TyMethod,
+            ItemType::Constant => ItemType::AssocConst,
+            item_type => item_type,
+        };
+    }
+    let path = match symbol.modules.is_empty() {
+        true => symbol.crate_name.clone(),
+        false => format!("{}::{}", symbol.crate_name, symbol.modules.join("::")),
+    };
+    SearchItem {
+        crate_name: symbol.crate_name.clone(),
+        item_type,
+        name: symbol.name.clone(),
+        normalized_name: symbol.name.to_lowercase(),
+        exact_path: path.clone(),
+        path,
+        id: ItemId::new(source, symbol.crate_name.as_str(), index),
+        param_types: vec![],
+        impl_disambiguator: None,
+        bit_index: index + 1,
+        parent_index,
+        parents: parents.clone(),
+        deprecated: false,
+        // The first entry is the signature, not a doc comment
+        empty_description: info.documentation.len() < 2,
+        description_location: None,
+    }
+}
+
+/// What an item's symbol says about where it is.
+#[derive(Debug, PartialEq, Eq)]
+struct ParsedSymbol {
+    /// The crate, by its name in code (`-` replaced with `_`)
+    crate_name: String,
+    /// The symbol of the parent, for looking up its kind
+    parent_symbol: Option<String>,
+    /// Modules between the crate root and the item
+    modules: Vec<String>,
+    /// The type or trait the item is a member of
+    parent: Option<String>,
+    name: String,
+    suffix: Suffix,
+}
+
+/// How a SCIP descriptor ends, which tells what kind of name it is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Suffix {
+    /// `name/`
+    Namespace,
+    /// `name#`
+    Type,
+    /// `name.`
+    Term,
+    /// `name(disambiguator).`
+    Method,
+    /// `[name]`
+    TypeParameter,
+    /// `(name)`
+    Parameter,
+    /// `name:`
+    Meta,
+    /// `name!`
+    Macro,
+}
+
+/// Split a global symbol (`scheme manager package version descriptors`)
+/// into the item's crate, modules, parent and name; `None` for local
+/// symbols and those that aren't items.
+fn parse_symbol(symbol: &str) -> Option<ParsedSymbol> {
+    let mut parts = symbol.splitn(5, ' ');
+    let scheme = parts.next()?;
+    if scheme == "local" {
+        return None;
+    }
+    let _manager = parts.next()?;
+    let package = parts.next()?;
+    let _version = parts.next()?;
+    let descriptors = parts.next()?;
+    let prefix = &symbol[..symbol.len() - descriptors.len()];
+
+    let mut modules = Vec::new();
+    let mut parent = None;
+    let mut last = None;
+    let mut rest = descriptors;
+    while !rest.is_empty() {
+        let (name, suffix, remaining) = descriptor(rest)?;
+        rest = remaining;
+        match (suffix, last.take()) {
+            // rust-analyzer names impl blocks' members `impl#[Type][Trait]name().`,
+            // so the first type parameter after `impl#` is the parent
+            (Suffix::TypeParameter, Some((previous, Suffix::Type))) if previous == "impl" => {
+                parent = Some(name);
+                continue;
+            }
+            (_, Some((previous, Suffix::Namespace))) => modules.push(previous),
+            (_, Some((previous, Suffix::Type))) => parent = Some(previous),
+            // The trait an impl block implements
+            (_, Some((_, Suffix::TypeParameter))) if parent.is_some() => {}
+            // Members of functions, such as parameters
+            (_, Some(_)) => return None,
+            (_, None) => {}
+        }
+        last = Some((name, suffix));
+    }
+    let (name, suffix) = last?;
+    if matches!(
+        suffix,
+        Suffix::TypeParameter | Suffix::Parameter | Suffix::Meta
+    ) || name == "impl"
+    {
+        return None;
+    }
+    // Impl blocks are taken to be in the module of the type they're for
+    let parent_symbol = parent.as_ref().map(|parent| {
+        let modules: String = modules
+            .iter()
+            .map(|module| format!("{}/", module))
+            .collect();
+        format!("{}{}{}#", prefix, modules, parent)
+    });
+    Some(ParsedSymbol {
+        crate_name: package.replace('-', "_"),
+        parent_symbol,
+        modules,
+        parent,
+        name,
+        suffix,
+    })
+}
+
+/// The first descriptor of `descriptors`: its name, how it ends, and the rest.
+fn descriptor(descriptors: &str) -> Option<(String, Suffix, &str)> {
+    let (opening, closing) = match descriptors.as_bytes()[0] {
+        b'[' => (Some(Suffix::TypeParameter), b']'),
+        b'(' => (Some(Suffix::Parameter), b')'),
+        _ => (None, 0),
+    };
+    let start = usize::from(opening.is_some());
+    let (name, rest) = name(&descriptors[start..])?;
+    if let Some(suffix) = opening {
+        return Some((name, suffix, rest.strip_prefix(closing as char)?));
+    }
+    let suffix = match rest.as_bytes().first()? {
+        b'/' => Suffix::Namespace,
+        b'#' => Suffix::Type,
+        b'.' => Suffix::Term,
+        b':' => Suffix::Meta,
+        b'!' => Suffix::Macro,
+        b'(' => {
+            // The disambiguator between the parentheses tells overloads apart
+            let close = rest.find(')')?;
+            let rest = rest[close + 1..].strip_prefix('.')?;
+            return Some((name, Suffix::Method, rest));
+        }
+        _ => return None,
+    };
+    Some((name, suffix, &rest[1..]))
+}
+
+/// A descriptor's name: a run of identifier characters, or anything between
+/// backticks, with doubled backticks for one.
+fn name(descriptors: &str) -> Option<(String, &str)> {
+    if let Some(escaped) = descriptors.strip_prefix('`') {
+        let mut name = String::new();
+        let mut chars = escaped.char_indices();
+        while let Some((i, c)) = chars.next() {
+            if c != '`' {
+                name.push(c);
+            } else if escaped[i + 1..].starts_with('`') {
+                name.push('`');
+                chars.next();
+            } else {
+                return Some((name, &escaped[i + 1..]));
+            }
+        }
+        return None;
+    }
+    let end = descriptors
+        .find(|c: char| !(c.is_alphanumeric() || matches!(c, '_' | '+' | '-' | '$')))
+        .unwrap_or(descriptors.len());
+    Some((descriptors[..end].to_string(), &descriptors[end..]))
+}
+
+/// The item type for SCIP's `kind`, or from how the symbol ends when the
+/// kind is unset or has no rustdoc counterpart.
+fn item_type(kind: u64, symbol: &ParsedSymbol) -> ItemType {
+    match kind {
+        3 => ItemType::AssocType,
+        8 => ItemType::Constant,
+        11 => ItemType::Enum,
+        12 => ItemType::Variant,
+        15 => ItemType::StructField,
+        17 => ItemType::Function,
+        25 => ItemType::Macro,
+        26 | 80 => ItemType::Method,
+        29 | 30 => ItemType::Module,
+        49 => ItemType::Struct,
+        53 => ItemType::Trait,
+        55 => ItemType::Typedef,
+        59 => ItemType::Union,
+        66 | 70 => ItemType::TyMethod,
+        82 => ItemType::Static,
+        _ => match (symbol.suffix, &symbol.parent) {
+            (Suffix::Namespace, _) => ItemType::Module,
+            (Suffix::Type, _) => ItemType::Struct,
+            (Suffix::Macro, _) => ItemType::Macro,
+            (Suffix::Method, None) => ItemType::Function,
+            (Suffix::Method, Some(_)) => ItemType::Method,
+            (_, Some(_)) => ItemType::StructField,
+            _ => ItemType::Constant,
+        },
+    }
+}
+
+/// A `SymbolInformation` message.
+fn symbol_information(message: &[u8]) -> Result<SymbolInformation, ScipError> {
+    let mut information = SymbolInformation {
+        symbol: String::new(),
+        kind: 0,
+        documentation: Vec::new(),
+    };
+    let mut fields = Fields::new(message);
+    while let Some((number, value)) = fields.next_field()? {
+        match (number, value) {
+            (1, Value::Bytes(symbol)) => information.symbol = utf8(symbol)?,
+            (3, Value::Bytes(documentation)) => {
+                information.documentation.push(utf8(documentation)?)
+            }
+            (5, Value::Varint(kind)) => information.kind = kind,
+            _ => {}
+        }
+    }
+    Ok(information)
+}
+
+fn utf8(bytes: &[u8]) -> Result<String, ScipError> {
+    String::from_utf8(bytes.to_vec()).map_err(|_| ScipError::Utf8)
+}
+
+/// A protobuf field's value, as far as SCIP's messages need.
+enum Value<'a> {
+    Varint(u64),
+    Bytes(&'a [u8]),
+    Fixed,
+}
+
+/// The fields of a protobuf message, in order.
+struct Fields<'a> {
+    bytes: &'a [u8],
+    offset: usize,
+}
+
+impl<'a> Fields<'a> {
+    fn new(bytes: &'a [u8]) -> Self {
+        Self { bytes, offset: 0 }
+    }
+
+    /// The next field's number and value, or `None` at the end.
+    fn next_field(&mut self) -> Result<Option<(u64, Value<'a>)>, ScipError> {
+        if self.offset == self.bytes.len() {
+            return Ok(None);
+        }
+        let key = self.varint()?;
+        let value = match key & 7 {
+            0 => Value::Varint(self.varint()?),
+            1 => {
+                self.skip(8)?;
+                Value::Fixed
+            }
+            2 => {
+                let len = self.varint()? as usize;
+                Value::Bytes(self.skip(len)?)
+            }
+            5 => {
+                self.skip(4)?;
+                Value::Fixed
+            }
+            wire_type => return Err(ScipError::WireType(wire_type)),
+        };
+        Ok(Some((key >> 3, value)))
+    }
+
+    fn varint(&mut self) -> Result<u64, ScipError> {
+        let mut value = 0u64;
+        for shift in (0..64).step_by(7) {
+            let byte = *self.bytes.get(self.offset).ok_or(ScipError::Truncated)?;
+            self.offset += 1;
+            value |= u64::from(byte & 0x7f) << shift;
+            if byte & 0x80 == 0 {
+                return Ok(value);
+            }
+        }
+        Err(ScipError::Truncated)
+    }
+
+    fn skip(&mut self, len: usize) -> Result<&'a [u8], ScipError> {
+        let end = self
+            .offset
+            .checked_add(len)
+            .filter(|&end| end <= self.bytes.len())
+            .ok_or(ScipError::Truncated)?;
+        let skipped = &self.bytes[self.offset..end];
+        self.offset = end;
+        Ok(skipped)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn varint(mut value: u64, out: &mut Vec<u8>) {
+        while value >= 0x80 {
+            out.push(value as u8 | 0x80);
+            value >>= 7;
+        }
+        out.push(value as u8);
+    }
+
+    fn bytes_field(number: u64, bytes: &[u8], out: &mut Vec<u8>) {
+        varint(number << 3 | 2, out);
+        varint(bytes.len() as u64, out);
+        out.extend_from_slice(bytes);
+    }
+
+    fn symbol(symbol: &str, kind: u64, documentation: &[&str]) -> Vec<u8> {
+        let mut message = Vec::new();
+        bytes_field(1, symbol.as_bytes(), &mut message);
+        for documentation in documentation {
+            bytes_field(3, documentation.as_bytes(), &mut message);
+        }
+        if kind != 0 {
+            varint(5 << 3, &mut message);
+            varint(kind, &mut message);
+        }
+        message
+    }
+
+    #[test]
+    fn test_read_symbols() {
+        let mut document = Vec::new();
+        bytes_field(1, b"src/lib.rs", &mut document);
+        let prefix = "rust-analyzer cargo geo-kit 0.1.0 ";
+        for (descriptors, kind, documentation) in [
+            ("shapes/", 29, &["mod shapes"][..]),
+            ("shapes/Point#", 49, &["struct Point", "A point."]),
+            ("shapes/Point#x.", 15, &["x: f64"]),
+            ("shapes/impl#[Point]new().", 26, &["fn new() -> Point"]),
+            ("shapes/impl#[Point][Display]fmt().", 0, &[]),
+            ("shapes/Shape#", 53, &["trait Shape"]),
+            ("shapes/Shape#area().", 0, &[]),
+            ("shapes/`r#type`().", 0, &[]),
+            ("shapes/Point#new().(x)", 0, &[]),
+            ("point!", 0, &[]),
+        ] {
+            let message = symbol(&format!("{}{}", prefix, descriptors), kind, documentation);
+            bytes_field(3, &message, &mut document);
+        }
+        bytes_field(3, &symbol("local 4", 0, &[]), &mut document);
+        let mut index = Vec::new();
+        bytes_field(2, &document, &mut index);
+
+        let symbols = read_symbols(&index).unwrap();
+        assert_eq!(symbols.len(), 11);
+        assert_eq!(symbols[1].documentation, ["struct Point", "A point."]);
+        let items = decode_symbols(&symbols, "index.scip");
+        let summary: Vec<(&str, ItemType, &str, Option<&str>)> = items
+            .iter()
+            .map(|item| {
+                (
+                    item.name.as_str(),
+                    item.item_type,
+                    item.path.as_str(),
+                    item.parent_name(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            summary,
+            [
+                ("shapes", ItemType::Module, "geo_kit", None),
+                ("Point", ItemType::Struct, "geo_kit::shapes", None),
+                ("x", ItemType::StructField, "geo_kit::shapes", Some("Point")),
+                ("new", ItemType::Method, "geo_kit::shapes", Some("Point")),
+                ("fmt", ItemType::Method, "geo_kit::shapes", Some("Point")),
+                ("Shape", ItemType::Trait, "geo_kit::shapes", None),
+                ("area", ItemType::TyMethod, "geo_kit::shapes", Some("Shape")),
+                ("r#type", ItemType::Function, "geo_kit::shapes", None),
+                ("point", ItemType::Macro, "geo_kit", None),
+            ]
+        );
+        assert_eq!(items[1].id.to_string(), "geo_kit#1@index.scip");
+        assert!(!items[1].empty_description && items[2].empty_description);
+    }
+
+    #[test]
+    fn test_read_symbols_errors() {
+        let mut index = Vec::new();
+        bytes_field(2, b"\x1a\x05ab", &mut index);
+        assert_eq!(read_symbols(&index), Err(ScipError::Truncated));
+        assert_eq!(read_symbols(b"\x13"), Err(ScipError::WireType(3)));
+        assert_eq!(read_symbols(&[]), Ok(vec![]));
+    }
+}
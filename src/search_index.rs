@@ -1,45 +1,205 @@
 // Parser for rustdoc search-index.js format
 
+use memchr::{memchr2, memmem};
 use serde::{Deserialize, Serialize};
-use serde_repr::{Deserialize_repr, Serialize_repr};
+use serde_json::value::RawValue;
 use serde_with::{StringWithSeparator, formats::CommaSeparator, serde_as};
+use std::borrow::Cow;
 use std::collections::HashMap;
+use std::fmt;
+use std::str::FromStr;
+use std::time::Instant;
+use tracing::debug;
 
 /// Item type ID from rustdoc search index.
 ///
 /// Represents the different kinds of Rust items that can appear in documentation.
-/// The numeric values correspond to rustdoc's internal type encoding.
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize_repr, Serialize_repr)]
-#[repr(u32)]
+/// Each type has the numeric code rustdoc's internal type encoding gives it
+/// (see [`ItemType::code`]); codes rdoc doesn't know, such as ones a newer
+/// rustdoc adds, are kept as [`ItemType::Unknown`].
+///
+/// Types are displayed and parsed by rustdoc's names for them (see
+/// [`ItemType::name`]), e.g. `struct`, `fn` or `method`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum ItemType {
-    MutRef = 0,
-    PrimitiveOrBuiltin = 1,
-    Module = 2,
-    ExternCrate = 3,
-    Import = 4,
-    Struct = 5,
-    Enum = 6,
-    Function = 7,
-    Typedef = 8,
-    Static = 9,
-    Trait = 10,
-    Impl = 11,
-    TyMethod = 12,
-    Method = 13,
-    StructField = 14,
-    Variant = 15,
-    Macro = 16,
-    Primitive = 17,
-    AssocConst = 18,
-    AssocType = 19,
-    Constant = 20,
-    Union = 21,
-    ForeignType = 22,
-    Keyword = 23,
-    OpaqueTy = 24,
-    ProcAttribute = 25,
-    ProcDerive = 26,
-    TraitAlias = 27,
+    Keyword,
+    Primitive,
+    Module,
+    ExternCrate,
+    Import,
+    Struct,
+    Enum,
+    Function,
+    Typedef,
+    Static,
+    Trait,
+    Impl,
+    TyMethod,
+    Method,
+    StructField,
+    Variant,
+    Macro,
+    AssocType,
+    Constant,
+    AssocConst,
+    Union,
+    ForeignType,
+    OpaqueTy,
+    ProcAttribute,
+    ProcDerive,
+    TraitAlias,
+    /// A type code rdoc doesn't know
+    Unknown(u8),
+}
+
+/// The item types rdoc knows, in the order of their codes, as in rustdoc's
+/// `ItemType` enum (`src/librustdoc/formats/item_type.rs`) and search.js's
+/// `itemTypes`. Code 26 is search.js's own, for generic parameters, and
+/// later codes are newer rustdocs'.
+pub(crate) const ITEM_TYPES: [ItemType; 26] = [
+    ItemType::Keyword,
+    ItemType::Primitive,
+    ItemType::Module,
+    ItemType::ExternCrate,
+    ItemType::Import,
+    ItemType::Struct,
+    ItemType::Enum,
+    ItemType::Function,
+    ItemType::Typedef,
+    ItemType::Static,
+    ItemType::Trait,
+    ItemType::Impl,
+    ItemType::TyMethod,
+    ItemType::Method,
+    ItemType::StructField,
+    ItemType::Variant,
+    ItemType::Macro,
+    ItemType::AssocType,
+    ItemType::Constant,
+    ItemType::AssocConst,
+    ItemType::Union,
+    ItemType::ForeignType,
+    ItemType::OpaqueTy,
+    ItemType::ProcAttribute,
+    ItemType::ProcDerive,
+    ItemType::TraitAlias,
+];
+
+impl ItemType {
+    /// The type rustdoc encodes as `code`.
+    pub fn from_code(code: u8) -> Self {
+        ITEM_TYPES
+            .get(usize::from(code))
+            .copied()
+            .unwrap_or(ItemType::Unknown(code))
+    }
+
+    /// rustdoc's code for the type.
+    pub fn code(self) -> u8 {
+        match self {
+            ItemType::Unknown(code) => code,
+            known => ITEM_TYPES
+                .iter()
+                .position(|item_type| *item_type == known)
+                .expect("every known item type is in ITEM_TYPES") as u8,
+        }
+    }
+}
+
+impl ItemType {
+    /// rustdoc's name for the type, as in its search filters (`method:len`)
+    /// and page file names (`struct.Value.html`); `None` for
+    /// [`ItemType::Unknown`].
+    pub fn name(self) -> Option<&'static str> {
+        use ItemType::*;
+        Some(match self {
+            Module => "mod",
+            ExternCrate => "externcrate",
+            Import => "import",
+            Struct => "struct",
+            Union => "union",
+            Enum => "enum",
+            Function => "fn",
+            Typedef => "type",
+            Static => "static",
+            Trait => "trait",
+            Impl => "impl",
+            TyMethod => "tymethod",
+            Method => "method",
+            StructField => "structfield",
+            Variant => "variant",
+            Macro => "macro",
+            Primitive => "primitive",
+            AssocType => "associatedtype",
+            Constant => "constant",
+            AssocConst => "associatedconstant",
+            ForeignType => "foreigntype",
+            Keyword => "keyword",
+            OpaqueTy => "opaque",
+            ProcAttribute => "attr",
+            ProcDerive => "derive",
+            TraitAlias => "traitalias",
+            Unknown(_) => return None,
+        })
+    }
+}
+
+/// rustdoc's name for the type, or `unknown(CODE)` for a code rdoc doesn't know.
+impl fmt::Display for ItemType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.name() {
+            Some(name) => f.write_str(name),
+            None => write!(f, "unknown({})", self.code()),
+        }
+    }
+}
+
+/// A string that isn't the name of an item type.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnknownItemTypeName(pub String);
+
+impl fmt::Display for UnknownItemTypeName {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "unknown item type \"{}\" (expected struct, enum, trait, fn, method, macro, mod, ...)",
+            self.0
+        )
+    }
+}
+
+impl std::error::Error for UnknownItemTypeName {}
+
+/// Parses what [`ItemType`]'s `Display` writes.
+impl FromStr for ItemType {
+    type Err = UnknownItemTypeName;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if let Some(found) = ITEM_TYPES
+            .iter()
+            .find(|item_type| item_type.name() == Some(s))
+        {
+            return Ok(*found);
+        }
+        s.strip_prefix("unknown(")
+            .and_then(|rest| rest.strip_suffix(')'))
+            .and_then(|code| code.parse().ok())
+            .map(ItemType::from_code)
+            .filter(|item_type| matches!(item_type, ItemType::Unknown(_)))
+            .ok_or_else(|| UnknownItemTypeName(s.to_string()))
+    }
+}
+
+impl Serialize for ItemType {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_u8(self.code())
+    }
+}
+
+impl<'de> Deserialize<'de> for ItemType {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        u8::deserialize(deserializer).map(ItemType::from_code)
+    }
 }
 
 /// A crate entry from the search index
@@ -53,6 +213,29 @@ pub struct CrateEntry {
     pub data: CrateData,
 }
 
+/// A crate entry whose data has not been deserialized yet.
+///
+/// Borrows from the JSON string it was parsed from: the crate name is only copied
+/// if it contains escapes, and the compact data is kept as raw JSON until
+/// [`LazyCrateEntry::data`] is called. This lets callers decode one crate at a
+/// time instead of materializing every crate's owned strings up front.
+#[derive(Debug, Deserialize)]
+pub struct LazyCrateEntry<'a> {
+    /// Name of the crate
+    #[serde(rename = "0", borrow)]
+    pub name: Cow<'a, str>,
+    /// Raw, unparsed compact data for this crate
+    #[serde(rename = "1", borrow)]
+    pub raw_data: &'a RawValue,
+}
+
+impl LazyCrateEntry<'_> {
+    /// Deserialize this crate's compact data.
+    pub fn data(&self) -> serde_json::Result<CrateData> {
+        serde_json::from_str(self.raw_data.get())
+    }
+}
+
 /// Qualified path entry - maps an item index to its module path.
 #[derive(Debug, Deserialize, Serialize)]
 pub struct QualifiedPath {
@@ -222,46 +405,151 @@ pub struct CrateData {
     pub aliases: Option<HashMap<String, Vec<usize>>>,
 }
 
-/// Extract the JSON string from search-index.js
-/// The file format is: var searchIndex = new Map(JSON.parse('[...]'));
-pub fn extract_json_string(content: &str) -> String {
-    // Find the pattern JSON.parse(' and ')
-    let start_pattern = "JSON.parse('";
-    let end_pattern = "')";
+impl CrateData {
+    /// Number of items: one per character of `types`, which isn't the byte
+    /// length if a malformed index has non-ASCII type codes.
+    pub fn item_count(&self) -> usize {
+        self.types.chars().count()
+    }
+}
 
-    let start = content
-        .find(start_pattern)
-        .expect("Could not find JSON.parse('")
-        + start_pattern.len();
+/// Error returned when search-index.js doesn't have the expected wrapper.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ExtractError {
+    /// There is no `JSON.parse('` to start the index
+    MissingStart,
+    /// The index is never closed with `')`
+    MissingEnd,
+}
 
-    let end = content[start..]
-        .find(end_pattern)
-        .expect("Could not find closing ')")
-        + start;
+impl fmt::Display for ExtractError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ExtractError::MissingStart => write!(f, "could not find JSON.parse(' in search index"),
+            ExtractError::MissingEnd => {
+                write!(f, "could not find the closing ') of the search index")
+            }
+        }
+    }
+}
 
-    let json_str = &content[start..end];
+impl std::error::Error for ExtractError {}
 
-    // Unescape \' to '
-    json_str.replace(r"\'", "'")
+/// Extract the JSON string from search-index.js
+/// The file format is: var searchIndex = new Map(JSON.parse('[...]'));
+///
+/// The JSON is written as a single-quoted JavaScript string, with `\`, `'`
+/// and `"` escaped by a backslash. It's unescaped in one pass over the bytes;
+/// most indexes have no escapes at all, and then the JSON is borrowed from
+/// `content` rather than copied. Otherwise the runs between escapes are
+/// copied into a buffer sized for the whole index, so even an index of
+/// hundreds of megabytes on one line is only copied once. The string ends at
+/// the first unescaped `')`.
+pub fn extract_json_string(content: &str) -> Result<Cow<'_, str>, ExtractError> {
+    const START_PATTERN: &[u8] = b"JSON.parse('";
+
+    let bytes = content.as_bytes();
+    let start =
+        memmem::find(bytes, START_PATTERN).ok_or(ExtractError::MissingStart)? + START_PATTERN.len();
+
+    // Only allocated at the first escape
+    let mut unescaped: Option<String> = None;
+    // Start of the run not yet copied
+    let mut copied = start;
+    let mut search = start;
+    while let Some(found) = memchr2(b'\\', b'\'', &bytes[search..]) {
+        let at = search + found;
+        if bytes[at] == b'\'' {
+            if bytes.get(at + 1) == Some(&b')') {
+                return Ok(match unescaped {
+                    Some(mut json) => {
+                        json.push_str(&content[copied..at]);
+                        Cow::Owned(json)
+                    }
+                    None => Cow::Borrowed(&content[start..at]),
+                });
+            }
+            // A stray quote is kept, as JavaScript would reject it anyway
+            search = at + 1;
+            continue;
+        }
+        match bytes.get(at + 1) {
+            Some(&escaped @ (b'\\' | b'\'' | b'"')) => {
+                let json =
+                    unescaped.get_or_insert_with(|| String::with_capacity(bytes.len() - start));
+                json.push_str(&content[copied..at]);
+                json.push(char::from(escaped));
+                copied = at + 2;
+                search = at + 2;
+            }
+            // Other escapes (which rustdoc doesn't write) are left as they are
+            _ => search = at + 1,
+        }
+    }
+    Err(ExtractError::MissingEnd)
 }
 
 /// Parse the JSON string into a vector of crate entries
 /// The format is an array of [crate_name, crate_data] pairs
-pub fn parse_search_index(json_string: &str) -> Vec<CrateEntry> {
+pub fn parse_search_index(json_string: &str) -> serde_json::Result<Vec<CrateEntry>> {
+    let start = Instant::now();
+
     // Parse directly as a JSON array of CrateEntry structs
-    serde_json::from_str(json_string).expect("Failed to parse JSON")
+    let entries: Vec<CrateEntry> = serde_json::from_str(json_string)?;
+
+    debug!(
+        "parsed {} crates from {} bytes of JSON in {:?}",
+        entries.len(),
+        json_string.len(),
+        start.elapsed()
+    );
+    Ok(entries)
+}
+
+/// Parse the JSON string into lazily-decoded crate entries.
+///
+/// Only the outer array is parsed; each crate's data stays as a borrowed slice of
+/// `json_string` until it is requested.
+pub fn parse_search_index_lazy(json_string: &str) -> serde_json::Result<Vec<LazyCrateEntry<'_>>> {
+    let start = Instant::now();
+
+    let entries: Vec<LazyCrateEntry> = serde_json::from_str(json_string)?;
+
+    debug!(
+        "split {} bytes of JSON into {} lazy crate entries in {:?}",
+        json_string.len(),
+        entries.len(),
+        start.elapsed()
+    );
+    Ok(entries)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_item_type_names() {
+        assert_eq!(ItemType::Function.to_string(), "fn");
+        assert_eq!(ItemType::AssocType.to_string(), "associatedtype");
+        assert_eq!(ItemType::Unknown(40).to_string(), "unknown(40)");
+        for item_type in ITEM_TYPES.into_iter().chain([ItemType::Unknown(40)]) {
+            assert_eq!(item_type.to_string().parse(), Ok(item_type));
+        }
+        assert_eq!(
+            "Struct".parse::<ItemType>(),
+            Err(UnknownItemTypeName("Struct".to_string()))
+        );
+        // A known code isn't unknown
+        assert!("unknown(5)".parse::<ItemType>().is_err());
+    }
+
     #[test]
     fn test_extract_json_string() {
         let content = std::fs::read_to_string("tests/fixtures/search-index.js")
             .expect("Failed to read fixture");
 
-        let json_string = extract_json_string(&content);
+        let json_string = extract_json_string(&content).unwrap();
 
         // Should extract a non-empty string
         assert!(
@@ -282,7 +570,8 @@ mod tests {
         let content =
             r#"var searchIndex = new Map(JSON.parse('[["test",{"desc":"It\'s a test"}]]'));"#;
 
-        let json_string = extract_json_string(&content);
+        let json_string = extract_json_string(content).unwrap();
+        assert!(matches!(json_string, Cow::Owned(_)));
 
         // Should not contain \' - should be unescaped to just '
         assert!(
@@ -295,13 +584,46 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_extract_json_string_unescapes_backslashes() {
+        // rustdoc doubles backslashes, so the JSON's own escapes survive the
+        // JavaScript string: `"a\"b"` in JSON is written `"a\\\"b"`
+        let content = r#"JSON.parse('[["q",{"n":["a\\\"b","c\\\\d"],"x":"\')"}]]')"#;
+        let json_string = extract_json_string(content).unwrap();
+        assert_eq!(json_string, r#"[["q",{"n":["a\"b","c\\d"],"x":"')"}]]"#);
+        let entries: serde_json::Value = serde_json::from_str(&json_string).unwrap();
+        assert_eq!(entries[0][1]["n"][0], "a\"b");
+        assert_eq!(entries[0][1]["n"][1], "c\\d");
+    }
+
+    #[test]
+    fn test_extract_json_string_borrows_without_escapes() {
+        let content = r#"var searchIndex = new Map(JSON.parse('[["test",{"t":"F"}]]'));"#;
+        let json_string = extract_json_string(content).unwrap();
+        assert!(matches!(json_string, Cow::Borrowed(_)));
+        assert_eq!(json_string, r#"[["test",{"t":"F"}]]"#);
+    }
+
+    #[test]
+    fn test_extract_json_string_errors() {
+        assert_eq!(
+            extract_json_string("var searchIndex = new Map();"),
+            Err(ExtractError::MissingStart)
+        );
+        assert_eq!(
+            extract_json_string("var searchIndex = new Map(JSON.parse('[[\"a\",{}]]"),
+            Err(ExtractError::MissingEnd)
+        );
+        assert!(parse_search_index("[[\"a\"").is_err());
+    }
+
     #[test]
     fn test_parse_search_index() {
         let content = std::fs::read_to_string("tests/fixtures/search-index.js")
             .expect("Failed to read fixture");
 
-        let json_string = extract_json_string(&content);
-        let crates = parse_search_index(&json_string);
+        let json_string = extract_json_string(&content).unwrap();
+        let crates = parse_search_index(&json_string).unwrap();
 
         // Should have parsed multiple crates
         assert!(!crates.is_empty(), "Should have parsed at least one crate");
@@ -328,13 +650,44 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_parse_search_index_lazy_matches_eager() {
+        let content = std::fs::read_to_string("tests/fixtures/search-index.js")
+            .expect("Failed to read fixture");
+
+        let json_string = extract_json_string(&content).unwrap();
+        let eager = parse_search_index(&json_string).unwrap();
+        let lazy = parse_search_index_lazy(&json_string).expect("Failed to parse lazily");
+
+        assert_eq!(eager.len(), lazy.len());
+        for (eager_entry, lazy_entry) in eager.iter().zip(&lazy) {
+            assert_eq!(eager_entry.name, lazy_entry.name);
+            // Crate names never need unescaping, so they should be borrowed
+            assert!(matches!(lazy_entry.name, Cow::Borrowed(_)));
+
+            let data = lazy_entry.data().expect("Failed to decode crate data");
+            assert_eq!(eager_entry.data.types, data.types);
+            assert_eq!(eager_entry.data.names, data.names);
+        }
+    }
+
+    #[test]
+    fn test_parse_search_index_lazy_reports_errors() {
+        assert!(parse_search_index_lazy("[[\"broken\"").is_err());
+
+        // The outer array parses, but the crate data itself is malformed
+        let lazy =
+            parse_search_index_lazy(r#"[["test", {"t": 5}]]"#).expect("outer array is valid");
+        assert!(lazy[0].data().is_err());
+    }
+
     #[test]
     fn explore_aliases_field() {
         let content = std::fs::read_to_string("tests/fixtures/search-index.js")
             .expect("Failed to read fixture");
 
-        let json_string = extract_json_string(&content);
-        let crates = parse_search_index(&json_string);
+        let json_string = extract_json_string(&content).unwrap();
+        let crates = parse_search_index(&json_string).unwrap();
 
         // Collect all aliases from all crates that have them
         let all_aliases: Vec<_> = crates
@@ -388,4 +741,61 @@ mod tests {
         let result3: Result<Vec<CrateEntry>, _> = serde_json::from_str(valid_json);
         assert!(result3.is_ok(), "Should succeed with valid data");
     }
+
+    #[test]
+    fn test_item_type_names_by_code() {
+        // rustdoc's `ItemType` and search.js's `itemTypes`
+        let names = [
+            "keyword",
+            "primitive",
+            "mod",
+            "externcrate",
+            "import",
+            "struct",
+            "enum",
+            "fn",
+            "type",
+            "static",
+            "trait",
+            "impl",
+            "tymethod",
+            "method",
+            "structfield",
+            "variant",
+            "macro",
+            "associatedtype",
+            "constant",
+            "associatedconstant",
+            "union",
+            "foreigntype",
+            "opaque",
+            "attr",
+            "derive",
+            "traitalias",
+        ];
+        for (code, name) in names.into_iter().enumerate() {
+            let item_type = ItemType::from_code(code as u8);
+            assert_eq!(item_type.name(), Some(name), "code {}", code);
+            assert_eq!(name.parse::<ItemType>(), Ok(item_type));
+        }
+        assert_eq!(ItemType::from_code(26), ItemType::Unknown(26));
+    }
+
+    #[test]
+    fn test_item_type_codes() {
+        for code in 0..=u8::MAX {
+            assert_eq!(ItemType::from_code(code).code(), code);
+        }
+        assert_eq!(ItemType::from_code(10), ItemType::Trait);
+        assert_eq!(ItemType::from_code(40), ItemType::Unknown(40));
+
+        // Parents of a type rdoc doesn't know don't fail the crate
+        let json = r#"[["test", {"t":"F", "n":["foo"], "p":[[40, "Thing"]]}]]"#;
+        let entries: Vec<CrateEntry> = serde_json::from_str(json).unwrap();
+        assert_eq!(entries[0].data.parent_items[0].ty, ItemType::Unknown(40));
+        assert_eq!(
+            serde_json::to_string(&entries[0].data.parent_items[0].ty).unwrap(),
+            "40"
+        );
+    }
 }
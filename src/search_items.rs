@@ -1,7 +1,19 @@
 // Decoded search index items
 
+use crate::bitmap::RoaringBitmap;
+use crate::descriptions::{DescriptionLocation, description_locations};
+use crate::diagnostics::Diagnostic;
+use crate::human::count;
+use crate::item_id::ItemId;
+use crate::normalize::{crate_name, crate_path, normalize_name};
+use crate::rustdoc_version::IndexFeatures;
 use crate::search_index::{CrateData, ItemType};
 use crate::vlq::VlqHexDecoder;
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::sync::Arc;
+use std::time::Instant;
+use tracing::{debug, warn};
 
 /// A fully decoded search index item with all metadata resolved.
 #[derive(Debug, Clone, PartialEq)]
@@ -24,8 +36,8 @@ pub struct SearchItem {
     /// Exact path for re-exports (may differ from path)
     pub exact_path: String,
 
-    /// Unique ID for this item
-    pub id: usize,
+    /// Unique ID for this item across every loaded index
+    pub id: ItemId,
 
     /// Type parameters for functions/methods
     pub param_types: Vec<String>,
@@ -36,158 +48,628 @@ pub struct SearchItem {
     /// Bit index for deprecated/description bitmaps
     pub bit_index: usize,
 
-    /// Index into the parent_items array (0-based), if this item has a parent
+    /// Index into `parents` (0-based), if this item has a parent
     pub parent_index: Option<usize>,
+
+    /// The crate's parent table, shared by all of its items
+    pub parents: Parents,
+
+    /// Whether the item is marked deprecated (`c` bitmap)
+    pub deprecated: bool,
+
+    /// Whether the item has no doc comment (`e` bitmap)
+    pub empty_description: bool,
+
+    /// Where the item's description is stored, if it has one
+    pub description_location: Option<DescriptionLocation>,
+}
+
+/// An item that others belong to, like the type a method is on: an entry of
+/// a crate's parent table (the `p` array).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Parent {
+    pub name: String,
+    /// Which decides the page the parent's members are documented on
+    pub item_type: ItemType,
+}
+
+/// A crate's parent table, which [`SearchItem::parent_index`] points into.
+/// Cloning it shares the table.
+#[derive(Clone, Default)]
+pub struct Parents(Arc<[Parent]>);
+
+impl Parents {
+    pub fn new(parents: Vec<Parent>) -> Self {
+        Self(parents.into())
+    }
+
+    /// The parent at `index` in the table.
+    pub fn get(&self, index: usize) -> Option<&Parent> {
+        self.0.get(index)
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
 }
 
-/// Decode a crate's compact data into a vector of search items.
+impl PartialEq for Parents {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.0, &other.0) || self.0 == other.0
+    }
+}
+
+/// Only the size: the table is the same for every item of a crate, and
+/// [`SearchItem::parent`] is what's of interest in an item.
+impl fmt::Debug for Parents {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Parents({})", self.len())
+    }
+}
+
+/// Decode a crate's compact data from the local project's docs into search items.
 pub fn decode_crate(crate_name: &str, crate_data: &CrateData) -> Vec<SearchItem> {
-    let mut items = Vec::new();
-    let mut last_name = String::new();
-    let mut last_path = String::new();
-
-    // Build lookup maps for sparse arrays
-    let paths_map: std::collections::HashMap<usize, &str> = crate_data
-        .paths
-        .iter()
-        .map(|qp| (qp.index, qp.path.as_str()))
-        .collect();
-
-    // Create VLQ decoder for parent indices
-    let mut parent_decoder = VlqHexDecoder::new(&crate_data.i);
-
-    let reexports_map: std::collections::HashMap<usize, usize> = crate_data
-        .reexports
-        .iter()
-        .map(|r| (r.item_index, r.path_index))
-        .collect();
-
-    let param_types_map: std::collections::HashMap<usize, &[String]> = crate_data
-        .param_types
-        .iter()
-        .map(|pt| (pt.item_index, pt.types.as_slice()))
-        .collect();
-
-    let impl_disamb_map: std::collections::HashMap<usize, &str> = crate_data
-        .impl_disambiguators
-        .iter()
-        .map(|id| (id.item_index, id.disambiguator.as_str()))
-        .collect();
-
-    // Iterate through all items (parallel arrays types and names)
-    for i in 0..crate_data.types.len() {
-        let bit_index = i + 1;
+    decode_crate_from(&Arc::from(ItemId::LOCAL_SOURCE), crate_name, crate_data)
+}
 
+/// Decode a crate's compact data into search items whose IDs record `source`.
+///
+/// Problems decoding works around are logged; see
+/// [`decode_crate_with_diagnostics`] to get them instead.
+pub fn decode_crate_from(
+    source: &Arc<str>,
+    crate_name: &str,
+    crate_data: &CrateData,
+) -> Vec<SearchItem> {
+    let (items, diagnostics) =
+        decode_crate_with_diagnostics(source, crate_name, crate_data, IndexFeatures::default());
+    warn_unknown_item_types(crate_name, &diagnostics);
+    for diagnostic in &diagnostics {
+        if !matches!(diagnostic, Diagnostic::UnknownItemType { .. }) {
+            warn!("crate {}: {}", crate_name, diagnostic);
+        }
+    }
+    items
+}
+
+/// Warn, once for the crate, if any of `crate_name`'s items have type codes
+/// rdoc doesn't know, likely because a newer rustdoc wrote the index.
+pub fn warn_unknown_item_types(crate_name: &str, diagnostics: &[Diagnostic]) {
+    let mut codes = Vec::new();
+    for diagnostic in diagnostics {
+        if let Diagnostic::UnknownItemType { code, .. } = diagnostic {
+            codes.push(*code);
+        }
+    }
+    if codes.is_empty() {
+        return;
+    }
+    let items = codes.len();
+    codes.sort_unstable();
+    codes.dedup();
+    let codes: Vec<String> = codes.iter().map(u8::to_string).collect();
+    warn!(
+        "crate {}: {} of unknown type (code {}), listed as unknown; rdoc may be older than the rustdoc that wrote the index",
+        crate_name,
+        count(items, "item", "items"),
+        codes.join(", ")
+    );
+}
+
+/// Like [`decode_crate_from`], reading the fields `features` says the index
+/// has, and also returning the problems with the crate's data that decoding
+/// worked around.
+pub fn decode_crate_with_diagnostics(
+    source: &Arc<str>,
+    crate_name: &str,
+    crate_data: &CrateData,
+    features: IndexFeatures,
+) -> (Vec<SearchItem>, Vec<Diagnostic>) {
+    let mut iter = CrateItems::new(source, crate_name, crate_data, features);
+    let items: Vec<SearchItem> = iter.by_ref().collect();
+    (items, iter.into_diagnostics())
+}
+
+/// Decode a crate's compact data from the local project's docs one item at a
+/// time, so callers can stop early or filter without holding every item.
+///
+/// Unlike [`decode_crate`], problems decoding works around aren't logged; read
+/// them from [`CrateItems::diagnostics`].
+pub fn decode_crate_iter<'a>(crate_name: &str, crate_data: &'a CrateData) -> CrateItems<'a> {
+    CrateItems::new(
+        &Arc::from(ItemId::LOCAL_SOURCE),
+        crate_name,
+        crate_data,
+        IndexFeatures::default(),
+    )
+}
+
+/// A crate's items, decoded lazily in index order by [`decode_crate_iter`].
+pub struct CrateItems<'a> {
+    crate_data: &'a CrateData,
+    source: Arc<str>,
+    crate_id: Arc<str>,
+    /// The index of the next item, and its type code
+    types: std::iter::Enumerate<std::str::Chars<'a>>,
+    // Lookup maps for the sparse arrays
+    paths_map: HashMap<usize, &'a str>,
+    reexports_map: HashMap<usize, usize>,
+    param_types_map: HashMap<usize, &'a [String]>,
+    impl_disamb_map: HashMap<usize, &'a str>,
+    parent_decoder: VlqHexDecoder<'a>,
+    parent_table: Parents,
+    deprecated: RoaringBitmap,
+    empty_descriptions: RoaringBitmap,
+    locations: std::vec::IntoIter<Option<DescriptionLocation>>,
+    last_name: String,
+    last_path: String,
+    item_count: usize,
+    decoded: usize,
+    parents: usize,
+    finished: bool,
+    diagnostics: Vec<Diagnostic>,
+    start: Instant,
+}
+
+impl<'a> CrateItems<'a> {
+    /// Decode `crate_data`'s items with IDs recording `source`, reading the
+    /// fields `features` says the index has.
+    pub fn new(
+        source: &Arc<str>,
+        crate_name: &str,
+        crate_data: &'a CrateData,
+        features: IndexFeatures,
+    ) -> Self {
+        let mut diagnostics = Vec::new();
+        // Indexes from before description shards have neither the bitmaps nor
+        // the shard lengths
+        let (deprecated, empty_descriptions, locations) = if features.description_shards {
+            let deprecated = decode_bitmap("c", &crate_data.c, &mut diagnostics);
+            let empty_descriptions = decode_bitmap("e", &crate_data.e, &mut diagnostics);
+            let locations =
+                description_locations(&crate_data.desc, crate_data.item_count(), |bit_index| {
+                    empty_descriptions.contains(bit_index)
+                });
+            (deprecated, empty_descriptions, locations)
+        } else {
+            Default::default()
+        };
+
+        Self {
+            crate_data,
+            source: source.clone(),
+            crate_id: Arc::from(crate_name),
+            types: crate_data.types.chars().enumerate(),
+            paths_map: crate_data
+                .paths
+                .iter()
+                .map(|qp| (qp.index, qp.path.as_str()))
+                .collect(),
+            reexports_map: crate_data
+                .reexports
+                .iter()
+                .map(|r| (r.item_index, r.path_index))
+                .collect(),
+            param_types_map: crate_data
+                .param_types
+                .iter()
+                .map(|pt| (pt.item_index, pt.types.as_slice()))
+                .collect(),
+            impl_disamb_map: crate_data
+                .impl_disambiguators
+                .iter()
+                .map(|id| (id.item_index, id.disambiguator.as_str()))
+                .collect(),
+            // Parent indices are VLQ-encoded, one per item
+            parent_decoder: VlqHexDecoder::new(&crate_data.i),
+            parent_table: Parents::new(
+                crate_data
+                    .parent_items
+                    .iter()
+                    .map(|parent| Parent {
+                        name: parent.name.clone(),
+                        item_type: parent.ty,
+                    })
+                    .collect(),
+            ),
+            deprecated,
+            empty_descriptions,
+            locations: locations.into_iter(),
+            last_name: String::new(),
+            last_path: String::new(),
+            item_count: crate_data.item_count(),
+            decoded: 0,
+            parents: 0,
+            finished: false,
+            diagnostics,
+            start: Instant::now(),
+        }
+    }
+
+    /// The problems with the crate's data worked around so far. Those about
+    /// the crate as a whole are only known once every item has been decoded.
+    pub fn diagnostics(&self) -> &[Diagnostic] {
+        &self.diagnostics
+    }
+
+    /// The problems worked around, for when decoding is done.
+    pub fn into_diagnostics(self) -> Vec<Diagnostic> {
+        self.diagnostics
+    }
+
+    /// The type of item `i`, from its `type_char` in the types string.
+    fn item_type(&mut self, i: usize, type_char: char) -> ItemType {
         // Decode type from types string: char - 'A' (65)
-        let type_char = crate_data.types.as_bytes()[i];
-        let type_id = type_char - b'A';
-        let item_type = decode_item_type(type_id);
+        // Codes outside 'A'..='Z' only appear in malformed indexes; treat them as unknown
+        let type_id =
+            u8::try_from(u32::from(type_char).wrapping_sub(u32::from('A'))).unwrap_or(u8::MAX);
+        let item_type = ItemType::from_code(type_id);
+        if let ItemType::Unknown(code) = item_type {
+            self.diagnostics
+                .push(Diagnostic::UnknownItemType { item: i, code });
+        }
+        item_type
+    }
+
+    /// A name or path left out reuses the one before it. Rustdoc's search
+    /// starts from empty ones, which only a malformed index relies on.
+    fn check_first_item(&mut self) {
+        if self.crate_data.names.first().is_none_or(String::is_empty) {
+            self.diagnostics.push(Diagnostic::EmptyFirstName);
+        }
+        if !self.paths_map.contains_key(&0) {
+            self.diagnostics.push(Diagnostic::MissingFirstPath);
+        }
+    }
+
+    /// Where item `i`, documented in `path`, is defined: the path it's
+    /// re-exported from, if it is.
+    fn exact_path(&mut self, i: usize, path: &str) -> String {
+        let Some(&path_index) = self.reexports_map.get(&i) else {
+            return path.to_string();
+        };
+        match self.paths_map.get(&path_index) {
+            Some(exact_path) => exact_path.to_string(),
+            None => {
+                self.diagnostics.push(Diagnostic::MissingReexportPath {
+                    item: i,
+                    path_index,
+                });
+                path.to_string()
+            }
+        }
+    }
+
+    /// The position of item `i`'s parent in the parent table, decoded from
+    /// the next parent index (1-based, 0 means no parent).
+    fn parent_index(&mut self, i: usize) -> Option<usize> {
+        let parent_value = self.parent_decoder.next();
+        self.parents += usize::from(parent_value.is_some());
+        let index = parent_value.filter(|&parent_idx| parent_idx > 0)? as usize - 1;
+        if self.parent_table.get(index).is_none() {
+            self.diagnostics.push(Diagnostic::MissingParent {
+                item: i,
+                parent_index: index,
+            });
+            return None;
+        }
+        Some(index)
+    }
+
+    /// Check what can only be checked once every item has been decoded.
+    fn finish(&mut self) {
+        self.finished = true;
+        // Any values left over are parents of items that don't exist
+        self.parents += self.parent_decoder.by_ref().count();
+        if self.parents != self.decoded {
+            self.diagnostics.push(Diagnostic::ParentCountMismatch {
+                items: self.decoded,
+                parents: self.parents,
+            });
+        }
+        debug!(
+            "crate {} contributed {} items (decoded in {:?})",
+            self.crate_id,
+            self.decoded,
+            self.start.elapsed()
+        );
+    }
+}
+
+impl Iterator for CrateItems<'_> {
+    type Item = SearchItem;
+
+    fn next(&mut self) -> Option<SearchItem> {
+        let Some((i, type_char)) = self.types.next() else {
+            if !self.finished {
+                self.finish();
+            }
+            return None;
+        };
+        let bit_index = i + 1;
+        let item_type = self.item_type(i, type_char);
+        if i == 0 {
+            self.check_first_item();
+        }
 
         // Get name with compression: empty string means "reuse last name"
-        let name = if crate_data.names[i].is_empty() {
-            last_name.clone()
-        } else {
-            crate_data.names[i].clone()
+        let name = match self.crate_data.names.get(i) {
+            Some(name) if !name.is_empty() => name.clone(),
+            _ => self.last_name.clone(),
         };
 
-        // Create normalized name: lowercase and remove underscores
-        let normalized_name = name.to_lowercase().replace('_', "");
+        // Create normalized name: case folded without underscores
+        let normalized_name = normalize_name(&name);
 
         // Get path with compression: if not in paths_map, reuse last path
-        let path = paths_map
+        let path = self
+            .paths_map
             .get(&i)
             .map(|s| s.to_string())
-            .unwrap_or_else(|| last_path.clone());
-
-        // Get exact_path: check reexports, otherwise use path
-        let exact_path = if let Some(&path_index) = reexports_map.get(&i) {
-            paths_map
-                .get(&path_index)
-                .map(|s| s.to_string())
-                .unwrap_or_else(|| path.clone())
-        } else {
-            path.clone()
-        };
+            .unwrap_or_else(|| self.last_path.clone());
+        let exact_path = self.exact_path(i, &path);
 
         // Get param_types from sparse array
-        let param_types = param_types_map
+        let param_types = self
+            .param_types_map
             .get(&i)
             .map(|types| types.to_vec())
             .unwrap_or_default();
 
         // Get impl_disambiguator from sparse array
-        let impl_disambiguator = impl_disamb_map.get(&i).map(|s| s.to_string());
-
-        // Decode parent index (1-based, 0 means no parent)
-        let parent_index = parent_decoder.next().and_then(|parent_idx| {
-            if parent_idx > 0 {
-                Some((parent_idx - 1) as usize)
-            } else {
-                None
-            }
-        });
+        let impl_disambiguator = self.impl_disamb_map.get(&i).map(|s| s.to_string());
 
-        items.push(SearchItem {
-            crate_name: crate_name.to_string(),
+        let item = SearchItem {
+            crate_name: self.crate_id.to_string(),
             item_type,
             name: name.clone(),
             normalized_name,
             path: path.clone(),
             exact_path,
-            id: i,
+            id: ItemId {
+                source: self.source.clone(),
+                crate_name: self.crate_id.clone(),
+                index: i,
+            },
             param_types,
             impl_disambiguator,
             bit_index,
-            parent_index,
-        });
+            parent_index: self.parent_index(i),
+            parents: self.parent_table.clone(),
+            deprecated: self.deprecated.contains(bit_index as u32),
+            empty_description: self.empty_descriptions.contains(bit_index as u32),
+            description_location: self.locations.next().flatten(),
+        };
 
         // Update "last" values for next iteration
-        last_name = name;
-        last_path = path;
+        self.last_name = name;
+        self.last_path = path;
+        self.decoded += 1;
+        Some(item)
     }
 
-    items
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let left = self.item_count - self.decoded;
+        (left, Some(left))
+    }
+}
+
+impl ExactSizeIterator for CrateItems<'_> {}
+
+impl SearchItem {
+    /// The item's parent (e.g. the type a method belongs to), if it has one.
+    pub fn parent(&self) -> Option<&Parent> {
+        self.parent_index.and_then(|index| self.parents.get(index))
+    }
+
+    /// Name of the item's parent.
+    pub fn parent_name(&self) -> Option<&str> {
+        self.parent().map(|parent| parent.name.as_str())
+    }
+
+    /// Item type of the item's parent, which decides the page the item is
+    /// documented on.
+    pub fn parent_type(&self) -> Option<ItemType> {
+        self.parent().map(|parent| parent.item_type)
+    }
+
+    /// Whether the item is inside `scope`, a module or type path such as
+    /// `serde::de` or `serde_json::Value`.
+    ///
+    /// Both the path the item is documented at and the path it's defined at
+    /// count, so a re-export is inside the module that re-exports it. Paths
+    /// match whole segments: `serde::de` doesn't contain `serde::derive`.
+    pub fn is_within(&self, scope: &str) -> bool {
+        // It starts with a crate, which may be written with `-`
+        let scope = scope.trim_end_matches("::");
+        let scope = if scope.contains("::") {
+            crate_path(scope)
+        } else {
+            crate_name(scope)
+        };
+        let scope = scope.as_ref();
+        [&self.path, &self.exact_path].into_iter().any(|module| {
+            let container = match self.parent_name() {
+                Some(parent) if !module.is_empty() => format!("{}::{}", module, parent),
+                Some(parent) => parent.to_string(),
+                None => module.clone(),
+            };
+            container == scope
+                || container
+                    .strip_prefix(scope)
+                    .is_some_and(|rest| rest.starts_with("::"))
+        })
+    }
+}
+
+/// Collapse items that are the same underlying item re-exported elsewhere.
+///
+/// Items are the same when they share an exact path, parent, name, type and
+/// impl disambiguator. The occurrence in the defining crate (the one `exact_path`
+/// starts with) is kept if present, otherwise the first; order is otherwise preserved.
+pub fn dedup_reexports<'a>(items: impl IntoIterator<Item = &'a SearchItem>) -> Vec<&'a SearchItem> {
+    type Key<'a> = (&'a str, Option<&'a str>, &'a str, ItemType, Option<&'a str>);
+
+    let mut positions: HashMap<Key, usize> = HashMap::new();
+    let mut unique: Vec<&SearchItem> = Vec::new();
+    for item in items {
+        let key = (
+            item.exact_path.as_str(),
+            item.parent_name(),
+            item.name.as_str(),
+            item.item_type,
+            item.impl_disambiguator.as_deref(),
+        );
+        match positions.get(&key) {
+            Some(&position) => {
+                if !is_definition(unique[position]) && is_definition(item) {
+                    unique[position] = item;
+                }
+            }
+            None => {
+                positions.insert(key, unique.len());
+                unique.push(item);
+            }
+        }
+    }
+    unique
+}
+
+/// Collapses re-exports one item at a time, for results that are written as
+/// they're found.
+///
+/// Items are the same as for [`dedup_reexports`], but since later items aren't
+/// known yet the first occurrence is kept rather than the definition.
+#[derive(Debug, Default)]
+pub struct ReexportFilter {
+    seen: HashSet<ReexportKey>,
+}
+
+/// Exact path, parent, name, type and impl disambiguator.
+type ReexportKey = (String, Option<String>, String, ItemType, Option<String>);
+
+impl ReexportFilter {
+    /// Whether `item` is the first occurrence of its underlying item.
+    pub fn is_first(&mut self, item: &SearchItem) -> bool {
+        self.seen.insert((
+            item.exact_path.clone(),
+            item.parent_name().map(str::to_string),
+            item.name.clone(),
+            item.item_type,
+            item.impl_disambiguator.clone(),
+        ))
+    }
+}
+
+/// Builds [`SearchItem`]s for tests, which then only spell out the fields
+/// they're about.
+#[cfg(test)]
+#[derive(Debug, Clone)]
+pub(crate) struct ItemBuilder(SearchItem);
+
+#[cfg(test)]
+impl SearchItem {
+    /// A documented item named `name` at the root of `crate_name` in the
+    /// local docs, without a parent.
+    pub(crate) fn builder(crate_name: &str, item_type: ItemType, name: &str) -> ItemBuilder {
+        ItemBuilder(SearchItem {
+            crate_name: crate_name.to_string(),
+            item_type,
+            name: name.to_string(),
+            normalized_name: normalize_name(name),
+            path: crate_name.to_string(),
+            exact_path: crate_name.to_string(),
+            id: ItemId::new(ItemId::LOCAL_SOURCE, crate_name, 0),
+            param_types: vec![],
+            impl_disambiguator: None,
+            bit_index: 1,
+            parent_index: None,
+            parents: Parents::default(),
+            deprecated: false,
+            empty_description: false,
+            description_location: None,
+        })
+    }
 }
 
-/// Decode a type ID to ItemType
-fn decode_item_type(type_id: u8) -> ItemType {
-    match type_id {
-        0 => ItemType::MutRef,
-        1 => ItemType::PrimitiveOrBuiltin,
-        2 => ItemType::Module,
-        3 => ItemType::ExternCrate,
-        4 => ItemType::Import,
-        5 => ItemType::Struct,
-        6 => ItemType::Enum,
-        7 => ItemType::Function,
-        8 => ItemType::Typedef,
-        9 => ItemType::Static,
-        10 => ItemType::Trait,
-        11 => ItemType::Impl,
-        12 => ItemType::TyMethod,
-        13 => ItemType::Method,
-        14 => ItemType::StructField,
-        15 => ItemType::Variant,
-        16 => ItemType::Macro,
-        17 => ItemType::Primitive,
-        18 => ItemType::AssocConst,
-        19 => ItemType::AssocType,
-        20 => ItemType::Constant,
-        21 => ItemType::Union,
-        22 => ItemType::ForeignType,
-        23 => ItemType::Keyword,
-        24 => ItemType::OpaqueTy,
-        25 => ItemType::ProcAttribute,
-        26 => ItemType::ProcDerive,
-        27 => ItemType::TraitAlias,
-        _ => ItemType::Module, // Fallback
+#[cfg(test)]
+impl ItemBuilder {
+    /// The module the item is documented in, which it's also defined in
+    /// unless [`ItemBuilder::exact_path`] follows.
+    pub(crate) fn path(mut self, path: &str) -> Self {
+        self.0.path = path.to_string();
+        self.0.exact_path = path.to_string();
+        self
+    }
+
+    /// The module the item is defined in, for a re-export.
+    pub(crate) fn exact_path(mut self, exact_path: &str) -> Self {
+        self.0.exact_path = exact_path.to_string();
+        self
+    }
+
+    /// A parent, alone in the item's parent table.
+    pub(crate) fn parent(mut self, name: &str, item_type: ItemType) -> Self {
+        self.0.parent_index = Some(0);
+        self.0.parents = Parents::new(vec![Parent {
+            name: name.to_string(),
+            item_type,
+        }]);
+        self
+    }
+
+    /// The item's position in its crate's compact data.
+    pub(crate) fn index(mut self, index: usize) -> Self {
+        self.0.id.index = index;
+        self.0.bit_index = index + 1;
+        self
+    }
+
+    /// The site the item's docs came from, rather than the local docs.
+    pub(crate) fn source(mut self, source: &str) -> Self {
+        self.0.id.source = Arc::from(source);
+        self
+    }
+
+    pub(crate) fn empty_description(mut self, empty_description: bool) -> Self {
+        self.0.empty_description = empty_description;
+        self
+    }
+
+    pub(crate) fn build(self) -> SearchItem {
+        self.0
     }
 }
 
+fn is_definition(item: &SearchItem) -> bool {
+    item.exact_path.split("::").next() == Some(item.crate_name.as_str())
+}
+
+/// Decode one of a crate's bitmap fields, treating a malformed bitmap as empty.
+fn decode_bitmap(
+    field: &'static str,
+    encoded: &str,
+    diagnostics: &mut Vec<Diagnostic>,
+) -> RoaringBitmap {
+    RoaringBitmap::from_base64(encoded).unwrap_or_else(|err| {
+        diagnostics.push(Diagnostic::MalformedBitmap {
+            field,
+            error: err.to_string(),
+        });
+        RoaringBitmap::default()
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::search_index::CrateData;
+    use crate::encode::{encode_crate, tables};
+    use crate::search_index::{CrateData, QualifiedPath, Reexport};
+    use proptest::prelude::*;
 
     #[test]
     fn test_decode_basic_fields() {
@@ -215,15 +697,229 @@ mod tests {
 
         // Check first item basic fields
         assert_eq!(items[0].crate_name, "test_crate");
-        assert_eq!(items[0].id, 0);
+        assert_eq!(
+            items[0].id,
+            ItemId::new(ItemId::LOCAL_SOURCE, "test_crate", 0)
+        );
         assert_eq!(items[0].bit_index, 1); // bit_index is i + 1
 
         // Check second item basic fields
         assert_eq!(items[1].crate_name, "test_crate");
-        assert_eq!(items[1].id, 1);
+        assert_eq!(items[1].id.index, 1);
         assert_eq!(items[1].bit_index, 2);
     }
 
+    #[test]
+    fn test_decode_crate_iter() {
+        let crate_data = CrateData {
+            types: "HHF".to_string(),
+            names: vec![
+                "from_str".to_string(),
+                "to_string".to_string(),
+                "Value".to_string(),
+            ],
+            paths: vec![QualifiedPath {
+                index: 0,
+                path: "serde_json".to_string(),
+            }],
+            parent_items: vec![],
+            reexports: vec![],
+            i: "```".to_string(),
+            f: String::new(),
+            desc: String::new(),
+            param_types: vec![],
+            impl_disambiguators: vec![],
+            c: String::new(),
+            e: String::new(),
+            aliases: None,
+        };
+
+        // The same items decode_crate gives, one at a time
+        let all = decode_crate("serde_json", &crate_data);
+        assert_eq!(
+            decode_crate_iter("serde_json", &crate_data).collect::<Vec<_>>(),
+            all
+        );
+
+        // Stopping early, or filtering, decodes only as far as it's read
+        let mut items = decode_crate_iter("serde_json", &crate_data);
+        assert_eq!(items.size_hint(), (3, Some(3)));
+        let value = items.find(|item| item.item_type == ItemType::Struct);
+        assert_eq!(value.as_ref(), all.last());
+        assert_eq!(items.next(), None);
+        assert!(items.diagnostics().is_empty());
+
+        // Problems with the whole crate are known once it's all read
+        let mut short = CrateData {
+            i: "``".to_string(),
+            ..crate_data
+        };
+        short.names.truncate(3);
+        let mut items = decode_crate_iter("serde_json", &short);
+        assert!(items.next().is_some());
+        assert!(items.diagnostics().is_empty());
+        items.by_ref().for_each(drop);
+        assert_eq!(
+            items.into_diagnostics(),
+            [Diagnostic::ParentCountMismatch {
+                items: 3,
+                parents: 2
+            }]
+        );
+    }
+
+    #[test]
+    fn test_decode_type_codes_below_a() {
+        // Found by the parse_search_index fuzz target: used to underflow
+        let crate_data = CrateData {
+            types: "S1H".to_string(),
+            names: vec!["A".to_string(), "b".to_string(), "c".to_string()],
+            paths: vec![],
+            parent_items: vec![],
+            reexports: vec![],
+            i: String::new(),
+            f: String::new(),
+            desc: String::new(),
+            param_types: vec![],
+            impl_disambiguators: vec![],
+            c: String::new(),
+            e: String::new(),
+            aliases: None,
+        };
+
+        let items = decode_crate("test_crate", &crate_data);
+        assert_eq!(items.len(), 3);
+        assert_eq!(items[1].item_type, ItemType::Unknown(u8::MAX));
+        assert_eq!(items[2].item_type, ItemType::Function);
+    }
+
+    #[test]
+    fn test_decode_diagnostics() {
+        let crate_data = CrateData {
+            types: "Fz".to_string(),
+            names: vec!["Config".to_string(), "load".to_string()],
+            paths: vec![QualifiedPath {
+                index: 0,
+                path: "test_crate".to_string(),
+            }],
+            parent_items: vec![],
+            reexports: vec![Reexport {
+                item_index: 1,
+                path_index: 5,
+            }],
+            // Parent 3 of the first item, and nothing for the second
+            i: "f".to_string(),
+            f: String::new(),
+            desc: String::new(),
+            param_types: vec![],
+            impl_disambiguators: vec![],
+            c: "not base64!".to_string(),
+            e: String::new(),
+            aliases: None,
+        };
+
+        let (items, diagnostics) = decode_crate_with_diagnostics(
+            &Arc::from(ItemId::LOCAL_SOURCE),
+            "test_crate",
+            &crate_data,
+            IndexFeatures::default(),
+        );
+        assert_eq!(items.len(), 2);
+        assert_eq!(items[0].parent_index, None);
+        assert_eq!(items[1].item_type, ItemType::Unknown(57));
+        assert!(matches!(
+            diagnostics[0],
+            Diagnostic::MalformedBitmap { field: "c", .. }
+        ));
+        assert_eq!(
+            diagnostics[1..],
+            [
+                Diagnostic::MissingParent {
+                    item: 0,
+                    parent_index: 2
+                },
+                Diagnostic::UnknownItemType { item: 1, code: 57 },
+                Diagnostic::MissingReexportPath {
+                    item: 1,
+                    path_index: 5
+                },
+                Diagnostic::ParentCountMismatch {
+                    items: 2,
+                    parents: 1
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_decode_without_first_name_or_path() {
+        let crate_data = CrateData {
+            types: "HH".to_string(),
+            names: vec![String::new(), "load".to_string()],
+            paths: vec![QualifiedPath {
+                index: 1,
+                path: "test_crate::config".to_string(),
+            }],
+            parent_items: vec![],
+            reexports: vec![],
+            i: "``".to_string(),
+            f: String::new(),
+            desc: String::new(),
+            param_types: vec![],
+            impl_disambiguators: vec![],
+            c: String::new(),
+            e: String::new(),
+            aliases: None,
+        };
+
+        let (items, diagnostics) = decode_crate_with_diagnostics(
+            &Arc::from(ItemId::LOCAL_SOURCE),
+            "test_crate",
+            &crate_data,
+            IndexFeatures::default(),
+        );
+        // As rustdoc's search decodes them
+        assert_eq!((items[0].name.as_str(), items[0].path.as_str()), ("", ""));
+        assert_eq!(items[1].name, "load");
+        assert_eq!(
+            diagnostics,
+            [Diagnostic::EmptyFirstName, Diagnostic::MissingFirstPath]
+        );
+        assert_eq!(
+            diagnostics[0].to_string(),
+            "item 0: empty name with none before it to reuse"
+        );
+    }
+
+    #[test]
+    fn test_decode_non_ascii() {
+        // A multi-byte type code must count as one item, not shift the rest
+        let crate_data = CrateData {
+            types: "SéH".to_string(),
+            names: vec!["Größe".to_string(), "ΣΟΦΟΣ".to_string(), "🦀".to_string()],
+            paths: vec![],
+            parent_items: vec![],
+            reexports: vec![],
+            i: String::new(),
+            f: String::new(),
+            desc: String::new(),
+            param_types: vec![],
+            impl_disambiguators: vec![],
+            c: String::new(),
+            e: String::new(),
+            aliases: None,
+        };
+
+        assert_eq!(crate_data.item_count(), 3);
+        let items = decode_crate("test_crate", &crate_data);
+        assert_eq!(items.len(), 3);
+        assert_eq!(items[1].item_type, ItemType::Unknown(168));
+        assert_eq!(items[2].item_type, ItemType::Function);
+        assert_eq!(items[2].name, "🦀");
+        assert_eq!(items[0].normalized_name, "grösse");
+        assert_eq!(items[1].normalized_name, "σοφοσ");
+    }
+
     #[test]
     fn test_decode_name_with_compression() {
         // Test name compression: empty string means "reuse last name"
@@ -412,4 +1108,193 @@ mod tests {
         // Item 2 should have parent_items[1] as parent (index 1)
         assert_eq!(items[2].parent_index, Some(1));
     }
+
+    #[test]
+    fn test_decode_parent_backreferences_from_fixture() {
+        use crate::search_index::{extract_json_string, parse_search_index};
+
+        let content = std::fs::read_to_string("tests/fixtures/search-index.js")
+            .expect("Failed to read fixture");
+        let crates = parse_search_index(&extract_json_string(&content).unwrap()).unwrap();
+        let either = crates
+            .iter()
+            .find(|entry| entry.name == "either")
+            .expect("either crate in fixture");
+
+        let items = decode_crate("either", &either.data);
+        let parent_name = |i: usize| {
+            items[i]
+                .parent_index
+                .map(|parent| either.data.parent_items[parent].name.as_str())
+        };
+
+        // `i` for either starts with "````d`00Ab10" - mostly backreferences
+        assert_eq!(items[7].name, "all");
+        assert_eq!(parent_name(7), Some("Either"));
+        assert_eq!(items[8].name, "all");
+        assert_eq!(parent_name(8), Some("IterEither"));
+        assert_eq!(items[19].name, "as_ref");
+        assert_eq!(parent_name(19), Some("Either"));
+
+        // Items look their parent up in one table the crate's items share
+        assert_eq!(items[7].parent_name(), Some("Either"));
+        assert_eq!(items[7].parent_type(), Some(ItemType::Enum));
+        assert_eq!(items[8].parent_name(), Some("IterEither"));
+        assert_eq!(items[7].parents.len(), either.data.parent_items.len());
+        assert!(Arc::ptr_eq(&items[7].parents.0, &items[8].parents.0));
+    }
+
+    #[test]
+    fn test_decode_bitmap_flags_from_fixture() {
+        use crate::search_index::{extract_json_string, parse_search_index};
+
+        let content = std::fs::read_to_string("tests/fixtures/search-index.js")
+            .expect("Failed to read fixture");
+        let crates = parse_search_index(&extract_json_string(&content).unwrap()).unwrap();
+        let serde = crates
+            .iter()
+            .find(|entry| entry.name == "serde")
+            .expect("serde crate in fixture");
+
+        let items = decode_crate("serde", &serde.data);
+
+        // serde's `c` bitmap holds bit indices 134-136 and 145-147
+        let deprecated: Vec<usize> = items
+            .iter()
+            .filter(|item| item.deprecated)
+            .map(|item| item.bit_index)
+            .collect();
+        assert_eq!(deprecated, vec![134, 135, 136, 145, 146, 147]);
+        assert!(items.iter().any(|item| item.empty_description));
+        assert!(items.iter().any(|item| !item.empty_description));
+    }
+
+    #[test]
+    fn test_description_locations_fill_shards_in_fixture() {
+        use crate::search_index::{extract_json_string, parse_search_index};
+
+        let content = std::fs::read_to_string("tests/fixtures/search-index.js")
+            .expect("Failed to read fixture");
+        for entry in parse_search_index(&extract_json_string(&content).unwrap()).unwrap() {
+            let items = decode_crate(&entry.name, &entry.data);
+            let shard_lengths: Vec<usize> = VlqHexDecoder::new(&entry.data.desc)
+                .map(|len| len as usize)
+                .collect();
+
+            // Every described item lands inside a shard, and the last one ends it
+            let mut last = None;
+            for location in items.iter().filter_map(|item| item.description_location) {
+                assert!(
+                    location.index < shard_lengths[location.shard],
+                    "{}",
+                    entry.name
+                );
+                last = Some(location);
+            }
+            if let Some(last) = last {
+                assert_eq!(last.shard + 1, shard_lengths.len(), "{}", entry.name);
+                assert_eq!(last.index + 1, shard_lengths[last.shard], "{}", entry.name);
+            }
+        }
+    }
+
+    /// A `deserialize` method, as found in the serde crates.
+    fn method(crate_name: &str, path: &str, exact_path: &str, parent: Option<&str>) -> SearchItem {
+        let method = SearchItem::builder(crate_name, ItemType::Method, "deserialize")
+            .path(path)
+            .exact_path(exact_path);
+        match parent {
+            Some(parent) => method.parent(parent, ItemType::Struct).build(),
+            None => method.build(),
+        }
+    }
+
+    #[test]
+    fn test_dedup_reexports() {
+        let item = method;
+        let items = vec![
+            item("serde", "serde::de", "serde_core::de", Some("Deserialize")),
+            item(
+                "serde_core",
+                "serde_core::de",
+                "serde_core::de",
+                Some("Deserialize"),
+            ),
+            // Same name and module, but a method of a different type
+            item(
+                "serde_core",
+                "serde_core::de",
+                "serde_core::de",
+                Some("Visitor"),
+            ),
+        ];
+
+        let unique = dedup_reexports(&items);
+
+        assert_eq!(unique.len(), 2);
+        // The definition replaces the re-export that was seen first
+        assert_eq!(unique[0].crate_name, "serde_core");
+        assert_eq!(unique[1].parent_name(), Some("Visitor"));
+
+        // Streaming keeps whichever came first
+        let mut filter = ReexportFilter::default();
+        let first: Vec<_> = items.iter().filter(|item| filter.is_first(item)).collect();
+        assert_eq!(first.len(), 2);
+        assert_eq!(first[0].crate_name, "serde");
+    }
+
+    proptest! {
+        #[test]
+        fn test_decode_reverses_encoding((rows, parents) in tables()) {
+            let data = encode_crate(&rows, &parents);
+            let (items, diagnostics) = decode_crate_with_diagnostics(
+                &Arc::from(ItemId::LOCAL_SOURCE),
+                "test_crate",
+                &data,
+                IndexFeatures::default(),
+            );
+            // Rustdoc always writes the first name and path
+            let expected: Vec<Diagnostic> = rows
+                .first()
+                .map(|first| {
+                    let empty_name = first.name.is_empty().then_some(Diagnostic::EmptyFirstName);
+                    let no_path = first.path.is_empty().then_some(Diagnostic::MissingFirstPath);
+                    empty_name.into_iter().chain(no_path).collect()
+                })
+                .unwrap_or_default();
+            prop_assert_eq!(diagnostics, expected);
+            prop_assert_eq!(items.len(), rows.len());
+            for (index, (item, row)) in items.iter().zip(&rows).enumerate() {
+                prop_assert_eq!(item.item_type, row.ty);
+                prop_assert_eq!(&item.name, &row.name);
+                prop_assert_eq!(&item.normalized_name, &normalize_name(&row.name));
+                prop_assert_eq!(&item.path, &row.path);
+                prop_assert_eq!(&item.exact_path, &row.path);
+                prop_assert_eq!(item.id.index, index);
+                prop_assert_eq!(item.bit_index, index + 1);
+                prop_assert_eq!(item.parent_index, row.parent);
+                let parent = row.parent.map(|parent| &parents[parent]);
+                prop_assert_eq!(item.parent_type(), parent.map(|(ty, _)| *ty));
+                prop_assert_eq!(item.parent_name(), parent.map(|(_, name)| name.as_str()));
+            }
+        }
+    }
+
+    #[test]
+    fn test_is_within() {
+        let reexport = method("serde", "serde::de", "serde_core::de", Some("Deserialize"));
+
+        assert!(reexport.is_within("serde"));
+        assert!(reexport.is_within("serde::de"));
+        assert!(reexport.is_within("serde::de::"));
+        // Where it's defined counts too, and a type scopes its methods
+        assert!(reexport.is_within("serde_core::de"));
+        assert!(reexport.is_within("serde-core::de"));
+        assert!(reexport.is_within("serde-core"));
+        assert!(reexport.is_within("serde::de::Deserialize"));
+        // Whole segments only
+        assert!(!reexport.is_within("serde::d"));
+        assert!(!reexport.is_within("serde::de::Visitor"));
+        assert!(!reexport.is_within("serde_json"));
+    }
 }
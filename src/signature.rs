@@ -0,0 +1,573 @@
+// Function signatures from the `f` field, and type patterns to search them with
+
+use crate::normalize::fold_case;
+use crate::search_index::{CrateData, ItemType};
+use crate::unify::Unifier;
+use crate::vlq::{VlqListDecoder, VlqNode};
+use std::collections::HashMap;
+use std::fmt;
+
+/// A type in a function signature.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SignatureType {
+    pub kind: TypeKind,
+    /// Generic arguments, e.g. `T` and `E` in `Result<T, E>`
+    pub generics: Vec<SignatureType>,
+    /// Associated type bindings, e.g. `Item = u8` in `Iterator<Item = u8>`
+    pub bindings: Vec<(SignatureType, Vec<SignatureType>)>,
+}
+
+/// What a [`SignatureType`] refers to.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TypeKind {
+    /// The function's generic parameter with this index. rustdoc doesn't keep
+    /// their names since they aren't part of the API.
+    Generic(usize),
+    /// A type from the crate's `p` list
+    Named {
+        name: String,
+        item_type: ItemType,
+        /// Module path of the type, e.g. `std::io::error`
+        path: String,
+        /// Whether type search looks through it to its generic arguments, as
+        /// for `Option<T>` and references (`#[doc(search_unbox)]`)
+        unbox: bool,
+    },
+    /// A type rustdoc left out of the index
+    Unknown,
+}
+
+/// A function's parameter and return types, as rustdoc indexes them for type search.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FunctionSignature {
+    pub inputs: Vec<SignatureType>,
+    pub output: Vec<SignatureType>,
+    /// Trait bounds on each generic parameter, indexed like [`TypeKind::Generic`]
+    pub where_clause: Vec<Vec<SignatureType>>,
+}
+
+impl FunctionSignature {
+    /// Whether the function returns a type matching `pattern`.
+    pub fn returns(&self, pattern: &TypePattern) -> bool {
+        // Functions returning `()` have no output in the index
+        if self.output.is_empty() {
+            return pattern.is_unit();
+        }
+        let unifier = Unifier::new(self);
+        self.output.iter().any(|ty| unifier.unify(pattern, ty))
+    }
+}
+
+/// A named type from the `p` list, with its module path resolved.
+struct PathType<'a> {
+    name: &'a str,
+    item_type: ItemType,
+    path: &'a str,
+    unbox: bool,
+}
+
+/// Decode the `f` field: the signature of each item, or `None` for items that
+/// aren't functions.
+///
+/// The result has one entry per item, in the same order as `data.names`.
+pub fn decode_signatures(data: &CrateData) -> Vec<Option<FunctionSignature>> {
+    let paths_map: HashMap<usize, &str> = data
+        .paths
+        .iter()
+        .map(|qp| (qp.index, qp.path.as_str()))
+        .collect();
+
+    // Like item paths, a `p` entry without a path reuses the previous one
+    let mut last_path = paths_map.get(&0).copied().unwrap_or("");
+    let path_types: Vec<PathType> = data
+        .parent_items
+        .iter()
+        .map(|item| {
+            if let Some(path) = item.path_index.and_then(|index| paths_map.get(&index)) {
+                last_path = path;
+            }
+            PathType {
+                name: &item.name,
+                item_type: item.ty,
+                path: last_path,
+                unbox: item.unbox_flag.is_some_and(|flag| flag != 0),
+            }
+        })
+        .collect();
+
+    let mut signatures: Vec<_> = VlqListDecoder::new(&data.f)
+        .take(data.names.len())
+        .map(|node| function_signature(&node, &path_types))
+        .collect();
+    signatures.resize(data.names.len(), None);
+    signatures
+}
+
+/// Build a signature from `[inputs, output, where_clause...]`; `0` means none.
+fn function_signature(node: &VlqNode, path_types: &[PathType]) -> Option<FunctionSignature> {
+    let VlqNode::List(parts) = node else {
+        return None;
+    };
+    let mut parts = parts.iter().map(|part| match part {
+        VlqNode::Number(_) => vec![signature_type(part, path_types)],
+        VlqNode::List(types) => types
+            .iter()
+            .map(|ty| signature_type(ty, path_types))
+            .collect(),
+    });
+
+    Some(FunctionSignature {
+        inputs: parts.next().unwrap_or_default(),
+        output: parts.next().unwrap_or_default(),
+        where_clause: parts.collect(),
+    })
+}
+
+/// Build a type from a path index, or `[path index, generics, bindings]`.
+///
+/// Negative indexes are generic parameters, `0` is a type rustdoc didn't
+/// index, and anything else is 1-based into the `p` list.
+fn signature_type(node: &VlqNode, path_types: &[PathType]) -> SignatureType {
+    let (index, generics, bindings) = match node {
+        VlqNode::Number(index) => (*index, &[][..], &[][..]),
+        VlqNode::List(parts) => {
+            let index = match parts.first() {
+                Some(VlqNode::Number(index)) => *index,
+                _ => 0,
+            };
+            (index, list(parts.get(1)), list(parts.get(2)))
+        }
+    };
+
+    let kind = match index {
+        index if index < 0 => TypeKind::Generic((-index - 1) as usize),
+        0 => TypeKind::Unknown,
+        index => match path_types.get(index as usize - 1) {
+            Some(path_type) => TypeKind::Named {
+                name: path_type.name.to_string(),
+                item_type: path_type.item_type,
+                path: path_type.path.to_string(),
+                unbox: path_type.unbox,
+            },
+            None => TypeKind::Unknown,
+        },
+    };
+
+    SignatureType {
+        kind,
+        generics: generics
+            .iter()
+            .map(|ty| signature_type(ty, path_types))
+            .collect(),
+        bindings: bindings
+            .iter()
+            .filter_map(|binding| match binding {
+                VlqNode::List(pair) => Some((
+                    signature_type(pair.first()?, path_types),
+                    list(pair.get(1))
+                        .iter()
+                        .map(|ty| signature_type(ty, path_types))
+                        .collect(),
+                )),
+                VlqNode::Number(_) => None,
+            })
+            .collect(),
+    }
+}
+
+fn list(node: Option<&VlqNode>) -> &[VlqNode] {
+    match node {
+        Some(VlqNode::List(values)) => values,
+        _ => &[],
+    }
+}
+
+impl fmt::Display for SignatureType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match &self.kind {
+            // Parameter names aren't indexed, so use the conventional letters
+            TypeKind::Generic(index) => match b"TUVWXYZ".get(*index) {
+                Some(&letter) => return write!(f, "{}", letter as char),
+                None => return write!(f, "T{}", index),
+            },
+            TypeKind::Unknown => "_",
+            TypeKind::Named { name, .. } => name.as_str(),
+        };
+
+        match (name, self.generics.as_slice()) {
+            ("reference", [inner]) => return write!(f, "&{}", inner),
+            ("reference", [marker, inner]) if marker.is_named("mut") => {
+                return write!(f, "&mut {}", inner);
+            }
+            ("slice", [inner]) => return write!(f, "[{}]", inner),
+            ("unit", []) => return write!(f, "()"),
+            ("tuple", generics) => return write!(f, "({})", join(generics)),
+            _ => {}
+        }
+
+        write!(f, "{}", name)?;
+        if !self.generics.is_empty() || !self.bindings.is_empty() {
+            let bindings = self
+                .bindings
+                .iter()
+                .map(|(name, types)| format!("{} = {}", name, join(types)));
+            let arguments: Vec<String> = self
+                .generics
+                .iter()
+                .map(ToString::to_string)
+                .chain(bindings)
+                .collect();
+            write!(f, "<{}>", arguments.join(", "))?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for FunctionSignature {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "({})", join(&self.inputs))?;
+        match self.output.as_slice() {
+            [] => Ok(()),
+            [output] => write!(f, " -> {}", output),
+            outputs => write!(f, " -> ({})", join(outputs)),
+        }
+    }
+}
+
+impl SignatureType {
+    fn is_named(&self, expected: &str) -> bool {
+        matches!(&self.kind, TypeKind::Named { name, .. } if name == expected)
+    }
+}
+
+fn join(types: &[SignatureType]) -> String {
+    types
+        .iter()
+        .map(ToString::to_string)
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Why a type pattern could not be parsed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TypePatternError {
+    /// There is no type after `->`
+    Empty,
+    /// Something other than a type where one was expected
+    Unexpected(String),
+    /// A `<`, `(` or `[` without its closing bracket
+    Unclosed(char),
+}
+
+impl fmt::Display for TypePatternError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TypePatternError::Empty => write!(f, "expected a type after ->"),
+            TypePatternError::Unexpected(text) => write!(f, "unexpected \"{}\" in type", text),
+            TypePatternError::Unclosed(open) => write!(f, "unclosed '{}' in type", open),
+        }
+    }
+}
+
+impl std::error::Error for TypePatternError {}
+
+/// A type written in a query, such as `Result<_, io::Error>`.
+///
+/// See [`Unifier`] for how patterns match signature types.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TypePattern {
+    /// `_`, matching any type
+    Wildcard,
+    /// A single-letter generic such as `T`, matching any type as long as every
+    /// use of the same letter matches the same type
+    Generic(String),
+    Named {
+        /// Lowercase type name
+        name: String,
+        /// Lowercase leading path segments, e.g. `["io"]` for `io::Error`
+        path: Vec<String>,
+        generics: Vec<TypePattern>,
+    },
+}
+
+impl TypePattern {
+    /// Parse a return type query such as `-> Result<_, io::Error>`.
+    ///
+    /// Returns `None` if `query` doesn't start with `->`.
+    pub fn parse_return_query(query: &str) -> Option<Result<TypePattern, TypePatternError>> {
+        let pattern = query.trim_start().strip_prefix("->")?;
+        Some(TypePattern::parse(pattern))
+    }
+
+    /// Parse a type such as `Option<&str>`, `(u8, _)` or `[T]`.
+    pub fn parse(input: &str) -> Result<TypePattern, TypePatternError> {
+        let tokens = tokenize(input);
+        if tokens.is_empty() {
+            return Err(TypePatternError::Empty);
+        }
+        let mut parser = PatternParser {
+            tokens,
+            position: 0,
+        };
+        let pattern = parser.parse_type()?;
+        match parser.tokens.get(parser.position) {
+            None => Ok(pattern),
+            Some(token) => Err(TypePatternError::Unexpected(token.clone())),
+        }
+    }
+
+    fn named(name: &str, generics: Vec<TypePattern>) -> Self {
+        TypePattern::Named {
+            name: name.to_string(),
+            path: vec![],
+            generics,
+        }
+    }
+
+    fn is_unit(&self) -> bool {
+        matches!(self, TypePattern::Named { name, .. } if name == "unit")
+    }
+}
+
+fn tokenize(input: &str) -> Vec<String> {
+    let mut tokens = Vec::new();
+    let mut chars = input.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c.is_whitespace() {
+            continue;
+        }
+        if c.is_alphanumeric() || c == '_' {
+            let mut word = c.to_string();
+            while let Some(&next) = chars.peek() {
+                if !(next.is_alphanumeric() || next == '_') {
+                    break;
+                }
+                word.push(next);
+                chars.next();
+            }
+            tokens.push(word);
+        } else if c == ':' && chars.peek() == Some(&':') {
+            chars.next();
+            tokens.push("::".to_string());
+        } else {
+            tokens.push(c.to_string());
+        }
+    }
+    tokens
+}
+
+struct PatternParser {
+    tokens: Vec<String>,
+    position: usize,
+}
+
+impl PatternParser {
+    fn peek(&self) -> Option<&str> {
+        self.tokens.get(self.position).map(String::as_str)
+    }
+
+    fn next(&mut self) -> Option<&str> {
+        let token = self.tokens.get(self.position)?;
+        self.position += 1;
+        Some(token)
+    }
+
+    fn eat(&mut self, token: &str) -> bool {
+        let matches = self.peek() == Some(token);
+        if matches {
+            self.position += 1;
+        }
+        matches
+    }
+
+    fn expect_close(&mut self, open: char, close: &str) -> Result<(), TypePatternError> {
+        if self.eat(close) {
+            Ok(())
+        } else {
+            Err(TypePatternError::Unclosed(open))
+        }
+    }
+
+    fn parse_type(&mut self) -> Result<TypePattern, TypePatternError> {
+        let Some(token) = self.next().map(str::to_string) else {
+            return Err(TypePatternError::Empty);
+        };
+
+        match token.as_str() {
+            "_" => Ok(TypePattern::Wildcard),
+            "!" => Ok(TypePattern::named("never", vec![])),
+            // rustdoc indexes `&mut T` as `reference<mut, T>`
+            "&" => {
+                let mut generics = vec![];
+                if self.eat("mut") {
+                    generics.push(TypePattern::named("mut", vec![]));
+                }
+                generics.push(self.parse_type()?);
+                Ok(TypePattern::named("reference", generics))
+            }
+            "[" => {
+                let inner = self.parse_type()?;
+                self.expect_close('[', "]")?;
+                Ok(TypePattern::named("slice", vec![inner]))
+            }
+            "(" => {
+                let mut elements = self.parse_list(")")?;
+                self.expect_close('(', ")")?;
+                Ok(match elements.len() {
+                    0 => TypePattern::named("unit", vec![]),
+                    1 => elements.remove(0),
+                    _ => TypePattern::named("tuple", elements),
+                })
+            }
+            word if is_identifier(word) => {
+                let mut segments = vec![fold_case(word)];
+                while self.eat("::") {
+                    match self.next() {
+                        Some(segment) if is_identifier(segment) => {
+                            segments.push(fold_case(segment))
+                        }
+                        Some(other) => return Err(TypePatternError::Unexpected(other.to_string())),
+                        None => return Err(TypePatternError::Empty),
+                    }
+                }
+
+                let mut generics = vec![];
+                if self.eat("<") {
+                    generics = self.parse_list(">")?;
+                    self.expect_close('<', ">")?;
+                }
+
+                // `T` is a generic, not a type named T
+                if segments.len() == 1
+                    && generics.is_empty()
+                    && word.len() == 1
+                    && word.chars().all(|c| c.is_ascii_uppercase())
+                {
+                    return Ok(TypePattern::Generic(word.to_string()));
+                }
+                let name = segments.pop().unwrap_or_default();
+                Ok(TypePattern::Named {
+                    name,
+                    path: segments,
+                    generics,
+                })
+            }
+            other => Err(TypePatternError::Unexpected(other.to_string())),
+        }
+    }
+
+    /// Comma-separated types up to (not including) `close`.
+    fn parse_list(&mut self, close: &str) -> Result<Vec<TypePattern>, TypePatternError> {
+        let mut types = Vec::new();
+        while self.peek().is_some_and(|token| token != close) {
+            types.push(self.parse_type()?);
+            if !self.eat(",") {
+                break;
+            }
+        }
+        Ok(types)
+    }
+}
+
+fn is_identifier(token: &str) -> bool {
+    token
+        .chars()
+        .next()
+        .is_some_and(|c| c.is_alphabetic() || c == '_')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pattern(input: &str) -> TypePattern {
+        TypePattern::parse(input).unwrap()
+    }
+
+    #[test]
+    fn test_decode_fixture_signature() {
+        let content = std::fs::read_to_string("tests/fixtures/search-index.js").unwrap();
+        let json_string = crate::search_index::extract_json_string(&content).unwrap();
+        let crates = crate::search_index::parse_search_index(&json_string).unwrap();
+        let entry = crates
+            .iter()
+            .find(|entry| entry.name == "precomputed_hash")
+            .unwrap();
+
+        // trait PrecomputedHash { fn precomputed_hash(&self) -> u32; }
+        let signatures = decode_signatures(&entry.data);
+        assert_eq!(signatures.len(), entry.data.names.len());
+        assert_eq!(signatures[0], None);
+        let method = signatures[1].as_ref().unwrap();
+        assert_eq!(method.inputs[0].to_string(), "&PrecomputedHash");
+        assert_eq!(method.output[0].to_string(), "u32");
+        assert_eq!(method.to_string(), "(&PrecomputedHash) -> u32");
+        assert!(method.returns(&pattern("u32")));
+        assert!(!method.returns(&pattern("u64")));
+    }
+
+    #[test]
+    fn test_parse_patterns() {
+        assert_eq!(
+            pattern("io::Error"),
+            TypePattern::Named {
+                name: "error".to_string(),
+                path: vec!["io".to_string()],
+                generics: vec![],
+            }
+        );
+        assert_eq!(
+            pattern("Result<_, T>"),
+            TypePattern::named(
+                "result",
+                vec![TypePattern::Wildcard, TypePattern::Generic("T".to_string())]
+            )
+        );
+        assert!(pattern("()").is_unit());
+        assert_eq!(
+            pattern("&mut [u8]"),
+            TypePattern::named(
+                "reference",
+                vec![
+                    TypePattern::named("mut", vec![]),
+                    TypePattern::named("slice", vec![TypePattern::named("u8", vec![])])
+                ]
+            )
+        );
+        assert_eq!(
+            pattern("(u8, bool)"),
+            TypePattern::named(
+                "tuple",
+                vec![
+                    TypePattern::named("u8", vec![]),
+                    TypePattern::named("bool", vec![])
+                ]
+            )
+        );
+
+        assert!(TypePattern::parse_return_query("Result").is_none());
+        assert_eq!(
+            TypePattern::parse_return_query("->"),
+            Some(Err(TypePatternError::Empty))
+        );
+        assert_eq!(
+            TypePattern::parse("Vec<u8"),
+            Err(TypePatternError::Unclosed('<'))
+        );
+        assert_eq!(
+            TypePattern::parse("Vec>"),
+            Err(TypePatternError::Unexpected(">".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_unit_return() {
+        let signature = FunctionSignature {
+            inputs: vec![],
+            output: vec![],
+            where_clause: vec![],
+        };
+        assert!(signature.returns(&pattern("()")));
+        assert!(!signature.returns(&pattern("u8")));
+    }
+}
@@ -0,0 +1,167 @@
+// Scoring how alike two function signatures are
+
+use crate::signature::{FunctionSignature, SignatureType, TypeKind};
+use std::collections::HashMap;
+
+/// How much matching parameter types, return types and parameter counts each
+/// contribute to a [`similarity`] score.
+const INPUT_WEIGHT: f64 = 0.4;
+const OUTPUT_WEIGHT: f64 = 0.4;
+const ARITY_WEIGHT: f64 = 0.2;
+
+/// Name standing in for every generic parameter. Parameters are only known by
+/// position, so `T` in one function is as good a match as `U` in another.
+const GENERIC: &str = "<generic>";
+
+/// How alike two signatures are, from 0.0 (nothing in common) to 1.0 (the
+/// same types in the same number of parameters).
+///
+/// Parameter and return types are each compared as the multiset of type names
+/// they mention, generic arguments included, so `(&str) -> Result<Config, Error>`
+/// is closer to `(&[u8]) -> Result<Config, Error>` than to `(&str) -> usize`.
+/// Parameter order is ignored.
+pub fn similarity(a: &FunctionSignature, b: &FunctionSignature) -> f64 {
+    let inputs = overlap(&type_names(&a.inputs), &type_names(&b.inputs));
+    let output = overlap(&type_names(&a.output), &type_names(&b.output));
+    let (shorter, longer) = if a.inputs.len() <= b.inputs.len() {
+        (a.inputs.len(), b.inputs.len())
+    } else {
+        (b.inputs.len(), a.inputs.len())
+    };
+    let arity = if longer == 0 {
+        1.0
+    } else {
+        shorter as f64 / longer as f64
+    };
+    INPUT_WEIGHT * inputs + OUTPUT_WEIGHT * output + ARITY_WEIGHT * arity
+}
+
+/// How many times each type name appears in `types`, nested ones included.
+fn type_names(types: &[SignatureType]) -> HashMap<&str, usize> {
+    fn visit<'a>(ty: &'a SignatureType, counts: &mut HashMap<&'a str, usize>) {
+        let name = match &ty.kind {
+            TypeKind::Generic(_) => Some(GENERIC),
+            TypeKind::Named { name, .. } => Some(name.as_str()),
+            TypeKind::Unknown => None,
+        };
+        if let Some(name) = name {
+            *counts.entry(name).or_default() += 1;
+        }
+        for inner in &ty.generics {
+            visit(inner, counts);
+        }
+        for (name, types) in &ty.bindings {
+            visit(name, counts);
+            for inner in types {
+                visit(inner, counts);
+            }
+        }
+    }
+
+    let mut counts = HashMap::new();
+    for ty in types {
+        visit(ty, &mut counts);
+    }
+    counts
+}
+
+/// Weighted Jaccard index of two multisets; two empty sets are identical.
+fn overlap(a: &HashMap<&str, usize>, b: &HashMap<&str, usize>) -> f64 {
+    let mut shared = 0;
+    let mut total = 0;
+    for (name, &count) in a {
+        let other = b.get(name).copied().unwrap_or(0);
+        shared += count.min(other);
+        total += count.max(other);
+    }
+    total += b
+        .iter()
+        .filter(|(name, _)| !a.contains_key(*name))
+        .map(|(_, &count)| count)
+        .sum::<usize>();
+    if total == 0 {
+        1.0
+    } else {
+        shared as f64 / total as f64
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::search_index::ItemType;
+
+    fn ty(name: &str, generics: Vec<SignatureType>) -> SignatureType {
+        SignatureType {
+            kind: TypeKind::Named {
+                name: name.to_string(),
+                item_type: ItemType::Struct,
+                path: "core".to_string(),
+                unbox: false,
+            },
+            generics,
+            bindings: vec![],
+        }
+    }
+
+    fn generic(index: usize) -> SignatureType {
+        SignatureType {
+            kind: TypeKind::Generic(index),
+            generics: vec![],
+            bindings: vec![],
+        }
+    }
+
+    fn signature(inputs: Vec<SignatureType>, output: Vec<SignatureType>) -> FunctionSignature {
+        FunctionSignature {
+            inputs,
+            output,
+            where_clause: vec![],
+        }
+    }
+
+    fn str_ref() -> SignatureType {
+        ty("reference", vec![ty("str", vec![])])
+    }
+
+    fn config_result() -> SignatureType {
+        ty("Result", vec![ty("Config", vec![]), ty("Error", vec![])])
+    }
+
+    #[test]
+    fn test_identical_and_disjoint_signatures() {
+        let parse = signature(vec![str_ref()], vec![config_result()]);
+        assert_eq!(similarity(&parse, &parse), 1.0);
+        assert_eq!(
+            similarity(&signature(vec![], vec![]), &signature(vec![], vec![])),
+            1.0
+        );
+
+        let unrelated = signature(vec![ty("u8", vec![]), ty("u8", vec![])], vec![]);
+        // Only half the arity score is left: one parameter against two
+        assert!((similarity(&parse, &unrelated) - ARITY_WEIGHT / 2.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_ranks_closer_signatures_higher() {
+        let parse = signature(vec![str_ref()], vec![config_result()]);
+        let from_bytes = signature(
+            vec![ty("reference", vec![ty("slice", vec![ty("u8", vec![])])])],
+            vec![config_result()],
+        );
+        let len = signature(vec![str_ref()], vec![ty("usize", vec![])]);
+        assert!(similarity(&parse, &from_bytes) > similarity(&parse, &len));
+        // The score is symmetric
+        assert_eq!(
+            similarity(&parse, &from_bytes),
+            similarity(&from_bytes, &parse)
+        );
+    }
+
+    #[test]
+    fn test_generics_match_each_other() {
+        let first = signature(vec![generic(0)], vec![ty("Vec", vec![generic(0)])]);
+        let other = signature(vec![generic(1)], vec![ty("Vec", vec![generic(2)])]);
+        assert_eq!(similarity(&first, &other), 1.0);
+    }
+}
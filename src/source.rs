@@ -0,0 +1,244 @@
+// Rustdoc source pages: where an item's source is and its highlighted lines
+
+use std::ops::RangeInclusive;
+
+/// The source lines an item's page links to.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SourceLink {
+    /// The source page, relative to the documentation root with `/` separators
+    /// (e.g. `src/serde_json/value/mod.rs.html`), or a full URL for sources
+    /// hosted elsewhere, such as the standard library's
+    pub file: String,
+    /// The item's lines, numbered from 1
+    pub lines: RangeInclusive<usize>,
+}
+
+impl SourceLink {
+    /// Whether the source is on another site rather than in these docs.
+    pub fn is_external(&self) -> bool {
+        self.file.contains("://")
+    }
+}
+
+/// A run of source text and the highlighting class rustdoc gave it, such as
+/// `kw`, `comment` or `string`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Highlight {
+    pub class: Option<String>,
+    pub text: String,
+}
+
+/// The "Source" link of the item documented at `anchor` on the page `page_file`,
+/// or of the page's own item when `anchor` is `None`.
+pub fn source_link(html: &str, page_file: &str, anchor: Option<&str>) -> Option<SourceLink> {
+    let scope = match anchor {
+        None => {
+            // The page's link is in its heading, before any member
+            let end = html.find("<h2").unwrap_or(html.len());
+            &html[..end]
+        }
+        Some(anchor) => {
+            let start = html.find(&format!("id=\"{}\"", anchor))?;
+            let rest = &html[start..];
+            let end = rest.find("</section>").unwrap_or(rest.len());
+            &rest[..end]
+        }
+    };
+
+    // `class="src"` (or `"src rightside"`) now, `class="srclink"` in older rustdoc
+    let link = ["<a class=\"src", "<a class=\"srclink"]
+        .iter()
+        .filter_map(|marker| scope.find(marker))
+        .min()?;
+    let tag = &scope[link..link + scope[link..].find('>')?];
+    let href_start = tag.find("href=\"")? + "href=\"".len();
+    let href = &tag[href_start..href_start + tag[href_start..].find('"')?];
+
+    let (file, fragment) = href.split_once('#')?;
+    let lines = match fragment.split_once('-') {
+        Some((start, end)) => start.parse().ok()?..=end.parse().ok()?,
+        None => {
+            let line = fragment.parse().ok()?;
+            line..=line
+        }
+    };
+    let file = if file.contains("://") {
+        file.to_string()
+    } else {
+        resolve_relative(page_file, file)
+    };
+    Some(SourceLink { file, lines })
+}
+
+/// The code of a source page, one entry per line, each split into highlighted runs.
+///
+/// Line numbers are left out, whether rustdoc put them in a separate block or,
+/// as it does now, as a link at the start of each line.
+pub fn source_lines(html: &str) -> Vec<Vec<Highlight>> {
+    let Some(block) = html.find("<pre class=\"rust") else {
+        return Vec::new();
+    };
+    let code = &html[block..];
+    let start = code
+        .find("<code>")
+        .map_or(0, |start| start + "<code>".len());
+    let end = code.find("</code>").unwrap_or(code.len());
+    let code = &code[start..end];
+
+    let mut lines = vec![Vec::new()];
+    let mut classes: Vec<Option<String>> = Vec::new();
+    let mut text = String::new();
+    let mut rest = code;
+    while !rest.is_empty() {
+        let next = rest.find(['<', '\n']).unwrap_or(rest.len());
+        text.push_str(&rest[..next]);
+        rest = &rest[next..];
+
+        let class = classes.last().cloned().flatten();
+        if let Some(after) = rest.strip_prefix('\n') {
+            push_run(lines.last_mut().unwrap(), class, &mut text);
+            lines.push(Vec::new());
+            rest = after;
+            continue;
+        }
+        let Some(tag_end) = rest.find('>') else {
+            break;
+        };
+        let tag = &rest[..=tag_end];
+        rest = &rest[tag_end + 1..];
+
+        if tag.starts_with("<span") {
+            push_run(lines.last_mut().unwrap(), class, &mut text);
+            classes.push(attribute(tag, "class"));
+        } else if tag == "</span>" {
+            push_run(lines.last_mut().unwrap(), class, &mut text);
+            classes.pop();
+        } else if tag.contains("data-nosnippet") {
+            // A line number: skip its text too
+            rest = rest
+                .find("</a>")
+                .map_or("", |end| &rest[end + "</a>".len()..]);
+        }
+        // Other tags, such as jump-to-definition links, only wrap text
+    }
+    let class = classes.last().cloned().flatten();
+    push_run(lines.last_mut().unwrap(), class, &mut text);
+
+    // The code block ends with a newline
+    if lines.last().is_some_and(Vec::is_empty) {
+        lines.pop();
+    }
+    lines
+}
+
+/// Move `text` into `line` as a run with `class`, unless it's empty.
+fn push_run(line: &mut Vec<Highlight>, class: Option<String>, text: &mut String) {
+    if text.is_empty() {
+        return;
+    }
+    line.push(Highlight {
+        class,
+        text: unescape(&std::mem::take(text)),
+    });
+}
+
+/// The value of attribute `name` in an HTML start tag.
+fn attribute(tag: &str, name: &str) -> Option<String> {
+    let start = tag.find(&format!("{}=\"", name))? + name.len() + 2;
+    let end = start + tag[start..].find('"')?;
+    Some(tag[start..end].to_string())
+}
+
+fn unescape(text: &str) -> String {
+    text.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&#39;", "'")
+        .replace("&amp;", "&")
+}
+
+/// Resolve `href` against the directory of `page_file`, both `/`-separated.
+fn resolve_relative(page_file: &str, href: &str) -> String {
+    let mut segments: Vec<&str> = page_file.split('/').collect();
+    segments.pop();
+    for segment in href.split('/') {
+        match segment {
+            ".." => {
+                segments.pop();
+            }
+            "." | "" => {}
+            segment => segments.push(segment),
+        }
+    }
+    segments.join("/")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn text(line: &[Highlight]) -> String {
+        line.iter().map(|run| run.text.as_str()).collect()
+    }
+
+    #[test]
+    fn test_source_link() {
+        let html = r#"<h1>Struct <span>Point</span></h1><a class="src" href="../src/fixture_crate/lib.rs.html#4-9">Source</a><h2 id="implementations">Implementations</h2><section id="method.manhattan" class="method"><a class="src rightside" href="../src/fixture_crate/lib.rs.html#13-15">Source</a><h4 class="code-header">pub fn manhattan(&amp;self) -&gt; i32</h4></section><section id="method.type_id" class="method"><a class="src rightside" href="https://doc.rust-lang.org/1.95.0/src/core/any.rs.html#141">Source</a></section>"#;
+        let page = "fixture_crate/struct.Point.html";
+
+        assert_eq!(
+            source_link(html, page, None),
+            Some(SourceLink {
+                file: "src/fixture_crate/lib.rs.html".to_string(),
+                lines: 4..=9,
+            })
+        );
+        assert_eq!(
+            source_link(html, page, Some("method.manhattan")).map(|link| link.lines),
+            Some(13..=15)
+        );
+        let external = source_link(html, page, Some("method.type_id")).unwrap();
+        assert!(external.is_external());
+        assert_eq!(external.lines, 141..=141);
+        assert_eq!(source_link(html, page, Some("method.missing")), None);
+        // Older rustdoc
+        assert_eq!(
+            source_link(
+                r#"<a class="srclink" href="../../src/serde/de/mod.rs.html#10">source</a>"#,
+                "serde/de/trait.Deserialize.html",
+                None
+            )
+            .map(|link| link.file),
+            Some("src/serde/de/mod.rs.html".to_string())
+        );
+    }
+
+    #[test]
+    fn test_source_lines() {
+        // src/fixture_crate/lib.rs.html from rustdoc 1.95, where a span
+        // carries on across lines
+        let html = r#"<div class="example-wrap digits-1"><pre class="rust"><code><a href=#1 id=1 data-nosnippet>1</a><span class="doccomment">/// A point on a plane.
+<a href=#2 id=2 data-nosnippet>2</a></span><span class="kw">pub struct </span>Point&lt;T&gt; {
+<a href=#3 id=3 data-nosnippet>3</a>    x: <span class="string">"&amp;"</span>,
+</code></pre></div>"#;
+
+        let lines = source_lines(html);
+        assert_eq!(lines.len(), 3);
+        assert_eq!(text(&lines[0]), "/// A point on a plane.");
+        assert_eq!(lines[0][0].class.as_deref(), Some("doccomment"));
+        assert_eq!(text(&lines[1]), "pub struct Point<T> {");
+        assert_eq!(lines[1][0].class.as_deref(), Some("kw"));
+        assert_eq!(lines[1][1].class, None);
+        assert_eq!(text(&lines[2]), "    x: \"&\",");
+
+        // Older rustdoc numbered lines in a separate block
+        let html = r##"<pre class="src-line-numbers"><a href="#1" id="1">1</a>
+<a href="#2" id="2">2</a></pre><pre class="rust"><code><span class="kw">fn </span>main() {
+}
+</code></pre>"##;
+        let lines = source_lines(html);
+        assert_eq!(lines.len(), 2);
+        assert_eq!(text(&lines[0]), "fn main() {");
+        assert_eq!(text(&lines[1]), "}");
+    }
+}
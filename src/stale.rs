@@ -0,0 +1,312 @@
+// Spotting crate docs left behind by older versions of a project's dependencies
+
+use crate::crate_graph::library_name;
+use crate::lockfile::LockedPackage;
+use serde_json::Value;
+use std::collections::HashSet;
+use std::fmt;
+
+/// Crates documented with the toolchain rather than from Cargo.lock
+pub const SYSROOT_CRATES: &[&str] = &["std", "core", "alloc", "proc_macro", "test"];
+
+/// A crate whose docs don't match any version of it in Cargo.lock.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StaleCrate {
+    pub name: String,
+    /// The version its docs were built from, if the page says
+    pub documented: Option<String>,
+    /// The versions Cargo.lock has; empty when it's no longer a dependency
+    pub locked: Vec<String>,
+}
+
+impl fmt::Display for StaleCrate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.locked.is_empty() {
+            return write!(f, "{} (not in Cargo.lock)", self.name);
+        }
+        write!(
+            f,
+            "{} (documented {}, Cargo.lock has {})",
+            self.name,
+            self.documented.as_deref().unwrap_or("?"),
+            self.locked.join(" and ")
+        )
+    }
+}
+
+/// The libraries of the packages in Cargo.lock, under the names rustdoc
+/// documents them as.
+#[derive(Debug, Default)]
+pub struct LockedLibraries {
+    /// Each library's crate name and locked version
+    libraries: Vec<(String, String)>,
+    /// Whether some locked package was missing from the metadata, so a crate
+    /// not among `libraries` may still be one of them
+    incomplete: bool,
+}
+
+impl LockedLibraries {
+    /// The libraries of `locked`, named from the same project's `cargo
+    /// metadata` output: a library can be named anything, not just after its
+    /// package. Packages without a library are left out.
+    pub fn new(locked: &[LockedPackage], metadata: &Value) -> Self {
+        let packages = metadata["packages"]
+            .as_array()
+            .map_or(&[][..], Vec::as_slice);
+        let mut libraries = LockedLibraries::default();
+        for package in locked {
+            let Some(metadata) = packages.iter().find(|metadata| {
+                metadata["name"] == package.name.as_str()
+                    && metadata["version"] == package.version.as_str()
+            }) else {
+                libraries.incomplete = true;
+                continue;
+            };
+            if let Some(name) = library_name(metadata) {
+                libraries.libraries.push((name, package.version.clone()));
+            }
+        }
+        libraries
+    }
+
+    /// The locked versions of the library named `crate_name`.
+    fn versions(&self, crate_name: &str) -> Vec<String> {
+        self.libraries
+            .iter()
+            .filter(|(name, _)| name == crate_name)
+            .map(|(_, version)| version.clone())
+            .collect()
+    }
+}
+
+/// The crate names in a `crates.js` (`window.ALL_CRATES = ["serde", ...];`).
+pub fn all_crates(crates_js: &str) -> Option<Vec<String>> {
+    let start = crates_js.find('[')?;
+    let end = start + crates_js[start..].find(']')?;
+    serde_json::from_str(&crates_js[start..=end]).ok()
+}
+
+/// The crate version on a crate's `index.html`, from the sidebar
+/// (`<span class="version">1.0.219</span>`, or `<p>Version 1.0.219</p>` in
+/// older rustdoc).
+pub fn documented_version(index_html: &str) -> Option<&str> {
+    let start = match index_html.find("class=\"version\">") {
+        Some(start) => start + "class=\"version\">".len(),
+        None => index_html.find("<p>Version ")? + "<p>Version ".len(),
+    };
+    let end = start + index_html[start..].find('<')?;
+    let version = index_html[start..end].trim();
+    (!version.is_empty()).then_some(version)
+}
+
+/// The crates among `documented` (each crate's name and documented version)
+/// whose docs are from a version Cargo.lock doesn't have, or from a package
+/// that's gone from it.
+///
+/// Crates without a known version are only stale if they're gone, and none are
+/// gone while some locked package couldn't be named; the toolchain's own crates
+/// never are.
+pub fn stale_crates<'a>(
+    documented: impl IntoIterator<Item = (&'a str, Option<&'a str>)>,
+    locked: &LockedLibraries,
+) -> Vec<StaleCrate> {
+    let mut stale = Vec::new();
+    for (name, version) in documented {
+        if SYSROOT_CRATES.contains(&name) {
+            continue;
+        }
+        let versions = locked.versions(name);
+        if versions.is_empty() && locked.incomplete {
+            continue;
+        }
+        let missing = match version {
+            Some(version) => !versions.iter().any(|locked| locked == version),
+            None => versions.is_empty(),
+        };
+        if missing {
+            stale.push(StaleCrate {
+                name: name.to_string(),
+                documented: version.map(str::to_string),
+                locked: versions,
+            });
+        }
+    }
+    stale
+}
+
+/// A file rustdoc shares between crates (`search-index.js` or `crates.js`)
+/// without the entries of the crates in `names`.
+///
+/// Rustdoc ends these files with a comment giving where their entries start
+/// and each one's length, which it reads back when merging in more crates; this
+/// keeps that in step. Returns `None` for files without it (from older
+/// rustdoc), whose entries can't be split reliably.
+pub fn remove_crates(shared_js: &str, names: &HashSet<&str>) -> Option<String> {
+    let content = shared_js.trim_end();
+    let trailer_start = content.rfind("\n//")?;
+    let trailer: serde_json::Value = serde_json::from_str(&content[trailer_start + 3..]).ok()?;
+    let start = usize::try_from(trailer["start"].as_u64()?).ok()?;
+
+    // Every entry but the first starts with the comma separating it
+    let mut entries = Vec::new();
+    let mut end = start;
+    for length in trailer["fragment_lengths"].as_array()? {
+        let length = usize::try_from(length.as_u64()?).ok()?;
+        let fragment = content.get(end..end + length)?;
+        end += length;
+        let entry = fragment.strip_prefix(',').unwrap_or(fragment);
+        // `["serde",{...}]` in the search index, `"serde"` in crates.js
+        let name = entry
+            .strip_prefix('[')
+            .unwrap_or(entry)
+            .strip_prefix('"')?
+            .split('"')
+            .next()?;
+        if !names.contains(name) {
+            entries.push(entry);
+        }
+    }
+    if end > trailer_start {
+        return None;
+    }
+
+    let lengths: Vec<String> = entries
+        .iter()
+        .enumerate()
+        .map(|(i, entry)| (entry.len() + usize::from(i > 0)).to_string())
+        .collect();
+    Some(format!(
+        "{}{}{}//{{\"start\":{},\"fragment_lengths\":[{}]}}{}",
+        &content[..start],
+        entries.join(","),
+        &content[end..trailer_start + 1],
+        start,
+        lengths.join(","),
+        &shared_js[content.len()..]
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::search_index::{extract_json_string, parse_search_index};
+    use serde_json::json;
+
+    fn package(name: &str, version: &str) -> LockedPackage {
+        LockedPackage {
+            name: name.to_string(),
+            version: version.to_string(),
+            source: None,
+        }
+    }
+
+    fn metadata_package(name: &str, version: &str, library: &str) -> Value {
+        json!({
+            "name": name,
+            "version": version,
+            "targets": [{ "name": library, "kind": ["lib"] }],
+        })
+    }
+
+    #[test]
+    fn test_documented_version() {
+        let html = r#"<h2><a href="../serde/index.html">serde</a><span class="version">1.0.219</span></h2>"#;
+        assert_eq!(documented_version(html), Some("1.0.219"));
+        let old = r#"<div class="block version"><p>Version 0.4.3</p></div>"#;
+        assert_eq!(documented_version(old), Some("0.4.3"));
+        assert_eq!(documented_version("<h2>serde</h2>"), None);
+    }
+
+    #[test]
+    fn test_stale_crates() {
+        let locked = [
+            package("serde-json", "1.0.1"),
+            package("rand", "0.7.3"),
+            package("rand", "0.8.5"),
+            package("foo-bar", "1.0.0"),
+        ];
+        let metadata = json!({ "packages": [
+            metadata_package("serde-json", "1.0.1", "serde_json"),
+            metadata_package("rand", "0.7.3", "rand"),
+            metadata_package("rand", "0.8.5", "rand"),
+            // A library named differently from its package
+            metadata_package("foo-bar", "1.0.0", "baz"),
+        ] });
+        let libraries = LockedLibraries::new(&locked, &metadata);
+        let documented = [
+            ("serde_json", Some("1.0.0")),
+            ("rand", Some("0.8.5")),
+            ("baz", Some("1.0.0")),
+            ("foo_bar", Some("1.0.0")),
+            ("either", None),
+            ("std", None),
+        ];
+        let stale = stale_crates(documented, &libraries);
+        let described: Vec<String> = stale.iter().map(ToString::to_string).collect();
+        assert_eq!(
+            described,
+            [
+                "serde_json (documented 1.0.0, Cargo.lock has 1.0.1)",
+                "foo_bar (not in Cargo.lock)",
+                "either (not in Cargo.lock)",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_stale_crates_unmapped() {
+        // Without foo-bar in the metadata, its library could be any of them
+        let locked = [package("rand", "0.8.5"), package("foo-bar", "1.0.0")];
+        let metadata = json!({ "packages": [metadata_package("rand", "0.8.5", "rand")] });
+        let libraries = LockedLibraries::new(&locked, &metadata);
+        let documented = [
+            ("rand", Some("0.7.3")),
+            ("baz", Some("1.0.0")),
+            ("either", None),
+        ];
+        let stale = stale_crates(documented, &libraries);
+        let names: Vec<&str> = stale.iter().map(|krate| krate.name.as_str()).collect();
+        assert_eq!(names, ["rand"]);
+    }
+
+    #[test]
+    fn test_remove_crates() {
+        let content = std::fs::read_to_string("tests/fixtures/search-index.js").unwrap();
+        assert_eq!(
+            remove_crates(&content, &HashSet::new()).as_deref(),
+            Some(content.as_str())
+        );
+
+        let removed = remove_crates(&content, &HashSet::from(["cfg_if", "either"])).unwrap();
+        let names: Vec<String> = parse_search_index(&extract_json_string(&removed).unwrap())
+            .unwrap()
+            .into_iter()
+            .map(|entry| entry.name)
+            .collect();
+        assert!(!names.contains(&"cfg_if".to_string()));
+        assert!(!names.contains(&"either".to_string()));
+        assert!(names.contains(&"serde".to_string()));
+        // The rewritten lengths still split the file, so it can be edited again
+        let again = remove_crates(&removed, &HashSet::from(["serde"])).unwrap();
+        assert!(!again.contains("[\"serde\","));
+
+        assert_eq!(
+            remove_crates("var searchIndex = [];", &HashSet::new()),
+            None
+        );
+    }
+
+    #[test]
+    fn test_crates_js() {
+        let crates_js =
+            "window.ALL_CRATES = [\"a\",\"b_c\"];\n//{\"start\":21,\"fragment_lengths\":[3,6]}";
+        assert_eq!(all_crates(crates_js).unwrap(), ["a", "b_c"]);
+
+        let removed = remove_crates(crates_js, &HashSet::from(["a"])).unwrap();
+        assert_eq!(
+            removed,
+            "window.ALL_CRATES = [\"b_c\"];\n//{\"start\":21,\"fragment_lengths\":[5]}"
+        );
+        assert_eq!(all_crates(&removed).unwrap(), ["b_c"]);
+    }
+}
@@ -0,0 +1,156 @@
+// Public API size statistics and comparisons between snapshots
+
+use crate::search_index::{ITEM_TYPES, ItemType};
+use crate::search_items::SearchItem;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Item counts per item type and per module.
+///
+/// Serialized as JSON, this is the snapshot format `rdoc stats --compare` reads.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ApiStats {
+    /// Total number of items
+    pub total: usize,
+    /// Items per item type, keyed by the type's name (e.g. "struct")
+    pub by_type: BTreeMap<String, usize>,
+    /// Items per module path (e.g. "serde::de")
+    pub by_module: BTreeMap<String, usize>,
+}
+
+impl ApiStats {
+    /// Count items by type and module.
+    pub fn of<'a>(items: impl IntoIterator<Item = &'a SearchItem>) -> Self {
+        let mut stats = Self::default();
+        for item in items {
+            stats.total += 1;
+            *stats.by_type.entry(item.item_type.to_string()).or_default() += 1;
+            *stats.by_module.entry(item.path.clone()).or_default() += 1;
+        }
+        stats
+    }
+
+    /// Compare against an earlier snapshot.
+    pub fn compare(&self, before: &ApiStats) -> StatsComparison {
+        let before_by_type = before
+            .by_type
+            .iter()
+            .map(|(name, count)| (current_type_name(name), *count))
+            .collect();
+        StatsComparison {
+            total: Change::new(before.total, self.total),
+            by_type: compare_counts(&before_by_type, &self.by_type),
+            by_module: compare_counts(&before.by_module, &self.by_module),
+        }
+    }
+}
+
+/// `name` as item types are named now. Snapshots from schema version 1 named
+/// them like the Rust enum's variants (e.g. "Struct", "AssocType").
+fn current_type_name(name: &str) -> String {
+    ITEM_TYPES
+        .iter()
+        .find(|item_type| format!("{:?}", item_type) == name)
+        .map_or_else(|| name.to_string(), ItemType::to_string)
+}
+
+/// How a count changed between two snapshots.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct Change {
+    pub before: usize,
+    pub after: usize,
+    /// `after - before`; negative when the API shrank
+    pub change: i64,
+}
+
+impl Change {
+    fn new(before: usize, after: usize) -> Self {
+        Self {
+            before,
+            after,
+            change: after as i64 - before as i64,
+        }
+    }
+}
+
+/// Differences between two [`ApiStats`] snapshots.
+///
+/// Keys present in only one snapshot count as zero in the other.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct StatsComparison {
+    pub total: Change,
+    pub by_type: BTreeMap<String, Change>,
+    pub by_module: BTreeMap<String, Change>,
+}
+
+fn compare_counts(
+    before: &BTreeMap<String, usize>,
+    after: &BTreeMap<String, usize>,
+) -> BTreeMap<String, Change> {
+    before
+        .keys()
+        .chain(after.keys())
+        .map(|key| {
+            let change = Change::new(
+                before.get(key).copied().unwrap_or(0),
+                after.get(key).copied().unwrap_or(0),
+            );
+            (key.clone(), change)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn stats(by_type: &[(&str, usize)], by_module: &[(&str, usize)]) -> ApiStats {
+        let to_map = |counts: &[(&str, usize)]| {
+            counts
+                .iter()
+                .map(|&(key, count)| (key.to_string(), count))
+                .collect::<BTreeMap<_, _>>()
+        };
+        ApiStats {
+            total: by_type.iter().map(|(_, count)| count).sum(),
+            by_type: to_map(by_type),
+            by_module: to_map(by_module),
+        }
+    }
+
+    #[test]
+    fn test_compare_stats() {
+        let before = stats(
+            &[("struct", 3), ("fn", 5)],
+            &[("mylib", 6), ("mylib::old", 2)],
+        );
+        let after = stats(&[("struct", 4), ("fn", 2), ("trait", 1)], &[("mylib", 7)]);
+
+        let comparison = after.compare(&before);
+
+        assert_eq!(comparison.total, Change::new(8, 7));
+        assert_eq!(comparison.by_type["struct"].change, 1);
+        assert_eq!(comparison.by_type["fn"].change, -3);
+        assert_eq!(comparison.by_type["trait"], Change::new(0, 1));
+        assert_eq!(comparison.by_module["mylib::old"], Change::new(2, 0));
+    }
+
+    #[test]
+    fn test_compare_with_version_1_snapshot() {
+        let before = stats(&[("Struct", 3), ("AssocType", 1)], &[("mylib", 4)]);
+        let after = stats(&[("struct", 3), ("associatedtype", 2)], &[("mylib", 5)]);
+
+        let comparison = after.compare(&before);
+
+        assert_eq!(comparison.by_type["struct"], Change::new(3, 3));
+        assert_eq!(comparison.by_type["associatedtype"].change, 1);
+        assert!(!comparison.by_type.contains_key("Struct"));
+    }
+
+    #[test]
+    fn test_stats_round_trip_as_json() {
+        let original = stats(&[("struct", 3)], &[("mylib", 3)]);
+        let json = serde_json::to_string(&original).unwrap();
+        assert_eq!(serde_json::from_str::<ApiStats>(&json).unwrap(), original);
+    }
+}
@@ -0,0 +1,204 @@
+// The `std` paths of items defined in `core` and `alloc`
+
+use crate::search_index::ItemType;
+use crate::search_items::SearchItem;
+use std::collections::HashMap;
+
+/// The crates `std` re-exports from, for the `std` docs' own items
+const FACADE_CRATES: &[&str] = &["core", "alloc"];
+
+/// Modules of `core` and `alloc` that `std` re-exports under the same name,
+/// for items the `std` index has no re-export of (methods, fields, items of
+/// modules it re-exports whole).
+const STD_MODULES: &[&str] = &[
+    "alloc",
+    "any",
+    "arch",
+    "array",
+    "ascii",
+    "borrow",
+    "boxed",
+    "cell",
+    "char",
+    "clone",
+    "cmp",
+    "collections",
+    "convert",
+    "default",
+    "error",
+    "f32",
+    "f64",
+    "ffi",
+    "fmt",
+    "future",
+    "hash",
+    "hint",
+    "i8",
+    "i16",
+    "i32",
+    "i64",
+    "i128",
+    "isize",
+    "iter",
+    "marker",
+    "mem",
+    "net",
+    "num",
+    "ops",
+    "option",
+    "panic",
+    "pin",
+    "prelude",
+    "primitive",
+    "ptr",
+    "rc",
+    "result",
+    "slice",
+    "str",
+    "string",
+    "sync",
+    "task",
+    "time",
+    "u8",
+    "u16",
+    "u32",
+    "u64",
+    "u128",
+    "usize",
+    "vec",
+];
+
+/// Where items defined in `core` and `alloc` are re-exported in `std`, the
+/// path users write.
+///
+/// The paths come from the `std` index's own re-exports where it has one,
+/// otherwise from the modules `std` is known to re-export whole.
+#[derive(Debug, Default)]
+pub struct StdPaths {
+    /// `std` module paths, by defining module path, name and type
+    reexports: HashMap<(String, String, ItemType), String>,
+}
+
+impl StdPaths {
+    /// The `std` paths of `items`' `core` and `alloc` items, or `None` when
+    /// `items` don't include the `std` docs.
+    pub fn new<'a>(items: impl IntoIterator<Item = &'a SearchItem>) -> Option<Self> {
+        let mut has_std = false;
+        let mut reexports = HashMap::new();
+        for item in items.into_iter().filter(|item| item.crate_name == "std") {
+            has_std = true;
+            if item.parent_name().is_none() && is_facade_path(&item.exact_path) {
+                reexports
+                    .entry((item.exact_path.clone(), item.name.clone(), item.item_type))
+                    .or_insert_with(|| item.path.clone());
+            }
+        }
+        has_std.then_some(Self { reexports })
+    }
+
+    /// The `std` module path `item` is at, e.g. `std::option` for
+    /// `core::option::Option` or its methods, or `None` if it isn't a `core`
+    /// or `alloc` item `std` re-exports.
+    pub fn module_path(&self, item: &SearchItem) -> Option<String> {
+        if item.crate_name == "std" {
+            return None;
+        }
+        // A member is wherever its type is
+        let (name, item_type) = match item.parent() {
+            Some(parent) => (&parent.name, parent.item_type),
+            None => (&item.name, item.item_type),
+        };
+        let paths = [&item.exact_path, &item.path];
+        let mut paths = paths.into_iter().filter(|path| is_facade_path(path));
+        let defined = paths.clone().next()?;
+        if let Some(path) =
+            paths.find_map(|path| self.reexports.get(&(path.clone(), name.clone(), item_type)))
+        {
+            return Some(path.clone());
+        }
+        let (_, rest) = defined.split_once("::")?;
+        let module = rest.split("::").next()?;
+        STD_MODULES
+            .contains(&module)
+            .then(|| format!("std::{}", rest))
+    }
+}
+
+fn is_facade_path(path: &str) -> bool {
+    let root = path.split("::").next().unwrap_or_default();
+    FACADE_CRATES.contains(&root)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::search_items::{Parent, Parents};
+
+    fn item(crate_name: &str, path: &str, exact_path: &str, name: &str) -> SearchItem {
+        SearchItem::builder(crate_name, ItemType::Struct, name)
+            .path(path)
+            .exact_path(exact_path)
+            .build()
+    }
+
+    #[test]
+    fn test_module_path() {
+        let core_only = [item("core", "core::cell", "core::cell", "RefCell")];
+        assert!(StdPaths::new(&core_only).is_none());
+
+        let items = [
+            // `std::collections::HashMap` is std's own; `BTreeMap` comes from alloc
+            item(
+                "std",
+                "std::collections",
+                "std::collections::hash::map",
+                "HashMap",
+            ),
+            item(
+                "std",
+                "std::collections",
+                "alloc::collections::btree::map",
+                "BTreeMap",
+            ),
+            item(
+                "alloc",
+                "alloc::collections::btree_map",
+                "alloc::collections::btree::map",
+                "BTreeMap",
+            ),
+            item("core", "core::cell", "core::cell", "RefCell"),
+            item("core", "core::intrinsics", "core::intrinsics", "transmute"),
+        ];
+        let std_paths = StdPaths::new(&items).unwrap();
+        assert_eq!(std_paths.module_path(&items[0]), None);
+        assert_eq!(std_paths.module_path(&items[1]), None);
+        // From the std index, which re-exports it from a private module
+        assert_eq!(
+            std_paths.module_path(&items[2]).as_deref(),
+            Some("std::collections")
+        );
+        // From the modules std re-exports whole
+        assert_eq!(
+            std_paths.module_path(&items[3]).as_deref(),
+            Some("std::cell")
+        );
+        assert_eq!(std_paths.module_path(&items[4]), None);
+
+        let mut method = item(
+            "alloc",
+            "alloc::collections::btree_map",
+            "alloc::collections::btree::map",
+            "insert",
+        );
+        method.item_type = ItemType::Method;
+        method.parent_index = Some(0);
+        method.parents = Parents::new(vec![Parent {
+            name: "BTreeMap".to_string(),
+            item_type: ItemType::Struct,
+        }]);
+        assert_eq!(
+            std_paths.module_path(&method).as_deref(),
+            Some("std::collections")
+        );
+    }
+}
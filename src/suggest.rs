@@ -0,0 +1,183 @@
+// Suggesting the dependency to add for a symbol the project's docs don't have
+
+use crate::resolve::disambiguated_path;
+use crate::search_items::SearchItem;
+
+/// Items people often look for before depending on the crate providing them,
+/// by name, for symbols no cached docs have
+const COMMON_SYMBOLS: &[(&str, &str)] = &[
+    ("Regex", "regex"),
+    ("RegexSet", "regex"),
+    ("Serialize", "serde"),
+    ("Deserialize", "serde"),
+    ("json", "serde_json"),
+    ("to_string_pretty", "serde_json"),
+    ("Lazy", "once_cell"),
+    ("OnceCell", "once_cell"),
+    ("lazy_static", "lazy_static"),
+    ("DateTime", "chrono"),
+    ("NaiveDate", "chrono"),
+    ("Utc", "chrono"),
+    ("OffsetDateTime", "time"),
+    ("Uuid", "uuid"),
+    ("Url", "url"),
+    ("Parser", "clap"),
+    ("ValueEnum", "clap"),
+    ("anyhow", "anyhow"),
+    ("bail", "anyhow"),
+    ("Context", "anyhow"),
+    ("Rng", "rand"),
+    ("thread_rng", "rand"),
+    ("Bytes", "bytes"),
+    ("BytesMut", "bytes"),
+    ("Runtime", "tokio"),
+    ("JoinHandle", "tokio"),
+    ("join_all", "futures"),
+    ("StreamExt", "futures"),
+    ("FutureExt", "futures"),
+    ("async_trait", "async-trait"),
+    ("Itertools", "itertools"),
+    ("IndexMap", "indexmap"),
+    ("IndexSet", "indexmap"),
+    ("SmallVec", "smallvec"),
+    ("ArrayVec", "arrayvec"),
+    ("DashMap", "dashmap"),
+    ("ParallelIterator", "rayon"),
+    ("par_iter", "rayon"),
+    ("instrument", "tracing"),
+    ("Subscriber", "tracing-subscriber"),
+    ("Router", "axum"),
+    ("HeaderMap", "http"),
+    ("StatusCode", "http"),
+    ("Sha256", "sha2"),
+    ("Decimal", "rust_decimal"),
+    ("BigInt", "num-bigint"),
+    ("TempDir", "tempfile"),
+    ("NamedTempFile", "tempfile"),
+    ("WalkDir", "walkdir"),
+    ("Version", "semver"),
+    ("Bump", "bumpalo"),
+    ("Mmap", "memmap2"),
+    ("Error", "thiserror"),
+];
+
+/// Crates that come with Rust rather than from crates.io
+const TOOLCHAIN_CRATES: &[&str] = &["std", "core", "alloc", "proc_macro", "test"];
+
+/// A crate to add for a symbol.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Suggestion {
+    /// The crate's name, as `cargo add` takes it
+    pub crate_name: String,
+    /// What the crate has that's named like the symbol: the item's
+    /// disambiguated path in docs cached for it, or `None` when the crate is
+    /// only known for providing something named so
+    pub item: Option<String>,
+}
+
+/// Crates to add for `symbol`, a query that found nothing: those whose items
+/// in `cached_items`, decoded from docs cached for other crates, are named
+/// like it, then those commonly used for it. Crates in `documented` (the
+/// project's own docs have them) and ones that come with Rust are left out.
+pub fn suggest_dependencies(
+    symbol: &str,
+    cached_items: &[SearchItem],
+    documented: &[&str],
+) -> Vec<Suggestion> {
+    let name = symbol_name(symbol);
+    let from_docs = cached_items
+        .iter()
+        .filter(|item| item.name.eq_ignore_ascii_case(name))
+        .map(|item| Suggestion {
+            crate_name: item.crate_name.clone(),
+            item: Some(disambiguated_path(item)),
+        });
+    let common = COMMON_SYMBOLS
+        .iter()
+        .filter(|(common, _)| common.eq_ignore_ascii_case(name))
+        .map(|(_, crate_name)| Suggestion {
+            crate_name: crate_name.to_string(),
+            item: None,
+        });
+    // A path's first segment may name the crate itself, e.g. `regex::Regex`
+    let named = symbol
+        .split_once("::")
+        .map(|(crate_name, _)| crate_name.trim())
+        .filter(|crate_name| is_crate_name(crate_name))
+        .map(|crate_name| Suggestion {
+            crate_name: crate_name.to_string(),
+            item: None,
+        });
+
+    let mut suggestions: Vec<Suggestion> = Vec::new();
+    for suggestion in from_docs.chain(named).chain(common) {
+        let known = |crate_name: &str| same_crate(crate_name, &suggestion.crate_name);
+        if TOOLCHAIN_CRATES.iter().any(|toolchain| known(toolchain))
+            || documented.iter().any(|documented| known(documented))
+            || suggestions.iter().any(|found| known(&found.crate_name))
+        {
+            continue;
+        }
+        suggestions.push(suggestion);
+    }
+    suggestions
+}
+
+/// The name a query for `symbol` looks for: its last path segment, without a
+/// `kind@` prefix or a `!` or `()` suffix.
+pub fn symbol_name(symbol: &str) -> &str {
+    let symbol = symbol.split_once('@').map_or(symbol, |(_, path)| path);
+    let name = symbol.rsplit("::").next().unwrap_or(symbol).trim();
+    name.trim_end_matches("()").trim_end_matches('!')
+}
+
+fn is_crate_name(name: &str) -> bool {
+    !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '-'))
+}
+
+/// Whether two crate names are the same crate: crates.io treats `-` and `_`
+/// as the same, and rustdoc writes both as `_`.
+fn same_crate(a: &str, b: &str) -> bool {
+    a.len() == b.len()
+        && a.chars()
+            .zip(b.chars())
+            .all(|(a, b)| a == b || matches!((a, b), ('-', '_') | ('_', '-')))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::search_index::ItemType;
+
+    #[test]
+    fn test_suggest_dependencies() {
+        let regex = SearchItem::builder("regex", ItemType::Struct, "Regex")
+            .source("https://docs.rs/regex/latest/")
+            .index(3)
+            .build();
+        let cached = [regex];
+
+        // Cached docs first, then the curated list, each crate once
+        assert_eq!(
+            suggest_dependencies("regex", &cached, &["serde"]),
+            [Suggestion {
+                crate_name: "regex".to_string(),
+                item: Some("struct@regex::Regex".to_string()),
+            }]
+        );
+        let suggestions = suggest_dependencies("async_trait!", &[], &[]);
+        assert_eq!(suggestions[0].crate_name, "async-trait");
+        assert_eq!(suggestions[0].item, None);
+        // Already a dependency, or part of Rust
+        assert!(suggest_dependencies("Regex", &cached, &["regex"]).is_empty());
+        assert!(suggest_dependencies("std::fs::read", &[], &[]).is_empty());
+        assert!(suggest_dependencies("async_trait", &[], &["async_trait"]).is_empty());
+        assert_eq!(
+            suggest_dependencies("indexmap::map::Entry", &[], &[])[0].crate_name,
+            "indexmap"
+        );
+    }
+}
@@ -0,0 +1,112 @@
+// Where the time answering a query goes, for `scan --timings`
+
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::time::Duration;
+
+/// How long each phase of answering one query took. Phases that didn't run
+/// are zero: decoding, for a query a running `rdoc serve` answered from docs
+/// it had decoded already.
+///
+/// In JSON each phase is a number of milliseconds, e.g. `"decode_ms": 41.7`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct Timings {
+    /// Finding the docs, generating them first if they're missing or stale
+    #[serde(rename = "locate_ms", with = "millis")]
+    pub locate: Duration,
+    /// Reading `search-index.js` and extracting the JSON in it
+    #[serde(rename = "extract_ms", with = "millis")]
+    pub extract: Duration,
+    /// Parsing the JSON into crate entries
+    #[serde(rename = "parse_ms", with = "millis")]
+    pub parse: Duration,
+    /// Decoding the crates' items
+    #[serde(rename = "decode_ms", with = "millis")]
+    pub decode: Duration,
+    /// Matching items against the query and filtering them
+    #[serde(rename = "match_ms", with = "millis")]
+    pub matching: Duration,
+    /// Ordering the matches by score and crate, and collapsing re-exports
+    #[serde(rename = "rank_ms", with = "millis")]
+    pub rank: Duration,
+    /// Writing the results; not known yet in the JSON the results are written as
+    #[serde(rename = "render_ms", with = "millis")]
+    pub render: Duration,
+}
+
+impl Timings {
+    /// Each phase's name and time, in the order they run.
+    pub fn phases(&self) -> [(&'static str, Duration); 7] {
+        [
+            ("locate", self.locate),
+            ("extract", self.extract),
+            ("parse", self.parse),
+            ("decode", self.decode),
+            ("match", self.matching),
+            ("rank", self.rank),
+            ("render", self.render),
+        ]
+    }
+
+    pub fn total(&self) -> Duration {
+        self.phases().iter().map(|(_, time)| *time).sum()
+    }
+}
+
+/// One line per phase, then the total, with times in milliseconds.
+impl fmt::Display for Timings {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (name, time) in self.phases() {
+            writeln!(f, "{:<8} {:>10.3} ms", name, time.as_secs_f64() * 1000.0)?;
+        }
+        write!(
+            f,
+            "{:<8} {:>10.3} ms",
+            "total",
+            self.total().as_secs_f64() * 1000.0
+        )
+    }
+}
+
+/// Durations as fractional milliseconds.
+mod millis {
+    use serde::{Deserialize, Deserializer, Serializer};
+    use std::time::Duration;
+
+    pub fn serialize<S: Serializer>(time: &Duration, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_f64(time.as_secs_f64() * 1000.0)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Duration, D::Error> {
+        let millis = f64::deserialize(deserializer)?;
+        Duration::try_from_secs_f64(millis / 1000.0).map_err(serde::de::Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_timings() {
+        let timings = Timings {
+            decode: Duration::from_millis(40),
+            matching: Duration::from_micros(2500),
+            ..Timings::default()
+        };
+        assert_eq!(timings.total(), Duration::from_micros(42_500));
+
+        let json = serde_json::to_value(timings).unwrap();
+        assert_eq!(json["decode_ms"], 40.0);
+        assert_eq!(json["match_ms"], 2.5);
+        assert_eq!(json["locate_ms"], 0.0);
+        let read: Timings = serde_json::from_value(json).unwrap();
+        assert_eq!(read, timings);
+
+        let table = timings.to_string();
+        assert_eq!(table.lines().count(), 8);
+        assert!(table.contains("decode       40.000 ms"), "{}", table);
+        assert!(table.ends_with("total        42.500 ms"), "{}", table);
+        assert!(serde_json::from_str::<Timings>(r#"{"decode_ms": -1}"#).is_err());
+    }
+}
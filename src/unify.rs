@@ -0,0 +1,283 @@
+// Unifying query type patterns with the types in a function signature
+
+use crate::normalize::fold_case;
+use crate::signature::{FunctionSignature, SignatureType, TypeKind, TypePattern};
+use std::collections::HashMap;
+
+/// How many times matching may look through a type to its generic arguments
+/// or a generic parameter to its bounds. rustdoc uses the same limit.
+const UNBOXING_LIMIT: usize = 5;
+
+/// Query generics bound so far, by name.
+type Bindings<'a> = HashMap<&'a str, &'a SignatureType>;
+
+/// Matches type patterns against one function's types, following rustdoc's
+/// type search:
+///
+/// - `_` matches any type.
+/// - A query generic such as `T` matches any type, but each use of `T` in a
+///   pattern must match the same type.
+/// - A named type matches a type with the same name (and path segments, if
+///   given) when each of its generic arguments matches a different one of the
+///   type's, in any order. `Vec` matches `Vec<u8>`, `&[u8]` matches `&mut [u8]`,
+///   but `&mut T` doesn't match `&T`.
+/// - Types marked for unboxing in the index, such as `Option` and references,
+///   can be looked through: `u32` matches `Option<u32>` and `&u32`.
+/// - The function's own generic parameters match a named type through their
+///   bounds: `Iterator` matches `I` in `fn f<I: Iterator>() -> I`.
+pub struct Unifier<'a> {
+    where_clause: &'a [Vec<SignatureType>],
+}
+
+impl<'a> Unifier<'a> {
+    pub fn new(signature: &'a FunctionSignature) -> Self {
+        Self {
+            where_clause: &signature.where_clause,
+        }
+    }
+
+    /// Whether `ty` matches `pattern`.
+    pub fn unify(&self, pattern: &'a TypePattern, ty: &'a SignatureType) -> bool {
+        self.unify_with(pattern, ty, &mut Bindings::new(), 0)
+    }
+
+    fn unify_with(
+        &self,
+        pattern: &'a TypePattern,
+        ty: &'a SignatureType,
+        bindings: &mut Bindings<'a>,
+        depth: usize,
+    ) -> bool {
+        let (name, path, generics) = match pattern {
+            TypePattern::Wildcard => return true,
+            TypePattern::Generic(generic) => {
+                return match bindings.get(generic.as_str()) {
+                    Some(&bound) => bound == ty,
+                    None => {
+                        bindings.insert(generic, ty);
+                        true
+                    }
+                };
+            }
+            TypePattern::Named {
+                name,
+                path,
+                generics,
+            } => (name, path, generics),
+        };
+
+        match &ty.kind {
+            TypeKind::Unknown => false,
+            TypeKind::Generic(index) => {
+                depth < UNBOXING_LIMIT
+                    && self
+                        .where_clause
+                        .get(*index)
+                        .into_iter()
+                        .flatten()
+                        .any(|bound| {
+                            attempt(bindings, |bindings| {
+                                self.unify_with(pattern, bound, bindings, depth + 1)
+                            })
+                        })
+            }
+            TypeKind::Named {
+                name: ty_name,
+                path: ty_path,
+                unbox,
+                ..
+            } => {
+                let same_type = fold_case(ty_name) == *name
+                    && path_contains(ty_path, path)
+                    && attempt(bindings, |bindings| {
+                        self.unify_generics(generics, &ty.generics, bindings, depth)
+                    });
+                same_type
+                    || (*unbox
+                        && depth < UNBOXING_LIMIT
+                        && ty.generics.iter().any(|inner| {
+                            attempt(bindings, |bindings| {
+                                self.unify_with(pattern, inner, bindings, depth + 1)
+                            })
+                        }))
+            }
+        }
+    }
+
+    /// Whether each pattern matches a different one of `types`, in any order.
+    fn unify_generics(
+        &self,
+        patterns: &'a [TypePattern],
+        types: &'a [SignatureType],
+        bindings: &mut Bindings<'a>,
+        depth: usize,
+    ) -> bool {
+        fn assign<'a>(
+            unifier: &Unifier<'a>,
+            patterns: &'a [TypePattern],
+            types: &'a [SignatureType],
+            used: &mut [bool],
+            bindings: &mut Bindings<'a>,
+            depth: usize,
+        ) -> bool {
+            let Some((pattern, rest)) = patterns.split_first() else {
+                return true;
+            };
+            for (index, ty) in types.iter().enumerate() {
+                if used[index] {
+                    continue;
+                }
+                used[index] = true;
+                let matched = attempt(bindings, |bindings| {
+                    unifier.unify_with(pattern, ty, bindings, depth)
+                        && assign(unifier, rest, types, used, bindings, depth)
+                });
+                if matched {
+                    return true;
+                }
+                used[index] = false;
+            }
+            false
+        }
+
+        patterns.len() <= types.len()
+            && assign(
+                self,
+                patterns,
+                types,
+                &mut vec![false; types.len()],
+                bindings,
+                depth,
+            )
+    }
+}
+
+/// Run `f`, undoing any bindings it made if it fails.
+fn attempt<'a>(bindings: &mut Bindings<'a>, f: impl FnOnce(&mut Bindings<'a>) -> bool) -> bool {
+    let saved = bindings.clone();
+    if f(bindings) {
+        return true;
+    }
+    *bindings = saved;
+    false
+}
+
+/// Whether `segments` appear in `path`, in order but not necessarily adjacent.
+fn path_contains(path: &str, segments: &[String]) -> bool {
+    let mut path_segments = path.split("::").map(fold_case);
+    segments
+        .iter()
+        .all(|segment| path_segments.any(|path_segment| path_segment == *segment))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::search_index::ItemType;
+
+    fn ty(name: &str, generics: Vec<SignatureType>) -> SignatureType {
+        SignatureType {
+            kind: TypeKind::Named {
+                name: name.to_string(),
+                item_type: ItemType::Struct,
+                path: match name {
+                    "Error" => "std::io::error",
+                    _ => "core",
+                }
+                .to_string(),
+                unbox: matches!(name, "Option" | "reference"),
+            },
+            generics,
+            bindings: vec![],
+        }
+    }
+
+    fn generic(index: usize) -> SignatureType {
+        SignatureType {
+            kind: TypeKind::Generic(index),
+            generics: vec![],
+            bindings: vec![],
+        }
+    }
+
+    fn signature(
+        output: SignatureType,
+        where_clause: Vec<Vec<SignatureType>>,
+    ) -> FunctionSignature {
+        FunctionSignature {
+            inputs: vec![],
+            output: vec![output],
+            where_clause,
+        }
+    }
+
+    fn returns(signature: &FunctionSignature, pattern: &str) -> bool {
+        signature.returns(&TypePattern::parse(pattern).unwrap())
+    }
+
+    #[test]
+    fn test_names_paths_and_generics() {
+        let io_result = signature(ty("Result", vec![generic(0), ty("Error", vec![])]), vec![]);
+
+        assert!(returns(&io_result, "Result<_, io::Error>"));
+        assert!(returns(&io_result, "result"));
+        assert!(returns(&io_result, "_"));
+        // Generic arguments match in any order
+        assert!(returns(&io_result, "Result<io::Error, T>"));
+        assert!(!returns(&io_result, "Result<_, fmt::Error>"));
+        assert!(!returns(&io_result, "Result<_, _, _>"));
+        // An unbounded generic parameter only matches wildcards
+        assert!(!returns(&io_result, "Result<String, io::Error>"));
+        assert!(!returns(&io_result, "Option"));
+    }
+
+    #[test]
+    fn test_query_generics_bind_consistently() {
+        let same = signature(
+            ty("tuple", vec![ty("u8", vec![]), ty("u8", vec![])]),
+            vec![],
+        );
+        let different = signature(
+            ty("tuple", vec![ty("u8", vec![]), ty("bool", vec![])]),
+            vec![],
+        );
+
+        assert!(returns(&same, "(T, T)"));
+        assert!(!returns(&different, "(T, T)"));
+        assert!(returns(&different, "(T, U)"));
+    }
+
+    #[test]
+    fn test_unboxing() {
+        let option = signature(ty("Option", vec![ty("u32", vec![])]), vec![]);
+        assert!(returns(&option, "u32"));
+        assert!(returns(&option, "Option<u32>"));
+
+        // Result isn't marked for unboxing
+        let result = signature(ty("Result", vec![ty("u32", vec![])]), vec![]);
+        assert!(!returns(&result, "u32"));
+
+        // fn f<I: Iterator<Item = u8>>() -> I
+        let iterator = signature(generic(0), vec![vec![ty("Iterator", vec![])]]);
+        assert!(returns(&iterator, "Iterator"));
+        assert!(!returns(&iterator, "Vec"));
+    }
+
+    #[test]
+    fn test_references_and_mutability() {
+        let mut_slice = ty(
+            "reference",
+            vec![ty("mut", vec![]), ty("slice", vec![ty("u8", vec![])])],
+        );
+        let shared_slice = ty("reference", vec![ty("slice", vec![ty("u8", vec![])])]);
+        let mut_ref = signature(mut_slice, vec![]);
+        let shared_ref = signature(shared_slice, vec![]);
+
+        assert!(returns(&mut_ref, "&mut [u8]"));
+        assert!(returns(&mut_ref, "&[u8]"));
+        assert!(!returns(&shared_ref, "&mut [u8]"));
+        // References unbox, so the referenced type matches on its own
+        assert!(returns(&shared_ref, "[u8]"));
+        assert_eq!(mut_ref.output[0].to_string(), "&mut [u8]");
+    }
+}
@@ -1,56 +1,170 @@
+use std::collections::VecDeque;
+
+/// Number of recently decoded values that can be referenced by a backreference.
+const BACKREF_QUEUE_LEN: usize = 16;
+
 /// VLQ (Variable-Length Quantity) hex decoder for rustdoc search index
 ///
 /// Decodes hex-encoded strings where each character contributes 4 bits.
 /// Characters with code < 96 are continuation bytes, >= 96 are terminal bytes.
+/// Characters `'0'..='?'` (48-63) are backreferences to one of the last 16
+/// decoded values, and `` ` `` (96) is shorthand for zero.
 pub struct VlqHexDecoder<'a> {
     string: &'a str,
     offset: usize,
+    backrefs: VecDeque<i32>,
 }
 
 impl<'a> VlqHexDecoder<'a> {
     pub fn new(string: &'a str) -> Self {
-        Self { string, offset: 0 }
+        Self {
+            string,
+            offset: 0,
+            backrefs: VecDeque::with_capacity(BACKREF_QUEUE_LEN),
+        }
+    }
+
+    /// Byte offset of the next value in the encoded string.
+    ///
+    /// Comparing the offset before and after a call to `next` gives the raw
+    /// bytes that encoded that value.
+    pub fn offset(&self) -> usize {
+        self.offset
     }
 
-    pub fn next(&mut self) -> Option<i32> {
-        if self.offset >= self.string.len() {
-            return None;
+    /// Decode a single number starting at the current offset.
+    fn decode(&mut self) -> i32 {
+        decode_number(self.string.as_bytes(), &mut self.offset)
+    }
+}
+
+/// Decode the number starting at `*offset`, advancing past it.
+fn decode_number(bytes: &[u8], offset: &mut usize) -> i32 {
+    let mut n = 0u32;
+
+    // Read hex digits until the terminal byte (char code >= 96)
+    while let Some(&c) = bytes.get(*offset) {
+        n = (n << 4) | (c as u32 & 15);
+        *offset += 1;
+        if c >= 96 {
+            break;
         }
+    }
+
+    // LSB is sign bit, rest is value
+    let sign = n & 1;
+    let value = (n >> 1) as i32;
+
+    if sign == 1 { -value } else { value }
+}
 
-        let c = self.string.as_bytes()[self.offset] as u32;
+impl Iterator for VlqHexDecoder<'_> {
+    type Item = i32;
 
-        // Decode a single VLQ value
-        let mut n = 0u32;
-        let mut current = c;
+    fn next(&mut self) -> Option<i32> {
+        let c = *self.string.as_bytes().get(self.offset)?;
 
-        // Read hex digits while char code < 96 (continuation bytes)
-        while current < 96 && self.offset < self.string.len() {
-            n = (n << 4) | (current & 15);
+        // Backreference to a recently decoded value
+        if (48..64).contains(&c) {
             self.offset += 1;
-            if self.offset < self.string.len() {
-                current = self.string.as_bytes()[self.offset] as u32;
-            } else {
+            // A dangling backreference only happens in malformed input; treat it as 0
+            return Some(self.backrefs.get((c - 48) as usize).copied().unwrap_or(0));
+        }
+
+        // '`' is shorthand for zero and is not added to the backreference queue
+        if c == 96 {
+            self.offset += 1;
+            return Some(0);
+        }
+
+        let value = self.decode();
+        self.backrefs.push_front(value);
+        self.backrefs.truncate(BACKREF_QUEUE_LEN);
+
+        Some(value)
+    }
+}
+
+/// A value from a VLQ string that can hold lists, such as the `f` field.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum VlqNode {
+    Number(i32),
+    /// Values between `{` and `}`
+    List(Vec<VlqNode>),
+}
+
+/// Like [`VlqHexDecoder`], but for strings whose values may be nested lists.
+///
+/// Backreferences repeat a whole recently decoded value, list or not.
+pub struct VlqListDecoder<'a> {
+    string: &'a str,
+    offset: usize,
+    backrefs: VecDeque<VlqNode>,
+}
+
+impl<'a> VlqListDecoder<'a> {
+    pub fn new(string: &'a str) -> Self {
+        Self {
+            string,
+            offset: 0,
+            backrefs: VecDeque::with_capacity(BACKREF_QUEUE_LEN),
+        }
+    }
+
+    fn decode(&mut self) -> VlqNode {
+        let bytes = self.string.as_bytes();
+        if bytes.get(self.offset) != Some(&b'{') {
+            return VlqNode::Number(decode_number(bytes, &mut self.offset));
+        }
+
+        self.offset += 1;
+        let mut values = Vec::new();
+        // A list left open by malformed input ends with the string
+        while let Some(&c) = bytes.get(self.offset) {
+            if c == b'}' {
+                self.offset += 1;
                 break;
             }
+            values.push(self.decode());
         }
+        VlqNode::List(values)
+    }
+}
+
+impl Iterator for VlqListDecoder<'_> {
+    type Item = VlqNode;
+
+    fn next(&mut self) -> Option<VlqNode> {
+        let c = *self.string.as_bytes().get(self.offset)?;
 
-        // Last byte (char code >= 96)
-        if current >= 96 && self.offset < self.string.len() {
-            n = (n << 4) | (current & 15);
+        if (48..64).contains(&c) {
             self.offset += 1;
+            return Some(
+                self.backrefs
+                    .get((c - 48) as usize)
+                    .cloned()
+                    .unwrap_or(VlqNode::Number(0)),
+            );
         }
 
-        // LSB is sign bit, rest is value
-        let sign = n & 1;
-        let value = (n >> 1) as i32;
+        if c == 96 {
+            self.offset += 1;
+            return Some(VlqNode::Number(0));
+        }
 
-        Some(if sign == 1 { -value } else { value })
+        let value = self.decode();
+        self.backrefs.push_front(value.clone());
+        self.backrefs.truncate(BACKREF_QUEUE_LEN);
+
+        Some(value)
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::encode::encode_vlq;
+    use proptest::prelude::*;
 
     #[test]
     fn test_decode_zero() {
@@ -79,4 +193,65 @@ mod tests {
         assert_eq!(decoder.next(), Some(0));
         assert_eq!(decoder.next(), None);
     }
+
+    #[test]
+    fn test_decode_multi_byte_value() {
+        // 'A' (65) is a continuation byte contributing 1, 'a' (97) terminates with 1:
+        // n = (1 << 4) | 1 = 17, sign = 1 (negative), value = 8
+        let mut decoder = VlqHexDecoder::new("Aa");
+        assert_eq!(decoder.next(), Some(-8));
+        assert_eq!(decoder.offset(), 2);
+        assert_eq!(decoder.next(), None);
+    }
+
+    #[test]
+    fn test_decode_backtick_is_zero() {
+        let mut decoder = VlqHexDecoder::new("`b`");
+        assert_eq!(decoder.next(), Some(0));
+        assert_eq!(decoder.next(), Some(1));
+        assert_eq!(decoder.next(), Some(0));
+        assert_eq!(decoder.next(), None);
+    }
+
+    #[test]
+    fn test_decode_backreferences() {
+        // 'b' = 1, 'd' = 2, then '0' refers to the most recent value (2) and
+        // '1' to the one before it (1). Backreferences aren't re-queued.
+        let values: Vec<_> = VlqHexDecoder::new("bd0110").collect();
+        assert_eq!(values, vec![1, 2, 2, 1, 1, 2]);
+    }
+
+    #[test]
+    fn test_backtick_is_not_queued_for_backreferences() {
+        let values: Vec<_> = VlqHexDecoder::new("d`0").collect();
+        assert_eq!(values, vec![2, 0, 2]);
+    }
+
+    proptest! {
+        #[test]
+        fn test_decode_reverses_encoding(
+            values in prop::collection::vec(prop_oneof![-20..20, any::<i32>().prop_map(|n| n / 2)], 0..60)
+        ) {
+            let encoded = encode_vlq(&values);
+            prop_assert_eq!(VlqHexDecoder::new(&encoded).collect::<Vec<_>>(), values);
+        }
+    }
+
+    #[test]
+    fn test_decode_lists() {
+        use VlqNode::*;
+
+        // A method signature from the fixture: ([[2, [1]]], 3), then a
+        // backreference to it and a zero
+        let values: Vec<_> = VlqListDecoder::new("{{{d{b}}}f}0`").collect();
+        let signature = List(vec![
+            List(vec![List(vec![Number(2), List(vec![Number(1)])])]),
+            Number(3),
+        ]);
+        assert_eq!(values, vec![signature.clone(), signature, Number(0)]);
+
+        // Unterminated lists end with the input
+        let values: Vec<_> = VlqListDecoder::new("{b{d").collect();
+        assert_eq!(values, vec![List(vec![Number(1), List(vec![Number(2)])])]);
+    }
 }
@@ -0,0 +1,61 @@
+//! One of each kind of item rdoc's integration tests look for.
+
+/// A point on a plane.
+pub struct Point {
+    /// Horizontal position
+    pub x: i32,
+    /// Vertical position
+    pub y: i32,
+}
+
+impl Point {
+    /// Distance from the origin, in taxicab geometry.
+    pub fn manhattan(&self) -> i32 {
+        self.x.abs() + self.y.abs()
+    }
+}
+
+/// Compass directions.
+pub enum Direction {
+    North,
+    South,
+}
+
+/// Things that can be drawn.
+pub trait Draw {
+    /// Draw to a string.
+    fn draw(&self) -> String;
+}
+
+/// Parse a point from `"x,y"`.
+pub fn parse_point(text: &str) -> Option<Point> {
+    let (x, y) = text.split_once(',')?;
+    Some(Point {
+        x: x.trim().parse().ok()?,
+        y: y.trim().parse().ok()?,
+    })
+}
+
+/// Use [`parse_point`] instead.
+#[deprecated]
+pub fn old_parse_point(text: &str) -> Option<Point> {
+    parse_point(text)
+}
+
+/// Build a [`Point`].
+#[macro_export]
+macro_rules! point {
+    ($x:expr, $y:expr) => {
+        $crate::Point { x: $x, y: $y }
+    };
+}
+
+pub mod shapes {
+    //! Shapes made of points.
+
+    /// A circle around a center point.
+    pub struct Circle {
+        pub center: crate::Point,
+        pub radius: u32,
+    }
+}
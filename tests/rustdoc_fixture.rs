@@ -0,0 +1,227 @@
+//! End-to-end tests against an index generated by the installed rustdoc.
+//!
+//! `tests/fixtures/search-index.js` is a snapshot from one toolchain, so it can't
+//! show when rustdoc changes its output. These tests run `cargo doc` on the tiny
+//! crate in `tests/fixture-crate/` and check the whole pipeline against the fresh
+//! index, so format drift shows up as a failure naming the toolchain.
+//!
+//! Newer rustdoc writes a `search.index/` directory rdoc can't read yet
+//! instead. With it the tests check that rdoc says so, and skip the rest with a
+//! message; point `RDOC_FIXTURE_RUSTDOC` at an older rustdoc to run them all:
+//!
+//! ```text
+//! RDOC_FIXTURE_RUSTDOC=$(rustup which --toolchain 1.90 rustdoc) \
+//!     cargo test --test rustdoc_fixture
+//! ```
+
+use rdoc::search_index::{ItemType, extract_json_string, parse_search_index_lazy};
+use rdoc::search_items::{SearchItem, decode_crate};
+use std::ffi::OsString;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output};
+use std::sync::OnceLock;
+
+const CRATE_NAME: &str = "fixture_crate";
+
+/// Where newer rustdoc writes its index instead of `search-index.js`
+const SEARCH_INDEX_DIR: &str = "search.index";
+
+/// What rustdoc wrote for the fixture crate.
+enum FixtureIndex {
+    /// A search-index.js, which rdoc reads
+    File(PathBuf),
+    /// Only the newer `search.index/` directory
+    Directory,
+}
+
+fn fixture_dir() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests")
+        .join("fixture-crate")
+}
+
+/// Target directory for the fixture's docs, kept apart from rdoc's own build
+/// and one per rustdoc, since cargo doesn't document again for another one.
+fn target_dir() -> PathBuf {
+    let version: String = rustdoc_version()
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '-' })
+        .collect();
+    Path::new(env!("CARGO_TARGET_TMPDIR")).join(format!("fixture-crate-{}", version))
+}
+
+/// The rustdoc documenting the fixture: `RDOC_FIXTURE_RUSTDOC`, or the
+/// toolchain's own.
+fn rustdoc() -> OsString {
+    std::env::var_os("RDOC_FIXTURE_RUSTDOC").unwrap_or_else(|| "rustdoc".into())
+}
+
+fn rustdoc_version() -> &'static str {
+    static VERSION: OnceLock<String> = OnceLock::new();
+    VERSION.get_or_init(|| {
+        Command::new(rustdoc())
+            .arg("--version")
+            .output()
+            .map(|output| String::from_utf8_lossy(&output.stdout).trim().to_string())
+            .unwrap_or_else(|_| "unknown rustdoc".to_string())
+    })
+}
+
+/// Document the fixture crate once per test run and say what index it got.
+fn fixture_index() -> &'static FixtureIndex {
+    static INDEX: OnceLock<FixtureIndex> = OnceLock::new();
+    INDEX.get_or_init(|| {
+        let cargo = std::env::var_os("CARGO").unwrap_or_else(|| "cargo".into());
+        let output = Command::new(cargo)
+            .args(["doc", "--offline", "--target-dir"])
+            .arg(target_dir())
+            .env("RUSTDOC", rustdoc())
+            .current_dir(fixture_dir())
+            .output()
+            .expect("Failed to run cargo doc");
+        assert!(
+            output.status.success(),
+            "cargo doc failed on the fixture crate:\n{}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+
+        let doc_dir = target_dir().join("doc");
+        let path = doc_dir.join("search-index.js");
+        if path.exists() {
+            return FixtureIndex::File(path);
+        }
+        assert!(
+            doc_dir.join(SEARCH_INDEX_DIR).is_dir(),
+            "{} wrote neither search-index.js nor {}/; rdoc doesn't understand this toolchain's doc layout",
+            rustdoc_version(),
+            SEARCH_INDEX_DIR
+        );
+        FixtureIndex::Directory
+    })
+}
+
+/// The fixture's search-index.js, or `None` (after saying so) if rustdoc
+/// wrote the newer layout and the test should be skipped.
+fn search_index_path() -> Option<&'static Path> {
+    match fixture_index() {
+        FixtureIndex::File(path) => Some(path),
+        FixtureIndex::Directory => {
+            eprintln!(
+                "skipping: {} writes a {}/ index rdoc can't read yet; set RDOC_FIXTURE_RUSTDOC to an older rustdoc",
+                rustdoc_version(),
+                SEARCH_INDEX_DIR
+            );
+            None
+        }
+    }
+}
+
+fn fixture_items(search_index_path: &Path) -> Vec<SearchItem> {
+    let content = std::fs::read_to_string(search_index_path).unwrap();
+    let json_string = extract_json_string(&content).unwrap();
+    let entries = parse_search_index_lazy(&json_string).unwrap_or_else(|err| {
+        panic!(
+            "{} wrote a search index rdoc can't parse: {}",
+            rustdoc_version(),
+            err
+        )
+    });
+    let entry = entries
+        .iter()
+        .find(|entry| entry.name == CRATE_NAME)
+        .expect("fixture crate missing from the index");
+    decode_crate(CRATE_NAME, &entry.data().unwrap())
+}
+
+fn find<'a>(items: &'a [SearchItem], name: &str) -> &'a SearchItem {
+    items
+        .iter()
+        .find(|item| item.name == name)
+        .unwrap_or_else(|| panic!("{} not decoded from the fixture index", name))
+}
+
+/// Run the rdoc binary in the fixture crate, pointed at its docs.
+fn rdoc(args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_rdoc"))
+        .args(["--quiet"])
+        .args(args)
+        .current_dir(fixture_dir())
+        .env("CARGO_TARGET_DIR", target_dir())
+        .env("RDOC_CACHE_DIR", target_dir().join("rdoc-cache"))
+        .output()
+        .expect("Failed to run rdoc")
+}
+
+#[test]
+fn test_decodes_items_from_real_rustdoc() {
+    let Some(search_index_path) = search_index_path() else {
+        return;
+    };
+    let items = fixture_items(search_index_path);
+
+    let point = find(&items, "Point");
+    assert_eq!(point.item_type, ItemType::Struct);
+    assert_eq!(point.path, CRATE_NAME);
+
+    let manhattan = find(&items, "manhattan");
+    assert_eq!(manhattan.item_type, ItemType::Method);
+    assert_eq!(manhattan.parent_name(), Some("Point"));
+
+    assert_eq!(find(&items, "Direction").item_type, ItemType::Enum);
+    assert_eq!(find(&items, "Draw").item_type, ItemType::Trait);
+    assert_eq!(find(&items, "draw").item_type, ItemType::TyMethod);
+    assert_eq!(find(&items, "point").item_type, ItemType::Macro);
+    assert_eq!(find(&items, "parse_point").item_type, ItemType::Function);
+    assert!(find(&items, "old_parse_point").deprecated);
+    assert!(!find(&items, "parse_point").deprecated);
+
+    let circle = find(&items, "Circle");
+    assert_eq!(circle.path, format!("{}::shapes", CRATE_NAME));
+}
+
+#[test]
+fn test_cli_against_real_rustdoc() {
+    if let FixtureIndex::Directory = fixture_index() {
+        // rdoc must name the layout rather than fail to find docs
+        let output = rdoc(&["scan", "parse_point"]);
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        assert!(!output.status.success());
+        assert!(
+            stderr.contains("search.index/ index, which rdoc can't read yet"),
+            "newer index layout not reported:\n{}",
+            stderr
+        );
+        return;
+    }
+
+    let output = rdoc(&["scan", "parse_point", "--format", "json"]);
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let results: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    let mut names: Vec<&str> = results["results"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .filter_map(|result| result["name"].as_str())
+        .collect();
+    names.sort();
+    assert_eq!(names, ["old_parse_point", "parse_point"]);
+
+    let output = rdoc(&["resolve", "fixture_crate::Point::manhattan"]);
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    let output = rdoc(&["show", "fixture_crate::parse_point"]);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(
+        stdout.contains("pub fn parse_point(text: &str) -> Option<Point>"),
+        "declaration not read from the generated page:\n{}",
+        stdout
+    );
+}
@@ -0,0 +1,98 @@
+// Run rustdoc's own search.js on a search-index.js and print what it builds, as JSON.
+//
+// Usage: node driver.js <search-index.js> <search.js> items
+//        node driver.js <search-index.js> <search.js> search <query>...
+//
+// `items` prints every item search.js decodes. `search` prints, for each
+// query, the name results search.js lists that contain the query, best first.
+//
+// Loads both scripts the way rustdoc's rustdoc-js tester does: search-index.js
+// exports `searchIndex` and search.js exports `initSearch` when run under Node.
+"use strict";
+
+const path = require("path");
+
+// rustdoc's `itemTypes`: the `t` codes of the index are positions in this list
+const itemTypes = [
+    "keyword",
+    "primitive",
+    "mod",
+    "externcrate",
+    "import",
+    "struct",
+    "enum",
+    "fn",
+    "type",
+    "static",
+    "trait",
+    "impl",
+    "tymethod",
+    "method",
+    "structfield",
+    "variant",
+    "macro",
+    "associatedtype",
+    "constant",
+    "associatedconstant",
+    "union",
+    "foreigntype",
+    "opaque",
+    "attr",
+    "derive",
+    "traitalias",
+];
+
+const [searchIndexJs, searchJs, mode, ...queries] = process.argv.slice(2);
+if (!searchIndexJs || !searchJs || !["items", "search"].includes(mode)) {
+    console.error("usage: node driver.js <search-index.js> <search.js> items|search [query...]");
+    process.exit(2);
+}
+
+// search.js reads descriptions through the page's search state; items are
+// all this needs, so descriptions are never loaded
+globalThis.searchState = {
+    descShards: new Map(),
+    loadDesc: async () => null,
+};
+
+const { searchIndex } = require(path.resolve(searchIndexJs));
+const searchModule = require(path.resolve(searchJs));
+searchModule.initSearch(searchIndex);
+const { docSearch, parseQuery } = searchModule;
+
+// bitIndex 0 is the row search.js adds for each crate itself, and aliases
+// (`#[doc(alias)]`) get rows of their own pointing at the aliased item
+const isItem = row => row.bitIndex > 0 && !row.is_alias;
+
+async function main() {
+    if (mode === "items") {
+        return docSearch.searchIndex.filter(isItem).map(row => ({
+            crate: row.crate,
+            index: row.bitIndex - 1,
+            name: row.name,
+            type: itemTypes[row.ty],
+            path: row.path,
+            exact_path: row.exactPath,
+            parent: row.parent ? row.parent.name : null,
+        }));
+    }
+
+    const results = {};
+    for (const query of queries) {
+        const { others } = await docSearch.execQuery(parseQuery(query), null, null);
+        results[query] = others
+            // `index` is -1 for names within an edit distance of the query
+            // without containing it, which rdoc doesn't match
+            .filter(result => isItem(result) && result.index >= 0)
+            .map(result => ({ crate: result.crate, index: result.bitIndex - 1 }));
+    }
+    return results;
+}
+
+main().then(
+    output => process.stdout.write(JSON.stringify(output)),
+    error => {
+        console.error(error);
+        process.exit(1);
+    },
+);
@@ -0,0 +1,232 @@
+//! Differential tests against rustdoc's own `search.js`.
+//!
+//! rdoc reimplements how the browser decodes `search-index.js` and orders
+//! search results. These tests run rustdoc's JavaScript on the same fixture
+//! under Node and compare every item it builds, and the results it lists for
+//! a few queries, with rdoc's, so a change in how either side reads the format
+//! or ranks matches shows up as a list of differences.
+//!
+//! They use the `search.js` of the toolchain that wrote
+//! `tests/fixtures/search-index.js`, vendored in `tests/search-js/`; set
+//! `RDOC_SEARCH_JS` to try another one. `RDOC_NODE` picks the Node binary
+//! (default `node`); without Node the tests are skipped with a message.
+
+use rdoc::matcher::{CaseMode, Matcher, RustdocRank, TermMatcher};
+use rdoc::pages::type_slug;
+use rdoc::search_index::{extract_json_string, parse_search_index};
+use rdoc::search_items::{SearchItem, decode_crate};
+use serde::Deserialize;
+use serde::de::DeserializeOwned;
+use std::collections::{HashMap, HashSet};
+use std::ffi::OsString;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// Most differences to list before giving up.
+const MAX_REPORTED: usize = 20;
+
+/// Queries whose results are compared: short and long names, with and
+/// without underscores, matching across many crates.
+const QUERIES: &[&str] = &[
+    "map",
+    "from_str",
+    "value",
+    "iter",
+    "intoiter",
+    "deserialize",
+];
+
+/// One item as rustdoc's search.js builds it, printed by `driver.js items`.
+#[derive(Debug, Deserialize)]
+struct JsItem {
+    #[serde(rename = "crate")]
+    crate_name: String,
+    /// Position in the crate's compact data, like `ItemId::index`
+    index: usize,
+    name: String,
+    /// rustdoc's name for the item type, e.g. `struct` or `associatedconstant`
+    #[serde(rename = "type")]
+    item_type: Option<String>,
+    path: String,
+    exact_path: String,
+    parent: Option<String>,
+}
+
+/// One search result as search.js lists it, printed by `driver.js search`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Deserialize)]
+struct JsResult {
+    #[serde(rename = "crate")]
+    crate_name: String,
+    index: usize,
+}
+
+fn fixture_path() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/search-index.js")
+}
+
+/// The Node binary to run search.js with, or `None` (after saying so) if
+/// there's none and the test should be skipped.
+fn node() -> Option<OsString> {
+    if let Some(node) = std::env::var_os("RDOC_NODE") {
+        return Some(node);
+    }
+    match Command::new("node").arg("--version").output() {
+        Ok(output) if output.status.success() => Some("node".into()),
+        _ => {
+            eprintln!("skipping: node isn't installed (set RDOC_NODE to use another binary)");
+            None
+        }
+    }
+}
+
+/// Run `driver.js` in `mode` on the fixture and parse what it prints.
+fn run_driver<T: DeserializeOwned>(node: &OsString, mode: &str, queries: &[&str]) -> T {
+    let search_js = std::env::var_os("RDOC_SEARCH_JS").map_or_else(
+        || Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/search-js/search-1.90.0.js"),
+        PathBuf::from,
+    );
+    let driver = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/search-js/driver.js");
+
+    let output = Command::new(node)
+        .arg(driver)
+        .arg(fixture_path())
+        .arg(&search_js)
+        .arg(mode)
+        .args(queries)
+        .output()
+        .unwrap_or_else(|err| panic!("Failed to run {}: {}", node.to_string_lossy(), err));
+    assert!(
+        output.status.success(),
+        "search.js couldn't load the fixture:\n{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    serde_json::from_slice(&output.stdout).expect("driver.js printed invalid JSON")
+}
+
+/// Every item rdoc decodes from the fixture, in index order.
+fn rdoc_items() -> Vec<SearchItem> {
+    let content = std::fs::read_to_string(fixture_path()).unwrap();
+    let entries = parse_search_index(&extract_json_string(&content).unwrap()).unwrap();
+    entries
+        .iter()
+        .flat_map(|entry| decode_crate(&entry.name, &entry.data))
+        .collect()
+}
+
+/// How `item` differs from what search.js built, one line per field.
+fn differences(js: &JsItem, item: &SearchItem) -> Vec<String> {
+    let mut differences = Vec::new();
+    let mut compare = |field: &str, js_value: Option<&str>, rdoc_value: Option<&str>| {
+        if js_value != rdoc_value {
+            differences.push(format!(
+                "{}#{} {}: search.js has {:?}, rdoc has {:?}",
+                js.crate_name, js.index, field, js_value, rdoc_value
+            ));
+        }
+    };
+    compare("name", Some(&js.name), Some(&item.name));
+    compare("type", js.item_type.as_deref(), type_slug(item.item_type));
+    compare("path", Some(&js.path), Some(&item.path));
+    compare("exact path", Some(&js.exact_path), Some(&item.exact_path));
+    compare("parent", js.parent.as_deref(), item.parent_name());
+    differences
+}
+
+/// Fail listing the first few of `differences`, if there are any.
+fn assert_no_differences(differences: &[String]) {
+    assert!(
+        differences.is_empty(),
+        "{} differences from search.js, starting with:\n{}",
+        differences.len(),
+        differences
+            .iter()
+            .take(MAX_REPORTED)
+            .cloned()
+            .collect::<Vec<_>>()
+            .join("\n")
+    );
+}
+
+#[test]
+fn test_items_match_search_js() {
+    let Some(node) = node() else { return };
+    let js_items: Vec<JsItem> = run_driver(&node, "items", &[]);
+    let rdoc_items: HashMap<(String, usize), SearchItem> = rdoc_items()
+        .into_iter()
+        .map(|item| ((item.crate_name.clone(), item.id.index), item))
+        .collect();
+    assert_eq!(
+        js_items.len(),
+        rdoc_items.len(),
+        "search.js and rdoc decoded different numbers of items"
+    );
+
+    let mut all_differences = Vec::new();
+    for js in &js_items {
+        match rdoc_items.get(&(js.crate_name.clone(), js.index)) {
+            Some(item) => all_differences.extend(differences(js, item)),
+            None => all_differences.push(format!(
+                "{}#{} ({}) is missing from rdoc's items",
+                js.crate_name, js.index, js.name
+            )),
+        }
+    }
+    assert_no_differences(&all_differences);
+}
+
+/// search.js drops results that duplicate an earlier one's path and stops at
+/// 200, so its list is compared with rdoc's ranking of the same items: every
+/// result must be one of rdoc's matches, in the same order.
+#[test]
+fn test_results_ranked_as_search_js() {
+    let Some(node) = node() else { return };
+    let js_results: HashMap<String, Vec<JsResult>> = run_driver(&node, "search", QUERIES);
+    let rdoc_items = rdoc_items();
+
+    let mut all_differences = Vec::new();
+    for query in QUERIES {
+        let js_list = &js_results[*query];
+        assert!(
+            !js_list.is_empty(),
+            "search.js found nothing for {:?}",
+            query
+        );
+        let listed: HashSet<&JsResult> = js_list.iter().collect();
+
+        let matcher = TermMatcher::new(query, CaseMode::Smart).unwrap();
+        let mut matches: Vec<&SearchItem> = rdoc_items
+            .iter()
+            .filter(|item| matcher.matches_item(item))
+            .collect();
+        matches.sort_by_cached_key(|item| RustdocRank::new(query, item));
+        let rdoc_list: Vec<JsResult> = matches
+            .iter()
+            .map(|item| JsResult {
+                crate_name: item.crate_name.clone(),
+                index: item.id.index,
+            })
+            .filter(|result| listed.contains(result))
+            .collect();
+
+        if rdoc_list.len() != js_list.len() {
+            all_differences.push(format!(
+                "{:?}: search.js lists {} results, rdoc matches {} of them",
+                query,
+                js_list.len(),
+                rdoc_list.len()
+            ));
+        }
+        if let Some((position, (js, rdoc))) = js_list
+            .iter()
+            .zip(&rdoc_list)
+            .enumerate()
+            .find(|(_, (js, rdoc))| js != rdoc)
+        {
+            all_differences.push(format!(
+                "{:?}: result {} is {}#{} in search.js, {}#{} in rdoc",
+                query, position, js.crate_name, js.index, rdoc.crate_name, rdoc.index
+            ));
+        }
+    }
+    assert_no_differences(&all_differences);
+}